@@ -18,7 +18,7 @@ use std::sync::Arc;
 use bytes::Bytes;
 use ethereum_types::{H256, Address, Public};
 use ethabi::RawLog;
-use crypto::publickey::{Signature, Error as EthKeyError};
+use crypto::publickey::{Secret, Signature, Error as EthKeyError};
 
 /// Type for block number.
 /// Duplicated from ethcore types
@@ -58,6 +58,18 @@ pub trait SigningKeyPair: Send + Sync {
 	fn sign(&self, data: &H256) -> Result<Signature, EthKeyError>;
 }
 
+/// Key pair with ability to derive secrets from its own secret && to decrypt data, encrypted with its public.
+/// Unlike signatures, derived secrets && decrypted data never leave the node. So this is kept apart from
+/// `SigningKeyPair`, which signs data chosen by other nodes (e.g. during cluster handshake).
+pub trait SecretKeyPair: Send + Sync {
+	/// Public portion of key.
+	fn public(&self) -> &Public;
+	/// Derive secret for given purpose. Same purpose must always lead to the same secret.
+	fn derive_secret(&self, purpose: &[u8]) -> Result<Secret, EthKeyError>;
+	/// Decrypt data, encrypted (ECIES) with public portion of key.
+	fn decrypt(&self, shared_mac: &[u8], data: &[u8]) -> Result<Vec<u8>, EthKeyError>;
+}
+
 /// Wrapps client ChainNotify in order to send signal about new blocks
 pub trait NewBlocksNotify: Send + Sync {
 	/// Fires when chain has new blocks.
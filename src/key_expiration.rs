@@ -0,0 +1,210 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use futures::{future, Future, Stream};
+use parking_lot::RwLock;
+use parity_runtime::Executor;
+use tokio::timer::Interval;
+use blockchain::{SecretStoreChain, NewBlocksNotify, BlockId, BlockNumber};
+use key_storage::{KeyStorage, DocumentKeyShare};
+use types::ServerKeyId;
+
+/// Interval (in seconds) between expired keys sweeps. Keys that are expiring at given timestamp
+/// must be deleted even if no new blocks are enacted.
+const SWEEP_INTERVAL: u64 = 60;
+
+/// Source of current time && block number, used to check if server key has expired.
+pub trait ExpirationClock: Send + Sync {
+	/// Current unix timestamp (in seconds).
+	fn timestamp(&self) -> u64 {
+		SystemTime::now().duration_since(UNIX_EPOCH)
+			.map(|duration| duration.as_secs())
+			.unwrap_or(0)
+	}
+
+	/// Number of the best known block. None if it is unknown.
+	fn block_number(&self) -> Option<BlockNumber>;
+
+	/// Check if key share has expired.
+	fn is_expired(&self, key_share: &DocumentKeyShare) -> bool {
+		key_share.expiration
+			.map(|expiration| expiration.is_expired(self.timestamp(), self.block_number()))
+			.unwrap_or(false)
+	}
+}
+
+/// Clock that only knows system time. Keys, expiring at given block, never expire with this clock.
+#[derive(Default)]
+pub struct SystemClock;
+
+/// Expired keys sweeper. Remembers the best block && deletes shares of expired keys when new blocks are enacted
+/// and every SWEEP_INTERVAL seconds.
+pub struct KeyExpirationSweeper {
+	/// Blockchain client.
+	client: Arc<dyn SecretStoreChain>,
+	/// Key storage.
+	key_storage: Arc<dyn KeyStorage>,
+	/// Number of the best known block.
+	best_block: RwLock<Option<BlockNumber>>,
+}
+
+impl ExpirationClock for SystemClock {
+	fn block_number(&self) -> Option<BlockNumber> {
+		None
+	}
+}
+
+impl KeyExpirationSweeper {
+	/// Create new sweeper, subscribe to new blocks notifications && schedule periodic sweeps.
+	pub fn new(client: Arc<dyn SecretStoreChain>, key_storage: Arc<dyn KeyStorage>, executor: &Executor) -> Arc<Self> {
+		let best_block = client.block_number(BlockId::Latest);
+		let sweeper = Arc::new(KeyExpirationSweeper {
+			client: client.clone(),
+			key_storage,
+			best_block: RwLock::new(best_block),
+		});
+		client.add_listener(sweeper.clone());
+		schedule_sweep(executor, Arc::downgrade(&sweeper));
+		sweeper
+	}
+
+	/// Delete shares of all expired keys from the key storage.
+	pub fn sweep(&self) -> Vec<ServerKeyId> {
+		sweep_expired_keys(&*self.key_storage, self)
+	}
+}
+
+impl ExpirationClock for KeyExpirationSweeper {
+	fn block_number(&self) -> Option<BlockNumber> {
+		self.best_block.read().clone()
+	}
+}
+
+impl NewBlocksNotify for KeyExpirationSweeper {
+	fn new_blocks(&self, _new_enacted_len: usize) {
+		if let Some(best_block) = self.client.block_number(BlockId::Latest) {
+			*self.best_block.write() = Some(best_block);
+		}
+
+		self.sweep();
+	}
+}
+
+/// Sweep expired keys every SWEEP_INTERVAL seconds, until the sweeper is dropped.
+fn schedule_sweep(executor: &Executor, sweeper: Weak<KeyExpirationSweeper>) {
+	let sweep = Interval::new_interval(Duration::new(SWEEP_INTERVAL, 0))
+		.map_err(|error| warn!(target: "secretstore", "Expired keys sweep timer has failed: {}", error))
+		.for_each(move |_| match sweeper.upgrade() {
+			Some(sweeper) => {
+				sweeper.sweep();
+				Ok(())
+			},
+			None => Err(()),
+		})
+		.then(|_| future::ok(()));
+	if let Err(error) = future::Executor::execute(executor, Box::new(sweep)) {
+		error!(target: "secretstore", "Secret store runtime unable to spawn expired keys sweep. Runtime is shutting down. ({:?})", error);
+	}
+}
+
+/// Delete (tombstone) shares of all expired keys from the key storage. Returns ids of deleted keys.
+pub fn sweep_expired_keys(key_storage: &dyn KeyStorage, clock: &dyn ExpirationClock) -> Vec<ServerKeyId> {
+	let expired_keys: Vec<_> = key_storage.iter()
+		.filter(|&(_, ref key_share)| clock.is_expired(key_share))
+		.map(|(key_id, _)| key_id)
+		.collect();
+	expired_keys.into_iter()
+		.filter(|key_id| match key_storage.tombstone(key_id) {
+			Ok(()) => {
+				trace!(target: "secretstore", "Deleted share of expired key {:?}", key_id);
+				true
+			},
+			Err(error) => {
+				warn!(target: "secretstore", "Failed to delete share of expired key {:?}: {}", key_id, error);
+				false
+			},
+		})
+		.collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+	use std::collections::BTreeSet;
+	use blockchain::BlockNumber;
+	use key_storage::{KeyStorage, DocumentKeyShare};
+	use key_storage::tests::DummyKeyStorage;
+	use types::{ServerKeyId, KeyExpiration};
+	use super::{ExpirationClock, sweep_expired_keys};
+
+	/// Clock with fixed timestamp && block number.
+	#[derive(Default)]
+	pub struct FixedClock {
+		pub timestamp: u64,
+		pub block_number: Option<BlockNumber>,
+	}
+
+	impl ExpirationClock for FixedClock {
+		fn timestamp(&self) -> u64 {
+			self.timestamp
+		}
+
+		fn block_number(&self) -> Option<BlockNumber> {
+			self.block_number
+		}
+	}
+
+	fn key_share(expiration: Option<KeyExpiration>) -> DocumentKeyShare {
+		DocumentKeyShare {
+			expiration,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn key_expiration_is_checked() {
+		let clock = FixedClock { timestamp: 100, block_number: Some(10) };
+		assert!(!clock.is_expired(&key_share(None)));
+		assert!(!clock.is_expired(&key_share(Some(KeyExpiration::Timestamp(100)))));
+		assert!(clock.is_expired(&key_share(Some(KeyExpiration::Timestamp(99)))));
+		assert!(!clock.is_expired(&key_share(Some(KeyExpiration::Block(10)))));
+		assert!(clock.is_expired(&key_share(Some(KeyExpiration::Block(9)))));
+
+		let clock = FixedClock { timestamp: 100, block_number: None };
+		assert!(!clock.is_expired(&key_share(Some(KeyExpiration::Block(9)))));
+	}
+
+	#[test]
+	fn only_expired_keys_are_swept() {
+		let key_storage = DummyKeyStorage::default();
+		key_storage.insert(ServerKeyId::from_low_u64_be(1), key_share(None)).unwrap();
+		key_storage.insert(ServerKeyId::from_low_u64_be(2), key_share(Some(KeyExpiration::Timestamp(99)))).unwrap();
+		key_storage.insert(ServerKeyId::from_low_u64_be(3), key_share(Some(KeyExpiration::Timestamp(100)))).unwrap();
+		key_storage.insert(ServerKeyId::from_low_u64_be(4), key_share(Some(KeyExpiration::Block(9)))).unwrap();
+		key_storage.insert(ServerKeyId::from_low_u64_be(5), key_share(Some(KeyExpiration::Block(10)))).unwrap();
+
+		let clock = FixedClock { timestamp: 100, block_number: Some(10) };
+		let swept: BTreeSet<_> = sweep_expired_keys(&key_storage, &clock).into_iter().collect();
+		assert_eq!(swept, vec![ServerKeyId::from_low_u64_be(2), ServerKeyId::from_low_u64_be(4)].into_iter().collect());
+		assert!(key_storage.contains(&ServerKeyId::from_low_u64_be(1)));
+		assert!(key_storage.is_tombstoned(&ServerKeyId::from_low_u64_be(2)));
+		assert!(key_storage.contains(&ServerKeyId::from_low_u64_be(3)));
+		assert!(key_storage.is_tombstoned(&ServerKeyId::from_low_u64_be(4)));
+		assert!(key_storage.contains(&ServerKeyId::from_low_u64_be(5)));
+		assert!(sweep_expired_keys(&key_storage, &clock).is_empty());
+	}
+}
@@ -16,19 +16,25 @@
 
 use std::collections::BTreeSet;
 use std::sync::Arc;
-use futures::{future::{err, result}, Future};
+use futures::{future::{err, result, join_all}, Future};
 use parking_lot::Mutex;
 use crypto::DEFAULT_MAC;
-use crypto::publickey::public_to_address;
+use crypto::publickey::{public_to_address, recover};
+use ethereum_types::H256;
+use hash::keccak;
 use parity_runtime::Executor;
 use super::acl_storage::AclStorage;
 use super::key_storage::KeyStorage;
+use super::key_expiration::ExpirationClock;
+use super::key_storage_backup::{self, KEY_SHARES_EXPORT_ID};
 use super::key_server_set::KeyServerSet;
-use blockchain::SigningKeyPair;
-use key_server_cluster::{math, new_network_cluster, ClusterSession, WaitableSession};
-use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, KeyServer};
-use types::{Error, Public, RequestSignature, Requester, ServerKeyId, EncryptedDocumentKey, EncryptedDocumentKeyShadow,
-	ClusterConfiguration, MessageHash, EncryptedMessageSignature, NodeId};
+use blockchain::{SigningKeyPair, SecretKeyPair};
+use key_server_cluster::{math, math_eddsa, new_network_cluster, ClusterSession, WaitableSession, SchnorrSigningScheme};
+use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, ChildKeyServer, DocumentKeySlotsServer,
+	KeyAgreementServer, RandomnessBeacon, KeyServer};
+use types::{Error, Public, EddsaPublic, BlsPublic, RequestSignature, Requester, ServerKeyId, EncryptedDocumentKey, EncryptedDocumentKeyShadow,
+	EncryptedEciesSecret, EncryptedEcdhSharedPoint, ClusterConfiguration, MessageHash, EncryptedMessageSignature, NodeId, KeySharesFilter, KeySharesImportResult, KeyDerivationPath,
+	RandomnessBeaconOutput, KeyImportData, EncryptedServerKey, DocumentKeySlotId, KeyExpiration};
 use key_server_cluster::{ClusterClient, ClusterConfiguration as NetClusterConfiguration, NetConnectionsManagerConfig};
 
 /// Secret store key server implementation
@@ -39,15 +45,21 @@ pub struct KeyServerImpl {
 /// Secret store key server data.
 pub struct KeyServerCore {
 	cluster: Arc<dyn ClusterClient>,
+	self_key_pair: Arc<dyn SigningKeyPair>,
+	self_secret_key_pair: Arc<dyn SecretKeyPair>,
+	key_storage: Arc<dyn KeyStorage>,
+	admin_public: Option<Public>,
 }
 
 impl KeyServerImpl {
 	/// Create new key server instance
 	pub fn new(config: &ClusterConfiguration, key_server_set: Arc<dyn KeyServerSet>, self_key_pair: Arc<dyn SigningKeyPair>,
-		acl_storage: Arc<dyn AclStorage>, key_storage: Arc<dyn KeyStorage>, executor: Executor) -> Result<Self, Error>
+		self_secret_key_pair: Arc<dyn SecretKeyPair>, acl_storage: Arc<dyn AclStorage>, key_storage: Arc<dyn KeyStorage>,
+		expiration_clock: Arc<dyn ExpirationClock>, executor: Executor) -> Result<Self, Error>
 	{
 		Ok(KeyServerImpl {
-			data: Arc::new(Mutex::new(KeyServerCore::new(config, key_server_set, self_key_pair, acl_storage, key_storage, executor)?)),
+			data: Arc::new(Mutex::new(KeyServerCore::new(config, key_server_set, self_key_pair, self_secret_key_pair, acl_storage,
+				key_storage, expiration_clock, executor)?)),
 		})
 	}
 
@@ -55,6 +67,130 @@ impl KeyServerImpl {
 	pub fn cluster(&self) -> Arc<dyn ClusterClient> {
 		self.data.lock().cluster.clone()
 	}
+
+	/// Restore document key (default, or stored in given slot), using shares of the server key (if derivation path
+	/// is empty) or its child key.
+	fn restore_document_key_using_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		document_key_slot: Option<DocumentKeySlotId>,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+		// recover requestor' public key from signature
+		let public = result(requester.public(&key_id).map_err(Error::InsufficientRequesterData));
+
+		// decrypt document key
+		let data = self.data.clone();
+		let stored_document_key = public.and_then(move |public| {
+			let data = data.lock();
+			let session = data.cluster.new_decryption_session(key_id, None, requester.clone(), derivation_path, None,
+				document_key_slot, None, false, false);
+			result(session.map(|session| (public, session)))
+		})
+		.and_then(|(public, session)| session.into_wait_future().map(move |document_key| (public, document_key)));
+
+		// encrypt document key with requestor public key
+		let encrypted_document_key = stored_document_key
+			.and_then(|(public, document_key)|
+				crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, document_key.decrypted_secret.as_bytes())
+					.map_err(|err| Error::Internal(format!("Error encrypting document key: {}", err))));
+
+		Box::new(encrypted_document_key)
+	}
+
+	/// Sign messages with Schnorr signature, using the server key (if derivation path is empty) or its child key.
+	fn sign_messages_schnorr_using_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		messages: Vec<MessageHash>,
+	) -> Box<dyn Future<Item=Vec<EncryptedMessageSignature>, Error=Error> + Send> {
+		// recover requestor' public key from signature
+		let public = result(requester.public(&key_id).map_err(Error::InsufficientRequesterData));
+
+		// sign messages
+		let data = self.data.clone();
+		let signatures = public.and_then(move |public| {
+			let data = data.lock();
+			let session = data.cluster.new_schnorr_signing_session(key_id, requester.clone().into(), derivation_path, None,
+				SchnorrSigningScheme::Legacy, messages);
+			result(session.map(|session| (public, session)))
+		})
+		.and_then(|(public, session)| session.into_wait_future().map(move |signatures| (public, signatures)));
+
+		// compose two message signature components into single one && encrypt it with requestor public key
+		let encrypted_signatures = signatures.and_then(|(public, signatures)| signatures.into_iter()
+			.map(|signature| {
+				let mut combined_signature = [0; 64];
+				combined_signature[..32].clone_from_slice(signature.0.as_bytes());
+				combined_signature[32..].clone_from_slice(signature.1.as_bytes());
+				crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &combined_signature)
+					.map_err(|err| Error::Internal(format!("Error encrypting message signature: {}", err)))
+			})
+			.collect::<Result<Vec<_>, _>>());
+
+		Box::new(encrypted_signatures)
+	}
+
+	/// Sign message with ECDSA signature, using the server key (if derivation path is empty) or its child key.
+	fn sign_message_ecdsa_using_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		// recover requestor' public key from signature
+		let public = result(requester.public(&key_id).map_err(Error::InsufficientRequesterData));
+
+		// sign message
+		let data = self.data.clone();
+		let signature = public.and_then(move |public| {
+			let data = data.lock();
+			let session = data.cluster.new_ecdsa_signing_session(key_id, requester.clone().into(), derivation_path, None, message);
+			result(session.map(|session| (public, session)))
+		})
+		.and_then(|(public, session)| session.into_wait_future().map(move |signature| (public, signature)));
+
+		// encrypt combined signature with requestor public key
+		let encrypted_signature = signature
+			.and_then(|(public, signature)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &*signature)
+				.map_err(|err| Error::Internal(format!("Error encrypting message signature: {}", err))));
+
+		Box::new(encrypted_signature)
+	}
+
+	/// Compute ECDH shared point of the server key and given public. Public is decrypted by consensus group as if it
+	/// was a document key, encrypted with itself as a common point. Returns requester public && the shared point.
+	fn compute_ecdh_shared_point(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		counterparty_public: Public,
+	) -> Box<dyn Future<Item=(Public, Public), Error=Error> + Send> {
+		// recover requestor' public key from signature
+		let public = result(requester.public(&key_id).map_err(Error::InsufficientRequesterData));
+
+		// decrypt counterparty public
+		let data = self.data.clone();
+		let session_counterparty_public = counterparty_public.clone();
+		let decrypted_point = public.and_then(move |public| {
+			let data = data.lock();
+			let session = data.cluster.new_decryption_session(key_id, None, requester.clone(), Vec::new(),
+				Some(session_counterparty_public), None, None, false, false);
+			result(session.map(|session| (public, session)))
+		})
+		.and_then(|(public, session)| session.into_wait_future().map(move |decrypted| (public, decrypted)));
+
+		// restore shared point
+		let shared_point = decrypted_point
+			.and_then(move |(public, decrypted)| math::compute_ecdh_shared_point(&counterparty_public, &decrypted.decrypted_secret)
+				.map(|shared_point| (public, shared_point)));
+
+		Box::new(shared_point)
+	}
 }
 
 impl KeyServer for KeyServerImpl {}
@@ -69,6 +205,74 @@ impl AdminSessionsServer for KeyServerImpl {
 		return_session(self.data.lock().cluster
 			.new_servers_set_change_session(None, None, new_servers_set, old_set_signature, new_set_signature))
 	}
+
+	fn refresh_key_shares(
+		&self,
+		key_id: ServerKeyId,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_share_refresh_session(key_id, signature))
+	}
+
+	fn refresh_all_key_shares(
+		&self,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		match self.data.lock().cluster.new_share_refresh_sessions(signature) {
+			Ok(sessions) => Box::new(join_all(sessions.into_iter().map(|session| session.into_wait_future()))
+				.map(|_| ())),
+			Err(error) => Box::new(err(error)),
+		}
+	}
+
+	fn rotate_server_key(
+		&self,
+		key_id: ServerKeyId,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_key_rotation_session(key_id, signature))
+	}
+
+	fn change_server_key_threshold(
+		&self,
+		key_id: ServerKeyId,
+		new_threshold: usize,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_threshold_change_session(key_id, new_threshold, signature))
+	}
+
+	fn export_server_key(
+		&self,
+		key_id: ServerKeyId,
+		recipient: Public,
+		expiry: u64,
+		approvals: Vec<RequestSignature>,
+	) -> Box<dyn Future<Item=EncryptedServerKey, Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_key_export_session(key_id, recipient, expiry, approvals))
+	}
+
+	fn export_key_shares(
+		&self,
+		signature: RequestSignature,
+		filter: KeySharesFilter,
+	) -> Box<dyn Future<Item=Vec<u8>, Error=Error> + Send> {
+		let data = self.data.lock();
+		Box::new(result(check_admin_signature(&data.admin_public, &signature, &*KEY_SHARES_EXPORT_ID)
+			.and_then(|_| key_storage_backup::export_key_shares(&*data.key_storage, &*data.self_key_pair,
+				&*data.self_secret_key_pair, &filter))))
+	}
+
+	fn import_key_shares(
+		&self,
+		signature: RequestSignature,
+		archive: Vec<u8>,
+	) -> Box<dyn Future<Item=KeySharesImportResult, Error=Error> + Send> {
+		let data = self.data.lock();
+		Box::new(result(check_admin_signature(&data.admin_public, &signature, &keccak(&archive))
+			.and_then(|_| key_storage_backup::import_key_shares(&*data.key_storage, &*data.self_key_pair,
+				&*data.self_secret_key_pair, &archive))))
+	}
 }
 
 impl ServerKeyGenerator for KeyServerImpl {
@@ -83,7 +287,64 @@ impl ServerKeyGenerator for KeyServerImpl {
 
 		// generate server key
 		return_session(address.and_then(|address| self.data.lock().cluster
-			.new_generation_session(key_id, None, address, threshold)))
+			.new_generation_session(key_id, None, address, threshold, None)))
+	}
+
+	fn generate_expiring_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+		expiration: KeyExpiration,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+		// recover requestor' address key from signature
+		let address = author.address(&key_id).map_err(Error::InsufficientRequesterData);
+
+		// generate server key
+		return_session(address.and_then(|address| self.data.lock().cluster
+			.new_generation_session(key_id, None, address, threshold, Some(expiration))))
+	}
+
+	fn generate_eddsa_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+	) -> Box<dyn Future<Item=EddsaPublic, Error=Error> + Send> {
+		// recover requestor' address key from signature
+		let address = author.address(&key_id).map_err(Error::InsufficientRequesterData);
+
+		// generate server key
+		return_session(address.and_then(|address| self.data.lock().cluster
+			.new_eddsa_generation_session(key_id, address, threshold)))
+	}
+
+	fn generate_bls_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+	) -> Box<dyn Future<Item=BlsPublic, Error=Error> + Send> {
+		// recover requestor' address key from signature
+		let address = author.address(&key_id).map_err(Error::InsufficientRequesterData);
+
+		// generate server key
+		return_session(address.and_then(|address| self.data.lock().cluster
+			.new_bls_generation_session(key_id, address, threshold)))
+	}
+
+	fn import_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		key: KeyImportData,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+		// recover requestor' address key from signature
+		let address = author.address(&key_id).map_err(Error::InsufficientRequesterData);
+
+		// import server key
+		return_session(address.and_then(|address| self.data.lock().cluster
+			.new_key_import_session(key_id, address, key)))
 	}
 
 	fn restore_key_public(
@@ -113,6 +374,14 @@ impl ServerKeyGenerator for KeyServerImpl {
 				Err(Error::AccessDenied)
 			}))
 	}
+
+	fn delete_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_key_deletion_session(key_id, author))
+	}
 }
 
 impl DocumentKeyServer for KeyServerImpl {
@@ -125,7 +394,7 @@ impl DocumentKeyServer for KeyServerImpl {
 	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
 		// store encrypted key
 		return_session(self.data.lock().cluster.new_encryption_session(key_id,
-			author.clone(), common_point, encrypted_document_key))
+			author.clone(), None, common_point, encrypted_document_key))
 	}
 
 	fn generate_document_key(
@@ -141,7 +410,7 @@ impl DocumentKeyServer for KeyServerImpl {
 		let data = self.data.clone();
 		let server_key = public.and_then(move |public| {
 			let data = data.lock();
-			let session = data.cluster.new_generation_session(key_id, None, public_to_address(&public), threshold);
+			let session = data.cluster.new_generation_session(key_id, None, public_to_address(&public), threshold, None);
 			result(session.map(|session| (public, session)))
 		})
 		.and_then(|(public, session)| session.into_wait_future().map(move |server_key| (public, server_key)));
@@ -158,7 +427,7 @@ impl DocumentKeyServer for KeyServerImpl {
 		let stored_document_key = document_key.and_then(move |(public, document_key, encrypted_document_key)| {
 			let data = data.lock();
 			let session = data.cluster.new_encryption_session(key_id,
-				author.clone(), encrypted_document_key.common_point, encrypted_document_key.encrypted_point);
+				author.clone(), None, encrypted_document_key.common_point, encrypted_document_key.encrypted_point);
 			result(session.map(|session| (public, document_key, session)))
 		})
 		.and_then(|(public, document_key, session)| session.into_wait_future().map(move |_| (public, document_key)));
@@ -176,39 +445,91 @@ impl DocumentKeyServer for KeyServerImpl {
 		key_id: ServerKeyId,
 		requester: Requester,
 	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+		self.restore_document_key_using_key(key_id, Vec::new(), None, requester)
+	}
+
+	fn restore_document_key_shadow(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_decryption_session(key_id,
+			None, requester.clone(), Vec::new(), None, None, None, true, false))
+	}
+
+	fn restore_ecies_secret(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		ephemeral_public: Public,
+	) -> Box<dyn Future<Item=EncryptedEciesSecret, Error=Error> + Send> {
+		// ECDH secret is the X coordinate of the shared point => encrypt it with requestor public key
+		let encrypted_secret = self.compute_ecdh_shared_point(key_id, requester, ephemeral_public)
+			.and_then(|(public, shared_point)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &shared_point[..32])
+				.map_err(|err| Error::Internal(format!("Error encrypting ECIES secret: {}", err))));
+
+		Box::new(encrypted_secret)
+	}
+}
+
+impl MessageSigner for KeyServerImpl {
+	fn sign_message_schnorr(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		Box::new(self.sign_messages_schnorr(key_id, requester, vec![message])
+			.and_then(|signatures| signatures.into_iter().next()
+				.ok_or_else(|| Error::Internal("Signing session has returned no signatures".into()))))
+	}
+
+	fn sign_messages_schnorr(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		messages: Vec<MessageHash>,
+	) -> Box<dyn Future<Item=Vec<EncryptedMessageSignature>, Error=Error> + Send> {
+		self.sign_messages_schnorr_using_key(key_id, Vec::new(), requester, messages)
+	}
+
+	fn sign_message_schnorr_bip340(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
 		// recover requestor' public key from signature
 		let public = result(requester.public(&key_id).map_err(Error::InsufficientRequesterData));
 
-		// decrypt document key
+		// sign message
 		let data = self.data.clone();
-		let stored_document_key = public.and_then(move |public| {
+		let signature = public.and_then(move |public| {
 			let data = data.lock();
-			let session = data.cluster.new_decryption_session(key_id, None, requester.clone(), None, false, false);
+			let session = data.cluster.new_schnorr_signing_session(key_id, requester.clone().into(), Vec::new(), None,
+				SchnorrSigningScheme::Bip340, vec![message]);
 			result(session.map(|session| (public, session)))
 		})
-		.and_then(|(public, session)| session.into_wait_future().map(move |document_key| (public, document_key)));
+		.and_then(|(public, session)| session.into_wait_future().map(move |signatures| (public, signatures[0].clone())));
 
-		// encrypt document key with requestor public key
-		let encrypted_document_key = stored_document_key
-			.and_then(|(public, document_key)|
-				crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, document_key.decrypted_secret.as_bytes())
-					.map_err(|err| Error::Internal(format!("Error encrypting document key: {}", err))));
+		// encrypt serialized signature with requestor public key
+		let encrypted_signature = signature
+			.and_then(|(public, signature)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &math::serialize_bip340_signature(&signature))
+				.map_err(|err| Error::Internal(format!("Error encrypting message signature: {}", err))));
 
-		Box::new(encrypted_document_key)
+		Box::new(encrypted_signature)
 	}
 
-	fn restore_document_key_shadow(
+	fn sign_message_ecdsa(
 		&self,
 		key_id: ServerKeyId,
 		requester: Requester,
-	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
-		return_session(self.data.lock().cluster.new_decryption_session(key_id,
-			None, requester.clone(), None, true, false))
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		self.sign_message_ecdsa_using_key(key_id, Vec::new(), requester, message)
 	}
-}
 
-impl MessageSigner for KeyServerImpl {
-	fn sign_message_schnorr(
+	fn sign_message_eddsa(
 		&self,
 		key_id: ServerKeyId,
 		requester: Requester,
@@ -221,28 +542,20 @@ impl MessageSigner for KeyServerImpl {
 		let data = self.data.clone();
 		let signature = public.and_then(move |public| {
 			let data = data.lock();
-			let session = data.cluster.new_schnorr_signing_session(key_id, requester.clone().into(), None, message);
+			let session = data.cluster.new_eddsa_signing_session(key_id, requester.clone().into(), None, message);
 			result(session.map(|session| (public, session)))
 		})
 		.and_then(|(public, session)| session.into_wait_future().map(move |signature| (public, signature)));
 
-		// compose two message signature components into single one
-		let combined_signature = signature.map(|(public, signature)| {
-			let mut combined_signature = [0; 64];
-			combined_signature[..32].clone_from_slice(signature.0.as_bytes());
-			combined_signature[32..].clone_from_slice(signature.1.as_bytes());
-			(public, combined_signature)
-		});
-
-		// encrypt signature with requestor public key
-		let encrypted_signature = combined_signature
-			.and_then(|(public, combined_signature)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &combined_signature)
+		// encrypt serialized signature with requestor public key
+		let encrypted_signature = signature
+			.and_then(|(public, signature)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &math_eddsa::serialize_signature(&signature))
 				.map_err(|err| Error::Internal(format!("Error encrypting message signature: {}", err))));
 
 		Box::new(encrypted_signature)
 	}
 
-	fn sign_message_ecdsa(
+	fn sign_message_bls(
 		&self,
 		key_id: ServerKeyId,
 		requester: Requester,
@@ -255,31 +568,145 @@ impl MessageSigner for KeyServerImpl {
 		let data = self.data.clone();
 		let signature = public.and_then(move |public| {
 			let data = data.lock();
-			let session = data.cluster.new_ecdsa_signing_session(key_id, requester.clone().into(), None, message);
+			let session = data.cluster.new_bls_signing_session(key_id, requester.clone().into(), None, message);
 			result(session.map(|session| (public, session)))
 		})
 		.and_then(|(public, session)| session.into_wait_future().map(move |signature| (public, signature)));
 
-		// encrypt combined signature with requestor public key
+		// encrypt compressed signature with requestor public key
 		let encrypted_signature = signature
-			.and_then(|(public, signature)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &*signature)
+			.and_then(|(public, signature)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, &signature)
 				.map_err(|err| Error::Internal(format!("Error encrypting message signature: {}", err))));
 
 		Box::new(encrypted_signature)
 	}
 }
 
+impl ChildKeyServer for KeyServerImpl {
+	fn restore_child_key_public(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		derivation_path: KeyDerivationPath,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+		Box::new(self.restore_key_public(key_id, author)
+			.and_then(move |public| math::compute_child_key_tweak(&public, &derivation_path)
+				.map(|(_, child_public)| child_public)))
+	}
+
+	fn sign_message_schnorr_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		Box::new(self.sign_messages_schnorr_using_key(key_id, derivation_path, requester, vec![message])
+			.and_then(|signatures| signatures.into_iter().next()
+				.ok_or_else(|| Error::Internal("Signing session has returned no signatures".into()))))
+	}
+
+	fn sign_message_ecdsa_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		self.sign_message_ecdsa_using_key(key_id, derivation_path, requester, message)
+	}
+
+	fn restore_document_key_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+		self.restore_document_key_using_key(key_id, derivation_path, None, requester)
+	}
+}
+
+impl DocumentKeySlotsServer for KeyServerImpl {
+	fn store_document_key_in_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		author: Requester,
+		common_point: Public,
+		encrypted_document_key: Public,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_encryption_session(key_id,
+			author, Some(slot), common_point, encrypted_document_key))
+	}
+
+	fn restore_document_key_from_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+		self.restore_document_key_using_key(key_id, Vec::new(), Some(slot), requester)
+	}
+
+	fn restore_document_key_shadow_from_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
+		return_session(self.data.lock().cluster.new_decryption_session(key_id,
+			None, requester, Vec::new(), None, Some(slot), None, true, false))
+	}
+}
+
+impl RandomnessBeacon for KeyServerImpl {
+	fn generate_randomness(
+		&self,
+		beacon_id: H256,
+		requester: Requester,
+	) -> Box<dyn Future<Item=RandomnessBeaconOutput, Error=Error> + Send> {
+		// recover requestor' address from signature
+		let address = requester.address(&beacon_id).map_err(Error::InsufficientRequesterData);
+
+		// generate randomness
+		return_session(address.and_then(|address| self.data.lock().cluster
+			.new_randomness_beacon_session(beacon_id, address)))
+	}
+}
+
+impl KeyAgreementServer for KeyServerImpl {
+	fn agree_shared_point(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		counterparty_public: Public,
+	) -> Box<dyn Future<Item=EncryptedEcdhSharedPoint, Error=Error> + Send> {
+		// encrypt shared point with requestor public key
+		let encrypted_shared_point = self.compute_ecdh_shared_point(key_id, requester, counterparty_public)
+			.and_then(|(public, shared_point)| crypto::publickey::ecies::encrypt(&public, &DEFAULT_MAC, shared_point.as_bytes())
+				.map_err(|err| Error::Internal(format!("Error encrypting ECDH shared point: {}", err))));
+
+		Box::new(encrypted_shared_point)
+	}
+}
+
 impl KeyServerCore {
 	pub fn new(config: &ClusterConfiguration, key_server_set: Arc<dyn KeyServerSet>, self_key_pair: Arc<dyn SigningKeyPair>,
-		acl_storage: Arc<dyn AclStorage>, key_storage: Arc<dyn KeyStorage>, executor: Executor) -> Result<Self, Error>
+		self_secret_key_pair: Arc<dyn SecretKeyPair>, acl_storage: Arc<dyn AclStorage>, key_storage: Arc<dyn KeyStorage>,
+		expiration_clock: Arc<dyn ExpirationClock>, executor: Executor) -> Result<Self, Error>
 	{
 		let cconfig = NetClusterConfiguration {
 			self_key_pair: self_key_pair.clone(),
+			self_secret_key_pair: self_secret_key_pair.clone(),
 			key_server_set: key_server_set,
 			acl_storage: acl_storage,
-			key_storage: key_storage,
+			key_storage: key_storage.clone(),
 			admin_public: config.admin_public,
+			key_export_approvers: config.key_export_approvers.clone(),
 			preserve_sessions: false,
+			reliable_broadcast: config.reliable_broadcast,
+			ecdsa_mta_enabled: config.ecdsa_mta_enabled,
+			expiration_clock,
 		};
 		let net_config = NetConnectionsManagerConfig {
 			listen_address: (config.listener_address.address.clone(), config.listener_address.port),
@@ -293,10 +720,23 @@ impl KeyServerCore {
 
 		Ok(KeyServerCore {
 			cluster,
+			self_key_pair,
+			self_secret_key_pair,
+			key_storage,
+			admin_public: config.admin_public,
 		})
 	}
 }
 
+/// Check that the message has been signed by administrator.
+fn check_admin_signature(admin_public: &Option<Public>, signature: &RequestSignature, message: &MessageHash) -> Result<(), Error> {
+	let admin_public = admin_public.as_ref().ok_or(Error::AccessDenied)?;
+	match recover(signature, message) {
+		Ok(ref signer) if signer == admin_public => Ok(()),
+		_ => Err(Error::AccessDenied),
+	}
+}
+
 fn return_session<S: ClusterSession>(
 	session: Result<WaitableSession<S>, Error>,
 ) -> Box<dyn Future<Item=S::SuccessfulResult, Error=Error> + Send> {
@@ -314,25 +754,44 @@ pub mod tests {
 	use std::net::SocketAddr;
 	use std::collections::BTreeMap;
 	use futures::Future;
+	use sha2::{Digest, Sha256};
 	use crypto::DEFAULT_MAC;
-	use crypto::publickey::{Secret, Random, Generator, verify_public};
+	use crypto::publickey::{Secret, Random, Generator, verify_public, recover};
 	use acl_storage::DummyAclStorage;
 	use key_storage::KeyStorage;
 	use key_storage::tests::DummyKeyStorage;
+	use key_expiration::SystemClock;
 	use node_key_pair::PlainNodeKeyPair;
 	use key_server_set::tests::MapKeyServerSet;
-	use key_server_cluster::math;
+	use key_server_cluster::{math, math_bls, math_eddsa};
 	use ethereum_types::{H256, H520};
 	use parity_runtime::Runtime;
-	use types::{Error, Public, ClusterConfiguration, NodeAddress, RequestSignature, ServerKeyId,
+	use types::{Error, Public, EddsaPublic, BlsPublic, ClusterConfiguration, NodeAddress, RequestSignature, ServerKeyId,
 		EncryptedDocumentKey, EncryptedDocumentKeyShadow, MessageHash, EncryptedMessageSignature,
-		Requester, NodeId};
-	use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, KeyServer};
+		Requester, NodeId, KeySharesFilter, KeySharesImportResult, KeyDerivationPath, RandomnessBeaconOutput, KeyImportData,
+		EncryptedServerKey, DocumentKeySlotId, KeyExpiration, EncryptedEciesSecret, EncryptedEcdhSharedPoint};
+	use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, ChildKeyServer,
+		DocumentKeySlotsServer, KeyAgreementServer, RandomnessBeacon, KeyServer};
 	use super::KeyServerImpl;
 
 	#[derive(Default)]
 	pub struct DummyKeyServer;
 
+	/// Decrypt ECIES ciphertext (0x04 || ephemeral public || iv || cipher || mac), using ECDH secret of
+	/// its ephemeral public. The key is derived the same way as in `crypto::publickey::ecies`.
+	fn decrypt_with_ecies_secret(ecies_secret: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+		let mut hasher = Sha256::new();
+		hasher.input(&[0u8, 0, 0, 1]);
+		hasher.input(ecies_secret);
+		let key = hasher.result();
+
+		let iv = &ciphertext[65..81];
+		let cipher = &ciphertext[81..ciphertext.len() - 32];
+		let mut plain = vec![0u8; cipher.len()];
+		crypto::aes::decrypt_128_ctr(&key[..16], iv, cipher, &mut plain).unwrap();
+		plain
+	}
+
 	impl KeyServer for DummyKeyServer {}
 
 	impl AdminSessionsServer for DummyKeyServer {
@@ -344,6 +803,64 @@ pub mod tests {
 		) -> Box<dyn Future<Item=(), Error=Error> + Send> {
 			unimplemented!("test-only")
 		}
+
+		fn refresh_key_shares(
+			&self,
+			_key_id: ServerKeyId,
+			_signature: RequestSignature,
+		) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn refresh_all_key_shares(
+			&self,
+			_signature: RequestSignature,
+		) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn rotate_server_key(
+			&self,
+			_key_id: ServerKeyId,
+			_signature: RequestSignature,
+		) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn change_server_key_threshold(
+			&self,
+			_key_id: ServerKeyId,
+			_new_threshold: usize,
+			_signature: RequestSignature,
+		) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn export_server_key(
+			&self,
+			_key_id: ServerKeyId,
+			_recipient: Public,
+			_expiry: u64,
+			_approvals: Vec<RequestSignature>,
+		) -> Box<dyn Future<Item=EncryptedServerKey, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn export_key_shares(
+			&self,
+			_signature: RequestSignature,
+			_filter: KeySharesFilter,
+		) -> Box<dyn Future<Item=Vec<u8>, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn import_key_shares(
+			&self,
+			_signature: RequestSignature,
+			_archive: Vec<u8>,
+		) -> Box<dyn Future<Item=KeySharesImportResult, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
 	}
 
 	impl ServerKeyGenerator for DummyKeyServer {
@@ -356,6 +873,43 @@ pub mod tests {
 			unimplemented!("test-only")
 		}
 
+		fn generate_expiring_key(
+			&self,
+			_key_id: ServerKeyId,
+			_author: Requester,
+			_threshold: usize,
+			_expiration: KeyExpiration,
+		) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn generate_eddsa_key(
+			&self,
+			_key_id: ServerKeyId,
+			_author: Requester,
+			_threshold: usize,
+		) -> Box<dyn Future<Item=EddsaPublic, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn generate_bls_key(
+			&self,
+			_key_id: ServerKeyId,
+			_author: Requester,
+			_threshold: usize,
+		) -> Box<dyn Future<Item=BlsPublic, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn import_key(
+			&self,
+			_key_id: ServerKeyId,
+			_author: Requester,
+			_key: KeyImportData,
+		) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
 		fn restore_key_public(
 			&self,
 			_key_id: ServerKeyId,
@@ -363,6 +917,14 @@ pub mod tests {
 		) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
 			unimplemented!("test-only")
 		}
+
+		fn delete_key(
+			&self,
+			_key_id: ServerKeyId,
+			_author: Requester,
+		) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
 	}
 
 	impl DocumentKeyServer for DummyKeyServer {
@@ -385,44 +947,185 @@ pub mod tests {
 			unimplemented!("test-only")
 		}
 
-		fn restore_document_key(
+		fn restore_document_key(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+		) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn restore_document_key_shadow(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+		) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn restore_ecies_secret(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+			_ephemeral_public: Public,
+		) -> Box<dyn Future<Item=EncryptedEciesSecret, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+	}
+
+	impl MessageSigner for DummyKeyServer {
+		fn sign_message_schnorr(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+			_message: MessageHash,
+		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn sign_messages_schnorr(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+			_messages: Vec<MessageHash>,
+		) -> Box<dyn Future<Item=Vec<EncryptedMessageSignature>, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn sign_message_schnorr_bip340(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+			_message: MessageHash,
+		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn sign_message_ecdsa(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+			_message: MessageHash,
+		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn sign_message_eddsa(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+			_message: MessageHash,
+		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn sign_message_bls(
+			&self,
+			_key_id: ServerKeyId,
+			_requester: Requester,
+			_message: MessageHash,
+		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+	}
+
+	impl ChildKeyServer for DummyKeyServer {
+		fn restore_child_key_public(
+			&self,
+			_key_id: ServerKeyId,
+			_author: Requester,
+			_derivation_path: KeyDerivationPath,
+		) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn sign_message_schnorr_with_child_key(
+			&self,
+			_key_id: ServerKeyId,
+			_derivation_path: KeyDerivationPath,
+			_requester: Requester,
+			_message: MessageHash,
+		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn sign_message_ecdsa_with_child_key(
+			&self,
+			_key_id: ServerKeyId,
+			_derivation_path: KeyDerivationPath,
+			_requester: Requester,
+			_message: MessageHash,
+		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn restore_document_key_with_child_key(
+			&self,
+			_key_id: ServerKeyId,
+			_derivation_path: KeyDerivationPath,
+			_requester: Requester,
+		) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+	}
+
+	impl DocumentKeySlotsServer for DummyKeyServer {
+		fn store_document_key_in_slot(
+			&self,
+			_key_id: ServerKeyId,
+			_slot: DocumentKeySlotId,
+			_author: Requester,
+			_common_point: Public,
+			_encrypted_document_key: Public,
+		) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
+
+		fn restore_document_key_from_slot(
 			&self,
 			_key_id: ServerKeyId,
+			_slot: DocumentKeySlotId,
 			_requester: Requester,
 		) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
 			unimplemented!("test-only")
 		}
 
-		fn restore_document_key_shadow(
+		fn restore_document_key_shadow_from_slot(
 			&self,
 			_key_id: ServerKeyId,
+			_slot: DocumentKeySlotId,
 			_requester: Requester,
 		) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
 			unimplemented!("test-only")
 		}
 	}
 
-	impl MessageSigner for DummyKeyServer {
-		fn sign_message_schnorr(
+	impl KeyAgreementServer for DummyKeyServer {
+		fn agree_shared_point(
 			&self,
 			_key_id: ServerKeyId,
 			_requester: Requester,
-			_message: MessageHash,
-		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+			_counterparty_public: Public,
+		) -> Box<dyn Future<Item=EncryptedEcdhSharedPoint, Error=Error> + Send> {
 			unimplemented!("test-only")
 		}
+	}
 
-		fn sign_message_ecdsa(
+	impl RandomnessBeacon for DummyKeyServer {
+		fn generate_randomness(
 			&self,
-			_key_id: ServerKeyId,
+			_beacon_id: H256,
 			_requester: Requester,
-			_message: MessageHash,
-		) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		) -> Box<dyn Future<Item=RandomnessBeaconOutput, Error=Error> + Send> {
 			unimplemented!("test-only")
 		}
 	}
 
 	fn make_key_servers(start_port: u16, num_nodes: usize) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>, Runtime) {
+		make_key_servers_with_ecdsa_mta(start_port, num_nodes, false)
+	}
+
+	fn make_key_servers_with_ecdsa_mta(start_port: u16, num_nodes: usize, ecdsa_mta_enabled: bool) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>, Runtime) {
 		let key_pairs: Vec<_> = (0..num_nodes).map(|_| Random.generate()).collect();
 		let configs: Vec<_> = (0..num_nodes).map(|i| ClusterConfiguration {
 				listener_address: NodeAddress {
@@ -437,19 +1140,23 @@ pub mod tests {
 				key_server_set_contract_address: None,
 				allow_connecting_to_higher_nodes: false,
 				admin_public: None,
+				key_export_approvers: None,
 				auto_migrate_enabled: false,
+				reliable_broadcast: false,
+				ecdsa_mta_enabled,
 			}).collect();
 		let key_servers_set: BTreeMap<Public, SocketAddr> = configs[0].nodes.iter()
 			.map(|(k, a)| (k.clone(), format!("{}:{}", a.address, a.port).parse().unwrap()))
 			.collect();
 		let key_storages = (0..num_nodes).map(|_| Arc::new(DummyKeyStorage::default())).collect::<Vec<_>>();
 		let runtime = Runtime::with_thread_count(4);
-		let key_servers: Vec<_> = configs.into_iter().enumerate().map(|(i, cfg)|
+		let key_servers: Vec<_> = configs.into_iter().enumerate().map(|(i, cfg)| {
+			let node_key_pair = Arc::new(PlainNodeKeyPair::new(key_pairs[i].clone()));
 			KeyServerImpl::new(&cfg, Arc::new(MapKeyServerSet::new(false, key_servers_set.clone())),
-				Arc::new(PlainNodeKeyPair::new(key_pairs[i].clone())),
+				node_key_pair.clone(), node_key_pair,
 				Arc::new(DummyAclStorage::default()),
-				key_storages[i].clone(), runtime.executor()).unwrap()
-		).collect();
+				key_storages[i].clone(), Arc::new(SystemClock), runtime.executor()).unwrap()
+		}).collect();
 
 		// wait until connections are established. It is fast => do not bother with events here
 		let start = time::Instant::now();
@@ -615,6 +1322,144 @@ pub mod tests {
 		drop(runtime);
 	}
 
+	#[test]
+	fn server_key_generation_and_batch_message_signing_works_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6127, 3);
+
+		let test_cases = [0, 1, 2];
+		for threshold in &test_cases {
+			// generate server key
+			let server_key_id = Random.generate().secret().clone();
+			let requestor_secret = Random.generate().secret().clone();
+			let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+			let server_public = key_servers[0].generate_key(
+				*server_key_id,
+				signature.clone(),
+				*threshold,
+			).wait().unwrap();
+
+			// sign messages
+			let message_hashes: Vec<_> = (0..5).map(|_| H256::random()).collect();
+			let combined_signatures = key_servers[0].sign_messages_schnorr(
+				*server_key_id,
+				signature,
+				message_hashes.clone(),
+			).wait().unwrap();
+			assert_eq!(combined_signatures.len(), message_hashes.len());
+
+			// check signatures
+			for (combined_signature, message_hash) in combined_signatures.into_iter().zip(message_hashes.iter()) {
+				let combined_signature = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &combined_signature).unwrap();
+				let signature_c = Secret::copy_from_slice(&combined_signature[..32]).unwrap();
+				let signature_s = Secret::copy_from_slice(&combined_signature[32..]).unwrap();
+				assert_eq!(math::verify_schnorr_signature(&server_public, &(signature_c, signature_s), message_hash), Ok(true));
+			}
+		}
+		drop(runtime);
+	}
+
+	#[test]
+	fn server_key_generation_and_bip340_message_signing_works_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6121, 3);
+
+		let test_cases = [0, 1, 2];
+		for threshold in &test_cases {
+			// generate server key
+			let server_key_id = Random.generate().secret().clone();
+			let requestor_secret = Random.generate().secret().clone();
+			let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+			let server_public = key_servers[0].generate_key(
+				*server_key_id,
+				signature.clone(),
+				*threshold,
+			).wait().unwrap();
+
+			// sign message
+			let message_hash = H256::from_low_u64_be(42);
+			let combined_signature = key_servers[0].sign_message_schnorr_bip340(
+				*server_key_id,
+				signature,
+				message_hash,
+			).wait().unwrap();
+			let combined_signature = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &combined_signature).unwrap();
+			let signature_r = Secret::copy_from_slice(&combined_signature[..32]).unwrap();
+			let signature_s = Secret::copy_from_slice(&combined_signature[32..]).unwrap();
+
+			// check signature
+			let server_public_x = math::bip340_x_only_public(&server_public);
+			assert_eq!(math::verify_bip340_signature(&server_public_x, &(signature_r, signature_s), &message_hash), Ok(true));
+		}
+		drop(runtime);
+	}
+
+	#[test]
+	fn eddsa_key_generation_and_message_signing_works_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6105, 3);
+
+		let test_cases = [0, 1, 2];
+		for threshold in &test_cases {
+			// generate server key
+			let server_key_id = Random.generate().secret().clone();
+			let requestor_secret = Random.generate().secret().clone();
+			let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+			let server_public = key_servers[0].generate_eddsa_key(
+				*server_key_id,
+				signature.clone(),
+				*threshold,
+			).wait().unwrap();
+
+			// sign message
+			let message_hash = H256::from_low_u64_be(42);
+			let combined_signature = key_servers[0].sign_message_eddsa(
+				*server_key_id,
+				signature,
+				message_hash,
+			).wait().unwrap();
+			let combined_signature = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &combined_signature).unwrap();
+			let signature_r = H256::from_slice(&combined_signature[..32]);
+			let signature_s = Secret::copy_from_slice(&combined_signature[32..]).unwrap();
+
+			// check signature
+			assert_eq!(math_eddsa::verify_signature(&server_public, &(signature_r, signature_s), &message_hash), Ok(true));
+		}
+		drop(runtime);
+	}
+
+	#[test]
+	fn bls_key_generation_and_message_signing_works_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6145, 3);
+
+		let test_cases = [0, 1, 2];
+		for threshold in &test_cases {
+			// generate server key
+			let server_key_id = Random.generate().secret().clone();
+			let requestor_secret = Random.generate().secret().clone();
+			let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+			let server_public = key_servers[0].generate_bls_key(
+				*server_key_id,
+				signature.clone(),
+				*threshold,
+			).wait().unwrap();
+
+			// sign message
+			let message_hash = H256::from_low_u64_be(42);
+			let combined_signature = key_servers[0].sign_message_bls(
+				*server_key_id,
+				signature,
+				message_hash,
+			).wait().unwrap();
+			let combined_signature = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &combined_signature).unwrap();
+
+			// check signature
+			assert_eq!(math_bls::verify_signature(&server_public, &combined_signature, &message_hash), Ok(true));
+		}
+		drop(runtime);
+	}
+
 	#[test]
 	fn decryption_session_is_delegated_when_node_does_not_have_key_share() {
 		let _ = ::env_logger::try_init();
@@ -707,6 +1552,243 @@ pub mod tests {
 		drop(runtime);
 	}
 
+	#[test]
+	fn ecdsa_signing_works_when_threshold_is_not_less_than_half_of_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers_with_ecdsa_mta(6124, 3, true);
+		let threshold = 2;
+
+		// generate server key
+		let server_key_id = Random.generate().secret().clone();
+		let requestor_secret = Random.generate().secret().clone();
+		let signature = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap();
+		let server_public = key_servers[0].generate_key(
+			*server_key_id,
+			signature.clone().into(),
+			threshold,
+		).wait().unwrap();
+
+		// sign message
+		let message_hash = H256::random();
+		let signature = key_servers[0].sign_message_ecdsa(
+			*server_key_id,
+			signature.clone().into(),
+			message_hash,
+		).wait().unwrap();
+		let signature = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &signature).unwrap();
+		let signature = H520::from_slice(&signature[0..65]);
+
+		// check signature
+		assert!(verify_public(&server_public, &signature.into(), &message_hash).unwrap());
+		drop(runtime);
+	}
+
+	#[test]
+	fn child_key_signing_and_decryption_works_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6130, 3);
+		let threshold = 1;
+
+		// generate server key && document key
+		let server_key_id = Random.generate().secret().clone();
+		let requestor_secret = Random.generate().secret().clone();
+		let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+		let generated_key = key_servers[0].generate_document_key(
+			*server_key_id,
+			signature.clone(),
+			threshold,
+		).wait().unwrap();
+		let generated_key = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &generated_key).unwrap();
+
+		// compute child public
+		let derivation_path = vec![0, 42];
+		let server_public = key_servers[0].restore_key_public(*server_key_id, signature.clone()).wait().unwrap();
+		let child_public = key_servers[1].restore_child_key_public(
+			*server_key_id,
+			signature.clone(),
+			derivation_path.clone(),
+		).wait().unwrap();
+		assert!(child_public != server_public);
+		assert_eq!(math::compute_child_key_tweak(&server_public, &derivation_path).unwrap().1, child_public);
+
+		// sign message with Schnorr signature using the child key
+		let message_hash = H256::random();
+		let combined_signature = key_servers[0].sign_message_schnorr_with_child_key(
+			*server_key_id,
+			derivation_path.clone(),
+			signature.clone(),
+			message_hash,
+		).wait().unwrap();
+		let combined_signature = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &combined_signature).unwrap();
+		let signature_c = Secret::copy_from_slice(&combined_signature[..32]).unwrap();
+		let signature_s = Secret::copy_from_slice(&combined_signature[32..]).unwrap();
+		assert_eq!(math::verify_schnorr_signature(&child_public, &(signature_c, signature_s), &message_hash), Ok(true));
+
+		// sign message with ECDSA signature using the child key
+		let ecdsa_signature = key_servers[1].sign_message_ecdsa_with_child_key(
+			*server_key_id,
+			derivation_path.clone(),
+			signature.clone(),
+			message_hash,
+		).wait().unwrap();
+		let ecdsa_signature = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &ecdsa_signature).unwrap();
+		let ecdsa_signature = H520::from_slice(&ecdsa_signature[0..65]);
+		assert!(verify_public(&child_public, &ecdsa_signature.into(), &message_hash).unwrap());
+
+		// document key is decrypted using shares of the child key
+		let retrieved_key = key_servers[2].restore_document_key_with_child_key(
+			*server_key_id,
+			derivation_path.clone(),
+			signature.clone(),
+		).wait().unwrap();
+		let retrieved_key = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &retrieved_key).unwrap();
+		assert_eq!(retrieved_key, generated_key);
+
+		// hardened child keys are not supported
+		assert_eq!(key_servers[0].sign_message_schnorr_with_child_key(
+			*server_key_id,
+			vec![math::HARDENED_KEY_INDEX],
+			signature,
+			message_hash,
+		).wait(), Err(Error::InvalidDerivationPath));
+		drop(runtime);
+	}
+
+	#[test]
+	fn ecies_secret_is_restored_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6133, 3);
+		let threshold = 1;
+
+		// generate server key (no document key is required)
+		let server_key_id = Random.generate().secret().clone();
+		let requestor_secret = Random.generate().secret().clone();
+		let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+		let server_public = key_servers[0].generate_key(*server_key_id, signature.clone(), threshold).wait().unwrap();
+
+		// client encrypts payload with ECIES, using server public
+		let ciphertext = crypto::publickey::ecies::encrypt(&server_public, &DEFAULT_MAC, b"arbitrary payload").unwrap();
+		let ephemeral_public = Public::from_slice(&ciphertext[1..65]);
+
+		// every key server is able to restore ECDH secret, which is then used to decrypt the payload
+		for key_server in key_servers.iter() {
+			let ecies_secret = key_server.restore_ecies_secret(
+				*server_key_id,
+				signature.clone(),
+				ephemeral_public.clone(),
+			).wait().unwrap();
+			let ecies_secret = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &ecies_secret).unwrap();
+			assert_eq!(decrypt_with_ecies_secret(&ecies_secret, &ciphertext), b"arbitrary payload".to_vec());
+		}
+		drop(runtime);
+	}
+
+	#[test]
+	fn ecdh_shared_point_is_computed_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6142, 3);
+		let threshold = 1;
+
+		// generate server key
+		let server_key_id = Random.generate().secret().clone();
+		let requestor_secret = Random.generate().secret().clone();
+		let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+		let server_public = key_servers[0].generate_key(*server_key_id, signature.clone(), threshold).wait().unwrap();
+
+		// shared point is the same that counterparty computes using its secret && server public
+		let counterparty_key_pair = Random.generate();
+		let mut expected_shared_point = server_public.clone();
+		crypto::publickey::ec_math_utils::public_mul_secret(&mut expected_shared_point, counterparty_key_pair.secret()).unwrap();
+
+		// every key server is able to compute shared point
+		for key_server in key_servers.iter() {
+			let shared_point = key_server.agree_shared_point(
+				*server_key_id,
+				signature.clone(),
+				counterparty_key_pair.public().clone(),
+			).wait().unwrap();
+			let shared_point = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &shared_point).unwrap();
+			assert_eq!(&shared_point[..], expected_shared_point.as_bytes());
+		}
+		drop(runtime);
+	}
+
+	#[test]
+	fn randomness_is_generated_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6136, 3);
+
+		// every key server is able to start the beacon && every key server signs the transcript
+		for (i, key_server) in key_servers.iter().enumerate() {
+			let beacon_id = H256::from_low_u64_be(i as u64);
+			let requestor_secret = Random.generate().secret().clone();
+			let signature = crypto::publickey::sign(&requestor_secret, &beacon_id).unwrap();
+			let output = key_server.generate_randomness(beacon_id, signature.into()).wait().unwrap();
+			assert_eq!(output.beacon_id, beacon_id);
+			assert_eq!(output.signatures.len(), 3);
+			for (node, signature) in &output.signatures {
+				assert_eq!(recover(signature, &output.transcript_hash).unwrap(), *node);
+			}
+		}
+		drop(runtime);
+	}
+
+	#[test]
+	fn document_key_slots_are_stored_and_retrieved_over_network_with_3_nodes() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6139, 3);
+		let threshold = 1;
+
+		// generate server key && default document key
+		let server_key_id = Random.generate().secret().clone();
+		let requestor_secret = Random.generate().secret().clone();
+		let signature: Requester = crypto::publickey::sign(&requestor_secret, &server_key_id).unwrap().into();
+		let default_key = key_servers[0].generate_document_key(
+			*server_key_id,
+			signature.clone(),
+			threshold,
+		).wait().unwrap();
+		let default_key = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &default_key).unwrap();
+		let server_public = key_servers[0].restore_key_public(*server_key_id, signature.clone()).wait().unwrap();
+
+		// store two additional document keys in slots
+		let slots: Vec<(DocumentKeySlotId, Public, Public)> = (1..3).map(|i| {
+			let slot = H256::from_low_u64_be(i);
+			let document_key = Random.generate().public().clone();
+			let encrypted_document_key = math::encrypt_secret(&document_key, &server_public).unwrap();
+			key_servers[i as usize].store_document_key_in_slot(*server_key_id, slot, signature.clone(),
+				encrypted_document_key.common_point.clone(), encrypted_document_key.encrypted_point).wait().unwrap();
+			(slot, document_key, encrypted_document_key.common_point)
+		}).collect();
+
+		// slot can't be overwritten
+		let (slot, _, _) = slots[0].clone();
+		let encrypted_document_key = math::encrypt_secret(Random.generate().public(), &server_public).unwrap();
+		assert_eq!(key_servers[0].store_document_key_in_slot(*server_key_id, slot, signature.clone(),
+			encrypted_document_key.common_point, encrypted_document_key.encrypted_point).wait(),
+			Err(Error::DocumentKeyAlreadyStored));
+
+		// every document key is retrieved from its own slot
+		for (slot, document_key, common_point) in slots {
+			let retrieved_key = key_servers[0].restore_document_key_from_slot(*server_key_id, slot, signature.clone()).wait().unwrap();
+			let retrieved_key = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &retrieved_key).unwrap();
+			assert_eq!(Public::from_slice(&retrieved_key), document_key);
+
+			let shadow = key_servers[2].restore_document_key_shadow_from_slot(*server_key_id, slot, signature.clone()).wait().unwrap();
+			assert_eq!(shadow.common_point, Some(math::make_common_shadow_point(threshold, common_point).unwrap()));
+		}
+
+		// default document key is untouched
+		let retrieved_key = key_servers[1].restore_document_key(*server_key_id, signature.clone()).wait().unwrap();
+		let retrieved_key = crypto::publickey::ecies::decrypt(&requestor_secret, &DEFAULT_MAC, &retrieved_key).unwrap();
+		assert_eq!(retrieved_key, default_key);
+
+		// empty slot can't be decrypted
+		assert_eq!(key_servers[0].restore_document_key_from_slot(*server_key_id, H256::from_low_u64_be(3), signature.clone()).wait(),
+			Err(Error::DocumentKeyIsNotFound));
+		drop(runtime);
+	}
+
 	#[test]
 	fn servers_set_change_session_works_over_network() {
 		// TODO [Test]
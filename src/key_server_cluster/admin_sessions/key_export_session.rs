@@ -0,0 +1,636 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::sync::Arc;
+use crypto::DEFAULT_MAC;
+use crypto::publickey::{Public, Secret, Signature, ecies, recover};
+use ethereum_types::H256;
+use futures::Oneshot;
+use parking_lot::Mutex;
+use tiny_keccak::Keccak;
+use key_server_cluster::{Error, NodeId, SessionId, KeyStorage, DocumentKeyShare, KeyCurve, KeyExportApprovers,
+	EncryptedServerKey};
+use key_server_cluster::math;
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
+use key_server_cluster::message::{Message, KeyExportMessage, InitializeKeyExportSession, KeyExportShare,
+	KeyExportSessionError};
+use key_expiration::ExpirationClock;
+
+/// Key export session.
+/// Exports the full server key secret to designated recipient. The secret is never reconstructed by key servers:
+/// every selected version holder sends its secret shadow (key share, multiplied by Lagrange coefficient),
+/// encrypted with the recipient public, and the recipient computes the secret as the sum of decrypted shadows.
+/// Brief overview:
+/// 1) initialization: master node (which has received the export request) checks that the request is approved by
+///    the configured number of key export approvers, selects threshold + 1 shadow holders
+///    && initializes the session on all version holders
+/// 2) every version holder checks approvals on its own, logs the export && marks its key share as exported
+/// 3) every shadow holder computes its secret shadow, encrypts it with the recipient public
+///    and sends it to the master node
+/// 4) master node returns all encrypted shadows
+/// Approvals are only valid until their expiry, so that they couldn't be replayed later.
+/// Key shares are marked as exported before they're released, so even failed sessions leave the key flagged.
+pub struct SessionImpl {
+	/// Unique session id.
+	id: SessionId,
+	/// Public identifier of this node.
+	self_node_id: NodeId,
+	/// Public identifier of master node.
+	master_node_id: NodeId,
+	/// Key share of this node (if any).
+	key_share: Option<DocumentKeyShare>,
+	/// Key storage.
+	key_storage: Arc<dyn KeyStorage>,
+	/// Key export approvers (if key export is enabled).
+	approvers: Option<KeyExportApprovers>,
+	/// Clock, used to check approvals expiry.
+	clock: Arc<dyn ExpirationClock>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	nonce: u64,
+	/// Session completion signal.
+	completed: CompletionSignal<EncryptedServerKey>,
+	/// Mutable session data.
+	data: Mutex<SessionData>,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// SessionImpl identifier.
+	pub id: SessionId,
+	/// Id of node, on which this session is running.
+	pub self_node_id: NodeId,
+	/// Id of node, which has started this session.
+	pub master_node_id: NodeId,
+	/// Key share of this node (if any).
+	pub key_share: Option<DocumentKeyShare>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Key export approvers (if key export is enabled).
+	pub approvers: Option<KeyExportApprovers>,
+	/// Clock, used to check approvals expiry.
+	pub clock: Arc<dyn ExpirationClock>,
+	/// Cluster
+	pub cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+/// Mutable data of key export session.
+#[derive(Debug)]
+struct SessionData {
+	/// Current state of the session.
+	state: SessionState,
+	/// Encrypted secret shadows, received from shadow holders.
+	shadows: BTreeMap<NodeId, Option<Vec<u8>>>,
+	/// Key export session result.
+	result: Option<Result<EncryptedServerKey, Error>>,
+}
+
+/// Key export session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for encrypted secret shadows from every shadow holder.
+	WaitingForShares,
+	/// Key is exported.
+	Finished,
+	/// Failed to export key.
+	Failed,
+}
+
+impl SessionImpl {
+	/// Create new key export session.
+	pub fn new(params: SessionParams) -> (Self, Oneshot<Result<EncryptedServerKey, Error>>) {
+		let (completed, oneshot) = CompletionSignal::new();
+		(SessionImpl {
+			id: params.id,
+			self_node_id: params.self_node_id,
+			master_node_id: params.master_node_id,
+			key_share: params.key_share,
+			key_storage: params.key_storage,
+			approvers: params.approvers,
+			clock: params.clock,
+			cluster: params.cluster,
+			nonce: params.nonce,
+			completed,
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				shadows: BTreeMap::new(),
+				result: None,
+			}),
+		}, oneshot)
+	}
+
+	/// Get this node Id.
+	pub fn node(&self) -> &NodeId {
+		&self.self_node_id
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Start new session initialization. This must be called on master node.
+	pub fn initialize(&self, recipient: Public, expiry: u64, approvals: Vec<Signature>) -> Result<(), Error> {
+		debug_assert!(self.self_node_id == self.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// every version holder must be connected to this node
+		let key_version = self.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?.last_version()?.clone();
+		let connected_nodes = self.cluster.nodes();
+		if key_version.id_numbers.keys().any(|node| !connected_nodes.contains(node)) {
+			return Err(Error::NodeDisconnected);
+		}
+
+		// check approvals && mark own key share as exported
+		self.approve_export(&recipient, expiry, &approvals)?;
+
+		// any t + 1 shadows are enough to reconstruct the key
+		let threshold = self.key_share.as_ref().expect("checked above; qed").threshold;
+		let shadow_holders: BTreeSet<NodeId> = ::std::iter::once(self.node().clone())
+			.chain(key_version.id_numbers.keys().filter(|n| *n != self.node()).take(threshold).cloned())
+			.collect();
+		let self_shadow = compute_encrypted_secret_shadow(&key_version.secret_share,
+			&key_version.id_numbers, &shadow_holders, self.node(), &recipient)?;
+
+		// update state
+		data.state = SessionState::WaitingForShares;
+		data.shadows.extend(shadow_holders.iter().map(|node| (node.clone(), None)));
+		data.shadows.insert(self.node().clone(), Some(self_shadow));
+
+		// ask other version holders to approve export && shadow holders to send their shadows
+		for node in key_version.id_numbers.keys().filter(|n| *n != self.node()) {
+			self.cluster.send(node, Message::KeyExport(KeyExportMessage::InitializeKeyExportSession(InitializeKeyExportSession {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				version: key_version.hash.clone().into(),
+				recipient: recipient.clone().into(),
+				expiry: expiry,
+				approvals: approvals.iter().cloned().map(Into::into).collect(),
+				shadow_holders: shadow_holders.iter().cloned().map(Into::into).collect(),
+			})))?;
+		}
+
+		// if this node is the only shadow holder => export the key right now
+		self.try_complete(&mut data)
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: NodeId, message: &InitializeKeyExportSession) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		if sender != self.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that shadow holders are selected correctly
+		let key_share = self.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let key_version = key_share.version(&message.version.clone().into())?;
+		let shadow_holders: BTreeSet<NodeId> = message.shadow_holders.iter().cloned().map(Into::into).collect();
+		if shadow_holders.len() != key_share.threshold + 1 || !shadow_holders.contains(&sender)
+			|| shadow_holders.iter().any(|n| !key_version.id_numbers.contains_key(n)) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// check approvals && mark own key share as exported
+		let recipient: Public = message.recipient.clone().into();
+		let approvals: Vec<Signature> = message.approvals.iter().cloned().map(Into::into).collect();
+		self.approve_export(&recipient, message.expiry, &approvals)?;
+
+		// update state
+		data.state = SessionState::Finished;
+
+		// send encrypted secret shadow to master node
+		if !shadow_holders.contains(self.node()) {
+			return Ok(());
+		}
+
+		let encrypted_secret_shadow = compute_encrypted_secret_shadow(&key_version.secret_share,
+			&key_version.id_numbers, &shadow_holders, self.node(), &recipient)?;
+		self.cluster.send(&sender, Message::KeyExport(KeyExportMessage::KeyExportShare(KeyExportShare {
+			session: self.id.clone().into(),
+			session_nonce: self.nonce,
+			encrypted_secret_shadow: encrypted_secret_shadow.into(),
+		})))
+	}
+
+	/// When encrypted secret shadow is received.
+	pub fn on_key_share(&self, sender: NodeId, message: &KeyExportShare) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForShares {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		{
+			let shadow = data.shadows.get_mut(&sender).ok_or(Error::InvalidMessage)?;
+			if shadow.is_some() {
+				return Err(Error::InvalidStateForRequest);
+			}
+			*shadow = Some(message.encrypted_secret_shadow.clone().into());
+		}
+
+		self.try_complete(&mut data)
+	}
+
+	/// Check that the export is approved and mark key share of this node as exported.
+	fn approve_export(&self, recipient: &Public, expiry: u64, approvals: &[Signature]) -> Result<(), Error> {
+		let mut key_share = self.key_share.clone().ok_or(Error::ServerKeyIsNotFound)?;
+		if key_share.curve != KeyCurve::Secp256k1 {
+			return Err(Error::InvalidKeyCurve);
+		}
+
+		// expired approvals are rejected, so that they couldn't be replayed
+		if expiry < self.clock.timestamp() {
+			return Err(Error::AccessDenied);
+		}
+
+		let signers = check_approvals(self.approvers.as_ref(), &key_export_hash(&self.id, recipient, expiry), approvals)?;
+		warn!(target: "secretstore", "{}: exporting server key {} to {}, approved by {:?}",
+			self.node(), self.id, recipient, signers);
+
+		key_share.exported = true;
+		self.key_storage.update(self.id.clone(), key_share)
+	}
+
+	/// Complete the session, if encrypted shadows from all shadow holders are received.
+	fn try_complete(&self, data: &mut SessionData) -> Result<(), Error> {
+		if data.shadows.values().any(|s| s.is_none()) {
+			return Ok(());
+		}
+
+		let key_share = self.key_share.as_ref()
+			.expect("key share is checked on initialization; completion follows initialization; qed");
+		let server_key = EncryptedServerKey {
+			public: key_share.public.clone(),
+			secret_shadows: data.shadows.values()
+				.map(|s| s.clone().expect("checked above; qed"))
+				.collect(),
+		};
+
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(server_key.clone()));
+		self.completed.send(Ok(server_key));
+
+		Ok(())
+	}
+}
+
+/// Compute secret shadow of the node && encrypt it with recipient public.
+fn compute_encrypted_secret_shadow(
+	secret_share: &Secret,
+	id_numbers: &BTreeMap<NodeId, Secret>,
+	shadow_holders: &BTreeSet<NodeId>,
+	self_node_id: &NodeId,
+	recipient: &Public,
+) -> Result<Vec<u8>, Error> {
+	let self_id_number = id_numbers.get(self_node_id).ok_or(Error::InvalidMessage)?;
+	let other_id_numbers = shadow_holders.iter()
+		.filter(|n| *n != self_node_id)
+		.map(|n| id_numbers.get(n).ok_or(Error::InvalidMessage))
+		.collect::<Result<Vec<_>, _>>()?;
+	let other_id_numbers_count = other_id_numbers.len();
+	let mut secret_shadow = math::compute_node_shadow(secret_share, self_id_number, other_id_numbers.into_iter())?;
+	// shadow is computed with (-1)^t multiplier => fix its sign, so that the key secret is the sum of shadows
+	if other_id_numbers_count % 2 != 0 {
+		secret_shadow.neg()?;
+	}
+	Ok(ecies::encrypt(recipient, &DEFAULT_MAC, secret_shadow.as_bytes())?)
+}
+
+/// Reconstruct exported server key secret using recipient secret && check it against server key public.
+pub fn decrypt_server_key(recipient: &Secret, server_key: &EncryptedServerKey) -> Result<Secret, Error> {
+	let secret_shadows = server_key.secret_shadows.iter()
+		.map(|s| ecies::decrypt(recipient, &DEFAULT_MAC, s)
+			.map_err(Error::from)
+			.and_then(|s| Secret::copy_from_slice(&s).ok_or(Error::InvalidMessage)))
+		.collect::<Result<Vec<_>, _>>()?;
+	let secret = math::compute_secret_sum(secret_shadows.iter())?;
+	if math::compute_public_share(&secret)? != server_key.public {
+		return Err(Error::InvalidMessage);
+	}
+
+	Ok(secret)
+}
+
+/// Compute hash of the key export request, which must be signed by approvers: keccak256(key_id || recipient || expiry).
+pub fn key_export_hash(key_id: &SessionId, recipient: &Public, expiry: u64) -> H256 {
+	let mut request_keccak = Keccak::new_keccak256();
+	request_keccak.update(key_id.as_bytes());
+	request_keccak.update(recipient.as_bytes());
+	request_keccak.update(&expiry.to_be_bytes());
+
+	let mut request_keccak_value = [0u8; 32];
+	request_keccak.finalize(&mut request_keccak_value);
+
+	request_keccak_value.into()
+}
+
+/// Check that the request is signed by required number of distinct approvers, returning these approvers.
+fn check_approvals(approvers: Option<&KeyExportApprovers>, hash: &H256, approvals: &[Signature]) -> Result<BTreeSet<Public>, Error> {
+	let approvers = approvers.ok_or(Error::AccessDenied)?;
+	let mut signers = BTreeSet::new();
+	for approval in approvals {
+		match recover(approval, hash) {
+			Ok(ref signer) if approvers.approvers.contains(signer) => signers.insert(signer.clone()),
+			_ => return Err(Error::AccessDenied),
+		};
+	}
+
+	if approvers.threshold == 0 || signers.len() < approvers.threshold {
+		return Err(Error::AccessDenied);
+	}
+
+	Ok(signers)
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = ();
+	type SuccessfulResult = EncryptedServerKey;
+
+	fn type_name() -> &'static str {
+		"key export"
+	}
+
+	fn id(&self) -> SessionId {
+		self.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		let mut data = self.data.lock();
+
+		warn!("{}: key export session failed because {} connection has timeouted", self.node(), node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(Error::NodeDisconnected));
+		self.completed.send(Err(Error::NodeDisconnected));
+	}
+
+	fn on_session_timeout(&self) {
+		let mut data = self.data.lock();
+
+		warn!("{}: key export session failed with timeout", self.node());
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(Error::NodeDisconnected));
+		self.completed.send(Err(Error::NodeDisconnected));
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in key export session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.cluster.broadcast(Message::KeyExport(KeyExportMessage::KeyExportSessionError(KeyExportSessionError {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		let mut data = self.data.lock();
+
+		warn!("{}: key export session failed with error: {} from {}", self.node(), error, node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error.clone()));
+		self.completed.send(Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		if Some(self.nonce) != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&Message::KeyExport(ref message) => match message {
+				&KeyExportMessage::InitializeKeyExportSession(ref message) =>
+					self.on_initialize_session(sender.clone(), message),
+				&KeyExportMessage::KeyExportShare(ref message) =>
+					self.on_key_share(sender.clone(), message),
+				&KeyExportMessage::KeyExportSessionError(ref message) => {
+					self.on_session_error(sender, message.error.clone());
+					Ok(())
+				},
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "Key export session {} on {}", self.id, self.self_node_id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::Future;
+	use crypto::DEFAULT_MAC;
+	use crypto::publickey::{Random, Generator, KeyPair, Public, Secret, Signature, ecies, public_to_address, sign};
+	use key_server_cluster::{Error, KeyStorage, SessionId, KeyExportApprovers, EncryptedServerKey};
+	use key_server_cluster::cluster::ClusterClient;
+	use key_server_cluster::cluster::tests::{MessageLoop, make_clusters, make_clusters_with_key_export_approvers};
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use key_server_cluster::generation_session::SessionState as GenerationSessionState;
+	use super::{key_export_hash, decrypt_server_key};
+
+	/// Approvals expiry, used in tests (2100-01-01).
+	const EXPIRY: u64 = 4102444800;
+
+	fn make_approvers(num_approvers: usize, threshold: usize) -> (Vec<KeyPair>, KeyExportApprovers) {
+		let approvers: Vec<_> = (0..num_approvers).map(|_| Random.generate()).collect();
+		let config = KeyExportApprovers {
+			approvers: approvers.iter().map(|a| a.public().clone()).collect(),
+			threshold,
+		};
+		(approvers, config)
+	}
+
+	fn make_key(ml: &MessageLoop) -> (SessionId, Public) {
+		let key_id = SessionId::from([1u8; 32]);
+		let author = Random.generate();
+		let session = ml.cluster(0).client()
+			.new_generation_session(key_id, None, public_to_address(author.public()), 1, None).unwrap().session;
+		ml.loop_until(|| session.state() == GenerationSessionState::Finished
+			&& (0..ml.nodes().len()).all(|i| ml.sessions(i).generation_sessions.is_empty()));
+		(key_id, session.joint_public_and_secret().unwrap().unwrap().0)
+	}
+
+	fn approve(approvers: &[KeyPair], key_id: &SessionId, recipient: &Public) -> Vec<Signature> {
+		approve_until(approvers, key_id, recipient, EXPIRY)
+	}
+
+	fn approve_until(approvers: &[KeyPair], key_id: &SessionId, recipient: &Public, expiry: u64) -> Vec<Signature> {
+		approvers.iter().map(|a| sign(a.secret(), &key_export_hash(key_id, recipient, expiry)).unwrap()).collect()
+	}
+
+	fn export_key(ml: &MessageLoop, key_id: SessionId, recipient: &Public, approvals: Vec<Signature>) -> Result<EncryptedServerKey, Error> {
+		export_key_until(ml, key_id, recipient, EXPIRY, approvals)
+	}
+
+	fn export_key_until(
+		ml: &MessageLoop,
+		key_id: SessionId,
+		recipient: &Public,
+		expiry: u64,
+		approvals: Vec<Signature>,
+	) -> Result<EncryptedServerKey, Error> {
+		let session = ml.cluster(0).client().new_key_export_session(key_id, recipient.clone(), expiry, approvals)?;
+		let session_handle = session.session.clone();
+		ml.loop_until(|| session_handle.is_finished()
+			&& (0..ml.nodes().len()).all(|i| ml.sessions(i).key_export_sessions.is_empty()));
+		session.into_wait_future().wait()
+	}
+
+	fn is_exported(ml: &MessageLoop, key_id: &SessionId) -> Vec<bool> {
+		(0..ml.nodes().len()).map(|i| ml.key_storage(i).get(key_id).unwrap().unwrap().exported).collect()
+	}
+
+	#[test]
+	fn key_is_exported_to_recipient() {
+		let (approvers, config) = make_approvers(3, 2);
+		let ml = make_clusters_with_key_export_approvers(3, config);
+		let (key_id, public) = make_key(&ml);
+		let recipient = Random.generate();
+
+		let server_key = export_key(&ml, key_id, recipient.public(), approve(&approvers[1..], &key_id, recipient.public())).unwrap();
+		assert_eq!(server_key.public, public);
+		assert_eq!(server_key.secret_shadows.len(), 2);
+		assert_eq!(is_exported(&ml, &key_id), vec![true; 3]);
+
+		// only recipient is able to reconstruct the secret
+		let secret = decrypt_server_key(recipient.secret(), &server_key).unwrap();
+		assert_eq!(KeyPair::from_secret(secret).unwrap().public(), &public);
+		assert!(decrypt_server_key(Random.generate().secret(), &server_key).is_err());
+
+		// every single shadow is not the key secret
+		for secret_shadow in &server_key.secret_shadows {
+			let secret_shadow = ecies::decrypt(recipient.secret(), &DEFAULT_MAC, secret_shadow).unwrap();
+			let secret_shadow = Secret::copy_from_slice(&secret_shadow).unwrap();
+			assert!(KeyPair::from_secret(secret_shadow).unwrap().public() != &public);
+		}
+	}
+
+	#[test]
+	fn key_is_not_exported_with_expired_approvals() {
+		let (approvers, config) = make_approvers(3, 2);
+		let ml = make_clusters_with_key_export_approvers(3, config);
+		let (key_id, _) = make_key(&ml);
+		let recipient = Random.generate();
+
+		let approvals = approve_until(&approvers, &key_id, recipient.public(), 1);
+		assert_eq!(export_key_until(&ml, key_id, recipient.public(), 1, approvals), Err(Error::AccessDenied));
+		assert_eq!(is_exported(&ml, &key_id), vec![false; 3]);
+
+		// approvals couldn't be reused with other expiry
+		let approvals = approve_until(&approvers, &key_id, recipient.public(), 1);
+		assert_eq!(export_key(&ml, key_id, recipient.public(), approvals), Err(Error::AccessDenied));
+		assert_eq!(is_exported(&ml, &key_id), vec![false; 3]);
+	}
+
+	#[test]
+	fn key_is_not_exported_without_enough_approvals() {
+		let (approvers, config) = make_approvers(3, 2);
+		let ml = make_clusters_with_key_export_approvers(3, config);
+		let (key_id, _) = make_key(&ml);
+		let recipient = Random.generate();
+
+		// the same approver signature is only counted once
+		let mut approvals = approve(&approvers[..1], &key_id, recipient.public());
+		approvals.push(approvals[0].clone());
+		assert_eq!(export_key(&ml, key_id, recipient.public(), approvals), Err(Error::AccessDenied));
+		assert_eq!(is_exported(&ml, &key_id), vec![false; 3]);
+	}
+
+	#[test]
+	fn key_is_not_exported_when_signed_by_unknown_approver() {
+		let (approvers, config) = make_approvers(3, 2);
+		let ml = make_clusters_with_key_export_approvers(3, config);
+		let (key_id, _) = make_key(&ml);
+		let recipient = Random.generate();
+
+		let mut approvals = approve(&approvers[..1], &key_id, recipient.public());
+		approvals.extend(approve(&[Random.generate()], &key_id, recipient.public()));
+		assert_eq!(export_key(&ml, key_id, recipient.public(), approvals), Err(Error::AccessDenied));
+	}
+
+	#[test]
+	fn key_is_not_exported_to_other_recipient() {
+		let (approvers, config) = make_approvers(3, 2);
+		let ml = make_clusters_with_key_export_approvers(3, config);
+		let (key_id, _) = make_key(&ml);
+		let recipient = Random.generate();
+
+		let approvals = approve(&approvers, &key_id, recipient.public());
+		assert_eq!(export_key(&ml, key_id, Random.generate().public(), approvals), Err(Error::AccessDenied));
+	}
+
+	#[test]
+	fn key_is_not_exported_when_export_is_disabled() {
+		let (approvers, _) = make_approvers(3, 2);
+		let ml = make_clusters(3);
+		let (key_id, _) = make_key(&ml);
+		let recipient = Random.generate();
+
+		let approvals = approve(&approvers, &key_id, recipient.public());
+		assert_eq!(export_key(&ml, key_id, recipient.public(), approvals), Err(Error::AccessDenied));
+	}
+
+	#[test]
+	fn fails_to_export_unknown_key() {
+		let (approvers, config) = make_approvers(3, 2);
+		let ml = make_clusters_with_key_export_approvers(3, config);
+		let key_id = SessionId::from([1u8; 32]);
+		let recipient = Random.generate();
+
+		let approvals = approve(&approvers, &key_id, recipient.public());
+		assert_eq!(export_key(&ml, key_id, recipient.public(), approvals), Err(Error::ServerKeyIsNotFound));
+	}
+}
@@ -0,0 +1,924 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::sync::Arc;
+use ethereum_types::H256;
+use crypto::publickey::{Public, Secret, Signature, recover};
+use futures::Oneshot;
+use parking_lot::Mutex;
+use key_server_cluster::{Error, NodeId, SessionId, DocumentKeyShare, DocumentKeyShareVersion, DocumentKeySlotId, KeyStorage};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
+use key_server_cluster::math;
+use key_server_cluster::message::{Message, KeyRotationMessage, InitializeKeyRotationSession,
+	ConfirmKeyRotationInitialization, KeyRotationKeysDissemination, ConfirmKeyRotation, CommitKeyRotation,
+	KeyRotationError, ServersSetChangeMessage};
+use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+use key_server_cluster::admin_sessions::resharing::{Resharing, AbsoluteTerm, compute_public_shares_footprint};
+
+/// Key rotation session.
+/// Replaces server key with the new one, re-encrypting stored document key without ever reconstructing it.
+/// Brief overview:
+/// 1) initialization: master node (which has received request for key rotation) initializes the session on all version holders
+/// 2) every version holder generates random polynom && sends its value to every other version holder, along with the
+///    publics of polynom coefficients
+/// 3) every version holder verifies received values against publics && adds them to its secret share, computing share of
+///    the rotated key. Sum of received values is the node share of the difference between rotated and obsolete keys
+///    (`y' - y`), so the node multiplies common point by this share (and lagrange coefficient) && sends the result to
+///    master node, along with the proof that it has used the share, committed by received publics
+/// 4) master node verifies proofs && adds re-encryption points to the encrypted point: (M + k * y) + (y' - y) * (k * G) = M + k * y'
+/// 5) master node sends re-encrypted point to all version holders, which are saving rotated key share && retire all
+///    obsolete key versions
+/// Document keys, stored in slots, are re-encrypted the same way, using their own common points.
+/// Since shares of the difference are only combined in the exponent, neither the document key, nor the server key secret
+/// is ever reconstructed.
+pub struct SessionImpl {
+	/// Session core.
+	core: SessionCore,
+	/// Session data.
+	data: Mutex<SessionData>,
+}
+
+/// Immutable session data.
+struct SessionCore {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// Original key share.
+	pub key_share: Option<DocumentKeyShare>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	pub cluster: Arc<dyn Cluster>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Public,
+	/// Session completion signal.
+	pub completed: CompletionSignal<()>,
+}
+
+/// Mutable session data.
+struct SessionData {
+	/// Session state.
+	pub state: SessionState,
+	/// Key version to rotate.
+	pub version: Option<H256>,
+	/// Hash of the rotated key version.
+	pub new_version: Option<H256>,
+	/// Version holders data.
+	pub nodes: BTreeMap<NodeId, NodeData>,
+	/// Re-sharing of the rotated version.
+	pub resharing: Option<Resharing>,
+	/// Rotated key share (without re-encrypted document key).
+	pub rotated_key_share: Option<DocumentKeyShare>,
+	/// Key rotation result.
+	pub result: Option<Result<(), Error>>,
+}
+
+/// Version holder data.
+struct NodeData {
+	/// Flag marking that node has confirmed session initialization.
+	pub initialization_confirmed: bool,
+	/// Rotated joint public, computed by the node (on master node only).
+	pub new_public: Option<Public>,
+	/// Footprint of rotated public shares, computed by the node (on master node only).
+	pub public_shares_footprint: Option<H256>,
+	/// Node share of the re-encryption point along with its proof (on master node only).
+	pub reencryption_point: Option<(Public, (Secret, Secret))>,
+	/// Node shares of re-encryption points of document keys, stored in slots, along with their proofs (on master node only).
+	pub slots_reencryption_points: BTreeMap<DocumentKeySlotId, (Public, (Secret, Secret))>,
+}
+
+/// Session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for every version holder to confirm initialization.
+	WaitingForInitializationConfirm,
+	/// Waiting for secret subshares from every version holder.
+	WaitingForKeysDissemination,
+	/// Master node waits for re-encryption points from every version holder.
+	WaitingForRotationConfirm,
+	/// Slave node waits for the master node to commit rotated key.
+	WaitingForCommit,
+	/// Session is completed.
+	Finished,
+	/// Session has failed.
+	Failed,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Cluster.
+	pub cluster: Arc<dyn Cluster>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Public,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+impl SessionImpl {
+	/// Create new key rotation session.
+	pub fn new(params: SessionParams) -> Result<(Self, Oneshot<Result<(), Error>>), Error> {
+		let key_share = params.key_storage.get(&params.meta.id)?;
+		let (completed, oneshot) = CompletionSignal::new();
+		Ok((SessionImpl {
+			core: SessionCore {
+				meta: params.meta,
+				nonce: params.nonce,
+				key_share: key_share,
+				cluster: params.cluster,
+				key_storage: params.key_storage,
+				admin_public: params.admin_public,
+				completed,
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				version: None,
+				new_version: None,
+				nodes: BTreeMap::new(),
+				resharing: None,
+				rotated_key_share: None,
+				result: None,
+			}),
+		}, oneshot))
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Initialize key rotation session on master node.
+	/// `admin_signature` is the key id, signed with administrator key.
+	pub fn initialize(&self, version: Option<H256>, admin_signature: Signature) -> Result<(), Error> {
+		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the request is authorized by administrator
+		Self::check_admin_signature(&self.core, &admin_signature)?;
+
+		// rotate the latest version of the key by default
+		let key_share = self.core.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let version = match version {
+			Some(version) => version,
+			None => key_share.versions.iter().last().map(|v| v.hash.clone()).ok_or(Error::ServerKeyIsNotFound)?,
+		};
+		Self::fill_nodes(&self.core, &mut *data, &version, H256::random())?;
+
+		// update state
+		data.state = SessionState::WaitingForInitializationConfirm;
+		data.nodes.get_mut(&self.core.meta.self_node_id)
+			.expect("fill_nodes checks that this node is version holder; qed")
+			.initialization_confirmed = true;
+
+		// start initialization
+		let message = InitializeKeyRotationSession {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+			version: version.into(),
+			new_version: data.new_version.clone().expect("filled by fill_nodes; qed").into(),
+			admin_signature: admin_signature.into(),
+		};
+		for node in data.nodes.keys().filter(|n| **n != self.core.meta.self_node_id) {
+			self.core.cluster.send(node, Message::KeyRotation(KeyRotationMessage::InitializeKeyRotationSession(message.clone())))?;
+		}
+
+		// if this node is the only version holder => proceed
+		Self::on_initialization_confirmed(&self.core, &mut *data)
+	}
+
+	/// Process single message.
+	pub fn process_message(&self, sender: &NodeId, message: &KeyRotationMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&KeyRotationMessage::InitializeKeyRotationSession(ref message) =>
+				self.on_initialize_session(sender, message),
+			&KeyRotationMessage::ConfirmKeyRotationInitialization(ref message) =>
+				self.on_confirm_initialization(sender, message),
+			&KeyRotationMessage::KeyRotationKeysDissemination(ref message) =>
+				self.on_keys_dissemination(sender, message),
+			&KeyRotationMessage::ConfirmKeyRotation(ref message) =>
+				self.on_confirm_rotation(sender, message),
+			&KeyRotationMessage::CommitKeyRotation(ref message) =>
+				self.on_commit_rotation(sender, message),
+			&KeyRotationMessage::KeyRotationError(ref message) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+		}
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: &NodeId, message: &InitializeKeyRotationSession) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the request is authorized by administrator
+		Self::check_admin_signature(&self.core, &message.admin_signature.clone().into())?;
+		Self::fill_nodes(&self.core, &mut *data, &message.version.clone().into(), message.new_version.clone().into())?;
+
+		// update state
+		data.state = SessionState::WaitingForKeysDissemination;
+
+		// send confirmation back to master node
+		self.core.cluster.send(sender, Message::KeyRotation(KeyRotationMessage::ConfirmKeyRotationInitialization(ConfirmKeyRotationInitialization {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+		})))
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: &NodeId, message: &ConfirmKeyRotationInitialization) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// mark node as confirmed
+		{
+			let node = data.nodes.get_mut(sender).ok_or(Error::InvalidMessage)?;
+			if node.initialization_confirmed {
+				return Err(Error::InvalidMessage);
+			}
+			node.initialization_confirmed = true;
+		}
+
+		Self::on_initialization_confirmed(&self.core, &mut *data)
+	}
+
+	/// When keys dissemination message is received.
+	pub fn on_keys_dissemination(&self, sender: &NodeId, message: &KeyRotationKeysDissemination) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForKeysDissemination {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// every node sends exactly one subshare, which is checked against publics of its polynom coefficients
+		data.resharing.as_mut().expect("resharing is filled on initialization; qed").on_subshare(
+			sender,
+			message.public_share.clone().into(),
+			Some(message.public_delta.clone().into()),
+			message.commitments.iter().cloned().map(Into::into).collect(),
+			message.secret_subshare.clone().into(),
+		)?;
+
+		// if we have received subshare from master node, it means that we should start dissemination
+		if sender == &self.core.meta.master_node_id {
+			Self::disseminate_keys(&self.core, &mut *data)?;
+		}
+
+		Self::try_rotate_key_share(&self.core, &mut *data)
+	}
+
+	/// When rotation confirmation message is received.
+	pub fn on_confirm_rotation(&self, sender: &NodeId, message: &ConfirmKeyRotation) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// slave nodes could rotate key share before master has received all subshares
+		if self.core.meta.self_node_id != self.core.meta.master_node_id
+			|| (data.state != SessionState::WaitingForKeysDissemination && data.state != SessionState::WaitingForRotationConfirm) {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// remember node' results
+		{
+			let node = data.nodes.get_mut(sender).ok_or(Error::InvalidMessage)?;
+			if node.new_public.is_some() {
+				return Err(Error::InvalidMessage);
+			}
+			node.new_public = Some(message.new_public.clone().into());
+			node.public_shares_footprint = Some(message.public_shares_footprint.clone().into());
+			node.reencryption_point = match (message.reencryption_point.clone(), message.reencryption_proof.clone()) {
+				(Some(point), Some((c, r))) => Some((point.into(), (c.into(), r.into()))),
+				(None, None) => None,
+				_ => return Err(Error::InvalidMessage),
+			};
+			node.slots_reencryption_points = message.slots_reencryption_points.iter()
+				.map(|(slot, point)| message.slots_reencryption_proofs.get(slot)
+					.map(|&(ref c, ref r)| (slot.clone().into(), (point.clone().into(), (c.clone().into(), r.clone().into()))))
+					.ok_or(Error::InvalidMessage))
+				.collect::<Result<_, _>>()?;
+		}
+
+		Self::try_commit(&self.core, &mut *data)
+	}
+
+	/// When rotation commit message is received.
+	pub fn on_commit_rotation(&self, sender: &NodeId, message: &CommitKeyRotation) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForCommit {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// re-encrypted point must be provided iff document key is stored
+		let rotated_key_share = data.rotated_key_share.as_ref().expect("rotated key share is computed before commit; qed");
+		if rotated_key_share.common_point.is_some() != message.encrypted_point.is_some() {
+			return Err(Error::InvalidMessage);
+		}
+
+		// re-encrypted points must be provided for every stored slot
+		if rotated_key_share.document_key_slots.len() != message.slots_encrypted_points.len()
+			|| message.slots_encrypted_points.keys().any(|slot| !rotated_key_share.document_key_slots.contains_key(&**slot)) {
+			return Err(Error::InvalidMessage);
+		}
+
+		let slots_encrypted_points = message.slots_encrypted_points.iter()
+			.map(|(slot, point)| (slot.clone().into(), point.clone().into()))
+			.collect();
+		Self::complete_session(&self.core, &mut *data, message.encrypted_point.clone().map(Into::into), slots_encrypted_points)
+	}
+
+	/// Check that key id is signed by administrator.
+	fn check_admin_signature(core: &SessionCore, admin_signature: &Signature) -> Result<(), Error> {
+		if recover(admin_signature, &core.meta.id)? != core.admin_public {
+			return Err(Error::AccessDenied);
+		}
+
+		Ok(())
+	}
+
+	/// Fill version holders data.
+	fn fill_nodes(core: &SessionCore, data: &mut SessionData, version: &H256, new_version: H256) -> Result<(), Error> {
+		let key_share = core.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let key_version = key_share.version(version)?;
+
+		// the rotated version must differ from every known version
+		if key_share.versions.iter().any(|v| v.hash == new_version) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// every version holder must participate, or its share would become obsolete
+		let connected_nodes = core.cluster.nodes();
+		if key_version.id_numbers.keys().any(|n| !connected_nodes.contains(n)) {
+			return Err(Error::ConsensusUnreachable);
+		}
+		if !key_version.id_numbers.contains_key(&core.meta.master_node_id) {
+			return Err(Error::ConsensusUnreachable);
+		}
+
+		data.version = Some(version.clone());
+		data.new_version = Some(new_version);
+		data.resharing = Some(Resharing::new(core.meta.self_node_id.clone(), AbsoluteTerm::Random, key_share.threshold,
+			key_share, key_version)?);
+		data.nodes = key_version.id_numbers.keys()
+			.map(|n| (n.clone(), NodeData {
+				initialization_confirmed: false,
+				new_public: None,
+				public_shares_footprint: None,
+				reencryption_point: None,
+				slots_reencryption_points: BTreeMap::new(),
+			}))
+			.collect();
+
+		Ok(())
+	}
+
+	/// When all version holders have confirmed initialization.
+	fn on_initialization_confirmed(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		if data.nodes.values().any(|n| !n.initialization_confirmed) {
+			return Ok(());
+		}
+
+		data.state = SessionState::WaitingForKeysDissemination;
+		Self::disseminate_keys(core, data)?;
+		Self::try_rotate_key_share(core, data)
+	}
+
+	/// Generate random polynom && send its values to all version holders.
+	fn disseminate_keys(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let dealing = data.resharing.as_mut()
+			.expect("disseminate_keys is called after initialization; resharing is filled on initialization; qed")
+			.deal()?;
+		let public_delta = dealing.absolute_term_public.expect("absolute term is random when rotating key; qed");
+
+		for (node, secret_subshare) in dealing.secret_subshares {
+			core.cluster.send(&node, Message::KeyRotation(KeyRotationMessage::KeyRotationKeysDissemination(KeyRotationKeysDissemination {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				secret_subshare: secret_subshare.into(),
+				public_delta: public_delta.clone().into(),
+				public_share: dealing.public_share.clone().into(),
+				commitments: dealing.commitments.iter().cloned().map(Into::into).collect(),
+			})))?;
+		}
+
+		Ok(())
+	}
+
+	/// Compute rotated key share && share of re-encryption point, if subshares from all version holders are received.
+	fn try_rotate_key_share(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let explanation = "try_rotate_key_share is called after initialization; version holders have key share; qed";
+		let (delta_share, secret_share, new_public, public_shares) = {
+			let resharing = data.resharing.as_ref().expect(explanation);
+			if !resharing.is_completed() {
+				return Ok(());
+			}
+
+			// rotated secret share = old secret share + sum of received subshares
+			// rotated public = old public + sum of received polynoms absolute terms publics
+			// rotated public share = old public share + sum of publics of subshares, computed from commitments
+			(resharing.compute_delta_share()?, resharing.compute_secret_share()?,
+				resharing.compute_public()?, resharing.compute_public_shares()?)
+		};
+		let public_shares_footprint = compute_public_shares_footprint(&public_shares)?;
+
+		let mut rotated_key_share = core.key_share.clone().expect(explanation);
+		let key_version = rotated_key_share.version(data.version.as_ref().expect(explanation)).expect(explanation).clone();
+
+		// share of the re-encryption point is only required when document key is stored
+		let compute_reencryption_point = |common_point: &Public| math::compute_node_reencryption_point(
+			&delta_share,
+			&key_version.id_numbers[&core.meta.self_node_id],
+			key_version.id_numbers.iter().filter(|&(n, _)| *n != core.meta.self_node_id).map(|(_, id_number)| id_number),
+			common_point,
+		);
+		let reencryption_point = match rotated_key_share.common_point.as_ref() {
+			Some(common_point) => Some(compute_reencryption_point(common_point)?),
+			None => None,
+		};
+		let slots_reencryption_points = rotated_key_share.document_key_slots.iter()
+			.map(|(slot, document_key)| compute_reencryption_point(&document_key.common_point).map(|point| (slot.clone(), point)))
+			.collect::<Result<BTreeMap<_, _>, _>>()?;
+
+		// obsolete versions are retired, because they are useless with rotated public
+		let mut rotated_key_version = DocumentKeyShareVersion::new(key_version.id_numbers.clone(), secret_share);
+		rotated_key_version.hash = data.new_version.clone().expect(explanation);
+		rotated_key_version.public_shares = public_shares;
+		rotated_key_share.public = new_public.clone();
+		rotated_key_share.versions = vec![rotated_key_version];
+		data.rotated_key_share = Some(rotated_key_share);
+
+		if core.meta.self_node_id != core.meta.master_node_id {
+			data.state = SessionState::WaitingForCommit;
+			return core.cluster.send(&core.meta.master_node_id, Message::KeyRotation(KeyRotationMessage::ConfirmKeyRotation(ConfirmKeyRotation {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				new_public: new_public.into(),
+				public_shares_footprint: public_shares_footprint.into(),
+				reencryption_point: reencryption_point.clone().map(|(point, _)| point.into()),
+				reencryption_proof: reencryption_point.map(|(_, (c, r))| (c.into(), r.into())),
+				slots_reencryption_points: slots_reencryption_points.iter()
+					.map(|(slot, &(ref point, _))| (slot.clone().into(), point.clone().into()))
+					.collect(),
+				slots_reencryption_proofs: slots_reencryption_points.into_iter()
+					.map(|(slot, (_, (c, r)))| (slot.into(), (c.into(), r.into())))
+					.collect(),
+			})));
+		}
+
+		data.state = SessionState::WaitingForRotationConfirm;
+		{
+			let self_node = data.nodes.get_mut(&core.meta.self_node_id).expect("master node is always a version holder; qed");
+			self_node.new_public = Some(new_public);
+			self_node.public_shares_footprint = Some(public_shares_footprint);
+			self_node.reencryption_point = reencryption_point;
+			self_node.slots_reencryption_points = slots_reencryption_points;
+		}
+		Self::try_commit(core, data)
+	}
+
+	/// Re-encrypt document key && commit rotated key, if all version holders have computed rotated key share.
+	fn try_commit(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		if data.state != SessionState::WaitingForRotationConfirm || data.nodes.values().any(|n| n.new_public.is_none()) {
+			return Ok(());
+		}
+
+		// all nodes must agree on rotated public && public shares
+		let rotated_key_share = data.rotated_key_share.as_ref().expect("rotated key share is computed before commit; qed");
+		let public_shares_footprint = data.nodes[&core.meta.self_node_id].public_shares_footprint.clone();
+		if data.nodes.values().any(|n| n.new_public.as_ref() != Some(&rotated_key_share.public)
+			|| n.public_shares_footprint != public_shares_footprint) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// every share of re-encryption point must be proved against public of the node share of (y' - y)
+		let resharing = data.resharing.as_ref().expect("resharing is completed before commit; qed");
+		let id_numbers = &rotated_key_share.versions[0].id_numbers;
+		let mut delta_publics = BTreeMap::new();
+		for node in data.nodes.keys() {
+			delta_publics.insert(node.clone(), resharing.compute_delta_public(node)?.ok_or(Error::InvalidMessage)?);
+		}
+		let check_reencryption_point = |node: &NodeId, common_point: &Public, reencryption_point: Option<&(Public, (Secret, Secret))>| -> Result<Public, Error> {
+			let &(ref reencryption_point, ref reencryption_proof) = reencryption_point.ok_or(Error::InvalidMessage)?;
+			let is_proved = math::verify_node_reencryption_point(&delta_publics[node], &id_numbers[node],
+				id_numbers.iter().filter(|&(n, _)| n != node).map(|(_, id_number)| id_number),
+				common_point, reencryption_point, reencryption_proof)?;
+			match is_proved {
+				true => Ok(reencryption_point.clone()),
+				false => Err(Error::InvalidPartialResponse(node.clone())),
+			}
+		};
+
+		// re-encrypt document key (if it is stored)
+		let encrypted_point = match (rotated_key_share.common_point.as_ref(), rotated_key_share.encrypted_point.as_ref()) {
+			(Some(common_point), Some(encrypted_point)) => {
+				let reencryption_points = data.nodes.iter()
+					.map(|(node, n)| check_reencryption_point(node, common_point, n.reencryption_point.as_ref()))
+					.collect::<Result<Vec<_>, _>>()?;
+				Some(math::compute_reencrypted_point(encrypted_point, reencryption_points.iter())?)
+			},
+			_ => None,
+		};
+
+		// re-encrypt document keys, stored in slots
+		let slots_encrypted_points = rotated_key_share.document_key_slots.iter()
+			.map(|(slot, document_key)| -> Result<_, Error> {
+				let reencryption_points = data.nodes.iter()
+					.map(|(node, n)| check_reencryption_point(node, &document_key.common_point, n.slots_reencryption_points.get(slot)))
+					.collect::<Result<Vec<_>, _>>()?;
+				Ok((slot.clone(), math::compute_reencrypted_point(&document_key.encrypted_point, reencryption_points.iter())?))
+			})
+			.collect::<Result<BTreeMap<_, _>, _>>()?;
+
+		for node in data.nodes.keys().filter(|n| **n != core.meta.self_node_id) {
+			core.cluster.send(node, Message::KeyRotation(KeyRotationMessage::CommitKeyRotation(CommitKeyRotation {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				encrypted_point: encrypted_point.clone().map(Into::into),
+				slots_encrypted_points: slots_encrypted_points.iter()
+					.map(|(slot, point)| (slot.clone().into(), point.clone().into()))
+					.collect(),
+			})))?;
+		}
+
+		Self::complete_session(core, data, encrypted_point, slots_encrypted_points)
+	}
+
+	/// Save rotated key share && complete session.
+	fn complete_session(
+		core: &SessionCore,
+		data: &mut SessionData,
+		encrypted_point: Option<Public>,
+		slots_encrypted_points: BTreeMap<DocumentKeySlotId, Public>,
+	) -> Result<(), Error> {
+		let mut rotated_key_share = data.rotated_key_share.take().expect("complete_session is called after key share is rotated; qed");
+		rotated_key_share.encrypted_point = encrypted_point;
+		for (slot, encrypted_point) in slots_encrypted_points {
+			if let Some(document_key) = rotated_key_share.document_key_slots.get_mut(&slot) {
+				document_key.encrypted_point = encrypted_point;
+			}
+		}
+		core.key_storage.update(core.meta.id.clone(), rotated_key_share)?;
+
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(()));
+		core.completed.send(Ok(()));
+
+		Ok(())
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = (); // never used directly
+	type SuccessfulResult = ();
+
+	fn type_name() -> &'static str {
+		"key rotation"
+	}
+
+	fn id(&self) -> SessionId {
+		self.core.meta.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_session_timeout(&self) {
+		self.on_session_error(&self.core.meta.self_node_id, Error::NodeDisconnected)
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		self.on_session_error(node, Error::NodeDisconnected)
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in key rotation session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.core.meta.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.core.cluster.broadcast(Message::KeyRotation(KeyRotationMessage::KeyRotationError(KeyRotationError {
+				session: self.core.meta.id.clone().into(),
+				session_nonce: self.core.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		let mut data = self.data.lock();
+
+		warn!(target: "secretstore_net", "{}: key rotation session failed: {} on {}",
+			self.core.meta.self_node_id, error, node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error.clone()));
+		self.core.completed.send(Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::KeyRotation(ref message) => self.process_message(sender, message),
+			// admin sessions creator reports session creation errors using this message
+			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeError(ref message)) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "Key rotation session {} on {}", self.core.meta.id, self.core.meta.self_node_id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+	use ethereum_types::H256;
+	use crypto::publickey::{Random, Generator, Public, sign};
+	use blockchain::SigningKeyPair;
+	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage, DocumentKeySlot};
+	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
+	use key_server_cluster::message::{Message, KeyRotationMessage};
+	use key_server_cluster::servers_set_change_session::tests::{MessageLoop, AdminSessionAdapter, generate_key};
+	use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+	use key_server_cluster::math;
+	use super::{SessionImpl, SessionParams};
+
+	struct Adapter;
+
+	impl AdminSessionAdapter<SessionImpl> for Adapter {
+		const SIGN_NEW_NODES: bool = false;
+
+		fn create(
+			mut meta: ShareChangeSessionMeta,
+			admin_public: Public,
+			_: BTreeSet<NodeId>,
+			ml: &ClusterMessageLoop,
+			idx: usize
+		) -> SessionImpl {
+			meta.self_node_id = *ml.node_key_pair(idx).public();
+			SessionImpl::new(SessionParams {
+				meta: meta,
+				cluster: ml.cluster(idx).view().unwrap(),
+				key_storage: ml.key_storage(idx).clone(),
+				admin_public: admin_public,
+				nonce: 1,
+			}).unwrap().0
+		}
+	}
+
+	impl MessageLoop<SessionImpl> {
+		pub fn run_rotation_at(mut self, master: NodeId, signed_id: SessionId) -> Result<Self, Error> {
+			let signature = sign(self.admin_key_pair.secret(), &signed_id).unwrap();
+			self.sessions[&master].initialize(None, signature)?;
+			self.run();
+			Ok(self)
+		}
+	}
+
+	fn key_id() -> SessionId {
+		SessionId::from([1u8; 32])
+	}
+
+	fn store_document_key(ml: &ClusterMessageLoop) -> (Public, math::EncryptedSecret) {
+		let document_key = math::generate_random_point().unwrap();
+		let encrypted_document_key = math::encrypt_secret(&document_key, &ml.key_storage(0).get(&key_id()).unwrap().unwrap().public).unwrap();
+		for i in 0..ml.nodes().len() {
+			let mut key_share = ml.key_storage(i).get(&key_id()).unwrap().unwrap();
+			key_share.common_point = Some(encrypted_document_key.common_point.clone());
+			key_share.encrypted_point = Some(encrypted_document_key.encrypted_point.clone());
+			ml.key_storage(i).update(key_id(), key_share).unwrap();
+		}
+
+		(document_key, encrypted_document_key)
+	}
+
+	#[test]
+	fn key_is_rotated_and_document_key_is_reencrypted() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+
+		// store document key, encrypted with original key
+		let (document_key, encrypted_document_key) = store_document_key(&gml.0);
+
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_rotation_at(master, key_id()).unwrap();
+		ml.check_public_shares_are_filled(ml.sessions.keys());
+
+		// every node has single rotated version && the same rotated public
+		let key_shares: Vec<_> = (0..3).map(|i| ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap()).collect();
+		let new_public = key_shares[0].public.clone();
+		assert!(new_public != *ml.original_key_pair.public());
+		for key_share in &key_shares {
+			assert_eq!(key_share.public, new_public);
+			assert_eq!(key_share.versions.len(), 1);
+			assert!(key_share.versions[0].hash != ml.original_key_version);
+			assert_eq!(key_share.common_point, Some(encrypted_document_key.common_point.clone()));
+			assert!(key_share.encrypted_point != Some(encrypted_document_key.encrypted_point.clone()));
+		}
+
+		// rotated shares are restoring secret of rotated public && document key is decrypted with it
+		let new_secret = math::compute_joint_secret_from_shares(1,
+			&[&key_shares[0].versions[0].secret_share, &key_shares[2].versions[0].secret_share],
+			&[&key_shares[0].versions[0].id_numbers[&ml.ml.node(0)], &key_shares[2].versions[0].id_numbers[&ml.ml.node(2)]]).unwrap();
+		assert_eq!(math::compute_public_share(&new_secret).unwrap(), new_public);
+		assert_eq!(math::decrypt_with_joint_secret(key_shares[1].encrypted_point.as_ref().unwrap(),
+			key_shares[1].common_point.as_ref().unwrap(), &new_secret).unwrap(), document_key);
+	}
+
+	#[test]
+	fn document_keys_in_slots_are_reencrypted() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(2);
+
+		// store two document keys in slots (default document key is not stored)
+		let public = gml.0.key_storage(0).get(&key_id()).unwrap().unwrap().public;
+		let document_keys: Vec<_> = (0..2).map(|_| math::generate_random_point().unwrap()).collect();
+		let encrypted_document_keys: Vec<_> = document_keys.iter()
+			.map(|document_key| math::encrypt_secret(document_key, &public).unwrap())
+			.collect();
+		for i in 0..3 {
+			let mut key_share = gml.0.key_storage(i).get(&key_id()).unwrap().unwrap();
+			for (slot, encrypted_document_key) in encrypted_document_keys.iter().enumerate() {
+				key_share.document_key_slots.insert(H256::from_low_u64_be(slot as u64), DocumentKeySlot {
+					common_point: encrypted_document_key.common_point.clone(),
+					encrypted_point: encrypted_document_key.encrypted_point.clone(),
+				});
+			}
+			gml.0.key_storage(i).update(key_id(), key_share).unwrap();
+		}
+
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_rotation_at(master, key_id()).unwrap();
+
+		// every document key is decrypted with rotated secret
+		let key_shares: Vec<_> = (0..3).map(|i| ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap()).collect();
+		let new_secret = math::compute_joint_secret_from_shares(1,
+			&[&key_shares[0].versions[0].secret_share, &key_shares[1].versions[0].secret_share],
+			&[&key_shares[0].versions[0].id_numbers[&ml.ml.node(0)], &key_shares[1].versions[0].id_numbers[&ml.ml.node(1)]]).unwrap();
+		for key_share in &key_shares {
+			assert!(key_share.encrypted_point.is_none());
+			assert_eq!(key_share.document_key_slots, key_shares[0].document_key_slots);
+		}
+		for (slot, document_key) in document_keys.into_iter().enumerate() {
+			let document_key_slot = &key_shares[2].document_key_slots[&H256::from_low_u64_be(slot as u64)];
+			assert!(document_key_slot.encrypted_point != encrypted_document_keys[slot].encrypted_point);
+			assert_eq!(math::decrypt_with_joint_secret(&document_key_slot.encrypted_point,
+				&document_key_slot.common_point, &new_secret).unwrap(), document_key);
+		}
+	}
+
+	#[test]
+	fn key_without_document_key_is_rotated() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(1);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_rotation_at(master, key_id()).unwrap();
+
+		let key_shares: Vec<_> = (0..3).map(|i| ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap()).collect();
+		assert!(key_shares[0].public != *ml.original_key_pair.public());
+		assert!(key_shares.iter().all(|ks| ks.public == key_shares[0].public && ks.encrypted_point.is_none()));
+	}
+
+	#[test]
+	fn rotation_fails_if_signed_by_non_admin() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(Random.generate().secret(), &key_id()).unwrap();
+		assert_eq!(ml.sessions[&master].initialize(None, signature).unwrap_err(), Error::AccessDenied);
+	}
+
+	#[test]
+	fn rotation_fails_if_other_key_is_signed() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_rotation_at(master, SessionId::from([2u8; 32])).unwrap_err(), Error::AccessDenied);
+	}
+
+	#[test]
+	fn rotation_fails_if_version_holder_is_isolated() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let isolate = ::std::iter::once(gml.0.node(1)).collect();
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, Some(isolate))
+			.run_rotation_at(master, key_id()).unwrap_err(), Error::ConsensusUnreachable);
+	}
+
+	#[test]
+	fn document_key_is_reencrypted_when_number_of_holders_is_even() {
+		let gml = generate_key(4, 2);
+		let master = gml.0.node(3);
+		let (document_key, _) = store_document_key(&gml.0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_rotation_at(master, key_id()).unwrap();
+		ml.check_public_shares_are_filled(ml.sessions.keys());
+
+		let key_shares: Vec<_> = (0..4).map(|i| ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap()).collect();
+		let new_secret = math::compute_joint_secret_from_shares(2,
+			&key_shares.iter().take(3).map(|ks| &ks.versions[0].secret_share).collect::<Vec<_>>(),
+			&(0..3).map(|i| &key_shares[i].versions[0].id_numbers[&ml.ml.node(i)]).collect::<Vec<_>>()).unwrap();
+		assert_eq!(math::compute_public_share(&new_secret).unwrap(), key_shares[0].public);
+		assert_eq!(math::decrypt_with_joint_secret(key_shares[0].encrypted_point.as_ref().unwrap(),
+			key_shares[0].common_point.as_ref().unwrap(), &new_secret).unwrap(), document_key);
+	}
+
+	#[test]
+	fn rotation_fails_if_reencryption_point_is_not_proved() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		store_document_key(&gml.0);
+		let mut ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(ml.admin_key_pair.secret(), &key_id()).unwrap();
+		ml.sessions[&master].initialize(None, signature).unwrap();
+
+		// node 1 sends share of re-encryption point, which isn't computed using its share of the key delta
+		let malicious_node = ml.ml.node(1);
+		let mut result = Ok(());
+		while let Some((from, to, mut message)) = ml.take_message() {
+			if let Message::KeyRotation(KeyRotationMessage::ConfirmKeyRotation(ref mut message)) = message {
+				if from == malicious_node {
+					message.reencryption_point = Some(math::generate_random_point().unwrap().into());
+				}
+			}
+
+			result = ml.process_message((from, to, message));
+			if result.is_err() {
+				break;
+			}
+		}
+		assert_eq!(result, Err(Error::InvalidPartialResponse(malicious_node)));
+	}
+}
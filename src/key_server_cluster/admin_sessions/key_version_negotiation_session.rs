@@ -20,11 +20,13 @@ use ethereum_types::{Address, H256};
 use crypto::publickey::Secret;
 use futures::Oneshot;
 use parking_lot::Mutex;
-use key_server_cluster::{Error, SessionId, NodeId, DocumentKeyShare};
+use key_server_cluster::{Error, SessionId, NodeId, DocumentKeyShare, SchnorrSigningScheme};
 use key_server_cluster::cluster::Cluster;
 use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession, CompletionSignal};
 use key_server_cluster::decryption_session::SessionImpl as DecryptionSession;
 use key_server_cluster::signing_session_ecdsa::SessionImpl as EcdsaSigningSession;
+use key_server_cluster::signing_session_eddsa::SessionImpl as EddsaSigningSession;
+use key_server_cluster::signing_session_bls::SessionImpl as BlsSigningSession;
 use key_server_cluster::signing_session_schnorr::SessionImpl as SchnorrSigningSession;
 use key_server_cluster::message::{Message, KeyVersionNegotiationMessage, RequestKeyVersions,
 	KeyVersions, KeyVersionsError, FailedKeyVersionContinueAction, CommonKeyData};
@@ -61,10 +63,14 @@ pub struct SessionImpl<T: SessionTransport> {
 pub enum ContinueAction {
 	/// Decryption session + origin + is_shadow_decryption + is_broadcast_decryption.
 	Decrypt(Arc<DecryptionSession>, Option<Address>, bool, bool),
-	/// Schnorr signing session + message hash.
-	SchnorrSign(Arc<SchnorrSigningSession>, H256),
+	/// Schnorr signing session + signature scheme + message hashes.
+	SchnorrSign(Arc<SchnorrSigningSession>, SchnorrSigningScheme, Vec<H256>),
 	/// ECDSA signing session + message hash.
 	EcdsaSign(Arc<EcdsaSigningSession>, H256),
+	/// EdDSA signing session + message hash.
+	EddsaSign(Arc<EddsaSigningSession>, H256),
+	/// BLS signing session + message hash.
+	BlsSign(Arc<BlsSigningSession>, H256),
 }
 
 /// Failed action after key version is negotiated.
@@ -619,7 +625,7 @@ mod tests {
 	use ethereum_types::{H512, H160, Address};
 	use crypto::publickey::public_to_address;
 	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage, DummyKeyStorage,
-		DocumentKeyShare, DocumentKeyShareVersion};
+		DocumentKeyShare, DocumentKeyShareVersion, KeyCurve};
 	use key_server_cluster::math;
 	use key_server_cluster::cluster::Cluster;
 	use key_server_cluster::cluster::tests::DummyCluster;
@@ -904,10 +910,16 @@ mod tests {
 			public: H512::from_low_u64_be(3),
 			common_point: None,
 			encrypted_point: None,
+			curve: KeyCurve::Secp256k1,
+			ecdsa_scheme: Default::default(),
+			exported: false,
+			document_key_slots: Default::default(),
+			expiration: None,
 			versions: vec![DocumentKeyShareVersion {
 				hash: version_id,
 				id_numbers: vec![(nodes.keys().cloned().nth(0).unwrap(), math::generate_random_scalar().unwrap())].into_iter().collect(),
 				secret_share: math::generate_random_scalar().unwrap(),
+				public_shares: Default::default(),
 			}],
 		}).unwrap();
 		let ml = MessageLoop::new(nodes);
@@ -14,11 +14,16 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod key_export_session;
+pub mod key_rotation_session;
 pub mod key_version_negotiation_session;
 pub mod servers_set_change_session;
 pub mod share_add_session;
 pub mod share_change_session;
+pub mod share_refresh_session;
+pub mod threshold_change_session;
 
+mod resharing;
 mod sessions_queue;
 
 use key_server_cluster::{SessionId, NodeId, SessionMeta, Error};
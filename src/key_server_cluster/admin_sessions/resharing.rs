@@ -0,0 +1,307 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use ethereum_types::H256;
+use crypto::publickey::{Public, Secret};
+use key_server_cluster::{Error, NodeId, DocumentKeyShare, DocumentKeyShareVersion, KeyCurve};
+use key_server_cluster::math;
+
+/// Absolute term of the polynom, dealt by every version holder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AbsoluteTerm {
+	/// Zero absolute term: shares are re-randomized, while joint secret is preserved (share refresh).
+	Zero,
+	/// Random absolute term: joint secret is shifted by the sum of all absolute terms (key rotation).
+	Random,
+	/// Secret share of the holder: joint secret is re-shared using polynoms of another degree (threshold change).
+	SecretShare,
+}
+
+/// Polynom, dealt by this node.
+pub struct Dealing {
+	/// Public share of this node in the re-shared key version.
+	pub public_share: Public,
+	/// Public of the polynom absolute term (for random absolute term only, since other terms are known to receivers).
+	pub absolute_term_public: Option<Public>,
+	/// Publics of other polynom coefficients (Feldman commitments).
+	pub commitments: Vec<Public>,
+	/// Values of the polynom at id numbers of other version holders.
+	pub secret_subshares: BTreeMap<NodeId, Secret>,
+}
+
+/// Re-sharing of single key version between all its holders, which is the core of share refresh, key rotation && threshold
+/// change sessions. Every version holder deals random polynom && sends its value to every other holder, along with publics
+/// of polynom coefficients. Received values are verified against these publics, so the holder can't send subshare that
+/// doesn't belong to its polynom, or deal polynom with wrong absolute term (i.e. public of the absolute term must be equal
+/// to the holder' public share, when it is re-sharing its secret share). Publics are also used to compute public shares of
+/// all holders in the new key version.
+pub struct Resharing {
+	/// This node id.
+	self_node_id: NodeId,
+	/// Absolute term of dealt polynoms.
+	absolute_term: AbsoluteTerm,
+	/// Degree of dealt polynoms.
+	threshold: usize,
+	/// Threshold of the re-shared key version.
+	key_threshold: usize,
+	/// Joint public of the re-shared key version.
+	key_public: Public,
+	/// Secret share of this node in the re-shared key version.
+	secret_share: Secret,
+	/// Version holders data.
+	nodes: BTreeMap<NodeId, NodeData>,
+}
+
+/// Version holder data.
+struct NodeData {
+	/// Id number of the node.
+	pub id_number: Secret,
+	/// Public share of the node in the re-shared key version.
+	pub public_share: Option<Public>,
+	/// Secret subshare, received from the node.
+	pub secret_subshare: Option<Secret>,
+	/// Public of the node polynom absolute term (for random absolute term only).
+	pub absolute_term_public: Option<Public>,
+	/// Publics of other coefficients of the node polynom.
+	pub commitments: Vec<Public>,
+}
+
+impl Resharing {
+	/// Prepare re-sharing of given key version with polynoms of given degree.
+	pub fn new(self_node_id: NodeId, absolute_term: AbsoluteTerm, threshold: usize, key_share: &DocumentKeyShare, key_version: &DocumentKeyShareVersion) -> Result<Self, Error> {
+		// publics of polynoms coefficients are only computed for secp256k1 keys
+		if key_share.curve != KeyCurve::Secp256k1 {
+			return Err(Error::InvalidKeyCurve);
+		}
+		if !key_version.id_numbers.contains_key(&self_node_id) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+
+		Ok(Resharing {
+			self_node_id: self_node_id,
+			absolute_term: absolute_term,
+			threshold: threshold,
+			key_threshold: key_share.threshold,
+			key_public: key_share.public.clone(),
+			secret_share: key_version.secret_share.clone(),
+			nodes: key_version.id_numbers.iter()
+				.map(|(n, id_number)| (n.clone(), NodeData {
+					id_number: id_number.clone(),
+					public_share: key_version.public_shares.get(n).cloned(),
+					secret_subshare: None,
+					absolute_term_public: None,
+					commitments: Vec::new(),
+				}))
+				.collect(),
+		})
+	}
+
+	/// Deal random polynom. Subshare of this node is remembered, subshares of other nodes are returned.
+	pub fn deal(&mut self) -> Result<Dealing, Error> {
+		let mut polynom = math::generate_random_polynom(self.threshold)?;
+		let absolute_term_public = match self.absolute_term {
+			AbsoluteTerm::Zero => {
+				polynom[0] = math::zero_scalar();
+				None
+			},
+			AbsoluteTerm::Random => Some(math::compute_public_share(&polynom[0])?),
+			AbsoluteTerm::SecretShare => {
+				polynom[0] = self.secret_share.clone();
+				None
+			},
+		};
+		let commitments = math::prepare_share_proof(&polynom[1..])?;
+		let public_share = math::compute_public_share(&self.secret_share)?;
+
+		let mut secret_subshares = BTreeMap::new();
+		for (node, node_data) in self.nodes.iter_mut() {
+			let secret_subshare = math::compute_polynom(&polynom, &node_data.id_number)?;
+			if *node != self.self_node_id {
+				secret_subshares.insert(node.clone(), secret_subshare);
+				continue;
+			}
+
+			node_data.public_share = Some(public_share.clone());
+			node_data.secret_subshare = Some(secret_subshare);
+			node_data.absolute_term_public = absolute_term_public.clone();
+			node_data.commitments = commitments.clone();
+		}
+
+		Ok(Dealing {
+			public_share: public_share,
+			absolute_term_public: absolute_term_public,
+			commitments: commitments,
+			secret_subshares: secret_subshares,
+		})
+	}
+
+	/// Process subshare, dealt by other node.
+	pub fn on_subshare(&mut self, sender: &NodeId, public_share: Public, absolute_term_public: Option<Public>, commitments: Vec<Public>, secret_subshare: Secret) -> Result<(), Error> {
+		let self_id_number = self.nodes.get(&self.self_node_id).expect("self node is always version holder; qed").id_number.clone();
+		let node = self.nodes.get_mut(sender).ok_or(Error::InvalidMessage)?;
+
+		// every node sends exactly one subshare of the polynom of required degree && with required absolute term
+		if node.secret_subshare.is_some() || commitments.len() != self.threshold {
+			return Err(Error::InvalidMessage);
+		}
+		if absolute_term_public.is_some() != (self.absolute_term == AbsoluteTerm::Random) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// public share of the node (if known) must be the same
+		if node.public_share.as_ref().map(|p| *p != public_share).unwrap_or(false) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// subshare must be the value of committed polynom
+		let polynom_absolute_term_public = match self.absolute_term {
+			AbsoluteTerm::SecretShare => Some(&public_share),
+			AbsoluteTerm::Zero | AbsoluteTerm::Random => absolute_term_public.as_ref(),
+		};
+		if !math::polynom_value_verification(polynom_absolute_term_public, &commitments, &self_id_number, &secret_subshare)? {
+			return Err(Error::InvalidMessage);
+		}
+
+		node.public_share = Some(public_share);
+		node.secret_subshare = Some(secret_subshare);
+		node.absolute_term_public = absolute_term_public;
+		node.commitments = commitments;
+		Ok(())
+	}
+
+	/// Are subshares from all version holders received?
+	pub fn is_completed(&self) -> bool {
+		self.nodes.values().all(|n| n.secret_subshare.is_some())
+	}
+
+	/// Compute node share of the difference between new && re-shared joint secrets (when absolute term is zero or random).
+	pub fn compute_delta_share(&self) -> Result<Secret, Error> {
+		debug_assert!(self.is_completed());
+		debug_assert!(self.absolute_term != AbsoluteTerm::SecretShare);
+		math::compute_secret_sum(self.nodes.values().map(|n| n.secret_subshare.as_ref().expect("called when all subshares are received; qed")))
+	}
+
+	/// Compute node share of the new key version.
+	pub fn compute_secret_share(&self) -> Result<Secret, Error> {
+		match self.absolute_term {
+			// new secret share = old secret share + sum of received subshares
+			AbsoluteTerm::Zero | AbsoluteTerm::Random =>
+				math::compute_secret_sum(vec![self.secret_share.clone(), self.compute_delta_share()?].iter()),
+			// new secret share = sum of received subshares, multiplied by Lagrange coefficients of senders
+			AbsoluteTerm::SecretShare => {
+				debug_assert!(self.is_completed());
+				let mut secret_subshares = Vec::with_capacity(self.nodes.len());
+				for (node, node_data) in &self.nodes {
+					let lagrange_coeff = math::compute_lagrange_coeff(&node_data.id_number, self.other_id_numbers(node))?;
+					secret_subshares.push(math::compute_secret_mul(&lagrange_coeff,
+						node_data.secret_subshare.as_ref().expect("called when all subshares are received; qed"))?);
+				}
+				math::compute_secret_share(secret_subshares.iter())
+			},
+		}
+	}
+
+	/// Compute joint public of the new key version.
+	pub fn compute_public(&self) -> Result<Public, Error> {
+		debug_assert!(self.is_completed());
+		math::compute_public_sum(::std::iter::once(&self.key_public)
+			.chain(self.nodes.values().filter_map(|n| n.absolute_term_public.as_ref())))
+	}
+
+	/// Compute public of the node share of the difference between new && re-shared joint secrets (when absolute term is
+	/// zero or random).
+	pub fn compute_delta_public(&self, node: &NodeId) -> Result<Option<Public>, Error> {
+		debug_assert!(self.is_completed());
+		debug_assert!(self.absolute_term != AbsoluteTerm::SecretShare);
+		let id_number = &self.nodes.get(node).ok_or(Error::InvalidMessage)?.id_number;
+		let mut delta_publics = Vec::with_capacity(self.nodes.len());
+		for node_data in self.nodes.values() {
+			if let Some(delta_public) = math::compute_polynom_public(node_data.absolute_term_public.as_ref(), &node_data.commitments, id_number)? {
+				delta_publics.push(delta_public);
+			}
+		}
+
+		match delta_publics.is_empty() {
+			true => Ok(None),
+			false => Ok(Some(math::compute_public_sum(delta_publics.iter())?)),
+		}
+	}
+
+	/// Compute public of the node share of the new key version, when secret shares are re-shared.
+	fn compute_reshared_public_share(&self, node: &NodeId) -> Result<Public, Error> {
+		let id_number = &self.nodes.get(node).ok_or(Error::InvalidMessage)?.id_number;
+		let mut subshares_publics = Vec::with_capacity(self.nodes.len());
+		for (sender, sender_data) in &self.nodes {
+			let subshare_public = math::compute_polynom_public(Self::absolute_term_public(self.absolute_term, sender_data),
+				&sender_data.commitments, id_number)?.expect("absolute term public is known for every sender; qed");
+			let lagrange_coeff = math::compute_lagrange_coeff(&sender_data.id_number, self.other_id_numbers(sender))?;
+			subshares_publics.push(math::compute_public_mul(&subshare_public, &lagrange_coeff)?);
+		}
+
+		math::compute_public_sum(subshares_publics.iter())
+	}
+
+	/// Get id numbers of all version holders, except given node.
+	fn other_id_numbers<'a>(&'a self, node: &'a NodeId) -> impl Iterator<Item=&'a Secret> + 'a {
+		self.nodes.iter().filter(move |&(n, _)| n != node).map(|(_, n)| &n.id_number)
+	}
+
+	/// Get public of the node polynom absolute term.
+	fn absolute_term_public(absolute_term: AbsoluteTerm, node: &NodeData) -> Option<&Public> {
+		match absolute_term {
+			AbsoluteTerm::Zero => None,
+			AbsoluteTerm::Random => node.absolute_term_public.as_ref(),
+			AbsoluteTerm::SecretShare => node.public_share.as_ref(),
+		}
+	}
+
+	/// Compute public shares of all version holders in the new key version.
+	pub fn compute_public_shares(&self) -> Result<BTreeMap<NodeId, Public>, Error> {
+		debug_assert!(self.is_completed());
+
+		// public shares of the re-shared version are reported by holders themselves => check that they are consistent
+		// with the joint public, so that the holder can't use fake public share to prove wrong computations later
+		let id_numbers: Vec<_> = self.nodes.values().map(|n| &n.id_number).collect();
+		let public_shares: Vec<_> = self.nodes.values()
+			.map(|n| n.public_share.as_ref().expect("public share is filled along with subshare; qed"))
+			.collect();
+		if !math::public_shares_verification(self.key_threshold, &self.key_public, &id_numbers, &public_shares)? {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut new_public_shares = BTreeMap::new();
+		for (node, public_share) in self.nodes.keys().zip(public_shares) {
+			let new_public_share = match self.absolute_term {
+				AbsoluteTerm::SecretShare => self.compute_reshared_public_share(node)?,
+				AbsoluteTerm::Zero | AbsoluteTerm::Random => match self.compute_delta_public(node)? {
+					Some(delta_public) => math::compute_public_sum(vec![public_share.clone(), delta_public].iter())?,
+					None => public_share.clone(),
+				},
+			};
+			new_public_shares.insert(node.clone(), new_public_share);
+		}
+
+		Ok(new_public_shares)
+	}
+}
+
+/// Compute footprint of public shares, which is used to check that all version holders have computed the same public shares.
+pub fn compute_public_shares_footprint(public_shares: &BTreeMap<NodeId, Public>) -> Result<H256, Error> {
+	math::compute_publics_footprint(public_shares.iter()
+		.map(|(node, public_share)| (node.clone(), vec![node.clone(), public_share.clone()]))
+		.collect())
+}
@@ -15,6 +15,7 @@
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::{BTreeSet, BTreeMap};
 use std::collections::btree_map::Entry;
 use futures::Oneshot;
@@ -157,6 +158,8 @@ struct ServersSetChangeConsensusTransport {
 	nonce: u64,
 	/// Migration id (if part of auto-migration process).
 	migration_id: Option<H256>,
+	/// Is consensus request already broadcasted?
+	is_request_broadcasted: AtomicBool,
 	/// Cluster.
 	cluster: Arc<dyn Cluster>,
 }
@@ -245,6 +248,7 @@ impl SessionImpl {
 				id: self.core.meta.id.clone(),
 				nonce: self.core.nonce,
 				migration_id: self.core.migration_id.clone(),
+				is_request_broadcasted: AtomicBool::new(false),
 				cluster: self.core.cluster.clone(),
 			},
 		})?;
@@ -319,6 +323,7 @@ impl SessionImpl {
 								id: self.core.meta.id.clone(),
 								nonce: self.core.nonce,
 								migration_id: self.core.migration_id.clone(),
+								is_request_broadcasted: AtomicBool::new(false),
 								cluster: self.core.cluster.clone(),
 							},
 						})?);
@@ -521,6 +526,11 @@ impl SessionImpl {
 					}
 				}
 
+				// plan is broadcasted to all session participants => ignore it if we're not participating in share change
+				if !master_plan.new_nodes_map.contains_key(&self.core.meta.self_node_id) {
+					return Ok(());
+				}
+
 				let session = Self::create_share_change_session(&self.core, key_id, master_node_id, master_plan)?;
 				if !session.is_finished() {
 					data.active_key_sessions.insert(key_id.clone(), session);
@@ -834,8 +844,9 @@ impl SessionImpl {
 				.map(|(n, nid)| (n.clone().into(), nid.clone().map(Into::into)))
 				.collect(),
 		}));
-		for node in &confirmations {
-			core.cluster.send(&node, initialization_message.clone())?;
+		// plan is broadcasted reliably, so that all honest nodes are receiving the same plan
+		if !confirmations.is_empty() {
+			core.cluster.reliable_broadcast(initialization_message)?;
 		}
 
 		// create session on this node if required
@@ -895,7 +906,7 @@ impl SessionImpl {
 		debug_assert_eq!(core.meta.self_node_id, core.meta.master_node_id);
 
 		// send completion notification
-		core.cluster.broadcast(Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeCompleted(ServersSetChangeCompleted {
+		core.cluster.reliable_broadcast(Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeCompleted(ServersSetChangeCompleted {
 			session: core.meta.id.clone().into(),
 			session_nonce: core.nonce,
 		})))?;
@@ -974,8 +985,13 @@ impl JobTransport for ServersSetChangeConsensusTransport {
 	type PartialJobRequest=ServersSetChangeAccessRequest;
 	type PartialJobResponse=bool;
 
-	fn send_partial_request(&self, node: &NodeId, request: ServersSetChangeAccessRequest) -> Result<(), Error> {
-		self.cluster.send(node, Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeConsensusMessage(ServersSetChangeConsensusMessage {
+	fn send_partial_request(&self, _node: &NodeId, request: ServersSetChangeAccessRequest) -> Result<(), Error> {
+		// the same request is sent to every node => broadcast it reliably once
+		if self.is_request_broadcasted.swap(true, Ordering::Relaxed) {
+			return Ok(());
+		}
+
+		self.cluster.reliable_broadcast(Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeConsensusMessage(ServersSetChangeConsensusMessage {
 			session: self.id.clone().into(),
 			session_nonce: self.nonce,
 			message: ConsensusMessageWithServersSet::InitializeConsensusSession(InitializeConsensusSessionWithServersSet {
@@ -1055,6 +1071,7 @@ pub mod tests {
 	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage, PlainNodeKeyPair};
 	use key_server_cluster::cluster_sessions::ClusterSession;
 	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
+	use key_server_cluster::cluster_reliable_broadcast::ReliableBroadcast;
 	use key_server_cluster::generation_session::tests::{MessageLoop as GenerationMessageLoop};
 	use key_server_cluster::math;
 	use key_server_cluster::message::Message;
@@ -1084,6 +1101,7 @@ pub mod tests {
 		pub all_set_signature: Signature,
 		pub new_set_signature: Signature,
 		pub sessions: BTreeMap<NodeId, S>,
+		pub broadcasts: BTreeMap<NodeId, ReliableBroadcast>,
 		pub queue: VecDeque<(NodeId, NodeId, Message)>,
 	}
 
@@ -1174,9 +1192,16 @@ pub mod tests {
 			let admin_key_pair = Random.generate();
 			let admin_public = admin_key_pair.public().clone();
 
+			// nodes that are isolated in the cluster message loop are still configured, but are offline
+			let offline_nodes_ids: BTreeSet<_> = (0..ml.nodes().len())
+				.filter(|idx| ml.is_isolated(*idx))
+				.map(|idx| ml.node(idx))
+				.filter(|n| !isolated_nodes_ids.contains(n))
+				.collect();
+
 			// all active nodes set
 			let mut all_nodes_set: BTreeSet<_> = ml.nodes().into_iter()
-				.filter(|n| !isolated_nodes_ids.contains(n))
+				.filter(|n| !isolated_nodes_ids.contains(n) && !offline_nodes_ids.contains(n))
 				.collect();
 			// new nodes set includes all old nodes, except nodes being removed + all nodes being added
 			let new_nodes_set: BTreeSet<NodeId> = all_nodes_set.iter().cloned()
@@ -1209,11 +1234,20 @@ pub mod tests {
 				let idx = ml.nodes().iter().position(|n| n == isolated_node_id).unwrap();
 				ml.exclude(idx);
 			}
+			// new nodes are not connected to offline nodes
+			for offline_node_id in &offline_nodes_ids {
+				let idx = ml.nodes().iter().position(|n| n == offline_node_id).unwrap();
+				ml.isolate(idx);
+			}
 
 			// prepare set of nodes
 			let sessions: BTreeMap<_, _> = (0..ml.nodes().len())
+				.filter(|idx| !offline_nodes_ids.contains(&ml.node(*idx)))
 				.map(|idx| (ml.node(idx), C::create(meta.clone(), admin_public, all_nodes_set.clone(), &ml, idx)))
 				.collect();
+			let broadcasts = sessions.keys()
+				.map(|n| (*n, ReliableBroadcast::new(*n)))
+				.collect();
 
 			let all_set_signature = sign(admin_key_pair.secret(), &ordered_nodes_hash(&old_set_to_sign)).unwrap();
 			let new_set_signature = sign(admin_key_pair.secret(), &ordered_nodes_hash(&new_nodes_set)).unwrap();
@@ -1228,6 +1262,7 @@ pub mod tests {
 				all_set_signature: all_set_signature,
 				new_set_signature: new_set_signature,
 				sessions,
+				broadcasts,
 				queue: Default::default(),
 			}
 		}
@@ -1247,6 +1282,17 @@ pub mod tests {
 		}
 
 		pub fn process_message(&mut self, msg: (NodeId, NodeId, Message)) -> Result<(), Error> {
+			// reliable broadcast messages are processed by the message loop itself
+			if let Message::Cluster(ref message) = msg.2 {
+				let cluster_nodes = self.ml.cluster_nodes_of(&msg.1);
+				let result = self.broadcasts[&msg.1].process_message(&msg.0, &cluster_nodes, message)?;
+				self.queue.extend(result.messages.into_iter().map(|(to, message)| (msg.1, to, message)));
+				return match result.delivered {
+					Some((origin, message)) => self.process_message((origin, msg.1, message)),
+					None => Ok(()),
+				};
+			}
+
 			match self.sessions[&msg.1].on_message(&msg.0, &msg.2) {
 				Ok(_) => Ok(()),
 				Err(Error::TooEarlyForRequest) => {
@@ -1285,6 +1331,21 @@ pub mod tests {
 				}
 			}
 		}
+
+		pub fn check_public_shares_are_filled<'a, I: IntoIterator<Item=&'a NodeId>>(&self, nodes: I) {
+			let nodes: Vec<_> = nodes.into_iter().collect();
+			let key_shares: Vec<_> = nodes.iter()
+				.map(|n| self.ml.key_storage_of(n).get(&SessionId::from([1u8; 32])).unwrap().unwrap())
+				.collect();
+			for key_share in &key_shares {
+				let public_shares = &key_share.last_version().unwrap().public_shares;
+				assert_eq!(public_shares.len(), nodes.len());
+				for (node, node_key_share) in nodes.iter().zip(key_shares.iter()) {
+					assert_eq!(public_shares[*node],
+						math::compute_public_share(&node_key_share.last_version().unwrap().secret_share).unwrap());
+				}
+			}
+		}
 	}
 
 	impl MessageLoop<SessionImpl> {
@@ -1395,6 +1456,26 @@ pub mod tests {
 			.all(|k| ml.ml.key_storage_of(k).get(&SessionId::from([1u8; 32])).unwrap().is_some()));
 	}
 
+	#[test]
+	fn offline_node_removed_using_servers_set_change_with_reliable_broadcast() {
+		// initial 2-of-4 session
+		let gml = GenerationMessageLoop::with_reliable_broadcast(4).init(1).unwrap();
+		gml.0.loop_until(|| gml.0.is_empty());
+
+		// remove offline (but still configured) node && add 1 node, so that the plan is broadcasted
+		let remove: BTreeSet<_> = ::std::iter::once(gml.0.node(3)).collect();
+		gml.0.isolate(3);
+		let add = vec![Random.generate()];
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, Some(add.clone()), Some(remove.clone()), None)
+			.run_at(master);
+
+		// try to recover secret for every possible combination of nodes && check that secret is the same
+		assert!(ml.sessions.keys().all(|k| !remove.contains(k)));
+		assert!(ml.sessions.contains_key(add[0].public()));
+		ml.check_secret_is_preserved(ml.sessions.keys());
+	}
+
 	#[test]
 	fn having_less_than_required_nodes_after_change_does_not_fail_change_session() {
 		// initial 2-of-3 session
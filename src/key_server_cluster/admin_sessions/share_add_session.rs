@@ -20,7 +20,8 @@ use ethereum_types::{H256, Address};
 use crypto::publickey::{Public, Secret, Signature};
 use futures::Oneshot;
 use parking_lot::Mutex;
-use key_server_cluster::{Error, SessionId, NodeId, DocumentKeyShare, DocumentKeyShareVersion, KeyStorage};
+use key_server_cluster::{Error, SessionId, NodeId, DocumentKeyShare, DocumentKeyShareVersion, DocumentKeySlot,
+	DocumentKeySlotId, KeyCurve, EcdsaSigningScheme, KeyStorage, KeyExpiration};
 use key_server_cluster::cluster::Cluster;
 use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
 use key_server_cluster::math;
@@ -49,8 +50,8 @@ pub trait SessionTransport: Clone + JobTransport<PartialJobRequest=ServersSetCha
 /// Brief overview:
 /// 1) initialization: master node (which has received request for shares addition the message) asks all other nodes to support addition
 /// 2) key refreshing distribution (KRD): node generates new random polynom && sends required data to all other nodes
-/// 3) key refreshing verification (KRV): node verifies received data
-/// 4) node updates its own key share using generated (&& received) data
+/// 3) key refreshing verification (KRV): node verifies received data against publics of polynom coefficients
+/// 4) node updates its own key share using generated (&& received) data && computes public shares of all nodes
 pub struct SessionImpl<T: SessionTransport> {
 	/// Session core.
 	core: SessionCore<T>,
@@ -95,6 +96,8 @@ struct SessionData<T: SessionTransport> {
 	pub id_numbers: Option<BTreeMap<NodeId, Option<Secret>>>,
 	/// Secret subshares received from nodes.
 	pub secret_subshares: Option<BTreeMap<NodeId, Option<Secret>>>,
+	/// Publics of polynoms absolute terms (public shares of dealers) && other coefficients, received from nodes.
+	pub polynoms_publics: BTreeMap<NodeId, (Public, Vec<Public>)>,
 	/// Share add change result.
 	pub result: Option<Result<(), Error>>,
 }
@@ -111,6 +114,14 @@ struct NewKeyShare {
 	pub common_point: Option<Public>,
 	/// NewKeyShare: Encrypted point.
 	pub encrypted_point: Option<Public>,
+	/// NewKeyShare: ECDSA signing scheme.
+	pub ecdsa_scheme: EcdsaSigningScheme,
+	/// NewKeyShare: key export flag.
+	pub exported: bool,
+	/// NewKeyShare: document keys, stored in slots.
+	pub document_key_slots: BTreeMap<DocumentKeySlotId, DocumentKeySlot>,
+	/// NewKeyShare: key expiration.
+	pub expiration: Option<KeyExpiration>,
 }
 
 /// Session state.
@@ -180,6 +191,7 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 				new_key_share: None,
 				id_numbers: None,
 				secret_subshares: None,
+				polynoms_publics: BTreeMap::new(),
 				result: None,
 			}),
 		}, oneshot))
@@ -475,6 +487,15 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 			joint_public: message.key_common.public.clone().into(),
 			common_point: message.common_point.clone().map(Into::into),
 			encrypted_point: message.encrypted_point.clone().map(Into::into),
+			ecdsa_scheme: message.ecdsa_scheme,
+			exported: message.exported,
+			document_key_slots: message.document_key_slots.iter()
+				.map(|(slot, &(ref common_point, ref encrypted_point))| (slot.clone().into(), DocumentKeySlot {
+					common_point: common_point.clone().into(),
+					encrypted_point: encrypted_point.clone().into(),
+				}))
+				.collect(),
+			expiration: message.expiration,
 		});
 
 		let id_numbers = data.id_numbers.as_mut()
@@ -525,10 +546,16 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 				Some(&None) => (),
 			};
 
-			let secret_subshare = Self::compute_secret_subshare(&self.core, &mut *data, sender, &message.secret_subshare.clone().into())?;
+			let secret_value = message.secret_subshare.clone().into();
+			let public_share = message.public_share.clone().into();
+			let commitments: Vec<_> = message.commitments.iter().cloned().map(Into::into).collect();
+			Self::check_secret_value(&self.core, &*data, sender, &secret_value, &public_share, &commitments)?;
+
+			let secret_subshare = Self::compute_secret_subshare(&self.core, &mut *data, sender, &secret_value)?;
 			*data.secret_subshares.as_mut().expect(explanation)
 				.get_mut(sender)
 				.expect("checked couple of lines above; qed") = Some(secret_subshare);
+			data.polynoms_publics.insert(sender.clone(), (public_share, commitments));
 		}
 
 		// if we have received subshare from master node, it means that we should start dissemination
@@ -541,7 +568,6 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 			return Ok(())
 		}
 
-		// TODO [Trust]: find a way to verificate keys
 		Self::complete_session(&self.core, &mut *data)
 	}
 
@@ -625,7 +651,6 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 			return Ok(())
 		}
 
-		// TODO [Trust]: find a way to verificate keys
 		Self::complete_session(core, data)
 	}
 
@@ -653,6 +678,13 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 				},
 				common_point: old_key_share.common_point.clone().map(Into::into),
 				encrypted_point: old_key_share.encrypted_point.clone().map(Into::into),
+				ecdsa_scheme: old_key_share.ecdsa_scheme,
+				exported: old_key_share.exported,
+				document_key_slots: old_key_share.document_key_slots.iter()
+					.map(|(slot, document_key)| (slot.clone().into(),
+						(document_key.common_point.clone().into(), document_key.encrypted_point.clone().into())))
+					.collect(),
+				expiration: old_key_share.expiration,
 				id_numbers: old_key_version.id_numbers.iter()
 					.filter(|&(k, _)| version_holders.contains(k))
 					.map(|(k, v)| (k.clone().into(), v.clone().into())).collect(),
@@ -670,6 +702,8 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 		let key_version = key_share.version(data.version.as_ref().expect(explanation)).expect(explanation);
 		let mut secret_share_polynom = math::generate_random_polynom(key_share.threshold)?;
 		secret_share_polynom[0] = key_version.secret_share.clone();
+		let public_share = math::compute_public_share(&key_version.secret_share)?;
+		let commitments = math::prepare_share_proof(&secret_share_polynom[1..])?;
 
 		// calculate secret subshare for every new node (including this node)
 		let explanation = "disseminate_keys is called after initialization has completed; this field is filled during initialization; qed";
@@ -681,6 +715,8 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 					session: core.meta.id.clone().into(),
 					session_nonce: core.nonce,
 					secret_subshare: secret_subshare.into(),
+					public_share: public_share.clone().into(),
+					commitments: commitments.iter().cloned().map(Into::into).collect(),
 				}))?;
 			} else {
 				let secret_subshare = Self::compute_secret_subshare(core, data, new_node, &secret_subshare)?;
@@ -690,10 +726,71 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 						= Some(secret_subshare);
 			}
 		}
+		data.polynoms_publics.insert(core.meta.self_node_id.clone(), (public_share, commitments));
+
+		Ok(())
+	}
+
+	/// Check that secret value, received from consensus group node, is the value of its committed polynom && that the
+	/// absolute term of this polynom is the public share of the sender (if known to this node).
+	fn check_secret_value(core: &SessionCore<T>, data: &SessionData<T>, sender: &NodeId, secret_value: &Secret, public_share: &Public, commitments: &[Public]) -> Result<(), Error> {
+		let threshold = core.key_share.as_ref().map(|ks| ks.threshold)
+			.unwrap_or_else(|| data.new_key_share.as_ref()
+				.expect("secret values are received after receiving key share threshold if not having one already; qed")
+				.threshold);
+		if commitments.len() != threshold {
+			return Err(Error::InvalidMessage);
+		}
+
+		let known_public_share = core.key_share.as_ref()
+			.and_then(|ks| data.version.as_ref().and_then(|v| ks.version(v).ok()))
+			.and_then(|v| v.public_shares.get(sender));
+		if known_public_share.map(|p| p != public_share).unwrap_or(false) {
+			return Err(Error::InvalidMessage);
+		}
+
+		let self_id_number = data.id_numbers.as_ref()
+			.and_then(|id_numbers| id_numbers.get(&core.meta.self_node_id).cloned())
+			.and_then(|id_number| id_number)
+			.ok_or(Error::InvalidStateForRequest)?;
+		if !math::polynom_value_verification(Some(public_share), commitments, &self_id_number, secret_value)? {
+			return Err(Error::InvalidMessage);
+		}
 
 		Ok(())
 	}
 
+	/// Compute public shares of all nodes in the new key version, using publics of consensus group polynoms.
+	fn compute_public_shares(joint_public: &Public, id_numbers: &BTreeMap<NodeId, Secret>, polynoms_publics: &BTreeMap<NodeId, (Public, Vec<Public>)>) -> Result<BTreeMap<NodeId, Public>, Error> {
+		// every dealer has re-shared its secret share => interpolated public shares must give the joint public
+		let mut lagrange_coeffs = BTreeMap::new();
+		for dealer in polynoms_publics.keys() {
+			let other_id_numbers = polynoms_publics.keys().filter(|n| *n != dealer).map(|n| &id_numbers[n]);
+			lagrange_coeffs.insert(dealer.clone(), math::compute_lagrange_coeff(&id_numbers[dealer], other_id_numbers)?);
+		}
+		let mut dealers_publics = Vec::with_capacity(polynoms_publics.len());
+		for (dealer, &(ref public_share, _)) in polynoms_publics {
+			dealers_publics.push(math::compute_public_mul(public_share, &lagrange_coeffs[dealer])?);
+		}
+		if math::compute_public_sum(dealers_publics.iter())? != *joint_public {
+			return Err(Error::InvalidMessage);
+		}
+
+		// public share of the node = sum of publics of its subshares, multiplied by Lagrange coefficients of dealers
+		let mut public_shares = BTreeMap::new();
+		for (node, id_number) in id_numbers {
+			let mut subshares_publics = Vec::with_capacity(polynoms_publics.len());
+			for (dealer, &(ref public_share, ref commitments)) in polynoms_publics {
+				let subshare_public = math::compute_polynom_public(Some(public_share), commitments, id_number)?
+					.ok_or(Error::InvalidMessage)?;
+				subshares_publics.push(math::compute_public_mul(&subshare_public, &lagrange_coeffs[dealer])?);
+			}
+			public_shares.insert(node.clone(), math::compute_public_sum(subshares_publics.iter())?);
+		}
+
+		Ok(public_shares)
+	}
+
 	/// Compute secret subshare from passed secret value.
 	fn compute_secret_subshare(core: &SessionCore<T>, data: &SessionData<T>, sender: &NodeId, secret_value: &Secret) -> Result<Secret, Error> {
 		let explanation = "this field is a result of consensus job; compute_secret_subshare is called after consensus is established";
@@ -725,9 +822,15 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 		let secret_share = math::compute_secret_share(secret_subshares.values().map(|ss| ss.as_ref()
 			.expect("complete_session is only called when subshares from all nodes are received; qed")))?;
 
-		let refreshed_key_version = DocumentKeyShareVersion::new(id_numbers.clone().into_iter().map(|(k, v)| (k.clone(),
-			v.expect("id_numbers are checked to have Some value for every consensus group node when consensus is establishe; qed"))).collect(),
-			secret_share);
+		let id_numbers: BTreeMap<_, _> = id_numbers.clone().into_iter().map(|(k, v)| (k.clone(),
+			v.expect("id_numbers are checked to have Some value for every consensus group node when consensus is establishe; qed"))).collect();
+		let joint_public = core.key_share.as_ref().map(|ks| ks.public.clone())
+			.unwrap_or_else(|| data.new_key_share.as_ref()
+				.expect("this is new node; on new nodes this field is filled before KRD; session is completed after KRD; qed")
+				.joint_public.clone());
+		let public_shares = Self::compute_public_shares(&joint_public, &id_numbers, &data.polynoms_publics)?;
+		let mut refreshed_key_version = DocumentKeyShareVersion::new(id_numbers, secret_share);
+		refreshed_key_version.public_shares = public_shares;
 		let mut refreshed_key_share = core.key_share.as_ref().cloned().unwrap_or_else(|| {
 			let new_key_share = data.new_key_share.as_ref()
 				.expect("this is new node; on new nodes this field is filled before KRD; session is completed after KRD; qed");
@@ -737,6 +840,11 @@ impl<T> SessionImpl<T> where T: SessionTransport {
 				public: new_key_share.joint_public.clone(),
 				common_point: new_key_share.common_point.clone(),
 				encrypted_point: new_key_share.encrypted_point.clone(),
+				curve: KeyCurve::Secp256k1,
+				ecdsa_scheme: new_key_share.ecdsa_scheme,
+				exported: new_key_share.exported,
+				document_key_slots: new_key_share.document_key_slots.clone(),
+				expiration: new_key_share.expiration,
 				versions: Vec::new(),
 			}
 		});
@@ -892,8 +1000,10 @@ pub mod tests {
 	use blockchain::SigningKeyPair;
 	use key_server_cluster::{NodeId, Error, KeyStorage};
 	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
+	use key_server_cluster::message::{Message, ShareAddMessage};
 	use key_server_cluster::servers_set_change_session::tests::{MessageLoop, AdminSessionAdapter, generate_key};
 	use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+	use key_server_cluster::math;
 	use super::{SessionImpl, SessionParams, IsolatedSessionTransport};
 
 	struct Adapter;
@@ -1017,7 +1127,34 @@ pub mod tests {
 
 			// check that secret is still the same as before adding the share
 			ml.check_secret_is_preserved(ml.sessions.keys());
+
+			// check that every node knows public shares of all nodes
+			ml.check_public_shares_are_filled(ml.sessions.keys());
+		}
+	}
+
+	#[test]
+	fn nodes_add_fails_if_subshare_does_not_match_commitments() {
+		let gml = generate_key(3, 1);
+		let add = vec![Random.generate()];
+		let master = gml.0.node(0);
+		let mut ml = MessageLoop::with_gml::<Adapter>(gml, master, Some(add), None, None).init_at(master).unwrap();
+
+		// master node sends subshares that aren't values of its committed polynom
+		let mut result = Ok(());
+		while let Some((from, to, mut message)) = ml.take_message() {
+			if let Message::ShareAdd(ShareAddMessage::NewKeysDissemination(ref mut message)) = message {
+				if from == master {
+					message.secret_subshare = math::generate_random_scalar().unwrap().into();
+				}
+			}
+
+			result = ml.process_message((from, to, message));
+			if result.is_err() {
+				break;
+			}
 		}
+		assert_eq!(result, Err(Error::InvalidMessage));
 	}
 
 	#[test]
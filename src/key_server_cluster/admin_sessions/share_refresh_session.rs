@@ -0,0 +1,767 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::sync::Arc;
+use ethereum_types::H256;
+use crypto::publickey::{Public, Signature, recover};
+use futures::Oneshot;
+use parking_lot::Mutex;
+use key_server_cluster::{Error, NodeId, SessionId, DocumentKeyShare, DocumentKeyShareVersion, KeyStorage};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal, SHARE_REFRESH_ALL_KEYS_ID};
+use key_server_cluster::message::{Message, ShareRefreshMessage, InitializeShareRefreshSession,
+	ConfirmShareRefreshInitialization, ShareRefreshKeysDissemination, ConfirmShareRefresh, CommitShareRefresh,
+	ShareRefreshError, ServersSetChangeMessage};
+use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+use key_server_cluster::admin_sessions::resharing::{Resharing, AbsoluteTerm, compute_public_shares_footprint};
+
+/// Share refresh session.
+/// Proactively re-randomizes key shares without changing joint public (and secret) key.
+/// Brief overview:
+/// 1) initialization: master node (which has received request for shares refresh) initializes the session on all version holders
+/// 2) every version holder generates random polynom with zero absolute term && sends its value to every other version holder,
+///    along with publics of polynom coefficients
+/// 3) every version holder verifies received values against publics, adds them to its secret share && saves it as a new
+///    key version, along with public shares of all holders, computed from the publics
+/// 4) when all version holders have saved new version (with the same public shares), master node asks them to remove
+///    the obsolete version
+/// Since the sum of all polynoms is zero at the origin, the joint secret is preserved, while shares of the obsolete
+/// version could not be combined with shares of the new version. Until the commit, both versions are kept, so the
+/// key stays usable if the session fails.
+pub struct SessionImpl {
+	/// Session core.
+	core: SessionCore,
+	/// Session data.
+	data: Mutex<SessionData>,
+}
+
+/// Immutable session data.
+struct SessionCore {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// Original key share.
+	pub key_share: Option<DocumentKeyShare>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	pub cluster: Arc<dyn Cluster>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Public,
+	/// Session completion signal.
+	pub completed: CompletionSignal<()>,
+}
+
+/// Mutable session data.
+struct SessionData {
+	/// Session state.
+	pub state: SessionState,
+	/// Key version to refresh.
+	pub version: Option<H256>,
+	/// Hash of the refreshed key version.
+	pub new_version: Option<H256>,
+	/// Version holders data.
+	pub nodes: BTreeMap<NodeId, NodeData>,
+	/// Re-sharing of the refreshed version.
+	pub resharing: Option<Resharing>,
+	/// Key share with both obsolete and refreshed versions.
+	pub refreshed_key_share: Option<DocumentKeyShare>,
+	/// Share refresh result.
+	pub result: Option<Result<(), Error>>,
+}
+
+/// Version holder data.
+struct NodeData {
+	/// Flag marking that node has confirmed session initialization.
+	pub initialization_confirmed: bool,
+	/// Footprint of refreshed public shares, computed by the node (on master node only).
+	pub public_shares_footprint: Option<H256>,
+}
+
+/// Session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for every version holder to confirm initialization.
+	WaitingForInitializationConfirm,
+	/// Waiting for secret subshares from every version holder.
+	WaitingForKeysDissemination,
+	/// Master node waits for every version holder to save refreshed key share.
+	WaitingForRefreshConfirm,
+	/// Slave node waits for the master node to commit refreshed version.
+	WaitingForCommit,
+	/// Session is completed.
+	Finished,
+	/// Session has failed.
+	Failed,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Cluster.
+	pub cluster: Arc<dyn Cluster>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Public,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+impl SessionImpl {
+	/// Create new share refresh session.
+	pub fn new(params: SessionParams) -> Result<(Self, Oneshot<Result<(), Error>>), Error> {
+		let key_share = params.key_storage.get(&params.meta.id)?;
+		let (completed, oneshot) = CompletionSignal::new();
+		Ok((SessionImpl {
+			core: SessionCore {
+				meta: params.meta,
+				nonce: params.nonce,
+				key_share: key_share,
+				cluster: params.cluster,
+				key_storage: params.key_storage,
+				admin_public: params.admin_public,
+				completed,
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				version: None,
+				new_version: None,
+				nodes: BTreeMap::new(),
+				resharing: None,
+				refreshed_key_share: None,
+				result: None,
+			}),
+		}, oneshot))
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Initialize share refresh session on master node.
+	/// `signed_id` is either key id, or `SHARE_REFRESH_ALL_KEYS_ID`, signed with administrator key.
+	pub fn initialize(&self, version: Option<H256>, signed_id: H256, admin_signature: Signature) -> Result<(), Error> {
+		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the request is authorized by administrator
+		Self::check_admin_signature(&self.core, &signed_id, &admin_signature)?;
+
+		// refresh the latest version of the key by default
+		let key_share = self.core.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let version = match version {
+			Some(version) => version,
+			None => key_share.versions.iter().last().map(|v| v.hash.clone()).ok_or(Error::ServerKeyIsNotFound)?,
+		};
+		Self::fill_nodes(&self.core, &mut *data, &version, H256::random())?;
+
+		// update state
+		data.state = SessionState::WaitingForInitializationConfirm;
+		data.nodes.get_mut(&self.core.meta.self_node_id)
+			.expect("fill_nodes checks that this node is version holder; qed")
+			.initialization_confirmed = true;
+
+		// start initialization
+		let message = InitializeShareRefreshSession {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+			version: version.into(),
+			new_version: data.new_version.clone().expect("filled by fill_nodes; qed").into(),
+			signed_id: signed_id.into(),
+			admin_signature: admin_signature.into(),
+		};
+		for node in data.nodes.keys().filter(|n| **n != self.core.meta.self_node_id) {
+			self.core.cluster.send(node, Message::ShareRefresh(ShareRefreshMessage::InitializeShareRefreshSession(message.clone())))?;
+		}
+
+		// if this node is the only version holder => proceed
+		Self::on_initialization_confirmed(&self.core, &mut *data)
+	}
+
+	/// Process single message.
+	pub fn process_message(&self, sender: &NodeId, message: &ShareRefreshMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&ShareRefreshMessage::InitializeShareRefreshSession(ref message) =>
+				self.on_initialize_session(sender, message),
+			&ShareRefreshMessage::ConfirmShareRefreshInitialization(ref message) =>
+				self.on_confirm_initialization(sender, message),
+			&ShareRefreshMessage::ShareRefreshKeysDissemination(ref message) =>
+				self.on_keys_dissemination(sender, message),
+			&ShareRefreshMessage::ConfirmShareRefresh(ref message) =>
+				self.on_confirm_refresh(sender, message),
+			&ShareRefreshMessage::CommitShareRefresh(ref message) =>
+				self.on_commit_refresh(sender, message),
+			&ShareRefreshMessage::ShareRefreshError(ref message) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+		}
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: &NodeId, message: &InitializeShareRefreshSession) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the request is authorized by administrator
+		Self::check_admin_signature(&self.core, &message.signed_id.clone().into(), &message.admin_signature.clone().into())?;
+		Self::fill_nodes(&self.core, &mut *data, &message.version.clone().into(), message.new_version.clone().into())?;
+
+		// update state
+		data.state = SessionState::WaitingForKeysDissemination;
+
+		// send confirmation back to master node
+		self.core.cluster.send(sender, Message::ShareRefresh(ShareRefreshMessage::ConfirmShareRefreshInitialization(ConfirmShareRefreshInitialization {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+		})))
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: &NodeId, message: &ConfirmShareRefreshInitialization) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// mark node as confirmed
+		{
+			let node = data.nodes.get_mut(sender).ok_or(Error::InvalidMessage)?;
+			if node.initialization_confirmed {
+				return Err(Error::InvalidMessage);
+			}
+			node.initialization_confirmed = true;
+		}
+
+		Self::on_initialization_confirmed(&self.core, &mut *data)
+	}
+
+	/// When keys dissemination message is received.
+	pub fn on_keys_dissemination(&self, sender: &NodeId, message: &ShareRefreshKeysDissemination) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForKeysDissemination {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// every node sends exactly one subshare, which is checked against publics of its polynom coefficients
+		data.resharing.as_mut().expect("resharing is filled on initialization; qed").on_subshare(
+			sender,
+			message.public_share.clone().into(),
+			None,
+			message.commitments.iter().cloned().map(Into::into).collect(),
+			message.secret_subshare.clone().into(),
+		)?;
+
+		// if we have received subshare from master node, it means that we should start dissemination
+		if sender == &self.core.meta.master_node_id {
+			Self::disseminate_keys(&self.core, &mut *data)?;
+		}
+
+		Self::try_refresh_key_share(&self.core, &mut *data)
+	}
+
+	/// When refresh confirmation message is received.
+	pub fn on_confirm_refresh(&self, sender: &NodeId, message: &ConfirmShareRefresh) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// slave nodes could save refreshed share before master has received all subshares
+		if self.core.meta.self_node_id != self.core.meta.master_node_id
+			|| (data.state != SessionState::WaitingForKeysDissemination && data.state != SessionState::WaitingForRefreshConfirm) {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// remember public shares footprint, computed by the node
+		{
+			let node = data.nodes.get_mut(sender).ok_or(Error::InvalidMessage)?;
+			if node.public_shares_footprint.is_some() {
+				return Err(Error::InvalidMessage);
+			}
+			node.public_shares_footprint = Some(message.public_shares_footprint.clone().into());
+		}
+
+		Self::try_commit(&self.core, &mut *data)
+	}
+
+	/// When refresh commit message is received.
+	pub fn on_commit_refresh(&self, sender: &NodeId, message: &CommitShareRefresh) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForCommit {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		Self::complete_session(&self.core, &mut *data)
+	}
+
+	/// Check that `signed_id` is signed by administrator && could be used to refresh this key.
+	fn check_admin_signature(core: &SessionCore, signed_id: &H256, admin_signature: &Signature) -> Result<(), Error> {
+		if *signed_id != core.meta.id && *signed_id != *SHARE_REFRESH_ALL_KEYS_ID {
+			return Err(Error::AccessDenied);
+		}
+
+		if recover(admin_signature, signed_id)? != core.admin_public {
+			return Err(Error::AccessDenied);
+		}
+
+		Ok(())
+	}
+
+	/// Fill version holders data.
+	fn fill_nodes(core: &SessionCore, data: &mut SessionData, version: &H256, new_version: H256) -> Result<(), Error> {
+		let key_share = core.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let key_version = key_share.version(version)?;
+
+		// the refreshed version must differ from every known version
+		if key_share.versions.iter().any(|v| v.hash == new_version) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// every version holder must participate, or its share would become obsolete
+		let connected_nodes = core.cluster.nodes();
+		if key_version.id_numbers.keys().any(|n| !connected_nodes.contains(n)) {
+			return Err(Error::ConsensusUnreachable);
+		}
+		if !key_version.id_numbers.contains_key(&core.meta.master_node_id) {
+			return Err(Error::ConsensusUnreachable);
+		}
+
+		data.version = Some(version.clone());
+		data.new_version = Some(new_version);
+		data.resharing = Some(Resharing::new(core.meta.self_node_id.clone(), AbsoluteTerm::Zero, key_share.threshold,
+			key_share, key_version)?);
+		data.nodes = key_version.id_numbers.keys()
+			.map(|n| (n.clone(), NodeData {
+				initialization_confirmed: false,
+				public_shares_footprint: None,
+			}))
+			.collect();
+
+		Ok(())
+	}
+
+	/// When all version holders have confirmed initialization.
+	fn on_initialization_confirmed(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		if data.nodes.values().any(|n| !n.initialization_confirmed) {
+			return Ok(());
+		}
+
+		data.state = SessionState::WaitingForKeysDissemination;
+		Self::disseminate_keys(core, data)?;
+		Self::try_refresh_key_share(core, data)
+	}
+
+	/// Generate random polynom with zero absolute term && send its values to all version holders.
+	fn disseminate_keys(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let dealing = data.resharing.as_mut()
+			.expect("disseminate_keys is called after initialization; resharing is filled on initialization; qed")
+			.deal()?;
+
+		for (node, secret_subshare) in dealing.secret_subshares {
+			core.cluster.send(&node, Message::ShareRefresh(ShareRefreshMessage::ShareRefreshKeysDissemination(ShareRefreshKeysDissemination {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				secret_subshare: secret_subshare.into(),
+				public_share: dealing.public_share.clone().into(),
+				commitments: dealing.commitments.iter().cloned().map(Into::into).collect(),
+			})))?;
+		}
+
+		Ok(())
+	}
+
+	/// Save refreshed key share, if subshares from all version holders are received.
+	fn try_refresh_key_share(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let explanation = "try_refresh_key_share is called after initialization; version holders have key share; qed";
+		let (secret_share, public_shares) = {
+			let resharing = data.resharing.as_ref().expect(explanation);
+			if !resharing.is_completed() {
+				return Ok(());
+			}
+
+			// refreshed secret share = old secret share + sum of received subshares
+			// refreshed public share = old public share + sum of publics of subshares, computed from commitments
+			(resharing.compute_secret_share()?, resharing.compute_public_shares()?)
+		};
+		let public_shares_footprint = compute_public_shares_footprint(&public_shares)?;
+
+		let mut refreshed_key_share = core.key_share.clone().expect(explanation);
+		let id_numbers = refreshed_key_share.version(data.version.as_ref().expect(explanation)).expect(explanation).id_numbers.clone();
+		let mut refreshed_key_version = DocumentKeyShareVersion::new(id_numbers, secret_share);
+		refreshed_key_version.hash = data.new_version.clone().expect(explanation);
+		refreshed_key_version.public_shares = public_shares;
+		refreshed_key_share.versions.push(refreshed_key_version);
+
+		// save refreshed share, leaving the obsolete version until the commit
+		core.key_storage.update(core.meta.id.clone(), refreshed_key_share.clone())?;
+		data.refreshed_key_share = Some(refreshed_key_share);
+
+		if core.meta.self_node_id != core.meta.master_node_id {
+			data.state = SessionState::WaitingForCommit;
+			return core.cluster.send(&core.meta.master_node_id, Message::ShareRefresh(ShareRefreshMessage::ConfirmShareRefresh(ConfirmShareRefresh {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				public_shares_footprint: public_shares_footprint.into(),
+			})));
+		}
+
+		data.state = SessionState::WaitingForRefreshConfirm;
+		data.nodes.get_mut(&core.meta.self_node_id)
+			.expect("master node is always a version holder; qed")
+			.public_shares_footprint = Some(public_shares_footprint);
+		Self::try_commit(core, data)
+	}
+
+	/// Commit refreshed version, if all version holders have saved refreshed key share.
+	fn try_commit(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		if data.state != SessionState::WaitingForRefreshConfirm || data.nodes.values().any(|n| n.public_shares_footprint.is_none()) {
+			return Ok(());
+		}
+
+		// all nodes must agree on refreshed public shares
+		let public_shares_footprint = data.nodes[&core.meta.self_node_id].public_shares_footprint.clone();
+		if data.nodes.values().any(|n| n.public_shares_footprint != public_shares_footprint) {
+			return Err(Error::InvalidMessage);
+		}
+
+		for node in data.nodes.keys().filter(|n| **n != core.meta.self_node_id) {
+			core.cluster.send(node, Message::ShareRefresh(ShareRefreshMessage::CommitShareRefresh(CommitShareRefresh {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+			})))?;
+		}
+
+		Self::complete_session(core, data)
+	}
+
+	/// Remove obsolete key version && complete session.
+	fn complete_session(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let explanation = "complete_session is called after refreshed key share is saved; qed";
+		let mut refreshed_key_share = data.refreshed_key_share.take().expect(explanation);
+		let version = data.version.as_ref().expect(explanation);
+		refreshed_key_share.versions.retain(|v| v.hash != *version);
+		core.key_storage.update(core.meta.id.clone(), refreshed_key_share)?;
+
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(()));
+		core.completed.send(Ok(()));
+
+		Ok(())
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = (); // never used directly
+	type SuccessfulResult = ();
+
+	fn type_name() -> &'static str {
+		"share refresh"
+	}
+
+	fn id(&self) -> SessionId {
+		self.core.meta.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_session_timeout(&self) {
+		self.on_session_error(&self.core.meta.self_node_id, Error::NodeDisconnected)
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		self.on_session_error(node, Error::NodeDisconnected)
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in share refresh session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.core.meta.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.core.cluster.broadcast(Message::ShareRefresh(ShareRefreshMessage::ShareRefreshError(ShareRefreshError {
+				session: self.core.meta.id.clone().into(),
+				session_nonce: self.core.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		let mut data = self.data.lock();
+
+		warn!(target: "secretstore_net", "{}: share refresh session failed: {} on {}",
+			self.core.meta.self_node_id, error, node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error.clone()));
+		self.core.completed.send(Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::ShareRefresh(ref message) => self.process_message(sender, message),
+			// admin sessions creator reports session creation errors using this message
+			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeError(ref message)) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "Share refresh session {} on {}", self.core.meta.id, self.core.meta.self_node_id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+	use crypto::publickey::{Random, Generator, Public, sign};
+	use blockchain::SigningKeyPair;
+	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage};
+	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
+	use key_server_cluster::cluster_sessions::SHARE_REFRESH_ALL_KEYS_ID;
+	use key_server_cluster::message::{Message, ShareRefreshMessage};
+	use key_server_cluster::servers_set_change_session::tests::{MessageLoop, AdminSessionAdapter, generate_key};
+	use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+	use key_server_cluster::math;
+	use super::{SessionImpl, SessionParams};
+
+	struct Adapter;
+
+	impl AdminSessionAdapter<SessionImpl> for Adapter {
+		const SIGN_NEW_NODES: bool = false;
+
+		fn create(
+			mut meta: ShareChangeSessionMeta,
+			admin_public: Public,
+			_: BTreeSet<NodeId>,
+			ml: &ClusterMessageLoop,
+			idx: usize
+		) -> SessionImpl {
+			meta.self_node_id = *ml.node_key_pair(idx).public();
+			SessionImpl::new(SessionParams {
+				meta: meta,
+				cluster: ml.cluster(idx).view().unwrap(),
+				key_storage: ml.key_storage(idx).clone(),
+				admin_public: admin_public,
+				nonce: 1,
+			}).unwrap().0
+		}
+	}
+
+	impl MessageLoop<SessionImpl> {
+		pub fn run_refresh_at(mut self, master: NodeId, signed_id: SessionId) -> Result<Self, Error> {
+			let signature = sign(self.admin_key_pair.secret(), &signed_id).unwrap();
+			self.sessions[&master].initialize(None, signed_id, signature)?;
+			self.run();
+			Ok(self)
+		}
+	}
+
+	fn key_id() -> SessionId {
+		SessionId::from([1u8; 32])
+	}
+
+	#[test]
+	fn shares_are_refreshed() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let old_key_shares: Vec<_> = (0..3).map(|i| gml.0.key_storage(i).get(&key_id()).unwrap().unwrap()).collect();
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_refresh_at(master, key_id()).unwrap();
+
+		// check that secret is still the same as before refreshing the shares
+		ml.check_secret_is_preserved(ml.sessions.keys());
+		ml.check_public_shares_are_filled(ml.sessions.keys());
+
+		// check that every node has single refreshed version with updated secret share
+		let new_version = ml.ml.key_storage(0).get(&key_id()).unwrap().unwrap().versions[0].hash.clone();
+		assert!(new_version != ml.original_key_version);
+		for i in 0..3 {
+			let key_share = ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap();
+			assert_eq!(key_share.versions.len(), 1);
+			assert_eq!(key_share.versions[0].hash, new_version);
+			assert_eq!(key_share.versions[0].id_numbers, old_key_shares[i].versions[0].id_numbers);
+			assert!(key_share.versions[0].secret_share != old_key_shares[i].versions[0].secret_share);
+			assert_eq!(key_share.public, old_key_shares[i].public);
+		}
+	}
+
+	#[test]
+	fn obsolete_shares_are_useless_after_refresh() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let old_key_share = gml.0.key_storage(0).get(&key_id()).unwrap().unwrap();
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_refresh_at(master, key_id()).unwrap();
+
+		// combine obsolete share of the first node with refreshed share of the second node
+		let new_key_share = ml.ml.key_storage(1).get(&key_id()).unwrap().unwrap();
+		let id_numbers = [
+			old_key_share.versions[0].id_numbers[&ml.ml.node(0)].clone(),
+			new_key_share.versions[0].id_numbers[&ml.ml.node(1)].clone(),
+		];
+		let joint_secret = math::compute_joint_secret_from_shares(1,
+			&[&old_key_share.versions[0].secret_share, &new_key_share.versions[0].secret_share],
+			&[&id_numbers[0], &id_numbers[1]]).unwrap();
+		assert!(joint_secret != *ml.original_key_pair.secret());
+	}
+
+	#[test]
+	fn shares_are_refreshed_with_bulk_signature() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(1);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_refresh_at(master, *SHARE_REFRESH_ALL_KEYS_ID).unwrap();
+
+		ml.check_secret_is_preserved(ml.sessions.keys());
+	}
+
+	#[test]
+	fn refresh_fails_if_signed_by_non_admin() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(Random.generate().secret(), &key_id()).unwrap();
+		assert_eq!(ml.sessions[&master].initialize(None, key_id(), signature).unwrap_err(), Error::AccessDenied);
+	}
+
+	#[test]
+	fn refresh_fails_if_other_key_is_signed() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_refresh_at(master, SessionId::from([2u8; 32])).unwrap_err(), Error::AccessDenied);
+	}
+
+	#[test]
+	fn refresh_fails_if_version_holder_is_isolated() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let isolate = ::std::iter::once(gml.0.node(1)).collect();
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, Some(isolate))
+			.run_refresh_at(master, key_id()).unwrap_err(), Error::ConsensusUnreachable);
+	}
+
+	#[test]
+	fn refresh_fails_if_initialized_twice() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(ml.admin_key_pair.secret(), &key_id()).unwrap();
+		ml.sessions[&master].initialize(None, key_id(), signature.clone()).unwrap();
+		assert_eq!(ml.sessions[&master].initialize(None, key_id(), signature).unwrap_err(), Error::InvalidStateForRequest);
+	}
+
+	#[test]
+	fn public_shares_are_filled_when_refreshing_version_without_public_shares() {
+		let gml = generate_key(3, 1);
+		for i in 0..3 {
+			let mut key_share = gml.0.key_storage(i).get(&key_id()).unwrap().unwrap();
+			key_share.versions[0].public_shares.clear();
+			gml.0.key_storage(i).update(key_id(), key_share).unwrap();
+		}
+
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_refresh_at(master, key_id()).unwrap();
+		ml.check_secret_is_preserved(ml.sessions.keys());
+		ml.check_public_shares_are_filled(ml.sessions.keys());
+	}
+
+	#[test]
+	fn refresh_fails_if_subshare_does_not_match_commitments() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let mut ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(ml.admin_key_pair.secret(), &key_id()).unwrap();
+		ml.sessions[&master].initialize(None, key_id(), signature).unwrap();
+
+		// node 1 sends subshare that isn't the value of its committed polynom
+		let malicious_node = ml.ml.node(1);
+		let mut result = Ok(());
+		while let Some((from, to, mut message)) = ml.take_message() {
+			if let Message::ShareRefresh(ShareRefreshMessage::ShareRefreshKeysDissemination(ref mut message)) = message {
+				if from == malicious_node {
+					message.secret_subshare = math::generate_random_scalar().unwrap().into();
+				}
+			}
+
+			result = ml.process_message((from, to, message));
+			if result.is_err() {
+				break;
+			}
+		}
+		assert_eq!(result, Err(Error::InvalidMessage));
+	}
+}
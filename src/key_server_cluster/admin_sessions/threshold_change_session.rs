@@ -0,0 +1,773 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::sync::Arc;
+use ethereum_types::H256;
+use crypto::publickey::{Public, Signature, recover};
+use futures::Oneshot;
+use parking_lot::Mutex;
+use tiny_keccak::Keccak;
+use key_server_cluster::{Error, NodeId, SessionId, DocumentKeyShare, DocumentKeyShareVersion, KeyStorage};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
+use key_server_cluster::message::{Message, ThresholdChangeMessage, InitializeThresholdChangeSession,
+	ConfirmThresholdChangeInitialization, ThresholdChangeKeysDissemination, ConfirmThresholdChange, CommitThresholdChange,
+	ThresholdChangeError, ServersSetChangeMessage};
+use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+use key_server_cluster::admin_sessions::resharing::{Resharing, AbsoluteTerm, compute_public_shares_footprint};
+
+/// Threshold change session.
+/// Re-shares existing key under the new threshold without changing joint public (and secret) key.
+/// Brief overview:
+/// 1) initialization: master node (which has received request for threshold change) initializes the session on all version holders
+/// 2) every version holder generates random polynom of new degree with its secret share as absolute term && sends its
+/// value to every other version holder, along with publics of polynom coefficients. Public of the absolute term is the
+/// public share of the holder, so every value is verified against the commitments && holder' public share
+/// 3) every version holder computes new secret share by interpolating received values, computes public shares of all
+/// holders from commitments && reports their footprint to master node
+/// 4) when all version holders have computed the same public shares, master node asks them to replace obsolete key share
+/// Since the threshold is common for all versions of the key, new key share only contains the new version and it isn't
+/// saved until the commit. So the key stays usable (with the old threshold) if the session fails before commit.
+pub struct SessionImpl {
+	/// Session core.
+	core: SessionCore,
+	/// Session data.
+	data: Mutex<SessionData>,
+}
+
+/// Immutable session data.
+struct SessionCore {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// Original key share.
+	pub key_share: Option<DocumentKeyShare>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	pub cluster: Arc<dyn Cluster>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Public,
+	/// Session completion signal.
+	pub completed: CompletionSignal<()>,
+}
+
+/// Mutable session data.
+struct SessionData {
+	/// Session state.
+	pub state: SessionState,
+	/// Key version to re-share.
+	pub version: Option<H256>,
+	/// Hash of the new key version.
+	pub new_version: Option<H256>,
+	/// New key threshold.
+	pub new_threshold: Option<usize>,
+	/// Version holders data.
+	pub nodes: BTreeMap<NodeId, NodeData>,
+	/// Re-sharing of the key version (filled on initialization).
+	pub resharing: Option<Resharing>,
+	/// Key share with the new threshold, waiting for commit.
+	pub new_key_share: Option<DocumentKeyShare>,
+	/// Threshold change result.
+	pub result: Option<Result<(), Error>>,
+}
+
+/// Version holder data.
+struct NodeData {
+	/// Flag marking that node has confirmed session initialization.
+	pub initialization_confirmed: bool,
+	/// Footprint of public shares of the new key version, computed by the node.
+	pub public_shares_footprint: Option<H256>,
+}
+
+/// Session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for every version holder to confirm initialization.
+	WaitingForInitializationConfirm,
+	/// Waiting for secret subshares from every version holder.
+	WaitingForKeysDissemination,
+	/// Master node waits for every version holder to compute new key share.
+	WaitingForChangeConfirm,
+	/// Slave node waits for the master node to commit new key share.
+	WaitingForCommit,
+	/// Session is completed.
+	Finished,
+	/// Session has failed.
+	Failed,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Cluster.
+	pub cluster: Arc<dyn Cluster>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Public,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+impl SessionImpl {
+	/// Create new threshold change session.
+	pub fn new(params: SessionParams) -> Result<(Self, Oneshot<Result<(), Error>>), Error> {
+		let key_share = params.key_storage.get(&params.meta.id)?;
+		let (completed, oneshot) = CompletionSignal::new();
+		Ok((SessionImpl {
+			core: SessionCore {
+				meta: params.meta,
+				nonce: params.nonce,
+				key_share: key_share,
+				cluster: params.cluster,
+				key_storage: params.key_storage,
+				admin_public: params.admin_public,
+				completed,
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				version: None,
+				new_version: None,
+				new_threshold: None,
+				nodes: BTreeMap::new(),
+				resharing: None,
+				new_key_share: None,
+				result: None,
+			}),
+		}, oneshot))
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Initialize threshold change session on master node.
+	/// `admin_signature` is `threshold_change_hash(key_id, new_threshold)`, signed with administrator key.
+	pub fn initialize(&self, version: Option<H256>, new_threshold: usize, admin_signature: Signature) -> Result<(), Error> {
+		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the request is authorized by administrator
+		Self::check_admin_signature(&self.core, new_threshold, &admin_signature)?;
+
+		// re-share the latest version of the key by default
+		let key_share = self.core.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let version = match version {
+			Some(version) => version,
+			None => key_share.versions.iter().last().map(|v| v.hash.clone()).ok_or(Error::ServerKeyIsNotFound)?,
+		};
+		Self::fill_nodes(&self.core, &mut *data, &version, H256::random(), new_threshold)?;
+
+		// update state
+		data.state = SessionState::WaitingForInitializationConfirm;
+		data.nodes.get_mut(&self.core.meta.self_node_id)
+			.expect("fill_nodes checks that this node is version holder; qed")
+			.initialization_confirmed = true;
+
+		// start initialization
+		let message = InitializeThresholdChangeSession {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+			version: version.into(),
+			new_version: data.new_version.clone().expect("filled by fill_nodes; qed").into(),
+			new_threshold: new_threshold,
+			admin_signature: admin_signature.into(),
+		};
+		for node in data.nodes.keys().filter(|n| **n != self.core.meta.self_node_id) {
+			self.core.cluster.send(node, Message::ThresholdChange(ThresholdChangeMessage::InitializeThresholdChangeSession(message.clone())))?;
+		}
+
+		// if this node is the only version holder => proceed
+		Self::on_initialization_confirmed(&self.core, &mut *data)
+	}
+
+	/// Process single message.
+	pub fn process_message(&self, sender: &NodeId, message: &ThresholdChangeMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&ThresholdChangeMessage::InitializeThresholdChangeSession(ref message) =>
+				self.on_initialize_session(sender, message),
+			&ThresholdChangeMessage::ConfirmThresholdChangeInitialization(ref message) =>
+				self.on_confirm_initialization(sender, message),
+			&ThresholdChangeMessage::ThresholdChangeKeysDissemination(ref message) =>
+				self.on_keys_dissemination(sender, message),
+			&ThresholdChangeMessage::ConfirmThresholdChange(ref message) =>
+				self.on_confirm_change(sender, message),
+			&ThresholdChangeMessage::CommitThresholdChange(ref message) =>
+				self.on_commit_change(sender, message),
+			&ThresholdChangeMessage::ThresholdChangeError(ref message) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+		}
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: &NodeId, message: &InitializeThresholdChangeSession) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the request is authorized by administrator
+		Self::check_admin_signature(&self.core, message.new_threshold, &message.admin_signature.clone().into())?;
+		Self::fill_nodes(&self.core, &mut *data, &message.version.clone().into(), message.new_version.clone().into(),
+			message.new_threshold)?;
+
+		// update state
+		data.state = SessionState::WaitingForKeysDissemination;
+
+		// send confirmation back to master node
+		self.core.cluster.send(sender, Message::ThresholdChange(ThresholdChangeMessage::ConfirmThresholdChangeInitialization(ConfirmThresholdChangeInitialization {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+		})))
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: &NodeId, message: &ConfirmThresholdChangeInitialization) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// mark node as confirmed
+		{
+			let node = data.nodes.get_mut(sender).ok_or(Error::InvalidMessage)?;
+			if node.initialization_confirmed {
+				return Err(Error::InvalidMessage);
+			}
+			node.initialization_confirmed = true;
+		}
+
+		Self::on_initialization_confirmed(&self.core, &mut *data)
+	}
+
+	/// When keys dissemination message is received.
+	pub fn on_keys_dissemination(&self, sender: &NodeId, message: &ThresholdChangeKeysDissemination) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForKeysDissemination {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// every node sends exactly one subshare, which is checked against publics of its polynom coefficients && its
+		// public share
+		data.resharing.as_mut().expect("resharing is filled on initialization; qed").on_subshare(
+			sender,
+			message.public_share.clone().into(),
+			None,
+			message.commitments.iter().cloned().map(Into::into).collect(),
+			message.secret_subshare.clone().into(),
+		)?;
+
+		// if we have received subshare from master node, it means that we should start dissemination
+		if sender == &self.core.meta.master_node_id {
+			Self::disseminate_keys(&self.core, &mut *data)?;
+		}
+
+		Self::try_change_key_share(&self.core, &mut *data)
+	}
+
+	/// When change confirmation message is received.
+	pub fn on_confirm_change(&self, sender: &NodeId, message: &ConfirmThresholdChange) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// slave nodes could compute new share before master has received all subshares
+		if self.core.meta.self_node_id != self.core.meta.master_node_id
+			|| (data.state != SessionState::WaitingForKeysDissemination && data.state != SessionState::WaitingForChangeConfirm) {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// remember public shares footprint, computed by the node
+		{
+			let node = data.nodes.get_mut(sender).ok_or(Error::InvalidMessage)?;
+			if node.public_shares_footprint.is_some() {
+				return Err(Error::InvalidMessage);
+			}
+			node.public_shares_footprint = Some(message.public_shares_footprint.clone().into());
+		}
+
+		Self::try_commit(&self.core, &mut *data)
+	}
+
+	/// When change commit message is received.
+	pub fn on_commit_change(&self, sender: &NodeId, message: &CommitThresholdChange) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForCommit {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		Self::complete_session(&self.core, &mut *data)
+	}
+
+	/// Check that new threshold is signed by administrator.
+	fn check_admin_signature(core: &SessionCore, new_threshold: usize, admin_signature: &Signature) -> Result<(), Error> {
+		if recover(admin_signature, &threshold_change_hash(&core.meta.id, new_threshold))? != core.admin_public {
+			return Err(Error::AccessDenied);
+		}
+
+		Ok(())
+	}
+
+	/// Fill version holders data.
+	fn fill_nodes(core: &SessionCore, data: &mut SessionData, version: &H256, new_version: H256, new_threshold: usize) -> Result<(), Error> {
+		let key_share = core.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+		let key_version = key_share.version(version)?;
+
+		// the new version must differ from every known version
+		if key_share.versions.iter().any(|v| v.hash == new_version) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// every version holder gets share of the new version => there must be enough holders for the new threshold
+		if new_threshold + 1 > key_version.id_numbers.len() {
+			return Err(Error::NotEnoughNodesForThreshold);
+		}
+
+		// every version holder must participate, or its share would become obsolete
+		let connected_nodes = core.cluster.nodes();
+		if key_version.id_numbers.keys().any(|n| !connected_nodes.contains(n)) {
+			return Err(Error::ConsensusUnreachable);
+		}
+		if !key_version.id_numbers.contains_key(&core.meta.master_node_id) {
+			return Err(Error::ConsensusUnreachable);
+		}
+
+		data.version = Some(version.clone());
+		data.new_version = Some(new_version);
+		data.new_threshold = Some(new_threshold);
+		data.resharing = Some(Resharing::new(core.meta.self_node_id.clone(), AbsoluteTerm::SecretShare, new_threshold,
+			key_share, key_version)?);
+		data.nodes = key_version.id_numbers.keys()
+			.map(|n| (n.clone(), NodeData {
+				initialization_confirmed: false,
+				public_shares_footprint: None,
+			}))
+			.collect();
+
+		Ok(())
+	}
+
+	/// When all version holders have confirmed initialization.
+	fn on_initialization_confirmed(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		if data.nodes.values().any(|n| !n.initialization_confirmed) {
+			return Ok(());
+		}
+
+		data.state = SessionState::WaitingForKeysDissemination;
+		Self::disseminate_keys(core, data)?;
+		Self::try_change_key_share(core, data)
+	}
+
+	/// Generate random polynom of new degree with secret share as absolute term && send its values to all version holders.
+	fn disseminate_keys(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let dealing = data.resharing.as_mut()
+			.expect("disseminate_keys is called after initialization; resharing is filled on initialization; qed")
+			.deal()?;
+
+		for (node, secret_subshare) in dealing.secret_subshares {
+			core.cluster.send(&node, Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeKeysDissemination(ThresholdChangeKeysDissemination {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				secret_subshare: secret_subshare.into(),
+				public_share: dealing.public_share.clone().into(),
+				commitments: dealing.commitments.iter().cloned().map(Into::into).collect(),
+			})))?;
+		}
+
+		Ok(())
+	}
+
+	/// Compute new key share, if subshares from all version holders are received.
+	fn try_change_key_share(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let explanation = "try_change_key_share is called after initialization; version holders have key share; qed";
+		let (secret_share, public_shares) = {
+			let resharing = data.resharing.as_ref().expect(explanation);
+			if !resharing.is_completed() {
+				return Ok(());
+			}
+
+			// new secret share = sum of received subshares, multiplied by Lagrange coefficients of senders
+			// new public share = sum of publics of subshares (computed from commitments), multiplied by the same coefficients
+			(resharing.compute_secret_share()?, resharing.compute_public_shares()?)
+		};
+		let public_shares_footprint = compute_public_shares_footprint(&public_shares)?;
+
+		// threshold is common for all key versions => only the new version is left in the key share
+		let mut new_key_share = core.key_share.clone().expect(explanation);
+		let id_numbers = new_key_share.version(data.version.as_ref().expect(explanation)).expect(explanation).id_numbers.clone();
+		let mut new_key_version = DocumentKeyShareVersion::new(id_numbers, secret_share);
+		new_key_version.hash = data.new_version.clone().expect(explanation);
+		new_key_version.public_shares = public_shares;
+		new_key_share.threshold = data.new_threshold.expect(explanation);
+		new_key_share.versions = vec![new_key_version];
+		data.new_key_share = Some(new_key_share);
+
+		if core.meta.self_node_id != core.meta.master_node_id {
+			data.state = SessionState::WaitingForCommit;
+			return core.cluster.send(&core.meta.master_node_id, Message::ThresholdChange(ThresholdChangeMessage::ConfirmThresholdChange(ConfirmThresholdChange {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				public_shares_footprint: public_shares_footprint.into(),
+			})));
+		}
+
+		data.state = SessionState::WaitingForChangeConfirm;
+		data.nodes.get_mut(&core.meta.self_node_id)
+			.expect("master node is always a version holder; qed")
+			.public_shares_footprint = Some(public_shares_footprint);
+		Self::try_commit(core, data)
+	}
+
+	/// Commit new key share, if all version holders have computed it.
+	fn try_commit(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		if data.state != SessionState::WaitingForChangeConfirm || data.nodes.values().any(|n| n.public_shares_footprint.is_none()) {
+			return Ok(());
+		}
+
+		// all nodes must agree on public shares of the new version
+		let public_shares_footprint = data.nodes[&core.meta.self_node_id].public_shares_footprint.clone();
+		if data.nodes.values().any(|n| n.public_shares_footprint != public_shares_footprint) {
+			return Err(Error::InvalidMessage);
+		}
+
+		for node in data.nodes.keys().filter(|n| **n != core.meta.self_node_id) {
+			core.cluster.send(node, Message::ThresholdChange(ThresholdChangeMessage::CommitThresholdChange(CommitThresholdChange {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+			})))?;
+		}
+
+		Self::complete_session(core, data)
+	}
+
+	/// Replace obsolete key share with the new one && complete session.
+	fn complete_session(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let new_key_share = data.new_key_share.take().expect("complete_session is called after new key share is computed; qed");
+		core.key_storage.update(core.meta.id.clone(), new_key_share)?;
+
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(()));
+		core.completed.send(Ok(()));
+
+		Ok(())
+	}
+}
+
+/// Compute hash of the threshold change request, which must be signed by administrator: keccak256(key_id || new_threshold),
+/// where new threshold is serialized as big-endian u64.
+pub fn threshold_change_hash(key_id: &SessionId, new_threshold: usize) -> H256 {
+	let mut request_keccak = Keccak::new_keccak256();
+	request_keccak.update(key_id.as_bytes());
+	request_keccak.update(&(new_threshold as u64).to_be_bytes());
+
+	let mut request_keccak_value = [0u8; 32];
+	request_keccak.finalize(&mut request_keccak_value);
+
+	request_keccak_value.into()
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = (); // never used directly
+	type SuccessfulResult = ();
+
+	fn type_name() -> &'static str {
+		"threshold change"
+	}
+
+	fn id(&self) -> SessionId {
+		self.core.meta.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_session_timeout(&self) {
+		self.on_session_error(&self.core.meta.self_node_id, Error::NodeDisconnected)
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		self.on_session_error(node, Error::NodeDisconnected)
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in threshold change session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.core.meta.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.core.cluster.broadcast(Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeError(ThresholdChangeError {
+				session: self.core.meta.id.clone().into(),
+				session_nonce: self.core.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		let mut data = self.data.lock();
+
+		warn!(target: "secretstore_net", "{}: threshold change session failed: {} on {}",
+			self.core.meta.self_node_id, error, node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error.clone()));
+		self.core.completed.send(Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::ThresholdChange(ref message) => self.process_message(sender, message),
+			// admin sessions creator reports session creation errors using this message
+			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeError(ref message)) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "Threshold change session {} on {}", self.core.meta.id, self.core.meta.self_node_id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+	use crypto::publickey::{Random, Generator, Public, sign};
+	use blockchain::SigningKeyPair;
+	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage};
+	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
+	use key_server_cluster::message::{Message, ThresholdChangeMessage};
+	use key_server_cluster::servers_set_change_session::tests::{MessageLoop, AdminSessionAdapter, generate_key};
+	use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+	use key_server_cluster::math;
+	use super::{SessionImpl, SessionParams, threshold_change_hash};
+
+	struct Adapter;
+
+	impl AdminSessionAdapter<SessionImpl> for Adapter {
+		const SIGN_NEW_NODES: bool = false;
+
+		fn create(
+			mut meta: ShareChangeSessionMeta,
+			admin_public: Public,
+			_: BTreeSet<NodeId>,
+			ml: &ClusterMessageLoop,
+			idx: usize
+		) -> SessionImpl {
+			meta.self_node_id = *ml.node_key_pair(idx).public();
+			SessionImpl::new(SessionParams {
+				meta: meta,
+				cluster: ml.cluster(idx).view().unwrap(),
+				key_storage: ml.key_storage(idx).clone(),
+				admin_public: admin_public,
+				nonce: 1,
+			}).unwrap().0
+		}
+	}
+
+	impl MessageLoop<SessionImpl> {
+		pub fn run_threshold_change_at(mut self, master: NodeId, new_threshold: usize) -> Result<Self, Error> {
+			let signature = sign(self.admin_key_pair.secret(), &threshold_change_hash(&key_id(), new_threshold)).unwrap();
+			self.sessions[&master].initialize(None, new_threshold, signature)?;
+			self.run();
+			Ok(self)
+		}
+
+		pub fn check_joint_secret(&self, threshold: usize, shares_count: usize) -> bool {
+			let key_shares: Vec<_> = self.sessions.keys().take(shares_count)
+				.map(|n| self.ml.key_storage_of(n).get(&key_id()).unwrap().unwrap())
+				.collect();
+			let id_numbers: Vec<_> = self.sessions.keys().take(shares_count)
+				.zip(key_shares.iter())
+				.map(|(n, ks)| ks.versions[0].id_numbers[n].clone())
+				.collect();
+			let joint_secret = math::compute_joint_secret_from_shares(threshold,
+				&key_shares.iter().map(|ks| &ks.versions[0].secret_share).collect::<Vec<_>>(),
+				&id_numbers.iter().collect::<Vec<_>>()).unwrap();
+			joint_secret == *self.original_key_pair.secret()
+		}
+	}
+
+	fn key_id() -> SessionId {
+		SessionId::from([1u8; 32])
+	}
+
+	#[test]
+	fn threshold_is_increased() {
+		let gml = generate_key(5, 1);
+		let master = gml.0.node(0);
+		let old_key_shares: Vec<_> = (0..5).map(|i| gml.0.key_storage(i).get(&key_id()).unwrap().unwrap()).collect();
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_threshold_change_at(master, 3).unwrap();
+
+		// check that every node has single new version with the new threshold && the same public
+		let new_version = ml.ml.key_storage(0).get(&key_id()).unwrap().unwrap().versions[0].hash.clone();
+		assert!(new_version != ml.original_key_version);
+		for i in 0..5 {
+			let key_share = ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap();
+			assert_eq!(key_share.threshold, 3);
+			assert_eq!(key_share.versions.len(), 1);
+			assert_eq!(key_share.versions[0].hash, new_version);
+			assert_eq!(key_share.versions[0].id_numbers, old_key_shares[i].versions[0].id_numbers);
+			assert_eq!(key_share.public, old_key_shares[i].public);
+		}
+
+		// check that secret could be restored from 4 shares, but not from 2
+		assert!(ml.check_joint_secret(3, 4));
+		assert!(!ml.check_joint_secret(1, 2));
+
+		// check that public shares of the new version are filled
+		ml.check_public_shares_are_filled(ml.sessions.keys());
+	}
+
+	#[test]
+	fn threshold_is_decreased() {
+		let gml = generate_key(5, 3);
+		let master = gml.0.node(2);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_threshold_change_at(master, 1).unwrap();
+
+		for i in 0..5 {
+			assert_eq!(ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap().threshold, 1);
+		}
+		ml.check_secret_is_preserved(ml.sessions.keys());
+		ml.check_public_shares_are_filled(ml.sessions.keys());
+	}
+
+	#[test]
+	fn threshold_change_fails_if_other_secret_is_reshared() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let mut ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(ml.admin_key_pair.secret(), &threshold_change_hash(&key_id(), 2)).unwrap();
+		ml.sessions[&master].initialize(None, 2, signature).unwrap();
+
+		// node 1 re-shares shifted secret share, keeping commitments of the original polynom
+		let malicious_node = ml.ml.node(1);
+		let shift = math::generate_random_scalar().unwrap();
+		let mut result = Ok(());
+		while let Some((from, to, mut message)) = ml.take_message() {
+			if let Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeKeysDissemination(ref mut message)) = message {
+				if from == malicious_node {
+					message.secret_subshare = math::compute_secret_sum(vec![message.secret_subshare.clone().into(), shift.clone()].iter())
+						.unwrap().into();
+				}
+			}
+
+			result = ml.process_message((from, to, message));
+			if result.is_err() {
+				break;
+			}
+		}
+		assert_eq!(result, Err(Error::InvalidMessage));
+
+		// key share with the old threshold is left untouched
+		for i in 0..3 {
+			assert_eq!(ml.ml.key_storage(i).get(&key_id()).unwrap().unwrap().threshold, 1);
+		}
+	}
+
+	#[test]
+	fn threshold_change_fails_if_there_are_not_enough_holders() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_threshold_change_at(master, 3).unwrap_err(), Error::NotEnoughNodesForThreshold);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_signed_by_non_admin() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(Random.generate().secret(), &threshold_change_hash(&key_id(), 2)).unwrap();
+		assert_eq!(ml.sessions[&master].initialize(None, 2, signature).unwrap_err(), Error::AccessDenied);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_other_threshold_is_signed() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let signature = sign(ml.admin_key_pair.secret(), &threshold_change_hash(&key_id(), 1)).unwrap();
+		assert_eq!(ml.sessions[&master].initialize(None, 2, signature).unwrap_err(), Error::AccessDenied);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_version_holder_is_isolated() {
+		let gml = generate_key(3, 1);
+		let master = gml.0.node(0);
+		let isolate = ::std::iter::once(gml.0.node(1)).collect();
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, Some(isolate))
+			.run_threshold_change_at(master, 2).unwrap_err(), Error::ConsensusUnreachable);
+	}
+}
@@ -19,9 +19,9 @@ use std::sync::Arc;
 use futures::Oneshot;
 use parking_lot::Mutex;
 use ethereum_types::{Address, H256};
-use crypto::publickey::Secret;
+use crypto::publickey::{Public, Secret};
 use key_server_cluster::{Error, AclStorage, DocumentKeyShare, NodeId, SessionId, Requester,
-	EncryptedDocumentKeyShadow, SessionMeta};
+	EncryptedDocumentKeyShadow, SessionMeta, KeyDerivationPath, DocumentKeySlotId};
 use key_server_cluster::cluster::Cluster;
 use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession, CompletionSignal};
 use key_server_cluster::message::{Message, DecryptionMessage, DecryptionConsensusMessage, RequestPartialDecryption,
@@ -53,8 +53,14 @@ struct SessionCore {
 	pub meta: SessionMeta,
 	/// Decryption session access key.
 	pub access_key: Secret,
-	/// Key share.
+	/// Key share (of the child key, if derivation path is not empty).
 	pub key_share: Option<DocumentKeyShare>,
+	/// Derivation path of the child key, used to decrypt document key.
+	pub derivation_path: KeyDerivationPath,
+	/// Ephemeral public of ECIES ciphertext, if ECDH secret is computed instead of decrypting document key.
+	pub ecies_ephemeral_public: Option<Public>,
+	/// Slot of the document key to decrypt. None if default document key is decrypted.
+	pub document_key_slot: Option<DocumentKeySlotId>,
 	/// Cluster which allows this node to send messages to other nodes in the cluster.
 	pub cluster: Arc<dyn Cluster>,
 	/// Session-level nonce.
@@ -95,8 +101,14 @@ pub struct SessionParams {
 	pub meta: SessionMeta,
 	/// Session access key.
 	pub access_key: Secret,
-	/// Key share.
+	/// Key share (of the child key, if derivation path is not empty).
 	pub key_share: Option<DocumentKeyShare>,
+	/// Derivation path of the child key, used to decrypt document key.
+	pub derivation_path: KeyDerivationPath,
+	/// Ephemeral public of ECIES ciphertext, if ECDH secret is computed instead of decrypting document key.
+	pub ecies_ephemeral_public: Option<Public>,
+	/// Slot of the document key to decrypt. None if default document key is decrypted.
+	pub document_key_slot: Option<DocumentKeySlotId>,
 	/// ACL storage.
 	pub acl_storage: Arc<dyn AclStorage>,
 	/// Cluster.
@@ -117,6 +129,12 @@ struct DecryptionConsensusTransport {
 	origin: Option<Address>,
 	/// Selected key version (on master node).
 	version: Option<H256>,
+	/// Derivation path of the child key.
+	derivation_path: KeyDerivationPath,
+	/// Ephemeral public of ECIES ciphertext (if any).
+	ecies_ephemeral_public: Option<Public>,
+	/// Slot of the document key (if any).
+	document_key_slot: Option<DocumentKeySlotId>,
 	/// Cluster.
 	cluster: Arc<dyn Cluster>,
 }
@@ -167,6 +185,9 @@ impl SessionImpl {
 			nonce: params.nonce,
 			origin: None,
 			version: None,
+			derivation_path: params.derivation_path.clone(),
+			ecies_ephemeral_public: params.ecies_ephemeral_public.clone(),
+			document_key_slot: params.document_key_slot.clone(),
 			cluster: params.cluster.clone(),
 		};
 		let consensus_session = ConsensusSession::new(ConsensusSessionParams {
@@ -184,6 +205,9 @@ impl SessionImpl {
 				meta: params.meta,
 				access_key: params.access_key,
 				key_share: params.key_share,
+				derivation_path: params.derivation_path,
+				ecies_ephemeral_public: params.ecies_ephemeral_public,
+				document_key_slot: params.document_key_slot,
 				cluster: params.cluster,
 				nonce: params.nonce,
 				completed,
@@ -280,6 +304,9 @@ impl SessionImpl {
 				.expect("signature is passed to master node on creation; session can be delegated from master node only; qed")
 				.clone().into(),
 			version: version.into(),
+			derivation_path: self.core.derivation_path.clone(),
+			ecies_ephemeral_public: self.core.ecies_ephemeral_public.clone().map(Into::into),
+			document_key_slot: self.core.document_key_slot.clone().map(Into::into),
 			is_shadow_decryption: is_shadow_decryption,
 			is_broadcast_session: is_broadcast_session,
 		})))?;
@@ -481,11 +508,21 @@ impl SessionImpl {
 		let mut data = self.data.lock();
 		let is_master_node = self.core.meta.self_node_id == self.core.meta.master_node_id;
 		let result = if is_master_node {
-			data.consensus_session.on_job_response(sender, PartialDecryptionResponse {
+			let job_response_result = data.consensus_session.on_job_response(sender, PartialDecryptionResponse {
 				request_id: message.request_id.clone().into(),
 				shadow_point: message.shadow_point.clone().into(),
 				decrypt_shadow: message.decrypt_shadow.clone(),
-			})?;
+				decryption_proof: message.decryption_proof.clone().map(|(c, r)| (c.into(), r.into())),
+				shadow_decryption_proof: message.shadow_decryption_proof.clone().map(Into::into),
+			});
+			match job_response_result {
+				// node has sent invalid partial decryption => restart session without this node
+				Err(Error::InvalidPartialResponse(node)) => {
+					drop(data);
+					return self.process_node_error(Some(&node), Error::InvalidPartialResponse(node.clone()));
+				},
+				job_response_result => job_response_result?,
+			}
 
 			if data.consensus_session.state() != ConsensusSessionState::Finished &&
 				data.consensus_session.state() != ConsensusSessionState::Failed {
@@ -511,6 +548,8 @@ impl SessionImpl {
 						request_id: message.request_id.clone().into(),
 						shadow_point: message.shadow_point.clone().into(),
 						decrypt_shadow: message.decrypt_shadow.clone(),
+						decryption_proof: message.decryption_proof.clone().map(|(c, r)| (c.into(), r.into())),
+						shadow_decryption_proof: message.shadow_decryption_proof.clone().map(Into::into),
 					})?;
 
 					if broadcast_job_session.state() != JobSessionState::Finished &&
@@ -608,9 +647,10 @@ impl SessionImpl {
 		let requester = data.consensus_session.consensus_job().executor().requester().ok_or(Error::InvalidStateForRequest)?.clone();
 		let requester_public = requester.public(&core.meta.id).map_err(Error::InsufficientRequesterData)?;
 		let consensus_group = data.consensus_session.select_consensus_group()?.clone();
-		let decryption_job = DecryptionJob::new_on_master(core.meta.self_node_id.clone(),
+		let mut decryption_job = DecryptionJob::new_on_master(core.meta.self_node_id.clone(),
 			core.access_key.clone(), requester_public.clone(), key_share.clone(), key_version,
 			is_shadow_decryption, is_broadcast_session)?;
+		decryption_job.set_nodes(consensus_group.clone());
 		let decryption_request_id = decryption_job.request_id().clone()
 			.expect("DecryptionJob always have request_id when created on master; it is created using new_on_master above; qed");
 		let decryption_transport = core.decryption_transport(false);
@@ -633,6 +673,7 @@ impl SessionImpl {
 	fn create_broadcast_decryption_job(core: &SessionCore, data: &mut SessionData, mut consensus_group: BTreeSet<NodeId>, mut job: DecryptionJob, request_id: Secret, self_response: Option<PartialDecryptionResponse>) -> Result<(), Error> {
 		consensus_group.insert(core.meta.self_node_id.clone());
 		job.set_request_id(request_id.clone().into());
+		job.set_nodes(consensus_group.clone());
 
 		let transport = core.decryption_transport(true);
 		let mut job_session = JobSession::new(SessionMeta {
@@ -678,7 +719,7 @@ impl SessionImpl {
 
 impl ClusterSession for SessionImpl {
 	type Id = SessionIdWithSubSession;
-	type CreationData = Requester;
+	type CreationData = (Requester, KeyDerivationPath, Option<Public>, Option<DocumentKeySlotId>);
 	type SuccessfulResult = EncryptedDocumentKeyShadow;
 
 	fn type_name() -> &'static str {
@@ -765,6 +806,9 @@ impl JobTransport for DecryptionConsensusTransport {
 			message: ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
 				requester: request.into(),
 				version: version.clone().into(),
+				derivation_path: self.derivation_path.clone(),
+				ecies_ephemeral_public: self.ecies_ephemeral_public.clone().map(Into::into),
+				document_key_slot: self.document_key_slot.clone().map(Into::into),
 			})
 		})))
 	}
@@ -811,6 +855,8 @@ impl JobTransport for DecryptionJobTransport {
 				request_id: response.request_id.into(),
 				shadow_point: response.shadow_point.into(),
 				decrypt_shadow: response.decrypt_shadow,
+				decryption_proof: response.decryption_proof.map(|(c, r)| (c.into(), r.into())),
+				shadow_decryption_proof: response.shadow_decryption_proof.map(Into::into),
 			})))?;
 		}
 
@@ -835,6 +881,9 @@ pub fn create_default_decryption_session() -> Arc<SessionImpl> {
 		},
 		access_key: Secret::zero(),
 		key_share: Default::default(),
+		derivation_path: Vec::new(),
+		ecies_ephemeral_public: None,
+		document_key_slot: None,
 		acl_storage: Arc::new(DummyAclStorage::default()),
 		cluster: Arc::new(DummyCluster::new(Default::default())),
 		nonce: 0,
@@ -847,7 +896,7 @@ mod tests {
 	use std::collections::{BTreeMap, VecDeque};
 	use acl_storage::DummyAclStorage;
 	use crypto::publickey::{KeyPair, Random, Generator, Public, Secret, public_to_address};
-	use key_server_cluster::{NodeId, DocumentKeyShare, DocumentKeyShareVersion, SessionId, Requester,
+	use key_server_cluster::{NodeId, DocumentKeyShare, DocumentKeyShareVersion, KeyCurve, SessionId, Requester,
 		Error, EncryptedDocumentKeyShadow, SessionMeta};
 	use key_server_cluster::cluster::tests::DummyCluster;
 	use key_server_cluster::cluster_sessions::ClusterSession;
@@ -861,6 +910,10 @@ mod tests {
 	const SECRET_PLAIN: &'static str = "d2b57ae7619e070af0af6bc8c703c0cd27814c54d5d6a999cacac0da34ede279ca0d9216e85991029e54e2f0c92ee0bd30237725fa765cbdbfc4529489864c5f";
 	const DUMMY_SESSION_ID: [u8; 32]  = [1u8; 32];
 	fn prepare_decryption_sessions() -> (KeyPair, Vec<Arc<DummyCluster>>, Vec<Arc<DummyAclStorage>>, Vec<SessionImpl>) {
+		prepare_decryption_sessions_with_public_shares(true)
+	}
+
+	fn prepare_decryption_sessions_with_public_shares(with_public_shares: bool) -> (KeyPair, Vec<Arc<DummyCluster>>, Vec<Arc<DummyAclStorage>>, Vec<SessionImpl>) {
 		// prepare encrypted data + cluster configuration for scheme 4-of-5
 		let session_id = SessionId::from(DUMMY_SESSION_ID);
 		let access_key = Random.generate().secret().clone();
@@ -885,16 +938,26 @@ mod tests {
 		];
 		let common_point: Public = H512::from_str("6962be696e1bcbba8e64cc7fddf140f854835354b5804f3bb95ae5a2799130371b589a131bd39699ac7174ccb35fc4342dab05331202209582fc8f3a40916ab0").unwrap();
 		let encrypted_point: Public = H512::from_str("b07031982bde9890e12eff154765f03c56c3ab646ad47431db5dd2d742a9297679c4c65b998557f8008469afd0c43d40b6c5f6c6a1c7354875da4115237ed87a").unwrap();
+		let public_shares: BTreeMap<NodeId, Public> = id_numbers.iter().zip(secret_shares.iter())
+			.filter(|_| with_public_shares)
+			.map(|((node, _), secret_share)| (node.clone(), math::compute_public_share(secret_share).unwrap()))
+			.collect();
 		let encrypted_datas: Vec<_> = (0..5).map(|i| DocumentKeyShare {
 			author: Default::default(),
 			threshold: 3,
 			public: Default::default(),
 			common_point: Some(common_point.clone()),
 			encrypted_point: Some(encrypted_point.clone()),
+			curve: KeyCurve::Secp256k1,
+			ecdsa_scheme: Default::default(),
+			exported: false,
+			document_key_slots: Default::default(),
+			expiration: None,
 			versions: vec![DocumentKeyShareVersion {
 				hash: Default::default(),
 				id_numbers: id_numbers.clone().into_iter().collect(),
 				secret_share: secret_shares[i].clone(),
+				public_shares: public_shares.clone(),
 			}],
 		}).collect();
 		let acl_storages: Vec<_> = (0..5).map(|_| Arc::new(DummyAclStorage::default())).collect();
@@ -918,6 +981,9 @@ mod tests {
 			},
 			access_key: access_key.clone(),
 			key_share: Some(encrypted_datas[i].clone()),
+			derivation_path: Vec::new(),
+			ecies_ephemeral_public: None,
+			document_key_slot: None,
 			acl_storage: acl_storages[i].clone(),
 			cluster: clusters[i].clone(),
 			nonce: 0,
@@ -990,12 +1056,21 @@ mod tests {
 				public: Default::default(),
 				common_point: Some(Random.generate().public().clone()),
 				encrypted_point: Some(Random.generate().public().clone()),
+				curve: KeyCurve::Secp256k1,
+				ecdsa_scheme: Default::default(),
+				exported: false,
+				document_key_slots: Default::default(),
+				expiration: None,
 				versions: vec![DocumentKeyShareVersion {
 					hash: Default::default(),
 					id_numbers: nodes,
 					secret_share: Random.generate().secret().clone(),
+					public_shares: Default::default(),
 				}],
 			}),
+			derivation_path: Vec::new(),
+			ecies_ephemeral_public: None,
+			document_key_slot: None,
 			acl_storage: Arc::new(DummyAclStorage::default()),
 			cluster: Arc::new(DummyCluster::new(self_node_id.clone())),
 			nonce: 0,
@@ -1020,6 +1095,9 @@ mod tests {
 			},
 			access_key: Random.generate().secret().clone(),
 			key_share: None,
+			derivation_path: Vec::new(),
+			ecies_ephemeral_public: None,
+			document_key_slot: None,
 			acl_storage: Arc::new(DummyAclStorage::default()),
 			cluster: Arc::new(DummyCluster::new(self_node_id.clone())),
 			nonce: 0,
@@ -1051,12 +1129,21 @@ mod tests {
 				public: Default::default(),
 				common_point: Some(Random.generate().public().clone()),
 				encrypted_point: Some(Random.generate().public().clone()),
+				curve: KeyCurve::Secp256k1,
+				ecdsa_scheme: Default::default(),
+				exported: false,
+				document_key_slots: Default::default(),
+				expiration: None,
 				versions: vec![DocumentKeyShareVersion {
 					hash: Default::default(),
 					id_numbers: nodes,
 					secret_share: Random.generate().secret().clone(),
+					public_shares: Default::default(),
 				}],
 			}),
+			derivation_path: Vec::new(),
+			ecies_ephemeral_public: None,
+			document_key_slot: None,
 			acl_storage: Arc::new(DummyAclStorage::default()),
 			cluster: Arc::new(DummyCluster::new(self_node_id.clone())),
 			nonce: 0,
@@ -1086,6 +1173,9 @@ mod tests {
 					requester: Requester::Signature(crypto::publickey::sign(
 						Random.generate().secret(), &SessionId::from(DUMMY_SESSION_ID)).unwrap()).into(),
 					version: Default::default(),
+					derivation_path: Vec::new(),
+					ecies_ephemeral_public: None,
+					document_key_slot: None,
 				}),
 			}).unwrap_err(), Error::InvalidMessage);
 	}
@@ -1102,6 +1192,9 @@ mod tests {
 					requester: Requester::Signature(crypto::publickey::sign(Random.generate().secret(),
 						&SessionId::from(DUMMY_SESSION_ID)).unwrap()).into(),
 					version: Default::default(),
+					derivation_path: Vec::new(),
+					ecies_ephemeral_public: None,
+					document_key_slot: None,
 				}),
 		}).unwrap(), ());
 		assert_eq!(sessions[1].on_partial_decryption_requested(sessions[2].node(), &message::RequestPartialDecryption {
@@ -1127,6 +1220,9 @@ mod tests {
 					requester: Requester::Signature(crypto::publickey::sign(Random.generate().secret(),
 						&SessionId::from(DUMMY_SESSION_ID)).unwrap()).into(),
 					version: Default::default(),
+					derivation_path: Vec::new(),
+					ecies_ephemeral_public: None,
+					document_key_slot: None,
 				}),
 		}).unwrap(), ());
 		assert_eq!(sessions[1].on_partial_decryption_requested(sessions[0].node(), &message::RequestPartialDecryption {
@@ -1150,6 +1246,8 @@ mod tests {
 			request_id: Random.generate().secret().clone().into(),
 			shadow_point: Random.generate().public().clone().into(),
 			decrypt_shadow: None,
+			decryption_proof: None,
+			shadow_decryption_proof: None,
 		}).unwrap_err(), Error::InvalidStateForRequest);
 	}
 
@@ -1259,6 +1357,35 @@ mod tests {
 		assert!(!sessions[0].data.lock().consensus_session.computation_job().requests().contains(&disconnected));
 	}
 
+	#[test]
+	fn session_restarts_if_node_sends_invalid_partial_decryption() {
+		let (_, clusters, _, sessions) = prepare_decryption_sessions();
+		sessions[0].initialize(Default::default(), Default::default(), false, false).unwrap();
+
+		let mut pd_from = None;
+		let mut pd_msg = None;
+		do_messages_exchange_until(&clusters, &sessions, |from, _, msg| match msg {
+			&Message::Decryption(DecryptionMessage::PartialDecryption(ref msg)) => {
+				pd_from = Some(from.clone());
+				pd_msg = Some(msg.clone());
+				true
+			},
+			_ => false,
+		}).unwrap();
+
+		// node sends random point instead of its shadow point => it is excluded && session is restarted
+		let pd_from = pd_from.unwrap();
+		let mut pd_msg = pd_msg.unwrap();
+		pd_msg.shadow_point = math::generate_random_point().unwrap().into();
+		sessions[0].on_partial_decryption(&pd_from, &pd_msg).unwrap();
+		assert!(sessions[0].state() != ConsensusSessionState::Failed);
+		assert_eq!(sessions[0].data.lock().consensus_session.consensus_job().rejects().get(&pd_from), Some(&false));
+
+		// secret is decrypted by remaining nodes
+		do_messages_exchange(&clusters, &sessions).unwrap();
+		assert_eq!(sessions[0].decrypted_secret().unwrap().unwrap().decrypted_secret, H512::from_str(SECRET_PLAIN).unwrap());
+	}
+
 	#[test]
 	fn session_does_not_fail_if_non_master_node_disconnects_from_non_master_node() {
 		let (_, clusters, _, sessions) = prepare_decryption_sessions();
@@ -1294,6 +1421,18 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn complete_dec_session_when_public_shares_are_unknown() {
+		// key version has been migrated from older storage version => partial decryptions can't be verified
+		let (_, clusters, _, sessions) = prepare_decryption_sessions_with_public_shares(false);
+		sessions[0].initialize(Default::default(), Default::default(), false, false).unwrap();
+
+		do_messages_exchange(&clusters, &sessions).unwrap();
+
+		assert_eq!(sessions.iter().filter(|s| s.state() == ConsensusSessionState::Finished).count(), 5);
+		assert_eq!(sessions[0].decrypted_secret().unwrap().unwrap().decrypted_secret, H512::from_str(SECRET_PLAIN).unwrap());
+	}
+
 	#[test]
 	fn complete_shadow_dec_session() {
 		let (key_pair, clusters, _, sessions) = prepare_decryption_sessions();
@@ -22,7 +22,7 @@ use parking_lot::Mutex;
 use ethereum_types::Address;
 use crypto::publickey::Public;
 use key_server_cluster::{Error, NodeId, SessionId, Requester, KeyStorage,
-	DocumentKeyShare, ServerKeyId};
+	DocumentKeyShare, DocumentKeySlotId, ServerKeyId};
 use key_server_cluster::cluster::Cluster;
 use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
 use key_server_cluster::message::{Message, EncryptionMessage, InitializeEncryptionSession,
@@ -33,8 +33,8 @@ use key_server_cluster::message::{Message, EncryptionMessage, InitializeEncrypti
 /// http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.124.4128&rep=rep1&type=pdf
 /// Brief overview:
 /// 1) initialization: master node (which has received request for storing the secret) initializes the session on all other nodes
-/// 2) master node sends common_point + encrypted_point to all other nodes
-/// 3) common_point + encrypted_point are saved on all nodes
+/// 2) master node sends common_point + encrypted_point (and optional document key slot id) to all other nodes
+/// 3) common_point + encrypted_point are saved on all nodes (either as default document key, or in the given slot)
 /// 4) in case of error, previous values are restored
 pub struct SessionImpl {
 	/// Unique session id.
@@ -109,8 +109,6 @@ pub enum SessionState {
 impl SessionImpl {
 	/// Create new encryption session.
 	pub fn new(params: SessionParams) -> Result<(Self, Oneshot<Result<(), Error>>), Error> {
-		check_encrypted_data(params.encrypted_data.as_ref())?;
-
 		let (completed, oneshot) = CompletionSignal::new();
 		Ok((SessionImpl {
 			id: params.id,
@@ -134,7 +132,7 @@ impl SessionImpl {
 	}
 
 	/// Start new session initialization. This must be called on master node.
-	pub fn initialize(&self, requester: Requester, common_point: Public, encrypted_point: Public) -> Result<(), Error> {
+	pub fn initialize(&self, requester: Requester, document_key_slot: Option<DocumentKeySlotId>, common_point: Public, encrypted_point: Public) -> Result<(), Error> {
 		let mut data = self.data.lock();
 
 		// check state
@@ -142,6 +140,9 @@ impl SessionImpl {
 			return Err(Error::InvalidStateForRequest);
 		}
 
+		// check that the document key (slot) is still empty
+		check_encrypted_data(self.encrypted_data.as_ref(), document_key_slot.as_ref())?;
+
 		// update state
 		data.state = SessionState::WaitingForInitializationConfirm;
 		data.nodes.extend(self.cluster.nodes().into_iter().map(|n| (n, NodeData {
@@ -155,7 +156,7 @@ impl SessionImpl {
 		if let Some(encrypted_data) = self.encrypted_data.clone() {
 			let requester_address = requester.address(&self.id).map_err(Error::InsufficientRequesterData)?;
 			update_encrypted_data(&self.key_storage, self.id.clone(),
-				encrypted_data, requester_address, common_point.clone(), encrypted_point.clone())?;
+				encrypted_data, requester_address, document_key_slot.clone(), common_point.clone(), encrypted_point.clone())?;
 		}
 
 		// start initialization
@@ -166,6 +167,7 @@ impl SessionImpl {
 				requester: requester.into(),
 				common_point: common_point.into(),
 				encrypted_point: encrypted_point.into(),
+				document_key_slot: document_key_slot.map(Into::into),
 			})))
 		} else {
 			data.state = SessionState::Finished;
@@ -188,12 +190,16 @@ impl SessionImpl {
 			return Err(Error::InvalidStateForRequest);
 		}
 
+		// check that the document key (slot) is still empty
+		let document_key_slot = message.document_key_slot.clone().map(Into::into);
+		check_encrypted_data(self.encrypted_data.as_ref(), document_key_slot.as_ref())?;
+
 		// check that the requester is the author of the encrypted data
 		if let Some(encrypted_data) = self.encrypted_data.clone() {
 			let requester: Requester = message.requester.clone().into();
 			let requester_address = requester.address(&self.id).map_err(Error::InsufficientRequesterData)?;
-			update_encrypted_data(&self.key_storage, self.id.clone(),
-				encrypted_data, requester_address, message.common_point.clone().into(), message.encrypted_point.clone().into())?;
+			update_encrypted_data(&self.key_storage, self.id.clone(), encrypted_data, requester_address,
+				document_key_slot, message.common_point.clone().into(), message.encrypted_point.clone().into())?;
 		}
 
 		// update state
@@ -318,11 +324,15 @@ impl Debug for SessionImpl {
 	}
 }
 
-/// Check that common_point and encrypted point are not yet set in key share.
-pub fn check_encrypted_data(key_share: Option<&DocumentKeyShare>) -> Result<(), Error> {
+/// Check that common_point and encrypted point are not yet set in key share (or in its document key slot).
+pub fn check_encrypted_data(key_share: Option<&DocumentKeyShare>, document_key_slot: Option<&DocumentKeySlotId>) -> Result<(), Error> {
 	if let Some(key_share) = key_share {
 		// check that common_point and encrypted_point are still not set yet
-		if key_share.common_point.is_some() || key_share.encrypted_point.is_some() {
+		let is_stored = match document_key_slot {
+			None => key_share.common_point.is_some() || key_share.encrypted_point.is_some(),
+			Some(document_key_slot) => key_share.document_key_slots.contains_key(document_key_slot),
+		};
+		if is_stored {
 			return Err(Error::DocumentKeyAlreadyStored);
 		}
 	}
@@ -331,14 +341,13 @@ pub fn check_encrypted_data(key_share: Option<&DocumentKeyShare>) -> Result<(),
 }
 
 /// Update key share with encrypted document key.
-pub fn update_encrypted_data(key_storage: &Arc<dyn KeyStorage>, key_id: ServerKeyId, mut key_share: DocumentKeyShare, author: Address, common_point: Public, encrypted_point: Public) -> Result<(), Error> {
+pub fn update_encrypted_data(key_storage: &Arc<dyn KeyStorage>, key_id: ServerKeyId, mut key_share: DocumentKeyShare, author: Address, document_key_slot: Option<DocumentKeySlotId>, common_point: Public, encrypted_point: Public) -> Result<(), Error> {
 	// author must be the same
 	if key_share.author != author {
 		return Err(Error::AccessDenied);
 	}
 
 	// save encryption data
-	key_share.common_point = Some(common_point);
-	key_share.encrypted_point = Some(encrypted_point);
+	key_share.set_document_key(document_key_slot, common_point, encrypted_point);
 	key_storage.update(key_id, key_share)
 }
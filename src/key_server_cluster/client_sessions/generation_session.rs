@@ -21,13 +21,15 @@ use futures::Oneshot;
 use parking_lot::Mutex;
 use ethereum_types::{H256, Address};
 use crypto::publickey::{Public, Secret};
-use key_server_cluster::{Error, NodeId, SessionId, KeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
+use key_server_cluster::{Error, NodeId, SessionId, KeyStorage, DocumentKeyShare, DocumentKeyShareVersion, KeyCurve,
+	EcdsaSigningScheme, KeyExpiration};
 use key_server_cluster::math;
 use key_server_cluster::cluster::Cluster;
 use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
 use key_server_cluster::random_point_generation_session::{SessionImpl as RandomPointGenerationSession, SessionTransport as RandomPointGenerationSessionTransport};
 use key_server_cluster::message::{Message, GenerationMessage, InitializeSession, ConfirmInitialization,
-	DerivedPointGeneration, RandomPointGenerationMessage, KeysDissemination, PublicKeyShare, SessionError, SessionCompleted, JointPublicKey};
+	DerivedPointGeneration, RandomPointGenerationMessage, KeysDissemination, PublicKeyShare, KeysComplaintResponse, SessionError,
+	SessionCompleted, JointPublicKey};
 
 /// Distributed key generation session.
 /// Based on "ECDKG: A Distributed Key Generation Protocol Based on Elliptic Curve Discrete Logarithm" paper:
@@ -36,7 +38,10 @@ use key_server_cluster::message::{Message, GenerationMessage, InitializeSession,
 /// 1) initialization: master node (which has received request for generating joint public + secret) initializes the session on all other nodes
 /// 2) key dissemination (KD): all nodes are generating secret + public values and send these to appropriate nodes
 /// 3) key verification (KV): all nodes are checking values, received for other nodes
-/// 4) key generation phase (KG): nodes are exchanging with information, enough to generate joint public key
+/// 4) key generation phase (KG): nodes are exchanging with information, enough to generate joint public key.
+/// Nodes are also broadcasting complaints against nodes, which have sent them invalid values. Accused node must
+/// reveal disputed values. If revealed values are also invalid, or if accused node hasn't revealed them before the
+/// session times out, node is disqualified and joint key is generated by remaining (qualified) nodes
 pub struct SessionImpl {
 	/// Unique session id.
 	id: SessionId,
@@ -90,6 +95,10 @@ struct SessionData {
 	/// Threshold value for this DKG. Only `threshold + 1` will be able to collectively recreate joint secret,
 	/// and thus - decrypt message, encrypted with joint public.
 	threshold: Option<usize>,
+	/// Point after which generated key expires (None if key never expires).
+	expiration: Option<KeyExpiration>,
+	/// ECDSA signing scheme of generated key.
+	ecdsa_scheme: EcdsaSigningScheme,
 	/// Derived point generation session.
 	derived_point_generation: RandomPointGenerationSession,
 	/// Nodes-specific data.
@@ -98,6 +107,8 @@ struct SessionData {
 	// === Values, filled during KD phase ===
 	/// Polynom1.
 	polynom1: Option<Vec<Secret>>,
+	/// Polynom2.
+	polynom2: Option<Vec<Secret>>,
 	/// Value of polynom1[0], generated by this node.
 	secret_coeff: Option<Secret>,
 
@@ -110,6 +121,8 @@ struct SessionData {
 	joint_public_footprint: Option<H256>,
 	/// Secret share, which this node holds. Persistent + private.
 	secret_share: Option<Secret>,
+	/// Nodes, which have been disqualified after complaints have been processed.
+	disqualified_nodes: BTreeSet<NodeId>,
 
 	/// === Values, filled when DKG session is completed successfully ===
 	/// Key share.
@@ -137,6 +150,12 @@ struct NodeData {
 	// === Values, filled during KG phase ===
 	/// Public share, which has been received from this node.
 	pub public_share: Option<Public>,
+	/// Public share proof (publics of polynom1 coefficients), which has been received from this node.
+	pub public_share_proof: Option<Vec<Public>>,
+	/// Nodes, which have sent invalid keys to this node.
+	pub complaints: Option<BTreeSet<NodeId>>,
+	/// Keys, which have been revealed by this node in response to complaints of other nodes.
+	pub complaints_responses: BTreeMap<NodeId, (Secret, Secret)>,
 
 	// === Values, filled during completion phase ===
 	/// Flags marking that node has confirmed key joint public compution.
@@ -184,6 +203,8 @@ pub enum SessionState {
 	// === KG phase states ===
 	/// Node is waiting for joint public key share to be received from every other node.
 	WaitingForPublicKeyShare,
+	/// Node is waiting for accused nodes to reveal disputed keys.
+	WaitingForComplaintsResponses,
 	/// Waiting for joint public key confirmation.
 	WaitingForJointPublic,
 
@@ -246,6 +267,8 @@ impl SessionImpl {
 				origin: None,
 				is_zero: None,
 				threshold: None,
+				expiration: None,
+				ecdsa_scheme: Default::default(),
 				derived_point_generation: RandomPointGenerationSession::new(
 					params.self_node_id,
 					Arc::new(DerivedPointGenerationTransport {
@@ -256,11 +279,13 @@ impl SessionImpl {
 				),
 				nodes: BTreeMap::new(),
 				polynom1: None,
+				polynom2: None,
 				secret_coeff: None,
 				publics_footprint: None,
 				joint_public: None,
 				joint_public_footprint: None,
 				secret_share: None,
+				disqualified_nodes: BTreeSet::new(),
 				key_share: None,
 				joint_public_and_secret: None,
 			}),
@@ -298,8 +323,22 @@ impl SessionImpl {
 		self.data.lock().joint_public_and_secret.clone()
 	}
 
+	/// Get nodes, which have been disqualified during generation.
+	pub fn disqualified_nodes(&self) -> BTreeSet<NodeId> {
+		self.data.lock().disqualified_nodes.clone()
+	}
+
+	/// Get public shares of all session nodes (if all of them are known).
+	pub fn nodes_public_shares(&self) -> Option<BTreeMap<NodeId, Public>> {
+		self.data.lock().nodes.iter()
+			.map(|(node_id, node_data)| node_data.public_share.clone().map(|public_share| (node_id.clone(), public_share)))
+			.collect()
+	}
+
 	/// Start new session initialization. This must be called on master node.
-	pub fn initialize(&self, origin: Option<Address>, author: Address, is_zero: bool, threshold: usize, nodes: InitializationNodes) -> Result<(), Error> {
+	pub fn initialize(&self, origin: Option<Address>, author: Address, is_zero: bool, threshold: usize, expiration: Option<KeyExpiration>,
+		ecdsa_scheme: EcdsaSigningScheme, nodes: InitializationNodes) -> Result<(), Error>
+	{
 		check_cluster_nodes(self.node(), &nodes.set())?;
 		check_threshold(threshold, &nodes.set())?;
 
@@ -316,6 +355,8 @@ impl SessionImpl {
 		data.origin = origin.clone();
 		data.is_zero = Some(is_zero);
 		data.threshold = Some(threshold);
+		data.expiration = expiration;
+		data.ecdsa_scheme = ecdsa_scheme;
 		match nodes {
 			InitializationNodes::RandomNumbers(nodes) => {
 				for node_id in nodes {
@@ -338,7 +379,7 @@ impl SessionImpl {
 			drop(data);
 			self.disseminate_keys()?;
 			self.verify_keys()?;
-			self.compute_joint_public()?;
+			self.process_complaints(false)?;
 			self.complete_generation()?;
 
 			let mut data = self.data.lock();
@@ -353,7 +394,7 @@ impl SessionImpl {
 
 		// initialize session on other nodes
 		data.state = SessionState::WaitingForInitializationConfirm;
-		self.cluster.broadcast(Message::Generation(GenerationMessage::InitializeSession(
+		self.cluster.reliable_broadcast(Message::Generation(GenerationMessage::InitializeSession(
 			InitializeSession {
 				session: self.id.clone().into(),
 				session_nonce: self.nonce,
@@ -362,6 +403,8 @@ impl SessionImpl {
 				nodes: data.nodes.iter().map(|(k, v)| (k.clone().into(), v.id_number.clone().into())).collect(),
 				is_zero: data.is_zero.expect("is_zero is filled in initialization phase; KD phase follows initialization phase; qed"),
 				threshold: data.threshold.expect("threshold is filled in initialization phase; KD phase follows initialization phase; qed"),
+				expiration: data.expiration,
+				ecdsa_scheme: data.ecdsa_scheme,
 			},
 		)))?;
 
@@ -374,6 +417,11 @@ impl SessionImpl {
 			return Err(Error::ReplayProtection);
 		}
 
+		// disqualified nodes are not participating in the session anymore
+		if self.data.lock().disqualified_nodes.contains(sender) {
+			return Ok(());
+		}
+
 		match message {
 			&GenerationMessage::InitializeSession(ref message) =>
 				self.on_initialize_session(sender.clone(), message),
@@ -385,6 +433,8 @@ impl SessionImpl {
 				self.on_keys_dissemination(sender.clone(), message),
 			&GenerationMessage::PublicKeyShare(ref message) =>
 				self.on_public_key_share(sender.clone(), message),
+			&GenerationMessage::KeysComplaintResponse(ref message) =>
+				self.on_keys_complaint_response(sender.clone(), message),
 			&GenerationMessage::JointPublicKey(ref message) =>
 				self.on_joint_public_key(sender.clone(), message),
 			&GenerationMessage::SessionError(ref message) => {
@@ -427,6 +477,8 @@ impl SessionImpl {
 		data.origin = message.origin.clone().map(Into::into);
 		data.is_zero = Some(message.is_zero);
 		data.threshold = Some(message.threshold);
+		data.expiration = message.expiration;
+		data.ecdsa_scheme = message.ecdsa_scheme;
 
 		Ok(())
 	}
@@ -566,10 +618,20 @@ impl SessionImpl {
 			return Err(Error::InvalidMessage);
 		}
 
+		// check complaints of the node
+		let complaints: BTreeSet<NodeId> = message.complaints.iter().cloned().map(Into::into).collect();
+		if complaints.contains(&sender) || complaints.iter().any(|n| !data.nodes.contains_key(n)) {
+			return Err(Error::InvalidMessage);
+		}
+
 		// update node data with received public share
 		let self_id_number = data.nodes[self.node()].id_number.clone();
 		let threshold = data.threshold.expect("threshold is filled in initialization phase; KG phase follows initialization phase; qed");
 		let is_zero = data.is_zero.expect("is_zero is filled in initialization phase; KG phase follows initialization phase; qed");
+		// if we have complained about keys, received from the node, proof is verified when keys are revealed
+		let is_sender_accused = data.nodes[self.node()].complaints.as_ref()
+			.map(|complaints| complaints.contains(&sender))
+			.unwrap_or(false);
 		{
 			let node_data = &mut data.nodes.get_mut(&sender).ok_or(Error::InvalidMessage)?;
 			if node_data.public_share.is_some() {
@@ -577,12 +639,17 @@ impl SessionImpl {
 			}
 
 			// verify public share proof
-			let is_share_proof_valid = if !is_zero {
-				math::share_proof_verification(
+			let public_share_proof: Vec<Public> = message.public_share_proof.iter().cloned().map(Into::into).collect();
+			let is_share_proof_valid = if public_share_proof.is_empty() {
+				false
+			} else if is_sender_accused {
+				public_share_proof.len() == threshold + 1
+			} else if !is_zero {
+				public_share_proof.len() == threshold + 1 && math::share_proof_verification(
 					threshold,
 					&self_id_number,
 					node_data.secret1.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed"),
-					&message.public_share_proof.iter().cloned().map(Into::into).collect::<Vec<_>>(),
+					&public_share_proof,
 				)?
 			} else {
 				true
@@ -591,7 +658,25 @@ impl SessionImpl {
 				return Err(Error::InvalidMessage);
 			}
 
-			node_data.public_share = Some(message.public_share_proof[0].clone().into());
+			node_data.public_share = Some(public_share_proof[0].clone());
+			node_data.public_share_proof = Some(public_share_proof);
+			node_data.complaints = Some(complaints.clone());
+		}
+
+		// if we are accused by the node => reveal keys that we have sent to it
+		if complaints.contains(self.node()) {
+			let sender_id_number = data.nodes[&sender].id_number.clone();
+			let secret1 = math::compute_polynom(data.polynom1.as_ref().expect("polynom1 is generated on KD phase; KG phase follows KD phase; qed"), &sender_id_number)?;
+			let secret2 = math::compute_polynom(data.polynom2.as_ref().expect("polynom2 is generated on KD phase; KG phase follows KD phase; qed"), &sender_id_number)?;
+			data.nodes.get_mut(self.node()).expect("node is always qualified by himself; qed")
+				.complaints_responses.insert(sender.clone(), (secret1.clone(), secret2.clone()));
+			self.cluster.reliable_broadcast(Message::Generation(GenerationMessage::KeysComplaintResponse(KeysComplaintResponse {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				complainer: sender.clone().into(),
+				secret1: secret1.into(),
+				secret2: secret2.into(),
+			})))?;
 		}
 
 		// if there's also nodes, which has not sent us their public shares - do nothing
@@ -600,7 +685,45 @@ impl SessionImpl {
 		}
 
 		drop(data);
-		self.compute_joint_public()
+		self.process_complaints(false)
+	}
+
+	/// When accused node reveals keys, that it has sent to the complaining node.
+	pub fn on_keys_complaint_response(&self, sender: NodeId, message: &KeysComplaintResponse) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		match data.state {
+			SessionState::WaitingForDerivedPointGeneration |
+				SessionState::WaitingForKeysDissemination => return Err(Error::TooEarlyForRequest),
+			SessionState::WaitingForPublicKeyShare | SessionState::WaitingForComplaintsResponses => (),
+			_ => return Err(Error::InvalidStateForRequest),
+		}
+
+		// remember revealed keys
+		let complainer = message.complainer.clone().into();
+		if complainer == sender || !data.nodes.contains_key(&complainer) {
+			return Err(Error::InvalidMessage);
+		}
+		{
+			let node_data = data.nodes.get_mut(&sender).ok_or(Error::InvalidMessage)?;
+			if node_data.complaints_responses.contains_key(&complainer) {
+				return Err(Error::InvalidMessage);
+			}
+
+			node_data.complaints_responses.insert(complainer, (message.secret1.clone().into(), message.secret2.clone().into()));
+		}
+
+		// complaints are processed when all public shares are received
+		if data.state != SessionState::WaitingForComplaintsResponses {
+			return Ok(());
+		}
+
+		drop(data);
+		self.process_complaints(false)
 	}
 
 	/// When public key is computed.
@@ -609,7 +732,7 @@ impl SessionImpl {
 
 		// check state
 		match data.state {
-			SessionState::WaitingForPublicKeyShare => return Err(Error::TooEarlyForRequest),
+			SessionState::WaitingForPublicKeyShare | SessionState::WaitingForComplaintsResponses => return Err(Error::TooEarlyForRequest),
 			SessionState::WaitingForJointPublic => (),
 			_ => return Err(Error::InvalidStateForRequest),
 		}
@@ -678,10 +801,12 @@ impl SessionImpl {
 				public: joint_public,
 				common_point: None,
 				encrypted_point: None,
-				versions: vec![DocumentKeyShareVersion::new(
-					data.nodes.iter().map(|(node_id, node_data)| (node_id.clone(), node_data.id_number.clone())).collect(),
-					data.secret_share.as_ref().expect("secret_share is filled in KG phase; we are at the end of KG phase; qed").clone(),
-				)],
+				curve: KeyCurve::Secp256k1,
+				ecdsa_scheme: data.ecdsa_scheme,
+				exported: false,
+				document_key_slots: Default::default(),
+				expiration: data.expiration,
+				versions: vec![Self::key_share_version(&data)?],
 			};
 
 			if let Some(ref key_storage) = self.key_storage {
@@ -734,6 +859,7 @@ impl SessionImpl {
 		}
 		let polynom2 = math::generate_random_polynom(threshold)?;
 		data.polynom1 = Some(polynom1.clone());
+		data.polynom2 = Some(polynom2.clone());
 		data.secret_coeff = Some(polynom1[0].clone());
 
 		// compute t+1 public values
@@ -779,11 +905,12 @@ impl SessionImpl {
 		// key verification (KV) phase: check that other nodes have passed correct secrets
 		let threshold = data.threshold.expect("threshold is filled in initialization phase; KV phase follows initialization phase; qed");
 		let is_zero = data.is_zero.expect("is_zero is filled in initialization phase; KV phase follows initialization phase; qed");
+		let mut complaints = BTreeSet::new();
 		let self_public_share = {
 			if !is_zero {
 				let derived_point = data.derived_point_generation.generated_point().expect("derived point generated on initialization phase; KV phase follows initialization phase; qed");
 				let number_id = data.nodes[self.node()].id_number.clone();
-				for (_, node_data) in data.nodes.iter_mut().filter(|&(node_id, _)| node_id != self.node()) {
+				for (node_id, node_data) in data.nodes.iter().filter(|&(node_id, _)| node_id != self.node()) {
 					let secret1 = node_data.secret1.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed");
 					let secret2 = node_data.secret2.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed");
 					let publics = node_data.publics.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed");
@@ -791,8 +918,9 @@ impl SessionImpl {
 						secret1, secret2, publics)?;
 
 					if !is_key_verification_ok {
-						// node has sent us incorrect values. In original ECDKG protocol we should have sent complaint here.
-						return Err(Error::InvalidMessage);
+						// node has sent us incorrect values => complain, so that it reveals correct values or is disqualified
+						warn!("{}: complaining about keys, received from {} in generation session {}", self.node(), node_id, self.id);
+						complaints.insert(node_id.clone());
 					}
 				}
 
@@ -809,13 +937,6 @@ impl SessionImpl {
 			}
 		};
 
-		// calculate self secret + public shares
-		let self_secret_share = {
-			let secret_values_iter = data.nodes.values()
-				.map(|n| n.secret1.as_ref().expect("keys received on KD phase; KG phase follows KD phase; qed"));
-			math::compute_secret_share(secret_values_iter)?
-		};
-
 		// prepare publics footprint and public share proof
 		let publics_footprint = math::compute_publics_footprint(
 			data
@@ -837,20 +958,100 @@ impl SessionImpl {
 
 		// update state
 		data.state = SessionState::WaitingForPublicKeyShare;
-		data.secret_share = Some(self_secret_share);
 		data.publics_footprint = Some(publics_footprint);
 		let self_node = data.nodes.get_mut(self.node()).expect("node is always qualified by himself; qed");
 		self_node.public_share = Some(self_public_share.clone());
+		self_node.public_share_proof = Some(public_share_proof.clone());
+		self_node.complaints = Some(complaints.clone());
 
 		// broadcast self public key share
-		self.cluster.broadcast(Message::Generation(GenerationMessage::PublicKeyShare(PublicKeyShare {
+		self.cluster.reliable_broadcast(Message::Generation(GenerationMessage::PublicKeyShare(PublicKeyShare {
 			session: self.id.clone().into(),
 			session_nonce: self.nonce,
 			publics_footprint: publics_footprint.into(),
 			public_share_proof: public_share_proof.into_iter().map(Into::into).collect(),
+			complaints: complaints.into_iter().map(Into::into).collect(),
 		})))
 	}
 
+	/// Process complaints of all nodes, disqualify nodes that have failed to reveal valid keys and compute self secret share.
+	/// If complaints round is finished, accused nodes that haven't revealed disputed keys are also disqualified.
+	fn process_complaints(&self, is_round_finished: bool) -> Result<(), Error> {
+		let mut data = self.data.lock();
+
+		// wait until every accused node reveals disputed keys (or until complaints round is finished)
+		let is_every_complaint_answered = data.nodes.iter()
+			.all(|(complainer, complainer_data)| complainer_data.complaints.as_ref()
+				.expect("complaints are received along with public shares; all public shares are received; qed")
+				.iter()
+				.all(|accused| data.nodes[accused].complaints_responses.contains_key(complainer)));
+		if !is_every_complaint_answered && !is_round_finished {
+			data.state = SessionState::WaitingForComplaintsResponses;
+			return Ok(());
+		}
+
+		// check revealed keys
+		let threshold = data.threshold.expect("threshold is filled in initialization phase; KG phase follows initialization phase; qed");
+		let mut disqualified_nodes = BTreeSet::new();
+		let mut revealed_keys = BTreeMap::new();
+		for (complainer, complainer_data) in &data.nodes {
+			for accused in complainer_data.complaints.as_ref().expect("checked above; qed") {
+				let accused_data = &data.nodes[accused];
+				let (secret1, secret2) = match accused_data.complaints_responses.get(complainer) {
+					Some(&(ref secret1, ref secret2)) => (secret1, secret2),
+					None => {
+						// accused node hasn't revealed disputed keys within complaints round
+						disqualified_nodes.insert(accused.clone());
+						continue;
+					},
+				};
+				let derived_point = data.derived_point_generation.generated_point().expect("derived point generated on initialization phase; KG phase follows initialization phase; qed");
+				let publics = accused_data.publics.as_ref().expect("keys received on KD phase; KG phase follows KD phase; qed");
+				let public_share_proof = accused_data.public_share_proof.as_ref().expect("share proofs received on KG phase; all public shares are received; qed");
+				let are_revealed_keys_valid = math::keys_verification(threshold, &derived_point, &complainer_data.id_number, secret1, secret2, publics)?
+					&& math::share_proof_verification(threshold, &complainer_data.id_number, secret1, public_share_proof)?;
+				if !are_revealed_keys_valid {
+					disqualified_nodes.insert(accused.clone());
+				} else if complainer == self.node() {
+					revealed_keys.insert(accused.clone(), (secret1.clone(), secret2.clone()));
+				}
+			}
+		}
+
+		// exclude disqualified nodes from the session
+		if !disqualified_nodes.is_empty() {
+			warn!("{}: nodes {:?} are disqualified in generation session {}", self.node(), disqualified_nodes, self.id);
+		}
+		for disqualified_node in &disqualified_nodes {
+			data.nodes.remove(disqualified_node);
+		}
+		data.disqualified_nodes = disqualified_nodes;
+		if data.nodes.len() <= threshold {
+			return Err(Error::NotEnoughNodesForThreshold);
+		}
+		let master = data.master.clone().expect("master is filled in initialization phase; KG phase follows initialization phase; qed");
+		if data.disqualified_nodes.contains(self.node()) || data.disqualified_nodes.contains(&master) {
+			return Err(Error::InvalidMessage);
+		}
+		for (accused, (secret1, secret2)) in revealed_keys {
+			if let Some(accused_data) = data.nodes.get_mut(&accused) {
+				accused_data.secret1 = Some(secret1);
+				accused_data.secret2 = Some(secret2);
+			}
+		}
+
+		// calculate self secret share
+		let self_secret_share = {
+			let secret_values_iter = data.nodes.values()
+				.map(|n| n.secret1.as_ref().expect("keys received on KD phase; KG phase follows KD phase; qed"));
+			math::compute_secret_share(secret_values_iter)?
+		};
+		data.secret_share = Some(self_secret_share);
+
+		drop(data);
+		self.compute_joint_public()
+	}
+
 	/// Compute joint public key.
 	fn compute_joint_public(&self) -> Result<(), Error> {
 		let mut data = self.data.lock();
@@ -908,10 +1109,12 @@ impl SessionImpl {
 			public: joint_public,
 			common_point: None,
 			encrypted_point: None,
-			versions: vec![DocumentKeyShareVersion::new(
-				data.nodes.iter().map(|(node_id, node_data)| (node_id.clone(), node_data.id_number.clone())).collect(),
-				secret_share.clone(),
-			)],
+			curve: KeyCurve::Secp256k1,
+			ecdsa_scheme: data.ecdsa_scheme,
+			exported: false,
+			document_key_slots: Default::default(),
+			expiration: data.expiration,
+			versions: vec![Self::key_share_version(&data)?],
 		};
 
 		// if we are at the slave node - wait for session completion
@@ -945,6 +1148,27 @@ impl SessionImpl {
 
 		Ok(())
 	}
+
+	/// Prepare version of the generated key share.
+	fn key_share_version(data: &SessionData) -> Result<DocumentKeyShareVersion, Error> {
+		let mut key_version = DocumentKeyShareVersion::new(
+			data.nodes.iter().map(|(node_id, node_data)| (node_id.clone(), node_data.id_number.clone())).collect(),
+			data.secret_share.as_ref().expect("secret_share is filled in KG phase; we are at the end of KG phase; qed").clone(),
+		);
+
+		// public shares of all nodes are used later to verify partial results, computed by these nodes
+		let is_zero = data.is_zero.expect("is_zero is filled in initialization phase; KG phase follows initialization phase; qed");
+		if !is_zero {
+			for (node_id, node_data) in &data.nodes {
+				let share_proofs = data.nodes.values()
+					.map(|n| n.public_share_proof.as_ref().expect("share proofs received on KG phase; we are at the end of KG phase; qed").as_slice());
+				let public_share = math::compute_node_public_share(&node_data.id_number, share_proofs)?;
+				key_version.public_shares.insert(node_id.clone(), public_share);
+			}
+		}
+
+		Ok(key_version)
+	}
 }
 
 impl ClusterSession for SessionImpl {
@@ -980,6 +1204,23 @@ impl ClusterSession for SessionImpl {
 	}
 
 	fn on_session_timeout(&self) {
+		// complaints round is finished => accused nodes, that are still silent, are disqualified
+		if self.data.lock().state == SessionState::WaitingForComplaintsResponses {
+			match self.process_complaints(true) {
+				Ok(()) => return,
+				Err(error) => {
+					warn!("{}: generation session failed after complaints round: {}", self.node(), error);
+
+					let mut data = self.data.lock();
+					data.state = SessionState::Failed;
+					data.key_share = Some(Err(error.clone()));
+					data.joint_public_and_secret = Some(Err(error.clone()));
+					self.completed.send(Err(error));
+					return;
+				},
+			}
+		}
+
 		let mut data = self.data.lock();
 
 		warn!("{}: generation session failed with timeout", self.node());
@@ -1026,6 +1267,9 @@ impl NodeData {
 			secret2: None,
 			publics: None,
 			public_share: None,
+			public_share_proof: None,
+			complaints: None,
+			complaints_responses: BTreeMap::new(),
 			joint_computed: false,
 			completion_confirmed: false,
 		}
@@ -1067,11 +1311,15 @@ impl RandomPointGenerationSessionTransport for DerivedPointGenerationTransport {
 #[cfg(test)]
 pub mod tests {
 	use std::sync::Arc;
+	use std::collections::BTreeSet;
 	use ethereum_types::H256;
 	use crypto::publickey::{Random, Generator, KeyPair, Secret};
-	use key_server_cluster::{NodeId, Error, KeyStorage, SessionId};
-	use key_server_cluster::message::{self, KeysDissemination, PublicKeyShare, JointPublicKey, ConfirmInitialization};
-	use key_server_cluster::cluster::tests::{MessageLoop as ClusterMessageLoop, make_clusters_and_preserve_sessions};
+	use key_server_cluster::{NodeId, Error, KeyStorage, SessionId, EcdsaSigningScheme};
+	use key_server_cluster::message::{self, Message, GenerationMessage, KeysDissemination, PublicKeyShare, JointPublicKey,
+		ConfirmInitialization};
+	use key_server_cluster::cluster::tests::{MessageLoop as ClusterMessageLoop, make_clusters_and_preserve_sessions,
+		make_clusters_with_reliable_broadcast};
+	use key_server_cluster::cluster_reliable_broadcast::tests::replace_broadcasted_message;
 	use key_server_cluster::cluster_sessions::ClusterSession;
 	use key_server_cluster::generation_session::{SessionImpl, SessionState};
 	use key_server_cluster::math;
@@ -1086,8 +1334,12 @@ pub mod tests {
 			MessageLoop(make_clusters_and_preserve_sessions(num_nodes))
 		}
 
+		pub fn with_reliable_broadcast(num_nodes: usize) -> Self {
+			MessageLoop(make_clusters_with_reliable_broadcast(num_nodes))
+		}
+
 		pub fn init(self, threshold: usize) -> Result<Self, Error> {
-			self.0.cluster(0).client().new_generation_session(SessionId::from([1u8; 32]), None, Default::default(), threshold)
+			self.0.cluster(0).client().new_generation_session(SessionId::from([1u8; 32]), None, Default::default(), threshold, None)
 				.map(|_| self)
 		}
 
@@ -1146,7 +1398,7 @@ pub mod tests {
 	fn fails_to_initialize_when_already_initialized() {
 		let ml = MessageLoop::new(2).init(0).unwrap();
 		assert_eq!(
-			ml.session_at(0).initialize(Default::default(), Default::default(), false, 0, ml.0.nodes().into()),
+			ml.session_at(0).initialize(Default::default(), Default::default(), false, 0, None, Default::default(), ml.0.nodes().into()),
 			Err(Error::InvalidStateForRequest),
 		);
 	}
@@ -1244,7 +1496,7 @@ pub mod tests {
 		);
 	}
 
-	fn process_keys_dissemination(same_derived_point: bool) -> Result<(), Error> {
+	fn process_keys_dissemination(same_derived_point: bool) -> (MessageLoop, Result<(), Error>) {
 		// nodes are 'connecting' fo generate 2-of-2 key
 		let threshold = 1;
 		let ml = MessageLoop::new(2).init(threshold).unwrap();
@@ -1283,7 +1535,7 @@ pub mod tests {
 		}
 
 		// receive last KeysDissemination message
-		ml.session_of(&ml.0.node(0))
+		let result = ml.session_of(&ml.0.node(0))
 			.on_keys_dissemination(
 				ml.0.node(1),
 				&KeysDissemination {
@@ -1292,27 +1544,33 @@ pub mod tests {
 					secret1: secret1_from_1_to_0.into(),
 					secret2: secret2_from_1_to_0.into(),
 					publics: publics.into_iter().map(Into::into).collect(),
-				})
+				});
+		(ml, result)
 	}
 
 	#[test]
-	fn fails_to_accept_keys_dissemination_with_wrong_derived_point() {
+	fn complains_about_keys_dissemination_with_wrong_derived_point() {
 		// when node1 had the different derived_point than the node0,
-		// key verification fails
-		assert_eq!(
-			process_keys_dissemination(false),
-			Err(Error::InvalidMessage),
-		);
+		// key verification fails and node0 complains about node1
+		let (ml, result) = process_keys_dissemination(false);
+		assert_eq!(result, Ok(()));
+
+		let session = ml.session_of(&ml.0.node(0));
+		let data = session.data.lock();
+		assert_eq!(data.state, SessionState::WaitingForPublicKeyShare);
+		assert_eq!(data.nodes[&ml.0.node(0)].complaints, Some(vec![ml.0.node(1)].into_iter().collect()));
 	}
 
 	#[test]
 	fn accepts_keys_dissemination_with_correct_derived_point() {
 		// when both nodes have used the same derived_point,
 		// key verification succeeds
-		assert_eq!(
-			process_keys_dissemination(true),
-			Ok(()),
-		);
+		let (ml, result) = process_keys_dissemination(true);
+		assert_eq!(result, Ok(()));
+
+		let session = ml.session_of(&ml.0.node(0));
+		let data = session.data.lock();
+		assert_eq!(data.nodes[&ml.0.node(0)].complaints, Some(Default::default()));
 	}
 
 	fn process_public_key_share(valid_proof: bool, valid_footprint: bool) -> Result<(), Error> {
@@ -1371,6 +1629,7 @@ pub mod tests {
 					session_nonce: 0,
 					publics_footprint: publics_footprint_from_1_to_0.into(),
 					public_share_proof: public_share_proof_from_1_to_0.into_iter().map(Into::into).collect(),
+					complaints: Default::default(),
 				})
 	}
 
@@ -1458,6 +1717,7 @@ pub mod tests {
 			session_nonce: 0,
 			publics_footprint: H256::default().into(),
 			public_share_proof: Vec::new(),
+			complaints: Default::default(),
 		}), Err(Error::InvalidStateForRequest));
 	}
 
@@ -1476,6 +1736,7 @@ pub mod tests {
 						session_nonce: 0,
 						publics_footprint: H256::default().into(),
 						public_share_proof: Vec::new(),
+						complaints: Default::default(),
 					}),
 			Err(Error::InvalidMessage),
 		);
@@ -1526,6 +1787,215 @@ pub mod tests {
 		}
 	}
 
+	#[test]
+	fn public_shares_of_all_nodes_are_stored_with_key_share() {
+		let ml = MessageLoop::new(4).init(2).unwrap();
+		ml.0.loop_until(|| ml.0.is_empty());
+
+		let server_key_id = ServerKeyId::from([1u8; 32]);
+		let secret_shares = ml.nodes_secret_shares();
+		for i in 0..4 {
+			let key_share = ml.0.key_storage(i).get(&server_key_id).unwrap().unwrap();
+			let public_shares = &key_share.versions[0].public_shares;
+			assert_eq!(public_shares.len(), 4);
+			for j in 0..4 {
+				assert_eq!(public_shares[&ml.0.node(j)], math::compute_public_share(&secret_shares[j]).unwrap());
+			}
+		}
+	}
+
+	#[test]
+	fn nonce_inversion_ecdsa_scheme_is_used_by_default() {
+		let ml = MessageLoop::new(3).init(1).unwrap();
+		ml.0.loop_until(|| ml.0.is_empty());
+
+		let server_key_id = ServerKeyId::from([1u8; 32]);
+		for i in 0..3 {
+			assert_eq!(ml.0.key_storage(i).get(&server_key_id).unwrap().unwrap().ecdsa_scheme, EcdsaSigningScheme::NonceInversion);
+		}
+	}
+
+	fn run_generation_with_invalid_keys(reveal_valid_keys: bool) -> MessageLoop {
+		// node1 sends invalid keys to node2
+		let ml = MessageLoop::new(4).init(1).unwrap();
+		let (malicious_node, complaining_node) = (ml.0.node(1), ml.0.node(2));
+		while let Some((from, to, msg)) = ml.0.take_message() {
+			let msg = match msg {
+				Message::Generation(GenerationMessage::KeysDissemination(mut msg)) => {
+					if from == malicious_node && to == complaining_node {
+						msg.secret1 = math::generate_random_scalar().unwrap().into();
+					}
+					Message::Generation(GenerationMessage::KeysDissemination(msg))
+				},
+				Message::Generation(GenerationMessage::KeysComplaintResponse(mut msg)) => {
+					if from == malicious_node && !reveal_valid_keys {
+						msg.secret1 = math::generate_random_scalar().unwrap().into();
+					}
+					Message::Generation(GenerationMessage::KeysComplaintResponse(msg))
+				},
+				msg => msg,
+			};
+			ml.0.process_message(from, to, msg);
+		}
+
+		ml
+	}
+
+	#[test]
+	fn generation_completes_when_accused_node_reveals_valid_keys() {
+		let ml = run_generation_with_invalid_keys(true);
+
+		// all nodes are qualified && complaining node uses revealed keys
+		for i in 0..4 {
+			assert_eq!(ml.session_at(i).state(), SessionState::Finished);
+			assert!(ml.session_at(i).disqualified_nodes().is_empty());
+		}
+		let joint_public = ml.session_at(0).joint_public_and_secret().unwrap().unwrap().0;
+		assert_eq!(*ml.compute_key_pair().public(), joint_public);
+	}
+
+	#[test]
+	fn generation_completes_without_node_that_has_failed_to_reveal_valid_keys() {
+		let ml = run_generation_with_invalid_keys(false);
+
+		// malicious node is disqualified && key is generated by remaining nodes
+		let server_key_id = ServerKeyId::from([1u8; 32]);
+		let qualified_nodes = [0, 2, 3];
+		let joint_public = ml.session_at(0).joint_public_and_secret().unwrap().unwrap().0;
+		for &i in &qualified_nodes {
+			let session = ml.session_at(i);
+			assert_eq!(session.state(), SessionState::Finished);
+			assert_eq!(session.disqualified_nodes(), vec![ml.0.node(1)].into_iter().collect());
+			assert_eq!(session.joint_public_and_secret().unwrap().unwrap().0, joint_public);
+
+			let key_share = ml.0.key_storage(i).get(&server_key_id).unwrap().unwrap();
+			assert_eq!(key_share.versions[0].id_numbers.keys().cloned().collect::<Vec<_>>(),
+				qualified_nodes.iter().map(|i| ml.0.node(*i)).collect::<BTreeSet<_>>().into_iter().collect::<Vec<_>>());
+		}
+
+		// any t+1 qualified nodes are able to recover joint secret
+		let key_share = ml.0.key_storage(0).get(&server_key_id).unwrap().unwrap();
+		let secret_shares: Vec<_> = qualified_nodes.iter().take(2).map(|i| ml.session_at(*i).data.lock().secret_share.clone().unwrap()).collect();
+		let id_numbers: Vec<_> = qualified_nodes.iter().take(2).map(|i| key_share.versions[0].id_numbers[&ml.0.node(*i)].clone()).collect();
+		let joint_secret = math::compute_joint_secret_from_shares(1, &secret_shares.iter().collect::<Vec<_>>(),
+			&id_numbers.iter().collect::<Vec<_>>()).unwrap();
+		assert_eq!(*KeyPair::from_secret(joint_secret).unwrap().public(), joint_public);
+	}
+
+	#[test]
+	fn generation_completes_without_node_that_has_not_answered_complaint_within_round() {
+		// node1 sends invalid keys to node2 && then goes silent
+		let ml = MessageLoop::new(4).init(1).unwrap();
+		let (malicious_node, complaining_node) = (ml.0.node(1), ml.0.node(2));
+		let mut is_malicious_node_silent = false;
+		while let Some((from, to, msg)) = ml.0.take_message() {
+			let msg = match msg {
+				Message::Generation(GenerationMessage::KeysDissemination(mut msg)) => {
+					if from == malicious_node && to == complaining_node {
+						msg.secret1 = math::generate_random_scalar().unwrap().into();
+					}
+					Message::Generation(GenerationMessage::KeysDissemination(msg))
+				},
+				Message::Generation(GenerationMessage::KeysComplaintResponse(_)) if from == malicious_node => {
+					is_malicious_node_silent = true;
+					continue;
+				},
+				_ if from == malicious_node && is_malicious_node_silent => continue,
+				msg => msg,
+			};
+			ml.0.process_message(from, to, msg);
+		}
+
+		// qualified nodes are waiting for the response until complaints round is finished
+		let qualified_nodes = [0, 2, 3];
+		for &i in &qualified_nodes {
+			assert_eq!(ml.session_at(i).state(), SessionState::WaitingForComplaintsResponses);
+		}
+		for &i in &qualified_nodes {
+			ml.session_at(i).on_session_timeout();
+		}
+		while let Some((from, to, msg)) = ml.0.take_message() {
+			if from != malicious_node {
+				ml.0.process_message(from, to, msg);
+			}
+		}
+
+		// silent node is disqualified && key is generated by remaining nodes
+		let joint_public = ml.session_at(0).joint_public_and_secret().unwrap().unwrap().0;
+		for &i in &qualified_nodes {
+			let session = ml.session_at(i);
+			assert_eq!(session.state(), SessionState::Finished);
+			assert_eq!(session.disqualified_nodes(), vec![malicious_node].into_iter().collect());
+			assert_eq!(session.joint_public_and_secret().unwrap().unwrap().0, joint_public);
+		}
+	}
+
+	fn run_generation_with_equivocating_node<F>(equivocating_node: usize, victim_node: usize, replace: F) -> MessageLoop
+		where F: Fn(&MessageLoop, Message) -> Message
+	{
+		// equivocating node sends different reliably broadcasted messages to victim node && to other nodes
+		let ml = MessageLoop::with_reliable_broadcast(4).init(1).unwrap();
+		let (equivocating_node, victim_node) = (ml.0.node(equivocating_node), ml.0.node(victim_node));
+		while let Some((from, to, msg)) = ml.0.take_message() {
+			let msg = match from == equivocating_node && to == victim_node {
+				true => replace_broadcasted_message(msg, |msg| replace(&ml, msg)),
+				false => msg,
+			};
+			ml.0.process_message(from, to, msg);
+		}
+
+		ml
+	}
+
+	#[test]
+	fn generation_completes_with_reliable_broadcast() {
+		let ml = MessageLoop::with_reliable_broadcast(4).init(1).unwrap();
+		ml.0.loop_until(|| ml.0.is_empty());
+
+		for i in 0..4 {
+			assert_eq!(ml.session_at(i).state(), SessionState::Finished);
+		}
+		let joint_public = ml.session_at(0).joint_public_and_secret().unwrap().unwrap().0;
+		assert_eq!(*ml.compute_key_pair().public(), joint_public);
+	}
+
+	#[test]
+	fn master_fails_to_initialize_nodes_with_different_thresholds_when_reliable_broadcast_is_used() {
+		let ml = run_generation_with_equivocating_node(0, 1, |_, msg| match msg {
+			Message::Generation(GenerationMessage::InitializeSession(mut msg)) => {
+				msg.threshold = 2;
+				Message::Generation(GenerationMessage::InitializeSession(msg))
+			},
+			msg => msg,
+		});
+
+		// all nodes have been initialized with the same threshold
+		let server_key_id = ServerKeyId::from([1u8; 32]);
+		for i in 0..4 {
+			assert_eq!(ml.session_at(i).state(), SessionState::Finished);
+			assert_eq!(ml.0.key_storage(i).get(&server_key_id).unwrap().unwrap().threshold, 1);
+		}
+	}
+
+	#[test]
+	fn node_fails_to_complain_to_single_node_when_reliable_broadcast_is_used() {
+		let ml = run_generation_with_equivocating_node(1, 2, |ml, msg| match msg {
+			Message::Generation(GenerationMessage::PublicKeyShare(mut msg)) => {
+				msg.complaints.insert(ml.0.node(3).into());
+				Message::Generation(GenerationMessage::PublicKeyShare(msg))
+			},
+			msg => msg,
+		});
+
+		// all nodes have received the same (empty) complaints set => nobody is disqualified
+		for i in 0..4 {
+			assert_eq!(ml.session_at(i).state(), SessionState::Finished);
+			assert!(ml.session_at(i).disqualified_nodes().is_empty());
+		}
+		let joint_public = ml.session_at(0).joint_public_and_secret().unwrap().unwrap().0;
+		assert_eq!(*ml.compute_key_pair().public(), joint_public);
+	}
+
 	#[test]
 	fn generation_message_fails_when_nonce_is_wrong() {
 		let ml = MessageLoop::new(2).init(0).unwrap();
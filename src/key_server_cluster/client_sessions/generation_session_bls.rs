@@ -0,0 +1,812 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, BTreeMap};
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::sync::Arc;
+use futures::Oneshot;
+use parking_lot::Mutex;
+use ethereum_types::Address;
+use bytes::Bytes;
+use crypto::publickey::Secret;
+use key_server_cluster::{Error, NodeId, SessionId, KeyStorage, DocumentKeyShare, DocumentKeyShareVersion, KeyCurve};
+use key_server_cluster::math_bls;
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
+use key_server_cluster::generation_session::InitializationNodes;
+use key_server_cluster::message::{Message, BlsGenerationMessage, InitializeBlsGenerationSession,
+	ConfirmBlsGenerationInitialization, BlsKeysDissemination, BlsGenerationSessionCompleted,
+	BlsGenerationSessionError};
+
+/// Distributed BLS12-381 key generation session.
+/// Based on Feldman's verifiable secret sharing ("A Practical Scheme for Non-interactive Verifiable Secret Sharing").
+/// Brief overview:
+/// 1) initialization: master node (which has received request for generating joint key) initializes the session on all other nodes
+/// 2) key dissemination: every node generates random polynom, sends its value at other node' id number to this node
+/// and broadcasts commitments to polynom coefficients
+/// 3) key verification: every node checks received values against commitments and computes its own secret share
+/// 4) completion: every node sends computed joint public to master node, which checks that all nodes have
+/// computed the same key and confirms key generation
+pub struct SessionImpl {
+	/// Unique session id.
+	id: SessionId,
+	/// Public identifier of this node.
+	self_node_id: NodeId,
+	/// Key storage.
+	key_storage: Option<Arc<dyn KeyStorage>>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	cluster: Arc<dyn Cluster>,
+	/// Session-level nonce.
+	nonce: u64,
+	/// Mutable session data.
+	data: Mutex<SessionData>,
+	/// Session completion signal.
+	completed: CompletionSignal<Bytes>,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// SessionImpl identifier.
+	pub id: SessionId,
+	/// Id of node, on which this session is running.
+	pub self_node_id: NodeId,
+	/// Key storage.
+	pub key_storage: Option<Arc<dyn KeyStorage>>,
+	/// Cluster
+	pub cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	pub nonce: Option<u64>,
+}
+
+/// Mutable data of distributed key generation session.
+#[derive(Debug)]
+struct SessionData {
+	/// Current state of the session.
+	state: SessionState,
+
+	// === Values, filled when session initialization just starts ===
+	/// Reference to the node, which has started this session.
+	master: Option<NodeId>,
+	/// Address of the creator of the session.
+	author: Option<Address>,
+	/// Threshold value for this DKG.
+	threshold: Option<usize>,
+	/// Nodes-specific data.
+	nodes: BTreeMap<NodeId, NodeData>,
+
+	// === Values, filled during key dissemination phase ===
+	/// Polynom, generated by this node.
+	polynom: Option<Vec<Secret>>,
+
+	// === Values, filled during key verification phase ===
+	/// Secret share, which this node holds.
+	secret_share: Option<Secret>,
+	/// Joint public that we have computed locally.
+	joint_public: Option<Bytes>,
+
+	/// === Values, filled when session is completed ===
+	/// Jointly generated public key and secret share of this node.
+	joint_public_and_secret: Option<Result<(Bytes, Secret), Error>>,
+}
+
+/// Mutable node-specific data.
+#[derive(Debug, Clone)]
+struct NodeData {
+	/// True if node has confirmed initialization.
+	pub initialized: bool,
+	/// Random unique scalar. Persistent.
+	pub id_number: Secret,
+	/// Value of node' polynom at this node' id number.
+	pub secret_subshare: Option<Secret>,
+	/// Commitments to node' polynom coefficients.
+	pub commitments: Option<Vec<Bytes>>,
+	/// Joint public, computed by the node.
+	pub joint_public: Option<Bytes>,
+	/// True if node has saved generated key && confirmed session completion.
+	pub completion_confirmed: bool,
+}
+
+/// Distributed key generation session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for initialization confirmation from all other nodes.
+	WaitingForInitializationConfirm,
+	/// Node is waiting for generated keys from every other node.
+	WaitingForKeysDissemination,
+	/// Node is waiting for session completion/session completion confirmation.
+	WaitingForGenerationConfirmation,
+	/// Key generation is completed.
+	Finished,
+	/// Key generation is failed.
+	Failed,
+}
+
+impl SessionImpl {
+	/// Create new generation session.
+	pub fn new(params: SessionParams) -> (Self, Oneshot<Result<Bytes, Error>>) {
+		let (completed, oneshot) = CompletionSignal::new();
+		(SessionImpl {
+			id: params.id,
+			self_node_id: params.self_node_id,
+			key_storage: params.key_storage,
+			cluster: params.cluster,
+			// when nonce.is_none(), generation session is wrapped
+			// => nonce is checked somewhere else && we can pass any value
+			nonce: params.nonce.unwrap_or_default(),
+			completed,
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				master: None,
+				author: None,
+				threshold: None,
+				nodes: BTreeMap::new(),
+				polynom: None,
+				secret_share: None,
+				joint_public: None,
+				joint_public_and_secret: None,
+			}),
+		}, oneshot)
+	}
+
+	/// Get this node Id.
+	pub fn node(&self) -> &NodeId {
+		&self.self_node_id
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Get generated public and secret share (if any).
+	pub fn joint_public_and_secret(&self) -> Option<Result<(Bytes, Secret), Error>> {
+		self.data.lock().joint_public_and_secret.clone()
+	}
+
+	/// Start new session initialization. This must be called on master node.
+	pub fn initialize(&self, author: Address, threshold: usize, nodes: InitializationNodes) -> Result<(), Error> {
+		check_threshold(threshold, &nodes.set())?;
+		debug_assert!(nodes.set().contains(self.node()));
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// update state
+		data.master = Some(self.node().clone());
+		data.author = Some(author.clone());
+		data.threshold = Some(threshold);
+		match nodes {
+			InitializationNodes::RandomNumbers(nodes) => {
+				for node_id in nodes {
+					let node_id_number = math_bls::generate_random_scalar()?;
+					data.nodes.insert(node_id, NodeData::with_id_number(node_id == self.self_node_id, node_id_number));
+				}
+			},
+			InitializationNodes::SpecificNumbers(nodes) => {
+				for (node_id, node_id_number) in nodes {
+					data.nodes.insert(node_id, NodeData::with_id_number(node_id == self.self_node_id, node_id_number));
+				}
+			},
+		}
+
+		// if we are single node
+		if data.nodes.len() == 1 {
+			self.disseminate_keys(&mut *data)?;
+			self.verify_keys(&mut *data)?;
+			return self.complete_on_master(&mut *data);
+		}
+
+		// initialize session on other nodes
+		data.state = SessionState::WaitingForInitializationConfirm;
+		self.cluster.broadcast(Message::BlsGeneration(BlsGenerationMessage::InitializeBlsGenerationSession(
+			InitializeBlsGenerationSession {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				author: author.into(),
+				nodes: data.nodes.iter().map(|(k, v)| (k.clone().into(), v.id_number.clone().into())).collect(),
+				threshold: threshold,
+			},
+		)))
+	}
+
+	/// Process single message.
+	pub fn process_message(&self, sender: &NodeId, message: &BlsGenerationMessage) -> Result<(), Error> {
+		if self.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&BlsGenerationMessage::InitializeBlsGenerationSession(ref message) =>
+				self.on_initialize_session(sender.clone(), message),
+			&BlsGenerationMessage::ConfirmBlsGenerationInitialization(ref message) =>
+				self.on_confirm_initialization(sender.clone(), message),
+			&BlsGenerationMessage::BlsKeysDissemination(ref message) =>
+				self.on_keys_dissemination(sender.clone(), message),
+			&BlsGenerationMessage::BlsGenerationSessionCompleted(ref message) =>
+				self.on_session_completed(sender.clone(), message),
+			&BlsGenerationMessage::BlsGenerationSessionError(ref message) => {
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+		}
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: NodeId, message: &InitializeBlsGenerationSession) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		// check message
+		let nodes_ids: BTreeSet<NodeId> = message.nodes.keys().cloned().map(Into::into).collect();
+		check_threshold(message.threshold, &nodes_ids)?;
+		if !nodes_ids.contains(self.node()) || !nodes_ids.contains(&sender) {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// send confirmation back to master node
+		self.cluster.send(&sender, Message::BlsGeneration(BlsGenerationMessage::ConfirmBlsGenerationInitialization(
+			ConfirmBlsGenerationInitialization {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+			},
+		)))?;
+
+		// update state
+		data.master = Some(sender);
+		data.author = Some(message.author.clone().into());
+		data.threshold = Some(message.threshold);
+		data.nodes = message.nodes.iter().map(|(id, number)| (id.clone().into(), NodeData::with_id_number(true, number.clone().into()))).collect();
+		data.state = SessionState::WaitingForKeysDissemination;
+
+		Ok(())
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: NodeId, message: &ConfirmBlsGenerationInitialization) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// update node data
+		{
+			let node_data = data.nodes.get_mut(&sender).ok_or(Error::InvalidMessage)?;
+			if node_data.initialized {
+				return Err(Error::InvalidMessage);
+			}
+			node_data.initialized = true;
+		}
+
+		// if all nodes have confirmed initialization, start keys dissemination
+		if data.nodes.values().any(|nd| !nd.initialized) {
+			return Ok(());
+		}
+
+		self.disseminate_keys(&mut *data)
+	}
+
+	/// When keys dissemination message is received.
+	pub fn on_keys_dissemination(&self, sender: NodeId, message: &BlsKeysDissemination) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		match data.state {
+			SessionState::WaitingForInitializationConfirm => return Err(Error::TooEarlyForRequest),
+			SessionState::WaitingForKeysDissemination => (),
+			_ => return Err(Error::InvalidStateForRequest),
+		}
+
+		// check message
+		let threshold = data.threshold.expect("threshold is filled in initialization phase; KD phase follows initialization phase; qed");
+		if message.commitments.len() != threshold + 1 {
+			return Err(Error::InvalidMessage);
+		}
+
+		// update node data
+		{
+			let node_data = data.nodes.get_mut(&sender).ok_or(Error::InvalidMessage)?;
+			if node_data.secret_subshare.is_some() || node_data.commitments.is_some() {
+				return Err(Error::InvalidStateForRequest);
+			}
+
+			node_data.secret_subshare = Some(message.secret_subshare.clone().into());
+			node_data.commitments = Some(message.commitments.iter().cloned().map(Into::into).collect());
+		}
+
+		// first message from other node is a signal to start dissemination on slave nodes
+		if data.polynom.is_none() {
+			self.disseminate_keys(&mut *data)?;
+		}
+
+		// check if we have received keys from every other node
+		if data.nodes.values().any(|node_data| node_data.commitments.is_none()) {
+			return Ok(());
+		}
+
+		self.verify_keys(&mut *data)?;
+		if data.master.as_ref() == Some(self.node()) {
+			return self.complete_on_master(&mut *data);
+		}
+
+		let joint_public = data.joint_public.clone()
+			.expect("joint_public is filled in KV phase; we are at the end of KV phase; qed");
+		self.cluster.send(data.master.as_ref().expect("master is filled in initialization phase; qed"),
+			Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionCompleted(BlsGenerationSessionCompleted {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				joint_public: joint_public.into(),
+			})))
+	}
+
+	/// When session completion message is received.
+	pub fn on_session_completed(&self, sender: NodeId, message: &BlsGenerationSessionCompleted) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+		let joint_public: Bytes = message.joint_public.clone().into();
+
+		// if we are not master, check that master has computed the same key && save result
+		if data.master.as_ref() != Some(self.node()) {
+			if data.master.as_ref() != Some(&sender) {
+				return Err(Error::InvalidMessage);
+			}
+			match data.state {
+				SessionState::WaitingForKeysDissemination => return Err(Error::TooEarlyForRequest),
+				SessionState::WaitingForGenerationConfirmation => (),
+				_ => return Err(Error::InvalidStateForRequest),
+			}
+			if data.joint_public.as_ref() != Some(&joint_public) {
+				return Err(Error::InvalidMessage);
+			}
+
+			// save key and then respond with confirmation, so that master completes
+			// session only when the key is available on every node
+			self.save_key(&*data)?;
+			self.complete(&mut *data);
+			return self.cluster.send(&sender, Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionCompleted(BlsGenerationSessionCompleted {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				joint_public: joint_public.into(),
+			})));
+		}
+
+		match data.state {
+			SessionState::WaitingForKeysDissemination | SessionState::WaitingForGenerationConfirmation => (),
+			_ => return Err(Error::InvalidStateForRequest),
+		}
+
+		// first message from other node holds joint public, computed by this node
+		// second message is the confirmation that the node has saved the key
+		let is_completion_broadcasted = data.nodes.get(self.node()).expect("node is always qualified by himself; qed").completion_confirmed;
+		{
+			let node_data = data.nodes.get_mut(&sender).ok_or(Error::InvalidMessage)?;
+			match node_data.joint_public.is_some() {
+				false => node_data.joint_public = Some(joint_public),
+				true if is_completion_broadcasted && !node_data.completion_confirmed && node_data.joint_public == Some(joint_public) =>
+					node_data.completion_confirmed = true,
+				true => return Err(Error::InvalidMessage),
+			}
+		}
+
+		if data.state != SessionState::WaitingForGenerationConfirmation {
+			return Ok(());
+		}
+
+		if !is_completion_broadcasted {
+			return self.complete_on_master(&mut *data);
+		}
+
+		// wait for confirmation from all other nodes
+		if data.nodes.values().any(|n| !n.completion_confirmed) {
+			return Ok(());
+		}
+
+		self.complete(&mut *data);
+		Ok(())
+	}
+
+	/// Keys dissemination (KD) phase.
+	fn disseminate_keys(&self, data: &mut SessionData) -> Result<(), Error> {
+		// pick t + 1 random numbers as polynomial coefficients
+		let threshold = data.threshold.expect("threshold is filled on initialization phase; KD phase follows initialization phase; qed");
+		let polynom = math_bls::generate_random_polynom(threshold)?;
+		let commitments = math_bls::compute_polynom_commitments(&polynom)?;
+
+		// compute secret subshare for every node
+		for (node, node_data) in data.nodes.iter_mut() {
+			let secret_subshare = math_bls::compute_polynom(&polynom, &node_data.id_number)?;
+			if node != self.node() {
+				self.cluster.send(&node, Message::BlsGeneration(BlsGenerationMessage::BlsKeysDissemination(BlsKeysDissemination {
+					session: self.id.clone().into(),
+					session_nonce: self.nonce,
+					secret_subshare: secret_subshare.into(),
+					commitments: commitments.iter().cloned().map(Into::into).collect(),
+				})))?;
+			} else {
+				node_data.secret_subshare = Some(secret_subshare);
+				node_data.commitments = Some(commitments.clone());
+			}
+		}
+
+		data.polynom = Some(polynom);
+		data.state = SessionState::WaitingForKeysDissemination;
+
+		Ok(())
+	}
+
+	/// Keys verification (KV) phase.
+	fn verify_keys(&self, data: &mut SessionData) -> Result<(), Error> {
+		// check that other nodes have sent us values, matching their commitments
+		let self_id_number = data.nodes[self.node()].id_number.clone();
+		for node_data in data.nodes.values() {
+			let secret_subshare = node_data.secret_subshare.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed");
+			let commitments = node_data.commitments.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed");
+			if !math_bls::verify_secret_subshare(&self_id_number, secret_subshare, commitments)? {
+				return Err(Error::InvalidMessage);
+			}
+		}
+
+		// compute secret share && joint public
+		let secret_share = math_bls::compute_secret_sum(data.nodes.values()
+			.map(|n| n.secret_subshare.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed")))?;
+		let joint_public = math_bls::compute_public_sum(data.nodes.values()
+			.map(|n| &n.commitments.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed")[0]))?;
+
+		data.secret_share = Some(secret_share);
+		data.joint_public = Some(joint_public.clone());
+		data.nodes.get_mut(self.node()).expect("node is always qualified by himself; qed").joint_public = Some(joint_public);
+		data.state = SessionState::WaitingForGenerationConfirmation;
+
+		Ok(())
+	}
+
+	/// Complete session on master node, if every node has computed the same joint public.
+	fn complete_on_master(&self, data: &mut SessionData) -> Result<(), Error> {
+		if data.nodes.values().any(|n| n.joint_public.is_none()) {
+			return Ok(());
+		}
+		if data.nodes.values().any(|n| n.joint_public != data.joint_public) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// save key and then ask other nodes to save it too
+		let joint_public = data.joint_public.clone().expect("checked above; qed");
+		self.save_key(data)?;
+		data.nodes.get_mut(self.node()).expect("node is always qualified by himself; qed").completion_confirmed = true;
+		self.cluster.broadcast(Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionCompleted(BlsGenerationSessionCompleted {
+			session: self.id.clone().into(),
+			session_nonce: self.nonce,
+			joint_public: joint_public.into(),
+		})))?;
+
+		// single-node cluster => nothing to wait for
+		if data.nodes.len() == 1 {
+			self.complete(data);
+		}
+
+		Ok(())
+	}
+
+	/// Save generated key to the key storage.
+	fn save_key(&self, data: &SessionData) -> Result<(), Error> {
+		let joint_public = data.joint_public.clone().expect("joint_public is filled in KV phase; we are at the end of KV phase; qed");
+		let secret_share = data.secret_share.clone().expect("secret_share is filled in KV phase; we are at the end of KV phase; qed");
+		if let Some(ref key_storage) = self.key_storage {
+			let mut key_version = DocumentKeyShareVersion::new(
+				data.nodes.iter().map(|(node_id, node_data)| (node_id.clone(), node_data.id_number.clone())).collect(),
+				secret_share.clone(),
+			);
+
+			// public shares of all nodes are used later to verify partial signatures, computed by these nodes
+			for (node_id, node_data) in &data.nodes {
+				let commitments = data.nodes.values()
+					.map(|n| n.commitments.as_ref().expect("keys received on KD phase; KV phase follows KD phase; qed").as_slice());
+				let public_share = math_bls::compute_node_public_share(&node_data.id_number, commitments)?;
+				key_version.public_shares.insert(node_id.clone(), math_bls::into_key_share_public(&public_share)?);
+			}
+
+			key_storage.insert(self.id.clone(), DocumentKeyShare {
+				author: data.author.clone().expect("author is filled in initialization phase; KV phase follows initialization phase; qed"),
+				threshold: data.threshold.expect("threshold is filled in initialization phase; KV phase follows initialization phase; qed"),
+				public: math_bls::into_key_share_public(&joint_public)?,
+				common_point: None,
+				encrypted_point: None,
+				curve: KeyCurve::Bls12381,
+				ecdsa_scheme: Default::default(),
+				exported: false,
+				document_key_slots: Default::default(),
+				expiration: None,
+				versions: vec![key_version],
+			})?;
+		}
+
+		Ok(())
+	}
+
+	/// Complete session.
+	fn complete(&self, data: &mut SessionData) {
+		let joint_public = data.joint_public.clone().expect("joint_public is filled in KV phase; we are at the end of KV phase; qed");
+		let secret_share = data.secret_share.clone().expect("secret_share is filled in KV phase; we are at the end of KV phase; qed");
+		data.state = SessionState::Finished;
+		data.joint_public_and_secret = Some(Ok((joint_public.clone(), secret_share)));
+		self.completed.send(Ok(joint_public));
+	}
+
+	/// Fail session with given error.
+	fn fail(&self, data: &mut SessionData, error: Error) {
+		data.state = SessionState::Failed;
+		data.joint_public_and_secret = Some(Err(error.clone()));
+		self.completed.send(Err(error));
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = ();
+	type SuccessfulResult = Bytes;
+
+	fn type_name() -> &'static str {
+		"BLS generation"
+	}
+
+	fn id(&self) -> SessionId {
+		self.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		// all nodes are required for generation session
+		// => fail without check
+		warn!("{}: BLS generation session failed because {} connection has timeouted", self.node(), node);
+
+		self.fail(&mut *self.data.lock(), Error::NodeDisconnected);
+	}
+
+	fn on_session_timeout(&self) {
+		warn!("{}: BLS generation session failed with timeout", self.node());
+
+		self.fail(&mut *self.data.lock(), Error::NodeDisconnected);
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in generation session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.cluster.broadcast(Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionError(
+				BlsGenerationSessionError {
+					session: self.id.clone().into(),
+					session_nonce: self.nonce,
+					error: error.clone().into(),
+				},
+			)));
+		}
+
+		self.fail(&mut *self.data.lock(), error);
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::BlsGeneration(ref message) => self.process_message(sender, message),
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl NodeData {
+	fn with_id_number(initialized: bool, node_id_number: Secret) -> Self {
+		NodeData {
+			initialized,
+			id_number: node_id_number,
+			secret_subshare: None,
+			commitments: None,
+			joint_public: None,
+			completion_confirmed: false,
+		}
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "BLS generation session {} on {}", self.id, self.self_node_id)
+	}
+}
+
+fn check_threshold(threshold: usize, nodes: &BTreeSet<NodeId>) -> Result<(), Error> {
+	// at least threshold + 1 nodes are required to collectively sign message
+	if threshold >= nodes.len() {
+		return Err(Error::NotEnoughNodesForThreshold);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+	use std::sync::Arc;
+	use bytes::Bytes;
+	use crypto::publickey::Secret;
+	use key_server_cluster::{NodeId, Error, KeyStorage, KeyCurve, SessionId};
+	use key_server_cluster::cluster::tests::{MessageLoop as ClusterMessageLoop, make_clusters_and_preserve_sessions};
+	use key_server_cluster::math_bls;
+	use key_server_cluster::message::{Message, BlsGenerationMessage, BlsKeysDissemination};
+	use super::{SessionImpl, SessionState};
+
+	#[derive(Debug)]
+	pub struct MessageLoop(pub ClusterMessageLoop);
+
+	impl MessageLoop {
+		pub fn new(num_nodes: usize) -> Self {
+			MessageLoop(make_clusters_and_preserve_sessions(num_nodes))
+		}
+
+		pub fn init(self, threshold: usize) -> Result<Self, Error> {
+			self.0.cluster(0).client().new_bls_generation_session(SessionId::from([1u8; 32]), Default::default(), threshold)
+				.map(|_| self)
+		}
+
+		pub fn session_at(&self, idx: usize) -> Arc<SessionImpl> {
+			self.0.sessions(idx).bls_generation_sessions.first().unwrap()
+		}
+
+		pub fn session_of(&self, node: &NodeId) -> Arc<SessionImpl> {
+			self.0.sessions_of(node).bls_generation_sessions.first().unwrap()
+		}
+
+		pub fn joint_public(&self) -> Bytes {
+			self.session_at(0).joint_public_and_secret().unwrap().unwrap().0
+		}
+
+		pub fn compute_joint_secret(&self, t: usize) -> Secret {
+			let id_numbers: Vec<_> = (0..t + 1).map(|i| {
+				let session = self.session_at(i);
+				let data = session.data.lock();
+				data.nodes[session.node()].id_number.clone()
+			}).collect();
+			let secret_shares: Vec<_> = (0..t + 1)
+				.map(|i| self.session_at(i).joint_public_and_secret().unwrap().unwrap().1)
+				.collect();
+			math_bls::compute_joint_secret_from_shares(
+				&secret_shares.iter().collect::<Vec<_>>(),
+				&id_numbers.iter().collect::<Vec<_>>(),
+			).unwrap()
+		}
+	}
+
+	#[test]
+	fn initializes_in_cluster_of_single_node() {
+		let ml = MessageLoop::new(1).init(0).unwrap();
+		assert_eq!(ml.session_at(0).state(), SessionState::Finished);
+		let joint_public = ml.joint_public();
+		assert_eq!(math_bls::compute_public_share(&ml.compute_joint_secret(0)).unwrap(), joint_public);
+	}
+
+	#[test]
+	fn fails_to_initialize_if_threshold_is_wrong() {
+		assert_eq!(MessageLoop::new(2).init(2).unwrap_err(), Error::NotEnoughNodesForThreshold);
+	}
+
+	#[test]
+	fn fails_to_initialize_when_already_initialized() {
+		let ml = MessageLoop::new(2).init(0).unwrap();
+		assert_eq!(
+			ml.session_at(0).initialize(Default::default(), 0, ml.0.nodes().into()),
+			Err(Error::InvalidStateForRequest),
+		);
+	}
+
+	#[test]
+	fn fails_to_accept_keys_dissemination_if_not_waiting_for_it() {
+		let ml = MessageLoop::new(2).init(0).unwrap();
+		assert_eq!(ml.session_at(0).on_keys_dissemination(ml.0.node(1), &BlsKeysDissemination {
+			session: [1u8; 32].into(),
+			session_nonce: 0,
+			secret_subshare: math_bls::generate_random_scalar().unwrap().into(),
+			commitments: vec![vec![0u8; 48].into()],
+		}), Err(Error::TooEarlyForRequest));
+	}
+
+	#[test]
+	fn fails_to_accept_keys_dissemination_with_invalid_subshare() {
+		let ml = MessageLoop::new(3).init(1).unwrap();
+
+		// corrupt first subshare, sent by master node
+		let to = loop {
+			let (from, to, msg) = ml.0.take_message().unwrap();
+			match msg {
+				Message::BlsGeneration(BlsGenerationMessage::BlsKeysDissemination(mut msg)) => {
+					msg.secret_subshare = math_bls::generate_random_scalar().unwrap().into();
+					ml.0.process_message(from, to, Message::BlsGeneration(BlsGenerationMessage::BlsKeysDissemination(msg)));
+					break to;
+				},
+				msg => ml.0.process_message(from, to, msg),
+			}
+		};
+		ml.0.loop_until(|| ml.0.is_empty());
+
+		assert_eq!(ml.session_of(&to).state(), SessionState::Failed);
+		assert_eq!(ml.session_at(0).state(), SessionState::Failed);
+		assert!(ml.0.key_storage(0).get(&SessionId::from([1u8; 32])).unwrap().is_none());
+	}
+
+	#[test]
+	fn generates_key_in_cluster() {
+		let test_cases = [(0, 1), (0, 3), (1, 3), (2, 5), (3, 5)];
+		for &(threshold, num_nodes) in &test_cases {
+			let ml = MessageLoop::new(num_nodes).init(threshold).unwrap();
+			ml.0.loop_until(|| ml.0.is_empty());
+
+			// check that all nodes have finished session && computed the same public
+			let joint_public = ml.joint_public();
+			for i in 0..num_nodes {
+				assert_eq!(ml.session_at(i).state(), SessionState::Finished);
+				assert_eq!(ml.session_at(i).joint_public_and_secret().unwrap().unwrap().0, joint_public);
+
+				let key_share = ml.0.key_storage(i).get(&SessionId::from([1u8; 32])).unwrap().unwrap();
+				assert_eq!(key_share.curve, KeyCurve::Bls12381);
+				assert_eq!(key_share.threshold, threshold);
+				assert_eq!(math_bls::from_key_share_public(&key_share.public).unwrap(), joint_public);
+
+				// check that public shares of all nodes are stored
+				let secret_share = ml.session_at(i).joint_public_and_secret().unwrap().unwrap().1;
+				for j in 0..num_nodes {
+					let public_share = &ml.0.key_storage(j).get(&SessionId::from([1u8; 32])).unwrap().unwrap().versions[0].public_shares[&ml.0.node(i)];
+					assert_eq!(math_bls::from_key_share_public(public_share).unwrap(), math_bls::compute_public_share(&secret_share).unwrap());
+				}
+			}
+
+			// check that joint secret, recovered from t + 1 shares, corresponds to joint public
+			assert_eq!(math_bls::compute_public_share(&ml.compute_joint_secret(threshold)).unwrap(), joint_public);
+		}
+	}
+
+	#[test]
+	fn master_completes_session_after_key_is_saved_on_all_nodes() {
+		let ml = MessageLoop::new(3).init(2).unwrap();
+		ml.0.loop_until(|| ml.session_at(0).state() == SessionState::Finished);
+
+		for i in 0..3 {
+			assert!(ml.0.key_storage(i).get(&SessionId::from([1u8; 32])).unwrap().is_some());
+		}
+	}
+}
@@ -0,0 +1,572 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::sync::Arc;
+use futures::Oneshot;
+use parking_lot::Mutex;
+use key_server_cluster::{Error, NodeId, SessionId, Requester, KeyStorage, DocumentKeyShare};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
+use key_server_cluster::message::{Message, KeyDeletionMessage, InitializeKeyDeletionSession,
+	ConfirmKeyDeletionInitialization, CommitKeyDeletion, ConfirmKeyDeletion, KeyDeletionSessionError};
+
+/// Key deletion session.
+/// Brief overview:
+/// 1) initialization: master node (which has received request for deleting the key && must hold the key share)
+///    initializes the session on all other holders of the key share (of any known key version)
+/// 2) every holder checks that the requester is the author of the key
+/// 3) when all holders have confirmed initialization, master node asks every holder to delete its key share
+/// 4) every holder removes its key share, leaving a tombstone, which prevents the key from being generated again
+/// Nodes that aren't holding the key share aren't participating in the session. If any holder is offline, the session
+/// fails (with NodeDisconnected) before any share is deleted, so the request must be retried when holder is back online.
+pub struct SessionImpl {
+	/// Unique session id.
+	id: SessionId,
+	/// Public identifier of this node.
+	self_node_id: NodeId,
+	/// Public identifier of master node.
+	master_node_id: NodeId,
+	/// Key share of this node (if any).
+	key_share: Option<DocumentKeyShare>,
+	/// Key storage.
+	key_storage: Arc<dyn KeyStorage>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	nonce: u64,
+	/// Session completion signal.
+	completed: CompletionSignal<()>,
+	/// Mutable session data.
+	data: Mutex<SessionData>,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// SessionImpl identifier.
+	pub id: SessionId,
+	/// Id of node, on which this session is running.
+	pub self_node_id: NodeId,
+	/// Id of node, which has started this session.
+	pub master_node_id: NodeId,
+	/// Key share of this node (if any).
+	pub key_share: Option<DocumentKeyShare>,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Cluster
+	pub cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+/// Mutable data of key deletion session.
+#[derive(Debug)]
+struct SessionData {
+	/// Current state of the session.
+	state: SessionState,
+	/// Nodes-specific data.
+	nodes: BTreeMap<NodeId, NodeData>,
+	/// Key deletion session result.
+	result: Option<Result<(), Error>>,
+}
+
+/// Mutable node-specific data.
+#[derive(Debug, Clone)]
+struct NodeData {
+	/// Flag marking that node has confirmed session initialization.
+	pub initialization_confirmed: bool,
+	/// Flag marking that node has deleted its key share.
+	pub deletion_confirmed: bool,
+}
+
+/// Key deletion session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	// === Initialization states ===
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for every other key share holder to confirm initialization.
+	WaitingForInitializationConfirm,
+
+	// === Deletion states ===
+	/// Slave node waits for deletion request from master node.
+	WaitingForCommit,
+	/// Master node waits for every other key share holder to confirm deletion.
+	WaitingForCommitConfirm,
+
+	// === Final states of the session ===
+	/// Key is deleted.
+	Finished,
+	/// Failed to delete key.
+	Failed,
+}
+
+impl SessionImpl {
+	/// Create new key deletion session.
+	pub fn new(params: SessionParams) -> (Self, Oneshot<Result<(), Error>>) {
+		let (completed, oneshot) = CompletionSignal::new();
+		(SessionImpl {
+			id: params.id,
+			self_node_id: params.self_node_id,
+			master_node_id: params.master_node_id,
+			key_share: params.key_share,
+			key_storage: params.key_storage,
+			cluster: params.cluster,
+			nonce: params.nonce,
+			completed,
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				nodes: BTreeMap::new(),
+				result: None,
+			}),
+		}, oneshot)
+	}
+
+	/// Get this node Id.
+	pub fn node(&self) -> &NodeId {
+		&self.self_node_id
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Start new session initialization. This must be called on master node.
+	pub fn initialize(&self, requester: Requester) -> Result<(), Error> {
+		debug_assert!(self.self_node_id == self.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the key hasn't been deleted yet
+		if self.key_storage.is_tombstoned(&self.id) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
+
+		// holders of the key share are only known to nodes, holding the key share
+		let key_share = self.key_share.as_ref().ok_or(Error::ServerKeyIsNotFound)?;
+
+		// check that the requester is the author of the key
+		self.check_requester(&requester)?;
+
+		// every holder of the key share must confirm deletion => all holders must be connected
+		let holders: BTreeSet<_> = key_share.versions.iter()
+			.flat_map(|version| version.id_numbers.keys().cloned())
+			.chain(::std::iter::once(self.node().clone()))
+			.collect();
+		let connected_nodes = self.cluster.nodes();
+		if holders.iter().any(|n| !connected_nodes.contains(n)) {
+			return Err(Error::NodeDisconnected);
+		}
+
+		// update state
+		data.state = SessionState::WaitingForInitializationConfirm;
+		data.nodes.extend(holders.into_iter().map(|n| (n.clone(), NodeData {
+			initialization_confirmed: &n == self.node(),
+			deletion_confirmed: &n == self.node(),
+		})));
+
+		// start initialization
+		if data.nodes.len() > 1 {
+			for node in data.nodes.keys().filter(|n| *n != self.node()) {
+				self.cluster.send(node, Message::KeyDeletion(KeyDeletionMessage::InitializeKeyDeletionSession(InitializeKeyDeletionSession {
+					session: self.id.clone().into(),
+					session_nonce: self.nonce,
+					requester: requester.clone().into(),
+				})))?;
+			}
+
+			Ok(())
+		} else {
+			self.delete_key_share()?;
+			Self::complete(&mut data, &self.completed);
+
+			Ok(())
+		}
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: NodeId, message: &InitializeKeyDeletionSession) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		if sender != self.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that the requester is the author of the key
+		self.check_requester(&message.requester.clone().into())?;
+
+		// update state
+		data.state = SessionState::WaitingForCommit;
+
+		// send confirmation back to master node
+		self.cluster.send(&sender, Message::KeyDeletion(KeyDeletionMessage::ConfirmKeyDeletionInitialization(ConfirmKeyDeletionInitialization {
+			session: self.id.clone().into(),
+			session_nonce: self.nonce,
+			has_key_share: self.key_share.is_some(),
+		})))
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: NodeId, message: &ConfirmKeyDeletionInitialization) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check if all holders have confirmed initialization
+		{
+			let node_data = data.nodes.get_mut(&sender).ok_or(Error::InvalidMessage)?;
+			if !message.has_key_share {
+				warn!("{}: key share holder {} has no share of key {}", self.node(), sender, self.id);
+			}
+			node_data.initialization_confirmed = true;
+		}
+		if !data.nodes.values().all(|n| n.initialization_confirmed) {
+			return Ok(());
+		}
+
+		// all holders have agreed to delete the key => delete own share and ask others to do the same
+		self.delete_key_share()?;
+		data.state = SessionState::WaitingForCommitConfirm;
+		for node in data.nodes.keys().filter(|n| *n != self.node()) {
+			self.cluster.send(node, Message::KeyDeletion(KeyDeletionMessage::CommitKeyDeletion(CommitKeyDeletion {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+			})))?;
+		}
+
+		Ok(())
+	}
+
+	/// When key deletion request is received.
+	pub fn on_commit(&self, sender: NodeId, message: &CommitKeyDeletion) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		if sender != self.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForCommit {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// delete key share
+		self.delete_key_share()?;
+
+		// update state
+		data.state = SessionState::Finished;
+
+		// send confirmation back to master node
+		self.cluster.send(&sender, Message::KeyDeletion(KeyDeletionMessage::ConfirmKeyDeletion(ConfirmKeyDeletion {
+			session: self.id.clone().into(),
+			session_nonce: self.nonce,
+		})))
+	}
+
+	/// When key deletion confirmation message is received.
+	pub fn on_confirm_deletion(&self, sender: NodeId, message: &ConfirmKeyDeletion) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForCommitConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check if all holders have deleted their shares
+		data.nodes.get_mut(&sender)
+			.ok_or(Error::InvalidMessage)?
+			.deletion_confirmed = true;
+		if !data.nodes.values().all(|n| n.deletion_confirmed) {
+			return Ok(());
+		}
+
+		Self::complete(&mut data, &self.completed);
+
+		Ok(())
+	}
+
+	/// Check that the requester is the author of the key.
+	fn check_requester(&self, requester: &Requester) -> Result<(), Error> {
+		if let Some(key_share) = self.key_share.as_ref() {
+			let requester_address = requester.address(&self.id).map_err(Error::InsufficientRequesterData)?;
+			if key_share.author != requester_address {
+				return Err(Error::AccessDenied);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Delete key share of this node.
+	fn delete_key_share(&self) -> Result<(), Error> {
+		self.key_storage.tombstone(&self.id)
+	}
+
+	/// Complete session successfully.
+	fn complete(data: &mut SessionData, completed: &CompletionSignal<()>) {
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(()));
+		completed.send(Ok(()));
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = ();
+	type SuccessfulResult = ();
+
+	fn type_name() -> &'static str {
+		"key deletion"
+	}
+
+	fn id(&self) -> SessionId {
+		self.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		let mut data = self.data.lock();
+
+		// only master node && key share holders are participating in the session
+		if *node != self.master_node_id && !data.nodes.contains_key(node) {
+			return;
+		}
+
+		warn!("{}: key deletion session failed because {} connection has timeouted", self.node(), node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(Error::NodeDisconnected));
+		self.completed.send(Err(Error::NodeDisconnected));
+	}
+
+	fn on_session_timeout(&self) {
+		let mut data = self.data.lock();
+
+		warn!("{}: key deletion session failed with timeout", self.node());
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(Error::NodeDisconnected));
+		self.completed.send(Err(Error::NodeDisconnected));
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in key deletion session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.cluster.broadcast(Message::KeyDeletion(KeyDeletionMessage::KeyDeletionSessionError(KeyDeletionSessionError {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		let mut data = self.data.lock();
+
+		warn!("{}: key deletion session failed with error: {} from {}", self.node(), error, node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error.clone()));
+		self.completed.send(Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		if Some(self.nonce) != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&Message::KeyDeletion(ref message) => match message {
+				&KeyDeletionMessage::InitializeKeyDeletionSession(ref message) =>
+					self.on_initialize_session(sender.clone(), message),
+				&KeyDeletionMessage::ConfirmKeyDeletionInitialization(ref message) =>
+					self.on_confirm_initialization(sender.clone(), message),
+				&KeyDeletionMessage::CommitKeyDeletion(ref message) =>
+					self.on_commit(sender.clone(), message),
+				&KeyDeletionMessage::ConfirmKeyDeletion(ref message) =>
+					self.on_confirm_deletion(sender.clone(), message),
+				&KeyDeletionMessage::KeyDeletionSessionError(ref message) => {
+					self.on_session_error(sender, message.error.clone());
+					Ok(())
+				},
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "Key deletion session {} on {}", self.id, self.self_node_id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use futures::Future;
+	use crypto::publickey::{Random, Generator, KeyPair, public_to_address, sign};
+	use node_key_pair::PlainNodeKeyPair;
+	use key_server_cluster::{Error, KeyStorage, SessionId};
+	use key_server_cluster::cluster::ClusterClient;
+	use key_server_cluster::cluster::tests::{MessageLoop, make_clusters};
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use key_server_cluster::generation_session::SessionState as GenerationSessionState;
+
+	fn make_key(ml: &MessageLoop, author: &KeyPair) -> SessionId {
+		let key_id = SessionId::from([1u8; 32]);
+		let session = ml.cluster(0).client()
+			.new_generation_session(key_id, None, public_to_address(author.public()), 1, None).unwrap().session;
+		ml.loop_until(|| session.state() == GenerationSessionState::Finished
+			&& (0..ml.nodes().len()).all(|i| ml.sessions(i).generation_sessions.is_empty()));
+		key_id
+	}
+
+	fn delete_key(ml: &MessageLoop, key_id: SessionId, requester: &KeyPair) -> Result<(), Error> {
+		let requester = sign(requester.secret(), &key_id).unwrap();
+		let session = ml.cluster(0).client().new_key_deletion_session(key_id, requester.into())?;
+		let session_handle = session.session.clone();
+		ml.loop_until(|| session_handle.is_finished()
+			&& (0..ml.nodes().len()).all(|i| ml.sessions(i).key_deletion_sessions.is_empty()));
+		session.into_wait_future().wait()
+	}
+
+	#[test]
+	fn key_is_deleted_on_all_nodes() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = make_key(&ml, &author);
+
+		delete_key(&ml, key_id, &author).unwrap();
+		for i in 0..3 {
+			assert!(!ml.key_storage(i).contains(&key_id));
+			assert!(ml.key_storage(i).is_tombstoned(&key_id));
+		}
+	}
+
+	#[test]
+	fn key_is_deleted_when_node_without_key_share_is_offline() {
+		let mut ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = make_key(&ml, &author);
+
+		// new node doesn't hold the key share => it isn't required to confirm deletion
+		// (its id is greater than ids of other nodes, so that node 0 is still the master node)
+		let new_node_key_pair = loop {
+			let key_pair = Random.generate();
+			if ml.nodes().iter().all(|n| n < key_pair.public()) {
+				break key_pair;
+			}
+		};
+		let new_node = ml.include(Arc::new(PlainNodeKeyPair::new(new_node_key_pair)));
+		ml.isolate(new_node);
+
+		delete_key(&ml, key_id, &author).unwrap();
+		for i in 0..3 {
+			assert!(ml.key_storage(i).is_tombstoned(&key_id));
+		}
+		assert!(!ml.key_storage(new_node).is_tombstoned(&key_id));
+	}
+
+	#[test]
+	fn key_is_not_deleted_when_key_share_holder_is_offline() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = make_key(&ml, &author);
+
+		// every holder must confirm deletion => session fails before any share is deleted
+		ml.isolate(2);
+		assert_eq!(delete_key(&ml, key_id, &author), Err(Error::NodeDisconnected));
+		for i in 0..3 {
+			assert!(ml.key_storage(i).contains(&key_id));
+			assert!(!ml.key_storage(i).is_tombstoned(&key_id));
+		}
+	}
+
+	#[test]
+	fn deleted_key_is_not_generated_again() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = make_key(&ml, &author);
+		delete_key(&ml, key_id, &author).unwrap();
+
+		assert_eq!(ml.cluster(0).client().new_generation_session(key_id, None, Default::default(), 1, None).map(|_| ()),
+			Err(Error::ServerKeyIsDeleted));
+	}
+
+	#[test]
+	fn key_is_not_deleted_when_requester_is_not_author() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = make_key(&ml, &author);
+
+		assert_eq!(delete_key(&ml, key_id, &Random.generate()), Err(Error::AccessDenied));
+		for i in 0..3 {
+			assert!(ml.key_storage(i).contains(&key_id));
+			assert!(!ml.key_storage(i).is_tombstoned(&key_id));
+		}
+	}
+
+	#[test]
+	fn fails_to_delete_unknown_key() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+
+		assert_eq!(delete_key(&ml, SessionId::from([1u8; 32]), &author), Err(Error::ServerKeyIsNotFound));
+	}
+
+	#[test]
+	fn fails_to_delete_already_deleted_key() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = make_key(&ml, &author);
+		delete_key(&ml, key_id, &author).unwrap();
+
+		assert_eq!(delete_key(&ml, key_id, &author), Err(Error::ServerKeyIsDeleted));
+	}
+}
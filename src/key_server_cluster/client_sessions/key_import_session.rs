@@ -0,0 +1,674 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::iter::once;
+use std::sync::Arc;
+use futures::Oneshot;
+use parking_lot::Mutex;
+use crypto::publickey::{Public, Secret, ecies};
+use ethereum_types::Address;
+use key_server_cluster::{Error, NodeId, SessionId, KeyStorage, DocumentKeyShare, DocumentKeyShareVersion, KeyCurve,
+	EcdsaSigningScheme, SecretKeyPair, KeyImportData, ImportedKeyShare};
+use key_server_cluster::math;
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
+use key_server_cluster::message::{Message, KeyImportMessage, InitializeKeyImportSession,
+	ConfirmKeyImportInitialization, CommitKeyImport, ConfirmKeyImport, KeyImportSessionError};
+
+/// Key import session.
+/// Brief overview:
+/// 1) key owner (dealer) splits existing private key, using random polynomial of degree `threshold` with the key
+///    as the free coefficient. Every share is encrypted with public key of its owner. Commitments to coefficients
+///    of the polynomial are published along with the shares (see `prepare_key_import`)
+/// 2) initialization: master node (which has received the import request) sends every share owner its encrypted share
+/// 3) every share owner decrypts its share and verifies it against the commitments
+/// 4) when all share owners have confirmed that their shares are valid, master node asks them to store the shares
+/// 5) every share owner stores its key share, which is then used as a share of regular server key
+pub struct SessionImpl {
+	/// Unique session id.
+	id: SessionId,
+	/// Key pair of this node.
+	self_key_pair: Arc<dyn SecretKeyPair>,
+	/// Public identifier of master node.
+	master_node_id: NodeId,
+	/// Key storage.
+	key_storage: Arc<dyn KeyStorage>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	nonce: u64,
+	/// Session completion signal.
+	completed: CompletionSignal<Public>,
+	/// Mutable session data.
+	data: Mutex<SessionData>,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// SessionImpl identifier.
+	pub id: SessionId,
+	/// Key pair of this node.
+	pub self_key_pair: Arc<dyn SecretKeyPair>,
+	/// Id of node, which has started this session.
+	pub master_node_id: NodeId,
+	/// Key storage.
+	pub key_storage: Arc<dyn KeyStorage>,
+	/// Cluster
+	pub cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+/// Mutable data of key import session.
+#[derive(Debug)]
+struct SessionData {
+	/// Current state of the session.
+	state: SessionState,
+	/// Verified key share of this node, which is waiting to be stored.
+	key_share: Option<DocumentKeyShare>,
+	/// Public portion of the imported key.
+	public: Option<Public>,
+	/// Share owners-specific data.
+	nodes: BTreeMap<NodeId, NodeData>,
+	/// Key import session result.
+	result: Option<Result<Public, Error>>,
+}
+
+/// Mutable node-specific data.
+#[derive(Debug, Clone)]
+struct NodeData {
+	/// Flag marking that node has verified its key share.
+	pub initialization_confirmed: bool,
+	/// Flag marking that node has stored its key share.
+	pub import_confirmed: bool,
+}
+
+/// Key import session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	// === Initialization states ===
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for every share owner to verify its key share.
+	WaitingForInitializationConfirm,
+
+	// === Import states ===
+	/// Slave node waits for import request from master node.
+	WaitingForCommit,
+	/// Master node waits for every share owner to store its key share.
+	WaitingForCommitConfirm,
+
+	// === Final states of the session ===
+	/// Key is imported.
+	Finished,
+	/// Failed to import key.
+	Failed,
+}
+
+/// Split existing private key into shares of given key servers. This is supposed to be called by the
+/// key owner (dealer). Result could be passed to any of key servers to start the key import session.
+pub fn prepare_key_import(key_id: &SessionId, secret: &Secret, threshold: usize, nodes: &BTreeSet<NodeId>) -> Result<KeyImportData, Error> {
+	if threshold >= nodes.len() {
+		return Err(Error::NotEnoughNodesForThreshold);
+	}
+
+	// the key is the free coefficient of the dealer polynomial
+	let mut polynom = math::generate_random_polynom(threshold)?;
+	polynom[0] = secret.clone();
+
+	let share_commitments = math::prepare_share_proof(&polynom)?;
+	let shares = nodes.iter()
+		.map(|node| {
+			let id_number = math::generate_random_scalar()?;
+			let share = math::compute_polynom(&polynom, &id_number)?;
+			let encrypted_share = ecies::encrypt(node, key_id.as_bytes(), share.as_bytes())?;
+			Ok((node.clone(), ImportedKeyShare {
+				id_number,
+				encrypted_share,
+			}))
+		})
+		.collect::<Result<_, Error>>()?;
+
+	Ok(KeyImportData {
+		share_commitments,
+		shares,
+	})
+}
+
+impl SessionImpl {
+	/// Create new key import session.
+	pub fn new(params: SessionParams) -> (Self, Oneshot<Result<Public, Error>>) {
+		let (completed, oneshot) = CompletionSignal::new();
+		(SessionImpl {
+			id: params.id,
+			self_key_pair: params.self_key_pair,
+			master_node_id: params.master_node_id,
+			key_storage: params.key_storage,
+			cluster: params.cluster,
+			nonce: params.nonce,
+			completed,
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				key_share: None,
+				public: None,
+				nodes: BTreeMap::new(),
+				result: None,
+			}),
+		}, oneshot)
+	}
+
+	/// Get this node Id.
+	pub fn node(&self) -> &NodeId {
+		self.self_key_pair.public()
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Start new session initialization. This must be called on master node.
+	pub fn initialize(&self, author: Address, ecdsa_scheme: EcdsaSigningScheme, key: KeyImportData) -> Result<(), Error> {
+		debug_assert!(*self.node() == self.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// every share owner must be connected to this node
+		let connected_nodes = self.cluster.nodes();
+		if key.shares.keys().any(|node| !connected_nodes.contains(node)) {
+			return Err(Error::NodeDisconnected);
+		}
+
+		// check dealer data before sending it to other nodes
+		let id_numbers: BTreeMap<_, _> = key.shares.iter()
+			.map(|(node, share)| (node.clone(), share.id_number.clone()))
+			.collect();
+		check_import_data(&key.share_commitments, &id_numbers)?;
+
+		// update state
+		data.state = SessionState::WaitingForInitializationConfirm;
+		data.public = Some(key.share_commitments[0].clone());
+		data.nodes.extend(id_numbers.keys().map(|node| (node.clone(), NodeData {
+			initialization_confirmed: false,
+			import_confirmed: false,
+		})));
+
+		// verify own share (if any)
+		if let Some(share) = key.shares.get(self.node()) {
+			data.key_share = Some(self.verify_key_share(author.clone(), ecdsa_scheme, &key.share_commitments,
+				&id_numbers, &share.encrypted_share)?);
+			data.nodes.get_mut(self.node())
+				.expect("nodes are filled with share owners above; qed")
+				.initialization_confirmed = true;
+		}
+
+		// send shares to other owners
+		for (node, share) in key.shares.into_iter().filter(|&(ref node, _)| node != self.node()) {
+			self.cluster.send(&node, Message::KeyImport(KeyImportMessage::InitializeKeyImportSession(InitializeKeyImportSession {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				author: author.clone().into(),
+				share_commitments: key.share_commitments.iter().cloned().map(Into::into).collect(),
+				id_numbers: id_numbers.iter().map(|(node, id_number)| (node.clone().into(), id_number.clone().into())).collect(),
+				encrypted_share: share.encrypted_share.into(),
+				ecdsa_scheme,
+			})))?;
+		}
+
+		// if this node is the only share owner => store the key right now
+		self.try_commit(&mut data)
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: NodeId, message: &InitializeKeyImportSession) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		if sender != self.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// decrypt && verify key share
+		let share_commitments: Vec<Public> = message.share_commitments.iter().cloned().map(Into::into).collect();
+		let id_numbers: BTreeMap<NodeId, Secret> = message.id_numbers.iter()
+			.map(|(node, id_number)| (node.clone().into(), id_number.clone().into()))
+			.collect();
+		data.key_share = Some(self.verify_key_share(message.author.clone().into(), message.ecdsa_scheme, &share_commitments,
+			&id_numbers, &message.encrypted_share)?);
+
+		// update state
+		data.state = SessionState::WaitingForCommit;
+
+		// send confirmation back to master node
+		self.cluster.send(&sender, Message::KeyImport(KeyImportMessage::ConfirmKeyImportInitialization(ConfirmKeyImportInitialization {
+			session: self.id.clone().into(),
+			session_nonce: self.nonce,
+		})))
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: NodeId, message: &ConfirmKeyImportInitialization) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		data.nodes.get_mut(&sender)
+			.ok_or(Error::InvalidMessage)?
+			.initialization_confirmed = true;
+
+		self.try_commit(&mut data)
+	}
+
+	/// When key import request is received.
+	pub fn on_commit(&self, sender: NodeId, message: &CommitKeyImport) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		if sender != self.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForCommit {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// store key share
+		let key_share = data.key_share.take()
+			.expect("key share is verified on initialization; commit follows initialization; qed");
+		self.key_storage.insert(self.id.clone(), key_share)?;
+
+		// update state
+		data.state = SessionState::Finished;
+
+		// send confirmation back to master node
+		self.cluster.send(&sender, Message::KeyImport(KeyImportMessage::ConfirmKeyImport(ConfirmKeyImport {
+			session: self.id.clone().into(),
+			session_nonce: self.nonce,
+		})))
+	}
+
+	/// When key import confirmation message is received.
+	pub fn on_confirm_import(&self, sender: NodeId, message: &ConfirmKeyImport) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(&sender != self.node());
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForCommitConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		data.nodes.get_mut(&sender)
+			.ok_or(Error::InvalidMessage)?
+			.import_confirmed = true;
+
+		self.try_complete(&mut data);
+
+		Ok(())
+	}
+
+	/// Decrypt key share of this node and verify it against dealer commitments.
+	fn verify_key_share(
+		&self,
+		author: Address,
+		ecdsa_scheme: EcdsaSigningScheme,
+		share_commitments: &[Public],
+		id_numbers: &BTreeMap<NodeId, Secret>,
+		encrypted_share: &[u8],
+	) -> Result<DocumentKeyShare, Error> {
+		let threshold = check_import_data(share_commitments, id_numbers)?;
+		let id_number = id_numbers.get(self.node()).ok_or(Error::InvalidMessage)?;
+		let secret_share = self.self_key_pair.decrypt(self.id.as_bytes(), encrypted_share)
+			.ok()
+			.and_then(|secret_share| Secret::copy_from_slice(&secret_share))
+			.ok_or(Error::InvalidMessage)?;
+		if !math::share_proof_verification(threshold, id_number, &secret_share, share_commitments)? {
+			return Err(Error::InvalidMessage);
+		}
+
+		// public shares of all nodes are used later to verify partial results, computed by these nodes
+		let mut key_version = DocumentKeyShareVersion::new(id_numbers.clone(), secret_share);
+		for (node, id_number) in id_numbers {
+			let public_share = math::compute_node_public_share(id_number, once(share_commitments))?;
+			key_version.public_shares.insert(node.clone(), public_share);
+		}
+
+		Ok(DocumentKeyShare {
+			author,
+			threshold,
+			public: share_commitments[0].clone(),
+			common_point: None,
+			encrypted_point: None,
+			versions: vec![key_version],
+			curve: KeyCurve::Secp256k1,
+			ecdsa_scheme,
+			exported: false,
+			document_key_slots: Default::default(),
+			expiration: None,
+		})
+	}
+
+	/// Store own key share and ask other share owners to do the same, if all shares are verified.
+	fn try_commit(&self, data: &mut SessionData) -> Result<(), Error> {
+		if !data.nodes.values().all(|n| n.initialization_confirmed) {
+			return Ok(());
+		}
+
+		data.state = SessionState::WaitingForCommitConfirm;
+		if let Some(key_share) = data.key_share.take() {
+			self.key_storage.insert(self.id.clone(), key_share)?;
+			data.nodes.get_mut(self.node())
+				.expect("key share is only verified if this node is the share owner; qed")
+				.import_confirmed = true;
+		}
+
+		for node in data.nodes.keys().filter(|n| *n != self.node()) {
+			self.cluster.send(node, Message::KeyImport(KeyImportMessage::CommitKeyImport(CommitKeyImport {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+			})))?;
+		}
+
+		self.try_complete(data);
+
+		Ok(())
+	}
+
+	/// Complete session successfully, if all share owners have stored their shares.
+	fn try_complete(&self, data: &mut SessionData) {
+		if !data.nodes.values().all(|n| n.import_confirmed) {
+			return;
+		}
+
+		let public = data.public.clone().expect("public is filled on initialization; completion follows initialization; qed");
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(public.clone()));
+		self.completed.send(Ok(public));
+	}
+}
+
+/// Check dealer data, returning threshold of the imported key.
+fn check_import_data(share_commitments: &[Public], id_numbers: &BTreeMap<NodeId, Secret>) -> Result<usize, Error> {
+	// there are threshold + 1 coefficients in the dealer polynomial
+	let threshold = share_commitments.len().checked_sub(1).ok_or(Error::InvalidMessage)?;
+	if threshold >= id_numbers.len() {
+		return Err(Error::NotEnoughNodesForThreshold);
+	}
+
+	// share at zero point is the key itself => id numbers must be non-zero and distinct
+	let zero = math::zero_scalar();
+	let distinct_id_numbers: BTreeSet<_> = id_numbers.values().map(|id_number| **id_number).collect();
+	if id_numbers.values().any(|id_number| *id_number == zero) || distinct_id_numbers.len() != id_numbers.len() {
+		return Err(Error::InvalidMessage);
+	}
+
+	Ok(threshold)
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = ();
+	type SuccessfulResult = Public;
+
+	fn type_name() -> &'static str {
+		"key import"
+	}
+
+	fn id(&self) -> SessionId {
+		self.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		let mut data = self.data.lock();
+
+		warn!("{}: key import session failed because {} connection has timeouted", self.node(), node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(Error::NodeDisconnected));
+		self.completed.send(Err(Error::NodeDisconnected));
+	}
+
+	fn on_session_timeout(&self) {
+		let mut data = self.data.lock();
+
+		warn!("{}: key import session failed with timeout", self.node());
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(Error::NodeDisconnected));
+		self.completed.send(Err(Error::NodeDisconnected));
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in key import session is considered fatal
+		// => broadcast error if error occured on this node
+		if node == self.node() {
+			// do not bother processing send error, as we already processing error
+			let _ = self.cluster.broadcast(Message::KeyImport(KeyImportMessage::KeyImportSessionError(KeyImportSessionError {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		let mut data = self.data.lock();
+
+		warn!("{}: key import session failed with error: {} from {}", self.node(), error, node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error.clone()));
+		self.completed.send(Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		if Some(self.nonce) != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&Message::KeyImport(ref message) => match message {
+				&KeyImportMessage::InitializeKeyImportSession(ref message) =>
+					self.on_initialize_session(sender.clone(), message),
+				&KeyImportMessage::ConfirmKeyImportInitialization(ref message) =>
+					self.on_confirm_initialization(sender.clone(), message),
+				&KeyImportMessage::CommitKeyImport(ref message) =>
+					self.on_commit(sender.clone(), message),
+				&KeyImportMessage::ConfirmKeyImport(ref message) =>
+					self.on_confirm_import(sender.clone(), message),
+				&KeyImportMessage::KeyImportSessionError(ref message) => {
+					self.on_session_error(sender, message.error.clone());
+					Ok(())
+				},
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "Key import session {} on {}", self.id, self.node())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::Future;
+	use crypto::publickey::{Random, Generator, KeyPair, Public, public_to_address, sign};
+	use ethereum_types::H256;
+	use key_server_cluster::{Error, KeyStorage, SessionId, KeyImportData, SchnorrSigningScheme, EcdsaSigningScheme};
+	use key_server_cluster::math;
+	use key_server_cluster::cluster::ClusterClient;
+	use key_server_cluster::cluster::tests::{MessageLoop, make_clusters};
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use key_server_cluster::generation_session::SessionState as GenerationSessionState;
+	use super::prepare_key_import;
+
+	fn import_key(ml: &MessageLoop, key_id: SessionId, author: &KeyPair, key: KeyImportData) -> Result<Public, Error> {
+		let session = ml.cluster(0).client().new_key_import_session(key_id, public_to_address(author.public()), key)?;
+		let session_handle = session.session.clone();
+		ml.loop_until(|| session_handle.is_finished()
+			&& (0..ml.nodes().len()).all(|i| ml.sessions(i).key_import_sessions.is_empty()));
+		session.into_wait_future().wait()
+	}
+
+	#[test]
+	fn key_is_imported_on_all_nodes() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_pair = Random.generate();
+		let key_id = SessionId::from([1u8; 32]);
+		let key = prepare_key_import(&key_id, key_pair.secret(), 1, &ml.nodes()).unwrap();
+
+		assert_eq!(import_key(&ml, key_id, &author, key), Ok(key_pair.public().clone()));
+
+		// every node stores the share of imported key
+		let key_shares: Vec<_> = (0..3).map(|i| ml.key_storage(i).get(&key_id).unwrap().unwrap()).collect();
+		for key_share in &key_shares {
+			assert_eq!(key_share.author, public_to_address(author.public()));
+			assert_eq!(key_share.threshold, 1);
+			assert_eq!(key_share.public, *key_pair.public());
+			// MtA-based ECDSA is disabled in test clusters
+			assert_eq!(key_share.ecdsa_scheme, EcdsaSigningScheme::NonceInversion);
+		}
+
+		// any threshold + 1 shares are enough to restore the key
+		let id_numbers: Vec<_> = (0..3).map(|i| key_shares[i].last_version().unwrap().id_numbers[&ml.node(i)].clone()).collect();
+		let secret_shares: Vec<_> = key_shares.iter().map(|k| k.last_version().unwrap().secret_share.clone()).collect();
+		let joint_secret = math::compute_joint_secret_from_shares(1,
+			&[&secret_shares[0], &secret_shares[2]], &[&id_numbers[0], &id_numbers[2]]).unwrap();
+		assert_eq!(joint_secret, *key_pair.secret());
+	}
+
+	#[test]
+	fn imported_key_is_used_for_signing() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_pair = Random.generate();
+		let key_id = SessionId::from([1u8; 32]);
+		let key = prepare_key_import(&key_id, key_pair.secret(), 1, &ml.nodes()).unwrap();
+		import_key(&ml, key_id, &author, key).unwrap();
+
+		let message_hash = H256::from_low_u64_be(42);
+		let requester = sign(author.secret(), &key_id).unwrap();
+		let session = ml.cluster(0).client().new_schnorr_signing_session(key_id, requester.into(), Vec::new(), None,
+			SchnorrSigningScheme::Legacy, vec![message_hash]).unwrap();
+		let session_handle = session.session.clone();
+		ml.loop_until(|| session_handle.is_finished()
+			&& (0..3).all(|i| ml.sessions(i).schnorr_signing_sessions.is_empty()));
+		let signatures = session.into_wait_future().wait().unwrap();
+		assert!(math::verify_schnorr_signature(key_pair.public(), &signatures[0], &message_hash).unwrap());
+	}
+
+	#[test]
+	fn key_is_imported_on_single_node() {
+		let ml = make_clusters(1);
+		let author = Random.generate();
+		let key_pair = Random.generate();
+		let key_id = SessionId::from([1u8; 32]);
+		let key = prepare_key_import(&key_id, key_pair.secret(), 0, &ml.nodes()).unwrap();
+
+		assert_eq!(import_key(&ml, key_id, &author, key), Ok(key_pair.public().clone()));
+		assert!(ml.key_storage(0).contains(&key_id));
+	}
+
+	#[test]
+	fn key_is_not_imported_when_share_is_invalid() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_pair = Random.generate();
+		let key_id = SessionId::from([1u8; 32]);
+		let mut key = prepare_key_import(&key_id, key_pair.secret(), 1, &ml.nodes()).unwrap();
+
+		// share of node 1 is encrypted with public key of node 2
+		let other_share = key.shares[&ml.node(2)].encrypted_share.clone();
+		key.shares.get_mut(&ml.node(1)).unwrap().encrypted_share = other_share;
+
+		assert_eq!(import_key(&ml, key_id, &author, key), Err(Error::InvalidMessage));
+		for i in 0..3 {
+			assert!(!ml.key_storage(i).contains(&key_id));
+		}
+	}
+
+	#[test]
+	fn key_is_not_imported_when_commitments_are_invalid() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = SessionId::from([1u8; 32]);
+		let mut key = prepare_key_import(&key_id, Random.generate().secret(), 1, &ml.nodes()).unwrap();
+		key.share_commitments[0] = Random.generate().public().clone();
+
+		assert_eq!(import_key(&ml, key_id, &author, key), Err(Error::InvalidMessage));
+		for i in 0..3 {
+			assert!(!ml.key_storage(i).contains(&key_id));
+		}
+	}
+
+	#[test]
+	fn key_is_not_imported_when_threshold_is_too_large() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = SessionId::from([1u8; 32]);
+		let mut key = prepare_key_import(&key_id, Random.generate().secret(), 1, &ml.nodes()).unwrap();
+		key.shares.remove(&ml.node(2));
+		key.shares.remove(&ml.node(1));
+
+		assert_eq!(import_key(&ml, key_id, &author, key), Err(Error::NotEnoughNodesForThreshold));
+	}
+
+	#[test]
+	fn key_is_not_imported_when_already_generated() {
+		let ml = make_clusters(3);
+		let author = Random.generate();
+		let key_id = SessionId::from([1u8; 32]);
+		let session = ml.cluster(0).client()
+			.new_generation_session(key_id, None, public_to_address(author.public()), 1, None).unwrap().session;
+		ml.loop_until(|| session.state() == GenerationSessionState::Finished
+			&& (0..ml.nodes().len()).all(|i| ml.sessions(i).generation_sessions.is_empty()));
+
+		let key = prepare_key_import(&key_id, Random.generate().secret(), 1, &ml.nodes()).unwrap();
+		assert_eq!(import_key(&ml, key_id, &author, key), Err(Error::ServerKeyAlreadyGenerated));
+	}
+}
@@ -17,6 +17,13 @@
 pub mod decryption_session;
 pub mod encryption_session;
 pub mod generation_session;
+pub mod generation_session_bls;
+pub mod generation_session_eddsa;
+pub mod key_deletion_session;
+pub mod key_import_session;
 pub mod random_point_generation_session;
+pub mod randomness_beacon_session;
+pub mod signing_session_bls;
 pub mod signing_session_ecdsa;
+pub mod signing_session_eddsa;
 pub mod signing_session_schnorr;
@@ -0,0 +1,536 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter, Error as FmtError};
+use std::sync::Arc;
+use crypto::publickey::{Public, Signature, recover};
+use ethereum_types::H256;
+use futures::Oneshot;
+use hash::keccak;
+use parking_lot::Mutex;
+use tiny_keccak::Keccak;
+use blockchain::SigningKeyPair;
+use key_server_cluster::{Error, NodeId, SessionId, RandomnessBeaconOutput};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::{ClusterSession, CompletionSignal};
+use key_server_cluster::message::{Message, RandomnessBeaconMessage, InitializeRandomnessBeaconSession,
+	ConfirmRandomnessBeaconInitialization, RandomnessBeaconPointGeneration, RandomnessBeaconTranscriptSignature,
+	RandomnessBeaconError, RandomPointGenerationMessage};
+use key_server_cluster::random_point_generation_session::{SessionImpl as RandomPointGenerationSession,
+	SessionTransport as RandomPointGenerationSessionTransport};
+
+/// Domain of the transcript hash, signed with node key.
+const TRANSCRIPT_DOMAIN: &'static [u8] = b"secretstore:beacon";
+
+/// Randomness beacon session.
+/// Brief overview:
+/// 1) initialization: master node (which has received beacon request) initializes the session on all connected nodes
+/// 2) when all nodes have confirmed initialization, participants are running EC-Rand() (random point generation session),
+///    so that neither participant is able to bias the generated point
+/// 3) every participant signs transcript of the session (beacon id, generated point && participants) with its node key
+///    and sends the signature to master node
+/// 4) master node checks that all signatures are made over the same transcript && returns the point with signed transcript
+pub struct SessionImpl {
+	/// Unique session id (beacon id).
+	id: SessionId,
+	/// Public identifier of this node.
+	self_node_id: NodeId,
+	/// Public identifier of master node.
+	master_node_id: NodeId,
+	/// Key pair of this node, used to sign the transcript.
+	self_key_pair: Arc<dyn SigningKeyPair>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	nonce: u64,
+	/// Session completion signal.
+	completed: CompletionSignal<RandomnessBeaconOutput>,
+	/// Mutable session data.
+	data: Mutex<SessionData>,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams {
+	/// SessionImpl identifier.
+	pub id: SessionId,
+	/// Id of node, which has started this session.
+	pub master_node_id: NodeId,
+	/// Key pair of this node.
+	pub self_key_pair: Arc<dyn SigningKeyPair>,
+	/// Cluster
+	pub cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+/// Mutable data of randomness beacon session.
+struct SessionData {
+	/// Current state of the session.
+	state: SessionState,
+	/// All session participants.
+	participants: BTreeSet<NodeId>,
+	/// Participants, which have confirmed session initialization (on master node only).
+	confirmed: BTreeSet<NodeId>,
+	/// Random point generation session.
+	point_generation: RandomPointGenerationSession,
+	/// Hash of the session transcript.
+	transcript_hash: Option<H256>,
+	/// Participants signatures of the transcript (on master node only).
+	signatures: BTreeMap<NodeId, Signature>,
+	/// Randomness beacon session result.
+	result: Option<Result<RandomnessBeaconOutput, Error>>,
+}
+
+/// Randomness beacon session state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+	/// Every node starts in this state.
+	WaitingForInitialization,
+	/// Master node waits for every other node to confirm initialization.
+	WaitingForInitializationConfirm,
+	/// Random point is being generated.
+	WaitingForPointGeneration,
+	/// Master node waits for transcript signatures from every participant.
+	WaitingForTranscriptSignatures,
+	/// Session is completed.
+	Finished,
+	/// Session has failed.
+	Failed,
+}
+
+/// Random point generation transport.
+struct PointGenerationTransport {
+	/// Session id.
+	id: SessionId,
+	/// Session nonce.
+	nonce: u64,
+	/// Cluster.
+	cluster: Arc<dyn Cluster>,
+}
+
+impl SessionImpl {
+	/// Create new randomness beacon session.
+	pub fn new(params: SessionParams) -> (Self, Oneshot<Result<RandomnessBeaconOutput, Error>>) {
+		let (completed, oneshot) = CompletionSignal::new();
+		let self_node_id = params.self_key_pair.public().clone();
+		let point_generation = RandomPointGenerationSession::new(self_node_id.clone(), Arc::new(PointGenerationTransport {
+			id: params.id.clone(),
+			nonce: params.nonce,
+			cluster: params.cluster.clone(),
+		}));
+		(SessionImpl {
+			id: params.id,
+			self_node_id: self_node_id,
+			master_node_id: params.master_node_id,
+			self_key_pair: params.self_key_pair,
+			cluster: params.cluster,
+			nonce: params.nonce,
+			completed,
+			data: Mutex::new(SessionData {
+				state: SessionState::WaitingForInitialization,
+				participants: BTreeSet::new(),
+				confirmed: BTreeSet::new(),
+				point_generation: point_generation,
+				transcript_hash: None,
+				signatures: BTreeMap::new(),
+				result: None,
+			}),
+		}, oneshot)
+	}
+
+	/// Get session state.
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state.clone()
+	}
+
+	/// Start new session initialization. This must be called on master node.
+	pub fn initialize(&self) -> Result<(), Error> {
+		debug_assert!(self.self_node_id == self.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// all connected nodes are participating
+		data.participants = self.cluster.nodes();
+		data.confirmed.insert(self.self_node_id.clone());
+		data.state = SessionState::WaitingForInitializationConfirm;
+
+		// start initialization
+		if data.participants.len() > 1 {
+			let participants = data.participants.iter().cloned().map(Into::into).collect();
+			self.cluster.broadcast(Message::RandomnessBeacon(RandomnessBeaconMessage::InitializeRandomnessBeaconSession(InitializeRandomnessBeaconSession {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				participants: participants,
+			})))
+		} else {
+			self.start_point_generation(&mut *data)
+		}
+	}
+
+	/// When session initialization message is received.
+	pub fn on_initialize_session(&self, sender: NodeId, message: &InitializeRandomnessBeaconSession) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(sender != self.self_node_id);
+
+		if sender != self.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitialization {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check participants
+		let participants: BTreeSet<NodeId> = message.participants.iter().cloned().map(Into::into).collect();
+		if !participants.contains(&self.self_node_id) || !participants.contains(&sender) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// update state
+		data.participants = participants;
+		data.state = SessionState::WaitingForPointGeneration;
+
+		// send confirmation back to master node
+		self.cluster.send(&sender, Message::RandomnessBeacon(RandomnessBeaconMessage::ConfirmRandomnessBeaconInitialization(ConfirmRandomnessBeaconInitialization {
+			session: self.id.clone().into(),
+			session_nonce: self.nonce,
+		})))
+	}
+
+	/// When session initialization confirmation message is received.
+	pub fn on_confirm_initialization(&self, sender: NodeId, message: &ConfirmRandomnessBeaconInitialization) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(sender != self.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::WaitingForInitializationConfirm {
+			return Err(Error::InvalidStateForRequest);
+		}
+		if !data.participants.contains(&sender) || !data.confirmed.insert(sender) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// start point generation when all participants have confirmed initialization
+		if data.confirmed.len() != data.participants.len() {
+			return Ok(());
+		}
+
+		self.start_point_generation(&mut *data)
+	}
+
+	/// When random point generation message is received.
+	pub fn on_point_generation(&self, sender: NodeId, message: &RandomnessBeaconPointGeneration) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(sender != self.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		match data.state {
+			SessionState::WaitingForInitialization | SessionState::WaitingForInitializationConfirm =>
+				return Err(Error::TooEarlyForRequest),
+			SessionState::WaitingForPointGeneration => (),
+			_ => return Err(Error::InvalidStateForRequest),
+		}
+		if !data.participants.contains(&sender) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// slave nodes are starting point generation when the first point generation message is received
+		if !data.point_generation.is_started() {
+			let participants = data.participants.clone();
+			data.point_generation.start(participants)?;
+		}
+		data.point_generation.process_message(&sender, &message.message)?;
+
+		self.on_point_generation_progress(&mut *data)
+	}
+
+	/// When transcript signature is received.
+	pub fn on_transcript_signature(&self, sender: NodeId, message: &RandomnessBeaconTranscriptSignature) -> Result<(), Error> {
+		debug_assert!(self.id == *message.session);
+		debug_assert!(sender != self.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// signature could be received before random point is generated on master node
+		if self.self_node_id != self.master_node_id
+			|| (data.state != SessionState::WaitingForPointGeneration && data.state != SessionState::WaitingForTranscriptSignatures) {
+			return Err(Error::InvalidStateForRequest);
+		}
+		if !data.participants.contains(&sender) || data.signatures.contains_key(&sender) {
+			return Err(Error::InvalidMessage);
+		}
+
+		data.signatures.insert(sender, message.signature.clone().into());
+		self.try_complete(&mut *data)
+	}
+
+	/// Start random point generation on master node.
+	fn start_point_generation(&self, data: &mut SessionData) -> Result<(), Error> {
+		data.state = SessionState::WaitingForPointGeneration;
+		let participants = data.participants.clone();
+		data.point_generation.start(participants)?;
+		self.on_point_generation_progress(data)
+	}
+
+	/// Sign the transcript if random point has been generated.
+	fn on_point_generation_progress(&self, data: &mut SessionData) -> Result<(), Error> {
+		let point = match data.point_generation.generated_point() {
+			Some(point) => point,
+			None => return Ok(()),
+		};
+
+		let transcript_hash = compute_transcript_hash(&self.id, &point, &data.participants);
+		let signature = self.self_key_pair.sign(&transcript_hash)?;
+		data.transcript_hash = Some(transcript_hash);
+
+		if self.self_node_id != self.master_node_id {
+			data.state = SessionState::Finished;
+			return self.cluster.send(&self.master_node_id, Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconTranscriptSignature(RandomnessBeaconTranscriptSignature {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				signature: signature.into(),
+			})));
+		}
+
+		data.state = SessionState::WaitingForTranscriptSignatures;
+		data.signatures.insert(self.self_node_id.clone(), signature);
+		self.try_complete(data)
+	}
+
+	/// Complete session if all participants have signed the transcript.
+	fn try_complete(&self, data: &mut SessionData) -> Result<(), Error> {
+		if data.state != SessionState::WaitingForTranscriptSignatures || data.signatures.len() != data.participants.len() {
+			return Ok(());
+		}
+
+		// every participant must sign the same transcript
+		let transcript_hash = data.transcript_hash.clone().expect("transcript hash is computed before waiting for signatures; qed");
+		for (node, signature) in &data.signatures {
+			if recover(signature, &transcript_hash)? != *node {
+				warn!(target: "secretstore_net", "{}: randomness beacon transcript is signed incorrectly by {}",
+					self.self_node_id, node);
+				return Err(Error::InvalidMessage);
+			}
+		}
+
+		let point = data.point_generation.generated_point().expect("transcript hash is computed after point is generated; qed");
+		let output = RandomnessBeaconOutput {
+			beacon_id: self.id.clone(),
+			value: keccak(point.as_bytes()),
+			point: point,
+			transcript_hash: transcript_hash,
+			signatures: data.signatures.clone(),
+		};
+
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(output.clone()));
+		self.completed.send(Ok(output));
+
+		Ok(())
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionId;
+	type CreationData = ();
+	type SuccessfulResult = RandomnessBeaconOutput;
+
+	fn type_name() -> &'static str {
+		"randomness beacon"
+	}
+
+	fn id(&self) -> SessionId {
+		self.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.state == SessionState::Failed
+			|| data.state == SessionState::Finished
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		// every participant is required to generate random point
+		self.on_session_error(node, Error::NodeDisconnected)
+	}
+
+	fn on_session_timeout(&self) {
+		self.on_session_error(&self.self_node_id, Error::NodeDisconnected)
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		// error in randomness beacon session is considered fatal
+		// => broadcast error if error occured on this node
+		if *node == self.self_node_id {
+			// do not bother processing send error, as we already processing error
+			let _ = self.cluster.broadcast(Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconError(RandomnessBeaconError {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				error: error.clone().into(),
+			})));
+		}
+
+		let mut data = self.data.lock();
+
+		warn!(target: "secretstore_net", "{}: randomness beacon session failed: {} on {}",
+			self.self_node_id, error, node);
+
+		data.state = SessionState::Failed;
+		data.result = Some(Err(error.clone()));
+		self.completed.send(Err(error));
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		if Some(self.nonce) != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&Message::RandomnessBeacon(ref message) => match message {
+				&RandomnessBeaconMessage::InitializeRandomnessBeaconSession(ref message) =>
+					self.on_initialize_session(sender.clone(), message),
+				&RandomnessBeaconMessage::ConfirmRandomnessBeaconInitialization(ref message) =>
+					self.on_confirm_initialization(sender.clone(), message),
+				&RandomnessBeaconMessage::RandomnessBeaconPointGeneration(ref message) =>
+					self.on_point_generation(sender.clone(), message),
+				&RandomnessBeaconMessage::RandomnessBeaconTranscriptSignature(ref message) =>
+					self.on_transcript_signature(sender.clone(), message),
+				&RandomnessBeaconMessage::RandomnessBeaconError(ref message) => {
+					self.on_session_error(sender, message.error.clone());
+					Ok(())
+				},
+			},
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl RandomPointGenerationSessionTransport for PointGenerationTransport {
+	fn send(&self, node: &NodeId, message: RandomPointGenerationMessage) -> Result<(), Error> {
+		self.cluster.send(node, Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconPointGeneration(
+			RandomnessBeaconPointGeneration {
+				session: self.id.clone().into(),
+				session_nonce: self.nonce,
+				message,
+			}
+		)))
+	}
+}
+
+impl Debug for SessionImpl {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+		write!(f, "Randomness beacon session {} on {}", self.id, self.self_node_id)
+	}
+}
+
+/// Compute hash of the randomness beacon transcript:
+/// keccak256("secretstore:beacon" || beacon_id || point || sorted participants ids).
+/// Transcript is signed with node key, so it is domain-separated from all other data, signed with this key.
+pub fn compute_transcript_hash(beacon_id: &SessionId, point: &Public, participants: &BTreeSet<NodeId>) -> H256 {
+	let mut transcript_keccak = Keccak::new_keccak256();
+	transcript_keccak.update(TRANSCRIPT_DOMAIN);
+	transcript_keccak.update(beacon_id.as_bytes());
+	transcript_keccak.update(point.as_bytes());
+	for participant in participants {
+		transcript_keccak.update(participant.as_bytes());
+	}
+
+	let mut transcript_keccak_value = [0u8; 32];
+	transcript_keccak.finalize(&mut transcript_keccak_value);
+
+	transcript_keccak_value.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::Future;
+	use crypto::publickey::recover;
+	use hash::keccak;
+	use key_server_cluster::{Error, SessionId, RandomnessBeaconOutput};
+	use key_server_cluster::cluster::ClusterClient;
+	use key_server_cluster::cluster::tests::{MessageLoop, make_clusters};
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use super::compute_transcript_hash;
+
+	fn run_beacon(ml: &MessageLoop, beacon_id: SessionId) -> Result<RandomnessBeaconOutput, Error> {
+		let session = ml.cluster(0).client().new_randomness_beacon_session(beacon_id, Default::default())?;
+		let session_handle = session.session.clone();
+		ml.loop_until(|| session_handle.is_finished()
+			&& (0..ml.nodes().len()).all(|i| ml.sessions(i).randomness_beacon_sessions.is_empty()));
+		session.into_wait_future().wait()
+	}
+
+	#[test]
+	fn randomness_is_generated_and_signed_by_all_participants() {
+		let ml = make_clusters(3);
+		let beacon_id = SessionId::from([1u8; 32]);
+		let output = run_beacon(&ml, beacon_id).unwrap();
+
+		let participants = ml.nodes();
+		assert_eq!(output.beacon_id, beacon_id);
+		assert_eq!(output.value, keccak(output.point.as_bytes()));
+		assert_eq!(output.transcript_hash, compute_transcript_hash(&beacon_id, &output.point, &participants));
+		assert_eq!(output.signatures.keys().cloned().collect::<Vec<_>>(), participants.iter().cloned().collect::<Vec<_>>());
+		for (node, signature) in &output.signatures {
+			assert_eq!(recover(signature, &output.transcript_hash).unwrap(), *node);
+		}
+	}
+
+	#[test]
+	fn every_beacon_run_produces_different_randomness() {
+		let ml = make_clusters(3);
+		let output1 = run_beacon(&ml, SessionId::from([1u8; 32])).unwrap();
+		let output2 = run_beacon(&ml, SessionId::from([1u8; 32])).unwrap();
+		assert!(output1.value != output2.value);
+	}
+
+	#[test]
+	fn beacon_is_not_started_when_requester_has_no_access_to_beacon_id() {
+		let ml = make_clusters(3);
+		let beacon_id = SessionId::from([1u8; 32]);
+		ml.acl_storage(0).prohibit(Default::default(), beacon_id);
+		assert_eq!(run_beacon(&ml, beacon_id).unwrap_err(), Error::AccessDenied);
+	}
+
+	#[test]
+	fn beacon_is_not_started_when_the_same_beacon_is_in_progress() {
+		let ml = make_clusters(3);
+		let beacon_id = SessionId::from([1u8; 32]);
+		let _session = ml.cluster(0).client().new_randomness_beacon_session(beacon_id, Default::default()).unwrap();
+		assert_eq!(ml.cluster(0).client().new_randomness_beacon_session(beacon_id, Default::default()).map(|_| ()),
+			Err(Error::DuplicateSessionId));
+	}
+
+	#[test]
+	fn randomness_is_generated_on_single_node() {
+		let ml = make_clusters(1);
+		let output = run_beacon(&ml, SessionId::from([1u8; 32])).unwrap();
+		assert_eq!(output.signatures.len(), 1);
+	}
+}
@@ -21,25 +21,35 @@ use futures::Oneshot;
 use parking_lot::Mutex;
 use crypto::publickey::{Public, Secret, Signature, sign};
 use ethereum_types::H256;
-use key_server_cluster::{Error, NodeId, SessionId, SessionMeta, AclStorage, DocumentKeyShare, Requester};
+use key_server_cluster::{Error, NodeId, SessionId, SessionMeta, AclStorage, DocumentKeyShare, Requester, EcdsaSigningScheme,
+	KeyDerivationPath};
 use key_server_cluster::cluster::{Cluster};
 use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession, CompletionSignal};
 use key_server_cluster::generation_session::{SessionImpl as GenerationSession, SessionParams as GenerationSessionParams,
 	SessionState as GenerationSessionState};
 use key_server_cluster::math;
+use key_server_cluster::math_paillier::{self, EncryptedSecret, PaillierKeyPair, PaillierPublic};
 use key_server_cluster::message::{Message, EcdsaSigningMessage, EcdsaSigningConsensusMessage, EcdsaSignatureNonceGenerationMessage,
 	EcdsaInversionNonceGenerationMessage, EcdsaInversionZeroGenerationMessage, EcdsaSigningInversedNonceCoeffShare,
 	EcdsaRequestPartialSignature, EcdsaPartialSignature, EcdsaSigningSessionCompleted, GenerationMessage,
 	ConsensusMessage, EcdsaSigningSessionError, InitializeConsensusSession, ConfirmConsensusInitialization,
-	EcdsaSigningSessionDelegation, EcdsaSigningSessionDelegationCompleted};
-use key_server_cluster::jobs::job_session::JobTransport;
+	EcdsaSigningSessionDelegation, EcdsaSigningSessionDelegationCompleted, EcdsaMtaNonceGenerationStart,
+	EcdsaMtaPaillierPublic, EcdsaMtaEncryptedNonceShare, EcdsaMtaResponse};
+use key_server_cluster::jobs::job_session::{JobSessionState, JobTransport};
 use key_server_cluster::jobs::key_access_job::KeyAccessJob;
-use key_server_cluster::jobs::signing_job_ecdsa::{EcdsaPartialSigningRequest, EcdsaPartialSigningResponse, EcdsaSigningJob};
+use key_server_cluster::jobs::signing_job_ecdsa::{EcdsaPartialSigningRequest, EcdsaPartialSigningResponse, EcdsaSigningJob,
+	EcdsaSigningNonce};
 use key_server_cluster::jobs::consensus_session::{ConsensusSessionParams, ConsensusSessionState, ConsensusSession};
 
 /// Distributed ECDSA-signing session.
-/// Based on "A robust threshold elliptic curve digital signature providing a new verifiable secret sharing scheme" paper.
-/// WARNING: can only be used if 2*t < N is true for key generation scheme
+/// Protocol is selected using ECDSA signing scheme of the key:
+/// 1) NonceInversion: based on "A robust threshold elliptic curve digital signature providing a new verifiable secret sharing scheme" paper.
+/// WARNING: can only be used if 2*t < N is true for key generation scheme;
+/// 2) MultiplicativeToAdditive: t + 1 nodes are generating additive shares of inv(nonce) and gamma, and then every pair
+/// of nodes converts their multiplicative shares of inv(nonce) * gamma and inv(nonce) * secret into additive shares (see
+/// `math_paillier`). Sum of inv(nonce) * gamma shares is revealed to compute nonce public. Works for any t < N.
+/// If some of these t + 1 nodes fails, master selects other nodes and restarts nonce generation (next attempt).
+/// Every node generates new Paillier key for every attempt and proves that it is valid before MtA requests are sent.
 pub struct SessionImpl {
 	/// Session core.
 	core: SessionCore,
@@ -53,8 +63,12 @@ struct SessionCore {
 	pub meta: SessionMeta,
 	/// Signing session access key.
 	pub access_key: Secret,
-	/// Key share.
+	/// Key share (of the child key, if derivation path is not empty).
 	pub key_share: Option<DocumentKeyShare>,
+	/// Derivation path of the child key, used to sign message.
+	pub derivation_path: KeyDerivationPath,
+	/// ECDSA signing scheme of the key.
+	pub ecdsa_scheme: EcdsaSigningScheme,
 	/// Cluster which allows this node to send messages to other nodes in the cluster.
 	pub cluster: Arc<dyn Cluster>,
 	/// Session-level nonce.
@@ -82,6 +96,10 @@ struct SessionData {
 	pub inv_nonce_generation_session: Option<GenerationSession>,
 	/// Inversion zero generation session.
 	pub inv_zero_generation_session: Option<GenerationSession>,
+	/// Current attempt of MtA-based nonce generation.
+	pub mta_attempt: Option<MtaAttempt>,
+	/// MtA-based nonce generation.
+	pub mta_nonce_generation: Option<MtaNonceGeneration>,
 	/// Inversed nonce coefficient shares.
 	pub inversed_nonce_coeff_shares: Option<BTreeMap<NodeId, Secret>>,
 	/// Delegation status.
@@ -90,6 +108,38 @@ struct SessionData {
 	pub result: Option<Result<Signature, Error>>,
 }
 
+/// MtA-based nonce generation attempt.
+struct MtaAttempt {
+	/// Attempt number.
+	pub number: u32,
+	/// Nodes, participating in signing (including this node).
+	pub nodes: BTreeSet<NodeId>,
+	/// Verified Paillier public keys of other nodes, received during this attempt.
+	pub paillier_publics: BTreeMap<NodeId, PaillierPublic>,
+}
+
+/// MtA-based nonce generation data.
+struct MtaNonceGeneration {
+	/// Paillier key pair of this node.
+	pub paillier_key_pair: PaillierKeyPair,
+	/// Additive share of inv(nonce).
+	pub inv_nonce_share: Secret,
+	/// Additive share of inv(nonce), encrypted with Paillier key of this node (MtA request).
+	pub encrypted_inv_nonce_share: EncryptedSecret,
+	/// Additive share of gamma.
+	pub gamma_share: Secret,
+	/// Additive share of secret.
+	pub secret_share: Secret,
+	/// Gamma publics of all nodes, received so far (including this node).
+	pub gamma_publics: BTreeMap<NodeId, Public>,
+	/// Nodes, which have responded to our MtA requests.
+	pub responded_nodes: BTreeSet<NodeId>,
+	/// Additive share of inv(nonce) * gamma, accumulated so far.
+	pub delta_share: Secret,
+	/// Additive share of inv(nonce) * secret, accumulated so far.
+	pub inv_nonce_mul_secret_share: Secret,
+}
+
 /// Signing session state.
 #[derive(Debug, PartialEq)]
 pub enum SessionState {
@@ -109,8 +159,10 @@ pub struct SessionParams {
 	pub meta: SessionMeta,
 	/// Session access key.
 	pub access_key: Secret,
-	/// Key share.
+	/// Key share (of the child key, if derivation path is not empty).
 	pub key_share: Option<DocumentKeyShare>,
+	/// Derivation path of the child key, used to sign message.
+	pub derivation_path: KeyDerivationPath,
 	/// ACL storage.
 	pub acl_storage: Arc<dyn AclStorage>,
 	/// Cluster
@@ -129,6 +181,8 @@ struct SigningConsensusTransport {
 	nonce: u64,
 	/// Selected key version (on master node).
 	version: Option<H256>,
+	/// Derivation path of the child key.
+	derivation_path: KeyDerivationPath,
 	/// Cluster.
 	cluster: Arc<dyn Cluster>,
 }
@@ -182,15 +236,20 @@ impl SessionImpl {
 			access_key: params.access_key.clone(),
 			nonce: params.nonce,
 			version: None,
+			derivation_path: params.derivation_path.clone(),
 			cluster: params.cluster.clone(),
 		};
+		let ecdsa_scheme = params.key_share.as_ref().map(|ks| ks.ecdsa_scheme).unwrap_or_default();
 		let consensus_session = ConsensusSession::new(ConsensusSessionParams {
-			// this session requires responses from 2 * t nodes
+			// NonceInversion scheme requires responses from 2 * t nodes, MultiplicativeToAdditive scheme requires responses from t nodes
 			meta: SessionMeta {
 				id: params.meta.id,
 				master_node_id: params.meta.master_node_id,
 				self_node_id: params.meta.self_node_id,
-				threshold: params.meta.threshold * 2,
+				threshold: match ecdsa_scheme {
+					EcdsaSigningScheme::NonceInversion => params.meta.threshold * 2,
+					EcdsaSigningScheme::MultiplicativeToAdditive => params.meta.threshold,
+				},
 				configured_nodes_count: params.meta.configured_nodes_count,
 				connected_nodes_count: params.meta.connected_nodes_count,
 			},
@@ -207,6 +266,8 @@ impl SessionImpl {
 				meta: params.meta,
 				access_key: params.access_key,
 				key_share: params.key_share,
+				derivation_path: params.derivation_path,
+				ecdsa_scheme: ecdsa_scheme,
 				cluster: params.cluster,
 				nonce: params.nonce,
 				completed,
@@ -219,6 +280,8 @@ impl SessionImpl {
 				sig_nonce_generation_session: None,
 				inv_nonce_generation_session: None,
 				inv_zero_generation_session: None,
+				mta_attempt: None,
+				mta_nonce_generation: None,
 				inversed_nonce_coeff_shares: None,
 				delegation_status: None,
 				result: None,
@@ -253,6 +316,7 @@ impl SessionImpl {
 				.expect("requester is passed to master node on creation; session can be delegated from master node only; qed")
 				.clone().into(),
 			version: version.into(),
+			derivation_path: self.core.derivation_path.clone(),
 			message_hash: message_hash.into(),
 		})))?;
 		data.delegation_status = Some(DelegationStatus::DelegatedTo(master));
@@ -326,6 +390,14 @@ impl SessionImpl {
 				self.on_session_delegated(sender, message),
 			&EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(ref message) =>
 				self.on_session_delegation_completed(sender, message),
+			&EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(ref message) =>
+				self.on_mta_nonce_generation_start(sender, message),
+			&EcdsaSigningMessage::EcdsaMtaPaillierPublic(ref message) =>
+				self.on_mta_paillier_public(sender, message),
+			&EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(ref message) =>
+				self.on_mta_encrypted_nonce_share(sender, message),
+			&EcdsaSigningMessage::EcdsaMtaResponse(ref message) =>
+				self.on_mta_response(sender, message),
 		}
 	}
 
@@ -374,7 +446,10 @@ impl SessionImpl {
 		debug_assert!(sender != &self.core.meta.self_node_id);
 
 		let mut data = self.data.lock();
-		let is_establishing_consensus = data.consensus_session.state() == ConsensusSessionState::EstablishingConsensus;
+		// with MtA-based scheme, consensus could be lost (and then re-established) while nonce is generating
+		let is_establishing_consensus = data.consensus_session.state() == ConsensusSessionState::EstablishingConsensus
+			|| (self.core.ecdsa_scheme == EcdsaSigningScheme::MultiplicativeToAdditive
+				&& data.consensus_session.consensus_job().state() == JobSessionState::Active);
 
 		if let &ConsensusMessage::InitializeConsensusSession(ref msg) = &message.message {
 			let version = msg.version.clone().into();
@@ -391,6 +466,14 @@ impl SessionImpl {
 			return Ok(());
 		}
 
+		// start MtA-based nonce generation (this could also be a restart, if consensus has been re-established)
+		if self.core.ecdsa_scheme == EcdsaSigningScheme::MultiplicativeToAdditive {
+			let mta_attempt = Self::start_mta_attempt(&self.core, &mut *data)?;
+			drop(data);
+
+			return Self::generate_mta_nonce_shares(&self.core, &self.data, mta_attempt);
+		}
+
 		let key_share = self.core.key_share.as_ref()
 			.expect("this is master node; master node is selected so that it has key version; qed");
 		let key_version = key_share.version(data.version.as_ref()
@@ -399,6 +482,7 @@ impl SessionImpl {
 		let consensus_group = data.consensus_session.select_consensus_group()?.clone();
 		let mut other_consensus_group_nodes = consensus_group.clone();
 		other_consensus_group_nodes.remove(&self.core.meta.self_node_id);
+
 		let consensus_group_map: BTreeMap<_, _> = consensus_group.iter().map(|n| (n.clone(), key_version.id_numbers[n].clone())).collect();
 
 		// start generation of signature nonce
@@ -410,7 +494,7 @@ impl SessionImpl {
 					session_nonce: n,
 					message: m,
 				}));
-		sig_nonce_generation_session.initialize(Default::default(), Default::default(), false, key_share.threshold, consensus_group_map.clone().into())?;
+		sig_nonce_generation_session.initialize(Default::default(), Default::default(), false, key_share.threshold, None, Default::default(), consensus_group_map.clone().into())?;
 		data.sig_nonce_generation_session = Some(sig_nonce_generation_session);
 
 		// start generation of inversed nonce computation session
@@ -422,7 +506,7 @@ impl SessionImpl {
 					session_nonce: n,
 					message: m,
 				}));
-		inv_nonce_generation_session.initialize(Default::default(), Default::default(), false, key_share.threshold, consensus_group_map.clone().into())?;
+		inv_nonce_generation_session.initialize(Default::default(), Default::default(), false, key_share.threshold, None, Default::default(), consensus_group_map.clone().into())?;
 		data.inv_nonce_generation_session = Some(inv_nonce_generation_session);
 
 		// start generation of zero-secret shares for inversed nonce computation session
@@ -434,7 +518,7 @@ impl SessionImpl {
 					session_nonce: n,
 					message: m,
 				}));
-		inv_zero_generation_session.initialize(Default::default(), Default::default(), true, key_share.threshold * 2, consensus_group_map.clone().into())?;
+		inv_zero_generation_session.initialize(Default::default(), Default::default(), true, key_share.threshold * 2, None, Default::default(), consensus_group_map.clone().into())?;
 		data.inv_zero_generation_session = Some(inv_zero_generation_session);
 
 		data.state = SessionState::NoncesGenerating;
@@ -613,6 +697,185 @@ impl SessionImpl {
 		Ok(())
 	}
 
+	/// When MtA-based nonce generation is started by master node.
+	pub fn on_mta_nonce_generation_start(&self, sender: &NodeId, message: &EcdsaMtaNonceGenerationStart) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		if &self.core.meta.master_node_id != sender {
+			match data.delegation_status.as_ref() {
+				Some(&DelegationStatus::DelegatedTo(s)) if s == *sender => (),
+				_ => return Err(Error::InvalidMessage),
+			}
+		}
+		if self.core.ecdsa_scheme != EcdsaSigningScheme::MultiplicativeToAdditive {
+			return Err(Error::InvalidMessage);
+		}
+		// every restart comes with greater attempt number
+		if data.mta_attempt.as_ref().map(|attempt| attempt.number >= message.mta_attempt).unwrap_or(false) {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let nodes: BTreeSet<NodeId> = message.nodes.iter().cloned().map(Into::into).collect();
+		if !nodes.contains(sender) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// forget everything we have computed during previous attempt
+		data.mta_attempt = Some(MtaAttempt {
+			number: message.mta_attempt,
+			nodes: nodes,
+			paillier_publics: BTreeMap::new(),
+		});
+		data.mta_nonce_generation = None;
+		data.state = SessionState::NoncesGenerating;
+		drop(data);
+
+		Self::generate_mta_nonce_shares(&self.core, &self.data, message.mta_attempt)
+	}
+
+	/// When Paillier public key is received from other node.
+	pub fn on_mta_paillier_public(&self, sender: &NodeId, message: &EcdsaMtaPaillierPublic) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		if self.core.ecdsa_scheme != EcdsaSigningScheme::MultiplicativeToAdditive {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+		// other node could start nonce generation before we have received start message from master
+		match data.mta_attempt.as_ref() {
+			Some(attempt) if attempt.number == message.mta_attempt => {
+				if !attempt.nodes.contains(sender) {
+					return Err(Error::InvalidMessage);
+				}
+				if attempt.paillier_publics.contains_key(sender) {
+					return Err(Error::InvalidStateForRequest);
+				}
+			},
+			// message from previous attempt => ignore it
+			Some(attempt) if attempt.number > message.mta_attempt => return Ok(()),
+			_ => return Err(Error::TooEarlyForRequest),
+		}
+
+		let context = Self::mta_proof_context(&self.core, message.mta_attempt, sender);
+		let paillier_public = PaillierPublic::deserialize(&message.paillier_public, &message.paillier_public_proof, &context)?;
+		data.mta_attempt.as_mut()
+			.expect("checked above; qed")
+			.paillier_publics.insert(sender.clone(), paillier_public);
+
+		// if our own Paillier key is already generated, we could send MtA request to the sender
+		match data.mta_nonce_generation.is_some() {
+			true => Self::send_mta_encrypted_nonce_share(&self.core, &*data, sender),
+			false => Ok(()),
+		}
+	}
+
+	/// When encrypted share of inv(nonce) is received from other node.
+	pub fn on_mta_encrypted_nonce_share(&self, sender: &NodeId, message: &EcdsaMtaEncryptedNonceShare) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		if self.core.ecdsa_scheme != EcdsaSigningScheme::MultiplicativeToAdditive {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+		let response = {
+			let data = &mut *data;
+			let paillier_public = match data.mta_attempt.as_ref() {
+				Some(attempt) if attempt.number == message.mta_attempt => {
+					if !attempt.nodes.contains(sender) {
+						return Err(Error::InvalidMessage);
+					}
+
+					// MtA request is sent after Paillier public key
+					attempt.paillier_publics.get(sender).ok_or(Error::InvalidStateForRequest)?
+				},
+				// message from previous attempt => ignore it
+				Some(attempt) if attempt.number > message.mta_attempt => return Ok(()),
+				_ => return Err(Error::TooEarlyForRequest),
+			};
+
+			// MtA request is sent after our Paillier public key is received by the sender
+			let mta_nonce_generation = data.mta_nonce_generation.as_mut().ok_or(Error::InvalidStateForRequest)?;
+			if mta_nonce_generation.gamma_publics.contains_key(sender) {
+				return Err(Error::InvalidStateForRequest);
+			}
+
+			let sender_context = Self::mta_proof_context(&self.core, message.mta_attempt, sender);
+			math_paillier::verify_mta_request(paillier_public, mta_nonce_generation.paillier_key_pair.public(),
+				&message.encrypted_inv_nonce_share, &message.encrypted_inv_nonce_share_proof, &sender_context)?;
+
+			let context = Self::mta_proof_context(&self.core, message.mta_attempt, &self.core.meta.self_node_id);
+			let (encrypted_inv_nonce_mul_gamma, encrypted_inv_nonce_mul_gamma_proof, beta) = math_paillier::compute_mta_response(
+				paillier_public, &message.encrypted_inv_nonce_share, &mta_nonce_generation.gamma_share, &context)?;
+			let (encrypted_inv_nonce_mul_secret, encrypted_inv_nonce_mul_secret_proof, nu) = math_paillier::compute_mta_response(
+				paillier_public, &message.encrypted_inv_nonce_share, &mta_nonce_generation.secret_share, &context)?;
+			mta_nonce_generation.delta_share.add(&beta)?;
+			mta_nonce_generation.inv_nonce_mul_secret_share.add(&nu)?;
+			mta_nonce_generation.gamma_publics.insert(sender.clone(), message.gamma_public.clone().into());
+
+			EcdsaMtaResponse {
+				session: self.core.meta.id.clone().into(),
+				sub_session: self.core.access_key.clone().into(),
+				session_nonce: self.core.nonce,
+				mta_attempt: message.mta_attempt,
+				encrypted_inv_nonce_mul_gamma: encrypted_inv_nonce_mul_gamma.into(),
+				encrypted_inv_nonce_mul_gamma_proof: encrypted_inv_nonce_mul_gamma_proof.into(),
+				encrypted_inv_nonce_mul_secret: encrypted_inv_nonce_mul_secret.into(),
+				encrypted_inv_nonce_mul_secret_proof: encrypted_inv_nonce_mul_secret_proof.into(),
+			}
+		};
+
+		self.core.cluster.send(sender, Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaResponse(response)))?;
+		Self::on_mta_nonce_generation_progress(&self.core, &mut *data)
+	}
+
+	/// When response to our MtA request is received.
+	pub fn on_mta_response(&self, sender: &NodeId, message: &EcdsaMtaResponse) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+		{
+			match data.mta_attempt.as_ref() {
+				Some(attempt) if attempt.number == message.mta_attempt => {
+					if !attempt.nodes.contains(sender) {
+						return Err(Error::InvalidMessage);
+					}
+				},
+				// response to request of previous attempt => ignore it
+				Some(attempt) if attempt.number > message.mta_attempt => return Ok(()),
+				_ => return Err(Error::InvalidMessage),
+			}
+
+			let mta_nonce_generation = data.mta_nonce_generation.as_mut().ok_or(Error::InvalidStateForRequest)?;
+			if !mta_nonce_generation.responded_nodes.insert(sender.clone()) {
+				return Err(Error::InvalidStateForRequest);
+			}
+
+			let sender_context = Self::mta_proof_context(&self.core, message.mta_attempt, sender);
+			let alpha = math_paillier::compute_mta_share(&mta_nonce_generation.paillier_key_pair,
+				&mta_nonce_generation.encrypted_inv_nonce_share, &message.encrypted_inv_nonce_mul_gamma,
+				&message.encrypted_inv_nonce_mul_gamma_proof, &sender_context)?;
+			let mu = math_paillier::compute_mta_share(&mta_nonce_generation.paillier_key_pair,
+				&mta_nonce_generation.encrypted_inv_nonce_share, &message.encrypted_inv_nonce_mul_secret,
+				&message.encrypted_inv_nonce_mul_secret_proof, &sender_context)?;
+			mta_nonce_generation.delta_share.add(&alpha)?;
+			mta_nonce_generation.inv_nonce_mul_secret_share.add(&mu)?;
+		}
+
+		Self::on_mta_nonce_generation_progress(&self.core, &mut *data)
+	}
+
 	/// When inversed nonce share is received.
 	pub fn on_inversed_nonce_coeff_share(&self, sender: &NodeId, message: &EcdsaSigningInversedNonceCoeffShare) -> Result<(), Error> {
 		debug_assert!(self.core.meta.id == *message.session);
@@ -624,6 +887,14 @@ impl SessionImpl {
 		if self.core.meta.self_node_id != self.core.meta.master_node_id {
 			return Err(Error::InvalidMessage);
 		}
+		// share, computed during previous MtA attempt => ignore it
+		let current_mta_attempt = data.mta_attempt.as_ref().map(|attempt| attempt.number).unwrap_or_default();
+		if message.mta_attempt < current_mta_attempt {
+			return Ok(());
+		}
+		if message.mta_attempt > current_mta_attempt {
+			return Err(Error::InvalidMessage);
+		}
 		match data.state {
 			SessionState::WaitingForInversedNonceShares => (),
 			SessionState::NoncesGenerating => return Err(Error::TooEarlyForRequest),
@@ -631,7 +902,10 @@ impl SessionImpl {
 		}
 
 		let inversed_nonce_coeff = {
-			let consensus_group = data.consensus_session.select_consensus_group()?.clone();
+			let consensus_group = match data.mta_attempt.as_ref() {
+				Some(attempt) => attempt.nodes.clone(),
+				None => data.consensus_session.select_consensus_group()?.clone(),
+			};
 			{
 				let inversed_nonce_coeff_shares = data.inversed_nonce_coeff_shares.as_mut()
 					.expect("we are in WaitingForInversedNonceShares state; inversed_nonce_coeff_shares are filled before this state; qed");
@@ -653,12 +927,9 @@ impl SessionImpl {
 		let version = data.version.as_ref().ok_or(Error::InvalidMessage)?.clone();
 		let message_hash = data.message_hash
 			.expect("we are on master node; on master node message_hash is filled in initialize(); on_generation_message follows initialize; qed");
+		let nonce = Self::signing_nonce(&*data)?;
 
-		let nonce_exists_proof = "nonce is generated before signature is computed; we are in SignatureComputing state; qed";
-		let sig_nonce_public = data.sig_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.0;
-		let inv_nonce_share = data.inv_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.2;
-
-		self.core.disseminate_jobs(&mut data.consensus_session, &version, sig_nonce_public, inv_nonce_share, inversed_nonce_coeff, message_hash)
+		self.core.disseminate_jobs(&mut data.consensus_session, &version, nonce, inversed_nonce_coeff, message_hash)
 	}
 
 	/// When partial signature is requested.
@@ -681,14 +952,11 @@ impl SessionImpl {
 			return Err(Error::InvalidStateForRequest);
 		}
 
-		let nonce_exists_proof = "nonce is generated before signature is computed; we are in SignatureComputing state; qed";
-		let sig_nonce_public = data.sig_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.0;
-		let inv_nonce_share = data.inv_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.2;
-
+		let nonce = Self::signing_nonce(&*data)?;
 		let version = data.version.as_ref().ok_or(Error::InvalidMessage)?.clone();
 		let key_version = key_share.version(&version)?.hash.clone();
 
-		let signing_job = EcdsaSigningJob::new_on_slave(key_share.clone(), key_version, sig_nonce_public, inv_nonce_share)?;
+		let signing_job = EcdsaSigningJob::new_on_slave(key_share.clone(), key_version, nonce)?;
 		let signing_transport = self.core.signing_transport();
 
 		data.consensus_session.on_job_request(sender, EcdsaPartialSigningRequest {
@@ -748,12 +1016,45 @@ impl SessionImpl {
 			return Err(error);
 		}
 
+		// nonce shares are bound to the nodes that have generated them => if one of these nodes fails,
+		// MtA-based nonce generation must be restarted with other nodes
+		let is_mta_node_error = self.core.ecdsa_scheme == EcdsaSigningScheme::MultiplicativeToAdditive
+			&& self.core.meta.self_node_id == self.core.meta.master_node_id
+			&& data.result.is_none()
+			&& match (node, data.mta_attempt.as_ref()) {
+				(Some(node), Some(attempt)) => attempt.nodes.contains(node),
+				_ => false,
+			};
+
 		match {
 			match node {
 				Some(node) => data.consensus_session.on_node_error(node, error.clone()),
 				None => data.consensus_session.on_session_timeout(),
 			}
 		} {
+			Ok(false) if !is_mta_node_error => {
+				Ok(())
+			},
+			Ok(false) | Ok(true) if self.core.ecdsa_scheme == EcdsaSigningScheme::MultiplicativeToAdditive => {
+				// if consensus is lost, new attempt is started when it is re-established
+				data.consensus_session.reset_consensus_group();
+				if data.consensus_session.state() != ConsensusSessionState::ConsensusEstablished
+					|| data.consensus_session.consensus_job().state() != JobSessionState::Finished {
+					return Ok(());
+				}
+
+				let restart_result = Self::start_mta_attempt(&self.core, &mut *data);
+				drop(data);
+
+				match restart_result.and_then(|mta_attempt| Self::generate_mta_nonce_shares(&self.core, &self.data, mta_attempt)) {
+					Ok(()) => Ok(()),
+					Err(err) => {
+						warn!("{}: ECDSA signing session failed with error: {:?} from {:?}", &self.core.meta.self_node_id, error, node);
+						Self::set_signing_result(&self.core, &mut *self.data.lock(), Err(err.clone()));
+						Err(err)
+					},
+				}
+			},
 			Ok(false) => {
 				Ok(())
 			},
@@ -763,13 +1064,10 @@ impl SessionImpl {
 				let message_hash = data.message_hash.as_ref().cloned()
 					.expect("on_node_error returned true; this means that jobs must be REsent; this means that jobs already have been sent; jobs are sent when message_hash.is_some(); qed");
 
-				let nonce_exists_proof = "on_node_error returned true; this means that jobs must be REsent; this means that jobs already have been sent; jobs are sent when nonces generation has completed; qed";
-				let sig_nonce_public = data.sig_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.0;
-				let inv_nonce_share = data.inv_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.2;
-
+				let nonce = Self::signing_nonce(&*data)?;
 				let inversed_nonce_coeff = Self::compute_inversed_nonce_coeff(&self.core, &*data)?;
 
-				let disseminate_result = self.core.disseminate_jobs(&mut data.consensus_session, &version, sig_nonce_public, inv_nonce_share, inversed_nonce_coeff, message_hash);
+				let disseminate_result = self.core.disseminate_jobs(&mut data.consensus_session, &version, nonce, inversed_nonce_coeff, message_hash);
 				match disseminate_result {
 					Ok(()) => Ok(()),
 					Err(err) => {
@@ -843,20 +1141,195 @@ impl SessionImpl {
 			&& inv_zero_generation_session.state() == GenerationSessionState::Finished
 	}
 
+	/// Start new attempt of MtA-based nonce generation on master node: select nodes && ask them to generate nonce shares.
+	fn start_mta_attempt(core: &SessionCore, data: &mut SessionData) -> Result<u32, Error> {
+		let nodes = data.consensus_session.select_consensus_group()?.clone();
+		let number = data.mta_attempt.as_ref().map(|attempt| attempt.number + 1).unwrap_or_default();
+		for node in nodes.iter().filter(|n| **n != core.meta.self_node_id) {
+			core.cluster.send(node, Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(EcdsaMtaNonceGenerationStart {
+				session: core.meta.id.clone().into(),
+				sub_session: core.access_key.clone().into(),
+				session_nonce: core.nonce,
+				mta_attempt: number,
+				nodes: nodes.iter().cloned().map(Into::into).collect(),
+			})))?;
+		}
+
+		data.mta_attempt = Some(MtaAttempt {
+			number: number,
+			nodes: nodes,
+			paillier_publics: BTreeMap::new(),
+		});
+		data.mta_nonce_generation = None;
+		data.inversed_nonce_coeff_shares = None;
+		data.state = SessionState::NoncesGenerating;
+
+		Ok(number)
+	}
+
+	/// Generate Paillier key pair && its proof (this takes a while, so session data isn't locked meanwhile) && start
+	/// MtA-based nonce generation, unless given attempt has been superseded by other attempt.
+	fn generate_mta_nonce_shares(core: &SessionCore, data: &Mutex<SessionData>, mta_attempt: u32) -> Result<(), Error> {
+		let paillier_key_pair = PaillierKeyPair::generate()?;
+		let paillier_public_proof = paillier_key_pair.prove_public(&Self::mta_proof_context(core, mta_attempt, &core.meta.self_node_id))?;
+
+		let mut data = data.lock();
+		let is_current_attempt = data.mta_attempt.as_ref().map(|attempt| attempt.number == mta_attempt).unwrap_or(false);
+		if !is_current_attempt || data.mta_nonce_generation.is_some() || data.result.is_some() {
+			return Ok(());
+		}
+
+		Self::start_mta_nonce_generation(core, &mut *data, paillier_key_pair, paillier_public_proof)
+	}
+
+	/// Start MtA-based nonce generation: generate shares && send Paillier public key to other nodes. Encrypted share
+	/// of inv(nonce) is sent to every node, which Paillier public key has been already received.
+	fn start_mta_nonce_generation(core: &SessionCore, data: &mut SessionData, paillier_key_pair: PaillierKeyPair, paillier_public_proof: Vec<u8>) -> Result<(), Error> {
+		let key_share = core.key_share.as_ref().ok_or(Error::InvalidMessage)?;
+		let key_version = key_share.version(data.version.as_ref().ok_or(Error::InvalidMessage)?)?;
+		let (mta_attempt, nodes) = data.mta_attempt.as_ref()
+			.map(|attempt| (attempt.number, attempt.nodes.clone()))
+			.expect("start_mta_nonce_generation is called when MtA attempt is started; qed");
+		if nodes.len() != key_share.threshold + 1 || nodes.iter().any(|n| !key_version.id_numbers.contains_key(n))
+			|| !nodes.contains(&core.meta.self_node_id) {
+			return Err(Error::InvalidMessage);
+		}
+
+		let secret_share = math::compute_ecdsa_mta_secret_share(&key_version.secret_share,
+			&key_version.id_numbers[&core.meta.self_node_id],
+			nodes.iter().filter(|n| **n != core.meta.self_node_id).map(|n| &key_version.id_numbers[n]))?;
+		let inv_nonce_share = math::generate_random_scalar()?;
+		let gamma_share = math::generate_random_scalar()?;
+		let gamma_public = math::compute_public_share(&gamma_share)?;
+		let paillier_public = paillier_key_pair.public().serialize();
+		let encrypted_inv_nonce_share = math_paillier::encrypt_secret(paillier_key_pair.public(), &inv_nonce_share)?;
+		for node in nodes.iter().filter(|n| **n != core.meta.self_node_id) {
+			core.cluster.send(node, Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaPaillierPublic(EcdsaMtaPaillierPublic {
+				session: core.meta.id.clone().into(),
+				sub_session: core.access_key.clone().into(),
+				session_nonce: core.nonce,
+				mta_attempt: mta_attempt,
+				paillier_public: paillier_public.clone().into(),
+				paillier_public_proof: paillier_public_proof.clone().into(),
+			})))?;
+		}
+
+		let mut gamma_publics = BTreeMap::new();
+		gamma_publics.insert(core.meta.self_node_id.clone(), gamma_public);
+		data.mta_nonce_generation = Some(MtaNonceGeneration {
+			paillier_key_pair: paillier_key_pair,
+			delta_share: math::compute_secret_mul(&inv_nonce_share, &gamma_share)?,
+			inv_nonce_mul_secret_share: math::compute_secret_mul(&inv_nonce_share, &secret_share)?,
+			inv_nonce_share: inv_nonce_share,
+			encrypted_inv_nonce_share: encrypted_inv_nonce_share,
+			gamma_share: gamma_share,
+			secret_share: secret_share,
+			gamma_publics: gamma_publics,
+			responded_nodes: BTreeSet::new(),
+		});
+
+		let known_nodes: Vec<_> = data.mta_attempt.as_ref()
+			.expect("start_mta_nonce_generation is called when MtA attempt is started; qed")
+			.paillier_publics.keys().cloned().collect();
+		for node in &known_nodes {
+			Self::send_mta_encrypted_nonce_share(core, data, node)?;
+		}
+
+		Self::on_mta_nonce_generation_progress(core, data)
+	}
+
+	/// Send MtA request (encrypted share of inv(nonce) along with its proof) to the node, which Paillier public key is known.
+	fn send_mta_encrypted_nonce_share(core: &SessionCore, data: &SessionData, node: &NodeId) -> Result<(), Error> {
+		let proof = "send_mta_encrypted_nonce_share is called after MtA-based nonce generation is started; qed";
+		let mta_attempt = data.mta_attempt.as_ref().expect(proof);
+		let mta_nonce_generation = data.mta_nonce_generation.as_ref().expect(proof);
+		let paillier_public = mta_attempt.paillier_publics.get(node).ok_or(Error::InvalidStateForRequest)?;
+
+		let context = Self::mta_proof_context(core, mta_attempt.number, &core.meta.self_node_id);
+		let encrypted_inv_nonce_share_proof = math_paillier::prove_mta_request(&mta_nonce_generation.paillier_key_pair,
+			&mta_nonce_generation.encrypted_inv_nonce_share, paillier_public, &context)?;
+		core.cluster.send(node, Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(EcdsaMtaEncryptedNonceShare {
+			session: core.meta.id.clone().into(),
+			sub_session: core.access_key.clone().into(),
+			session_nonce: core.nonce,
+			mta_attempt: mta_attempt.number,
+			encrypted_inv_nonce_share: mta_nonce_generation.encrypted_inv_nonce_share.ciphertext().into(),
+			encrypted_inv_nonce_share_proof: encrypted_inv_nonce_share_proof.into(),
+			gamma_public: mta_nonce_generation.gamma_publics[&core.meta.self_node_id].clone().into(),
+		})))
+	}
+
+	/// Get context, MtA proofs of given node are bound to.
+	fn mta_proof_context(core: &SessionCore, mta_attempt: u32, node: &NodeId) -> Vec<u8> {
+		let mut context = Vec::new();
+		context.extend_from_slice(core.meta.id.as_bytes());
+		context.extend_from_slice(core.access_key.as_bytes());
+		context.extend_from_slice(&mta_attempt.to_be_bytes());
+		context.extend_from_slice(node.as_bytes());
+		context
+	}
+
+	/// Send inversed nonce share to master node, if MtA-based nonce generation is completed.
+	fn on_mta_nonce_generation_progress(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		{
+			let proof = "on_mta_nonce_generation_progress is called after MtA-based nonce generation is started; qed";
+			let nodes_count = data.mta_attempt.as_ref().expect(proof).nodes.len();
+			let mta_nonce_generation = data.mta_nonce_generation.as_ref().expect(proof);
+			if mta_nonce_generation.gamma_publics.len() != nodes_count
+				|| mta_nonce_generation.responded_nodes.len() + 1 != nodes_count {
+				return Ok(());
+			}
+		}
+
+		Self::send_inversed_nonce_coeff_share(core, data)?;
+		data.state = if core.meta.master_node_id != core.meta.self_node_id {
+			SessionState::SignatureComputing
+		} else {
+			SessionState::WaitingForInversedNonceShares
+		};
+
+		Ok(())
+	}
+
+	/// Get nonce data, required to compute partial signature.
+	fn signing_nonce(data: &SessionData) -> Result<EcdsaSigningNonce, Error> {
+		if let Some(mta_nonce_generation) = data.mta_nonce_generation.as_ref() {
+			return Ok(EcdsaSigningNonce::MultiplicativeToAdditive {
+				gamma_public: math::compute_public_sum(mta_nonce_generation.gamma_publics.values())?,
+				inv_nonce_share: mta_nonce_generation.inv_nonce_share.clone(),
+				inv_nonce_mul_secret_share: mta_nonce_generation.inv_nonce_mul_secret_share.clone(),
+			});
+		}
+
+		let nonce_exists_proof = "nonce is generated before signature is computed; qed";
+		let nonce_public = data.sig_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.0;
+		let inv_nonce_share = data.inv_nonce_generation_session.as_ref().expect(nonce_exists_proof).joint_public_and_secret().expect(nonce_exists_proof)?.2;
+		Ok(EcdsaSigningNonce::NonceInversion {
+			nonce_public: nonce_public,
+			inv_nonce_share: inv_nonce_share,
+		})
+	}
+
 	/// Broadcast inversed nonce share.
 	fn send_inversed_nonce_coeff_share(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
-		let proof = "inversed nonce coeff share is sent after nonces generation is completed; qed";
+		let inversed_nonce_coeff_share = match data.mta_nonce_generation.as_ref() {
+			// with MtA-based scheme, inversed nonce coeff is computed from additive shares of inv(nonce) * gamma
+			Some(mta_nonce_generation) => mta_nonce_generation.delta_share.clone(),
+			None => {
+				let proof = "inversed nonce coeff share is sent after nonces generation is completed; qed";
 
-		let sig_nonce_generation_session = data.sig_nonce_generation_session.as_ref().expect(proof);
-		let sig_nonce = sig_nonce_generation_session.joint_public_and_secret().expect(proof).expect(proof).2;
+				let sig_nonce_generation_session = data.sig_nonce_generation_session.as_ref().expect(proof);
+				let sig_nonce = sig_nonce_generation_session.joint_public_and_secret().expect(proof).expect(proof).2;
 
-		let inv_nonce_generation_session = data.inv_nonce_generation_session.as_ref().expect(proof);
-		let inv_nonce = inv_nonce_generation_session.joint_public_and_secret().expect(proof).expect(proof).2;
+				let inv_nonce_generation_session = data.inv_nonce_generation_session.as_ref().expect(proof);
+				let inv_nonce = inv_nonce_generation_session.joint_public_and_secret().expect(proof).expect(proof).2;
 
-		let inv_zero_generation_session = data.inv_zero_generation_session.as_ref().expect(proof);
-		let inv_zero = inv_zero_generation_session.joint_public_and_secret().expect(proof).expect(proof).2;
+				let inv_zero_generation_session = data.inv_zero_generation_session.as_ref().expect(proof);
+				let inv_zero = inv_zero_generation_session.joint_public_and_secret().expect(proof).expect(proof).2;
 
-		let inversed_nonce_coeff_share = math::compute_ecdsa_inversed_secret_coeff_share(&sig_nonce, &inv_nonce, &inv_zero)?;
+				math::compute_ecdsa_inversed_secret_coeff_share(&sig_nonce, &inv_nonce, &inv_zero)?
+			},
+		};
 		if core.meta.self_node_id == core.meta.master_node_id {
 			let mut inversed_nonce_coeff_shares = BTreeMap::new();
 			inversed_nonce_coeff_shares.insert(core.meta.self_node_id.clone(), inversed_nonce_coeff_share);
@@ -868,19 +1341,23 @@ impl SessionImpl {
 				sub_session: core.access_key.clone().into(),
 				session_nonce: core.nonce,
 				inversed_nonce_coeff_share: inversed_nonce_coeff_share.into(),
+				mta_attempt: data.mta_attempt.as_ref().map(|attempt| attempt.number).unwrap_or_default(),
 			})))
 		}
 	}
 
 	/// Compute inversed nonce coefficient on master node.
 	fn compute_inversed_nonce_coeff(core: &SessionCore, data: &SessionData) -> Result<Secret, Error> {
+		let proof = "inversed nonce coeff is computed after all shares are received; qed";
+		let inversed_nonce_coeff_shares = data.inversed_nonce_coeff_shares.as_ref().expect(proof);
+		if core.ecdsa_scheme == EcdsaSigningScheme::MultiplicativeToAdditive {
+			return math::compute_ecdsa_mta_inversed_nonce_coeff(inversed_nonce_coeff_shares.values());
+		}
+
 		let proof = "inversed nonce coeff is computed on master node; key version exists on master node";
 		let key_share = core.key_share.as_ref().expect(proof);
 		let key_version = key_share.version(data.version.as_ref().expect(proof)).expect(proof);
 
-		let proof = "inversed nonce coeff is computed after all shares are received; qed";
-		let inversed_nonce_coeff_shares = data.inversed_nonce_coeff_shares.as_ref().expect(proof);
-
 		math::compute_ecdsa_inversed_secret_coeff_from_shares(key_share.threshold,
 			&inversed_nonce_coeff_shares.keys().map(|n| key_version.id_numbers[n].clone()).collect::<Vec<_>>(),
 			&inversed_nonce_coeff_shares.values().cloned().collect::<Vec<_>>())
@@ -889,7 +1366,7 @@ impl SessionImpl {
 
 impl ClusterSession for SessionImpl {
 	type Id = SessionIdWithSubSession;
-	type CreationData = Requester;
+	type CreationData = (Requester, KeyDerivationPath);
 	type SuccessfulResult = Signature;
 
 	fn type_name() -> &'static str {
@@ -998,14 +1475,14 @@ impl SessionCore {
 		}
 	}
 
-	pub fn disseminate_jobs(&self, consensus_session: &mut SigningConsensusSession, version: &H256, nonce_public: Public, inv_nonce_share: Secret, inversed_nonce_coeff: Secret, message_hash: H256) -> Result<(), Error> {
+	pub fn disseminate_jobs(&self, consensus_session: &mut SigningConsensusSession, version: &H256, nonce: EcdsaSigningNonce, inversed_nonce_coeff: Secret, message_hash: H256) -> Result<(), Error> {
 		let key_share = match self.key_share.as_ref() {
 			None => return Err(Error::InvalidMessage),
 			Some(key_share) => key_share,
 		};
 
 		let key_version = key_share.version(version)?.hash.clone();
-		let signing_job = EcdsaSigningJob::new_on_master(key_share.clone(), key_version, nonce_public, inv_nonce_share, inversed_nonce_coeff, message_hash)?;
+		let signing_job = EcdsaSigningJob::new_on_master(key_share.clone(), key_version, nonce, inversed_nonce_coeff, message_hash)?;
 		consensus_session.disseminate_jobs(signing_job, self.signing_transport(), false).map(|_| ())
 	}
 }
@@ -1024,6 +1501,9 @@ impl JobTransport for SigningConsensusTransport {
 			message: ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
 				requester: request.into(),
 				version: version.clone().into(),
+				derivation_path: self.derivation_path.clone(),
+				ecies_ephemeral_public: None,
+				document_key_slot: None,
 			})
 		})))
 	}
@@ -1071,10 +1551,12 @@ mod tests {
 	use std::sync::Arc;
 	use ethereum_types::H256;
 	use crypto::publickey::{Random, Generator, Public, verify_public, public_to_address};
-	use key_server_cluster::{SessionId, Error, KeyStorage};
+	use key_server_cluster::{SessionId, Error, KeyStorage, EcdsaSigningScheme};
 	use key_server_cluster::cluster::tests::{MessageLoop as ClusterMessageLoop};
+	use key_server_cluster::cluster_sessions::ClusterSession;
 	use key_server_cluster::signing_session_ecdsa::SessionImpl;
 	use key_server_cluster::generation_session::tests::MessageLoop as GenerationMessageLoop;
+	use key_server_cluster::message::{Message, EcdsaSigningMessage};
 	use ServerKeyId;
 
 	const DUMMY_SESSION_ID: [u8; 32]  = [1u8; 32];
@@ -1090,12 +1572,28 @@ mod tests {
 			Ok(MessageLoop(ml.0))
 		}
 
+		pub fn new_with_scheme(num_nodes: usize, threshold: usize, ecdsa_scheme: EcdsaSigningScheme) -> Result<Self, Error> {
+			let ml = Self::new(num_nodes, threshold)?;
+			for idx in 0..num_nodes {
+				let key_storage = ml.0.key_storage(idx);
+				let mut key_share = key_storage.get(&ServerKeyId::from(DUMMY_SESSION_ID)).unwrap().unwrap();
+				key_share.ecdsa_scheme = ecdsa_scheme;
+				key_storage.update(ServerKeyId::from(DUMMY_SESSION_ID), key_share).unwrap();
+			}
+
+			Ok(ml)
+		}
+
+		pub fn new_mta(num_nodes: usize, threshold: usize) -> Result<Self, Error> {
+			Self::new_with_scheme(num_nodes, threshold, EcdsaSigningScheme::MultiplicativeToAdditive)
+		}
+
 		pub fn init_with_version(self, key_version: Option<H256>) -> Result<(Self, Public, H256), Error> {
 			let message_hash = H256::random();
 			let requester = Random.generate();
 			let signature = crypto::publickey::sign(requester.secret(), &SessionId::from(DUMMY_SESSION_ID)).unwrap();
 			self.0.cluster(0).client()
-				.new_ecdsa_signing_session(SessionId::from(DUMMY_SESSION_ID), signature.into(), key_version, message_hash)
+				.new_ecdsa_signing_session(SessionId::from(DUMMY_SESSION_ID), signature.into(), Vec::new(), key_version, message_hash)
 				.map(|_| (self, *requester.public(), message_hash))
 		}
 
@@ -1180,4 +1678,85 @@ mod tests {
 	fn ecdsa_signing_works_when_share_owners_are_isolated() {
 		MessageLoop::new(6, 2).unwrap().init_with_isolated().unwrap().0.ensure_completed();
 	}
+
+	#[test]
+	fn complete_gen_ecdsa_mta_sign_session() {
+		let test_cases = [(0, 1), (1, 2), (2, 3), (2, 5), (4, 5)];
+		for &(threshold, num_nodes) in &test_cases {
+			let (ml, _, message) = MessageLoop::new_mta(num_nodes, threshold).unwrap().init().unwrap();
+			ml.0.loop_until(|| ml.0.is_empty());
+
+			let signer_public = ml.0.key_storage(0).get(&ServerKeyId::from(DUMMY_SESSION_ID)).unwrap().unwrap().public;
+			let signature = ml.session_at(0).wait().unwrap();
+			assert!(verify_public(&signer_public, &signature, &message).unwrap());
+		}
+	}
+
+	#[test]
+	fn ecdsa_mta_complete_signing_session_with_single_node_failing() {
+		let (ml, requester, _) = MessageLoop::new_mta(3, 1).unwrap().init().unwrap();
+
+		// we need at least 2-of-3 nodes to agree to reach consensus
+		// let's say 1 of 3 nodes disagee
+		ml.0.acl_storage(1).prohibit(public_to_address(&requester), ServerKeyId::from(DUMMY_SESSION_ID));
+
+		// then consensus reachable, but single node will disagree
+		ml.ensure_completed();
+	}
+
+	#[test]
+	fn ecdsa_mta_signing_works_when_delegated_to_other_node() {
+		MessageLoop::new_mta(3, 1).unwrap().init_delegated().unwrap().0.ensure_completed();
+	}
+
+	#[test]
+	fn ecdsa_mta_signing_works_when_share_owners_are_isolated() {
+		MessageLoop::new_mta(4, 2).unwrap().init_with_isolated().unwrap().0.ensure_completed();
+	}
+
+	#[test]
+	fn ecdsa_mta_signing_is_restarted_when_nonce_generating_node_fails() {
+		let (ml, _, message) = MessageLoop::new_mta(3, 1).unwrap().init().unwrap();
+		let master_session = ml.session_at(0);
+
+		// wait until master has selected nodes for MtA-based nonce generation
+		ml.0.loop_until(|| master_session.data.lock().mta_attempt.is_some());
+		let failed_node = master_session.data.lock().mta_attempt.as_ref().unwrap().nodes.iter()
+			.find(|n| **n != ml.0.node(0)).cloned().unwrap();
+
+		// let's say selected node fails && doesn't respond to any messages
+		master_session.on_node_timeout(&failed_node);
+		while let Some((from, to, message)) = ml.0.take_message() {
+			if from != failed_node && to != failed_node {
+				ml.0.process_message(from, to, message);
+			}
+		}
+
+		// => nonce generation is restarted with the rest of nodes
+		assert_eq!(master_session.data.lock().mta_attempt.as_ref().unwrap().number, 1);
+		let signer_public = ml.0.key_storage(0).get(&ServerKeyId::from(DUMMY_SESSION_ID)).unwrap().unwrap().public;
+		let signature = master_session.wait().unwrap();
+		assert!(verify_public(&signer_public, &signature, &message).unwrap());
+	}
+
+	#[test]
+	fn ecdsa_mta_request_with_invalid_proof_is_rejected() {
+		let (ml, _, _) = MessageLoop::new_mta(3, 1).unwrap().init().unwrap();
+		loop {
+			let (from, to, message) = ml.0.take_message().unwrap();
+			match message {
+				Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(mut message)) => {
+					// let's say sender has corrupted range proof of its MtA request
+					*message.encrypted_inv_nonce_share_proof.0.last_mut().unwrap() ^= 1;
+
+					// => receiver rejects the request
+					let to_idx = ml.0.nodes().iter().position(|n| *n == to).unwrap();
+					assert_eq!(ml.session_at(to_idx).process_message(&from,
+						&EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(message)), Err(Error::InvalidMessage));
+					break;
+				},
+				message => ml.0.process_message(from, to, message),
+			}
+		}
+	}
 }
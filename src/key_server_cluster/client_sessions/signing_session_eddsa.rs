@@ -0,0 +1,1073 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, BTreeMap};
+use std::sync::Arc;
+use futures::Oneshot;
+use parking_lot::Mutex;
+use crypto::publickey::Secret;
+use ethereum_types::H256;
+use key_server_cluster::{Error, NodeId, SessionId, Requester, SessionMeta, AclStorage, DocumentKeyShare};
+use key_server_cluster::cluster::{Cluster};
+use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession, CompletionSignal};
+use key_server_cluster::generation_session_eddsa::{SessionImpl as GenerationSession, SessionParams as GenerationSessionParams,
+	SessionState as GenerationSessionState};
+use key_server_cluster::message::{Message, EddsaSigningMessage, EddsaSigningConsensusMessage, EddsaSigningGenerationMessage,
+	EddsaRequestPartialSignature, EddsaPartialSignature, EddsaSigningSessionCompleted, EddsaGenerationMessage,
+	ConsensusMessage, EddsaSigningSessionError, InitializeConsensusSession, ConfirmConsensusInitialization,
+	EddsaSigningSessionDelegation, EddsaSigningSessionDelegationCompleted};
+use key_server_cluster::jobs::job_session::JobTransport;
+use key_server_cluster::jobs::key_access_job::KeyAccessJob;
+use key_server_cluster::jobs::signing_job_eddsa::{EddsaPartialSigningRequest, EddsaPartialSigningResponse, EddsaSigningJob};
+use key_server_cluster::jobs::consensus_session::{ConsensusSessionParams, ConsensusSessionState, ConsensusSession};
+
+/// Distributed EdDSA (Ed25519) signing session.
+/// Brief overview:
+/// 1) initialization: master node (which has received request for signing the message) requests all other nodes to sign the message
+/// 2) ACL check: all nodes which have received the request are querying ACL-contract to check if requestor has access to the private key
+/// 3) nonce generation: nodes of consensus group are generating shared signature nonce, using the same id numbers as the key shares
+/// 4) partial signing: every node of consensus group computes its share of signature
+/// 5) signing: master node receives all partial signatures and computes the (standard Ed25519) signature
+pub struct SessionImpl {
+	/// Session core.
+	core: SessionCore,
+	/// Session data.
+	data: Mutex<SessionData>,
+}
+
+/// Immutable session data.
+struct SessionCore {
+	/// Session metadata.
+	pub meta: SessionMeta,
+	/// Signing session access key.
+	pub access_key: Secret,
+	/// Key share.
+	pub key_share: Option<DocumentKeyShare>,
+	/// Cluster which allows this node to send messages to other nodes in the cluster.
+	pub cluster: Arc<dyn Cluster>,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// SessionImpl completion signal.
+	pub completed: CompletionSignal<(H256, Secret)>,
+}
+
+/// Signing consensus session type.
+type SigningConsensusSession = ConsensusSession<KeyAccessJob, SigningConsensusTransport, EddsaSigningJob, SigningJobTransport>;
+
+/// Mutable session data.
+struct SessionData {
+	/// Session state.
+	pub state: SessionState,
+	/// Message hash.
+	pub message_hash: Option<H256>,
+	/// Key version to use for signing.
+	pub version: Option<H256>,
+	/// Consensus-based signing session.
+	pub consensus_session: SigningConsensusSession,
+	/// Signature nonce generation session.
+	pub generation_session: Option<GenerationSession>,
+	/// Delegation status.
+	pub delegation_status: Option<DelegationStatus>,
+	/// Signing result.
+	pub result: Option<Result<(H256, Secret), Error>>,
+}
+
+/// Signing session state.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(test, derive(Clone, Copy))]
+pub enum SessionState {
+	/// State when consensus is establishing.
+	ConsensusEstablishing,
+	/// State when signature nonce is generating.
+	SessionKeyGeneration,
+	/// State when signature is computing.
+	SignatureComputing,
+}
+
+/// Session creation parameters
+pub struct SessionParams {
+	/// Session metadata.
+	pub meta: SessionMeta,
+	/// Session access key.
+	pub access_key: Secret,
+	/// Key share.
+	pub key_share: Option<DocumentKeyShare>,
+	/// ACL storage.
+	pub acl_storage: Arc<dyn AclStorage>,
+	/// Cluster
+	pub cluster: Arc<dyn Cluster>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+/// Signing consensus transport.
+struct SigningConsensusTransport {
+	/// Session id.
+	id: SessionId,
+	/// Session access key.
+	access_key: Secret,
+	/// Session-level nonce.
+	nonce: u64,
+	/// Selected key version (on master node).
+	version: Option<H256>,
+	/// Cluster.
+	cluster: Arc<dyn Cluster>,
+}
+
+/// Signature nonce generation transport.
+struct SessionKeyGenerationTransport {
+	/// Session access key.
+	access_key: Secret,
+	/// Cluster.
+	cluster: Arc<dyn Cluster>,
+	/// Session-level nonce.
+	nonce: u64,
+	/// Other nodes ids.
+	other_nodes_ids: BTreeSet<NodeId>,
+}
+
+/// Signing job transport
+struct SigningJobTransport {
+	/// Session id.
+	id: SessionId,
+	/// Session access key.
+	access_key: Secret,
+	/// Session-level nonce.
+	nonce: u64,
+	/// Cluster.
+	cluster: Arc<dyn Cluster>,
+}
+
+/// Session delegation status.
+enum DelegationStatus {
+	/// Delegated to other node.
+	DelegatedTo(NodeId),
+	/// Delegated from other node.
+	DelegatedFrom(NodeId, u64),
+}
+
+impl SessionImpl {
+	/// Create new signing session.
+	pub fn new(
+		params: SessionParams,
+		requester: Option<Requester>,
+	) -> Result<(Self, Oneshot<Result<(H256, Secret), Error>>), Error> {
+		debug_assert_eq!(params.meta.threshold, params.key_share.as_ref().map(|ks| ks.threshold).unwrap_or_default());
+
+		let consensus_transport = SigningConsensusTransport {
+			id: params.meta.id.clone(),
+			access_key: params.access_key.clone(),
+			nonce: params.nonce,
+			version: None,
+			cluster: params.cluster.clone(),
+		};
+		let consensus_session = ConsensusSession::new(ConsensusSessionParams {
+			meta: params.meta.clone(),
+			consensus_executor: match requester {
+				Some(requester) => KeyAccessJob::new_on_master(params.meta.id.clone(), params.acl_storage.clone(), requester),
+				None => KeyAccessJob::new_on_slave(params.meta.id.clone(), params.acl_storage.clone()),
+			},
+			consensus_transport: consensus_transport,
+		})?;
+
+		let (completed, oneshot) = CompletionSignal::new();
+		Ok((SessionImpl {
+			core: SessionCore {
+				meta: params.meta,
+				access_key: params.access_key,
+				key_share: params.key_share,
+				cluster: params.cluster,
+				nonce: params.nonce,
+				completed,
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::ConsensusEstablishing,
+				message_hash: None,
+				version: None,
+				consensus_session: consensus_session,
+				generation_session: None,
+				delegation_status: None,
+				result: None,
+			}),
+		}, oneshot))
+	}
+
+	/// Wait for session completion.
+	#[cfg(test)]
+	pub fn wait(&self) -> Result<(H256, Secret), Error> {
+		Self::wait_session(&self.core.completed, &self.data, None, |data| data.result.clone())
+			.expect("wait_session returns Some if called without timeout; qed")
+	}
+
+	/// Get session state (tests only).
+	#[cfg(test)]
+	pub fn state(&self) -> SessionState {
+		self.data.lock().state
+	}
+
+	/// Delegate session to other node.
+	pub fn delegate(&self, master: NodeId, version: H256, message_hash: H256) -> Result<(), Error> {
+		if self.core.meta.master_node_id != self.core.meta.self_node_id {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let mut data = self.data.lock();
+		if data.consensus_session.state() != ConsensusSessionState::WaitingForInitialization || data.delegation_status.is_some() {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		data.consensus_session.consensus_job_mut().executor_mut().set_has_key_share(false);
+		self.core.cluster.send(&master, Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegation(EddsaSigningSessionDelegation {
+			session: self.core.meta.id.clone().into(),
+			sub_session: self.core.access_key.clone().into(),
+			session_nonce: self.core.nonce,
+			requester: data.consensus_session.consensus_job().executor().requester()
+				.expect("requester is passed to master node on creation; session can be delegated from master node only; qed")
+				.clone().into(),
+			version: version.into(),
+			message_hash: message_hash.into(),
+		})))?;
+		data.delegation_status = Some(DelegationStatus::DelegatedTo(master));
+		Ok(())
+	}
+
+	/// Initialize signing session on master node.
+	pub fn initialize(&self, version: H256, message_hash: H256) -> Result<(), Error> {
+		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
+
+		// check if version exists
+		let key_version = match self.core.key_share.as_ref() {
+			None => return Err(Error::InvalidMessage),
+			Some(key_share) => key_share.version(&version)?,
+		};
+
+		let mut data = self.data.lock();
+		let non_isolated_nodes = self.core.cluster.nodes();
+		let mut consensus_nodes: BTreeSet<_> = key_version.id_numbers.keys()
+			.filter(|n| non_isolated_nodes.contains(*n))
+			.cloned()
+			.chain(::std::iter::once(self.core.meta.self_node_id.clone()))
+			.collect();
+		if let Some(&DelegationStatus::DelegatedFrom(delegation_master, _)) = data.delegation_status.as_ref() {
+			consensus_nodes.remove(&delegation_master);
+		}
+
+		data.consensus_session.consensus_job_mut().transport_mut().version = Some(version.clone());
+		data.version = Some(version.clone());
+		data.message_hash = Some(message_hash);
+		data.consensus_session.initialize(consensus_nodes)?;
+
+		if data.consensus_session.state() == ConsensusSessionState::ConsensusEstablished {
+			let generation_session = self.core.start_generation_session(&version, &data.consensus_session
+				.select_consensus_group()?.clone())?;
+			debug_assert_eq!(generation_session.state(), GenerationSessionState::Finished);
+			let nonce_public_and_secret = generation_session
+				.joint_public_and_secret()
+				.expect("nonce is generated before signature is computed; we are in SignatureComputing state; qed")?;
+			data.generation_session = Some(generation_session);
+			data.state = SessionState::SignatureComputing;
+
+			self.core.disseminate_jobs(&mut data.consensus_session, &version, nonce_public_and_secret.0, nonce_public_and_secret.1, message_hash)?;
+
+			debug_assert!(data.consensus_session.state() == ConsensusSessionState::Finished);
+			let result = data.consensus_session.result()?;
+			Self::set_signing_result(&self.core, &mut *data, Ok(result));
+		}
+
+		Ok(())
+	}
+
+	/// Process signing message.
+	pub fn process_message(&self, sender: &NodeId, message: &EddsaSigningMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&EddsaSigningMessage::EddsaSigningConsensusMessage(ref message) =>
+				self.on_consensus_message(sender, message),
+			&EddsaSigningMessage::EddsaSigningGenerationMessage(ref message) =>
+				self.on_generation_message(sender, message),
+			&EddsaSigningMessage::EddsaRequestPartialSignature(ref message) =>
+				self.on_partial_signature_requested(sender, message),
+			&EddsaSigningMessage::EddsaPartialSignature(ref message) =>
+				self.on_partial_signature(sender, message),
+			&EddsaSigningMessage::EddsaSigningSessionError(ref message) =>
+				self.process_node_error(Some(&sender), message.error.clone()),
+			&EddsaSigningMessage::EddsaSigningSessionCompleted(ref message) =>
+				self.on_session_completed(sender, message),
+			&EddsaSigningMessage::EddsaSigningSessionDelegation(ref message) =>
+				self.on_session_delegated(sender, message),
+			&EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(ref message) =>
+				self.on_session_delegation_completed(sender, message),
+		}
+	}
+
+	/// When session is delegated to this node.
+	pub fn on_session_delegated(&self, sender: &NodeId, message: &EddsaSigningSessionDelegation) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+
+		{
+			let mut data = self.data.lock();
+			if data.consensus_session.state() != ConsensusSessionState::WaitingForInitialization || data.delegation_status.is_some() {
+				return Err(Error::InvalidStateForRequest);
+			}
+
+			data.consensus_session.consensus_job_mut().executor_mut().set_requester(message.requester.clone().into());
+			data.delegation_status = Some(DelegationStatus::DelegatedFrom(sender.clone(), message.session_nonce));
+		}
+
+		self.initialize(message.version.clone().into(), message.message_hash.clone().into())
+	}
+
+	/// When delegated session is completed on other node.
+	pub fn on_session_delegation_completed(&self, sender: &NodeId, message: &EddsaSigningSessionDelegationCompleted) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+
+		if self.core.meta.master_node_id != self.core.meta.self_node_id {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let mut data = self.data.lock();
+		match data.delegation_status.as_ref() {
+			Some(&DelegationStatus::DelegatedTo(ref node)) if node == sender => (),
+			_ => return Err(Error::InvalidMessage),
+		}
+
+		Self::set_signing_result(&self.core, &mut *data, Ok((message.signature_r.clone().into(), message.signature_s.clone().into())));
+
+		Ok(())
+	}
+
+	/// When consensus-related message is received.
+	pub fn on_consensus_message(&self, sender: &NodeId, message: &EddsaSigningConsensusMessage) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+		let is_establishing_consensus = data.consensus_session.state() == ConsensusSessionState::EstablishingConsensus;
+
+		if let &ConsensusMessage::InitializeConsensusSession(ref msg) = &message.message {
+			let version = msg.version.clone().into();
+			let has_key_share = self.core.key_share.as_ref()
+				.map(|ks| ks.version(&version).is_ok())
+				.unwrap_or(false);
+			data.consensus_session.consensus_job_mut().executor_mut().set_has_key_share(has_key_share);
+			data.version = Some(version);
+		}
+		data.consensus_session.on_consensus_message(&sender, &message.message)?;
+
+		let is_consensus_established = data.consensus_session.state() == ConsensusSessionState::ConsensusEstablished;
+		if self.core.meta.self_node_id != self.core.meta.master_node_id || !is_establishing_consensus || !is_consensus_established {
+			return Ok(());
+		}
+
+		// master node coordinates nonce generation => it must be a member of consensus group
+		let consensus_group = data.consensus_session.select_consensus_group()?.clone();
+		if !consensus_group.contains(&self.core.meta.self_node_id) {
+			return Err(Error::AccessDenied);
+		}
+
+		let version = data.version.clone().ok_or(Error::InvalidMessage)?;
+		let generation_session = self.core.start_generation_session(&version, &consensus_group)?;
+		data.generation_session = Some(generation_session);
+		data.state = SessionState::SessionKeyGeneration;
+
+		Ok(())
+	}
+
+	/// When nonce generation related message is received.
+	pub fn on_generation_message(&self, sender: &NodeId, message: &EddsaSigningGenerationMessage) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		if let &EddsaGenerationMessage::InitializeEddsaGenerationSession(ref message) = &message.message {
+			if &self.core.meta.master_node_id != sender {
+				match data.delegation_status.as_ref() {
+					Some(&DelegationStatus::DelegatedTo(s)) if s == *sender => (),
+					_ => return Err(Error::InvalidMessage),
+				}
+			}
+
+			// nonce shares must be computed at the same points as key shares
+			let key_share = self.core.key_share.as_ref().ok_or(Error::InvalidMessage)?;
+			let key_version = key_share.version(data.version.as_ref().ok_or(Error::InvalidMessage)?)?;
+			if message.threshold != key_share.threshold
+				|| message.nodes.iter().any(|(node, number)| key_version.id_numbers.get(&(**node)) != Some(&(**number))) {
+				return Err(Error::InvalidMessage);
+			}
+
+			let consensus_group: BTreeSet<NodeId> = message.nodes.keys().cloned().map(Into::into).collect();
+			data.generation_session = Some(self.core.create_generation_session(&consensus_group));
+			data.state = SessionState::SessionKeyGeneration;
+		}
+
+		{
+			let generation_session = data.generation_session.as_ref().ok_or(Error::InvalidStateForRequest)?;
+			let is_key_generating = generation_session.state() != GenerationSessionState::Finished;
+			generation_session.process_message(sender, &message.message)?;
+
+			let is_key_generated = generation_session.state() == GenerationSessionState::Finished;
+			if !is_key_generating || !is_key_generated {
+				return Ok(());
+			}
+		}
+
+		data.state = SessionState::SignatureComputing;
+		if self.core.meta.master_node_id != self.core.meta.self_node_id {
+			return Ok(());
+		}
+
+		let version = data.version.as_ref().ok_or(Error::InvalidMessage)?.clone();
+		let message_hash = data.message_hash
+			.expect("we are on master node; on master node message_hash is filled in initialize(); on_generation_message follows initialize; qed");
+		let nonce_public_and_secret = data.generation_session.as_ref()
+			.expect("nonce is generated before signature is computed; we are in SignatureComputing state; qed")
+			.joint_public_and_secret()
+			.expect("nonce is generated before signature is computed; we are in SignatureComputing state; qed")?;
+		self.core.disseminate_jobs(&mut data.consensus_session, &version, nonce_public_and_secret.0, nonce_public_and_secret.1, message_hash)
+	}
+
+	/// When partial signature is requested.
+	pub fn on_partial_signature_requested(&self, sender: &NodeId, message: &EddsaRequestPartialSignature) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let key_share = match self.core.key_share.as_ref() {
+			None => return Err(Error::InvalidMessage),
+			Some(key_share) => key_share,
+		};
+
+		let mut data = self.data.lock();
+
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+		if data.state != SessionState::SignatureComputing {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let nonce_public_and_secret = data.generation_session.as_ref()
+			.expect("nonce is generated before signature is computed; we are in SignatureComputing state; qed")
+			.joint_public_and_secret()
+			.expect("nonce is generated before signature is computed; we are in SignatureComputing state; qed")?;
+		let key_version = key_share.version(data.version.as_ref().ok_or(Error::InvalidMessage)?)?.hash.clone();
+		let signing_job = EddsaSigningJob::new_on_slave(self.core.meta.self_node_id.clone(), key_share.clone(), key_version,
+			nonce_public_and_secret.0, nonce_public_and_secret.1)?;
+		let signing_transport = self.core.signing_transport();
+
+		data.consensus_session.on_job_request(sender, EddsaPartialSigningRequest {
+			id: message.request_id.clone().into(),
+			message_hash: message.message_hash.clone().into(),
+			other_nodes_ids: message.nodes.iter().cloned().map(Into::into).collect(),
+		}, signing_job, signing_transport).map(|_| ())
+	}
+
+	/// When partial signature is received.
+	pub fn on_partial_signature(&self, sender: &NodeId, message: &EddsaPartialSignature) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+		data.consensus_session.on_job_response(sender, EddsaPartialSigningResponse {
+			request_id: message.request_id.clone().into(),
+			partial_signature: message.partial_signature.clone().into(),
+		})?;
+
+		if data.consensus_session.state() != ConsensusSessionState::Finished {
+			return Ok(());
+		}
+
+		// send completion signal to all nodes, except for rejected nodes
+		for node in data.consensus_session.consensus_non_rejected_nodes() {
+			self.core.cluster.send(&node, Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionCompleted(EddsaSigningSessionCompleted {
+				session: self.core.meta.id.clone().into(),
+				sub_session: self.core.access_key.clone().into(),
+				session_nonce: self.core.nonce,
+			})))?;
+		}
+
+		let result = data.consensus_session.result()?;
+		Self::set_signing_result(&self.core, &mut *data, Ok(result));
+
+		Ok(())
+	}
+
+	/// When session is completed.
+	pub fn on_session_completed(&self, sender: &NodeId, message: &EddsaSigningSessionCompleted) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(self.core.access_key == *message.sub_session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		self.data.lock().consensus_session.on_session_completed(sender)
+	}
+
+	/// Process error from the other node.
+	fn process_node_error(&self, node: Option<&NodeId>, error: Error) -> Result<(), Error> {
+		let mut data = self.data.lock();
+		let is_self_node_error = node.map(|n| n == &self.core.meta.self_node_id).unwrap_or(false);
+		// error is always fatal if coming from this node
+		if is_self_node_error {
+			Self::set_signing_result(&self.core, &mut *data, Err(error.clone()));
+			return Err(error);
+		}
+
+		match {
+			match node {
+				Some(node) => data.consensus_session.on_node_error(node, error.clone()),
+				None => data.consensus_session.on_session_timeout(),
+			}
+		} {
+			Ok(false) => {
+				Ok(())
+			},
+			// signature nonce is shared by the nodes of failed consensus group
+			// => jobs can't be resent to other group without regenerating the nonce
+			Ok(true) => {
+				warn!("{}: EdDSA signing session failed with error: {:?} from {:?}", &self.core.meta.self_node_id, error, node);
+				Self::set_signing_result(&self.core, &mut *data, Err(error.clone()));
+				Err(error)
+			},
+			Err(err) => {
+				warn!("{}: EdDSA signing session failed with error: {:?} from {:?}", &self.core.meta.self_node_id, error, node);
+				Self::set_signing_result(&self.core, &mut *data, Err(err.clone()));
+				Err(err)
+			},
+		}
+	}
+
+	/// Set signing session result.
+	fn set_signing_result(core: &SessionCore, data: &mut SessionData, result: Result<(H256, Secret), Error>) {
+		if let Some(DelegationStatus::DelegatedFrom(master, nonce)) = data.delegation_status.take() {
+			// error means can't communicate => ignore it
+			let _ = match result.as_ref() {
+				Ok(signature) => core.cluster.send(&master, Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(EddsaSigningSessionDelegationCompleted {
+					session: core.meta.id.clone().into(),
+					sub_session: core.access_key.clone().into(),
+					session_nonce: nonce,
+					signature_r: signature.0.clone().into(),
+					signature_s: signature.1.clone().into(),
+				}))),
+				Err(error) => core.cluster.send(&master, Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionError(EddsaSigningSessionError {
+					session: core.meta.id.clone().into(),
+					sub_session: core.access_key.clone().into(),
+					session_nonce: nonce,
+					error: error.clone().into(),
+				}))),
+			};
+		}
+
+		data.result = Some(result.clone());
+		core.completed.send(result);
+	}
+}
+
+impl ClusterSession for SessionImpl {
+	type Id = SessionIdWithSubSession;
+	type CreationData = Requester;
+	type SuccessfulResult = (H256, Secret);
+
+	fn type_name() -> &'static str {
+		"EdDSA signing"
+	}
+
+	fn id(&self) -> SessionIdWithSubSession {
+		SessionIdWithSubSession::new(self.core.meta.id.clone(), self.core.access_key.clone())
+	}
+
+	fn is_finished(&self) -> bool {
+		let data = self.data.lock();
+		data.consensus_session.state() == ConsensusSessionState::Failed
+			|| data.consensus_session.state() == ConsensusSessionState::Finished
+			|| data.result.is_some()
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		// ignore error, only state matters
+		let _ = self.process_node_error(Some(node), Error::NodeDisconnected);
+	}
+
+	fn on_session_timeout(&self) {
+		// ignore error, only state matters
+		let _ = self.process_node_error(None, Error::NodeDisconnected);
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		let is_fatal = self.process_node_error(Some(node), error.clone()).is_err();
+		let is_this_node_error = *node == self.core.meta.self_node_id;
+		if is_fatal || is_this_node_error {
+			// error in signing session is non-fatal, if occurs on slave node
+			// => either respond with error
+			// => or broadcast error
+			let message = Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionError(EddsaSigningSessionError {
+				session: self.core.meta.id.clone().into(),
+				sub_session: self.core.access_key.clone().into(),
+				session_nonce: self.core.nonce,
+				error: error.clone().into(),
+			}));
+
+			// do not bother processing send error, as we already processing error
+			let _ = if self.core.meta.master_node_id == self.core.meta.self_node_id {
+				self.core.cluster.broadcast(message)
+			} else {
+				self.core.cluster.send(&self.core.meta.master_node_id, message)
+			};
+		}
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::EddsaSigning(ref message) => self.process_message(sender, message),
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl SessionKeyGenerationTransport {
+	fn map_message(&self, message: Message) -> Result<Message, Error> {
+		match message {
+			Message::EddsaGeneration(message) => Ok(Message::EddsaSigning(EddsaSigningMessage::EddsaSigningGenerationMessage(EddsaSigningGenerationMessage {
+				session: message.session_id().clone().into(),
+				sub_session: self.access_key.clone().into(),
+				session_nonce: self.nonce,
+				message: message,
+			}))),
+			_ => Err(Error::InvalidMessage),
+		}
+	}
+}
+
+impl Cluster for SessionKeyGenerationTransport {
+	fn broadcast(&self, message: Message) -> Result<(), Error> {
+		let message = self.map_message(message)?;
+		for to in &self.other_nodes_ids {
+			self.cluster.send(to, message.clone())?;
+		}
+		Ok(())
+	}
+
+	fn send(&self, to: &NodeId, message: Message) -> Result<(), Error> {
+		debug_assert!(self.other_nodes_ids.contains(to));
+		self.cluster.send(to, self.map_message(message)?)
+	}
+
+	fn is_connected(&self, node: &NodeId) -> bool {
+		self.cluster.is_connected(node)
+	}
+
+	fn nodes(&self) -> BTreeSet<NodeId> {
+		self.cluster.nodes()
+	}
+
+	fn configured_nodes_count(&self) -> usize {
+		self.cluster.configured_nodes_count()
+	}
+
+	fn connected_nodes_count(&self) -> usize {
+		self.cluster.connected_nodes_count()
+	}
+}
+
+impl SessionCore {
+	pub fn signing_transport(&self) -> SigningJobTransport {
+		SigningJobTransport {
+			id: self.meta.id.clone(),
+			access_key: self.access_key.clone(),
+			nonce: self.nonce,
+			cluster: self.cluster.clone()
+		}
+	}
+
+	/// Create nonce generation session, running on given consensus group.
+	fn create_generation_session(&self, consensus_group: &BTreeSet<NodeId>) -> GenerationSession {
+		let mut other_consensus_group_nodes = consensus_group.clone();
+		other_consensus_group_nodes.remove(&self.meta.self_node_id);
+
+		GenerationSession::new(GenerationSessionParams {
+			id: self.meta.id.clone(),
+			self_node_id: self.meta.self_node_id.clone(),
+			key_storage: None,
+			cluster: Arc::new(SessionKeyGenerationTransport {
+				access_key: self.access_key.clone(),
+				cluster: self.cluster.clone(),
+				nonce: self.nonce,
+				other_nodes_ids: other_consensus_group_nodes,
+			}),
+			nonce: None,
+		}).0
+	}
+
+	/// Start nonce generation on master node. Nonce shares are computed at the same points as key shares.
+	fn start_generation_session(&self, version: &H256, consensus_group: &BTreeSet<NodeId>) -> Result<GenerationSession, Error> {
+		let key_share = match self.key_share.as_ref() {
+			None => return Err(Error::InvalidMessage),
+			Some(key_share) => key_share,
+		};
+		let key_version = key_share.version(version)?;
+		let id_numbers = consensus_group.iter()
+			.map(|node| key_version.id_numbers.get(node)
+				.map(|id_number| (node.clone(), id_number.clone()))
+				.ok_or(Error::InvalidMessage))
+			.collect::<Result<BTreeMap<_, _>, _>>()?;
+
+		let generation_session = self.create_generation_session(consensus_group);
+		generation_session.initialize(Default::default(), key_share.threshold, id_numbers.into())?;
+		Ok(generation_session)
+	}
+
+	pub fn disseminate_jobs(&self, consensus_session: &mut SigningConsensusSession, version: &H256, nonce_public: H256, nonce_secret_share: Secret, message_hash: H256) -> Result<(), Error> {
+		let key_share = match self.key_share.as_ref() {
+			None => return Err(Error::InvalidMessage),
+			Some(key_share) => key_share,
+		};
+
+		let key_version = key_share.version(version)?.hash.clone();
+		let signing_job = EddsaSigningJob::new_on_master(self.meta.self_node_id.clone(), key_share.clone(), key_version,
+			nonce_public, nonce_secret_share, message_hash)?;
+		consensus_session.disseminate_jobs(signing_job, self.signing_transport(), false).map(|_| ())
+	}
+}
+
+impl JobTransport for SigningConsensusTransport {
+	type PartialJobRequest=Requester;
+	type PartialJobResponse=bool;
+
+	fn send_partial_request(&self, node: &NodeId, request: Requester) -> Result<(), Error> {
+		let version = self.version.as_ref()
+			.expect("send_partial_request is called on initialized master node only; version is filled in before initialization starts on master node; qed");
+		self.cluster.send(node, Message::EddsaSigning(EddsaSigningMessage::EddsaSigningConsensusMessage(EddsaSigningConsensusMessage {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			message: ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
+				requester: request.into(),
+				version: version.clone().into(),
+				derivation_path: Vec::new(),
+				ecies_ephemeral_public: None,
+				document_key_slot: None,
+			})
+		})))
+	}
+
+	fn send_partial_response(&self, node: &NodeId, response: bool) -> Result<(), Error> {
+		self.cluster.send(node, Message::EddsaSigning(EddsaSigningMessage::EddsaSigningConsensusMessage(EddsaSigningConsensusMessage {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			message: ConsensusMessage::ConfirmConsensusInitialization(ConfirmConsensusInitialization {
+				is_confirmed: response,
+			})
+		})))
+	}
+}
+
+impl JobTransport for SigningJobTransport {
+	type PartialJobRequest=EddsaPartialSigningRequest;
+	type PartialJobResponse=EddsaPartialSigningResponse;
+
+	fn send_partial_request(&self, node: &NodeId, request: EddsaPartialSigningRequest) -> Result<(), Error> {
+		self.cluster.send(node, Message::EddsaSigning(EddsaSigningMessage::EddsaRequestPartialSignature(EddsaRequestPartialSignature {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			request_id: request.id.into(),
+			message_hash: request.message_hash.into(),
+			nodes: request.other_nodes_ids.into_iter().map(Into::into).collect(),
+		})))
+	}
+
+	fn send_partial_response(&self, node: &NodeId, response: EddsaPartialSigningResponse) -> Result<(), Error> {
+		self.cluster.send(node, Message::EddsaSigning(EddsaSigningMessage::EddsaPartialSignature(EddsaPartialSignature {
+			session: self.id.clone().into(),
+			sub_session: self.access_key.clone().into(),
+			session_nonce: self.nonce,
+			request_id: response.request_id.into(),
+			partial_signature: response.partial_signature.into(),
+		})))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::collections::BTreeMap;
+	use ethereum_types::{Address, H256};
+	use crypto::publickey::{Random, Generator, Public, public_to_address};
+	use acl_storage::DummyAclStorage;
+	use key_server_cluster::{SessionId, Requester, SessionMeta, Error, KeyStorage};
+	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
+	use key_server_cluster::generation_session::tests::MessageLoop as GenerationMessageLoop;
+	use key_server_cluster::generation_session_eddsa::tests::MessageLoop as EddsaGenerationMessageLoop;
+	use key_server_cluster::math_eddsa;
+	use key_server_cluster::message::{EddsaSigningMessage, EddsaSigningGenerationMessage, EddsaGenerationMessage,
+		InitializeEddsaGenerationSession, ConfirmEddsaGenerationInitialization};
+	use key_server_cluster::signing_session_eddsa::{SessionImpl, SessionState, SessionParams};
+
+	#[derive(Debug)]
+	pub struct MessageLoop(pub ClusterMessageLoop);
+
+	impl MessageLoop {
+		pub fn new(num_nodes: usize, threshold: usize) -> Result<Self, Error> {
+			let ml = EddsaGenerationMessageLoop::new(num_nodes).init(threshold)?;
+			ml.0.loop_until(|| ml.0.is_empty()); // complete generation session
+
+			Ok(MessageLoop(ml.0))
+		}
+
+		pub fn into_session(&self, at_node: usize) -> SessionImpl {
+			let requester = Some(Requester::Signature(
+				crypto::publickey::sign(Random.generate().secret(), &SessionId::from([1u8; 32])).unwrap())
+			);
+			let dummy_doc = [1u8; 32].into();
+			SessionImpl::new(SessionParams {
+				meta: SessionMeta {
+					id: SessionId::from([1u8; 32]),
+					self_node_id: self.0.node(at_node),
+					master_node_id: self.0.node(0),
+					threshold: self.0.key_storage(at_node).get(&dummy_doc).unwrap().unwrap().threshold,
+					configured_nodes_count: self.0.nodes().len(),
+					connected_nodes_count: self.0.nodes().len(),
+				},
+				access_key: Random.generate().secret().clone(),
+				key_share: self.0.key_storage(at_node).get(&dummy_doc).unwrap(),
+				acl_storage: Arc::new(DummyAclStorage::default()),
+				cluster: self.0.cluster(0).view().unwrap(),
+				nonce: 0,
+			}, requester).unwrap().0
+		}
+
+		pub fn init_with_version(self, key_version: Option<H256>) -> Result<(Self, Public, H256), Error> {
+			let message_hash = H256::random();
+			let requester = Random.generate();
+			let signature = crypto::publickey::sign(requester.secret(), &SessionId::from([1u8; 32])).unwrap();
+			self.0.cluster(0).client().new_eddsa_signing_session(
+				SessionId::from([1u8; 32]),
+				signature.into(),
+				key_version,
+				message_hash).map(|_| (self, *requester.public(), message_hash)
+			)
+		}
+
+		pub fn init(self) -> Result<(Self, Public, H256), Error> {
+			let key_version = self.key_version();
+			self.init_with_version(Some(key_version))
+		}
+
+		pub fn init_delegated(self) -> Result<(Self, Public, H256), Error> {
+			let doc = [1u8; 32].into();
+			self.0.key_storage(0).remove(&doc).unwrap();
+			self.init_with_version(None)
+		}
+
+		pub fn init_with_isolated(self) -> Result<(Self, Public, H256), Error> {
+			self.0.isolate(1);
+			self.init()
+		}
+
+		pub fn init_without_share(self) -> Result<(Self, Public, H256), Error> {
+			let key_version = self.key_version();
+			let doc = [1u8; 32].into();
+			self.0.key_storage(0).remove(&doc).unwrap();
+			self.init_with_version(Some(key_version))
+		}
+
+		pub fn session_at(&self, idx: usize) -> Arc<SessionImpl> {
+			self.0.sessions(idx).eddsa_signing_sessions.first().unwrap()
+		}
+
+		pub fn ensure_completed(&self) {
+			self.0.loop_until(|| self.0.is_empty());
+			assert!(self.session_at(0).wait().is_ok());
+		}
+
+		pub fn key_version(&self) -> H256 {
+			let doc = [1u8; 32].into();
+			self.0.key_storage(0).get(&doc)
+				.unwrap().unwrap().versions.iter().last().unwrap().hash
+		}
+
+		pub fn joint_public(&self) -> H256 {
+			let doc = [1u8; 32].into();
+			math_eddsa::from_key_share_public(&self.0.key_storage(0).get(&doc).unwrap().unwrap().public).unwrap()
+		}
+	}
+
+	#[test]
+	fn eddsa_complete_gen_sign_session() {
+		let test_cases = [(0, 1), (0, 5), (1, 3), (2, 5), (3, 5), (4, 5)];
+		for &(threshold, num_nodes) in &test_cases {
+			let (ml, _, message) = MessageLoop::new(num_nodes, threshold).unwrap().init().unwrap();
+			ml.0.loop_until(|| ml.0.is_empty());
+
+			let signature = ml.session_at(0).wait().unwrap();
+			assert_eq!(math_eddsa::verify_signature(&ml.joint_public(), &signature, &message), Ok(true));
+		}
+	}
+
+	#[test]
+	fn eddsa_constructs_in_cluster_of_single_node() {
+		MessageLoop::new(1, 0).unwrap().init().unwrap();
+	}
+
+	#[test]
+	fn eddsa_fails_to_initialize_if_does_not_have_a_share() {
+		assert!(MessageLoop::new(2, 1).unwrap().init_without_share().is_err());
+	}
+
+	#[test]
+	fn eddsa_fails_to_initialize_if_key_is_generated_for_other_curve() {
+		let ml = GenerationMessageLoop::new(3).init(1).unwrap();
+		ml.0.loop_until(|| ml.0.is_empty());
+		let key_version = ml.key_version();
+		let requester = Random.generate();
+		let signature = crypto::publickey::sign(requester.secret(), &SessionId::from([1u8; 32])).unwrap();
+		assert_eq!(ml.0.cluster(0).client().new_eddsa_signing_session(
+			SessionId::from([1u8; 32]), signature.into(), Some(key_version), H256::random()).map(|_| ()),
+			Err(Error::InvalidKeyCurve));
+	}
+
+	#[test]
+	fn eddsa_fails_to_initialize_when_already_initialized() {
+		let (ml, _, _) = MessageLoop::new(1, 0).unwrap().init().unwrap();
+		assert_eq!(ml.session_at(0).initialize(ml.key_version(), H256::from_low_u64_be(777)),
+			Err(Error::InvalidStateForRequest));
+	}
+
+	#[test]
+	fn eddsa_fails_when_generation_message_is_received_when_not_initialized() {
+		let ml = MessageLoop::new(3, 1).unwrap();
+		let session = ml.into_session(0);
+		assert_eq!(session.on_generation_message(&ml.0.node(1), &EddsaSigningGenerationMessage {
+			session: SessionId::from([1u8; 32]).into(),
+			sub_session: session.core.access_key.clone().into(),
+			session_nonce: 0,
+			message: EddsaGenerationMessage::ConfirmEddsaGenerationInitialization(ConfirmEddsaGenerationInitialization {
+				session: SessionId::from([1u8; 32]).into(),
+				session_nonce: 0,
+			}),
+		}), Err(Error::InvalidStateForRequest));
+	}
+
+	#[test]
+	fn eddsa_fails_when_generation_sesson_is_initialized_by_slave_node() {
+		let (ml, _, _) = MessageLoop::new(3, 1).unwrap().init().unwrap();
+		let session = ml.session_at(0);
+		ml.0.loop_until(|| session.state() == SessionState::SessionKeyGeneration);
+
+		let slave2_id = ml.0.node(2);
+		let slave1_session = ml.session_at(1);
+
+		assert_eq!(slave1_session.on_generation_message(&slave2_id, &EddsaSigningGenerationMessage {
+			session: SessionId::from([1u8; 32]).into(),
+			sub_session: session.core.access_key.clone().into(),
+			session_nonce: 0,
+			message: EddsaGenerationMessage::InitializeEddsaGenerationSession(InitializeEddsaGenerationSession {
+				session: SessionId::from([1u8; 32]).into(),
+				session_nonce: 0,
+				author: Address::zero().into(),
+				nodes: BTreeMap::new(),
+				threshold: 1,
+			})
+		}), Err(Error::InvalidMessage));
+	}
+
+	#[test]
+	fn eddsa_fails_when_nonce_is_generated_at_points_other_than_key_shares() {
+		let (ml, _, _) = MessageLoop::new(3, 1).unwrap().init().unwrap();
+		let session = ml.session_at(0);
+		ml.0.loop_until(|| session.state() == SessionState::SessionKeyGeneration);
+
+		let slave1_session = ml.session_at(1);
+		let nodes = vec![
+			(ml.0.node(0).into(), math_eddsa::generate_random_scalar().unwrap().into()),
+			(ml.0.node(1).into(), math_eddsa::generate_random_scalar().unwrap().into()),
+		].into_iter().collect();
+		assert_eq!(slave1_session.on_generation_message(&ml.0.node(0), &EddsaSigningGenerationMessage {
+			session: SessionId::from([1u8; 32]).into(),
+			sub_session: session.core.access_key.clone().into(),
+			session_nonce: 0,
+			message: EddsaGenerationMessage::InitializeEddsaGenerationSession(InitializeEddsaGenerationSession {
+				session: SessionId::from([1u8; 32]).into(),
+				session_nonce: 0,
+				author: Address::zero().into(),
+				nodes: nodes,
+				threshold: 1,
+			})
+		}), Err(Error::InvalidMessage));
+	}
+
+	#[test]
+	fn eddsa_failed_signing_session() {
+		let (ml, requester, _) = MessageLoop::new(3, 1).unwrap().init().unwrap();
+
+		// we need at least 2-of-3 nodes to agree to reach consensus
+		// let's say 2 of 3 nodes disagee
+		ml.0.acl_storage(1).prohibit(public_to_address(&requester), SessionId::from([1u8; 32]));
+		ml.0.acl_storage(2).prohibit(public_to_address(&requester), SessionId::from([1u8; 32]));
+
+		// then consensus is unreachable
+		ml.0.loop_until(|| ml.0.is_empty());
+		assert_eq!(ml.session_at(0).wait().unwrap_err(), Error::ConsensusUnreachable);
+	}
+
+	#[test]
+	fn eddsa_complete_signing_session_with_single_node_failing() {
+		let (ml, requester, _) = MessageLoop::new(3, 1).unwrap().init().unwrap();
+
+		// we need at least 2-of-3 nodes to agree to reach consensus
+		// let's say 1 of 3 nodes disagree
+		ml.0.acl_storage(1).prohibit(public_to_address(&requester), SessionId::from([1u8; 32]));
+
+		// then consensus reachable, but single node will disagree
+		ml.ensure_completed();
+	}
+
+	#[test]
+	fn eddsa_signing_message_fails_when_nonce_is_wrong() {
+		let ml = MessageLoop::new(3, 1).unwrap();
+		let session = ml.into_session(1);
+		let msg = EddsaSigningMessage::EddsaSigningGenerationMessage(EddsaSigningGenerationMessage {
+			session: SessionId::from([1u8; 32]).into(),
+			sub_session: session.core.access_key.clone().into(),
+			session_nonce: 10,
+			message: EddsaGenerationMessage::ConfirmEddsaGenerationInitialization(ConfirmEddsaGenerationInitialization {
+				session: SessionId::from([1u8; 32]).into(),
+				session_nonce: 0,
+			}),
+		});
+		assert_eq!(session.process_message(&ml.0.node(1), &msg), Err(Error::ReplayProtection));
+	}
+
+	#[test]
+	fn eddsa_signing_works_when_delegated_to_other_node() {
+		let (ml, _, _) = MessageLoop::new(3, 1).unwrap().init_delegated().unwrap();
+		ml.ensure_completed();
+	}
+
+	#[test]
+	fn eddsa_signing_works_when_share_owners_are_isolated() {
+		let (ml, _, _) = MessageLoop::new(3, 1).unwrap().init_with_isolated().unwrap();
+		ml.ensure_completed();
+	}
+}
@@ -14,13 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, BTreeMap};
+use std::iter::once;
 use std::sync::Arc;
 use futures::Oneshot;
 use parking_lot::Mutex;
 use crypto::publickey::{Public, Secret};
 use ethereum_types::H256;
-use key_server_cluster::{Error, NodeId, SessionId, Requester, SessionMeta, AclStorage, DocumentKeyShare};
+use key_server_cluster::{Error, NodeId, SessionId, Requester, SessionMeta, AclStorage, DocumentKeyShare, KeyDerivationPath};
 use key_server_cluster::cluster::{Cluster};
 use key_server_cluster::cluster_sessions::{SessionIdWithSubSession, ClusterSession, CompletionSignal};
 use key_server_cluster::generation_session::{SessionImpl as GenerationSession, SessionParams as GenerationSessionParams,
@@ -31,7 +32,8 @@ use key_server_cluster::message::{Message, SchnorrSigningMessage, SchnorrSigning
 	SchnorrSigningSessionDelegation, SchnorrSigningSessionDelegationCompleted};
 use key_server_cluster::jobs::job_session::JobTransport;
 use key_server_cluster::jobs::key_access_job::KeyAccessJob;
-use key_server_cluster::jobs::signing_job_schnorr::{SchnorrPartialSigningRequest, SchnorrPartialSigningResponse, SchnorrSigningJob};
+use key_server_cluster::jobs::signing_job_schnorr::{SchnorrPartialSigningRequest, SchnorrPartialSigningResponse, SchnorrSigningJob,
+	SchnorrSigningScheme};
 use key_server_cluster::jobs::consensus_session::{ConsensusSessionParams, ConsensusSessionState, ConsensusSession};
 
 /// Distributed Schnorr-signing session.
@@ -41,6 +43,9 @@ use key_server_cluster::jobs::consensus_session::{ConsensusSessionParams, Consen
 /// 2) ACL check: all nodes which have received the request are querying ACL-contract to check if requestor has access to the private key
 /// 3) partial signing: every node which has succussfully checked access for the requestor do a partial signing
 /// 4) signing: master node receives all partial signatures of the secret and computes the signature
+/// Several messages could be signed in a single session (batch). Then the consensus is established once and
+/// separate session key is generated for every message. Session keys are generated within the same generation round:
+/// messages of all session key generation sessions, sent to the same node, are combined into a single message.
 pub struct SessionImpl {
 	/// Session core.
 	core: SessionCore,
@@ -54,14 +59,18 @@ struct SessionCore {
 	pub meta: SessionMeta,
 	/// Signing session access key.
 	pub access_key: Secret,
-	/// Key share.
+	/// Key share (of the child key, if derivation path is not empty).
 	pub key_share: Option<DocumentKeyShare>,
+	/// Derivation path of the child key, used to sign messages.
+	pub derivation_path: KeyDerivationPath,
 	/// Cluster which allows this node to send messages to other nodes in the cluster.
 	pub cluster: Arc<dyn Cluster>,
 	/// Session-level nonce.
 	pub nonce: u64,
 	/// SessionImpl completion signal.
-	pub completed: CompletionSignal<(Secret, Secret)>,
+	pub completed: CompletionSignal<Vec<(Secret, Secret)>>,
+	/// Session key generation messages, waiting to be sent to other nodes.
+	pub generation_messages: Arc<Mutex<Vec<(NodeId, usize, GenerationMessage)>>>,
 }
 
 /// Signing consensus session type.
@@ -71,18 +80,20 @@ type SigningConsensusSession = ConsensusSession<KeyAccessJob, SigningConsensusTr
 struct SessionData {
 	/// Session state.
 	pub state: SessionState,
-	/// Message hash.
-	pub message_hash: Option<H256>,
+	/// Signature scheme.
+	pub scheme: SchnorrSigningScheme,
+	/// Hashes of messages to sign.
+	pub message_hashes: Option<Vec<H256>>,
 	/// Key version to use for decryption.
 	pub version: Option<H256>,
 	/// Consensus-based signing session.
 	pub consensus_session: SigningConsensusSession,
-	/// Session key generation session.
-	pub generation_session: Option<GenerationSession>,
+	/// Session key generation sessions, indexed by message index.
+	pub generation_sessions: BTreeMap<usize, GenerationSession>,
 	/// Delegation status.
 	pub delegation_status: Option<DelegationStatus>,
 	/// Decryption result.
-	pub result: Option<Result<(Secret, Secret), Error>>,
+	pub result: Option<Result<Vec<(Secret, Secret)>, Error>>,
 }
 
 /// Signing session state.
@@ -103,8 +114,10 @@ pub struct SessionParams {
 	pub meta: SessionMeta,
 	/// Session access key.
 	pub access_key: Secret,
-	/// Key share.
+	/// Key share (of the child key, if derivation path is not empty).
 	pub key_share: Option<DocumentKeyShare>,
+	/// Derivation path of the child key, used to sign messages.
+	pub derivation_path: KeyDerivationPath,
 	/// ACL storage.
 	pub acl_storage: Arc<dyn AclStorage>,
 	/// Cluster
@@ -123,20 +136,22 @@ struct SigningConsensusTransport {
 	nonce: u64,
 	/// Selected key version (on master node).
 	version: Option<H256>,
+	/// Derivation path of the child key.
+	derivation_path: KeyDerivationPath,
 	/// Cluster.
 	cluster: Arc<dyn Cluster>,
 }
 
 /// Signing key generation transport.
 struct SessionKeyGenerationTransport {
-	/// Session access key.
-	access_key: Secret,
 	/// Cluster.
 	cluster: Arc<dyn Cluster>,
-	/// Session-level nonce.
-	nonce: u64,
+	/// Index of generated session key.
+	nonce_index: usize,
 	/// Other nodes ids.
 	other_nodes_ids: BTreeSet<NodeId>,
+	/// Queue of messages, waiting to be sent to other nodes.
+	messages: Arc<Mutex<Vec<(NodeId, usize, GenerationMessage)>>>,
 }
 
 /// Signing job transport
@@ -164,7 +179,7 @@ impl SessionImpl {
 	pub fn new(
 		params: SessionParams,
 		requester: Option<Requester>,
-	) -> Result<(Self, Oneshot<Result<(Secret, Secret), Error>>), Error> {
+	) -> Result<(Self, Oneshot<Result<Vec<(Secret, Secret)>, Error>>), Error> {
 		debug_assert_eq!(params.meta.threshold, params.key_share.as_ref().map(|ks| ks.threshold).unwrap_or_default());
 
 		let consensus_transport = SigningConsensusTransport {
@@ -172,6 +187,7 @@ impl SessionImpl {
 			access_key: params.access_key.clone(),
 			nonce: params.nonce,
 			version: None,
+			derivation_path: params.derivation_path.clone(),
 			cluster: params.cluster.clone(),
 		};
 		let consensus_session = ConsensusSession::new(ConsensusSessionParams {
@@ -189,16 +205,19 @@ impl SessionImpl {
 				meta: params.meta,
 				access_key: params.access_key,
 				key_share: params.key_share,
+				derivation_path: params.derivation_path,
 				cluster: params.cluster,
 				nonce: params.nonce,
 				completed,
+				generation_messages: Default::default(),
 			},
 			data: Mutex::new(SessionData {
 				state: SessionState::ConsensusEstablishing,
-				message_hash: None,
+				scheme: SchnorrSigningScheme::Legacy,
+				message_hashes: None,
 				version: None,
 				consensus_session: consensus_session,
-				generation_session: None,
+				generation_sessions: BTreeMap::new(),
 				delegation_status: None,
 				result: None,
 			}),
@@ -207,7 +226,7 @@ impl SessionImpl {
 
 	/// Wait for session completion.
 	#[cfg(test)]
-	pub fn wait(&self) -> Result<(Secret, Secret), Error> {
+	pub fn wait(&self) -> Result<Vec<(Secret, Secret)>, Error> {
 		Self::wait_session(&self.core.completed, &self.data, None, |data| data.result.clone())
 			.expect("wait_session returns Some if called without timeout; qed")
 	}
@@ -219,7 +238,7 @@ impl SessionImpl {
 	}
 
 	/// Delegate session to other node.
-	pub fn delegate(&self, master: NodeId, version: H256, message_hash: H256) -> Result<(), Error> {
+	pub fn delegate(&self, master: NodeId, version: H256, scheme: SchnorrSigningScheme, message_hashes: Vec<H256>) -> Result<(), Error> {
 		if self.core.meta.master_node_id != self.core.meta.self_node_id {
 			return Err(Error::InvalidStateForRequest);
 		}
@@ -229,6 +248,9 @@ impl SessionImpl {
 			return Err(Error::InvalidStateForRequest);
 		}
 
+		let mut message_hashes = message_hashes.into_iter().map(Into::into);
+		let message_hash = message_hashes.next().ok_or(Error::InvalidMessage)?;
+
 		data.consensus_session.consensus_job_mut().executor_mut().set_has_key_share(false);
 		self.core.cluster.send(&master, Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionDelegation(SchnorrSigningSessionDelegation {
 			session: self.core.meta.id.clone().into(),
@@ -238,7 +260,10 @@ impl SessionImpl {
 				.expect("requester is passed to master node on creation; session can be delegated from master node only; qed")
 				.clone().into(),
 			version: version.into(),
-			message_hash: message_hash.into(),
+			derivation_path: self.core.derivation_path.clone(),
+			scheme: scheme,
+			message_hash: message_hash,
+			additional_message_hashes: message_hashes.collect(),
 		})))?;
 		data.delegation_status = Some(DelegationStatus::DelegatedTo(master));
 		Ok(())
@@ -246,9 +271,14 @@ impl SessionImpl {
 	}
 
 	/// Initialize signing session on master node.
-	pub fn initialize(&self, version: H256, message_hash: H256) -> Result<(), Error> {
+	pub fn initialize(&self, version: H256, scheme: SchnorrSigningScheme, message_hashes: Vec<H256>) -> Result<(), Error> {
 		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
 
+		// at least one message must be signed
+		if message_hashes.is_empty() {
+			return Err(Error::InvalidMessage);
+		}
+
 		// check if version exists
 		let key_version = match self.core.key_share.as_ref() {
 			None => return Err(Error::InvalidMessage),
@@ -268,32 +298,24 @@ impl SessionImpl {
 
 		data.consensus_session.consensus_job_mut().transport_mut().version = Some(version.clone());
 		data.version = Some(version.clone());
-		data.message_hash = Some(message_hash);
+		data.scheme = scheme;
+		data.message_hashes = Some(message_hashes.clone());
 		data.consensus_session.initialize(consensus_nodes)?;
 
 		if data.consensus_session.state() == ConsensusSessionState::ConsensusEstablished {
-			let generation_session = GenerationSession::new(GenerationSessionParams {
-				id: self.core.meta.id.clone(),
-				self_node_id: self.core.meta.self_node_id.clone(),
-				key_storage: None,
-				cluster: Arc::new(SessionKeyGenerationTransport {
-					access_key: self.core.access_key.clone(),
-					cluster: self.core.cluster.clone(),
-					nonce: self.core.nonce,
-					other_nodes_ids: BTreeSet::new()
-				}),
-				nonce: None,
-			}).0;
-			generation_session.initialize(Default::default(), Default::default(), false, 0, vec![self.core.meta.self_node_id.clone()].into_iter().collect::<BTreeSet<_>>().into())?;
-
-			debug_assert_eq!(generation_session.state(), GenerationSessionState::Finished);
-			let joint_public_and_secret = generation_session
-				.joint_public_and_secret()
-				.expect("session key is generated before signature is computed; we are in SignatureComputing state; qed")?;
-			data.generation_session = Some(generation_session);
+			for nonce_index in 0..message_hashes.len() {
+				let generation_session = self.core.generation_session(nonce_index, BTreeSet::new());
+				generation_session.initialize(Default::default(), Default::default(), false, 0, None, Default::default(), vec![self.core.meta.self_node_id.clone()].into_iter().collect::<BTreeSet<_>>().into())?;
+
+				debug_assert_eq!(generation_session.state(), GenerationSessionState::Finished);
+				data.generation_sessions.insert(nonce_index, generation_session);
+			}
+
+			let session_keys = Self::session_keys(&*data)?;
+			let session_public_shares = Self::session_public_shares(&*data)?;
 			data.state = SessionState::SignatureComputing;
 
-			self.core.disseminate_jobs(&mut data.consensus_session, &version, joint_public_and_secret.0, joint_public_and_secret.1, message_hash)?;
+			self.core.disseminate_jobs(&mut data.consensus_session, &version, session_keys, session_public_shares, scheme, message_hashes)?;
 
 			debug_assert!(data.consensus_session.state() == ConsensusSessionState::Finished);
 			let result = data.consensus_session.result()?;
@@ -344,7 +366,9 @@ impl SessionImpl {
 			data.delegation_status = Some(DelegationStatus::DelegatedFrom(sender.clone(), message.session_nonce));
 		}
 
-		self.initialize(message.version.clone().into(), message.message_hash.clone().into())
+		let message_hashes = once(&message.message_hash).chain(message.additional_message_hashes.iter())
+			.cloned().map(Into::into).collect();
+		self.initialize(message.version.clone().into(), message.scheme, message_hashes)
 	}
 
 	/// When delegated session is completed on other node.
@@ -362,7 +386,11 @@ impl SessionImpl {
 			_ => return Err(Error::InvalidMessage),
 		}
 
-		Self::set_signing_result(&self.core, &mut *data, Ok((message.signature_c.clone().into(), message.signature_s.clone().into())));
+		let signatures = once((&message.signature_c, &message.signature_s))
+			.chain(message.additional_signatures.iter().map(|&(ref c, ref s)| (c, s)))
+			.map(|(c, s)| (c.clone().into(), s.clone().into()))
+			.collect();
+		Self::set_signing_result(&self.core, &mut *data, Ok(signatures));
 
 		Ok(())
 	}
@@ -391,33 +419,7 @@ impl SessionImpl {
 			return Ok(());
 		}
 
-		let consensus_group = data.consensus_session.select_consensus_group()?.clone();
-		let mut other_consensus_group_nodes = consensus_group.clone();
-		other_consensus_group_nodes.remove(&self.core.meta.self_node_id);
-
-		let key_share = match self.core.key_share.as_ref() {
-			None => return Err(Error::InvalidMessage),
-			Some(key_share) => key_share,
-		};
-
-		let generation_session = GenerationSession::new(GenerationSessionParams {
-			id: self.core.meta.id.clone(),
-			self_node_id: self.core.meta.self_node_id.clone(),
-			key_storage: None,
-			cluster: Arc::new(SessionKeyGenerationTransport {
-				access_key: self.core.access_key.clone(),
-				cluster: self.core.cluster.clone(),
-				nonce: self.core.nonce,
-				other_nodes_ids: other_consensus_group_nodes,
-			}),
-			nonce: None,
-		}).0;
-
-		generation_session.initialize(Default::default(), Default::default(), false, key_share.threshold, consensus_group.into())?;
-		data.generation_session = Some(generation_session);
-		data.state = SessionState::SessionKeyGeneration;
-
-		Ok(())
+		Self::start_session_keys_generation(&self.core, &mut *data)
 	}
 
 	/// When session key related message is received.
@@ -428,7 +430,55 @@ impl SessionImpl {
 
 		let mut data = self.data.lock();
 
-		if let &GenerationMessage::InitializeSession(ref message) = &message.message {
+		// process messages of all session key generation sessions and send responses within single message
+		let messages = once((message.nonce_index, &message.message))
+			.chain(message.additional_messages.iter().map(|&(nonce_index, ref message)| (nonce_index, message)));
+		let mut is_any_key_generated = false;
+		let mut process_result = Ok(());
+		for (nonce_index, message) in messages {
+			match self.process_generation_message(&mut *data, sender, nonce_index, message) {
+				Ok(is_key_generated) => is_any_key_generated = is_any_key_generated || is_key_generated,
+				Err(error) => {
+					process_result = Err(error);
+					break;
+				},
+			}
+		}
+		self.core.send_generation_messages()?;
+		process_result?;
+
+		if !is_any_key_generated {
+			return Ok(());
+		}
+
+		// all session keys must be generated before signing
+		// (master node sends initialization messages for all session keys before any other message)
+		if data.generation_sessions.values().any(|s| s.state() != GenerationSessionState::Finished) {
+			return Ok(());
+		}
+
+		if self.core.meta.master_node_id != self.core.meta.self_node_id {
+			data.state = SessionState::SignatureComputing;
+			return Ok(());
+		}
+
+		let message_hashes = data.message_hashes.clone()
+			.expect("we are on master node; on master node message_hashes are filled in initialize(); on_generation_message follows initialize; qed");
+		if data.generation_sessions.len() != message_hashes.len() {
+			return Ok(());
+		}
+
+		data.state = SessionState::SignatureComputing;
+		let version = data.version.as_ref().ok_or(Error::InvalidMessage)?.clone();
+		let scheme = data.scheme;
+		let session_keys = Self::session_keys(&*data)?;
+		let session_public_shares = Self::session_public_shares(&*data)?;
+		self.core.disseminate_jobs(&mut data.consensus_session, &version, session_keys, session_public_shares, scheme, message_hashes)
+	}
+
+	/// Process message of single session key generation session. Returns true if the key has been generated.
+	fn process_generation_message(&self, data: &mut SessionData, sender: &NodeId, nonce_index: usize, message: &GenerationMessage) -> Result<bool, Error> {
+		if let &GenerationMessage::InitializeSession(ref message) = message {
 			if &self.core.meta.master_node_id != sender {
 				match data.delegation_status.as_ref() {
 					Some(&DelegationStatus::DelegatedTo(s)) if s == *sender => (),
@@ -440,46 +490,17 @@ impl SessionImpl {
 			let mut other_consensus_group_nodes = consensus_group.clone();
 			other_consensus_group_nodes.remove(&self.core.meta.self_node_id);
 
-			let generation_session = GenerationSession::new(GenerationSessionParams {
-				id: self.core.meta.id.clone(),
-				self_node_id: self.core.meta.self_node_id.clone(),
-				key_storage: None,
-				cluster: Arc::new(SessionKeyGenerationTransport {
-					access_key: self.core.access_key.clone(),
-					cluster: self.core.cluster.clone(),
-					nonce: self.core.nonce,
-					other_nodes_ids: other_consensus_group_nodes
-				}),
-				nonce: None,
-			}).0;
-			data.generation_session = Some(generation_session);
+			let generation_session = self.core.generation_session(nonce_index, other_consensus_group_nodes);
+			data.generation_sessions.insert(nonce_index, generation_session);
 			data.state = SessionState::SessionKeyGeneration;
 		}
 
-		{
-			let generation_session = data.generation_session.as_ref().ok_or(Error::InvalidStateForRequest)?;
-			let is_key_generating = generation_session.state() != GenerationSessionState::Finished;
-			generation_session.process_message(sender, &message.message)?;
-
-			let is_key_generated = generation_session.state() == GenerationSessionState::Finished;
-			if !is_key_generating || !is_key_generated {
-				return Ok(());
-			}
-		}
-
-		data.state = SessionState::SignatureComputing;
-		if self.core.meta.master_node_id != self.core.meta.self_node_id {
-			return Ok(());
-		}
+		let generation_session = data.generation_sessions.get(&nonce_index).ok_or(Error::InvalidStateForRequest)?;
+		let is_key_generating = generation_session.state() != GenerationSessionState::Finished;
+		generation_session.process_message(sender, message)?;
 
-		let version = data.version.as_ref().ok_or(Error::InvalidMessage)?.clone();
-		let message_hash = data.message_hash
-			.expect("we are on master node; on master node message_hash is filled in initialize(); on_generation_message follows initialize; qed");
-		let joint_public_and_secret = data.generation_session.as_ref()
-			.expect("session key is generated before signature is computed; we are in SignatureComputing state; qed")
-			.joint_public_and_secret()
-			.expect("session key is generated before signature is computed; we are in SignatureComputing state; qed")?;
-		self.core.disseminate_jobs(&mut data.consensus_session, &version, joint_public_and_secret.0, joint_public_and_secret.1, message_hash)
+		let is_key_generated = generation_session.state() == GenerationSessionState::Finished;
+		Ok(is_key_generating && is_key_generated)
 	}
 
 	/// When partial signature is requested.
@@ -502,17 +523,16 @@ impl SessionImpl {
 			return Err(Error::InvalidStateForRequest);
 		}
 
-		let joint_public_and_secret = data.generation_session.as_ref()
-			.expect("session key is generated before signature is computed; we are in SignatureComputing state; qed")
-			.joint_public_and_secret()
-			.expect("session key is generated before signature is computed; we are in SignatureComputing state; qed")?;
+		let session_keys = Self::session_keys(&*data)?;
 		let key_version = key_share.version(data.version.as_ref().ok_or(Error::InvalidMessage)?)?.hash.clone();
-		let signing_job = SchnorrSigningJob::new_on_slave(self.core.meta.self_node_id.clone(), key_share.clone(), key_version, joint_public_and_secret.0, joint_public_and_secret.1)?;
+		let signing_job = SchnorrSigningJob::new_on_slave(self.core.meta.self_node_id.clone(), key_share.clone(), key_version, session_keys)?;
 		let signing_transport = self.core.signing_transport();
 
 		data.consensus_session.on_job_request(sender, SchnorrPartialSigningRequest {
 			id: message.request_id.clone().into(),
-			message_hash: message.message_hash.clone().into(),
+			scheme: message.scheme,
+			message_hashes: once(&message.message_hash).chain(message.additional_message_hashes.iter())
+				.cloned().map(Into::into).collect(),
 			other_nodes_ids: message.nodes.iter().cloned().map(Into::into).collect(),
 		}, signing_job, signing_transport).map(|_| ())
 	}
@@ -524,10 +544,19 @@ impl SessionImpl {
 		debug_assert!(sender != &self.core.meta.self_node_id);
 
 		let mut data = self.data.lock();
-		data.consensus_session.on_job_response(sender, SchnorrPartialSigningResponse {
+		let job_response_result = data.consensus_session.on_job_response(sender, SchnorrPartialSigningResponse {
 			request_id: message.request_id.clone().into(),
-			partial_signature: message.partial_signature.clone().into(),
-		})?;
+			partial_signatures: once(&message.partial_signature).chain(message.additional_partial_signatures.iter())
+				.cloned().map(Into::into).collect(),
+		});
+		match job_response_result {
+			// node has sent invalid partial signature => restart session without this node
+			Err(Error::InvalidPartialResponse(node)) => {
+				drop(data);
+				return self.process_node_error(Some(&node), Error::InvalidPartialResponse(node.clone()));
+			},
+			job_response_result => job_response_result?,
+		}
 
 		if data.consensus_session.state() != ConsensusSessionState::Finished {
 			return Ok(());
@@ -577,15 +606,10 @@ impl SessionImpl {
 				Ok(())
 			},
 			Ok(true) => {
-				let version = data.version.as_ref().ok_or(Error::InvalidMessage)?.clone();
-				let message_hash = data.message_hash.as_ref().cloned()
-					.expect("on_node_error returned true; this means that jobs must be REsent; this means that jobs already have been sent; jobs are sent when message_hash.is_some(); qed");
-				let joint_public_and_secret = data.generation_session.as_ref()
-					.expect("on_node_error returned true; this means that jobs must be REsent; this means that jobs already have been sent; jobs are sent when message_hash.is_some(); qed")
-					.joint_public_and_secret()
-					.expect("on_node_error returned true; this means that jobs must be REsent; this means that jobs already have been sent; jobs are sent when message_hash.is_some(); qed")?;
-				let disseminate_result = self.core.disseminate_jobs(&mut data.consensus_session, &version, joint_public_and_secret.0, joint_public_and_secret.1, message_hash);
-				match disseminate_result {
+				// session keys are shared by nodes of the previous consensus group => they must be regenerated
+				// by the new consensus group; jobs are disseminated again when generation is completed
+				let generation_result = Self::start_session_keys_generation(&self.core, &mut *data);
+				match generation_result {
 					Ok(()) => Ok(()),
 					Err(err) => {
 						warn!("{}: signing session failed with error: {:?} from {:?}", &self.core.meta.self_node_id, error, node);
@@ -602,18 +626,64 @@ impl SessionImpl {
 		}
 	}
 
+	/// Start generation of session keys for all messages on master node, using selected consensus group.
+	fn start_session_keys_generation(core: &SessionCore, data: &mut SessionData) -> Result<(), Error> {
+		let consensus_group = data.consensus_session.select_consensus_group()?.clone();
+		let mut other_consensus_group_nodes = consensus_group.clone();
+		other_consensus_group_nodes.remove(&core.meta.self_node_id);
+
+		let key_share = match core.key_share.as_ref() {
+			None => return Err(Error::InvalidMessage),
+			Some(key_share) => key_share,
+		};
+
+		let messages_count = data.message_hashes.as_ref()
+			.expect("we are on master node; on master node message_hashes are filled in initialize(); session keys are generated after initialize; qed")
+			.len();
+		for nonce_index in 0..messages_count {
+			let generation_session = core.generation_session(nonce_index, other_consensus_group_nodes.clone());
+			generation_session.initialize(Default::default(), Default::default(), false, key_share.threshold, None, Default::default(), consensus_group.clone().into())?;
+			data.generation_sessions.insert(nonce_index, generation_session);
+		}
+		data.state = SessionState::SessionKeyGeneration;
+
+		core.send_generation_messages()
+	}
+
+	/// Get generated session keys (public key and secret coefficient) for all messages.
+	fn session_keys(data: &SessionData) -> Result<Vec<(Public, Secret)>, Error> {
+		data.generation_sessions.values()
+			.map(|generation_session| generation_session.joint_public_and_secret()
+				.ok_or(Error::InvalidStateForRequest)
+				.and_then(|joint_public_and_secret| joint_public_and_secret)
+				.map(|(public, secret, _)| (public, secret)))
+			.collect()
+	}
+
+	/// Get public shares of generated session keys (one-time public shares of all nodes) for all messages.
+	fn session_public_shares(data: &SessionData) -> Result<Vec<BTreeMap<NodeId, Public>>, Error> {
+		data.generation_sessions.values()
+			.map(|generation_session| generation_session.nodes_public_shares()
+				.ok_or(Error::InvalidStateForRequest))
+			.collect()
+	}
+
 	/// Set signing session result.
-	fn set_signing_result(core: &SessionCore, data: &mut SessionData, result: Result<(Secret, Secret), Error>) {
+	fn set_signing_result(core: &SessionCore, data: &mut SessionData, result: Result<Vec<(Secret, Secret)>, Error>) {
 		if let Some(DelegationStatus::DelegatedFrom(master, nonce)) = data.delegation_status.take() {
 			// error means can't communicate => ignore it
-			let _ = match result.as_ref() {
-				Ok(signature) => core.cluster.send(&master, Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionDelegationCompleted(SchnorrSigningSessionDelegationCompleted {
+			let _ = match result.as_ref().map(|signatures| signatures.split_first()) {
+				Ok(Some((signature, additional_signatures))) => core.cluster.send(&master, Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionDelegationCompleted(SchnorrSigningSessionDelegationCompleted {
 					session: core.meta.id.clone().into(),
 					sub_session: core.access_key.clone().into(),
 					session_nonce: nonce,
 					signature_c: signature.0.clone().into(),
 					signature_s: signature.1.clone().into(),
+					additional_signatures: additional_signatures.iter()
+						.map(|&(ref c, ref s)| (c.clone().into(), s.clone().into()))
+						.collect(),
 				}))),
+				Ok(None) => Ok(()),
 				Err(error) => core.cluster.send(&master, Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionError(SchnorrSigningSessionError {
 					session: core.meta.id.clone().into(),
 					sub_session: core.access_key.clone().into(),
@@ -630,8 +700,8 @@ impl SessionImpl {
 
 impl ClusterSession for SessionImpl {
 	type Id = SessionIdWithSubSession;
-	type CreationData = Requester;
-	type SuccessfulResult = (Secret, Secret);
+	type CreationData = (Requester, KeyDerivationPath);
+	type SuccessfulResult = Vec<(Secret, Secret)>;
 
 	fn type_name() -> &'static str {
 		"signing"
@@ -690,14 +760,12 @@ impl ClusterSession for SessionImpl {
 }
 
 impl SessionKeyGenerationTransport {
-	fn map_message(&self, message: Message) -> Result<Message, Error> {
+	fn queue_message(&self, to: &NodeId, message: Message) -> Result<(), Error> {
 		match message {
-			Message::Generation(message) => Ok(Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningGenerationMessage(SchnorrSigningGenerationMessage {
-				session: message.session_id().clone().into(),
-				sub_session: self.access_key.clone().into(),
-				session_nonce: self.nonce,
-				message: message,
-			}))),
+			Message::Generation(message) => {
+				self.messages.lock().push((to.clone(), self.nonce_index, message));
+				Ok(())
+			},
 			_ => Err(Error::InvalidMessage),
 		}
 	}
@@ -705,16 +773,15 @@ impl SessionKeyGenerationTransport {
 
 impl Cluster for SessionKeyGenerationTransport {
 	fn broadcast(&self, message: Message) -> Result<(), Error> {
-		let message = self.map_message(message)?;
 		for to in &self.other_nodes_ids {
-			self.cluster.send(to, message.clone())?;
+			self.queue_message(to, message.clone())?;
 		}
 		Ok(())
 	}
 
 	fn send(&self, to: &NodeId, message: Message) -> Result<(), Error> {
 		debug_assert!(self.other_nodes_ids.contains(to));
-		self.cluster.send(to, self.map_message(message)?)
+		self.queue_message(to, message)
 	}
 
 	fn is_connected(&self, node: &NodeId) -> bool {
@@ -744,7 +811,45 @@ impl SessionCore {
 		}
 	}
 
-	pub fn disseminate_jobs(&self, consensus_session: &mut SigningConsensusSession, version: &H256, session_public: Public, session_secret_share: Secret, message_hash: H256) -> Result<(), Error> {
+	pub fn generation_session(&self, nonce_index: usize, other_nodes_ids: BTreeSet<NodeId>) -> GenerationSession {
+		GenerationSession::new(GenerationSessionParams {
+			id: self.meta.id.clone(),
+			self_node_id: self.meta.self_node_id.clone(),
+			key_storage: None,
+			cluster: Arc::new(SessionKeyGenerationTransport {
+				cluster: self.cluster.clone(),
+				nonce_index: nonce_index,
+				other_nodes_ids: other_nodes_ids,
+				messages: self.generation_messages.clone(),
+			}),
+			nonce: None,
+		}).0
+	}
+
+	/// Send queued session key generation messages. All messages to the same node are sent within single message.
+	pub fn send_generation_messages(&self) -> Result<(), Error> {
+		let mut nodes_messages: BTreeMap<NodeId, Vec<(usize, GenerationMessage)>> = BTreeMap::new();
+		for (to, nonce_index, message) in self.generation_messages.lock().drain(..) {
+			nodes_messages.entry(to).or_insert_with(Vec::new).push((nonce_index, message));
+		}
+
+		for (to, mut messages) in nodes_messages {
+			let (nonce_index, message) = messages.remove(0);
+			self.cluster.send(&to, Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningGenerationMessage(SchnorrSigningGenerationMessage {
+				session: self.meta.id.clone().into(),
+				sub_session: self.access_key.clone().into(),
+				session_nonce: self.nonce,
+				nonce_index: nonce_index,
+				message: message,
+				additional_messages: messages,
+			})))?;
+		}
+
+		Ok(())
+	}
+
+	pub fn disseminate_jobs(&self, consensus_session: &mut SigningConsensusSession, version: &H256, session_keys: Vec<(Public, Secret)>,
+		session_public_shares: Vec<BTreeMap<NodeId, Public>>, scheme: SchnorrSigningScheme, message_hashes: Vec<H256>) -> Result<(), Error> {
 		let key_share = match self.key_share.as_ref() {
 			None => return Err(Error::InvalidMessage),
 			Some(key_share) => key_share,
@@ -752,7 +857,7 @@ impl SessionCore {
 
 		let key_version = key_share.version(version)?.hash.clone();
 		let signing_job = SchnorrSigningJob::new_on_master(self.meta.self_node_id.clone(), key_share.clone(), key_version,
-			session_public, session_secret_share, message_hash)?;
+			session_keys, session_public_shares, scheme, message_hashes)?;
 		consensus_session.disseminate_jobs(signing_job, self.signing_transport(), false).map(|_| ())
 	}
 }
@@ -771,6 +876,9 @@ impl JobTransport for SigningConsensusTransport {
 			message: ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
 				requester: request.into(),
 				version: version.clone().into(),
+				derivation_path: self.derivation_path.clone(),
+				ecies_ephemeral_public: None,
+				document_key_slot: None,
 			})
 		})))
 	}
@@ -792,23 +900,28 @@ impl JobTransport for SigningJobTransport {
 	type PartialJobResponse=SchnorrPartialSigningResponse;
 
 	fn send_partial_request(&self, node: &NodeId, request: SchnorrPartialSigningRequest) -> Result<(), Error> {
+		let mut message_hashes = request.message_hashes.into_iter().map(Into::into);
 		self.cluster.send(node, Message::SchnorrSigning(SchnorrSigningMessage::SchnorrRequestPartialSignature(SchnorrRequestPartialSignature {
 			session: self.id.clone().into(),
 			sub_session: self.access_key.clone().into(),
 			session_nonce: self.nonce,
 			request_id: request.id.into(),
-			message_hash: request.message_hash.into(),
+			scheme: request.scheme,
+			message_hash: message_hashes.next().expect("at least one message is signed in every session; qed"),
+			additional_message_hashes: message_hashes.collect(),
 			nodes: request.other_nodes_ids.into_iter().map(Into::into).collect(),
 		})))
 	}
 
 	fn send_partial_response(&self, node: &NodeId, response: SchnorrPartialSigningResponse) -> Result<(), Error> {
+		let mut partial_signatures = response.partial_signatures.into_iter().map(Into::into);
 		self.cluster.send(node, Message::SchnorrSigning(SchnorrSigningMessage::SchnorrPartialSignature(SchnorrPartialSignature {
 			session: self.id.clone().into(),
 			sub_session: self.access_key.clone().into(),
 			session_nonce: self.nonce,
 			request_id: response.request_id.into(),
-			partial_signature: response.partial_signature.into(),
+			partial_signature: partial_signatures.next().expect("at least one message is signed in every session; qed"),
+			additional_partial_signatures: partial_signatures.collect(),
 		})))
 	}
 }
@@ -817,6 +930,7 @@ impl JobTransport for SigningJobTransport {
 mod tests {
 	use std::sync::Arc;
 	use std::str::FromStr;
+	use std::iter::once;
 	use std::collections::BTreeMap;
 	use ethereum_types::{Address, H256};
 	use crypto::publickey::{Random, Generator, Public, Secret, public_to_address};
@@ -825,10 +939,11 @@ mod tests {
 	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
 	use key_server_cluster::generation_session::tests::MessageLoop as GenerationMessageLoop;
 	use key_server_cluster::math;
-	use key_server_cluster::message::{SchnorrSigningMessage, SchnorrSigningConsensusMessage,
+	use key_server_cluster::message::{Message, SchnorrSigningMessage, SchnorrSigningConsensusMessage,
 		ConsensusMessage, ConfirmConsensusInitialization, SchnorrSigningGenerationMessage, GenerationMessage,
 		ConfirmInitialization, InitializeSession, SchnorrRequestPartialSignature};
 	use key_server_cluster::signing_session_schnorr::{SessionImpl, SessionState, SessionParams};
+	use key_server_cluster::jobs::signing_job_schnorr::SchnorrSigningScheme;
 
 	#[derive(Debug)]
 	pub struct MessageLoop(pub ClusterMessageLoop);
@@ -857,33 +972,51 @@ mod tests {
 				},
 				access_key: Random.generate().secret().clone(),
 				key_share: self.0.key_storage(at_node).get(&dummy_doc).unwrap(),
+				derivation_path: Vec::new(),
 				acl_storage: Arc::new(DummyAclStorage::default()),
 				cluster: self.0.cluster(0).view().unwrap(),
 				nonce: 0,
 			}, requester).unwrap().0
 		}
 
-		pub fn init_with_version(self, key_version: Option<H256>) -> Result<(Self, Public, H256), Error> {
-			let message_hash = H256::random();
+		pub fn init_with_version(self, key_version: Option<H256>, scheme: SchnorrSigningScheme) -> Result<(Self, Public, H256), Error> {
+			self.init_batch_with_version(key_version, scheme, 1)
+				.map(|(ml, requester, message_hashes)| (ml, requester, message_hashes[0]))
+		}
+
+		pub fn init_batch_with_version(self, key_version: Option<H256>, scheme: SchnorrSigningScheme, messages_count: usize) -> Result<(Self, Public, Vec<H256>), Error> {
+			let message_hashes: Vec<_> = (0..messages_count).map(|_| H256::random()).collect();
 			let requester = Random.generate();
 			let signature = crypto::publickey::sign(requester.secret(), &SessionId::from([1u8; 32])).unwrap();
 			self.0.cluster(0).client().new_schnorr_signing_session(
 				SessionId::from([1u8; 32]),
 				signature.into(),
+				Vec::new(),
 				key_version,
-				message_hash).map(|_| (self, *requester.public(), message_hash)
+				scheme,
+				message_hashes.clone()).map(|_| (self, *requester.public(), message_hashes)
 			)
 		}
 
 		pub fn init(self) -> Result<(Self, Public, H256), Error> {
 			let key_version = self.key_version();
-			self.init_with_version(Some(key_version))
+			self.init_with_version(Some(key_version), SchnorrSigningScheme::Legacy)
+		}
+
+		pub fn init_bip340(self) -> Result<(Self, Public, H256), Error> {
+			let key_version = self.key_version();
+			self.init_with_version(Some(key_version), SchnorrSigningScheme::Bip340)
 		}
 
-		pub fn init_delegated(self) -> Result<(Self, Public, H256), Error> {
+		pub fn init_batch(self, scheme: SchnorrSigningScheme, messages_count: usize) -> Result<(Self, Public, Vec<H256>), Error> {
+			let key_version = self.key_version();
+			self.init_batch_with_version(Some(key_version), scheme, messages_count)
+		}
+
+		pub fn init_delegated(self, scheme: SchnorrSigningScheme) -> Result<(Self, Public, H256), Error> {
 			let doc = [1u8; 32].into();
 			self.0.key_storage(0).remove(&doc).unwrap();
-			self.init_with_version(None)
+			self.init_with_version(None, scheme)
 		}
 
 		pub fn init_with_isolated(self) -> Result<(Self, Public, H256), Error> {
@@ -895,7 +1028,18 @@ mod tests {
 			let key_version = self.key_version();
 			let doc = [1u8; 32].into();
 			self.0.key_storage(0).remove(&doc).unwrap();
-			self.init_with_version(Some(key_version))
+			self.init_with_version(Some(key_version), SchnorrSigningScheme::Legacy)
+		}
+
+		pub fn init_without_public_shares(self) -> Result<(Self, Public, H256), Error> {
+			let key_version = self.key_version();
+			let doc = [1u8; 32].into();
+			for i in 0..self.0.nodes().len() {
+				let mut key_share = self.0.key_storage(i).get(&doc).unwrap().unwrap();
+				key_share.versions.iter_mut().for_each(|v| v.public_shares.clear());
+				self.0.key_storage(i).update(doc, key_share).unwrap();
+			}
+			self.init_with_version(Some(key_version), SchnorrSigningScheme::Legacy)
 		}
 
 		pub fn session_at(&self, idx: usize) -> Arc<SessionImpl> {
@@ -924,10 +1068,76 @@ mod tests {
 			let doc = [1u8; 32].into();
 			let signer_public = ml.0.key_storage(0).get(&doc).unwrap().unwrap().public;
 			let signature = ml.session_at(0).wait().unwrap();
-			assert!(math::verify_schnorr_signature(&signer_public, &signature, &message).unwrap());
+			assert!(math::verify_schnorr_signature(&signer_public, &signature[0], &message).unwrap());
 		}
 	}
 
+	#[test]
+	fn schnorr_complete_gen_sign_session_bip340() {
+		let test_cases = [(0, 1), (0, 5), (1, 5), (2, 5), (3, 5)];
+		for &(threshold, num_nodes) in &test_cases {
+			let (ml, _, message) = MessageLoop::new(num_nodes, threshold).unwrap().init_bip340().unwrap();
+			ml.0.loop_until(|| ml.0.is_empty());
+
+			let doc = [1u8; 32].into();
+			let signer_public = ml.0.key_storage(0).get(&doc).unwrap().unwrap().public;
+			let signature = ml.session_at(0).wait().unwrap();
+			assert!(math::verify_bip340_signature(&math::bip340_x_only_public(&signer_public), &signature[0], &message).unwrap());
+		}
+	}
+
+	#[test]
+	fn schnorr_complete_gen_batch_sign_session() {
+		let test_cases = [(0, 1), (0, 3), (1, 3), (2, 5)];
+		for &(threshold, num_nodes) in &test_cases {
+			for &scheme in &[SchnorrSigningScheme::Legacy, SchnorrSigningScheme::Bip340] {
+				let (ml, _, messages) = MessageLoop::new(num_nodes, threshold).unwrap().init_batch(scheme, 4).unwrap();
+				ml.0.loop_until(|| ml.0.is_empty());
+
+				let doc = [1u8; 32].into();
+				let signer_public = ml.0.key_storage(0).get(&doc).unwrap().unwrap().public;
+				let signatures = ml.session_at(0).wait().unwrap();
+				assert_eq!(signatures.len(), messages.len());
+				for (signature, message) in signatures.iter().zip(messages.iter()) {
+					assert!(match scheme {
+						SchnorrSigningScheme::Legacy =>
+							math::verify_schnorr_signature(&signer_public, signature, message).unwrap(),
+						SchnorrSigningScheme::Bip340 =>
+							math::verify_bip340_signature(&math::bip340_x_only_public(&signer_public), signature, message).unwrap(),
+					});
+				}
+
+				// every message is signed using its own session key
+				let nonces: ::std::collections::BTreeSet<H256> = signatures.iter().map(|s| *s.0).collect();
+				assert_eq!(nonces.len(), messages.len());
+			}
+		}
+	}
+
+	#[test]
+	fn schnorr_batch_session_keys_are_generated_within_single_generation_round() {
+		let (ml, _, messages) = MessageLoop::new(3, 1).unwrap().init_batch(SchnorrSigningScheme::Legacy, 4).unwrap();
+		while let Some((from, to, message)) = ml.0.take_message() {
+			if let Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningGenerationMessage(ref message)) = message {
+				// there could be several messages of the same generation session in the round
+				let mut nonces_indices: Vec<_> = once(message.nonce_index)
+					.chain(message.additional_messages.iter().map(|&(nonce_index, _)| nonce_index))
+					.collect();
+				nonces_indices.dedup();
+				assert_eq!(nonces_indices, (0..messages.len()).collect::<Vec<_>>());
+			}
+			ml.0.process_message(from, to, message);
+		}
+
+		assert_eq!(ml.session_at(0).wait().unwrap().len(), messages.len());
+	}
+
+	#[test]
+	fn schnorr_fails_to_initialize_batch_without_messages() {
+		assert_eq!(MessageLoop::new(3, 1).unwrap().init_batch(SchnorrSigningScheme::Legacy, 0).unwrap_err(),
+			Error::InvalidMessage);
+	}
+
 	#[test]
 	fn schnorr_constructs_in_cluster_of_single_node() {
 		MessageLoop::new(1, 0).unwrap().init().unwrap();
@@ -948,7 +1158,7 @@ mod tests {
 	#[test]
 	fn schnorr_fails_to_initialize_when_already_initialized() {
 		let (ml, _, _) = MessageLoop::new(1, 0).unwrap().init().unwrap();
-		assert_eq!(ml.session_at(0).initialize(ml.key_version(), H256::from_low_u64_be(777)),
+		assert_eq!(ml.session_at(0).initialize(ml.key_version(), SchnorrSigningScheme::Legacy, vec![H256::from_low_u64_be(777)]),
 			Err(Error::InvalidStateForRequest));
 	}
 
@@ -995,10 +1205,12 @@ mod tests {
 			session: SessionId::from([1u8; 32]).into(),
 			sub_session: session.core.access_key.clone().into(),
 			session_nonce: 0,
+			nonce_index: 0,
 			message: GenerationMessage::ConfirmInitialization(ConfirmInitialization {
 				session: SessionId::from([1u8; 32]).into(),
 				session_nonce: 0,
 			}),
+			additional_messages: Vec::new(),
 		}), Err(Error::InvalidStateForRequest));
 	}
 
@@ -1015,6 +1227,7 @@ mod tests {
 			session: SessionId::from([1u8; 32]).into(),
 			sub_session: session.core.access_key.clone().into(),
 			session_nonce: 0,
+			nonce_index: 0,
 			message: GenerationMessage::InitializeSession(InitializeSession {
 				session: SessionId::from([1u8; 32]).into(),
 				session_nonce: 0,
@@ -1023,7 +1236,10 @@ mod tests {
 				nodes: BTreeMap::new(),
 				is_zero: false,
 				threshold: 1,
-			})
+				expiration: None,
+				ecdsa_scheme: Default::default(),
+			}),
+			additional_messages: Vec::new(),
 		}), Err(Error::InvalidMessage));
 	}
 
@@ -1036,7 +1252,9 @@ mod tests {
 			sub_session: session.core.access_key.clone().into(),
 			session_nonce: 0,
 			request_id: Secret::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap().into(),
+			scheme: SchnorrSigningScheme::Legacy,
 			message_hash: H256::zero().into(),
+			additional_message_hashes: Vec::new(),
 			nodes: Default::default(),
 		}), Err(Error::InvalidStateForRequest));
 	}
@@ -1050,7 +1268,9 @@ mod tests {
 			sub_session: session.core.access_key.clone().into(),
 			session_nonce: 0,
 			request_id: Secret::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap().into(),
+			scheme: SchnorrSigningScheme::Legacy,
 			message_hash: H256::zero().into(),
+			additional_message_hashes: Vec::new(),
 			nodes: Default::default(),
 		}), Err(Error::InvalidMessage));
 	}
@@ -1101,18 +1321,47 @@ mod tests {
 			session: SessionId::from([1u8; 32]).into(),
 			sub_session: session.core.access_key.clone().into(),
 			session_nonce: 10,
+			nonce_index: 0,
 			message: GenerationMessage::ConfirmInitialization(ConfirmInitialization {
 				session: SessionId::from([1u8; 32]).into(),
 				session_nonce: 0,
 			}),
+			additional_messages: Vec::new(),
 		});
 		assert_eq!(session.process_message(&ml.0.node(1), &msg), Err(Error::ReplayProtection));
 	}
 
 	#[test]
 	fn schnorr_signing_works_when_delegated_to_other_node() {
-		let (ml, _, _) = MessageLoop::new(3, 1).unwrap().init_delegated().unwrap();
+		let (ml, _, _) = MessageLoop::new(3, 1).unwrap().init_delegated(SchnorrSigningScheme::Legacy).unwrap();
+		ml.ensure_completed();
+	}
+
+	#[test]
+	fn schnorr_bip340_signing_works_when_delegated_to_other_node() {
+		let (ml, _, message) = MessageLoop::new(3, 1).unwrap().init_delegated(SchnorrSigningScheme::Bip340).unwrap();
 		ml.ensure_completed();
+
+		let doc = [1u8; 32].into();
+		let signer_public = ml.0.key_storage(1).get(&doc).unwrap().unwrap().public;
+		let signature = ml.session_at(0).wait().unwrap();
+		assert!(math::verify_bip340_signature(&math::bip340_x_only_public(&signer_public), &signature[0], &message).unwrap());
+	}
+
+	#[test]
+	fn schnorr_batch_signing_works_when_delegated_to_other_node() {
+		let doc = [1u8; 32].into();
+		let ml = MessageLoop::new(3, 1).unwrap();
+		ml.0.key_storage(0).remove(&doc).unwrap();
+		let (ml, _, messages) = ml.init_batch_with_version(None, SchnorrSigningScheme::Legacy, 3).unwrap();
+		ml.ensure_completed();
+
+		let signer_public = ml.0.key_storage(1).get(&doc).unwrap().unwrap().public;
+		let signatures = ml.session_at(0).wait().unwrap();
+		assert_eq!(signatures.len(), messages.len());
+		for (signature, message) in signatures.iter().zip(messages.iter()) {
+			assert!(math::verify_schnorr_signature(&signer_public, signature, message).unwrap());
+		}
 	}
 
 	#[test]
@@ -1120,4 +1369,44 @@ mod tests {
 		let (ml, _, _) = MessageLoop::new(3, 1).unwrap().init_with_isolated().unwrap();
 		ml.ensure_completed();
 	}
+	#[test]
+	fn schnorr_signing_works_when_public_shares_are_unknown() {
+		// key version has been migrated from older storage version => partial signatures can't be verified
+		let (ml, _, message) = MessageLoop::new(3, 1).unwrap().init_without_public_shares().unwrap();
+		ml.ensure_completed();
+
+		let doc = [1u8; 32].into();
+		let signer_public = ml.0.key_storage(0).get(&doc).unwrap().unwrap().public;
+		let signature = ml.session_at(0).wait().unwrap();
+		assert!(math::verify_schnorr_signature(&signer_public, &signature[0], &message).unwrap());
+	}
+
+	#[test]
+	fn schnorr_signing_restarts_when_node_sends_invalid_partial_signature() {
+		let (ml, _, message) = MessageLoop::new(3, 1).unwrap().init().unwrap();
+
+		// node sends random partial signature => it is excluded && session keys are regenerated by remaining nodes
+		let mut invalid_signature_from = None;
+		while invalid_signature_from.is_none() {
+			let (from, to, msg) = ml.0.take_message().unwrap();
+			let msg = match msg {
+				Message::SchnorrSigning(SchnorrSigningMessage::SchnorrPartialSignature(mut msg)) => {
+					msg.partial_signature = math::generate_random_scalar().unwrap().into();
+					invalid_signature_from = Some(from.clone());
+					Message::SchnorrSigning(SchnorrSigningMessage::SchnorrPartialSignature(msg))
+				},
+				msg => msg,
+			};
+			ml.0.process_message(from, to, msg);
+		}
+		ml.ensure_completed();
+
+		let invalid_signature_from = invalid_signature_from.unwrap();
+		assert_eq!(ml.session_at(0).data.lock().consensus_session.consensus_job().rejects().get(&invalid_signature_from), Some(&false));
+
+		let doc = [1u8; 32].into();
+		let signer_public = ml.0.key_storage(0).get(&doc).unwrap().unwrap().public;
+		let signature = ml.session_at(0).wait().unwrap();
+		assert!(math::verify_schnorr_signature(&signer_public, &signature[0], &message).unwrap());
+	}
 }
@@ -20,22 +20,32 @@ use parking_lot::RwLock;
 use crypto::publickey::{Public, Signature, Random, Generator};
 use ethereum_types::{Address, H256};
 use parity_runtime::Executor;
-use blockchain::SigningKeyPair;
-use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, KeyServerSet};
+use blockchain::{SigningKeyPair, SecretKeyPair};
+use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, KeyServerSet, SchnorrSigningScheme,
+	KeyDerivationPath, KeyImportData, KeyExportApprovers, DocumentKeySlotId, KeyExpiration, ExpirationClock, EcdsaSigningScheme};
 use key_server_cluster::cluster_sessions::{WaitableSession, ClusterSession, AdminSession, ClusterSessions,
-	SessionIdWithSubSession, ClusterSessionsContainer, SERVERS_SET_CHANGE_SESSION_ID, create_cluster_view,
+	SessionIdWithSubSession, ClusterSessionsContainer, SERVERS_SET_CHANGE_SESSION_ID, SHARE_REFRESH_ALL_KEYS_ID, create_cluster_view,
 	AdminSessionCreationData, ClusterSessionsListener};
 use key_server_cluster::cluster_sessions_creator::ClusterSessionCreator;
 use key_server_cluster::cluster_connections::{ConnectionProvider, ConnectionManager};
 use key_server_cluster::cluster_connections_net::{NetConnectionsManager,
 	NetConnectionsContainer, NetConnectionsManagerConfig};
 use key_server_cluster::cluster_message_processor::{MessageProcessor, SessionsMessageProcessor};
+use key_server_cluster::cluster_reliable_broadcast::ReliableBroadcast;
 use key_server_cluster::message::Message;
 use key_server_cluster::generation_session::{SessionImpl as GenerationSession};
 use key_server_cluster::decryption_session::{SessionImpl as DecryptionSession};
 use key_server_cluster::encryption_session::{SessionImpl as EncryptionSession};
+use key_server_cluster::key_deletion_session::{SessionImpl as KeyDeletionSession};
+use key_server_cluster::key_import_session::{SessionImpl as KeyImportSession};
+use key_server_cluster::key_export_session::{SessionImpl as KeyExportSession};
+use key_server_cluster::randomness_beacon_session::{SessionImpl as RandomnessBeaconSession};
 use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSession};
 use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSession};
+use key_server_cluster::generation_session_eddsa::{SessionImpl as EddsaGenerationSession};
+use key_server_cluster::signing_session_eddsa::{SessionImpl as EddsaSigningSession};
+use key_server_cluster::generation_session_bls::{SessionImpl as BlsGenerationSession};
+use key_server_cluster::signing_session_bls::{SessionImpl as BlsSigningSession};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSession,
 	IsolatedSessionTransport as KeyVersionNegotiationSessionTransport, ContinueAction};
 use key_server_cluster::connection_trigger::{ConnectionTrigger,
@@ -47,48 +57,109 @@ use key_server_cluster::cluster_connections::tests::{MessagesQueue, TestConnecti
 
 /// Cluster interface for external clients.
 pub trait ClusterClient: Send + Sync {
-	/// Start new generation session.
+	/// Start new generation session. If expiration is passed, generated key couldn't be used after
+	/// this point and it is eventually deleted by every key server.
 	fn new_generation_session(
 		&self,
 		session_id: SessionId,
 		origin: Option<Address>,
 		author: Address,
 		threshold: usize,
+		expiration: Option<KeyExpiration>,
 	) -> Result<WaitableSession<GenerationSession>, Error>;
-	/// Start new encryption session.
+	/// Start new encryption session. If document key slot is passed, document key is stored in this slot
+	/// instead of the default one.
 	fn new_encryption_session(
 		&self,
 		session_id: SessionId,
 		author: Requester,
+		document_key_slot: Option<DocumentKeySlotId>,
 		common_point: Public,
 		encrypted_point: Public,
 	) -> Result<WaitableSession<EncryptionSession>, Error>;
-	/// Start new decryption session.
+	/// Start new key deletion session. Key share is deleted from all its holders, which must be connected to this node.
+	fn new_key_deletion_session(
+		&self,
+		session_id: SessionId,
+		requester: Requester,
+	) -> Result<WaitableSession<KeyDeletionSession>, Error>;
+	/// Start new key import session. Shares are sent to share owners, which must be connected to this node.
+	fn new_key_import_session(
+		&self,
+		session_id: SessionId,
+		author: Address,
+		key: KeyImportData,
+	) -> Result<WaitableSession<KeyImportSession>, Error>;
+	/// Start new randomness beacon session among all connected nodes. Requester must have access to the beacon id.
+	fn new_randomness_beacon_session(
+		&self,
+		session_id: SessionId,
+		requester: Address,
+	) -> Result<WaitableSession<RandomnessBeaconSession>, Error>;
+	/// Start new decryption session. If ECIES ephemeral public is passed, ECDH secret of the ECIES ciphertext
+	/// is computed instead of decrypting the stored document key. If document key slot is passed, document key
+	/// from this slot is decrypted.
 	fn new_decryption_session(
 		&self,
 		session_id: SessionId,
 		origin: Option<Address>,
 		requester: Requester,
+		derivation_path: KeyDerivationPath,
+		ecies_ephemeral_public: Option<Public>,
+		document_key_slot: Option<DocumentKeySlotId>,
 		version: Option<H256>,
 		is_shadow_decryption: bool,
 		is_broadcast_decryption: bool,
 	) -> Result<WaitableSession<DecryptionSession>, Error>;
-	/// Start new Schnorr signing session.
+	/// Start new Schnorr signing session. All messages are signed within single session.
 	fn new_schnorr_signing_session(
 		&self,
 		session_id: SessionId,
 		requester: Requester,
+		derivation_path: KeyDerivationPath,
 		version: Option<H256>,
-		message_hash: H256,
+		scheme: SchnorrSigningScheme,
+		message_hashes: Vec<H256>,
 	) -> Result<WaitableSession<SchnorrSigningSession>, Error>;
 	/// Start new ECDSA session.
 	fn new_ecdsa_signing_session(
 		&self,
 		session_id: SessionId,
 		requester: Requester,
+		derivation_path: KeyDerivationPath,
 		version: Option<H256>,
 		message_hash: H256,
 	) -> Result<WaitableSession<EcdsaSigningSession>, Error>;
+	/// Start new Ed25519 key generation session.
+	fn new_eddsa_generation_session(
+		&self,
+		session_id: SessionId,
+		author: Address,
+		threshold: usize,
+	) -> Result<WaitableSession<EddsaGenerationSession>, Error>;
+	/// Start new EdDSA signing session.
+	fn new_eddsa_signing_session(
+		&self,
+		session_id: SessionId,
+		requester: Requester,
+		version: Option<H256>,
+		message_hash: H256,
+	) -> Result<WaitableSession<EddsaSigningSession>, Error>;
+	/// Start new BLS12-381 key generation session.
+	fn new_bls_generation_session(
+		&self,
+		session_id: SessionId,
+		author: Address,
+		threshold: usize,
+	) -> Result<WaitableSession<BlsGenerationSession>, Error>;
+	/// Start new BLS signing session.
+	fn new_bls_signing_session(
+		&self,
+		session_id: SessionId,
+		requester: Requester,
+		version: Option<H256>,
+		message_hash: H256,
+	) -> Result<WaitableSession<BlsSigningSession>, Error>;
 	/// Start new key version negotiation session.
 	fn new_key_version_negotiation_session(
 		&self,
@@ -103,6 +174,41 @@ pub trait ClusterClient: Send + Sync {
 		old_set_signature: Signature,
 		new_set_signature: Signature,
 	) -> Result<WaitableSession<AdminSession>, Error>;
+	/// Start new share refresh session. `admin_signature` is `session_id`, signed by administrator.
+	fn new_share_refresh_session(
+		&self,
+		session_id: SessionId,
+		admin_signature: Signature,
+	) -> Result<WaitableSession<AdminSession>, Error>;
+	/// Start share refresh sessions for all keys, which have shares on this node.
+	/// `admin_signature` is `SHARE_REFRESH_ALL_KEYS_ID`, signed by administrator.
+	fn new_share_refresh_sessions(
+		&self,
+		admin_signature: Signature,
+	) -> Result<Vec<WaitableSession<AdminSession>>, Error>;
+	/// Start new key rotation session. `admin_signature` is `session_id`, signed by administrator.
+	fn new_key_rotation_session(
+		&self,
+		session_id: SessionId,
+		admin_signature: Signature,
+	) -> Result<WaitableSession<AdminSession>, Error>;
+	/// Start new threshold change session. `admin_signature` is `threshold_change_hash(session_id, new_threshold)`,
+	/// signed by administrator.
+	fn new_threshold_change_session(
+		&self,
+		session_id: SessionId,
+		new_threshold: usize,
+		admin_signature: Signature,
+	) -> Result<WaitableSession<AdminSession>, Error>;
+	/// Start new key export session. Every version holder must be connected to this node.
+	/// `approvals` are keccak(session_id || recipient || expiry), signed by key export approvers.
+	fn new_key_export_session(
+		&self,
+		session_id: SessionId,
+		recipient: Public,
+		expiry: u64,
+		approvals: Vec<Signature>,
+	) -> Result<WaitableSession<KeyExportSession>, Error>;
 
 	/// Listen for new generation sessions.
 	fn add_generation_listener(&self, listener: Arc<dyn ClusterSessionsListener<GenerationSession>>);
@@ -138,6 +244,11 @@ pub trait Cluster: Send + Sync {
 	fn configured_nodes_count(&self) -> usize;
 	/// Get total count of connected key server nodes (valid at the time of ClusterView creation).
 	fn connected_nodes_count(&self) -> usize;
+	/// Broadcast message to all other nodes, so that either all honest nodes are receiving the same message,
+	/// or none of them. Falls back to plain broadcast when reliable broadcast isn't supported (or enabled).
+	fn reliable_broadcast(&self, message: Message) -> Result<(), Error> {
+		self.broadcast(message)
+	}
 }
 
 /// Cluster initialization parameters.
@@ -145,6 +256,8 @@ pub trait Cluster: Send + Sync {
 pub struct ClusterConfiguration {
 	/// KeyPair this node holds.
 	pub self_key_pair: Arc<dyn SigningKeyPair>,
+	/// KeyPair this node holds, used to decrypt data that has been encrypted with node public.
+	pub self_secret_key_pair: Arc<dyn SecretKeyPair>,
 	/// Cluster nodes set.
 	pub key_server_set: Arc<dyn KeyServerSet>,
 	/// Reference to key storage
@@ -153,8 +266,16 @@ pub struct ClusterConfiguration {
 	pub acl_storage: Arc<dyn AclStorage>,
 	/// Administrator public key.
 	pub admin_public: Option<Public>,
+	/// Administrators, who are able to approve export of the full server key.
+	pub key_export_approvers: Option<KeyExportApprovers>,
 	/// Do not remove sessions from container.
 	pub preserve_sessions: bool,
+	/// Use echo-based reliable broadcast for session messages that must be the same on all nodes.
+	pub reliable_broadcast: bool,
+	/// Generate server keys that are signed using MtA-based ECDSA scheme.
+	pub ecdsa_mta_enabled: bool,
+	/// Clock that is used to check if server key has expired.
+	pub expiration_clock: Arc<dyn ExpirationClock>,
 }
 
 /// Network cluster implementation.
@@ -175,6 +296,7 @@ pub struct ClusterView {
 	connected_nodes: BTreeSet<NodeId>,
 	connections: Arc<dyn ConnectionProvider>,
 	self_key_pair: Arc<dyn SigningKeyPair>,
+	reliable_broadcast: bool,
 }
 
 /// Cross-thread shareable cluster data.
@@ -306,7 +428,8 @@ impl<C: ConnectionManager> ClusterCore<C> {
 			self.data.self_key_pair.clone(),
 			connections,
 			connected_nodes,
-			connected_nodes_count + disconnected_nodes_count)))
+			connected_nodes_count + disconnected_nodes_count,
+			self.data.config.reliable_broadcast)))
 	}
 }
 
@@ -315,13 +438,15 @@ impl ClusterView {
 		self_key_pair: Arc<dyn SigningKeyPair>,
 		connections: Arc<dyn ConnectionProvider>,
 		nodes: BTreeSet<NodeId>,
-		configured_nodes_count: usize
+		configured_nodes_count: usize,
+		reliable_broadcast: bool,
 	) -> Self {
 		ClusterView {
 			configured_nodes_count: configured_nodes_count,
 			connected_nodes: nodes,
 			connections,
 			self_key_pair,
+			reliable_broadcast,
 		}
 	}
 }
@@ -358,6 +483,21 @@ impl Cluster for ClusterView {
 	fn connected_nodes_count(&self) -> usize {
 		self.connected_nodes.len()
 	}
+
+	fn reliable_broadcast(&self, message: Message) -> Result<(), Error> {
+		if !self.reliable_broadcast {
+			return self.broadcast(message);
+		}
+
+		// only session participants (i.e. nodes this view is connected to) are participating in reliable broadcast
+		trace!(target: "secretstore_net", "{}: reliably broadcasted message {}", self.self_key_pair.public(), message);
+		let messages = ReliableBroadcast::broadcast_messages(self.self_key_pair.public(), &self.connected_nodes, message)?;
+		for (node, message) in messages {
+			let connection = self.connections.connection(&node).ok_or(Error::NodeDisconnected)?;
+			connection.send_message(message);
+		}
+		Ok(())
+	}
 }
 
 impl<C: ConnectionManager> ClusterClientImpl<C> {
@@ -367,6 +507,14 @@ impl<C: ConnectionManager> ClusterClientImpl<C> {
 		}
 	}
 
+	/// ECDSA signing scheme of keys, generated or imported by this node.
+	fn ecdsa_scheme(&self) -> EcdsaSigningScheme {
+		match self.data.config.ecdsa_mta_enabled {
+			true => EcdsaSigningScheme::MultiplicativeToAdditive,
+			false => EcdsaSigningScheme::NonceInversion,
+		}
+	}
+
 	fn create_key_version_negotiation_session(
 		&self,
 		session_id: SessionId,
@@ -376,7 +524,8 @@ impl<C: ConnectionManager> ClusterClientImpl<C> {
 
 		let access_key = Random.generate().secret().clone();
 		let session_id = SessionIdWithSubSession::new(session_id, access_key);
-		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false)?;
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
 		let session = self.data.sessions.negotiation_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false, None)?;
 		match session.session.initialize(connected_nodes) {
 			Ok(()) => Ok(session),
@@ -386,6 +535,23 @@ impl<C: ConnectionManager> ClusterClientImpl<C> {
 			}
 		}
 	}
+
+	fn create_share_refresh_session(
+		&self,
+		session_id: SessionId,
+		signed_id: H256,
+		admin_signature: Signature,
+	) -> Result<WaitableSession<AdminSession>, Error> {
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.admin_sessions.insert(cluster, self.data.self_key_pair.public().clone(),
+			session_id, None, false, Some(AdminSessionCreationData::ShareRefresh))?;
+		let initialization_result = session.session.as_share_refresh().expect("share refresh session is created; qed")
+			.initialize(None, signed_id, admin_signature);
+		process_initialization_result(
+			initialization_result,
+			session, &self.data.sessions.admin_sessions)
+	}
 }
 
 impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
@@ -395,14 +561,17 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 		origin: Option<Address>,
 		author: Address,
 		threshold: usize,
+		expiration: Option<KeyExpiration>,
 	) -> Result<WaitableSession<GenerationSession>, Error> {
 		let mut connected_nodes = self.data.connections.provider().connected_nodes()?;
 		connected_nodes.insert(self.data.self_key_pair.public().clone());
 
-		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), true)?;
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), true,
+			self.data.config.reliable_broadcast)?;
+		let ecdsa_scheme = self.ecdsa_scheme();
 		let session = self.data.sessions.generation_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
 		process_initialization_result(
-			session.session.initialize(origin, author, false, threshold, connected_nodes.into()),
+			session.session.initialize(origin, author, false, threshold, expiration, ecdsa_scheme, connected_nodes.into()),
 			session, &self.data.sessions.generation_sessions)
 	}
 
@@ -410,24 +579,74 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 		&self,
 		session_id: SessionId,
 		requester: Requester,
+		document_key_slot: Option<DocumentKeySlotId>,
 		common_point: Public,
 		encrypted_point: Public,
 	) -> Result<WaitableSession<EncryptionSession>, Error> {
 		let mut connected_nodes = self.data.connections.provider().connected_nodes()?;
 		connected_nodes.insert(self.data.self_key_pair.public().clone());
 
-		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), true)?;
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), true,
+			self.data.config.reliable_broadcast)?;
 		let session = self.data.sessions.encryption_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
 		process_initialization_result(
-			session.session.initialize(requester, common_point, encrypted_point),
+			session.session.initialize(requester, document_key_slot, common_point, encrypted_point),
 			session, &self.data.sessions.encryption_sessions)
 	}
 
+	fn new_key_deletion_session(
+		&self,
+		session_id: SessionId,
+		requester: Requester,
+	) -> Result<WaitableSession<KeyDeletionSession>, Error> {
+		// only holders of the key share must be connected
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.key_deletion_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
+		process_initialization_result(
+			session.session.initialize(requester),
+			session, &self.data.sessions.key_deletion_sessions)
+	}
+
+	fn new_key_import_session(
+		&self,
+		session_id: SessionId,
+		author: Address,
+		key: KeyImportData,
+	) -> Result<WaitableSession<KeyImportSession>, Error> {
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.key_import_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
+		process_initialization_result(
+			session.session.initialize(author, self.ecdsa_scheme(), key),
+			session, &self.data.sessions.key_import_sessions)
+	}
+
+	fn new_randomness_beacon_session(
+		&self,
+		session_id: SessionId,
+		requester: Address,
+	) -> Result<WaitableSession<RandomnessBeaconSession>, Error> {
+		if !self.data.config.acl_storage.check(requester, &session_id)? {
+			return Err(Error::AccessDenied);
+		}
+
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.randomness_beacon_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
+		process_initialization_result(
+			session.session.initialize(),
+			session, &self.data.sessions.randomness_beacon_sessions)
+	}
+
 	fn new_decryption_session(
 		&self,
 		session_id: SessionId,
 		origin: Option<Address>,
 		requester: Requester,
+		derivation_path: KeyDerivationPath,
+		ecies_ephemeral_public: Option<Public>,
+		document_key_slot: Option<DocumentKeySlotId>,
 		version: Option<H256>,
 		is_shadow_decryption: bool,
 		is_broadcast_decryption: bool,
@@ -437,9 +656,10 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 
 		let access_key = Random.generate().secret().clone();
 		let session_id = SessionIdWithSubSession::new(session_id, access_key);
-		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false)?;
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
 		let session = self.data.sessions.decryption_sessions.insert(cluster, self.data.self_key_pair.public().clone(),
-			session_id.clone(), None, false, Some(requester))?;
+			session_id.clone(), None, false, Some((requester, derivation_path, ecies_ephemeral_public, document_key_slot)))?;
 
 		let initialization_result = match version {
 			Some(version) => session.session.initialize(origin, version, is_shadow_decryption, is_broadcast_decryption),
@@ -467,23 +687,27 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 		&self,
 		session_id: SessionId,
 		requester: Requester,
+		derivation_path: KeyDerivationPath,
 		version: Option<H256>,
-		message_hash: H256,
+		scheme: SchnorrSigningScheme,
+		message_hashes: Vec<H256>,
 	) -> Result<WaitableSession<SchnorrSigningSession>, Error> {
 		let mut connected_nodes = self.data.connections.provider().connected_nodes()?;
 		connected_nodes.insert(self.data.self_key_pair.public().clone());
 
 		let access_key = Random.generate().secret().clone();
 		let session_id = SessionIdWithSubSession::new(session_id, access_key);
-		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false)?;
-		let session = self.data.sessions.schnorr_signing_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false, Some(requester))?;
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.schnorr_signing_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false,
+			Some((requester, derivation_path)))?;
 
 		let initialization_result = match version {
-			Some(version) => session.session.initialize(version, message_hash),
+			Some(version) => session.session.initialize(version, scheme, message_hashes),
 			None => {
 				self.create_key_version_negotiation_session(session_id.id.clone())
 					.map(|version_session| {
-						let continue_action = ContinueAction::SchnorrSign(session.session.clone(), message_hash);
+						let continue_action = ContinueAction::SchnorrSign(session.session.clone(), scheme, message_hashes);
 						version_session.session.set_continue_action(continue_action);
 						self.data.message_processor.try_continue_session(Some(version_session.session));
 					})
@@ -499,6 +723,7 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 		&self,
 		session_id: SessionId,
 		requester: Requester,
+		derivation_path: KeyDerivationPath,
 		version: Option<H256>,
 		message_hash: H256,
 	) -> Result<WaitableSession<EcdsaSigningSession>, Error> {
@@ -507,8 +732,10 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 
 		let access_key = Random.generate().secret().clone();
 		let session_id = SessionIdWithSubSession::new(session_id, access_key);
-		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false)?;
-		let session = self.data.sessions.ecdsa_signing_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false, Some(requester))?;
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.ecdsa_signing_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false,
+			Some((requester, derivation_path)))?;
 
 		let initialization_result = match version {
 			Some(version) => session.session.initialize(version, message_hash),
@@ -527,6 +754,106 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 			session, &self.data.sessions.ecdsa_signing_sessions)
 	}
 
+	fn new_eddsa_generation_session(
+		&self,
+		session_id: SessionId,
+		author: Address,
+		threshold: usize,
+	) -> Result<WaitableSession<EddsaGenerationSession>, Error> {
+		let mut connected_nodes = self.data.connections.provider().connected_nodes()?;
+		connected_nodes.insert(self.data.self_key_pair.public().clone());
+
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), true,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.eddsa_generation_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
+		process_initialization_result(
+			session.session.initialize(author, threshold, connected_nodes.into()),
+			session, &self.data.sessions.eddsa_generation_sessions)
+	}
+
+	fn new_eddsa_signing_session(
+		&self,
+		session_id: SessionId,
+		requester: Requester,
+		version: Option<H256>,
+		message_hash: H256,
+	) -> Result<WaitableSession<EddsaSigningSession>, Error> {
+		let mut connected_nodes = self.data.connections.provider().connected_nodes()?;
+		connected_nodes.insert(self.data.self_key_pair.public().clone());
+
+		let access_key = Random.generate().secret().clone();
+		let session_id = SessionIdWithSubSession::new(session_id, access_key);
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.eddsa_signing_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false, Some(requester))?;
+
+		let initialization_result = match version {
+			Some(version) => session.session.initialize(version, message_hash),
+			None => {
+				self.create_key_version_negotiation_session(session_id.id.clone())
+					.map(|version_session| {
+						let continue_action = ContinueAction::EddsaSign(session.session.clone(), message_hash);
+						version_session.session.set_continue_action(continue_action);
+						self.data.message_processor.try_continue_session(Some(version_session.session));
+					})
+			},
+		};
+
+		process_initialization_result(
+			initialization_result,
+			session, &self.data.sessions.eddsa_signing_sessions)
+	}
+
+	fn new_bls_generation_session(
+		&self,
+		session_id: SessionId,
+		author: Address,
+		threshold: usize,
+	) -> Result<WaitableSession<BlsGenerationSession>, Error> {
+		let mut connected_nodes = self.data.connections.provider().connected_nodes()?;
+		connected_nodes.insert(self.data.self_key_pair.public().clone());
+
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), true,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.bls_generation_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
+		process_initialization_result(
+			session.session.initialize(author, threshold, connected_nodes.into()),
+			session, &self.data.sessions.bls_generation_sessions)
+	}
+
+	fn new_bls_signing_session(
+		&self,
+		session_id: SessionId,
+		requester: Requester,
+		version: Option<H256>,
+		message_hash: H256,
+	) -> Result<WaitableSession<BlsSigningSession>, Error> {
+		let mut connected_nodes = self.data.connections.provider().connected_nodes()?;
+		connected_nodes.insert(self.data.self_key_pair.public().clone());
+
+		let access_key = Random.generate().secret().clone();
+		let session_id = SessionIdWithSubSession::new(session_id, access_key);
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.bls_signing_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id.clone(), None, false, Some(requester))?;
+
+		let initialization_result = match version {
+			Some(version) => session.session.initialize(version, message_hash),
+			None => {
+				self.create_key_version_negotiation_session(session_id.id.clone())
+					.map(|version_session| {
+						let continue_action = ContinueAction::BlsSign(session.session.clone(), message_hash);
+						version_session.session.set_continue_action(continue_action);
+						self.data.message_processor.try_continue_session(Some(version_session.session));
+					})
+			},
+		};
+
+		process_initialization_result(
+			initialization_result,
+			session, &self.data.sessions.bls_signing_sessions)
+	}
+
 	fn new_key_version_negotiation_session(
 		&self,
 		session_id: SessionId,
@@ -556,6 +883,71 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 			})
 	}
 
+	fn new_share_refresh_session(
+		&self,
+		session_id: SessionId,
+		admin_signature: Signature,
+	) -> Result<WaitableSession<AdminSession>, Error> {
+		self.create_share_refresh_session(session_id, session_id, admin_signature)
+	}
+
+	fn new_share_refresh_sessions(
+		&self,
+		admin_signature: Signature,
+	) -> Result<Vec<WaitableSession<AdminSession>>, Error> {
+		self.data.config.key_storage.iter()
+			.map(|(key_id, _)| self.create_share_refresh_session(key_id, *SHARE_REFRESH_ALL_KEYS_ID, admin_signature.clone()))
+			.collect()
+	}
+
+	fn new_key_rotation_session(
+		&self,
+		session_id: SessionId,
+		admin_signature: Signature,
+	) -> Result<WaitableSession<AdminSession>, Error> {
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.admin_sessions.insert(cluster, self.data.self_key_pair.public().clone(),
+			session_id, None, false, Some(AdminSessionCreationData::KeyRotation))?;
+		let initialization_result = session.session.as_key_rotation().expect("key rotation session is created; qed")
+			.initialize(None, admin_signature);
+		process_initialization_result(
+			initialization_result,
+			session, &self.data.sessions.admin_sessions)
+	}
+
+	fn new_threshold_change_session(
+		&self,
+		session_id: SessionId,
+		new_threshold: usize,
+		admin_signature: Signature,
+	) -> Result<WaitableSession<AdminSession>, Error> {
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.admin_sessions.insert(cluster, self.data.self_key_pair.public().clone(),
+			session_id, None, false, Some(AdminSessionCreationData::ThresholdChange))?;
+		let initialization_result = session.session.as_threshold_change().expect("threshold change session is created; qed")
+			.initialize(None, new_threshold, admin_signature);
+		process_initialization_result(
+			initialization_result,
+			session, &self.data.sessions.admin_sessions)
+	}
+
+	fn new_key_export_session(
+		&self,
+		session_id: SessionId,
+		recipient: Public,
+		expiry: u64,
+		approvals: Vec<Signature>,
+	) -> Result<WaitableSession<KeyExportSession>, Error> {
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), false,
+			self.data.config.reliable_broadcast)?;
+		let session = self.data.sessions.key_export_sessions.insert(cluster, self.data.self_key_pair.public().clone(), session_id, None, false, None)?;
+		process_initialization_result(
+			session.session.initialize(recipient, expiry, approvals),
+			session, &self.data.sessions.key_export_sessions)
+	}
+
 	fn add_generation_listener(&self, listener: Arc<dyn ClusterSessionsListener<GenerationSession>>) {
 		self.data.sessions.generation_sessions.add_listener(listener);
 	}
@@ -610,7 +1002,7 @@ pub fn new_servers_set_change_session(
 		None => *SERVERS_SET_CHANGE_SESSION_ID,
 	};
 
-	let cluster = create_cluster_view(self_key_pair.clone(), connections, true)?;
+	let cluster = create_cluster_view(self_key_pair.clone(), connections, true, sessions.reliable_broadcast())?;
 	let creation_data = AdminSessionCreationData::ServersSetChange(params.migration_id, params.new_nodes_set.clone());
 	let session = sessions.admin_sessions
 		.insert(cluster, *self_key_pair.public(), session_id, None, true, Some(creation_data))?;
@@ -659,7 +1051,8 @@ pub mod tests {
 	use crypto::publickey::{Random, Generator, Public, Signature, sign};
 	use blockchain::SigningKeyPair;
 	use key_server_cluster::{NodeId, SessionId, Requester, Error, DummyAclStorage, DummyKeyStorage,
-		MapKeyServerSet, PlainNodeKeyPair};
+		MapKeyServerSet, PlainNodeKeyPair, SchnorrSigningScheme, KeyDerivationPath, KeyImportData, KeyExportApprovers,
+		DocumentKeySlotId, KeyExpiration, SystemClock};
 	use key_server_cluster::message::Message;
 	use key_server_cluster::cluster::{new_test_cluster, Cluster, ClusterCore, ClusterConfiguration, ClusterClient};
 	use key_server_cluster::cluster_connections::ConnectionManager;
@@ -670,8 +1063,16 @@ pub mod tests {
 		SessionState as GenerationSessionState};
 	use key_server_cluster::decryption_session::{SessionImpl as DecryptionSession};
 	use key_server_cluster::encryption_session::{SessionImpl as EncryptionSession};
+	use key_server_cluster::key_deletion_session::{SessionImpl as KeyDeletionSession};
+	use key_server_cluster::key_import_session::{SessionImpl as KeyImportSession};
+	use key_server_cluster::key_export_session::{SessionImpl as KeyExportSession};
+	use key_server_cluster::randomness_beacon_session::{SessionImpl as RandomnessBeaconSession};
 	use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSession};
 	use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSession};
+	use key_server_cluster::generation_session_eddsa::{SessionImpl as EddsaGenerationSession};
+	use key_server_cluster::signing_session_eddsa::{SessionImpl as EddsaSigningSession};
+	use key_server_cluster::generation_session_bls::{SessionImpl as BlsGenerationSession};
+	use key_server_cluster::signing_session_bls::{SessionImpl as BlsSigningSession};
 	use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSession,
 		IsolatedSessionTransport as KeyVersionNegotiationSessionTransport};
 
@@ -699,6 +1100,7 @@ pub mod tests {
 			_origin: Option<Address>,
 			_author: Address,
 			_threshold: usize,
+			_expiration: Option<KeyExpiration>,
 		) -> Result<WaitableSession<GenerationSession>, Error> {
 			self.generation_requests_count.fetch_add(1, Ordering::Relaxed);
 			Err(Error::Internal("test-error".into()))
@@ -707,16 +1109,42 @@ pub mod tests {
 			&self,
 			_session_id: SessionId,
 			_requester: Requester,
+			_document_key_slot: Option<DocumentKeySlotId>,
 			_common_point: Public,
 			_encrypted_point: Public,
 		) -> Result<WaitableSession<EncryptionSession>, Error> {
 			unimplemented!("test-only")
 		}
+		fn new_key_deletion_session(
+			&self,
+			_session_id: SessionId,
+			_requester: Requester,
+		) -> Result<WaitableSession<KeyDeletionSession>, Error> {
+			unimplemented!("test-only")
+		}
+		fn new_key_import_session(
+			&self,
+			_session_id: SessionId,
+			_author: Address,
+			_key: KeyImportData,
+		) -> Result<WaitableSession<KeyImportSession>, Error> {
+			unimplemented!("test-only")
+		}
+		fn new_randomness_beacon_session(
+			&self,
+			_session_id: SessionId,
+			_requester: Address,
+		) -> Result<WaitableSession<RandomnessBeaconSession>, Error> {
+			unimplemented!("test-only")
+		}
 		fn new_decryption_session(
 			&self,
 			_session_id: SessionId,
 			_origin: Option<Address>,
 			_requester: Requester,
+			_derivation_path: KeyDerivationPath,
+			_ecies_ephemeral_public: Option<Public>,
+			_document_key_slot: Option<DocumentKeySlotId>,
 			_version: Option<H256>,
 			_is_shadow_decryption: bool,
 			_is_broadcast_session: bool,
@@ -727,8 +1155,10 @@ pub mod tests {
 			&self,
 			_session_id: SessionId,
 			_requester: Requester,
+			_derivation_path: KeyDerivationPath,
 			_version: Option<H256>,
-			_message_hash: H256,
+			_scheme: SchnorrSigningScheme,
+			_message_hashes: Vec<H256>,
 		) -> Result<WaitableSession<SchnorrSigningSession>, Error> {
 			unimplemented!("test-only")
 		}
@@ -736,11 +1166,46 @@ pub mod tests {
 			&self,
 			_session_id: SessionId,
 			_requester: Requester,
+			_derivation_path: KeyDerivationPath,
 			_version: Option<H256>,
 			_message_hash: H256,
 		) -> Result<WaitableSession<EcdsaSigningSession>, Error> {
 			unimplemented!("test-only")
 		}
+		fn new_eddsa_generation_session(
+			&self,
+			_session_id: SessionId,
+			_author: Address,
+			_threshold: usize,
+		) -> Result<WaitableSession<EddsaGenerationSession>, Error> {
+			unimplemented!("test-only")
+		}
+		fn new_eddsa_signing_session(
+			&self,
+			_session_id: SessionId,
+			_requester: Requester,
+			_version: Option<H256>,
+			_message_hash: H256,
+		) -> Result<WaitableSession<EddsaSigningSession>, Error> {
+			unimplemented!("test-only")
+		}
+		fn new_bls_generation_session(
+			&self,
+			_session_id: SessionId,
+			_author: Address,
+			_threshold: usize,
+		) -> Result<WaitableSession<BlsGenerationSession>, Error> {
+			unimplemented!("test-only")
+		}
+		fn new_bls_signing_session(
+			&self,
+			_session_id: SessionId,
+			_requester: Requester,
+			_version: Option<H256>,
+			_message_hash: H256,
+		) -> Result<WaitableSession<BlsSigningSession>, Error> {
+			unimplemented!("test-only")
+		}
 
 		fn new_key_version_negotiation_session(
 			&self,
@@ -759,6 +1224,48 @@ pub mod tests {
 			unimplemented!("test-only")
 		}
 
+		fn new_share_refresh_session(
+			&self,
+			_session_id: SessionId,
+			_admin_signature: Signature,
+		) -> Result<WaitableSession<AdminSession>, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn new_share_refresh_sessions(
+			&self,
+			_admin_signature: Signature,
+		) -> Result<Vec<WaitableSession<AdminSession>>, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn new_key_rotation_session(
+			&self,
+			_session_id: SessionId,
+			_admin_signature: Signature,
+		) -> Result<WaitableSession<AdminSession>, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn new_threshold_change_session(
+			&self,
+			_session_id: SessionId,
+			_new_threshold: usize,
+			_admin_signature: Signature,
+		) -> Result<WaitableSession<AdminSession>, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn new_key_export_session(
+			&self,
+			_session_id: SessionId,
+			_recipient: Public,
+			_expiry: u64,
+			_approvals: Vec<Signature>,
+		) -> Result<WaitableSession<KeyExportSession>, Error> {
+			unimplemented!("test-only")
+		}
+
 		fn add_generation_listener(&self, _listener: Arc<dyn ClusterSessionsListener<GenerationSession>>) {}
 		fn add_decryption_listener(&self, _listener: Arc<dyn ClusterSessionsListener<DecryptionSession>>) {}
 		fn add_key_version_negotiation_listener(&self, _listener: Arc<dyn ClusterSessionsListener<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>>) {}
@@ -836,6 +1343,7 @@ pub mod tests {
 	pub struct MessageLoop {
 		messages: MessagesQueue,
 		preserve_sessions: bool,
+		reliable_broadcast: bool,
 		key_pairs_map: BTreeMap<NodeId, Arc<PlainNodeKeyPair>>,
 		acl_storages_map: BTreeMap<NodeId, Arc<DummyAclStorage>>,
 		key_storages_map: BTreeMap<NodeId, Arc<DummyKeyStorage>>,
@@ -911,6 +1419,20 @@ pub mod tests {
 			}
 		}
 
+		/// Is node isolated from others?
+		pub fn is_isolated(&self, idx: usize) -> bool {
+			self.cluster(idx).data.connections.provider().connected_nodes().is_err()
+		}
+
+		/// Returns set of all nodes the node is aware of (including the node itself).
+		pub fn cluster_nodes_of(&self, node: &NodeId) -> BTreeSet<NodeId> {
+			let connections = self.clusters_map[node].data.connections.provider();
+			connections.connected_nodes().unwrap_or_default().into_iter()
+				.chain(connections.disconnected_nodes())
+				.chain(::std::iter::once(*node))
+				.collect()
+		}
+
 		/// Exclude node from cluster.
 		pub fn exclude(&mut self, idx: usize) {
 			let node = self.node(idx);
@@ -931,6 +1453,7 @@ pub mod tests {
 			let acl_storage = Arc::new(DummyAclStorage::default());
 			let cluster_params = ClusterConfiguration {
 				self_key_pair: node_key_pair.clone(),
+				self_secret_key_pair: node_key_pair.clone(),
 				key_server_set: Arc::new(MapKeyServerSet::new(false, self.nodes().iter()
 					.chain(::std::iter::once(node_key_pair.public()))
 					.map(|n| (*n, format!("127.0.0.1:{}", 13).parse().unwrap()))
@@ -938,7 +1461,11 @@ pub mod tests {
 				key_storage: key_storage.clone(),
 				acl_storage: acl_storage.clone(),
 				admin_public: None,
+				key_export_approvers: None,
 				preserve_sessions: self.preserve_sessions,
+				reliable_broadcast: self.reliable_broadcast,
+				ecdsa_mta_enabled: false,
+				expiration_clock: Arc::new(SystemClock),
 			};
 			let cluster = new_test_cluster(self.messages.clone(), cluster_params).unwrap();
 
@@ -991,14 +1518,27 @@ pub mod tests {
 	}
 
 	pub fn make_clusters(num_nodes: usize) -> MessageLoop {
-		do_make_clusters(num_nodes, false)
+		do_make_clusters(num_nodes, false, false, None)
 	}
 
 	pub fn make_clusters_and_preserve_sessions(num_nodes: usize) -> MessageLoop {
-		do_make_clusters(num_nodes, true)
+		do_make_clusters(num_nodes, true, false, None)
+	}
+
+	pub fn make_clusters_with_reliable_broadcast(num_nodes: usize) -> MessageLoop {
+		do_make_clusters(num_nodes, true, true, None)
 	}
 
-	fn do_make_clusters(num_nodes: usize, preserve_sessions: bool) -> MessageLoop {
+	pub fn make_clusters_with_key_export_approvers(num_nodes: usize, approvers: KeyExportApprovers) -> MessageLoop {
+		do_make_clusters(num_nodes, false, false, Some(approvers))
+	}
+
+	fn do_make_clusters(
+		num_nodes: usize,
+		preserve_sessions: bool,
+		reliable_broadcast: bool,
+		key_export_approvers: Option<KeyExportApprovers>,
+	) -> MessageLoop {
 		let ports_begin = 0;
 		let messages = Arc::new(Mutex::new(VecDeque::new()));
 		let key_pairs: Vec<_> = (0..num_nodes)
@@ -1007,13 +1547,18 @@ pub mod tests {
 		let acl_storages: Vec<_> = (0..num_nodes).map(|_| Arc::new(DummyAclStorage::default())).collect();
 		let cluster_params: Vec<_> = (0..num_nodes).map(|i| ClusterConfiguration {
 			self_key_pair: key_pairs[i].clone(),
+			self_secret_key_pair: key_pairs[i].clone(),
 			key_server_set: Arc::new(MapKeyServerSet::new(false, key_pairs.iter().enumerate()
 				.map(|(j, kp)| (*kp.public(), format!("127.0.0.1:{}", ports_begin + j as u16).parse().unwrap()))
 				.collect())),
 			key_storage: key_storages[i].clone(),
 			acl_storage: acl_storages[i].clone(),
 			admin_public: None,
+			key_export_approvers: key_export_approvers.clone(),
 			preserve_sessions,
+			reliable_broadcast,
+			ecdsa_mta_enabled: false,
+			expiration_clock: Arc::new(SystemClock),
 		}).collect();
 		let clusters: Vec<_> = cluster_params.into_iter()
 			.map(|params| new_test_cluster(messages.clone(), params).unwrap())
@@ -1025,14 +1570,22 @@ pub mod tests {
 			.map(|(c, ks)| (*c.data.config.self_key_pair.public(), ks)).collect();
 		let acl_storages_map = clusters.iter().zip(acl_storages.into_iter())
 			.map(|(c, acls)| (*c.data.config.self_key_pair.public(), acls)).collect();
-		MessageLoop { preserve_sessions, messages, key_pairs_map, acl_storages_map, key_storages_map, clusters_map }
+		MessageLoop {
+			preserve_sessions,
+			reliable_broadcast,
+			messages,
+			key_pairs_map,
+			acl_storages_map,
+			key_storages_map,
+			clusters_map,
+		}
 	}
 
 	#[test]
 	fn cluster_wont_start_generation_session_if_not_fully_connected() {
 		let ml = make_clusters(3);
 		ml.cluster(0).data.connections.disconnect(*ml.cluster(0).data.self_key_pair.public());
-		match ml.cluster(0).client().new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1) {
+		match ml.cluster(0).client().new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1, None) {
 			Err(Error::NodeDisconnected) => (),
 			Err(e) => panic!("unexpected error {:?}", e),
 			_ => panic!("unexpected success"),
@@ -1049,7 +1602,7 @@ pub mod tests {
 
 		// start && wait for generation session to fail
 		let session = ml.cluster(0).client()
-			.new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1).unwrap().session;
+			.new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1, None).unwrap().session;
 		ml.loop_until(|| session.joint_public_and_secret().is_some()
 			&& ml.cluster(0).client().generation_session(&SessionId::from([1u8; 32])).is_none());
 		assert!(session.joint_public_and_secret().unwrap().is_err());
@@ -1076,7 +1629,7 @@ pub mod tests {
 
 		// start && wait for generation session to fail
 		let session = ml.cluster(0).client()
-			.new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1).unwrap().session;
+			.new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1, None).unwrap().session;
 		ml.loop_until(|| session.joint_public_and_secret().is_some()
 			&& ml.cluster(0).client().generation_session(&SessionId::from([1u8; 32])).is_none());
 		assert!(session.joint_public_and_secret().unwrap().is_err());
@@ -1101,7 +1654,7 @@ pub mod tests {
 
 		// start && wait for generation session to complete
 		let session = ml.cluster(0).client()
-			.new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1).unwrap().session;
+			.new_generation_session(SessionId::from([1u8; 32]), Default::default(), Default::default(), 1, None).unwrap().session;
 		ml.loop_until(|| (session.state() == GenerationSessionState::Finished
 			|| session.state() == GenerationSessionState::Failed)
 			&& ml.cluster(0).client().generation_session(&SessionId::from([1u8; 32])).is_none());
@@ -1130,12 +1683,12 @@ pub mod tests {
 		{
 			// try to start generation session => fail in initialization
 			assert_eq!(
-				client.new_generation_session(SessionId::from([1u8; 32]), None, Default::default(), 100).map(|_| ()),
+				client.new_generation_session(SessionId::from([1u8; 32]), None, Default::default(), 100, None).map(|_| ()),
 				Err(Error::NotEnoughNodesForThreshold));
 
 			// try to start generation session => fails in initialization
 			assert_eq!(
-				client.new_generation_session(SessionId::from([1u8; 32]), None, Default::default(), 100).map(|_| ()),
+				client.new_generation_session(SessionId::from([1u8; 32]), None, Default::default(), 100, None).map(|_| ()),
 				Err(Error::NotEnoughNodesForThreshold));
 
 			assert!(ml.cluster(0).data.sessions.generation_sessions.is_empty());
@@ -1146,14 +1699,14 @@ pub mod tests {
 			// try to start decryption session => fails in initialization
 			assert_eq!(
 				client.new_decryption_session(
-					Default::default(), Default::default(), Default::default(), Some(Default::default()), false, false
+					Default::default(), Default::default(), Default::default(), Vec::new(), None, None, Some(Default::default()), false, false
 				).map(|_| ()),
 				Err(Error::InvalidMessage));
 
 			// try to start generation session => fails in initialization
 			assert_eq!(
 				client.new_decryption_session(
-					Default::default(), Default::default(), Default::default(), Some(Default::default()), false, false
+					Default::default(), Default::default(), Default::default(), Vec::new(), None, None, Some(Default::default()), false, false
 				).map(|_| ()),
 				Err(Error::InvalidMessage));
 
@@ -1170,7 +1723,7 @@ pub mod tests {
 
 		// start && wait for generation session to complete
 		let session = ml.cluster(0).client().
-			new_generation_session(dummy_session_id, Default::default(), Default::default(), 1).unwrap().session;
+			new_generation_session(dummy_session_id, Default::default(), Default::default(), 1, None).unwrap().session;
 		ml.loop_until(|| (session.state() == GenerationSessionState::Finished
 			|| session.state() == GenerationSessionState::Failed)
 			&& ml.cluster(0).client().generation_session(&dummy_session_id).is_none());
@@ -1184,7 +1737,7 @@ pub mod tests {
 		let dummy_message = [1u8; 32].into();
 		let signature = sign(Random.generate().secret(), &dummy_message).unwrap();
 		let session0 = ml.cluster(0).client()
-			.new_schnorr_signing_session(dummy_session_id, signature.into(), None, Default::default()).unwrap();
+			.new_schnorr_signing_session(dummy_session_id, signature.into(), Vec::new(), None, SchnorrSigningScheme::Legacy, vec![Default::default()]).unwrap();
 		let session = ml.cluster(0).data.sessions.schnorr_signing_sessions.first().unwrap();
 
 		ml.loop_until(|| session.is_finished() && (0..3).all(|i|
@@ -1194,7 +1747,7 @@ pub mod tests {
 		// and try to sign message with generated key using node that has no key share
 		let signature = sign(Random.generate().secret(), &dummy_message).unwrap();
 		let session2 = ml.cluster(2).client()
-			.new_schnorr_signing_session(dummy_session_id, signature.into(), None, Default::default()).unwrap();
+			.new_schnorr_signing_session(dummy_session_id, signature.into(), Vec::new(), None, SchnorrSigningScheme::Legacy, vec![Default::default()]).unwrap();
 		let session = ml.cluster(2).data.sessions.schnorr_signing_sessions.first().unwrap();
 
 		ml.loop_until(|| session.is_finished()  && (0..3).all(|i|
@@ -1207,7 +1760,7 @@ pub mod tests {
 		// and try to sign message with generated key
 		let signature = sign(Random.generate().secret(), &dummy_message).unwrap();
 		let session1 = ml.cluster(0).client()
-			.new_schnorr_signing_session(dummy_session_id, signature.into(), None, Default::default()).unwrap();
+			.new_schnorr_signing_session(dummy_session_id, signature.into(), Vec::new(), None, SchnorrSigningScheme::Legacy, vec![Default::default()]).unwrap();
 		let session = ml.cluster(0).data.sessions.schnorr_signing_sessions.first().unwrap();
 
 		ml.loop_until(|| session.is_finished());
@@ -1222,7 +1775,7 @@ pub mod tests {
 
 		// start && wait for generation session to complete
 		let session = ml.cluster(0).client()
-			.new_generation_session(dummy_session_id, Default::default(), Default::default(), 1).unwrap().session;
+			.new_generation_session(dummy_session_id, Default::default(), Default::default(), 1, None).unwrap().session;
 		ml.loop_until(|| (session.state() == GenerationSessionState::Finished
 			|| session.state() == GenerationSessionState::Failed)
 			&& ml.cluster(0).client().generation_session(&dummy_session_id).is_none());
@@ -1236,7 +1789,7 @@ pub mod tests {
 		let dummy_message = [1u8; 32].into();
 		let signature = sign(Random.generate().secret(), &dummy_message).unwrap();
 		let session0 = ml.cluster(0).client()
-			.new_ecdsa_signing_session(dummy_session_id, signature.into(), None, H256::random()).unwrap();
+			.new_ecdsa_signing_session(dummy_session_id, signature.into(), Vec::new(), None, H256::random()).unwrap();
 		let session = ml.cluster(0).data.sessions.ecdsa_signing_sessions.first().unwrap();
 
 		ml.loop_until(|| session.is_finished() && (0..3).all(|i|
@@ -1246,7 +1799,7 @@ pub mod tests {
 		// and try to sign message with generated key using node that has no key share
 		let signature = sign(Random.generate().secret(), &dummy_message).unwrap();
 		let session2 = ml.cluster(2).client()
-			.new_ecdsa_signing_session(dummy_session_id, signature.into(), None, H256::random()).unwrap();
+			.new_ecdsa_signing_session(dummy_session_id, signature.into(), Vec::new(), None, H256::random()).unwrap();
 		let session = ml.cluster(2).data.sessions.ecdsa_signing_sessions.first().unwrap();
 		ml.loop_until(|| session.is_finished()  && (0..3).all(|i|
 			ml.cluster(i).data.sessions.ecdsa_signing_sessions.is_empty()));
@@ -1258,9 +1811,38 @@ pub mod tests {
 		// and try to sign message with generated key
 		let signature = sign(Random.generate().secret(), &dummy_message).unwrap();
 		let session1 = ml.cluster(0).client()
-			.new_ecdsa_signing_session(dummy_session_id, signature.into(), None, H256::random()).unwrap();
+			.new_ecdsa_signing_session(dummy_session_id, signature.into(), Vec::new(), None, H256::random()).unwrap();
 		let session = ml.cluster(0).data.sessions.ecdsa_signing_sessions.first().unwrap();
 		ml.loop_until(|| session.is_finished());
 		session1.into_wait_future().wait().unwrap_err();
 	}
+
+	#[test]
+	fn signing_sessions_are_refused_if_key_has_expired() {
+		let _ = ::env_logger::try_init();
+		let ml = make_clusters(3);
+		let dummy_session_id = SessionId::from([1u8; 32]);
+
+		// start && wait for generation session of the key, which has expired long ago
+		let expiration = KeyExpiration::Timestamp(1);
+		let session = ml.cluster(0).client()
+			.new_generation_session(dummy_session_id, Default::default(), Default::default(), 1, Some(expiration)).unwrap().session;
+		ml.loop_until(|| (session.state() == GenerationSessionState::Finished
+			|| session.state() == GenerationSessionState::Failed)
+			&& ml.cluster(0).client().generation_session(&dummy_session_id).is_none());
+		assert!(session.joint_public_and_secret().unwrap().is_ok());
+		assert!((0..3).all(|i| ml.cluster(i).data.config.key_storage.get(&dummy_session_id).unwrap().unwrap()
+			.expiration == Some(expiration)));
+
+		// and try to sign message with expired key
+		let dummy_message = [1u8; 32].into();
+		let signature = sign(Random.generate().secret(), &dummy_message).unwrap();
+		assert_eq!(ml.cluster(0).client()
+			.new_schnorr_signing_session(dummy_session_id, signature.clone().into(), Vec::new(), None,
+				SchnorrSigningScheme::Legacy, vec![Default::default()]).map(|_| ()),
+			Err(Error::ServerKeyIsExpired));
+		assert_eq!(ml.cluster(1).client()
+			.new_ecdsa_signing_session(dummy_session_id, signature.into(), Vec::new(), None, H256::random()).map(|_| ()),
+			Err(Error::ServerKeyIsExpired));
+	}
 }
@@ -20,6 +20,7 @@ use key_server_cluster::{Error, NodeId};
 use key_server_cluster::cluster::{ServersSetChangeParams, new_servers_set_change_session};
 use key_server_cluster::cluster_sessions::{AdminSession};
 use key_server_cluster::cluster_connections::{ConnectionProvider, Connection};
+use key_server_cluster::cluster_reliable_broadcast::ReliableBroadcast;
 use key_server_cluster::cluster_sessions::{ClusterSession, ClusterSessions, ClusterSessionsContainer,
 	create_cluster_view};
 use key_server_cluster::cluster_sessions_creator::{ClusterSessionCreator, IntoSessionId};
@@ -54,6 +55,7 @@ pub struct SessionsMessageProcessor {
 	servers_set_change_creator_connector: Arc<dyn ServersSetChangeSessionCreatorConnector>,
 	sessions: Arc<ClusterSessions>,
 	connections: Arc<dyn ConnectionProvider>,
+	reliable_broadcast: ReliableBroadcast,
 }
 
 impl SessionsMessageProcessor {
@@ -65,6 +67,7 @@ impl SessionsMessageProcessor {
 		connections: Arc<dyn ConnectionProvider>,
 	) -> Self {
 		SessionsMessageProcessor {
+			reliable_broadcast: ReliableBroadcast::new(*self_key_pair.public()),
 			self_key_pair,
 			servers_set_change_creator_connector,
 			sessions,
@@ -189,7 +192,8 @@ impl SessionsMessageProcessor {
 				let cluster = create_cluster_view(
 					self.self_key_pair.clone(),
 					self.connections.clone(),
-					requires_all_connections(&message))?;
+					requires_all_connections(&message),
+					self.sessions.reliable_broadcast())?;
 
 				let nonce = Some(message.session_nonce().ok_or(Error::InvalidMessage)?);
 				let exclusive = message.is_exclusive_session_message();
@@ -210,10 +214,47 @@ impl SessionsMessageProcessor {
 			ClusterMessage::KeepAliveResponse(msg) => if let Some(session_id) = msg.session_id {
 				self.sessions.on_session_keep_alive(connection.node_id(), session_id.into());
 			},
+			ClusterMessage::ReliableBroadcastSend(_) | ClusterMessage::ReliableBroadcastEcho(_)
+				| ClusterMessage::ReliableBroadcastReady(_) => self.process_reliable_broadcast_message(connection, message),
 			_ => warn!(target: "secretstore_net", "{}: received unexpected message {} from node {} at {}",
 				self.self_key_pair.public(), message, connection.node_id(), connection.node_address()),
 		}
 	}
+
+	/// Process single reliable broadcast message from the connection.
+	fn process_reliable_broadcast_message(&self, connection: Arc<dyn Connection>, message: ClusterMessage) {
+		// reliable broadcast could involve any subset of nodes that we're aware of
+		let mut cluster_nodes = match self.connections.connected_nodes() {
+			Ok(cluster_nodes) => cluster_nodes,
+			Err(_) => return,
+		};
+		cluster_nodes.extend(self.connections.disconnected_nodes());
+		cluster_nodes.insert(*self.self_key_pair.public());
+
+		let result = match self.reliable_broadcast.process_message(connection.node_id(), &cluster_nodes, &message) {
+			Ok(result) => result,
+			Err(error) => {
+				warn!(target: "secretstore_net", "{}: reliable broadcast error '{}' when processing message {} from node {}",
+					self.self_key_pair.public(), error, message, connection.node_id());
+				return;
+			},
+		};
+
+		for (node, message) in result.messages {
+			if let Some(connection) = self.connections.connection(&node) {
+				connection.send_message(message);
+			}
+		}
+
+		// delivered message is processed as if it has been received directly from its origin
+		if let Some((origin, message)) = result.delivered {
+			match self.connections.connection(&origin) {
+				Some(origin_connection) => self.process_connection_message(origin_connection, message),
+				None => warn!(target: "secretstore_net", "{}: ignoring reliably broadcasted message {} from disconnected node {}",
+					self.self_key_pair.public(), message, origin),
+			}
+		}
+	}
 }
 
 impl MessageProcessor for SessionsMessageProcessor {
@@ -233,6 +274,18 @@ impl MessageProcessor for SessionsMessageProcessor {
 			Message::Encryption(message) => self
 				.process_message(&self.sessions.encryption_sessions, connection, Message::Encryption(message))
 				.map(|_| ()).unwrap_or_default(),
+			Message::KeyDeletion(message) => self
+				.process_message(&self.sessions.key_deletion_sessions, connection, Message::KeyDeletion(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::KeyImport(message) => self
+				.process_message(&self.sessions.key_import_sessions, connection, Message::KeyImport(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::KeyExport(message) => self
+				.process_message(&self.sessions.key_export_sessions, connection, Message::KeyExport(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::RandomnessBeacon(message) => self
+				.process_message(&self.sessions.randomness_beacon_sessions, connection, Message::RandomnessBeacon(message))
+				.map(|_| ()).unwrap_or_default(),
 			Message::Decryption(message) => self
 				.process_message(&self.sessions.decryption_sessions, connection, Message::Decryption(message))
 				.map(|_| ()).unwrap_or_default(),
@@ -242,6 +295,18 @@ impl MessageProcessor for SessionsMessageProcessor {
 			Message::EcdsaSigning(message) => self
 				.process_message(&self.sessions.ecdsa_signing_sessions, connection, Message::EcdsaSigning(message))
 				.map(|_| ()).unwrap_or_default(),
+			Message::EddsaGeneration(message) => self
+				.process_message(&self.sessions.eddsa_generation_sessions, connection, Message::EddsaGeneration(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::EddsaSigning(message) => self
+				.process_message(&self.sessions.eddsa_signing_sessions, connection, Message::EddsaSigning(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::BlsGeneration(message) => self
+				.process_message(&self.sessions.bls_generation_sessions, connection, Message::BlsGeneration(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::BlsSigning(message) => self
+				.process_message(&self.sessions.bls_signing_sessions, connection, Message::BlsSigning(message))
+				.map(|_| ()).unwrap_or_default(),
 			Message::ServersSetChange(message) => {
 				let message = Message::ServersSetChange(message);
 				let is_initialization_message = message.is_initialization_message();
@@ -261,6 +326,15 @@ impl MessageProcessor for SessionsMessageProcessor {
 			Message::ShareAdd(message) => self.process_message(
 				&self.sessions.admin_sessions, connection, Message::ShareAdd(message))
 				.map(|_| ()).unwrap_or_default(),
+			Message::ShareRefresh(message) => self.process_message(
+				&self.sessions.admin_sessions, connection, Message::ShareRefresh(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::KeyRotation(message) => self.process_message(
+				&self.sessions.admin_sessions, connection, Message::KeyRotation(message))
+				.map(|_| ()).unwrap_or_default(),
+			Message::ThresholdChange(message) => self.process_message(
+				&self.sessions.admin_sessions, connection, Message::ThresholdChange(message))
+				.map(|_| ()).unwrap_or_default(),
 			Message::Cluster(message) => self.process_cluster_message(connection, message),
 		}
 	}
@@ -292,11 +366,11 @@ impl MessageProcessor for SessionsMessageProcessor {
 								self.sessions.decryption_sessions.remove(&session.id());
 							}
 						},
-						Some(ContinueAction::SchnorrSign(session, message_hash)) => {
+						Some(ContinueAction::SchnorrSign(session, scheme, message_hashes)) => {
 							let initialization_error = if self.self_key_pair.public() == &master {
-								session.initialize(version, message_hash)
+								session.initialize(version, scheme, message_hashes)
 							} else {
-								session.delegate(master, version, message_hash)
+								session.delegate(master, version, scheme, message_hashes)
 							};
 
 							if let Err(error) = initialization_error {
@@ -316,6 +390,30 @@ impl MessageProcessor for SessionsMessageProcessor {
 								self.sessions.ecdsa_signing_sessions.remove(&session.id());
 							}
 						},
+						Some(ContinueAction::EddsaSign(session, message_hash)) => {
+							let initialization_error = if self.self_key_pair.public() == &master {
+								session.initialize(version, message_hash)
+							} else {
+								session.delegate(master, version, message_hash)
+							};
+
+							if let Err(error) = initialization_error {
+								session.on_session_error(&meta.self_node_id, error);
+								self.sessions.eddsa_signing_sessions.remove(&session.id());
+							}
+						},
+						Some(ContinueAction::BlsSign(session, message_hash)) => {
+							let initialization_error = if self.self_key_pair.public() == &master {
+								session.initialize(version, message_hash)
+							} else {
+								session.delegate(master, version, message_hash)
+							};
+
+							if let Err(error) = initialization_error {
+								session.on_session_error(&meta.self_node_id, error);
+								self.sessions.bls_signing_sessions.remove(&session.id());
+							}
+						},
 						None => (),
 					},
 					Some(Err(error)) => match session.take_continue_action() {
@@ -323,7 +421,7 @@ impl MessageProcessor for SessionsMessageProcessor {
 							session.on_session_error(&meta.self_node_id, error);
 							self.sessions.decryption_sessions.remove(&session.id());
 						},
-						Some(ContinueAction::SchnorrSign(session, _)) => {
+						Some(ContinueAction::SchnorrSign(session, _, _)) => {
 							session.on_session_error(&meta.self_node_id, error);
 							self.sessions.schnorr_signing_sessions.remove(&session.id());
 						},
@@ -331,6 +429,14 @@ impl MessageProcessor for SessionsMessageProcessor {
 							session.on_session_error(&meta.self_node_id, error);
 							self.sessions.ecdsa_signing_sessions.remove(&session.id());
 						},
+						Some(ContinueAction::EddsaSign(session, _)) => {
+							session.on_session_error(&meta.self_node_id, error);
+							self.sessions.eddsa_signing_sessions.remove(&session.id());
+						},
+						Some(ContinueAction::BlsSign(session, _)) => {
+							session.on_session_error(&meta.self_node_id, error);
+							self.sessions.bls_signing_sessions.remove(&session.id());
+						},
 						None => (),
 					},
 					None | Some(Ok(None)) => unreachable!("is_master_node; session is finished;
@@ -344,6 +450,7 @@ impl MessageProcessor for SessionsMessageProcessor {
 	fn maintain_sessions(&self) {
 		self.sessions.stop_stalled_sessions();
 		self.sessions.sessions_keep_alive();
+		self.reliable_broadcast.remove_stale_broadcasts();
 	}
 
 	fn start_servers_set_change_session(&self, params: ServersSetChangeParams) -> Result<Arc<AdminSession>, Error> {
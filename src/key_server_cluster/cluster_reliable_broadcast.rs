@@ -0,0 +1,521 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use ethereum_types::H256;
+use crypto::publickey::{Random, Generator};
+use hash::keccak;
+use key_server_cluster::{Error, NodeId};
+use key_server_cluster::io::{MESSAGE_HEADER_SIZE, serialize_message, deserialize_header, deserialize_message};
+use key_server_cluster::message::{Message, ClusterMessage, ReliableBroadcastSend, ReliableBroadcastEcho,
+	ReliableBroadcastReady};
+
+/// When reliable broadcast isn't delivered for BROADCAST_TIMEOUT_INTERVAL seconds, it is forgotten.
+/// Session, which has been waiting for this broadcast, is stalled by this time anyway.
+const BROADCAST_TIMEOUT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Echo-based (Bracha) reliable broadcast.
+/// Plain broadcast sends message to every node independently, so malicious origin is able to send different
+/// messages to different nodes. With reliable broadcast, the message is either delivered to all honest nodes,
+/// or to none of them, and all honest nodes deliver the same message. This holds as long as there are at most
+/// f = (N - 1) / 3 faulty nodes (including the origin).
+/// Protocol:
+/// 1) origin sends the message to every other node (ReliableBroadcastSend);
+/// 2) when node receives message from the origin, it echoes it to every other node (ReliableBroadcastEcho);
+/// 3) when node receives (N + f) / 2 + 1 echoes or f + 1 ready messages for the same message, it tells every
+/// other node that it is ready to deliver this message (ReliableBroadcastReady);
+/// 4) when node receives 2 * f + 1 ready messages for the same message, it delivers the message.
+/// Origin is not waiting for its own broadcast, so its ReliableBroadcastSend also counts as its echo && ready
+/// messages and other nodes are not sending echo && ready messages to the origin.
+pub struct ReliableBroadcast {
+	/// This node id.
+	self_node_id: NodeId,
+	/// Active broadcasts, indexed by origin and broadcast id.
+	broadcasts: Mutex<BTreeMap<(NodeId, H256), BroadcastState>>,
+}
+
+/// Result of processing single reliable broadcast message.
+#[derive(Debug, Default)]
+pub struct ReliableBroadcastResult {
+	/// Messages that must be sent to other nodes.
+	pub messages: Vec<(NodeId, Message)>,
+	/// Message that has been delivered + its origin.
+	pub delivered: Option<(NodeId, Message)>,
+}
+
+/// Nodes set of the broadcast + hash of the payload that is echoed by (or is ready to be delivered by) the node.
+type Vote = (BTreeSet<NodeId>, H256);
+
+/// Single reliable broadcast state.
+struct BroadcastState {
+	/// Time when the first message of this broadcast has been received.
+	started: Instant,
+	/// Nodes, participating in this broadcast. Only known after the message from the origin is received.
+	nodes: Option<BTreeSet<NodeId>>,
+	/// All known payloads, indexed by their hashes.
+	payloads: BTreeMap<H256, Vec<u8>>,
+	/// Votes of nodes that have echoed the payload.
+	echoes: BTreeMap<NodeId, Vote>,
+	/// Votes of nodes that are ready to deliver the payload.
+	readies: BTreeMap<NodeId, Vote>,
+	/// Is echo message sent by this node?
+	is_echo_sent: bool,
+	/// Is ready message sent by this node?
+	is_ready_sent: bool,
+	/// Is message delivered?
+	is_delivered: bool,
+}
+
+impl ReliableBroadcast {
+	/// Create new reliable broadcast instance.
+	pub fn new(self_node_id: NodeId) -> Self {
+		ReliableBroadcast {
+			self_node_id,
+			broadcasts: Mutex::new(BTreeMap::new()),
+		}
+	}
+
+	/// Prepare messages that are sent by the origin of reliable broadcast.
+	pub fn broadcast_messages(
+		self_node_id: &NodeId,
+		nodes: &BTreeSet<NodeId>,
+		message: Message,
+	) -> Result<Vec<(NodeId, Message)>, Error> {
+		// only session messages could be broadcasted reliably
+		if let Message::Cluster(_) = message {
+			return Err(Error::InvalidMessage);
+		}
+		if !nodes.contains(self_node_id) {
+			return Err(Error::InvalidNodeForRequest);
+		}
+
+		let payload: Vec<u8> = serialize_message(message)?.into();
+		let broadcast_id = *Random.generate().secret().clone();
+		Ok(nodes.iter()
+			.filter(|n| *n != self_node_id)
+			.map(|n| (*n, Message::Cluster(ClusterMessage::ReliableBroadcastSend(ReliableBroadcastSend {
+				broadcast_id: broadcast_id.into(),
+				nodes: nodes.iter().cloned().map(Into::into).collect(),
+				payload: payload.clone().into(),
+			}))))
+			.collect())
+	}
+
+	/// Process reliable broadcast message, received from given node. `cluster_nodes` is the set of all nodes
+	/// that this node is aware of. Broadcast could be limited to the subset of cluster nodes (i.e. to session
+	/// participants). The nodes set is fixed by the message from the origin and all later messages with different
+	/// nodes set are rejected. Echo && ready messages that are received before the origin' message are counted
+	/// per (nodes set, payload hash), because otherwise any single node would be able to choose broadcast quorum.
+	/// Until the origin' message is received, only ready messages, supported by enough nodes of the whole cluster,
+	/// are trusted (so that the message is still delivered if the origin has failed to send it to this node).
+	pub fn process_message(
+		&self,
+		sender: &NodeId,
+		cluster_nodes: &BTreeSet<NodeId>,
+		message: &ClusterMessage,
+	) -> Result<ReliableBroadcastResult, Error> {
+		let (origin, broadcast_id, nodes) = match *message {
+			ClusterMessage::ReliableBroadcastSend(ref message) =>
+				(*sender, *message.broadcast_id, &message.nodes),
+			ClusterMessage::ReliableBroadcastEcho(ref message) =>
+				(*message.origin, *message.broadcast_id, &message.nodes),
+			ClusterMessage::ReliableBroadcastReady(ref message) =>
+				(*message.origin, *message.broadcast_id, &message.nodes),
+			_ => return Err(Error::InvalidMessage),
+		};
+
+		// check that the message is valid
+		let nodes: BTreeSet<NodeId> = nodes.iter().cloned().map(Into::into).collect();
+		if !nodes.is_subset(cluster_nodes) || !nodes.contains(&self.self_node_id)
+			|| !nodes.contains(&origin) || !nodes.contains(sender) {
+			return Err(Error::InvalidMessage);
+		}
+		// we never process messages of our own broadcasts && origin never echoes its own broadcast
+		if origin == self.self_node_id || *sender == self.self_node_id {
+			return Err(Error::InvalidMessage);
+		}
+		match *message {
+			ClusterMessage::ReliableBroadcastEcho(_) | ClusterMessage::ReliableBroadcastReady(_) if *sender == origin =>
+				return Err(Error::InvalidMessage),
+			_ => (),
+		}
+
+		let mut broadcasts = self.broadcasts.lock();
+		let broadcast = broadcasts.entry((origin, broadcast_id)).or_insert_with(|| BroadcastState {
+			started: Instant::now(),
+			nodes: None,
+			payloads: BTreeMap::new(),
+			echoes: BTreeMap::new(),
+			readies: BTreeMap::new(),
+			is_echo_sent: false,
+			is_ready_sent: false,
+			is_delivered: false,
+		});
+		if broadcast.nodes.as_ref().map(|broadcast_nodes| *broadcast_nodes != nodes).unwrap_or(false) {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut result = ReliableBroadcastResult::default();
+		match *message {
+			ClusterMessage::ReliableBroadcastSend(ref message) => {
+				// only the first message from the origin is processed
+				if broadcast.nodes.is_some() {
+					return Ok(result);
+				}
+
+				let payload_hash = keccak(&*message.payload);
+				broadcast.nodes = Some(nodes.clone());
+				broadcast.payloads.insert(payload_hash, message.payload.clone().into());
+				broadcast.echoes.insert(origin, (nodes.clone(), payload_hash));
+				broadcast.readies.insert(origin, (nodes.clone(), payload_hash));
+
+				if !broadcast.is_echo_sent {
+					broadcast.is_echo_sent = true;
+					broadcast.echoes.insert(self.self_node_id, (nodes.clone(), payload_hash));
+					result.messages.extend(self.other_nodes(&origin, &nodes)
+						.map(|n| (n, Message::Cluster(ClusterMessage::ReliableBroadcastEcho(ReliableBroadcastEcho {
+							origin: origin.into(),
+							broadcast_id: broadcast_id.into(),
+							nodes: message.nodes.clone(),
+							payload: message.payload.clone(),
+						})))));
+				}
+			},
+			ClusterMessage::ReliableBroadcastEcho(ref message) => {
+				// only the first echo from every node is processed
+				if broadcast.echoes.contains_key(sender) {
+					return Ok(result);
+				}
+
+				let payload_hash = keccak(&*message.payload);
+				broadcast.payloads.entry(payload_hash).or_insert_with(|| message.payload.clone().into());
+				broadcast.echoes.insert(*sender, (nodes.clone(), payload_hash));
+			},
+			ClusterMessage::ReliableBroadcastReady(ref message) => {
+				// only the first ready message from every node is processed
+				if broadcast.readies.contains_key(sender) {
+					return Ok(result);
+				}
+
+				broadcast.readies.insert(*sender, (nodes.clone(), *message.payload_hash));
+			},
+			_ => unreachable!("checked above; qed"),
+		}
+
+		// until the origin' message is received, nodes set of the broadcast is unknown => we only count votes
+		// for the same nodes set && use the number of faulty nodes in the whole cluster
+		let broadcast_nodes = broadcast.nodes.clone();
+		let is_counted_vote = |vote: &&Vote| broadcast_nodes.as_ref().map(|nodes| vote.0 == *nodes).unwrap_or(true);
+		let votes_nodes_count = broadcast_nodes.as_ref().map(|nodes| nodes.len()).unwrap_or_else(|| cluster_nodes.len());
+		let faulty_nodes_count = (votes_nodes_count - 1) / 3;
+
+		// send ready message if enough nodes have echoed the same message, or if enough nodes are ready
+		// to deliver the same message (=> at least one honest node is ready to deliver it)
+		if !broadcast.is_ready_sent {
+			let echoes_threshold = (votes_nodes_count + faulty_nodes_count) / 2 + 1;
+			let vote = broadcast_nodes.as_ref()
+				.and_then(|_| supported_vote(broadcast.echoes.values().filter(&is_counted_vote), echoes_threshold))
+				.or_else(|| supported_vote(broadcast.readies.values().filter(&is_counted_vote), faulty_nodes_count + 1));
+			if let Some((vote_nodes, payload_hash)) = vote {
+				broadcast.is_ready_sent = true;
+				broadcast.readies.insert(self.self_node_id, (vote_nodes.clone(), payload_hash));
+				result.messages.extend(self.other_nodes(&origin, &vote_nodes)
+					.map(|n| (n, Message::Cluster(ClusterMessage::ReliableBroadcastReady(ReliableBroadcastReady {
+						origin: origin.into(),
+						broadcast_id: broadcast_id.into(),
+						nodes: vote_nodes.iter().cloned().map(Into::into).collect(),
+						payload_hash: payload_hash.into(),
+					})))));
+			}
+		}
+
+		// deliver message if enough nodes are ready to deliver it
+		if !broadcast.is_delivered {
+			let payloads = &broadcast.payloads;
+			let payload = supported_vote(broadcast.readies.values().filter(&is_counted_vote), 2 * faulty_nodes_count + 1)
+				.and_then(|(_, payload_hash)| payloads.get(&payload_hash));
+			if let Some(payload) = payload {
+				broadcast.is_delivered = true;
+				match deserialize_payload(payload) {
+					Ok(Message::Cluster(_)) => warn!(target: "secretstore_net",
+						"{}: ignoring reliably broadcasted cluster message from {}", self.self_node_id, origin),
+					Ok(message) => result.delivered = Some((origin, message)),
+					Err(error) => warn!(target: "secretstore_net",
+						"{}: error '{}' when reading reliably broadcasted message from {}", self.self_node_id, error, origin),
+				}
+			}
+		}
+
+		Ok(result)
+	}
+
+	/// Forget about broadcasts that have been started long time ago.
+	pub fn remove_stale_broadcasts(&self) {
+		let now = Instant::now();
+		self.broadcasts.lock().retain(|_, broadcast| now - broadcast.started <= BROADCAST_TIMEOUT_INTERVAL);
+	}
+
+	/// Get all broadcast nodes, except this node and the origin.
+	fn other_nodes<'a>(&'a self, origin: &'a NodeId, nodes: &'a BTreeSet<NodeId>) -> impl Iterator<Item=NodeId> + 'a {
+		nodes.iter().filter(move |n| *n != origin && **n != self.self_node_id).cloned()
+	}
+}
+
+/// Get nodes set && hash of the payload that are supported by at least `threshold` nodes.
+fn supported_vote<'a, I: Iterator<Item=&'a Vote>>(votes: I, threshold: usize) -> Option<Vote> {
+	let mut votes_count: BTreeMap<&Vote, usize> = BTreeMap::new();
+	for vote in votes {
+		*votes_count.entry(vote).or_insert(0) += 1;
+	}
+
+	votes_count.into_iter()
+		.find(|&(_, count)| count >= threshold)
+		.map(|(vote, _)| vote.clone())
+}
+
+/// Deserialize reliably broadcasted message.
+fn deserialize_payload(payload: &[u8]) -> Result<Message, Error> {
+	if payload.len() < MESSAGE_HEADER_SIZE {
+		return Err(Error::InvalidMessage);
+	}
+
+	let header = deserialize_header(&payload[..MESSAGE_HEADER_SIZE])?;
+	if header.size as usize != payload.len() - MESSAGE_HEADER_SIZE {
+		return Err(Error::InvalidMessage);
+	}
+
+	deserialize_message(&header, payload[MESSAGE_HEADER_SIZE..].to_vec())
+}
+
+#[cfg(test)]
+pub mod tests {
+	use std::collections::{BTreeSet, VecDeque};
+	use crypto::publickey::{Random, Generator};
+	use hash::keccak;
+	use key_server_cluster::{Error, NodeId};
+	use key_server_cluster::io::serialize_message;
+	use key_server_cluster::message::{Message, ClusterMessage, GenerationMessage, ConfirmInitialization,
+		ReliableBroadcastEcho, ReliableBroadcastReady};
+	use super::{ReliableBroadcast, deserialize_payload};
+
+	/// Replace message that is sent by the origin of reliable broadcast (i.e. make the origin equivocate).
+	pub fn replace_broadcasted_message<F: FnOnce(Message) -> Message>(message: Message, replace: F) -> Message {
+		match message {
+			Message::Cluster(ClusterMessage::ReliableBroadcastSend(mut message)) => {
+				let payload = replace(deserialize_payload(&message.payload).unwrap());
+				let payload: Vec<u8> = serialize_message(payload).unwrap().into();
+				message.payload = payload.into();
+				Message::Cluster(ClusterMessage::ReliableBroadcastSend(message))
+			},
+			message => message,
+		}
+	}
+
+	fn message(session_nonce: u64) -> Message {
+		Message::Generation(GenerationMessage::ConfirmInitialization(ConfirmInitialization {
+			session: Default::default(),
+			session_nonce,
+		}))
+	}
+
+	fn cluster_message(messages: &[(NodeId, Message)], to: &NodeId) -> ClusterMessage {
+		match messages.iter().find(|&&(ref node, _)| node == to).unwrap().1 {
+			Message::Cluster(ref message) => message.clone(),
+			_ => panic!("unexpected message"),
+		}
+	}
+
+	fn session_nonce(message: &Message) -> u64 {
+		match *message {
+			Message::Generation(GenerationMessage::ConfirmInitialization(ref message)) => message.session_nonce,
+			_ => panic!("unexpected message"),
+		}
+	}
+
+	/// Run broadcast of origin (node 0), which sends message, returned by `equivocate`, to every node.
+	/// Returns messages, delivered by every other node.
+	fn run_broadcast<F: Fn(usize) -> Option<u64>>(num_nodes: usize, equivocate: F) -> Vec<Option<u64>> {
+		run_broadcast_with_offline_nodes(num_nodes, 0, equivocate)
+	}
+
+	/// Run broadcast of origin (node 0) when there are `num_offline_nodes` nodes in the cluster that are
+	/// not participating in the broadcast.
+	fn run_broadcast_with_offline_nodes<F: Fn(usize) -> Option<u64>>(
+		num_nodes: usize,
+		num_offline_nodes: usize,
+		equivocate: F,
+	) -> Vec<Option<u64>> {
+		let nodes: Vec<NodeId> = (0..num_nodes).map(|_| *Random.generate().public()).collect();
+		let nodes_set: BTreeSet<NodeId> = nodes.iter().cloned().collect();
+		let cluster_nodes: BTreeSet<NodeId> = nodes_set.iter().cloned()
+			.chain((0..num_offline_nodes).map(|_| *Random.generate().public()))
+			.collect();
+		let broadcasts: Vec<_> = nodes.iter().map(|n| ReliableBroadcast::new(*n)).collect();
+
+		let mut queue = VecDeque::new();
+		let messages = ReliableBroadcast::broadcast_messages(&nodes[0], &nodes_set, message(0)).unwrap();
+		for (idx, node) in nodes.iter().enumerate().skip(1) {
+			if let Some(session_nonce) = equivocate(idx) {
+				let send = messages.iter().find(|&&(ref to, _)| to == node).unwrap().1.clone();
+				queue.push_back((nodes[0], *node, replace_broadcasted_message(send, |_| message(session_nonce))));
+			}
+		}
+
+		let mut delivered = vec![None; num_nodes];
+		while let Some((from, to, message)) = queue.pop_front() {
+			let to_idx = nodes.iter().position(|n| *n == to).unwrap();
+			let message = match message {
+				Message::Cluster(message) => message,
+				_ => panic!("unexpected message"),
+			};
+
+			let result = broadcasts[to_idx].process_message(&from, &cluster_nodes, &message).unwrap();
+			queue.extend(result.messages.into_iter().map(|(n, m)| (to, n, m)));
+			if let Some((origin, message)) = result.delivered {
+				assert_eq!(origin, nodes[0]);
+				assert!(delivered[to_idx].is_none());
+				delivered[to_idx] = Some(session_nonce(&message));
+			}
+		}
+
+		delivered.into_iter().skip(1).collect()
+	}
+
+	#[test]
+	fn message_is_delivered_to_all_nodes_when_origin_is_honest() {
+		assert_eq!(run_broadcast(4, |_| Some(1)), vec![Some(1); 3]);
+		assert_eq!(run_broadcast(7, |_| Some(1)), vec![Some(1); 6]);
+	}
+
+	#[test]
+	fn the_same_message_is_delivered_to_all_nodes_when_origin_equivocates() {
+		assert_eq!(run_broadcast(4, |idx| Some(if idx == 1 { 1 } else { 2 })), vec![Some(2); 3]);
+		assert_eq!(run_broadcast(7, |idx| Some(if idx < 3 { 1 } else { 2 })), vec![Some(2); 6]);
+	}
+
+	#[test]
+	fn message_is_not_delivered_when_origin_equivocates_too_much() {
+		assert_eq!(run_broadcast(7, |idx| Some(if idx < 4 { 1 } else { 2 })), vec![None; 6]);
+	}
+
+	#[test]
+	fn message_is_not_delivered_when_origin_sends_it_to_the_minority() {
+		assert_eq!(run_broadcast(4, |idx| if idx == 1 { Some(1) } else { None }), vec![None; 3]);
+	}
+
+	#[test]
+	fn message_is_delivered_when_origin_sends_it_to_the_majority() {
+		assert_eq!(run_broadcast(4, |idx| if idx == 1 { None } else { Some(1) }), vec![Some(1); 3]);
+	}
+
+	#[test]
+	fn message_is_delivered_when_broadcasted_to_the_subset_of_cluster_nodes() {
+		assert_eq!(run_broadcast_with_offline_nodes(4, 1, |_| Some(1)), vec![Some(1); 3]);
+		assert_eq!(run_broadcast_with_offline_nodes(3, 2, |_| Some(1)), vec![Some(1); 2]);
+	}
+
+	#[test]
+	fn broadcast_with_unknown_nodes_is_rejected() {
+		let nodes: BTreeSet<NodeId> = (0..4).map(|_| *Random.generate().public()).collect();
+		let origin = *nodes.iter().nth(0).unwrap();
+		let receiver = *nodes.iter().nth(1).unwrap();
+		let cluster_nodes: BTreeSet<NodeId> = nodes.iter().cloned().take(3).collect();
+
+		let messages = ReliableBroadcast::broadcast_messages(&origin, &nodes, message(1)).unwrap();
+		let message = cluster_message(&messages, &receiver);
+		assert_eq!(ReliableBroadcast::new(receiver).process_message(&origin, &cluster_nodes, &message).unwrap_err(),
+			Error::InvalidMessage);
+	}
+
+	#[test]
+	fn broadcast_with_different_nodes_set_is_rejected() {
+		let nodes: BTreeSet<NodeId> = (0..4).map(|_| *Random.generate().public()).collect();
+		let origin = *nodes.iter().nth(0).unwrap();
+		let receiver = *nodes.iter().nth(1).unwrap();
+		let subset: BTreeSet<NodeId> = nodes.iter().cloned().take(3).collect();
+
+		// origin sends broadcast to the receiver
+		let broadcast = ReliableBroadcast::new(receiver);
+		let messages = ReliableBroadcast::broadcast_messages(&origin, &nodes, message(1)).unwrap();
+		let message = cluster_message(&messages, &receiver);
+		broadcast.process_message(&origin, &nodes, &message).unwrap();
+
+		// and then the same broadcast is echoed with different nodes set
+		let echo = match message {
+			ClusterMessage::ReliableBroadcastSend(message) => ClusterMessage::ReliableBroadcastEcho(ReliableBroadcastEcho {
+				origin: origin.into(),
+				broadcast_id: message.broadcast_id,
+				nodes: subset.iter().cloned().map(Into::into).collect(),
+				payload: message.payload,
+			}),
+			_ => panic!("unexpected message"),
+		};
+		let sender = *subset.iter().find(|n| **n != origin && **n != receiver).unwrap();
+		assert_eq!(broadcast.process_message(&sender, &nodes, &echo).unwrap_err(), Error::InvalidMessage);
+	}
+
+	#[test]
+	fn echo_received_before_origin_message_does_not_fix_nodes_set() {
+		let nodes: BTreeSet<NodeId> = (0..4).map(|_| *Random.generate().public()).collect();
+		let origin = *nodes.iter().nth(0).unwrap();
+		let receiver = *nodes.iter().nth(1).unwrap();
+		let malicious = *nodes.iter().nth(2).unwrap();
+		let subset: BTreeSet<NodeId> = vec![origin, receiver, malicious].into_iter().collect();
+
+		// malicious node echoes && is ready to deliver forged message with its own nodes set before the origin' message
+		// is received => it is not delivered, because single node can't be trusted
+		let broadcast = ReliableBroadcast::new(receiver);
+		let messages = ReliableBroadcast::broadcast_messages(&origin, &nodes, message(1)).unwrap();
+		let forged_messages = ReliableBroadcast::broadcast_messages(&origin, &subset, message(2)).unwrap();
+		let (broadcast_id, forged_payload) = match (cluster_message(&messages, &receiver), cluster_message(&forged_messages, &receiver)) {
+			(ClusterMessage::ReliableBroadcastSend(message), ClusterMessage::ReliableBroadcastSend(forged_message)) =>
+				(message.broadcast_id, forged_message.payload),
+			_ => panic!("unexpected message"),
+		};
+		let echo = ClusterMessage::ReliableBroadcastEcho(ReliableBroadcastEcho {
+			origin: origin.into(),
+			broadcast_id: broadcast_id.clone(),
+			nodes: subset.iter().cloned().map(Into::into).collect(),
+			payload: forged_payload.clone(),
+		});
+		let ready = ClusterMessage::ReliableBroadcastReady(ReliableBroadcastReady {
+			origin: origin.into(),
+			broadcast_id: broadcast_id.clone(),
+			nodes: subset.iter().cloned().map(Into::into).collect(),
+			payload_hash: keccak(&*forged_payload).into(),
+		});
+		let result = broadcast.process_message(&malicious, &nodes, &echo).unwrap();
+		assert!(result.messages.is_empty() && result.delivered.is_none());
+		let result = broadcast.process_message(&malicious, &nodes, &ready).unwrap();
+		assert!(result.messages.is_empty() && result.delivered.is_none());
+
+		// and then the origin' message is received => it fixes the nodes set && is echoed to all other nodes
+		let result = broadcast.process_message(&origin, &nodes, &cluster_message(&messages, &receiver)).unwrap();
+		assert!(result.delivered.is_none());
+		assert_eq!(result.messages.len(), 2);
+		for &(_, ref message) in &result.messages {
+			match *message {
+				Message::Cluster(ClusterMessage::ReliableBroadcastEcho(ref message)) => {
+					assert_eq!(message.nodes.iter().cloned().map(Into::into).collect::<BTreeSet<NodeId>>(), nodes);
+					assert_eq!(session_nonce(&deserialize_payload(&message.payload).unwrap()), 1);
+				},
+				_ => panic!("unexpected message"),
+			}
+		}
+	}
+}
@@ -29,18 +29,30 @@ use key_server_cluster::cluster_connections::ConnectionProvider;
 use key_server_cluster::connection_trigger::ServersSetChangeSessionCreatorConnector;
 use key_server_cluster::message::{self, Message};
 use key_server_cluster::generation_session::{SessionImpl as GenerationSessionImpl};
+use key_server_cluster::generation_session_eddsa::{SessionImpl as EddsaGenerationSessionImpl};
+use key_server_cluster::generation_session_bls::{SessionImpl as BlsGenerationSessionImpl};
 use key_server_cluster::decryption_session::{SessionImpl as DecryptionSessionImpl};
 use key_server_cluster::encryption_session::{SessionImpl as EncryptionSessionImpl};
+use key_server_cluster::key_deletion_session::{SessionImpl as KeyDeletionSessionImpl};
+use key_server_cluster::key_import_session::{SessionImpl as KeyImportSessionImpl};
+use key_server_cluster::key_export_session::{SessionImpl as KeyExportSessionImpl};
+use key_server_cluster::randomness_beacon_session::{SessionImpl as RandomnessBeaconSessionImpl};
 use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSessionImpl};
+use key_server_cluster::signing_session_eddsa::{SessionImpl as EddsaSigningSessionImpl};
+use key_server_cluster::signing_session_bls::{SessionImpl as BlsSigningSessionImpl};
 use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSessionImpl};
 use key_server_cluster::share_add_session::{SessionImpl as ShareAddSessionImpl, IsolatedSessionTransport as ShareAddTransport};
 use key_server_cluster::servers_set_change_session::{SessionImpl as ServersSetChangeSessionImpl};
+use key_server_cluster::share_refresh_session::{SessionImpl as ShareRefreshSessionImpl};
+use key_server_cluster::key_rotation_session::{SessionImpl as KeyRotationSessionImpl};
+use key_server_cluster::threshold_change_session::{SessionImpl as ThresholdChangeSessionImpl};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSessionImpl,
 	IsolatedSessionTransport as VersionNegotiationTransport};
 
 use key_server_cluster::cluster_sessions_creator::{GenerationSessionCreator, EncryptionSessionCreator, DecryptionSessionCreator,
 	SchnorrSigningSessionCreator, KeyVersionNegotiationSessionCreator, AdminSessionCreator, SessionCreatorCore,
-	EcdsaSigningSessionCreator, ClusterSessionCreator};
+	EcdsaSigningSessionCreator, KeyDeletionSessionCreator, EddsaGenerationSessionCreator, EddsaSigningSessionCreator,
+	BlsGenerationSessionCreator, BlsSigningSessionCreator, KeyImportSessionCreator, KeyExportSessionCreator, RandomnessBeaconSessionCreator, ClusterSessionCreator};
 
 /// When there are no session-related messages for SESSION_TIMEOUT_INTERVAL seconds,
 /// we must treat this session as stalled && finish it with an error.
@@ -55,6 +67,10 @@ lazy_static! {
 	pub static ref SERVERS_SET_CHANGE_SESSION_ID: SessionId = "10b7af423bb551d5dc8645db754163a2145d37d78d468fa7330435ed77064c1c"
 		.parse()
 		.expect("hardcoded id should parse without errors; qed");
+	/// Id, which is signed by administrator to refresh shares of all keys at once.
+	pub static ref SHARE_REFRESH_ALL_KEYS_ID: H256 = "3ee49bd1e8ad12ab5f7ef5a2bba9c8d91d4e59e5c1ba0a7ab1b29d3fa6cd0a7e"
+		.parse()
+		.expect("hardcoded id should parse without errors; qed");
 }
 
 /// Session id with sub session.
@@ -139,6 +155,12 @@ pub enum AdminSession {
 	ShareAdd(ShareAddSessionImpl<ShareAddTransport>),
 	/// Servers set change session.
 	ServersSetChange(ServersSetChangeSessionImpl),
+	/// Share refresh session.
+	ShareRefresh(ShareRefreshSessionImpl),
+	/// Key rotation session.
+	KeyRotation(KeyRotationSessionImpl),
+	/// Threshold change session.
+	ThresholdChange(ThresholdChangeSessionImpl),
 }
 
 /// Administrative session creation data.
@@ -147,6 +169,12 @@ pub enum AdminSessionCreationData {
 	ShareAdd(H256),
 	/// Servers set change session (block id, new_server_set).
 	ServersSetChange(Option<H256>, BTreeSet<NodeId>),
+	/// Share refresh session.
+	ShareRefresh,
+	/// Key rotation session.
+	KeyRotation,
+	/// Threshold change session.
+	ThresholdChange,
 }
 
 /// Active sessions on this cluster.
@@ -155,12 +183,28 @@ pub struct ClusterSessions {
 	pub generation_sessions: ClusterSessionsContainer<GenerationSessionImpl, GenerationSessionCreator>,
 	/// Encryption sessions.
 	pub encryption_sessions: ClusterSessionsContainer<EncryptionSessionImpl, EncryptionSessionCreator>,
+	/// Key deletion sessions.
+	pub key_deletion_sessions: ClusterSessionsContainer<KeyDeletionSessionImpl, KeyDeletionSessionCreator>,
+	/// Key import sessions.
+	pub key_import_sessions: ClusterSessionsContainer<KeyImportSessionImpl, KeyImportSessionCreator>,
+	/// Key export sessions.
+	pub key_export_sessions: ClusterSessionsContainer<KeyExportSessionImpl, KeyExportSessionCreator>,
 	/// Decryption sessions.
 	pub decryption_sessions: ClusterSessionsContainer<DecryptionSessionImpl, DecryptionSessionCreator>,
 	/// Schnorr signing sessions.
 	pub schnorr_signing_sessions: ClusterSessionsContainer<SchnorrSigningSessionImpl, SchnorrSigningSessionCreator>,
 	/// ECDSA signing sessions.
 	pub ecdsa_signing_sessions: ClusterSessionsContainer<EcdsaSigningSessionImpl, EcdsaSigningSessionCreator>,
+	/// Ed25519 key generation sessions.
+	pub eddsa_generation_sessions: ClusterSessionsContainer<EddsaGenerationSessionImpl, EddsaGenerationSessionCreator>,
+	/// EdDSA signing sessions.
+	pub eddsa_signing_sessions: ClusterSessionsContainer<EddsaSigningSessionImpl, EddsaSigningSessionCreator>,
+	/// BLS12-381 key generation sessions.
+	pub bls_generation_sessions: ClusterSessionsContainer<BlsGenerationSessionImpl, BlsGenerationSessionCreator>,
+	/// BLS signing sessions.
+	pub bls_signing_sessions: ClusterSessionsContainer<BlsSigningSessionImpl, BlsSigningSessionCreator>,
+	/// Randomness beacon sessions.
+	pub randomness_beacon_sessions: ClusterSessionsContainer<RandomnessBeaconSessionImpl, RandomnessBeaconSessionCreator>,
 	/// Key version negotiation sessions.
 	pub negotiation_sessions: ClusterSessionsContainer<
 		KeyVersionNegotiationSessionImpl<VersionNegotiationTransport>,
@@ -172,6 +216,8 @@ pub struct ClusterSessions {
 	self_node_id: NodeId,
 	/// Creator core.
 	creator_core: Arc<SessionCreatorCore>,
+	/// Use reliable broadcast in sessions that support it.
+	reliable_broadcast: bool,
 }
 
 /// Active sessions container listener.
@@ -230,6 +276,7 @@ impl ClusterSessions {
 		let creator_core = Arc::new(SessionCreatorCore::new(config));
 		ClusterSessions {
 			self_node_id: config.self_key_pair.public().clone(),
+			reliable_broadcast: config.reliable_broadcast,
 			generation_sessions: ClusterSessionsContainer::new(GenerationSessionCreator {
 				core: creator_core.clone(),
 				make_faulty_generation_sessions: AtomicBool::new(false),
@@ -237,6 +284,17 @@ impl ClusterSessions {
 			encryption_sessions: ClusterSessionsContainer::new(EncryptionSessionCreator {
 				core: creator_core.clone(),
 			}, container_state.clone()),
+			key_deletion_sessions: ClusterSessionsContainer::new(KeyDeletionSessionCreator {
+				core: creator_core.clone(),
+			}, container_state.clone()),
+			key_import_sessions: ClusterSessionsContainer::new(KeyImportSessionCreator {
+				core: creator_core.clone(),
+				self_key_pair: config.self_secret_key_pair.clone(),
+			}, container_state.clone()),
+			key_export_sessions: ClusterSessionsContainer::new(KeyExportSessionCreator {
+				core: creator_core.clone(),
+				approvers: config.key_export_approvers.clone(),
+			}, container_state.clone()),
 			decryption_sessions: ClusterSessionsContainer::new(DecryptionSessionCreator {
 				core: creator_core.clone(),
 			}, container_state.clone()),
@@ -246,6 +304,22 @@ impl ClusterSessions {
 			ecdsa_signing_sessions: ClusterSessionsContainer::new(EcdsaSigningSessionCreator {
 				core: creator_core.clone(),
 			}, container_state.clone()),
+			eddsa_generation_sessions: ClusterSessionsContainer::new(EddsaGenerationSessionCreator {
+				core: creator_core.clone(),
+			}, container_state.clone()),
+			eddsa_signing_sessions: ClusterSessionsContainer::new(EddsaSigningSessionCreator {
+				core: creator_core.clone(),
+			}, container_state.clone()),
+			bls_generation_sessions: ClusterSessionsContainer::new(BlsGenerationSessionCreator {
+				core: creator_core.clone(),
+			}, container_state.clone()),
+			bls_signing_sessions: ClusterSessionsContainer::new(BlsSigningSessionCreator {
+				core: creator_core.clone(),
+			}, container_state.clone()),
+			randomness_beacon_sessions: ClusterSessionsContainer::new(RandomnessBeaconSessionCreator {
+				core: creator_core.clone(),
+				self_key_pair: config.self_key_pair.clone(),
+			}, container_state.clone()),
 			negotiation_sessions: ClusterSessionsContainer::new(KeyVersionNegotiationSessionCreator {
 				core: creator_core.clone(),
 			}, container_state.clone()),
@@ -258,6 +332,11 @@ impl ClusterSessions {
 		}
 	}
 
+	/// Should sessions use reliable broadcast?
+	pub fn reliable_broadcast(&self) -> bool {
+		self.reliable_broadcast
+	}
+
 	#[cfg(test)]
 	pub fn make_faulty_generation_sessions(&self) {
 		self.generation_sessions.creator.make_faulty_generation_sessions();
@@ -267,9 +346,17 @@ impl ClusterSessions {
 	pub fn preserve_sessions(&mut self) {
 		self.generation_sessions.preserve_sessions = true;
 		self.encryption_sessions.preserve_sessions = true;
+		self.key_deletion_sessions.preserve_sessions = true;
+		self.key_import_sessions.preserve_sessions = true;
+		self.key_export_sessions.preserve_sessions = true;
 		self.decryption_sessions.preserve_sessions = true;
 		self.schnorr_signing_sessions.preserve_sessions = true;
 		self.ecdsa_signing_sessions.preserve_sessions = true;
+		self.eddsa_generation_sessions.preserve_sessions = true;
+		self.eddsa_signing_sessions.preserve_sessions = true;
+		self.bls_generation_sessions.preserve_sessions = true;
+		self.bls_signing_sessions.preserve_sessions = true;
+		self.randomness_beacon_sessions.preserve_sessions = true;
 		self.negotiation_sessions.preserve_sessions = true;
 		self.admin_sessions.preserve_sessions = true;
 	}
@@ -290,9 +377,17 @@ impl ClusterSessions {
 	pub fn stop_stalled_sessions(&self) {
 		self.generation_sessions.stop_stalled_sessions();
 		self.encryption_sessions.stop_stalled_sessions();
+		self.key_deletion_sessions.stop_stalled_sessions();
+		self.key_import_sessions.stop_stalled_sessions();
+		self.key_export_sessions.stop_stalled_sessions();
 		self.decryption_sessions.stop_stalled_sessions();
 		self.schnorr_signing_sessions.stop_stalled_sessions();
 		self.ecdsa_signing_sessions.stop_stalled_sessions();
+		self.eddsa_generation_sessions.stop_stalled_sessions();
+		self.eddsa_signing_sessions.stop_stalled_sessions();
+		self.bls_generation_sessions.stop_stalled_sessions();
+		self.bls_signing_sessions.stop_stalled_sessions();
+		self.randomness_beacon_sessions.stop_stalled_sessions();
 		self.negotiation_sessions.stop_stalled_sessions();
 		self.admin_sessions.stop_stalled_sessions();
 	}
@@ -301,9 +396,17 @@ impl ClusterSessions {
 	pub fn on_connection_timeout(&self, node_id: &NodeId) {
 		self.generation_sessions.on_connection_timeout(node_id);
 		self.encryption_sessions.on_connection_timeout(node_id);
+		self.key_deletion_sessions.on_connection_timeout(node_id);
+		self.key_import_sessions.on_connection_timeout(node_id);
+		self.key_export_sessions.on_connection_timeout(node_id);
 		self.decryption_sessions.on_connection_timeout(node_id);
 		self.schnorr_signing_sessions.on_connection_timeout(node_id);
 		self.ecdsa_signing_sessions.on_connection_timeout(node_id);
+		self.eddsa_generation_sessions.on_connection_timeout(node_id);
+		self.eddsa_signing_sessions.on_connection_timeout(node_id);
+		self.bls_generation_sessions.on_connection_timeout(node_id);
+		self.bls_signing_sessions.on_connection_timeout(node_id);
+		self.randomness_beacon_sessions.on_connection_timeout(node_id);
 		self.negotiation_sessions.on_connection_timeout(node_id);
 		self.admin_sessions.on_connection_timeout(node_id);
 		self.creator_core.on_connection_timeout(node_id);
@@ -560,6 +663,27 @@ impl AdminSession {
 			_ => None
 		}
 	}
+
+	pub fn as_share_refresh(&self) -> Option<&ShareRefreshSessionImpl> {
+		match *self {
+			AdminSession::ShareRefresh(ref session) => Some(session),
+			_ => None
+		}
+	}
+
+	pub fn as_key_rotation(&self) -> Option<&KeyRotationSessionImpl> {
+		match *self {
+			AdminSession::KeyRotation(ref session) => Some(session),
+			_ => None
+		}
+	}
+
+	pub fn as_threshold_change(&self) -> Option<&ThresholdChangeSessionImpl> {
+		match *self {
+			AdminSession::ThresholdChange(ref session) => Some(session),
+			_ => None
+		}
+	}
 }
 
 impl ClusterSession for AdminSession {
@@ -575,6 +699,9 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.id().clone(),
 			AdminSession::ServersSetChange(ref session) => session.id().clone(),
+			AdminSession::ShareRefresh(ref session) => session.id().clone(),
+			AdminSession::KeyRotation(ref session) => session.id().clone(),
+			AdminSession::ThresholdChange(ref session) => session.id().clone(),
 		}
 	}
 
@@ -582,6 +709,9 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.is_finished(),
 			AdminSession::ServersSetChange(ref session) => session.is_finished(),
+			AdminSession::ShareRefresh(ref session) => session.is_finished(),
+			AdminSession::KeyRotation(ref session) => session.is_finished(),
+			AdminSession::ThresholdChange(ref session) => session.is_finished(),
 		}
 	}
 
@@ -589,6 +719,9 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_session_timeout(),
 			AdminSession::ServersSetChange(ref session) => session.on_session_timeout(),
+			AdminSession::ShareRefresh(ref session) => session.on_session_timeout(),
+			AdminSession::KeyRotation(ref session) => session.on_session_timeout(),
+			AdminSession::ThresholdChange(ref session) => session.on_session_timeout(),
 		}
 	}
 
@@ -596,6 +729,9 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_node_timeout(node_id),
 			AdminSession::ServersSetChange(ref session) => session.on_node_timeout(node_id),
+			AdminSession::ShareRefresh(ref session) => session.on_node_timeout(node_id),
+			AdminSession::KeyRotation(ref session) => session.on_node_timeout(node_id),
+			AdminSession::ThresholdChange(ref session) => session.on_node_timeout(node_id),
 		}
 	}
 
@@ -603,6 +739,9 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_session_error(node, error),
 			AdminSession::ServersSetChange(ref session) => session.on_session_error(node, error),
+			AdminSession::ShareRefresh(ref session) => session.on_session_error(node, error),
+			AdminSession::KeyRotation(ref session) => session.on_session_error(node, error),
+			AdminSession::ThresholdChange(ref session) => session.on_session_error(node, error),
 		}
 	}
 
@@ -610,6 +749,9 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_message(sender, message),
 			AdminSession::ServersSetChange(ref session) => session.on_message(sender, message),
+			AdminSession::ShareRefresh(ref session) => session.on_message(sender, message),
+			AdminSession::KeyRotation(ref session) => session.on_message(sender, message),
+			AdminSession::ThresholdChange(ref session) => session.on_message(sender, message),
 		}
 	}
 }
@@ -648,7 +790,12 @@ impl<T> CompletionSignal<T> {
 	}
 }
 
-pub fn create_cluster_view(self_key_pair: Arc<dyn SigningKeyPair>, connections: Arc<dyn ConnectionProvider>, requires_all_connections: bool) -> Result<Arc<dyn Cluster>, Error> {
+pub fn create_cluster_view(
+	self_key_pair: Arc<dyn SigningKeyPair>,
+	connections: Arc<dyn ConnectionProvider>,
+	requires_all_connections: bool,
+	reliable_broadcast: bool,
+) -> Result<Arc<dyn Cluster>, Error> {
 	let mut connected_nodes = connections.connected_nodes()?;
 	let disconnected_nodes = connections.disconnected_nodes();
 
@@ -662,7 +809,8 @@ pub fn create_cluster_view(self_key_pair: Arc<dyn SigningKeyPair>, connections:
 	connected_nodes.insert(self_key_pair.public().clone());
 
 	let connected_nodes_count = connected_nodes.len();
-	Ok(Arc::new(ClusterView::new(self_key_pair, connections, connected_nodes, connected_nodes_count + disconnected_nodes_count)))
+	Ok(Arc::new(ClusterView::new(self_key_pair, connections, connected_nodes,
+		connected_nodes_count + disconnected_nodes_count, reliable_broadcast)))
 }
 
 #[cfg(test)]
@@ -670,7 +818,7 @@ mod tests {
 	use std::sync::Arc;
 	use std::sync::atomic::{AtomicUsize, Ordering};
 	use crypto::publickey::{Random, Generator};
-	use key_server_cluster::{Error, DummyAclStorage, DummyKeyStorage, MapKeyServerSet, PlainNodeKeyPair};
+	use key_server_cluster::{Error, DummyAclStorage, DummyKeyStorage, MapKeyServerSet, PlainNodeKeyPair, SystemClock};
 	use key_server_cluster::cluster::ClusterConfiguration;
 	use key_server_cluster::connection_trigger::SimpleServersSetChangeSessionCreatorConnector;
 	use key_server_cluster::cluster::tests::DummyCluster;
@@ -680,13 +828,19 @@ mod tests {
 
 	pub fn make_cluster_sessions() -> ClusterSessions {
 		let key_pair = Random.generate();
+		let node_key_pair = Arc::new(PlainNodeKeyPair::new(key_pair.clone()));
 		let config = ClusterConfiguration {
-			self_key_pair: Arc::new(PlainNodeKeyPair::new(key_pair.clone())),
+			self_key_pair: node_key_pair.clone(),
+			self_secret_key_pair: node_key_pair,
 			key_server_set: Arc::new(MapKeyServerSet::new(false, vec![(key_pair.public().clone(), format!("127.0.0.1:{}", 100).parse().unwrap())].into_iter().collect())),
 			key_storage: Arc::new(DummyKeyStorage::default()),
 			acl_storage: Arc::new(DummyAclStorage::default()),
 			admin_public: Some(Random.generate().public().clone()),
+			key_export_approvers: None,
 			preserve_sessions: false,
+			reliable_broadcast: false,
+			ecdsa_mta_enabled: false,
+			expiration_clock: Arc::new(SystemClock),
 		};
 		ClusterSessions::new(&config, Arc::new(SimpleServersSetChangeSessionCreatorConnector {
 			admin_public: Some(Random.generate().public().clone()),
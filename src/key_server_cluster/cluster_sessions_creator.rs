@@ -19,25 +19,48 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::collections::BTreeMap;
 use parking_lot::RwLock;
 use crypto::publickey::Public;
-use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, DocumentKeyShare, SessionMeta};
+use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, DocumentKeyShare, KeyCurve,
+	SessionMeta, KeyDerivationPath, SigningKeyPair, SecretKeyPair, KeyExportApprovers, DocumentKeySlotId, ExpirationClock};
+use key_server_cluster::math;
 use key_server_cluster::cluster::{Cluster, ClusterConfiguration};
 use key_server_cluster::connection_trigger::ServersSetChangeSessionCreatorConnector;
 use key_server_cluster::cluster_sessions::{WaitableSession, ClusterSession, SessionIdWithSubSession,
 	AdminSession, AdminSessionCreationData};
 use key_server_cluster::message::{self, Message, DecryptionMessage, SchnorrSigningMessage, ConsensusMessageOfShareAdd,
-	ShareAddMessage, ServersSetChangeMessage, ConsensusMessage, ConsensusMessageWithServersSet, EcdsaSigningMessage};
+	ShareAddMessage, ServersSetChangeMessage, ConsensusMessage, ConsensusMessageWithServersSet, EcdsaSigningMessage,
+	ShareRefreshMessage, KeyRotationMessage, ThresholdChangeMessage, EddsaSigningMessage,
+	BlsSigningMessage};
 use key_server_cluster::generation_session::{SessionImpl as GenerationSessionImpl, SessionParams as GenerationSessionParams};
 use key_server_cluster::decryption_session::{SessionImpl as DecryptionSessionImpl,
 	SessionParams as DecryptionSessionParams};
 use key_server_cluster::encryption_session::{SessionImpl as EncryptionSessionImpl, SessionParams as EncryptionSessionParams};
+use key_server_cluster::key_deletion_session::{SessionImpl as KeyDeletionSessionImpl, SessionParams as KeyDeletionSessionParams};
+use key_server_cluster::key_import_session::{SessionImpl as KeyImportSessionImpl, SessionParams as KeyImportSessionParams};
+use key_server_cluster::key_export_session::{SessionImpl as KeyExportSessionImpl, SessionParams as KeyExportSessionParams};
+use key_server_cluster::randomness_beacon_session::{SessionImpl as RandomnessBeaconSessionImpl,
+	SessionParams as RandomnessBeaconSessionParams};
 use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSessionImpl,
 	SessionParams as EcdsaSigningSessionParams};
 use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSessionImpl,
 	SessionParams as SchnorrSigningSessionParams};
+use key_server_cluster::generation_session_eddsa::{SessionImpl as EddsaGenerationSessionImpl,
+	SessionParams as EddsaGenerationSessionParams};
+use key_server_cluster::signing_session_eddsa::{SessionImpl as EddsaSigningSessionImpl,
+	SessionParams as EddsaSigningSessionParams};
+use key_server_cluster::generation_session_bls::{SessionImpl as BlsGenerationSessionImpl,
+	SessionParams as BlsGenerationSessionParams};
+use key_server_cluster::signing_session_bls::{SessionImpl as BlsSigningSessionImpl,
+	SessionParams as BlsSigningSessionParams};
 use key_server_cluster::share_add_session::{SessionImpl as ShareAddSessionImpl,
 	SessionParams as ShareAddSessionParams, IsolatedSessionTransport as ShareAddTransport};
 use key_server_cluster::servers_set_change_session::{SessionImpl as ServersSetChangeSessionImpl,
 	SessionParams as ServersSetChangeSessionParams};
+use key_server_cluster::share_refresh_session::{SessionImpl as ShareRefreshSessionImpl,
+	SessionParams as ShareRefreshSessionParams};
+use key_server_cluster::key_rotation_session::{SessionImpl as KeyRotationSessionImpl,
+	SessionParams as KeyRotationSessionParams};
+use key_server_cluster::threshold_change_session::{SessionImpl as ThresholdChangeSessionImpl,
+	SessionParams as ThresholdChangeSessionParams};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSessionImpl,
 	SessionParams as KeyVersionNegotiationSessionParams, IsolatedSessionTransport as VersionNegotiationTransport,
 	FastestResultComputer as FastestResultKeyVersionsResultComputer};
@@ -77,6 +100,8 @@ pub struct SessionCreatorCore {
 	key_storage: Arc<dyn KeyStorage>,
 	/// Reference to ACL storage
 	acl_storage: Arc<dyn AclStorage>,
+	/// Clock that is used to check if server key has expired.
+	expiration_clock: Arc<dyn ExpirationClock>,
 	/// Always-increasing sessions counter. Is used as session nonce to prevent replay attacks:
 	/// 1) during handshake, KeyServers generate new random key to encrypt messages
 	/// => there's no way to use messages from previous connections for replay attacks
@@ -98,6 +123,7 @@ impl SessionCreatorCore {
 			self_node_id: config.self_key_pair.public().clone(),
 			acl_storage: config.acl_storage.clone(),
 			key_storage: config.key_storage.clone(),
+			expiration_clock: config.expiration_clock.clone(),
 			session_counter: AtomicUsize::new(0),
 			max_nonce: RwLock::new(BTreeMap::new()),
 		}
@@ -121,9 +147,38 @@ impl SessionCreatorCore {
 		}
 	}
 
-	/// Read key share && remove disconnected nodes.
+	/// Read key share && warn if the full key has been exported, so that it isn't protected by the threshold anymore.
 	fn read_key_share(&self, key_id: &SessionId) -> Result<Option<DocumentKeyShare>, Error> {
-		self.key_storage.get(key_id)
+		let key_share = self.key_storage.get(key_id)?;
+		if key_share.as_ref().map(|key_share| key_share.exported).unwrap_or(false) {
+			warn!(target: "secretstore", "{}: using server key {}, which has been exported", self.self_node_id, key_id);
+		}
+
+		Ok(key_share)
+	}
+
+	/// Read key share && check that it has been generated for given curve.
+	fn read_key_share_of_curve(&self, key_id: &SessionId, curve: KeyCurve) -> Result<Option<DocumentKeyShare>, Error> {
+		match self.read_key_share(key_id)? {
+			Some(ref key_share) if key_share.curve != curve => Err(Error::InvalidKeyCurve),
+			key_share => Ok(key_share),
+		}
+	}
+
+	/// Read key share of given curve && check that it hasn't expired. Used by sessions that are using the key.
+	fn read_unexpired_key_share_of_curve(&self, key_id: &SessionId, curve: KeyCurve) -> Result<Option<DocumentKeyShare>, Error> {
+		match self.read_key_share_of_curve(key_id, curve)? {
+			Some(ref key_share) if self.expiration_clock.is_expired(key_share) => Err(Error::ServerKeyIsExpired),
+			key_share => Ok(key_share),
+		}
+	}
+
+	/// Read unexpired secp256k1 key share && derive share of the child key from it.
+	fn read_child_key_share(&self, key_id: &SessionId, derivation_path: &[u32]) -> Result<Option<DocumentKeyShare>, Error> {
+		match self.read_unexpired_key_share_of_curve(key_id, KeyCurve::Secp256k1)? {
+			Some(key_share) => derive_child_key_share(key_share, derivation_path).map(Some),
+			None => Ok(None),
+		}
 	}
 }
 
@@ -163,6 +218,10 @@ impl ClusterSessionCreator<GenerationSessionImpl> for GenerationSessionCreator {
 		if self.core.key_storage.contains(&id) {
 			return Err(Error::ServerKeyAlreadyGenerated);
 		}
+		// check that the key with the same id has not been deleted
+		if self.core.key_storage.is_tombstoned(&id) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
 
 		let nonce = self.core.check_session_nonce(&master, nonce)?;
 		let (session, oneshot) = GenerationSessionImpl::new(GenerationSessionParams {
@@ -204,7 +263,7 @@ impl ClusterSessionCreator<EncryptionSessionImpl> for EncryptionSessionCreator {
 		id: SessionId,
 		_creation_data: Option<()>,
 	) -> Result<WaitableSession<EncryptionSessionImpl>, Error> {
-		let encrypted_data = self.core.read_key_share(&id)?;
+		let encrypted_data = self.core.read_unexpired_key_share_of_curve(&id, KeyCurve::Secp256k1)?;
 		let nonce = self.core.check_session_nonce(&master, nonce)?;
 		let (session, oneshot) = EncryptionSessionImpl::new(EncryptionSessionParams {
 			id: id,
@@ -219,6 +278,174 @@ impl ClusterSessionCreator<EncryptionSessionImpl> for EncryptionSessionCreator {
 	}
 }
 
+/// Key deletion session creator.
+pub struct KeyDeletionSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+}
+
+impl ClusterSessionCreator<KeyDeletionSessionImpl> for KeyDeletionSessionCreator {
+	fn make_error_message(sid: SessionId, nonce: u64, err: Error) -> Message {
+		message::Message::KeyDeletion(message::KeyDeletionMessage::KeyDeletionSessionError(message::KeyDeletionSessionError {
+			session: sid.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionId,
+		_creation_data: Option<()>,
+	) -> Result<WaitableSession<KeyDeletionSessionImpl>, Error> {
+		let key_share = self.core.read_key_share(&id)?;
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = KeyDeletionSessionImpl::new(KeyDeletionSessionParams {
+			id: id,
+			self_node_id: self.core.self_node_id.clone(),
+			master_node_id: master,
+			key_share: key_share,
+			key_storage: self.core.key_storage.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		});
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
+/// Key import session creator.
+pub struct KeyImportSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+	/// Key pair of this node, used to decrypt imported key shares.
+	pub self_key_pair: Arc<dyn SecretKeyPair>,
+}
+
+impl ClusterSessionCreator<KeyImportSessionImpl> for KeyImportSessionCreator {
+	fn make_error_message(sid: SessionId, nonce: u64, err: Error) -> Message {
+		message::Message::KeyImport(message::KeyImportMessage::KeyImportSessionError(message::KeyImportSessionError {
+			session: sid.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionId,
+		_creation_data: Option<()>,
+	) -> Result<WaitableSession<KeyImportSessionImpl>, Error> {
+		// check that there's no key with the same id
+		if self.core.key_storage.contains(&id) {
+			return Err(Error::ServerKeyAlreadyGenerated);
+		}
+		// check that the key with the same id has not been deleted
+		if self.core.key_storage.is_tombstoned(&id) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
+
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = KeyImportSessionImpl::new(KeyImportSessionParams {
+			id: id,
+			self_key_pair: self.self_key_pair.clone(),
+			master_node_id: master,
+			key_storage: self.core.key_storage.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		});
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
+/// Key export session creator.
+pub struct KeyExportSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+	/// Key export approvers (if key export is enabled).
+	pub approvers: Option<KeyExportApprovers>,
+}
+
+impl ClusterSessionCreator<KeyExportSessionImpl> for KeyExportSessionCreator {
+	fn make_error_message(sid: SessionId, nonce: u64, err: Error) -> Message {
+		message::Message::KeyExport(message::KeyExportMessage::KeyExportSessionError(message::KeyExportSessionError {
+			session: sid.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionId,
+		_creation_data: Option<()>,
+	) -> Result<WaitableSession<KeyExportSessionImpl>, Error> {
+		let key_share = self.core.read_unexpired_key_share_of_curve(&id, KeyCurve::Secp256k1)?;
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = KeyExportSessionImpl::new(KeyExportSessionParams {
+			id: id,
+			self_node_id: self.core.self_node_id.clone(),
+			master_node_id: master,
+			key_share: key_share,
+			key_storage: self.core.key_storage.clone(),
+			approvers: self.approvers.clone(),
+			clock: self.core.expiration_clock.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		});
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
+/// Randomness beacon session creator.
+pub struct RandomnessBeaconSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+	/// Key pair of this node.
+	pub self_key_pair: Arc<dyn SigningKeyPair>,
+}
+
+impl ClusterSessionCreator<RandomnessBeaconSessionImpl> for RandomnessBeaconSessionCreator {
+	fn make_error_message(sid: SessionId, nonce: u64, err: Error) -> Message {
+		message::Message::RandomnessBeacon(message::RandomnessBeaconMessage::RandomnessBeaconError(message::RandomnessBeaconError {
+			session: sid.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionId,
+		_creation_data: Option<()>,
+	) -> Result<WaitableSession<RandomnessBeaconSessionImpl>, Error> {
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = RandomnessBeaconSessionImpl::new(RandomnessBeaconSessionParams {
+			id: id,
+			master_node_id: master,
+			self_key_pair: self.self_key_pair.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		});
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
 /// Decryption session creator.
 pub struct DecryptionSessionCreator {
 	/// Creator core.
@@ -226,13 +453,23 @@ pub struct DecryptionSessionCreator {
 }
 
 impl ClusterSessionCreator<DecryptionSessionImpl> for DecryptionSessionCreator {
-	fn creation_data_from_message(message: &Message) -> Result<Option<Requester>, Error> {
+	fn creation_data_from_message(message: &Message) -> Result<Option<(Requester, KeyDerivationPath, Option<Public>, Option<DocumentKeySlotId>)>, Error> {
 		match *message {
 			Message::Decryption(DecryptionMessage::DecryptionConsensusMessage(ref message)) => match &message.message {
-				&ConsensusMessage::InitializeConsensusSession(ref message) => Ok(Some(message.requester.clone().into())),
+				&ConsensusMessage::InitializeConsensusSession(ref message) => Ok(Some((
+					message.requester.clone().into(),
+					message.derivation_path.clone(),
+					message.ecies_ephemeral_public.clone().map(Into::into),
+					message.document_key_slot.clone().map(Into::into),
+				))),
 				_ => Err(Error::InvalidMessage),
 			},
-			Message::Decryption(DecryptionMessage::DecryptionSessionDelegation(ref message)) => Ok(Some(message.requester.clone().into())),
+			Message::Decryption(DecryptionMessage::DecryptionSessionDelegation(ref message)) => Ok(Some((
+				message.requester.clone().into(),
+				message.derivation_path.clone(),
+				message.ecies_ephemeral_public.clone().map(Into::into),
+				message.document_key_slot.clone().map(Into::into),
+			))),
 			_ => Err(Error::InvalidMessage),
 		}
 	}
@@ -252,9 +489,22 @@ impl ClusterSessionCreator<DecryptionSessionImpl> for DecryptionSessionCreator {
 		master: NodeId,
 		nonce: Option<u64>,
 		id: SessionIdWithSubSession,
-		requester: Option<Requester>,
+		creation_data: Option<(Requester, KeyDerivationPath, Option<Public>, Option<DocumentKeySlotId>)>,
 	) -> Result<WaitableSession<DecryptionSessionImpl>, Error> {
-		let encrypted_data = self.core.read_key_share(&id.id)?;
+		let (requester, derivation_path, ecies_ephemeral_public, document_key_slot) = match creation_data {
+			Some((requester, derivation_path, ecies_ephemeral_public, document_key_slot)) =>
+				(Some(requester), derivation_path, ecies_ephemeral_public, document_key_slot),
+			None => (None, Vec::new(), None, None),
+		};
+		let encrypted_data = self.core.read_child_key_share(&id.id, &derivation_path)?;
+		let encrypted_data = match document_key_slot.as_ref() {
+			Some(document_key_slot) => encrypted_data.map(|key_share| document_key_slot_key_share(key_share, document_key_slot)),
+			None => encrypted_data,
+		};
+		let encrypted_data = match ecies_ephemeral_public.as_ref() {
+			Some(ecies_ephemeral_public) => encrypted_data.map(|key_share| ecies_key_share(key_share, ecies_ephemeral_public)),
+			None => encrypted_data,
+		};
 		let nonce = self.core.check_session_nonce(&master, nonce)?;
 		let (session, oneshot) = DecryptionSessionImpl::new(DecryptionSessionParams {
 			meta: SessionMeta {
@@ -267,6 +517,9 @@ impl ClusterSessionCreator<DecryptionSessionImpl> for DecryptionSessionCreator {
 			},
 			access_key: id.access_key,
 			key_share: encrypted_data,
+			derivation_path: derivation_path,
+			ecies_ephemeral_public: ecies_ephemeral_public,
+			document_key_slot: document_key_slot,
 			acl_storage: self.core.acl_storage.clone(),
 			cluster: cluster,
 			nonce: nonce,
@@ -283,13 +536,15 @@ pub struct SchnorrSigningSessionCreator {
 }
 
 impl ClusterSessionCreator<SchnorrSigningSessionImpl> for SchnorrSigningSessionCreator {
-	fn creation_data_from_message(message: &Message) -> Result<Option<Requester>, Error> {
+	fn creation_data_from_message(message: &Message) -> Result<Option<(Requester, KeyDerivationPath)>, Error> {
 		match *message {
 			Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningConsensusMessage(ref message)) => match &message.message {
-				&ConsensusMessage::InitializeConsensusSession(ref message) => Ok(Some(message.requester.clone().into())),
+				&ConsensusMessage::InitializeConsensusSession(ref message) =>
+					Ok(Some((message.requester.clone().into(), message.derivation_path.clone()))),
 				_ => Err(Error::InvalidMessage),
 			},
-			Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionDelegation(ref message)) => Ok(Some(message.requester.clone().into())),
+			Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionDelegation(ref message)) =>
+				Ok(Some((message.requester.clone().into(), message.derivation_path.clone()))),
 			_ => Err(Error::InvalidMessage),
 		}
 	}
@@ -309,9 +564,13 @@ impl ClusterSessionCreator<SchnorrSigningSessionImpl> for SchnorrSigningSessionC
 		master: NodeId,
 		nonce: Option<u64>,
 		id: SessionIdWithSubSession,
-		requester: Option<Requester>,
+		creation_data: Option<(Requester, KeyDerivationPath)>,
 	) -> Result<WaitableSession<SchnorrSigningSessionImpl>, Error> {
-		let encrypted_data = self.core.read_key_share(&id.id)?;
+		let (requester, derivation_path) = match creation_data {
+			Some((requester, derivation_path)) => (Some(requester), derivation_path),
+			None => (None, Vec::new()),
+		};
+		let encrypted_data = self.core.read_child_key_share(&id.id, &derivation_path)?;
 		let nonce = self.core.check_session_nonce(&master, nonce)?;
 		let (session, oneshot) = SchnorrSigningSessionImpl::new(SchnorrSigningSessionParams {
 			meta: SessionMeta {
@@ -324,6 +583,7 @@ impl ClusterSessionCreator<SchnorrSigningSessionImpl> for SchnorrSigningSessionC
 			},
 			access_key: id.access_key,
 			key_share: encrypted_data,
+			derivation_path: derivation_path,
 			acl_storage: self.core.acl_storage.clone(),
 			cluster: cluster,
 			nonce: nonce,
@@ -339,13 +599,15 @@ pub struct EcdsaSigningSessionCreator {
 }
 
 impl ClusterSessionCreator<EcdsaSigningSessionImpl> for EcdsaSigningSessionCreator {
-	fn creation_data_from_message(message: &Message) -> Result<Option<Requester>, Error> {
+	fn creation_data_from_message(message: &Message) -> Result<Option<(Requester, KeyDerivationPath)>, Error> {
 		match *message {
 			Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningConsensusMessage(ref message)) => match &message.message {
-				&ConsensusMessage::InitializeConsensusSession(ref message) => Ok(Some(message.requester.clone().into())),
+				&ConsensusMessage::InitializeConsensusSession(ref message) =>
+					Ok(Some((message.requester.clone().into(), message.derivation_path.clone()))),
 				_ => Err(Error::InvalidMessage),
 			},
-			Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegation(ref message)) => Ok(Some(message.requester.clone().into())),
+			Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegation(ref message)) =>
+				Ok(Some((message.requester.clone().into(), message.derivation_path.clone()))),
 			_ => Err(Error::InvalidMessage),
 		}
 	}
@@ -359,10 +621,226 @@ impl ClusterSessionCreator<EcdsaSigningSessionImpl> for EcdsaSigningSessionCreat
 		}))
 	}
 
-	fn create(&self, cluster: Arc<dyn Cluster>, master: NodeId, nonce: Option<u64>, id: SessionIdWithSubSession, requester: Option<Requester>) -> Result<WaitableSession<EcdsaSigningSessionImpl>, Error> {
-		let encrypted_data = self.core.read_key_share(&id.id)?;
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionIdWithSubSession,
+		creation_data: Option<(Requester, KeyDerivationPath)>,
+	) -> Result<WaitableSession<EcdsaSigningSessionImpl>, Error> {
+		let (requester, derivation_path) = match creation_data {
+			Some((requester, derivation_path)) => (Some(requester), derivation_path),
+			None => (None, Vec::new()),
+		};
+		let encrypted_data = self.core.read_child_key_share(&id.id, &derivation_path)?;
 		let nonce = self.core.check_session_nonce(&master, nonce)?;
 		let (session, oneshot) = EcdsaSigningSessionImpl::new(EcdsaSigningSessionParams {
+			meta: SessionMeta {
+				id: id.id,
+				self_node_id: self.core.self_node_id.clone(),
+				master_node_id: master,
+				threshold: encrypted_data.as_ref().map(|ks| ks.threshold).unwrap_or_default(),
+				configured_nodes_count: cluster.configured_nodes_count(),
+				connected_nodes_count: cluster.connected_nodes_count(),
+			},
+			access_key: id.access_key,
+			key_share: encrypted_data,
+			derivation_path: derivation_path,
+			acl_storage: self.core.acl_storage.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		}, requester)?;
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
+/// Ed25519 key generation session creator.
+pub struct EddsaGenerationSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+}
+
+impl ClusterSessionCreator<EddsaGenerationSessionImpl> for EddsaGenerationSessionCreator {
+	fn make_error_message(sid: SessionId, nonce: u64, err: Error) -> Message {
+		message::Message::EddsaGeneration(message::EddsaGenerationMessage::EddsaGenerationSessionError(message::EddsaGenerationSessionError {
+			session: sid.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionId,
+		_creation_data: Option<()>,
+	) -> Result<WaitableSession<EddsaGenerationSessionImpl>, Error> {
+		// check that there's no finished generation session with the same id
+		if self.core.key_storage.contains(&id) {
+			return Err(Error::ServerKeyAlreadyGenerated);
+		}
+		// check that the key with the same id has not been deleted
+		if self.core.key_storage.is_tombstoned(&id) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
+
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = EddsaGenerationSessionImpl::new(EddsaGenerationSessionParams {
+			id: id.clone(),
+			self_node_id: self.core.self_node_id.clone(),
+			key_storage: Some(self.core.key_storage.clone()),
+			cluster: cluster,
+			nonce: Some(nonce),
+		});
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
+/// EdDSA signing session creator.
+pub struct EddsaSigningSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+}
+
+impl ClusterSessionCreator<EddsaSigningSessionImpl> for EddsaSigningSessionCreator {
+	fn creation_data_from_message(message: &Message) -> Result<Option<Requester>, Error> {
+		match *message {
+			Message::EddsaSigning(EddsaSigningMessage::EddsaSigningConsensusMessage(ref message)) => match &message.message {
+				&ConsensusMessage::InitializeConsensusSession(ref message) => Ok(Some(message.requester.clone().into())),
+				_ => Err(Error::InvalidMessage),
+			},
+			Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegation(ref message)) => Ok(Some(message.requester.clone().into())),
+			_ => Err(Error::InvalidMessage),
+		}
+	}
+
+	fn make_error_message(sid: SessionIdWithSubSession, nonce: u64, err: Error) -> Message {
+		message::Message::EddsaSigning(message::EddsaSigningMessage::EddsaSigningSessionError(message::EddsaSigningSessionError {
+			session: sid.id.into(),
+			sub_session: sid.access_key.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionIdWithSubSession,
+		requester: Option<Requester>,
+	) -> Result<WaitableSession<EddsaSigningSessionImpl>, Error> {
+		let encrypted_data = self.core.read_unexpired_key_share_of_curve(&id.id, KeyCurve::Ed25519)?;
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = EddsaSigningSessionImpl::new(EddsaSigningSessionParams {
+			meta: SessionMeta {
+				id: id.id,
+				self_node_id: self.core.self_node_id.clone(),
+				master_node_id: master,
+				threshold: encrypted_data.as_ref().map(|ks| ks.threshold).unwrap_or_default(),
+				configured_nodes_count: cluster.configured_nodes_count(),
+				connected_nodes_count: cluster.connected_nodes_count(),
+			},
+			access_key: id.access_key,
+			key_share: encrypted_data,
+			acl_storage: self.core.acl_storage.clone(),
+			cluster: cluster,
+			nonce: nonce,
+		}, requester)?;
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
+/// BLS12-381 key generation session creator.
+pub struct BlsGenerationSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+}
+
+impl ClusterSessionCreator<BlsGenerationSessionImpl> for BlsGenerationSessionCreator {
+	fn make_error_message(sid: SessionId, nonce: u64, err: Error) -> Message {
+		message::Message::BlsGeneration(message::BlsGenerationMessage::BlsGenerationSessionError(message::BlsGenerationSessionError {
+			session: sid.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionId,
+		_creation_data: Option<()>,
+	) -> Result<WaitableSession<BlsGenerationSessionImpl>, Error> {
+		// check that there's no finished generation session with the same id
+		if self.core.key_storage.contains(&id) {
+			return Err(Error::ServerKeyAlreadyGenerated);
+		}
+		// check that the key with the same id has not been deleted
+		if self.core.key_storage.is_tombstoned(&id) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
+
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = BlsGenerationSessionImpl::new(BlsGenerationSessionParams {
+			id: id.clone(),
+			self_node_id: self.core.self_node_id.clone(),
+			key_storage: Some(self.core.key_storage.clone()),
+			cluster: cluster,
+			nonce: Some(nonce),
+		});
+
+		Ok(WaitableSession::new(session, oneshot))
+	}
+}
+
+/// BLS signing session creator.
+pub struct BlsSigningSessionCreator {
+	/// Creator core.
+	pub core: Arc<SessionCreatorCore>,
+}
+
+impl ClusterSessionCreator<BlsSigningSessionImpl> for BlsSigningSessionCreator {
+	fn creation_data_from_message(message: &Message) -> Result<Option<Requester>, Error> {
+		match *message {
+			Message::BlsSigning(BlsSigningMessage::BlsSigningConsensusMessage(ref message)) => match &message.message {
+				&ConsensusMessage::InitializeConsensusSession(ref message) => Ok(Some(message.requester.clone().into())),
+				_ => Err(Error::InvalidMessage),
+			},
+			Message::BlsSigning(BlsSigningMessage::BlsSigningSessionDelegation(ref message)) => Ok(Some(message.requester.clone().into())),
+			_ => Err(Error::InvalidMessage),
+		}
+	}
+
+	fn make_error_message(sid: SessionIdWithSubSession, nonce: u64, err: Error) -> Message {
+		message::Message::BlsSigning(message::BlsSigningMessage::BlsSigningSessionError(message::BlsSigningSessionError {
+			session: sid.id.into(),
+			sub_session: sid.access_key.into(),
+			session_nonce: nonce,
+			error: err.into(),
+		}))
+	}
+
+	fn create(
+		&self,
+		cluster: Arc<dyn Cluster>,
+		master: NodeId,
+		nonce: Option<u64>,
+		id: SessionIdWithSubSession,
+		requester: Option<Requester>,
+	) -> Result<WaitableSession<BlsSigningSessionImpl>, Error> {
+		let encrypted_data = self.core.read_unexpired_key_share_of_curve(&id.id, KeyCurve::Bls12381)?;
+		let nonce = self.core.check_session_nonce(&master, nonce)?;
+		let (session, oneshot) = BlsSigningSessionImpl::new(BlsSigningSessionParams {
 			meta: SessionMeta {
 				id: id.id,
 				self_node_id: self.core.self_node_id.clone(),
@@ -462,6 +940,9 @@ impl ClusterSessionCreator<AdminSession> for AdminSessionCreator {
 				&ConsensusMessageOfShareAdd::InitializeConsensusSession(ref message) => Ok(Some(AdminSessionCreationData::ShareAdd(message.version.clone().into()))),
 				_ => Err(Error::InvalidMessage),
 			},
+			Message::ShareRefresh(ShareRefreshMessage::InitializeShareRefreshSession(_)) => Ok(Some(AdminSessionCreationData::ShareRefresh)),
+			Message::KeyRotation(KeyRotationMessage::InitializeKeyRotationSession(_)) => Ok(Some(AdminSessionCreationData::KeyRotation)),
+			Message::ThresholdChange(ThresholdChangeMessage::InitializeThresholdChangeSession(_)) => Ok(Some(AdminSessionCreationData::ThresholdChange)),
 			_ => Err(Error::InvalidMessage),
 		}
 	}
@@ -521,6 +1002,54 @@ impl ClusterSessionCreator<AdminSession> for AdminSessionCreator {
 				})?;
 				Ok(WaitableSession::new(AdminSession::ServersSetChange(session), oneshot))
 			},
+			Some(AdminSessionCreationData::ShareRefresh) => {
+				let (session, oneshot) = ShareRefreshSessionImpl::new(ShareRefreshSessionParams {
+					meta: ShareChangeSessionMeta {
+						id: id.clone(),
+						self_node_id: self.core.self_node_id.clone(),
+						master_node_id: master,
+						configured_nodes_count: cluster.configured_nodes_count(),
+						connected_nodes_count: cluster.connected_nodes_count(),
+					},
+					cluster: cluster,
+					key_storage: self.core.key_storage.clone(),
+					admin_public: self.admin_public.clone().ok_or(Error::AccessDenied)?,
+					nonce: nonce,
+				})?;
+				Ok(WaitableSession::new(AdminSession::ShareRefresh(session), oneshot))
+			},
+			Some(AdminSessionCreationData::KeyRotation) => {
+				let (session, oneshot) = KeyRotationSessionImpl::new(KeyRotationSessionParams {
+					meta: ShareChangeSessionMeta {
+						id: id.clone(),
+						self_node_id: self.core.self_node_id.clone(),
+						master_node_id: master,
+						configured_nodes_count: cluster.configured_nodes_count(),
+						connected_nodes_count: cluster.connected_nodes_count(),
+					},
+					cluster: cluster,
+					key_storage: self.core.key_storage.clone(),
+					admin_public: self.admin_public.clone().ok_or(Error::AccessDenied)?,
+					nonce: nonce,
+				})?;
+				Ok(WaitableSession::new(AdminSession::KeyRotation(session), oneshot))
+			},
+			Some(AdminSessionCreationData::ThresholdChange) => {
+				let (session, oneshot) = ThresholdChangeSessionImpl::new(ThresholdChangeSessionParams {
+					meta: ShareChangeSessionMeta {
+						id: id.clone(),
+						self_node_id: self.core.self_node_id.clone(),
+						master_node_id: master,
+						configured_nodes_count: cluster.configured_nodes_count(),
+						connected_nodes_count: cluster.connected_nodes_count(),
+					},
+					cluster: cluster,
+					key_storage: self.core.key_storage.clone(),
+					admin_public: self.admin_public.clone().ok_or(Error::AccessDenied)?,
+					nonce: nonce,
+				})?;
+				Ok(WaitableSession::new(AdminSession::ThresholdChange(session), oneshot))
+			},
 			None => unreachable!("expected to call with non-empty creation data; qed"),
 		}
 	}
@@ -531,11 +1060,22 @@ impl IntoSessionId<SessionId> for Message {
 		match *self {
 			Message::Generation(ref message) => Ok(message.session_id().clone()),
 			Message::Encryption(ref message) => Ok(message.session_id().clone()),
+			Message::KeyDeletion(ref message) => Ok(message.session_id().clone()),
 			Message::Decryption(_) => Err(Error::InvalidMessage),
 			Message::SchnorrSigning(_) => Err(Error::InvalidMessage),
 			Message::EcdsaSigning(_) => Err(Error::InvalidMessage),
+			Message::EddsaGeneration(ref message) => Ok(message.session_id().clone()),
+			Message::EddsaSigning(_) => Err(Error::InvalidMessage),
+			Message::BlsGeneration(ref message) => Ok(message.session_id().clone()),
+			Message::BlsSigning(_) => Err(Error::InvalidMessage),
 			Message::ServersSetChange(ref message) => Ok(message.session_id().clone()),
 			Message::ShareAdd(ref message) => Ok(message.session_id().clone()),
+			Message::ShareRefresh(ref message) => Ok(message.session_id().clone()),
+			Message::KeyRotation(ref message) => Ok(message.session_id().clone()),
+			Message::ThresholdChange(ref message) => Ok(message.session_id().clone()),
+			Message::RandomnessBeacon(ref message) => Ok(message.session_id().clone()),
+			Message::KeyImport(ref message) => Ok(message.session_id().clone()),
+			Message::KeyExport(ref message) => Ok(message.session_id().clone()),
 			Message::KeyVersionNegotiation(_) => Err(Error::InvalidMessage),
 			Message::Cluster(_) => Err(Error::InvalidMessage),
 		}
@@ -547,13 +1087,69 @@ impl IntoSessionId<SessionIdWithSubSession> for Message {
 		match *self {
 			Message::Generation(_) => Err(Error::InvalidMessage),
 			Message::Encryption(_) => Err(Error::InvalidMessage),
+			Message::KeyDeletion(_) => Err(Error::InvalidMessage),
 			Message::Decryption(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::SchnorrSigning(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::EcdsaSigning(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
+			Message::EddsaGeneration(_) => Err(Error::InvalidMessage),
+			Message::EddsaSigning(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
+			Message::BlsGeneration(_) => Err(Error::InvalidMessage),
+			Message::BlsSigning(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::ServersSetChange(_) => Err(Error::InvalidMessage),
 			Message::ShareAdd(_) => Err(Error::InvalidMessage),
+			Message::ShareRefresh(_) => Err(Error::InvalidMessage),
+			Message::KeyRotation(_) => Err(Error::InvalidMessage),
+			Message::ThresholdChange(_) => Err(Error::InvalidMessage),
+			Message::RandomnessBeacon(_) => Err(Error::InvalidMessage),
+			Message::KeyImport(_) => Err(Error::InvalidMessage),
+			Message::KeyExport(_) => Err(Error::InvalidMessage),
 			Message::KeyVersionNegotiation(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::Cluster(_) => Err(Error::InvalidMessage),
 		}
 	}
 }
+
+/// Derive share of the non-hardened child key from the server key share. Since derivation tweak only depends on
+/// the server key public, every node is able to derive both its own share && public shares of other nodes.
+fn derive_child_key_share(mut key_share: DocumentKeyShare, derivation_path: &[u32]) -> Result<DocumentKeyShare, Error> {
+	if derivation_path.is_empty() {
+		return Ok(key_share);
+	}
+
+	let (tweak, child_public) = math::compute_child_key_tweak(&key_share.public, derivation_path)?;
+	let tweak_public = math::compute_public_share(&tweak)?;
+	key_share.public = child_public;
+	if let (Some(common_point), Some(encrypted_point)) = (key_share.common_point.as_ref(), key_share.encrypted_point.as_mut()) {
+		*encrypted_point = math::compute_child_encrypted_point(&tweak, common_point, encrypted_point)?;
+	}
+	for slot in key_share.document_key_slots.values_mut() {
+		slot.encrypted_point = math::compute_child_encrypted_point(&tweak, &slot.common_point, &slot.encrypted_point)?;
+	}
+	for version in &mut key_share.versions {
+		version.secret_share = math::compute_secret_sum(vec![version.secret_share.clone(), tweak.clone()].iter())?;
+		for public_share in version.public_shares.values_mut() {
+			*public_share = math::compute_public_sum(vec![public_share.clone(), tweak_public.clone()].iter())?;
+		}
+	}
+
+	Ok(key_share)
+}
+
+/// Prepare key share for computing ECDH secret of ECIES ciphertext, encrypted with the key public. ECIES ephemeral public
+/// `R` is used as both common and encrypted point, so regular decryption computes `R - x * R`, from which the master
+/// node restores `x * R` (see `math::compute_ecdh_shared_point`).
+fn ecies_key_share(mut key_share: DocumentKeyShare, ecies_ephemeral_public: &Public) -> DocumentKeyShare {
+	key_share.common_point = Some(ecies_ephemeral_public.clone());
+	key_share.encrypted_point = Some(ecies_ephemeral_public.clone());
+	key_share
+}
+
+/// Prepare key share for decrypting document key, stored in given slot. Slot points replace the default document key
+/// points, so that the regular decryption session could be used. If slot is empty, both points are reset and session
+/// fails with DocumentKeyIsNotFound.
+fn document_key_slot_key_share(mut key_share: DocumentKeyShare, document_key_slot: &DocumentKeySlotId) -> DocumentKeyShare {
+	let document_key = key_share.document_key(Some(document_key_slot));
+	key_share.common_point = document_key.as_ref().map(|&(ref common_point, _)| common_point.clone());
+	key_share.encrypted_point = document_key.map(|(_, encrypted_point)| encrypted_point);
+	key_share
+}
@@ -24,7 +24,8 @@
 ///! 5) both nodes are checking that they're configured to communicate to server with received `message.self_key_pair.public`. Connection is closed otherwise
 ///! 6) both nodes are recovering peer' `session_key_pair.public` from `message.confirmation_plain` and `message.confirmation_signed_session`
 ///! 7) both nodes are computing shared session key pair using self' `session_key_pair.secret` && peer' `session_key_pair.public`. All following messages are encrypted using this key_pair.
-///! 8) both nodes are signing `message.confirmation_plain` with their own `self_key_pair.private` to receive `confirmation_signed`
+///! 8) both nodes are signing keccak256("secretstore:handshake" || `message.confirmation_plain`) with their own `self_key_pair.private`
+///!    to receive `confirmation_signed`. Domain prefix guarantees that peer can't make node sign anything else (e.g. beacon transcript)
 ///! 9) nodes exchange with `NodePrivateKeySignature` messages, containing `confirmation_signed`
 ///! 10) both nodes are checking that `confirmation_signed` is actually signed with the owner of peer' `self_key_pair.secret`
 ///!
@@ -40,12 +41,16 @@ use tokio_io::{AsyncRead, AsyncWrite};
 use crypto::publickey::ecdh::agree;
 use crypto::publickey::{Random, Generator, KeyPair, Public, Signature, verify_public, sign, recover};
 use ethereum_types::H256;
+use hash::keccak;
 use blockchain::SigningKeyPair;
 use key_server_cluster::{NodeId, Error};
 use key_server_cluster::message::{Message, ClusterMessage, NodePublicKey, NodePrivateKeySignature};
 use key_server_cluster::io::{write_message, write_encrypted_message, WriteMessage, ReadMessage,
 	read_message, read_encrypted_message, fix_shared_key};
 
+/// Domain of hashes, signed with node key during handshake.
+const CONFIRMATION_DOMAIN: &'static [u8] = b"secretstore:handshake";
+
 /// Start handshake procedure with another node from the cluster.
 pub fn handshake<A>(a: A, self_key_pair: Arc<dyn SigningKeyPair>, trusted_nodes: BTreeSet<NodeId>) -> Handshake<A> where A: AsyncWrite + AsyncRead {
 	let init_data = (
@@ -155,7 +160,7 @@ impl<A> Handshake<A> where A: AsyncRead + AsyncWrite {
 
 	fn make_private_key_signature_message(self_key_pair: &dyn SigningKeyPair, confirmation_plain: &H256) -> Result<Message, Error> {
 		Ok(Message::Cluster(ClusterMessage::NodePrivateKeySignature(NodePrivateKeySignature {
-			confirmation_signed: self_key_pair.sign(confirmation_plain)?.into(),
+			confirmation_signed: self_key_pair.sign(&compute_confirmation_hash(confirmation_plain))?.into(),
 		})))
 	}
 
@@ -288,7 +293,7 @@ impl<A> Future for Handshake<A> where A: AsyncRead + AsyncWrite {
 				};
 
 				let peer_public = self.peer_node_id.as_ref().expect("peer_node_id is filled in ReceivePublicKey; ReceivePrivateKeySignature follows ReceivePublicKey; qed");
-				if !verify_public(peer_public, &*message.confirmation_signed, &self.self_confirmation_plain).unwrap_or(false) {
+				if !verify_public(peer_public, &*message.confirmation_signed, &compute_confirmation_hash(&self.self_confirmation_plain)).unwrap_or(false) {
 					return Ok((stream, Err(Error::InvalidMessage)).into());
 				}
 
@@ -309,25 +314,61 @@ impl<A> Future for Handshake<A> where A: AsyncRead + AsyncWrite {
 	}
 }
 
+/// Compute hash that node signs with its own key to prove that it owns this key.
+fn compute_confirmation_hash(confirmation_plain: &H256) -> H256 {
+	keccak([CONFIRMATION_DOMAIN, confirmation_plain.as_bytes()].concat())
+}
+
 #[cfg(test)]
 mod tests {
 	use std::sync::Arc;
 	use std::collections::BTreeSet;
 	use futures::Future;
-	use crypto::publickey::{Random, Generator, sign};
-	use ethereum_types::H256;
-	use key_server_cluster::PlainNodeKeyPair;
+	use parking_lot::Mutex;
+	use hash::keccak;
+	use crypto::publickey::{Random, Generator, Public, Secret, Signature, Error as EthKeyError, sign, verify_public};
+	use ethereum_types::{H256, Address};
+	use blockchain::SigningKeyPair;
+	use key_storage::KeyStorageEncryptionKey;
+	use types::ServerKeyId;
+	use key_server_cluster::{PlainNodeKeyPair, SessionId};
 	use key_server_cluster::io::message::tests::TestIo;
 	use key_server_cluster::message::{Message, ClusterMessage, NodePublicKey, NodePrivateKeySignature};
-	use super::{handshake_with_init_data, accept_handshake, HandshakeResult};
+	use key_server_cluster::randomness_beacon_session::compute_transcript_hash;
+	use super::{handshake_with_init_data, accept_handshake, compute_confirmation_hash, HandshakeResult};
+
+	/// Node key pair that remembers all signatures it has produced.
+	struct RecordingKeyPair {
+		key_pair: PlainNodeKeyPair,
+		signatures: Mutex<Vec<Signature>>,
+	}
+
+	impl SigningKeyPair for RecordingKeyPair {
+		fn public(&self) -> &Public {
+			SigningKeyPair::public(&self.key_pair)
+		}
+
+		fn address(&self) -> Address {
+			self.key_pair.address()
+		}
+
+		fn sign(&self, data: &H256) -> Result<Signature, EthKeyError> {
+			let signature = self.key_pair.sign(data)?;
+			self.signatures.lock().push(signature.clone());
+			Ok(signature)
+		}
+	}
 
 	fn prepare_test_io() -> (H256, TestIo) {
+		prepare_test_io_with_peer_confirmation_plain(*Random.generate().secret().clone())
+	}
+
+	fn prepare_test_io_with_peer_confirmation_plain(peer_confirmation_plain: H256) -> (H256, TestIo) {
 		let mut io = TestIo::new();
 
 		let self_confirmation_plain = *Random.generate().secret().clone();
-		let peer_confirmation_plain = *Random.generate().secret().clone();
 
-		let self_confirmation_signed = sign(io.peer_key_pair().secret(), &self_confirmation_plain).unwrap();
+		let self_confirmation_signed = sign(io.peer_key_pair().secret(), &compute_confirmation_hash(&self_confirmation_plain)).unwrap();
 		let peer_confirmation_signed = sign(io.peer_session_key_pair().secret(), &peer_confirmation_plain).unwrap();
 
 		let peer_public = io.peer_key_pair().public().clone();
@@ -376,4 +417,58 @@ mod tests {
 			shared_key: shared_key,
 		}));
 	}
+
+	#[test]
+	fn handshake_cannot_be_used_to_obtain_key_storage_encryption_key() {
+		// peer asks node to sign the value, which has been used to derive key storage encryption key
+		let peer_confirmation_plain = keccak(b"secretstore:key_storage_encryption_key");
+		let (self_confirmation_plain, io) = prepare_test_io_with_peer_confirmation_plain(peer_confirmation_plain);
+		let self_key_pair = Arc::new(RecordingKeyPair {
+			key_pair: PlainNodeKeyPair::new(io.self_key_pair().clone()),
+			signatures: Mutex::new(Vec::new()),
+		});
+		let self_session_key_pair = io.self_session_key_pair().clone();
+		let encryption_key = KeyStorageEncryptionKey::derive(&PlainNodeKeyPair::new(io.self_key_pair().clone())).unwrap();
+
+		let mut handshake = accept_handshake(io, self_key_pair.clone());
+		handshake.set_self_confirmation_plain(self_confirmation_plain);
+		handshake.set_self_session_key_pair(self_session_key_pair);
+		assert!(handshake.wait().unwrap().1.is_ok());
+
+		// no signature that has been sent to peer could be used to read encrypted key shares
+		let document = ServerKeyId::from_low_u64_be(1);
+		let encrypted = encryption_key.encrypt(&document, b"key share").unwrap();
+		let signatures = self_key_pair.signatures.lock();
+		assert!(!signatures.is_empty());
+		for signature in signatures.iter() {
+			let guessed_secret = Secret::copy_from_slice(keccak(&**signature).as_bytes()).unwrap();
+			let guessed_key = KeyStorageEncryptionKey::new(guessed_secret).unwrap();
+			assert!(guessed_key.decrypt(&document, &encrypted).is_err());
+		}
+	}
+	#[test]
+	fn handshake_cannot_be_used_to_obtain_signed_beacon_transcript() {
+		// peer asks node to sign the hash of forged randomness beacon transcript
+		let participants: BTreeSet<_> = (0..3).map(|_| *Random.generate().public()).collect();
+		let transcript_hash = compute_transcript_hash(&SessionId::from_low_u64_be(1), Random.generate().public(), &participants);
+		let (self_confirmation_plain, io) = prepare_test_io_with_peer_confirmation_plain(transcript_hash);
+		let self_key_pair = Arc::new(RecordingKeyPair {
+			key_pair: PlainNodeKeyPair::new(io.self_key_pair().clone()),
+			signatures: Mutex::new(Vec::new()),
+		});
+		let self_public = io.self_key_pair().public().clone();
+		let self_session_key_pair = io.self_session_key_pair().clone();
+
+		let mut handshake = accept_handshake(io, self_key_pair.clone());
+		handshake.set_self_confirmation_plain(self_confirmation_plain);
+		handshake.set_self_session_key_pair(self_session_key_pair);
+		assert!(handshake.wait().unwrap().1.is_ok());
+
+		// no signature that has been sent to peer is a valid transcript signature
+		let signatures = self_key_pair.signatures.lock();
+		assert!(!signatures.is_empty());
+		for signature in signatures.iter() {
+			assert!(!verify_public(&self_public, signature, &transcript_hash).unwrap());
+		}
+	}
 }
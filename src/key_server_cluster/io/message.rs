@@ -25,7 +25,11 @@ use crypto::publickey::ec_math_utils::CURVE_ORDER;
 use ethereum_types::{H256, U256, BigEndianHash};
 use key_server_cluster::Error;
 use key_server_cluster::message::{Message, ClusterMessage, GenerationMessage, EncryptionMessage, DecryptionMessage,
-	SchnorrSigningMessage, EcdsaSigningMessage, ServersSetChangeMessage, ShareAddMessage, KeyVersionNegotiationMessage};
+	SchnorrSigningMessage, EcdsaSigningMessage, ServersSetChangeMessage, ShareAddMessage, KeyVersionNegotiationMessage,
+	KeyDeletionMessage, KeyImportMessage, ShareRefreshMessage, KeyRotationMessage, KeyExportMessage,
+	ThresholdChangeMessage,
+	RandomnessBeaconMessage, EddsaGenerationMessage, EddsaSigningMessage, BlsGenerationMessage,
+	BlsSigningMessage};
 
 /// Size of serialized header.
 pub const MESSAGE_HEADER_SIZE: usize = 18;
@@ -68,6 +72,9 @@ pub fn serialize_message(message: Message) -> Result<SerializedMessage, Error> {
 		Message::Cluster(ClusterMessage::NodePrivateKeySignature(payload))					=> (2, serde_json::to_vec(&payload)),
 		Message::Cluster(ClusterMessage::KeepAlive(payload))								=> (3, serde_json::to_vec(&payload)),
 		Message::Cluster(ClusterMessage::KeepAliveResponse(payload))						=> (4, serde_json::to_vec(&payload)),
+		Message::Cluster(ClusterMessage::ReliableBroadcastSend(payload))					=> (5, serde_json::to_vec(&payload)),
+		Message::Cluster(ClusterMessage::ReliableBroadcastEcho(payload))					=> (6, serde_json::to_vec(&payload)),
+		Message::Cluster(ClusterMessage::ReliableBroadcastReady(payload))					=> (7, serde_json::to_vec(&payload)),
 
 		Message::Generation(GenerationMessage::InitializeSession(payload))					=> (50, serde_json::to_vec(&payload)),
 		Message::Generation(GenerationMessage::ConfirmInitialization(payload))				=> (51, serde_json::to_vec(&payload)),
@@ -77,6 +84,7 @@ pub fn serialize_message(message: Message) -> Result<SerializedMessage, Error> {
 		Message::Generation(GenerationMessage::JointPublicKey(payload))						=> (55, serde_json::to_vec(&payload)),
 		Message::Generation(GenerationMessage::SessionError(payload))						=> (56, serde_json::to_vec(&payload)),
 		Message::Generation(GenerationMessage::SessionCompleted(payload))					=> (57, serde_json::to_vec(&payload)),
+		Message::Generation(GenerationMessage::KeysComplaintResponse(payload))				=> (58, serde_json::to_vec(&payload)),
 
 		Message::Encryption(EncryptionMessage::InitializeEncryptionSession(payload))		=> (100, serde_json::to_vec(&payload)),
 		Message::Encryption(EncryptionMessage::ConfirmEncryptionInitialization(payload))	=> (101, serde_json::to_vec(&payload)),
@@ -154,6 +162,132 @@ pub fn serialize_message(message: Message) -> Result<SerializedMessage, Error> {
 		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegation(payload))	=> (509, serde_json::to_vec(&payload)),
 		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(payload))
 																							=> (510, serde_json::to_vec(&payload)),
+		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(payload))	=> (511, serde_json::to_vec(&payload)),
+		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(payload))	=> (512, serde_json::to_vec(&payload)),
+		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaResponse(payload))				=> (513, serde_json::to_vec(&payload)),
+		Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaPaillierPublic(payload))			=> (514, serde_json::to_vec(&payload)),
+
+		Message::KeyDeletion(KeyDeletionMessage::InitializeKeyDeletionSession(payload))		=> (550, serde_json::to_vec(&payload)),
+		Message::KeyDeletion(KeyDeletionMessage::ConfirmKeyDeletionInitialization(payload))	=> (551, serde_json::to_vec(&payload)),
+		Message::KeyDeletion(KeyDeletionMessage::CommitKeyDeletion(payload))				=> (552, serde_json::to_vec(&payload)),
+		Message::KeyDeletion(KeyDeletionMessage::ConfirmKeyDeletion(payload))				=> (553, serde_json::to_vec(&payload)),
+		Message::KeyDeletion(KeyDeletionMessage::KeyDeletionSessionError(payload))			=> (554, serde_json::to_vec(&payload)),
+
+		Message::ShareRefresh(ShareRefreshMessage::InitializeShareRefreshSession(payload))		=> (600, serde_json::to_vec(&payload)),
+		Message::ShareRefresh(ShareRefreshMessage::ConfirmShareRefreshInitialization(payload))	=> (601, serde_json::to_vec(&payload)),
+		Message::ShareRefresh(ShareRefreshMessage::ShareRefreshKeysDissemination(payload))		=> (602, serde_json::to_vec(&payload)),
+		Message::ShareRefresh(ShareRefreshMessage::ConfirmShareRefresh(payload))				=> (603, serde_json::to_vec(&payload)),
+		Message::ShareRefresh(ShareRefreshMessage::CommitShareRefresh(payload))					=> (604, serde_json::to_vec(&payload)),
+		Message::ShareRefresh(ShareRefreshMessage::ShareRefreshError(payload))					=> (605, serde_json::to_vec(&payload)),
+
+		Message::EddsaGeneration(EddsaGenerationMessage::InitializeEddsaGenerationSession(payload))
+																							=> (650, serde_json::to_vec(&payload)),
+		Message::EddsaGeneration(EddsaGenerationMessage::ConfirmEddsaGenerationInitialization(payload))
+																							=> (651, serde_json::to_vec(&payload)),
+		Message::EddsaGeneration(EddsaGenerationMessage::EddsaKeysDissemination(payload))
+																							=> (652, serde_json::to_vec(&payload)),
+		Message::EddsaGeneration(EddsaGenerationMessage::EddsaGenerationSessionCompleted(payload))
+																							=> (653, serde_json::to_vec(&payload)),
+		Message::EddsaGeneration(EddsaGenerationMessage::EddsaGenerationSessionError(payload))
+																							=> (654, serde_json::to_vec(&payload)),
+
+		Message::EddsaSigning(EddsaSigningMessage::EddsaSigningConsensusMessage(payload))
+																							=> (700, serde_json::to_vec(&payload)),
+		Message::EddsaSigning(EddsaSigningMessage::EddsaSigningGenerationMessage(payload))
+																							=> (701, serde_json::to_vec(&payload)),
+		Message::EddsaSigning(EddsaSigningMessage::EddsaRequestPartialSignature(payload))
+																							=> (702, serde_json::to_vec(&payload)),
+		Message::EddsaSigning(EddsaSigningMessage::EddsaPartialSignature(payload))
+																							=> (703, serde_json::to_vec(&payload)),
+		Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionError(payload))
+																							=> (704, serde_json::to_vec(&payload)),
+		Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionCompleted(payload))
+																							=> (705, serde_json::to_vec(&payload)),
+		Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegation(payload))
+																							=> (706, serde_json::to_vec(&payload)),
+		Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(payload))
+																							=> (707, serde_json::to_vec(&payload)),
+
+		Message::KeyRotation(KeyRotationMessage::InitializeKeyRotationSession(payload))
+																							=> (750, serde_json::to_vec(&payload)),
+		Message::KeyRotation(KeyRotationMessage::ConfirmKeyRotationInitialization(payload))
+																							=> (751, serde_json::to_vec(&payload)),
+		Message::KeyRotation(KeyRotationMessage::KeyRotationKeysDissemination(payload))
+																							=> (752, serde_json::to_vec(&payload)),
+		Message::KeyRotation(KeyRotationMessage::ConfirmKeyRotation(payload))
+																							=> (753, serde_json::to_vec(&payload)),
+		Message::KeyRotation(KeyRotationMessage::CommitKeyRotation(payload))
+																							=> (754, serde_json::to_vec(&payload)),
+		Message::KeyRotation(KeyRotationMessage::KeyRotationError(payload))
+																							=> (755, serde_json::to_vec(&payload)),
+
+		Message::RandomnessBeacon(RandomnessBeaconMessage::InitializeRandomnessBeaconSession(payload))
+																							=> (800, serde_json::to_vec(&payload)),
+		Message::RandomnessBeacon(RandomnessBeaconMessage::ConfirmRandomnessBeaconInitialization(payload))
+																							=> (801, serde_json::to_vec(&payload)),
+		Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconPointGeneration(payload))
+																							=> (802, serde_json::to_vec(&payload)),
+		Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconTranscriptSignature(payload))
+																							=> (803, serde_json::to_vec(&payload)),
+		Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconError(payload))
+																							=> (804, serde_json::to_vec(&payload)),
+
+		Message::KeyImport(KeyImportMessage::InitializeKeyImportSession(payload))
+																							=> (850, serde_json::to_vec(&payload)),
+		Message::KeyImport(KeyImportMessage::ConfirmKeyImportInitialization(payload))
+																							=> (851, serde_json::to_vec(&payload)),
+		Message::KeyImport(KeyImportMessage::CommitKeyImport(payload))
+																							=> (852, serde_json::to_vec(&payload)),
+		Message::KeyImport(KeyImportMessage::ConfirmKeyImport(payload))
+																							=> (853, serde_json::to_vec(&payload)),
+		Message::KeyImport(KeyImportMessage::KeyImportSessionError(payload))
+																							=> (854, serde_json::to_vec(&payload)),
+
+		Message::KeyExport(KeyExportMessage::InitializeKeyExportSession(payload))
+																							=> (900, serde_json::to_vec(&payload)),
+		Message::KeyExport(KeyExportMessage::KeyExportShare(payload))
+																							=> (901, serde_json::to_vec(&payload)),
+		Message::KeyExport(KeyExportMessage::KeyExportSessionError(payload))
+																							=> (902, serde_json::to_vec(&payload)),
+
+		Message::ThresholdChange(ThresholdChangeMessage::InitializeThresholdChangeSession(payload))
+																							=> (950, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ConfirmThresholdChangeInitialization(payload))
+																							=> (951, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeKeysDissemination(payload))
+																							=> (952, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ConfirmThresholdChange(payload))
+																							=> (953, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::CommitThresholdChange(payload))
+																							=> (954, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeError(payload))
+																							=> (955, serde_json::to_vec(&payload)),
+
+		Message::BlsGeneration(BlsGenerationMessage::InitializeBlsGenerationSession(payload))
+																							=> (1000, serde_json::to_vec(&payload)),
+		Message::BlsGeneration(BlsGenerationMessage::ConfirmBlsGenerationInitialization(payload))
+																							=> (1001, serde_json::to_vec(&payload)),
+		Message::BlsGeneration(BlsGenerationMessage::BlsKeysDissemination(payload))
+																							=> (1002, serde_json::to_vec(&payload)),
+		Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionCompleted(payload))
+																							=> (1003, serde_json::to_vec(&payload)),
+		Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionError(payload))
+																							=> (1004, serde_json::to_vec(&payload)),
+
+		Message::BlsSigning(BlsSigningMessage::BlsSigningConsensusMessage(payload))
+																							=> (1050, serde_json::to_vec(&payload)),
+		Message::BlsSigning(BlsSigningMessage::BlsRequestPartialSignature(payload))
+																							=> (1051, serde_json::to_vec(&payload)),
+		Message::BlsSigning(BlsSigningMessage::BlsPartialSignature(payload))
+																							=> (1052, serde_json::to_vec(&payload)),
+		Message::BlsSigning(BlsSigningMessage::BlsSigningSessionError(payload))
+																							=> (1053, serde_json::to_vec(&payload)),
+		Message::BlsSigning(BlsSigningMessage::BlsSigningSessionCompleted(payload))
+																							=> (1054, serde_json::to_vec(&payload)),
+		Message::BlsSigning(BlsSigningMessage::BlsSigningSessionDelegation(payload))
+																							=> (1055, serde_json::to_vec(&payload)),
+		Message::BlsSigning(BlsSigningMessage::BlsSigningSessionDelegationCompleted(payload))
+																							=> (1056, serde_json::to_vec(&payload)),
 	};
 
 	let payload = payload.map_err(|err| Error::Serde(err.to_string()))?;
@@ -171,6 +305,9 @@ pub fn deserialize_message(header: &MessageHeader, payload: Vec<u8>) -> Result<M
 		2	=> Message::Cluster(ClusterMessage::NodePrivateKeySignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		3	=> Message::Cluster(ClusterMessage::KeepAlive(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		4	=> Message::Cluster(ClusterMessage::KeepAliveResponse(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		5	=> Message::Cluster(ClusterMessage::ReliableBroadcastSend(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		6	=> Message::Cluster(ClusterMessage::ReliableBroadcastEcho(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		7	=> Message::Cluster(ClusterMessage::ReliableBroadcastReady(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 
 		50	=> Message::Generation(GenerationMessage::InitializeSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		51	=> Message::Generation(GenerationMessage::ConfirmInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
@@ -180,6 +317,7 @@ pub fn deserialize_message(header: &MessageHeader, payload: Vec<u8>) -> Result<M
 		55	=> Message::Generation(GenerationMessage::JointPublicKey(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		56	=> Message::Generation(GenerationMessage::SessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		57	=> Message::Generation(GenerationMessage::SessionCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		58	=> Message::Generation(GenerationMessage::KeysComplaintResponse(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 
 		100	=> Message::Encryption(EncryptionMessage::InitializeEncryptionSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		101	=> Message::Encryption(EncryptionMessage::ConfirmEncryptionInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
@@ -234,6 +372,82 @@ pub fn deserialize_message(header: &MessageHeader, payload: Vec<u8>) -> Result<M
 		508	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		509	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegation(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		510	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		511	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		512	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		513	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaResponse(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		514	=> Message::EcdsaSigning(EcdsaSigningMessage::EcdsaMtaPaillierPublic(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		550	=> Message::KeyDeletion(KeyDeletionMessage::InitializeKeyDeletionSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		551	=> Message::KeyDeletion(KeyDeletionMessage::ConfirmKeyDeletionInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		552	=> Message::KeyDeletion(KeyDeletionMessage::CommitKeyDeletion(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		553	=> Message::KeyDeletion(KeyDeletionMessage::ConfirmKeyDeletion(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		554	=> Message::KeyDeletion(KeyDeletionMessage::KeyDeletionSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		600	=> Message::ShareRefresh(ShareRefreshMessage::InitializeShareRefreshSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		601	=> Message::ShareRefresh(ShareRefreshMessage::ConfirmShareRefreshInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		602	=> Message::ShareRefresh(ShareRefreshMessage::ShareRefreshKeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		603	=> Message::ShareRefresh(ShareRefreshMessage::ConfirmShareRefresh(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		604	=> Message::ShareRefresh(ShareRefreshMessage::CommitShareRefresh(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		605	=> Message::ShareRefresh(ShareRefreshMessage::ShareRefreshError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		650	=> Message::EddsaGeneration(EddsaGenerationMessage::InitializeEddsaGenerationSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		651	=> Message::EddsaGeneration(EddsaGenerationMessage::ConfirmEddsaGenerationInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		652	=> Message::EddsaGeneration(EddsaGenerationMessage::EddsaKeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		653	=> Message::EddsaGeneration(EddsaGenerationMessage::EddsaGenerationSessionCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		654	=> Message::EddsaGeneration(EddsaGenerationMessage::EddsaGenerationSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		700	=> Message::EddsaSigning(EddsaSigningMessage::EddsaSigningConsensusMessage(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		701	=> Message::EddsaSigning(EddsaSigningMessage::EddsaSigningGenerationMessage(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		702	=> Message::EddsaSigning(EddsaSigningMessage::EddsaRequestPartialSignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		703	=> Message::EddsaSigning(EddsaSigningMessage::EddsaPartialSignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		704	=> Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		705	=> Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		706	=> Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegation(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		707	=> Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		750	=> Message::KeyRotation(KeyRotationMessage::InitializeKeyRotationSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		751	=> Message::KeyRotation(KeyRotationMessage::ConfirmKeyRotationInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		752	=> Message::KeyRotation(KeyRotationMessage::KeyRotationKeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		753	=> Message::KeyRotation(KeyRotationMessage::ConfirmKeyRotation(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		754	=> Message::KeyRotation(KeyRotationMessage::CommitKeyRotation(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		755	=> Message::KeyRotation(KeyRotationMessage::KeyRotationError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		800	=> Message::RandomnessBeacon(RandomnessBeaconMessage::InitializeRandomnessBeaconSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		801	=> Message::RandomnessBeacon(RandomnessBeaconMessage::ConfirmRandomnessBeaconInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		802	=> Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconPointGeneration(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		803	=> Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconTranscriptSignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		804	=> Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		850	=> Message::KeyImport(KeyImportMessage::InitializeKeyImportSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		851	=> Message::KeyImport(KeyImportMessage::ConfirmKeyImportInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		852	=> Message::KeyImport(KeyImportMessage::CommitKeyImport(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		853	=> Message::KeyImport(KeyImportMessage::ConfirmKeyImport(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		854	=> Message::KeyImport(KeyImportMessage::KeyImportSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		900	=> Message::KeyExport(KeyExportMessage::InitializeKeyExportSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		901	=> Message::KeyExport(KeyExportMessage::KeyExportShare(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		902	=> Message::KeyExport(KeyExportMessage::KeyExportSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		950	=> Message::ThresholdChange(ThresholdChangeMessage::InitializeThresholdChangeSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		951	=> Message::ThresholdChange(ThresholdChangeMessage::ConfirmThresholdChangeInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		952	=> Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeKeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		953	=> Message::ThresholdChange(ThresholdChangeMessage::ConfirmThresholdChange(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		954	=> Message::ThresholdChange(ThresholdChangeMessage::CommitThresholdChange(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		955	=> Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		1000	=> Message::BlsGeneration(BlsGenerationMessage::InitializeBlsGenerationSession(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1001	=> Message::BlsGeneration(BlsGenerationMessage::ConfirmBlsGenerationInitialization(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1002	=> Message::BlsGeneration(BlsGenerationMessage::BlsKeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1003	=> Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1004	=> Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
+		1050	=> Message::BlsSigning(BlsSigningMessage::BlsSigningConsensusMessage(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1051	=> Message::BlsSigning(BlsSigningMessage::BlsRequestPartialSignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1052	=> Message::BlsSigning(BlsSigningMessage::BlsPartialSignature(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1053	=> Message::BlsSigning(BlsSigningMessage::BlsSigningSessionError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1054	=> Message::BlsSigning(BlsSigningMessage::BlsSigningSessionCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1055	=> Message::BlsSigning(BlsSigningMessage::BlsSigningSessionDelegation(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		1056	=> Message::BlsSigning(BlsSigningMessage::BlsSigningSessionDelegationCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 
 		_ => return Err(Error::Serde(format!("unknown message type {}", header.kind))),
 	})
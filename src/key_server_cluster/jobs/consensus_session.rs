@@ -177,6 +177,12 @@ impl<ConsensusExecutor, ConsensusTransport, ComputationExecutor, ComputationTran
 		Ok(&self.consensus_group)
 	}
 
+	/// Forget selected consensus group, so that it is selected again on next `select_consensus_group` call.
+	pub fn reset_consensus_group(&mut self) {
+		debug_assert!(self.meta.self_node_id == self.meta.master_node_id);
+		self.consensus_group.clear();
+	}
+
 	/// Disseminate jobs from master node.
 	pub fn disseminate_jobs(&mut self, executor: ComputationExecutor, transport: ComputationTransport, broadcast_self_response: bool) -> Result<Option<ComputationExecutor::PartialJobResponse>, Error> {
 		let consensus_group = self.select_consensus_group()?.clone();
@@ -206,6 +212,10 @@ impl<ConsensusExecutor, ConsensusTransport, ComputationExecutor, ComputationTran
 
 	/// Process job response on slave node.
 	pub fn on_job_response(&mut self, node: &NodeId, response: ComputationExecutor::PartialJobResponse) -> Result<(), Error> {
+		// response to the job that has been sent before the session restart => ignore it
+		if self.state == ConsensusSessionState::EstablishingConsensus && self.computation_job.is_some() {
+			return Ok(());
+		}
 		if self.state != ConsensusSessionState::WaitingForPartialResults {
 			return Err(Error::InvalidStateForRequest);
 		}
@@ -510,6 +520,9 @@ mod tests {
 		session.on_consensus_message(&NodeId::from_low_u64_be(1), &ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
 			requester: Requester::Signature(sign(Random.generate().secret(), &SessionId::from([1u8; 32])).unwrap()).into(),
 			version: Default::default(),
+			derivation_path: Vec::new(),
+			ecies_ephemeral_public: None,
+			document_key_slot: None,
 		})).unwrap();
 		assert_eq!(session.state(), ConsensusSessionState::ConsensusEstablished);
 		assert_eq!(session.on_job_request(&NodeId::from_low_u64_be(1), 20, SquaredSumJobExecutor, DummyJobTransport::default()).unwrap_err(), Error::InvalidMessage);
@@ -523,6 +536,9 @@ mod tests {
 		session.on_consensus_message(&NodeId::from_low_u64_be(1), &ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
 			requester: Requester::Signature(sign(Random.generate().secret(), &SessionId::from([1u8; 32])).unwrap()).into(),
 			version: Default::default(),
+			derivation_path: Vec::new(),
+			ecies_ephemeral_public: None,
+			document_key_slot: None,
 		})).unwrap();
 		assert_eq!(session.state(), ConsensusSessionState::ConsensusEstablished);
 		session.on_job_request(&NodeId::from_low_u64_be(1), 2, SquaredSumJobExecutor, DummyJobTransport::default()).unwrap();
@@ -553,6 +569,9 @@ mod tests {
 		session.on_consensus_message(&NodeId::from_low_u64_be(1), &ConsensusMessage::InitializeConsensusSession(InitializeConsensusSession {
 			requester: Requester::Signature(sign(Random.generate().secret(), &SessionId::from([1u8; 32])).unwrap()).into(),
 			version: Default::default(),
+			derivation_path: Vec::new(),
+			ecies_ephemeral_public: None,
+			document_key_slot: None,
 		})).unwrap();
 		session.on_session_completed(&NodeId::from_low_u64_be(1)).unwrap();
 		assert_eq!(session.state(), ConsensusSessionState::Finished);
@@ -664,6 +683,21 @@ mod tests {
 		assert_eq!(session.state(), ConsensusSessionState::EstablishingConsensus);
 	}
 
+	#[test]
+	fn consensus_session_ignores_computation_response_received_after_restart() {
+		let mut session = make_master_consensus_session(1, None, None);
+		session.initialize(vec![NodeId::from_low_u64_be(1), NodeId::from_low_u64_be(2), NodeId::from_low_u64_be(3), NodeId::from_low_u64_be(4)].into_iter().collect()).unwrap();
+		session.on_consensus_message(&NodeId::from_low_u64_be(2), &ConsensusMessage::ConfirmConsensusInitialization(ConfirmConsensusInitialization {
+			is_confirmed: true,
+		})).unwrap();
+		session.disseminate_jobs(SquaredSumJobExecutor, DummyJobTransport::default(), false).unwrap();
+		assert_eq!(session.on_node_error(&NodeId::from_low_u64_be(2), Error::AccessDenied), Ok(false));
+		assert_eq!(session.state(), ConsensusSessionState::EstablishingConsensus);
+
+		assert_eq!(session.on_job_response(&NodeId::from_low_u64_be(2), 4), Ok(()));
+		assert_eq!(session.state(), ConsensusSessionState::EstablishingConsensus);
+	}
+
 	#[test]
 	fn consensus_session_fails_if_node_error_received_from_slave_participating_in_computation_and_not_enough_nodes_left() {
 		let mut session = make_master_consensus_session(1, None, None);
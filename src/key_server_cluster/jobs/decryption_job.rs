@@ -41,6 +41,8 @@ pub struct DecryptionJob {
 	is_shadow_decryption: Option<bool>,
 	/// Is broadcast decryption requested.
 	is_broadcast_session: Option<bool>,
+	/// Nodes, participating in decryption.
+	nodes: Option<BTreeSet<NodeId>>,
 }
 
 /// Decryption job partial request.
@@ -65,6 +67,11 @@ pub struct PartialDecryptionResponse {
 	pub shadow_point: Public,
 	/// Decryption shadow coefficient, if requested.
 	pub decrypt_shadow: Option<Vec<u8>>,
+	/// Proof that shadow point has been computed using node public share (when shadow decryption isn't requested).
+	pub decryption_proof: Option<(Secret, Secret)>,
+	/// Proof that shadow point has been computed using node public share && decrypt shadow (when shadow decryption
+	/// is requested).
+	pub shadow_decryption_proof: Option<math::ShadowDecryptionProof>,
 }
 
 impl DecryptionJob {
@@ -79,11 +86,20 @@ impl DecryptionJob {
 			request_id: None,
 			is_shadow_decryption: None,
 			is_broadcast_session: None,
+			nodes: None,
 		})
 	}
 
 	pub fn new_on_master(self_node_id: NodeId, access_key: Secret, requester: Public, key_share: DocumentKeyShare, key_version: H256, is_shadow_decryption: bool, is_broadcast_session: bool) -> Result<Self, Error> {
 		debug_assert!(key_share.common_point.is_some() && key_share.encrypted_point.is_some());
+
+		// partial decryptions are verified using public shares of version holders. Versions that have been migrated
+		// from older storage versions have no public shares until they're refreshed => fall back to unverified combination
+		if is_legacy_key_version(&key_share, &key_version)? {
+			warn!(target: "secretstore", "{}: public shares of key version {} are unknown. Partial decryptions won't be verified until shares are refreshed",
+				self_node_id, key_version);
+		}
+
 		Ok(DecryptionJob {
 			self_node_id: self_node_id,
 			access_key: access_key,
@@ -93,6 +109,7 @@ impl DecryptionJob {
 			request_id: Some(math::generate_random_scalar()?),
 			is_shadow_decryption: Some(is_shadow_decryption),
 			is_broadcast_session: Some(is_broadcast_session),
+			nodes: None,
 		})
 	}
 
@@ -103,6 +120,39 @@ impl DecryptionJob {
 	pub fn set_request_id(&mut self, request_id: Secret) {
 		self.request_id = Some(request_id);
 	}
+
+	pub fn set_nodes(&mut self, nodes: BTreeSet<NodeId>) {
+		self.nodes = Some(nodes);
+	}
+
+	/// Check that shadow point of given node is proved to be computed using node public share. Errors are only returned
+	/// when the check can't be performed because of this node' state.
+	fn is_shadow_point_proved(&self, node: &NodeId, partial_response: &PartialDecryptionResponse) -> Result<bool, Error> {
+		let key_version = self.key_share.version(&self.key_version)?;
+		let node_public_share = key_version.public_shares.get(node).ok_or(Error::ServerKeyPublicSharesAreUnknown)?;
+		let node_number = key_version.id_numbers.get(node).ok_or(Error::InvalidNodeForRequest)?;
+		let other_id_numbers = self.nodes.as_ref()
+			.expect("is_shadow_point_proved is only called on master nodes; nodes are filled before job is started on master nodes; qed")
+			.iter()
+			.filter(|n| *n != node)
+			.map(|n| key_version.id_numbers.get(n).ok_or(Error::InvalidNodeForRequest))
+			.collect::<Result<Vec<_>, _>>()?;
+		let common_point = self.key_share.common_point.as_ref().expect("DecryptionJob is only created when common_point is known; qed");
+		let node_shadow_public = math::compute_node_shadow_public(&self.access_key, node_public_share, node_number, other_id_numbers.into_iter())?;
+		match (partial_response.decryption_proof.as_ref(), partial_response.shadow_decryption_proof.as_ref()) {
+			(Some(proof), None) if self.is_shadow_decryption == Some(false) =>
+				math::verify_dleq_proof(&node_shadow_public, common_point, &partial_response.shadow_point, proof),
+			(None, Some(proof)) if self.is_shadow_decryption == Some(true) =>
+				math::verify_shadow_decryption_proof(&node_shadow_public, common_point, &partial_response.shadow_point, proof),
+			_ => Ok(false),
+		}
+	}
+}
+
+/// Check if public shares of key version holders are unknown (version has been migrated from older storage versions).
+fn is_legacy_key_version(key_share: &DocumentKeyShare, key_version: &H256) -> Result<bool, Error> {
+	let key_version = key_share.version(key_version)?;
+	Ok(key_version.id_numbers.keys().any(|n| !key_version.public_shares.contains_key(n)))
 }
 
 impl JobExecutor for DecryptionJob {
@@ -143,6 +193,10 @@ impl JobExecutor for DecryptionJob {
 		let node_shadow = math::compute_node_shadow(&key_version.secret_share, &self_id_number, other_id_numbers)?;
 		let decrypt_shadow = if partial_request.is_shadow_decryption { Some(math::generate_random_scalar()?) } else { None };
 		let common_point = self.key_share.common_point.as_ref().expect("DecryptionJob is only created when common_point is known; qed");
+		let (decryption_proof, shadow_decryption_proof) = match decrypt_shadow {
+			None => (Some(math::compute_dleq_proof(&math::compute_secret_mul(&node_shadow, &self.access_key)?, &common_point)?), None),
+			Some(ref decrypt_shadow) => (None, Some(math::compute_shadow_decryption_proof(&self.access_key, &common_point, &node_shadow, decrypt_shadow)?)),
+		};
 		let (shadow_point, decrypt_shadow) = math::compute_node_shadow_point(&self.access_key, &common_point, &node_shadow, decrypt_shadow)?;
 
 		Ok(JobPartialRequestAction::Respond(PartialDecryptionResponse {
@@ -152,16 +206,23 @@ impl JobExecutor for DecryptionJob {
 				None => None,
 				Some(decrypt_shadow) => Some(encrypt(&self.requester, &DEFAULT_MAC, decrypt_shadow.as_bytes())?),
 			},
+			decryption_proof: decryption_proof,
+			shadow_decryption_proof: shadow_decryption_proof,
 		}))
 	}
 
-	fn check_partial_response(&mut self, _sender: &NodeId, partial_response: &PartialDecryptionResponse) -> Result<JobPartialResponseAction, Error> {
+	fn check_partial_response(&mut self, sender: &NodeId, partial_response: &PartialDecryptionResponse) -> Result<JobPartialResponseAction, Error> {
 		if Some(&partial_response.request_id) != self.request_id.as_ref() {
 			return Ok(JobPartialResponseAction::Ignore);
 		}
 		if self.is_shadow_decryption != Some(partial_response.decrypt_shadow.is_some()) {
 			return Ok(JobPartialResponseAction::Reject);
 		}
+
+		if !is_legacy_key_version(&self.key_share, &self.key_version)? && !self.is_shadow_point_proved(sender, partial_response)? {
+			return Err(Error::InvalidPartialResponse(sender.clone()));
+		}
+
 		Ok(JobPartialResponseAction::Accept)
 	}
 
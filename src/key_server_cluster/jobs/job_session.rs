@@ -292,14 +292,17 @@ impl<Executor, Transport> JobSession<Executor, Transport> where Executor: JobExe
 
 		let active_data = self.data.active_data.as_mut()
 			.expect("on_partial_response is only called on master nodes; on master nodes active_data is filled during initialization; qed");
-		if !active_data.requests.remove(node) {
+		if !active_data.requests.contains(node) {
 			return Err(Error::InvalidNodeForRequest);
 		}
 
+		// if response check fails, node is still waited for => the error must be reported using on_node_error
 		match self.executor.check_partial_response(node, &response)? {
+			// response to some previous request => still waiting for response to the current request
 			JobPartialResponseAction::Ignore => Ok(()),
 			JobPartialResponseAction::Reject => {
 				// direct reject is always considered as fatal
+				active_data.requests.remove(node);
 				active_data.rejects.insert(node.clone(), true);
 				if active_data.requests.len() + active_data.responses.len() >= self.meta.threshold + 1 {
 					return Ok(());
@@ -309,6 +312,7 @@ impl<Executor, Transport> JobSession<Executor, Transport> where Executor: JobExe
 				Err(consensus_unreachable(&active_data.rejects))
 			},
 			JobPartialResponseAction::Accept => {
+				active_data.requests.remove(node);
 				active_data.responses.insert(node.clone(), response);
 				if active_data.responses.len() < self.meta.threshold + 1 {
 					return Ok(());
@@ -405,7 +409,7 @@ pub mod tests {
 
 		fn prepare_partial_request(&self, _n: &NodeId, _nodes: &BTreeSet<NodeId>) -> Result<u32, Error> { Ok(2) }
 		fn process_partial_request(&mut self, r: u32) -> Result<JobPartialRequestAction<u32>, Error> { if r <= 10 { Ok(JobPartialRequestAction::Respond(r * r)) } else { Err(Error::InvalidMessage) } }
-		fn check_partial_response(&mut self, _s: &NodeId, r: &u32) -> Result<JobPartialResponseAction, Error> { if r % 2 == 0 { Ok(JobPartialResponseAction::Accept) } else { Ok(JobPartialResponseAction::Reject) } }
+		fn check_partial_response(&mut self, s: &NodeId, r: &u32) -> Result<JobPartialResponseAction, Error> { if *r == 0 { Err(Error::InvalidPartialResponse(s.clone())) } else if r % 2 == 0 { Ok(JobPartialResponseAction::Accept) } else { Ok(JobPartialResponseAction::Reject) } }
 		fn compute_response(&self, r: &BTreeMap<NodeId, u32>) -> Result<u32, Error> { Ok(r.values().fold(0, |v1, v2| v1 + v2)) }
 	}
 
@@ -560,6 +564,17 @@ pub mod tests {
 		assert_eq!(job.state(), JobSessionState::Finished);
 	}
 
+	#[test]
+	fn job_response_check_failure_is_reported_as_node_error() {
+		let mut job = JobSession::new(make_master_session_meta(1), SquaredSumJobExecutor, DummyJobTransport::default());
+		job.initialize(vec![Public::from_low_u64_be(1), Public::from_low_u64_be(2)].into_iter().collect(), None, false).unwrap();
+		let error = job.on_partial_response(&NodeId::from_low_u64_be(2), 0).unwrap_err();
+		assert_eq!(error, Error::InvalidPartialResponse(NodeId::from_low_u64_be(2)));
+		assert_eq!(job.state(), JobSessionState::Active);
+		assert_eq!(job.on_node_error(&NodeId::from_low_u64_be(2), error).unwrap_err(), Error::ConsensusUnreachable);
+		assert_eq!(job.state(), JobSessionState::Failed);
+	}
+
 	#[test]
 	fn job_node_error_ignored_when_slave_disconnects_from_slave() {
 		let mut job = JobSession::new(make_slave_session_meta(1), SquaredSumJobExecutor, DummyJobTransport::default());
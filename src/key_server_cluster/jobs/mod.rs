@@ -20,6 +20,8 @@ pub mod dummy_job;
 pub mod job_session;
 pub mod key_access_job;
 pub mod servers_set_change_access_job;
+pub mod signing_job_bls;
 pub mod signing_job_ecdsa;
+pub mod signing_job_eddsa;
 pub mod signing_job_schnorr;
 pub mod unknown_sessions_job;
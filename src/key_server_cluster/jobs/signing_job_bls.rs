@@ -0,0 +1,162 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, BTreeMap};
+use bytes::Bytes;
+use crypto::publickey::Secret;
+use ethereum_types::H256;
+use key_server_cluster::{Error, NodeId, DocumentKeyShare};
+use key_server_cluster::math_bls;
+use key_server_cluster::jobs::job_session::{JobPartialRequestAction, JobPartialResponseAction, JobExecutor};
+
+/// BLS signing job.
+pub struct BlsSigningJob {
+	/// This node id.
+	self_node_id: NodeId,
+	/// Key share.
+	key_share: DocumentKeyShare,
+	/// Key version.
+	key_version: H256,
+	/// Request id.
+	request_id: Option<Secret>,
+	/// Message hash.
+	message_hash: Option<H256>,
+}
+
+/// BLS signing job partial request.
+pub struct BlsPartialSigningRequest {
+	/// Request id.
+	pub id: Secret,
+	/// Message hash.
+	pub message_hash: H256,
+	/// Id of other nodes, participating in signing.
+	pub other_nodes_ids: BTreeSet<NodeId>,
+}
+
+/// BLS signing job partial response.
+#[derive(Clone)]
+pub struct BlsPartialSigningResponse {
+	/// Request id.
+	pub request_id: Secret,
+	/// Partial signature.
+	pub partial_signature: Bytes,
+}
+
+impl BlsSigningJob {
+	pub fn new_on_slave(self_node_id: NodeId, key_share: DocumentKeyShare, key_version: H256) -> Result<Self, Error> {
+		Ok(BlsSigningJob {
+			self_node_id: self_node_id,
+			key_share: key_share,
+			key_version: key_version,
+			request_id: None,
+			message_hash: None,
+		})
+	}
+
+	pub fn new_on_master(self_node_id: NodeId, key_share: DocumentKeyShare, key_version: H256, message_hash: H256) -> Result<Self, Error> {
+		// partial signatures are verified using public shares of version holders
+		{
+			let key_version = key_share.version(&key_version)?;
+			if key_version.id_numbers.keys().any(|n| !key_version.public_shares.contains_key(n)) {
+				return Err(Error::ServerKeyPublicSharesAreUnknown);
+			}
+		}
+
+		Ok(BlsSigningJob {
+			self_node_id: self_node_id,
+			key_share: key_share,
+			key_version: key_version,
+			request_id: Some(math_bls::generate_random_scalar()?),
+			message_hash: Some(message_hash),
+		})
+	}
+}
+
+impl JobExecutor for BlsSigningJob {
+	type PartialJobRequest = BlsPartialSigningRequest;
+	type PartialJobResponse = BlsPartialSigningResponse;
+	type JobResponse = Bytes;
+
+	fn prepare_partial_request(&self, node: &NodeId, nodes: &BTreeSet<NodeId>) -> Result<BlsPartialSigningRequest, Error> {
+		debug_assert!(nodes.len() == self.key_share.threshold + 1);
+
+		let request_id = self.request_id.as_ref()
+			.expect("prepare_partial_request is only called on master nodes; request_id is filed in constructor on master nodes; qed");
+		let message_hash = self.message_hash.as_ref()
+			.expect("compute_response is only called on master nodes; message_hash is filed in constructor on master nodes; qed");
+		let mut other_nodes_ids = nodes.clone();
+		other_nodes_ids.remove(node);
+
+		Ok(BlsPartialSigningRequest {
+			id: request_id.clone(),
+			message_hash: message_hash.clone(),
+			other_nodes_ids: other_nodes_ids,
+		})
+	}
+
+	fn process_partial_request(&mut self, partial_request: BlsPartialSigningRequest) -> Result<JobPartialRequestAction<BlsPartialSigningResponse>, Error> {
+		let key_version = self.key_share.version(&self.key_version)?;
+		if partial_request.other_nodes_ids.len() != self.key_share.threshold
+			|| partial_request.other_nodes_ids.contains(&self.self_node_id)
+			|| partial_request.other_nodes_ids.iter().any(|n| !key_version.id_numbers.contains_key(n)) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// Lagrange coefficient is applied by master => the share doesn't depend on other signers
+		Ok(JobPartialRequestAction::Respond(BlsPartialSigningResponse {
+			request_id: partial_request.id,
+			partial_signature: math_bls::compute_signature_share(&key_version.secret_share, &partial_request.message_hash)?,
+		}))
+	}
+
+	fn check_partial_response(&mut self, sender: &NodeId, partial_response: &BlsPartialSigningResponse) -> Result<JobPartialResponseAction, Error> {
+		if Some(&partial_response.request_id) != self.request_id.as_ref() {
+			return Ok(JobPartialResponseAction::Ignore);
+		}
+
+		let message_hash = self.message_hash.as_ref()
+			.expect("check_partial_response is only called on master nodes; message_hash is filed in constructor on master nodes; qed");
+		let key_version = self.key_share.version(&self.key_version)?;
+		let public_share = key_version.public_shares.get(sender).ok_or(Error::ServerKeyPublicSharesAreUnknown)?;
+		let public_share = math_bls::from_key_share_public(public_share)?;
+		match math_bls::verify_signature_share(&public_share, &partial_response.partial_signature, message_hash) {
+			Ok(true) => Ok(JobPartialResponseAction::Accept),
+			Ok(false) | Err(_) => Err(Error::InvalidPartialResponse(sender.clone())),
+		}
+	}
+
+	fn compute_response(&self, partial_responses: &BTreeMap<NodeId, BlsPartialSigningResponse>) -> Result<Bytes, Error> {
+		let message_hash = self.message_hash.as_ref()
+			.expect("compute_response is only called on master nodes; message_hash is filed in constructor on master nodes; qed");
+		let key_version = self.key_share.version(&self.key_version)?;
+
+		// combine partial signatures using Lagrange interpolation at id numbers of responded nodes
+		let signature_shares = partial_responses.iter()
+			.map(|(node, response)| key_version.id_numbers.get(node)
+				.map(|id_number| (id_number, &response.partial_signature))
+				.ok_or(Error::InvalidMessage))
+			.collect::<Result<Vec<_>, _>>()?;
+		let signature = math_bls::compute_signature(&signature_shares)?;
+
+		// partial signatures are checked individually => combined signature is only verified to be safe
+		let public = math_bls::from_key_share_public(&self.key_share.public)?;
+		if !math_bls::verify_signature(&public, &signature, message_hash)? {
+			return Err(Error::InvalidMessage);
+		}
+
+		Ok(signature)
+	}
+}
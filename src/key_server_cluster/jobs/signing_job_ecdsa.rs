@@ -15,7 +15,7 @@
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{BTreeSet, BTreeMap};
-use crypto::publickey::{Public, Secret, Signature};
+use crypto::publickey::{Public, Secret, Signature, verify_public};
 use ethereum_types::H256;
 use key_server_cluster::{Error, NodeId, DocumentKeyShare};
 use key_server_cluster::math;
@@ -27,10 +27,8 @@ pub struct EcdsaSigningJob {
 	key_share: DocumentKeyShare,
 	/// Key version.
 	key_version: H256,
-	/// Share of inv(nonce).
-	inv_nonce_share: Secret,
-	/// Nonce public.
-	nonce_public: Public,
+	/// Nonce data.
+	nonce: EcdsaSigningNonce,
 	/// Request id.
 	request_id: Option<Secret>,
 	/// ECDSA reversed-nonce coefficient
@@ -39,6 +37,26 @@ pub struct EcdsaSigningJob {
 	message_hash: Option<H256>,
 }
 
+/// Nonce data, used to compute partial signature.
+pub enum EcdsaSigningNonce {
+	/// Nonce-inversion scheme.
+	NonceInversion {
+		/// Nonce public.
+		nonce_public: Public,
+		/// Share of inv(nonce).
+		inv_nonce_share: Secret,
+	},
+	/// Multiplicative-to-additive scheme.
+	MultiplicativeToAdditive {
+		/// Sum of gamma publics of all signing nodes. Multiplied by inversed-nonce coefficient, it gives nonce public.
+		gamma_public: Public,
+		/// Additive share of inv(nonce).
+		inv_nonce_share: Secret,
+		/// Additive share of inv(nonce) * secret.
+		inv_nonce_mul_secret_share: Secret,
+	},
+}
+
 /// Signing job partial request.
 pub struct EcdsaPartialSigningRequest {
 	/// Request id.
@@ -59,24 +77,22 @@ pub struct EcdsaPartialSigningResponse {
 }
 
 impl EcdsaSigningJob {
-	pub fn new_on_slave(key_share: DocumentKeyShare, key_version: H256, nonce_public: Public, inv_nonce_share: Secret) -> Result<Self, Error> {
+	pub fn new_on_slave(key_share: DocumentKeyShare, key_version: H256, nonce: EcdsaSigningNonce) -> Result<Self, Error> {
 		Ok(EcdsaSigningJob {
 			key_share: key_share,
 			key_version: key_version,
-			nonce_public: nonce_public,
-			inv_nonce_share: inv_nonce_share,
+			nonce: nonce,
 			request_id: None,
 			inversed_nonce_coeff: None,
 			message_hash: None,
 		})
 	}
 
-	pub fn new_on_master(key_share: DocumentKeyShare, key_version: H256, nonce_public: Public, inv_nonce_share: Secret, inversed_nonce_coeff: Secret, message_hash: H256) -> Result<Self, Error> {
+	pub fn new_on_master(key_share: DocumentKeyShare, key_version: H256, nonce: EcdsaSigningNonce, inversed_nonce_coeff: Secret, message_hash: H256) -> Result<Self, Error> {
 		Ok(EcdsaSigningJob {
 			key_share: key_share,
 			key_version: key_version,
-			nonce_public: nonce_public,
-			inv_nonce_share: inv_nonce_share,
+			nonce: nonce,
 			request_id: Some(math::generate_random_scalar()?),
 			inversed_nonce_coeff: Some(inversed_nonce_coeff),
 			message_hash: Some(message_hash),
@@ -90,7 +106,10 @@ impl JobExecutor for EcdsaSigningJob {
 	type JobResponse = Signature;
 
 	fn prepare_partial_request(&self, _node: &NodeId, nodes: &BTreeSet<NodeId>) -> Result<EcdsaPartialSigningRequest, Error> {
-		debug_assert!(nodes.len() == self.key_share.threshold * 2 + 1);
+		debug_assert!(match self.nonce {
+			EcdsaSigningNonce::NonceInversion { .. } => nodes.len() == self.key_share.threshold * 2 + 1,
+			EcdsaSigningNonce::MultiplicativeToAdditive { .. } => nodes.len() == self.key_share.threshold + 1,
+		});
 
 		let request_id = self.request_id.as_ref()
 			.expect("prepare_partial_request is only called on master nodes; request_id is filed in constructor on master nodes; qed");
@@ -107,16 +126,30 @@ impl JobExecutor for EcdsaSigningJob {
 	}
 
 	fn process_partial_request(&mut self, partial_request: EcdsaPartialSigningRequest) -> Result<JobPartialRequestAction<EcdsaPartialSigningResponse>, Error> {
-		let inversed_nonce_coeff_mul_nonce = math::compute_secret_mul(&partial_request.inversed_nonce_coeff, &self.inv_nonce_share)?;
-		let key_version = self.key_share.version(&self.key_version)?;
-		let signature_r = math::compute_ecdsa_r(&self.nonce_public)?;
-		let inv_nonce_mul_secret = math::compute_secret_mul(&inversed_nonce_coeff_mul_nonce, &key_version.secret_share)?;
-		let partial_signature_s = math::compute_ecdsa_s_share(
-			&inversed_nonce_coeff_mul_nonce,
-			&inv_nonce_mul_secret,
-			&signature_r,
-			&math::to_scalar(partial_request.message_hash)?,
-		)?;
+		let partial_signature_s = match self.nonce {
+			EcdsaSigningNonce::NonceInversion { ref nonce_public, ref inv_nonce_share } => {
+				let inversed_nonce_coeff_mul_nonce = math::compute_secret_mul(&partial_request.inversed_nonce_coeff, inv_nonce_share)?;
+				let key_version = self.key_share.version(&self.key_version)?;
+				let signature_r = math::compute_ecdsa_r(nonce_public)?;
+				let inv_nonce_mul_secret = math::compute_secret_mul(&inversed_nonce_coeff_mul_nonce, &key_version.secret_share)?;
+				math::compute_ecdsa_s_share(
+					&inversed_nonce_coeff_mul_nonce,
+					&inv_nonce_mul_secret,
+					&signature_r,
+					&math::to_scalar(partial_request.message_hash)?,
+				)?
+			},
+			EcdsaSigningNonce::MultiplicativeToAdditive { ref gamma_public, ref inv_nonce_share, ref inv_nonce_mul_secret_share } => {
+				let nonce_public = math::compute_ecdsa_mta_nonce_public(gamma_public, &partial_request.inversed_nonce_coeff)?;
+				let signature_r = math::compute_ecdsa_r(&nonce_public)?;
+				math::compute_ecdsa_s_share(
+					inv_nonce_share,
+					inv_nonce_mul_secret_share,
+					&signature_r,
+					&math::to_scalar(partial_request.message_hash)?,
+				)?
+			},
+		};
 
 		Ok(JobPartialRequestAction::Respond(EcdsaPartialSigningResponse {
 			request_id: partial_request.id,
@@ -139,13 +172,32 @@ impl JobExecutor for EcdsaSigningJob {
 			return Err(Error::InvalidMessage);
 		}
 
-		let id_numbers: Vec<_> = partial_responses.keys().map(|n| key_version.id_numbers[n].clone()).collect();
 		let signature_s_shares: Vec<_> = partial_responses.values().map(|r| r.partial_signature_s.clone()).collect();
-		let signature_s = math::compute_ecdsa_s(self.key_share.threshold, &signature_s_shares, &id_numbers)?;
-		let signature_r = math::compute_ecdsa_r(&self.nonce_public)?;
-
-		let signature = math::serialize_ecdsa_signature(&self.nonce_public, signature_r, signature_s);
-
-		Ok(signature)
+		match self.nonce {
+			EcdsaSigningNonce::NonceInversion { ref nonce_public, .. } => {
+				let id_numbers: Vec<_> = partial_responses.keys().map(|n| key_version.id_numbers[n].clone()).collect();
+				let signature_s = math::compute_ecdsa_s(self.key_share.threshold, &signature_s_shares, &id_numbers)?;
+				let signature_r = math::compute_ecdsa_r(nonce_public)?;
+
+				Ok(math::serialize_ecdsa_signature(nonce_public, signature_r, signature_s))
+			},
+			EcdsaSigningNonce::MultiplicativeToAdditive { ref gamma_public, .. } => {
+				let inversed_nonce_coeff = self.inversed_nonce_coeff.as_ref()
+					.expect("compute_response is only called on master nodes; inversed_nonce_coeff is filed in constructor on master nodes; qed");
+				let message_hash = self.message_hash.as_ref()
+					.expect("compute_response is only called on master nodes; message_hash is filed in constructor on master nodes; qed");
+				let nonce_public = math::compute_ecdsa_mta_nonce_public(gamma_public, inversed_nonce_coeff)?;
+				let signature_s = math::compute_secret_sum(signature_s_shares.iter())?;
+				let signature_r = math::compute_ecdsa_r(&nonce_public)?;
+				let signature = math::serialize_ecdsa_signature(&nonce_public, signature_r, signature_s);
+
+				// shares are converted without any proofs => check that nodes have computed valid signature
+				if !verify_public(&self.key_share.public, &signature, message_hash)? {
+					return Err(Error::InvalidMessage);
+				}
+
+				Ok(signature)
+			},
+		}
 	}
 }
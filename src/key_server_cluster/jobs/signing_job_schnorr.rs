@@ -21,7 +21,16 @@ use key_server_cluster::{Error, NodeId, DocumentKeyShare};
 use key_server_cluster::math;
 use key_server_cluster::jobs::job_session::{JobPartialRequestAction, JobPartialResponseAction, JobExecutor};
 
-/// Signing job.
+/// Schnorr signature scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SchnorrSigningScheme {
+	/// Secret Store Schnorr signature: (keccak(message_hash || x(R)), k - c * secret).
+	Legacy,
+	/// BIP-340 Schnorr signature: (x(R), k + e * secret), verifiable using x-only public key.
+	Bip340,
+}
+
+/// Signing job. Signs one or several (batch) messages, using separate session key for every message.
 pub struct SchnorrSigningJob {
 	/// This node id.
 	self_node_id: NodeId,
@@ -29,22 +38,26 @@ pub struct SchnorrSigningJob {
 	key_share: DocumentKeyShare,
 	/// Key version.
 	key_version: H256,
-	/// Session public key.
-	session_public: Public,
-	/// Session secret coefficient.
-	session_secret_coeff: Secret,
+	/// Session keys (public key and secret coefficient) for every message.
+	session_keys: Vec<(Public, Secret)>,
+	/// Public shares of session keys (one-time public shares of every node) for every message.
+	session_public_shares: Option<Vec<BTreeMap<NodeId, Public>>>,
 	/// Request id.
 	request_id: Option<Secret>,
-	/// Message hash.
-	message_hash: Option<H256>,
+	/// Signature scheme.
+	scheme: Option<SchnorrSigningScheme>,
+	/// Hashes of messages to sign.
+	message_hashes: Option<Vec<H256>>,
 }
 
 /// Signing job partial request.
 pub struct SchnorrPartialSigningRequest {
 	/// Request id.
 	pub id: Secret,
-	/// Message hash.
-	pub message_hash: H256,
+	/// Signature scheme.
+	pub scheme: SchnorrSigningScheme,
+	/// Hashes of messages to sign.
+	pub message_hashes: Vec<H256>,
 	/// Id of other nodes, participating in signing.
 	pub other_nodes_ids: BTreeSet<NodeId>,
 }
@@ -54,54 +67,134 @@ pub struct SchnorrPartialSigningRequest {
 pub struct SchnorrPartialSigningResponse {
 	/// Request id.
 	pub request_id: Secret,
-	/// Partial signature.
-	pub partial_signature: Secret,
+	/// Partial signatures of every message.
+	pub partial_signatures: Vec<Secret>,
+}
+
+impl Default for SchnorrSigningScheme {
+	fn default() -> Self {
+		SchnorrSigningScheme::Legacy
+	}
 }
 
 impl SchnorrSigningJob {
-	pub fn new_on_slave(self_node_id: NodeId, key_share: DocumentKeyShare, key_version: H256, session_public: Public, session_secret_coeff: Secret) -> Result<Self, Error> {
+	pub fn new_on_slave(self_node_id: NodeId, key_share: DocumentKeyShare, key_version: H256, session_keys: Vec<(Public, Secret)>) -> Result<Self, Error> {
 		Ok(SchnorrSigningJob {
 			self_node_id: self_node_id,
 			key_share: key_share,
 			key_version: key_version,
-			session_public: session_public,
-			session_secret_coeff: session_secret_coeff,
+			session_keys: session_keys,
+			session_public_shares: None,
 			request_id: None,
-			message_hash: None,
+			scheme: None,
+			message_hashes: None,
 		})
 	}
 
-	pub fn new_on_master(self_node_id: NodeId, key_share: DocumentKeyShare, key_version: H256, session_public: Public, session_secret_coeff: Secret, message_hash: H256) -> Result<Self, Error> {
+	pub fn new_on_master(self_node_id: NodeId, key_share: DocumentKeyShare, key_version: H256, session_keys: Vec<(Public, Secret)>,
+		session_public_shares: Vec<BTreeMap<NodeId, Public>>, scheme: SchnorrSigningScheme, message_hashes: Vec<H256>) -> Result<Self, Error> {
+		if session_keys.len() != message_hashes.len() || session_public_shares.len() != message_hashes.len() {
+			return Err(Error::InvalidMessage);
+		}
+
+		// partial signatures are verified using public shares of version holders. Versions that have been migrated
+		// from older storage versions have no public shares until they're refreshed => fall back to unverified combination
+		if is_legacy_key_version(&key_share, &key_version)? {
+			warn!(target: "secretstore", "{}: public shares of key version {} are unknown. Partial signatures won't be verified until shares are refreshed",
+				self_node_id, key_version);
+		}
+
 		Ok(SchnorrSigningJob {
 			self_node_id: self_node_id,
 			key_share: key_share,
 			key_version: key_version,
-			session_public: session_public,
-			session_secret_coeff: session_secret_coeff,
+			session_keys: session_keys,
+			session_public_shares: Some(session_public_shares),
 			request_id: Some(math::generate_random_scalar()?),
-			message_hash: Some(message_hash),
+			scheme: Some(scheme),
+			message_hashes: Some(message_hashes),
 		})
 	}
+
+	/// Check that partial signature of given message, computed by given node, is valid. Errors are only returned
+	/// when the check can't be performed because of this node' state.
+	fn is_partial_signature_valid(&self, node: &NodeId, index: usize, partial_signature: &Secret) -> Result<bool, Error> {
+		let scheme = self.scheme
+			.expect("is_partial_signature_valid is only called on master nodes; scheme is filed in constructor on master nodes; qed");
+		let message_hash = &self.message_hashes.as_ref()
+			.expect("is_partial_signature_valid is only called on master nodes; message_hashes are filed in constructor on master nodes; qed")[index];
+		let session_public_shares = &self.session_public_shares.as_ref()
+			.expect("is_partial_signature_valid is only called on master nodes; session_public_shares are filed in constructor on master nodes; qed")[index];
+		let session_public = &self.session_keys[index].0;
+
+		let key_version = self.key_share.version(&self.key_version)?;
+		let public_share = key_version.public_shares.get(node).ok_or(Error::ServerKeyPublicSharesAreUnknown)?;
+		let one_time_public_share = session_public_shares.get(node).ok_or(Error::InvalidNodeForRequest)?;
+		let node_number = key_version.id_numbers.get(node).ok_or(Error::InvalidNodeForRequest)?;
+		// session keys are generated by the same nodes that are computing signature
+		let other_id_numbers = session_public_shares.keys()
+			.filter(|n| *n != node)
+			.map(|n| key_version.id_numbers.get(n).ok_or(Error::InvalidNodeForRequest))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		match scheme {
+			SchnorrSigningScheme::Legacy => {
+				let combined_hash = math::combine_message_hash_with_public(message_hash, session_public)?;
+				math::check_schnorr_signature_share(
+					self.key_share.threshold,
+					&combined_hash,
+					partial_signature,
+					public_share,
+					one_time_public_share,
+					node_number,
+					other_id_numbers.into_iter()
+				)
+			},
+			SchnorrSigningScheme::Bip340 => {
+				let challenge = math::compute_bip340_challenge(session_public, &self.key_share.public, message_hash)?;
+				math::check_bip340_signature_share(
+					self.key_share.threshold,
+					&challenge,
+					partial_signature,
+					session_public,
+					&self.key_share.public,
+					public_share,
+					one_time_public_share,
+					node_number,
+					other_id_numbers.into_iter()
+				)
+			},
+		}
+	}
+}
+
+/// Check if public shares of key version holders are unknown (version has been migrated from older storage versions).
+fn is_legacy_key_version(key_share: &DocumentKeyShare, key_version: &H256) -> Result<bool, Error> {
+	let key_version = key_share.version(key_version)?;
+	Ok(key_version.id_numbers.keys().any(|n| !key_version.public_shares.contains_key(n)))
 }
 
 impl JobExecutor for SchnorrSigningJob {
 	type PartialJobRequest = SchnorrPartialSigningRequest;
 	type PartialJobResponse = SchnorrPartialSigningResponse;
-	type JobResponse = (Secret, Secret);
+	type JobResponse = Vec<(Secret, Secret)>;
 
 	fn prepare_partial_request(&self, node: &NodeId, nodes: &BTreeSet<NodeId>) -> Result<SchnorrPartialSigningRequest, Error> {
 		debug_assert!(nodes.len() == self.key_share.threshold + 1);
 
 		let request_id = self.request_id.as_ref()
 			.expect("prepare_partial_request is only called on master nodes; request_id is filed in constructor on master nodes; qed");
-		let message_hash = self.message_hash.as_ref()
-			.expect("compute_response is only called on master nodes; message_hash is filed in constructor on master nodes; qed");
+		let scheme = self.scheme
+			.expect("prepare_partial_request is only called on master nodes; scheme is filed in constructor on master nodes; qed");
+		let message_hashes = self.message_hashes.as_ref()
+			.expect("compute_response is only called on master nodes; message_hashes are filed in constructor on master nodes; qed");
 		let mut other_nodes_ids = nodes.clone();
 		other_nodes_ids.remove(node);
 
 		Ok(SchnorrPartialSigningRequest {
 			id: request_id.clone(),
-			message_hash: message_hash.clone(),
+			scheme: scheme,
+			message_hashes: message_hashes.clone(),
 			other_nodes_ids: other_nodes_ids,
 		})
 	}
@@ -110,42 +203,96 @@ impl JobExecutor for SchnorrSigningJob {
 		let key_version = self.key_share.version(&self.key_version)?;
 		if partial_request.other_nodes_ids.len() != self.key_share.threshold
 			|| partial_request.other_nodes_ids.contains(&self.self_node_id)
-			|| partial_request.other_nodes_ids.iter().any(|n| !key_version.id_numbers.contains_key(n)) {
+			|| partial_request.other_nodes_ids.iter().any(|n| !key_version.id_numbers.contains_key(n))
+			|| partial_request.message_hashes.len() != self.session_keys.len() {
 			return Err(Error::InvalidMessage);
 		}
 
 		let self_id_number = &key_version.id_numbers[&self.self_node_id];
-		let other_id_numbers = partial_request.other_nodes_ids.iter().map(|n| &key_version.id_numbers[n]);
-		let combined_hash = math::combine_message_hash_with_public(&partial_request.message_hash, &self.session_public)?;
+		let mut partial_signatures = Vec::with_capacity(self.session_keys.len());
+		for (message_hash, &(ref session_public, ref session_secret_coeff)) in partial_request.message_hashes.iter().zip(self.session_keys.iter()) {
+			let other_id_numbers = partial_request.other_nodes_ids.iter().map(|n| &key_version.id_numbers[n]);
+			partial_signatures.push(match partial_request.scheme {
+				SchnorrSigningScheme::Legacy => {
+					let combined_hash = math::combine_message_hash_with_public(message_hash, session_public)?;
+					math::compute_schnorr_signature_share(
+						self.key_share.threshold,
+						&combined_hash,
+						session_secret_coeff,
+						&key_version.secret_share,
+						self_id_number,
+						other_id_numbers
+					)?
+				},
+				SchnorrSigningScheme::Bip340 => {
+					let challenge = math::compute_bip340_challenge(session_public, &self.key_share.public, message_hash)?;
+					math::compute_bip340_signature_share(
+						self.key_share.threshold,
+						&challenge,
+						session_public,
+						session_secret_coeff,
+						&self.key_share.public,
+						&key_version.secret_share,
+						self_id_number,
+						other_id_numbers
+					)?
+				},
+			});
+		}
+
 		Ok(JobPartialRequestAction::Respond(SchnorrPartialSigningResponse {
 			request_id: partial_request.id,
-			partial_signature: math::compute_schnorr_signature_share(
-				self.key_share.threshold,
-				&combined_hash,
-				&self.session_secret_coeff,
-				&key_version.secret_share,
-				self_id_number,
-				other_id_numbers
-			)?,
+			partial_signatures: partial_signatures,
 		}))
 	}
 
-	fn check_partial_response(&mut self, _sender: &NodeId, partial_response: &SchnorrPartialSigningResponse) -> Result<JobPartialResponseAction, Error> {
+	fn check_partial_response(&mut self, sender: &NodeId, partial_response: &SchnorrPartialSigningResponse) -> Result<JobPartialResponseAction, Error> {
 		if Some(&partial_response.request_id) != self.request_id.as_ref() {
 			return Ok(JobPartialResponseAction::Ignore);
 		}
-		// TODO [Trust]: check_schnorr_signature_share()
+		if partial_response.partial_signatures.len() != self.session_keys.len() {
+			return Ok(JobPartialResponseAction::Reject);
+		}
+
+		if !is_legacy_key_version(&self.key_share, &self.key_version)? {
+			for (index, partial_signature) in partial_response.partial_signatures.iter().enumerate() {
+				if !self.is_partial_signature_valid(sender, index, partial_signature)? {
+					return Err(Error::InvalidPartialResponse(sender.clone()));
+				}
+			}
+		}
 
 		Ok(JobPartialResponseAction::Accept)
 	}
 
-	fn compute_response(&self, partial_responses: &BTreeMap<NodeId, SchnorrPartialSigningResponse>) -> Result<(Secret, Secret), Error> {
-		let message_hash = self.message_hash.as_ref()
-			.expect("compute_response is only called on master nodes; message_hash is filed in constructor on master nodes; qed");
+	fn compute_response(&self, partial_responses: &BTreeMap<NodeId, SchnorrPartialSigningResponse>) -> Result<Vec<(Secret, Secret)>, Error> {
+		let scheme = self.scheme
+			.expect("compute_response is only called on master nodes; scheme is filed in constructor on master nodes; qed");
+		let message_hashes = self.message_hashes.as_ref()
+			.expect("compute_response is only called on master nodes; message_hashes are filed in constructor on master nodes; qed");
+
+		message_hashes.iter().zip(self.session_keys.iter()).enumerate()
+			.map(|(index, (message_hash, &(ref session_public, _)))| {
+				let partial_signatures = partial_responses.values().map(|r| &r.partial_signatures[index]);
+				match scheme {
+					SchnorrSigningScheme::Legacy => {
+						let signature_c = math::combine_message_hash_with_public(message_hash, session_public)?;
+						let signature_s = math::compute_schnorr_signature(partial_signatures)?;
 
-		let signature_c = math::combine_message_hash_with_public(message_hash, &self.session_public)?;
-		let signature_s = math::compute_schnorr_signature(partial_responses.values().map(|r| &r.partial_signature))?;
+						Ok((signature_c, signature_s))
+					},
+					SchnorrSigningScheme::Bip340 => {
+						// BIP-340 signature is going to be checked by third-party verifiers => check it before returning
+						let signature = math::compute_bip340_signature(session_public, partial_signatures)?;
+						let public_x = math::bip340_x_only_public(&self.key_share.public);
+						if !math::verify_bip340_signature(&public_x, &signature, message_hash)? {
+							return Err(Error::InvalidMessage);
+						}
 
-		Ok((signature_c, signature_s))
+						Ok(signature)
+					},
+				}
+			})
+			.collect()
 	}
 }
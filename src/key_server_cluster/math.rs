@@ -18,9 +18,13 @@ use std::collections::BTreeMap;
 use crypto::publickey::{Public, Secret, Signature, Random, Generator, ec_math_utils};
 use ethereum_types::{H256, U256, BigEndianHash};
 use hash::keccak;
+use sha2::{Digest, Sha256};
 use tiny_keccak::Keccak;
 use key_server_cluster::Error;
 
+/// First index of hardened child keys.
+pub const HARDENED_KEY_INDEX: u32 = 0x80000000;
+
 /// Encryption result.
 #[derive(Debug)]
 pub struct EncryptedSecret {
@@ -30,6 +34,19 @@ pub struct EncryptedSecret {
 	pub encrypted_point: Public,
 }
 
+/// Proof that node shadow point, computed for shadow decryption, is the node shadow point, multiplied by decrypt shadow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowDecryptionProof {
+	/// Public of the decrypt shadow.
+	pub decrypt_shadow_public: Public,
+	/// Public of the shadow key (node shadow, multiplied by access key && decrypt shadow).
+	pub shadow_key_public: Public,
+	/// Proof (challenge, response) of log(G, shadow_key_public) == log(common_point, node_shadow_point).
+	pub shadow_point_proof: (Secret, Secret),
+	/// Proof (challenge, response) of log(G, decrypt_shadow_public) == log(node_shadow_public, shadow_key_public).
+	pub decrypt_shadow_proof: (Secret, Secret),
+}
+
 /// Calculate the inversion of a Secret key (in place) using the `libsecp256k1` crate.
 fn invert_secret(s: &mut Secret) -> Result<(), Error> {
 	*s = secp256k1::SecretKey::parse(&s.0)?.inv().serialize().into();
@@ -60,6 +77,14 @@ pub fn generate_random_point() -> Result<Public, Error> {
 	Ok(Random.generate().public().clone())
 }
 
+/// Check if point is valid secp256k1 point.
+pub fn public_is_valid(public: &Public) -> bool {
+	let mut serialized = [0u8; 65];
+	serialized[0] = 0x04;
+	serialized[1..].copy_from_slice(public.as_bytes());
+	secp256k1::PublicKey::parse(&serialized).is_ok()
+}
+
 /// Get X coordinate of point.
 fn public_x(public: &Public) -> H256 {
 	H256::from_slice(&public.as_bytes()[0..32])
@@ -70,6 +95,45 @@ fn public_y(public: &Public) -> H256 {
 	H256::from_slice(&public.as_bytes()[32..64])
 }
 
+/// Check if Y coordinate of point is even.
+fn public_has_even_y(public: &Public) -> bool {
+	public.as_bytes()[63] & 1 == 0
+}
+
+/// Compute additive tweak of the non-hardened child key, derived from the key with given public along the
+/// given path. For every path index: tweak = keccak(public || index), child_public = public + tweak * G.
+/// Since tweak only depends on public data, every node could apply it to its own key share.
+/// Returns sum of all tweaks && public of the child key.
+pub fn compute_child_key_tweak(public: &Public, path: &[u32]) -> Result<(Secret, Public), Error> {
+	let mut tweak = zero_scalar();
+	let mut child_public = public.clone();
+	for index in path {
+		// hardened derivation requires knowledge of the private key => is impossible here
+		if *index >= HARDENED_KEY_INDEX {
+			return Err(Error::InvalidDerivationPath);
+		}
+
+		let mut buffer = [0; 68];
+		buffer[0..64].copy_from_slice(child_public.as_bytes());
+		buffer[64..68].copy_from_slice(&index.to_be_bytes());
+		let index_tweak = to_scalar(keccak(&buffer[..]))?;
+
+		ec_math_utils::public_add(&mut child_public, &compute_public_share(&index_tweak)?)?;
+		tweak.add(&index_tweak)?;
+	}
+
+	Ok((tweak, child_public))
+}
+
+/// Re-encrypt document key, encrypted with the parent key, so that it could be decrypted using the child key:
+/// (M + k * y) + tweak * (k * G) = M + k * (y + tweak * G).
+pub fn compute_child_encrypted_point(tweak: &Secret, common_point: &Public, encrypted_point: &Public) -> Result<Public, Error> {
+	let mut child_encrypted_point = common_point.clone();
+	ec_math_utils::public_mul_secret(&mut child_encrypted_point, tweak)?;
+	ec_math_utils::public_add(&mut child_encrypted_point, encrypted_point)?;
+	Ok(child_encrypted_point)
+}
+
 /// Compute publics sum.
 pub fn compute_public_sum<'a, I>(mut publics: I) -> Result<Public, Error> where I: Iterator<Item=&'a Public> {
 	let mut sum = publics.next().expect("compute_public_sum is called when there's at least one public; qed").clone();
@@ -252,6 +316,100 @@ pub fn compute_public_share(self_secret_value: &Secret) -> Result<Public, Error>
 	Ok(public_share)
 }
 
+/// Compute public share (secret_share * G) of the node with given id number, using public share proofs
+/// (publics of polynom1 coefficients), received from all nodes during key generation.
+pub fn compute_node_public_share<'a, I>(number_id: &Secret, share_proofs: I) -> Result<Public, Error> where I: Iterator<Item=&'a [Public]> {
+	let mut node_public_shares = Vec::new();
+	for share_proof in share_proofs {
+		let mut node_public_share = share_proof.first().cloned().ok_or(Error::InvalidMessage)?;
+		for (i, public_k) in share_proof.iter().enumerate().skip(1) {
+			let mut secret_pow = number_id.clone();
+			secret_pow.pow(i)?;
+
+			let mut public_k = public_k.clone();
+			ec_math_utils::public_mul_secret(&mut public_k, &secret_pow)?;
+
+			ec_math_utils::public_add(&mut node_public_share, &public_k)?;
+		}
+		node_public_shares.push(node_public_share);
+	}
+
+	compute_public_sum(node_public_shares.iter())
+}
+
+/// Compute public of polynom value at given id number (polynom(number_id) * G), using publics of polynom coefficients,
+/// except the absolute term. Absolute term public is `None` if absolute term is zero. Returns `None` if the value is zero.
+pub fn compute_polynom_public(absolute_term_public: Option<&Public>, coeffs_publics: &[Public], number_id: &Secret) -> Result<Option<Public>, Error> {
+	let mut polynom_public = absolute_term_public.cloned();
+	for (i, public_k) in coeffs_publics.iter().enumerate() {
+		let mut secret_pow = number_id.clone();
+		secret_pow.pow(i + 1)?;
+
+		let mut public_k = public_k.clone();
+		ec_math_utils::public_mul_secret(&mut public_k, &secret_pow)?;
+
+		polynom_public = match polynom_public {
+			Some(mut polynom_public) => {
+				ec_math_utils::public_add(&mut polynom_public, &public_k)?;
+				Some(polynom_public)
+			},
+			None => Some(public_k),
+		};
+	}
+
+	Ok(polynom_public)
+}
+
+/// Check that secret value is the value of polynom with given coefficients publics at given id number.
+pub fn polynom_value_verification(absolute_term_public: Option<&Public>, coeffs_publics: &[Public], number_id: &Secret, value: &Secret) -> Result<bool, Error> {
+	let value_public = match *value == zero_scalar() {
+		true => None,
+		false => Some(compute_public_share(value)?),
+	};
+
+	Ok(compute_polynom_public(absolute_term_public, coeffs_publics, number_id)? == value_public)
+}
+
+/// Compute Lagrange coefficient of the node with given id number for interpolation at zero over the node && other nodes.
+pub fn compute_lagrange_coeff<'a, I>(node_number: &Secret, other_nodes_numbers: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let other_nodes_numbers: Vec<_> = other_nodes_numbers.collect();
+	let one = to_scalar(H256::from_low_u64_be(1))?;
+	compute_secret_subshare(other_nodes_numbers.len(), &one, node_number, other_nodes_numbers.into_iter())
+}
+
+/// Compute public, multiplied by secret.
+pub fn compute_public_mul(public: &Public, secret: &Secret) -> Result<Public, Error> {
+	let mut public_mul = public.clone();
+	ec_math_utils::public_mul_secret(&mut public_mul, secret)?;
+	Ok(public_mul)
+}
+
+/// Check that public shares are publics of values of the same polynom of given degree, which has secret of joint public
+/// as absolute term. Polynom is defined by joint public && first `threshold` shares => it is enough to check that every
+/// subset of first `threshold` shares && some other share is interpolated to the joint public.
+pub fn public_shares_verification(threshold: usize, joint_public: &Public, id_numbers: &[&Secret], public_shares: &[&Public]) -> Result<bool, Error> {
+	debug_assert_eq!(id_numbers.len(), public_shares.len());
+	if id_numbers.len() < threshold + 1 {
+		return Ok(false);
+	}
+
+	for i in threshold..id_numbers.len() {
+		let subset: Vec<_> = (0..threshold).chain(::std::iter::once(i)).collect();
+		let mut interpolated_publics = Vec::with_capacity(threshold + 1);
+		for &j in &subset {
+			let lagrange_coeff = compute_lagrange_coeff(id_numbers[j],
+				subset.iter().filter(|&&k| k != j).map(|&k| id_numbers[k]))?;
+			interpolated_publics.push(compute_public_mul(public_shares[j], &lagrange_coeff)?);
+		}
+
+		if compute_public_sum(interpolated_publics.iter())? != *joint_public {
+			return Ok(false);
+		}
+	}
+
+	Ok(true)
+}
+
 /// Compute joint public key.
 pub fn compute_joint_public<'a, I>(public_shares: I) -> Result<Public, Error> where I: Iterator<Item=&'a Public> {
 	compute_public_sum(public_shares)
@@ -330,6 +488,88 @@ pub fn compute_node_shadow_point(access_key: &Secret, common_point: &Public, nod
 	Ok((node_shadow_point, decrypt_shadow))
 }
 
+/// Compute public of the node shadow point. If node shadow point has been computed correctly (without decrypt shadow),
+/// then log(common_point, node_shadow_point) == log(G, node_shadow_public).
+pub fn compute_node_shadow_public<'a, I>(access_key: &Secret, node_public_share: &Public, node_number: &Secret, other_nodes_numbers: I) -> Result<Public, Error> where I: Iterator<Item=&'a Secret> {
+	let shadow_coeff = compute_shadow_mul(access_key, node_number, other_nodes_numbers)?;
+	let mut node_shadow_public = node_public_share.clone();
+	ec_math_utils::public_mul_secret(&mut node_shadow_public, &shadow_coeff)?;
+	Ok(node_shadow_public)
+}
+
+/// Compute non-interactive Chaum-Pedersen proof of log(G, secret * G) == log(base, secret * base). Returns (challenge, response).
+pub fn compute_dleq_proof(secret: &Secret, base: &Public) -> Result<(Secret, Secret), Error> {
+	let public = compute_public_share(secret)?;
+	let mut base_public = base.clone();
+	ec_math_utils::public_mul_secret(&mut base_public, secret)?;
+
+	let nonce = generate_random_scalar()?;
+	let nonce_public = compute_public_share(&nonce)?;
+	let mut nonce_base_public = base.clone();
+	ec_math_utils::public_mul_secret(&mut nonce_base_public, &nonce)?;
+
+	// response = nonce - challenge * secret
+	let challenge = compute_dleq_challenge(&public, base, &base_public, &nonce_public, &nonce_base_public)?;
+	let mut response = compute_secret_mul(&challenge, secret)?;
+	response.neg()?;
+	response.add(&nonce)?;
+
+	Ok((challenge, response))
+}
+
+/// Verify non-interactive Chaum-Pedersen proof of log(G, public) == log(base, base_public).
+pub fn verify_dleq_proof(public: &Public, base: &Public, base_public: &Public, proof: &(Secret, Secret)) -> Result<bool, Error> {
+	let (ref challenge, ref response) = *proof;
+
+	// nonce * G = response * G + challenge * public
+	let nonce_public = compute_dleq_nonce_public(&ec_math_utils::generation_point(), public, challenge, response)?;
+	// nonce * base = response * base + challenge * base_public
+	let nonce_base_public = compute_dleq_nonce_public(base, base_public, challenge, response)?;
+
+	Ok(*challenge == compute_dleq_challenge(public, base, base_public, &nonce_public, &nonce_base_public)?)
+}
+
+/// Compute proof of node shadow point, computed for shadow decryption with given decrypt shadow.
+pub fn compute_shadow_decryption_proof(access_key: &Secret, common_point: &Public, node_shadow: &Secret, decrypt_shadow: &Secret) -> Result<ShadowDecryptionProof, Error> {
+	let node_shadow_key = compute_secret_mul(node_shadow, access_key)?;
+	let node_shadow_public = compute_public_share(&node_shadow_key)?;
+	let shadow_key = compute_secret_mul(&node_shadow_key, decrypt_shadow)?;
+	Ok(ShadowDecryptionProof {
+		decrypt_shadow_public: compute_public_share(decrypt_shadow)?,
+		shadow_key_public: compute_public_share(&shadow_key)?,
+		shadow_point_proof: compute_dleq_proof(&shadow_key, common_point)?,
+		decrypt_shadow_proof: compute_dleq_proof(decrypt_shadow, &node_shadow_public)?,
+	})
+}
+
+/// Verify proof of node shadow point, computed for shadow decryption. Together these proofs are showing that
+/// node_shadow_point == decrypt_shadow * (node shadow point, computed without decrypt shadow).
+pub fn verify_shadow_decryption_proof(node_shadow_public: &Public, common_point: &Public, node_shadow_point: &Public, proof: &ShadowDecryptionProof) -> Result<bool, Error> {
+	Ok(verify_dleq_proof(&proof.shadow_key_public, common_point, node_shadow_point, &proof.shadow_point_proof)?
+		&& verify_dleq_proof(&proof.decrypt_shadow_public, node_shadow_public, &proof.shadow_key_public, &proof.decrypt_shadow_proof)?)
+}
+
+/// Restore nonce public of Chaum-Pedersen proof.
+fn compute_dleq_nonce_public(base: &Public, base_public: &Public, challenge: &Secret, response: &Secret) -> Result<Public, Error> {
+	let mut nonce_public = base.clone();
+	ec_math_utils::public_mul_secret(&mut nonce_public, response)?;
+	let mut challenge_public = base_public.clone();
+	ec_math_utils::public_mul_secret(&mut challenge_public, challenge)?;
+	ec_math_utils::public_add(&mut nonce_public, &challenge_public)?;
+	Ok(nonce_public)
+}
+
+/// Compute challenge of Chaum-Pedersen proof.
+fn compute_dleq_challenge(public: &Public, base: &Public, base_public: &Public, nonce_public: &Public, nonce_base_public: &Public) -> Result<Secret, Error> {
+	let mut buffer = Vec::with_capacity(64 * 6);
+	buffer.extend_from_slice(ec_math_utils::generation_point().as_bytes());
+	for point in &[public, base, base_public, nonce_public, nonce_base_public] {
+		buffer.extend_from_slice(point.as_bytes());
+	}
+
+	to_scalar(keccak(&buffer))
+}
+
 /// Compute joint shadow point.
 pub fn compute_joint_shadow_point<'a, I>(nodes_shadow_points: I) -> Result<Public, Error> where I: Iterator<Item=&'a Public> {
 	compute_public_sum(nodes_shadow_points)
@@ -373,6 +613,36 @@ pub fn make_common_shadow_point(threshold: usize, mut common_point: Public) -> R
 	}
 }
 
+/// Compute node share of the document key re-encryption point, used when key is rotated:
+/// lagrange_coeff * delta_share * common_point, where delta_share is the node share of (y' - y).
+/// Returns the point along with the proof of log(common_point, point) == log(G, lagrange_coeff * delta_share * G).
+pub fn compute_node_reencryption_point<'a, I>(delta_share: &Secret, node_number: &Secret, other_nodes_numbers: I, common_point: &Public) -> Result<(Public, (Secret, Secret)), Error> where I: Iterator<Item=&'a Secret> {
+	let node_shadow = compute_secret_mul(&compute_lagrange_coeff(node_number, other_nodes_numbers)?, delta_share)?;
+	let reencryption_point = compute_public_mul(common_point, &node_shadow)?;
+	let reencryption_proof = compute_dleq_proof(&node_shadow, common_point)?;
+	Ok((reencryption_point, reencryption_proof))
+}
+
+/// Verify node share of the document key re-encryption point, using public of the node share of (y' - y).
+pub fn verify_node_reencryption_point<'a, I>(delta_public: &Public, node_number: &Secret, other_nodes_numbers: I, common_point: &Public,
+	reencryption_point: &Public, reencryption_proof: &(Secret, Secret)) -> Result<bool, Error> where I: Iterator<Item=&'a Secret> {
+	let node_shadow_public = compute_public_mul(delta_public, &compute_lagrange_coeff(node_number, other_nodes_numbers)?)?;
+	verify_dleq_proof(&node_shadow_public, common_point, reencryption_point, reencryption_proof)
+}
+
+/// Re-encrypt document key with rotated key: (M + k * y) + (y' - y) * (k * G) = M + k * y'.
+pub fn compute_reencrypted_point<'a, I>(encrypted_point: &'a Public, nodes_reencryption_points: I) -> Result<Public, Error> where I: Iterator<Item=&'a Public> {
+	compute_public_sum(::std::iter::once(encrypted_point).chain(nodes_reencryption_points))
+}
+
+/// Compute ECDH shared point of joint secret and given public. Public `P` is decrypted as if it was document key,
+/// encrypted with itself as a common point => decrypted point is `P - x * P` and result is `x * P`.
+pub fn compute_ecdh_shared_point(public: &Public, decrypted_point: &Public) -> Result<Public, Error> {
+	let mut shared_point = public.clone();
+	ec_math_utils::public_sub(&mut shared_point, decrypted_point)?;
+	Ok(shared_point)
+}
+
 /// Decrypt shadow-encrypted secret.
 #[cfg(test)]
 pub fn decrypt_with_shadow_coefficients(mut decrypted_shadow: Public, mut common_shadow_point: Public, shadow_coefficients: Vec<Secret>) -> Result<Public, Error> {
@@ -422,24 +692,20 @@ pub fn compute_schnorr_signature_share<'a, I>(threshold: usize, combined_hash: &
 	Ok(sum)
 }
 
-/// Check Schnorr signature share.
-pub fn _check_schnorr_signature_share<'a, I>(_combined_hash: &Secret, _signature_share: &Secret, _public_share: &Public, _one_time_public_share: &Public, _node_numbers: I)
-	-> Result<bool, Error> where I: Iterator<Item=&'a Secret> {
-	// TODO [Trust]: in paper partial signature is checked using comparison:
-	//    sig[i] * T                                  = r[i] - c * lagrange_coeff(i) * y[i]
-	// => (k[i] - c * lagrange_coeff(i) * s[i]) * T   = r[i] - c * lagrange_coeff(i) * y[i]
-	// => k[i] * T - c * lagrange_coeff(i) * s[i] * T = k[i] * T - c * lagrange_coeff(i) * y[i]
-	// => this means that y[i] = s[i] * T
-	// but when verifying signature (for t = 1), nonce public (r) is restored using following expression:
-	// r = (sig[0] + sig[1]) * T - c * y
-	// r = (k[0] - c * lagrange_coeff(0) * s[0] + k[1] - c * lagrange_coeff(1) * s[1]) * T - c * y
-	// r = (k[0] + k[1]) * T - c * (lagrange_coeff(0) * s[0] + lagrange_coeff(1) * s[1]) * T - c * y
-	// r = r - c * (lagrange_coeff(0) * s[0] + lagrange_coeff(1) * s[1]) * T - c * y
-	// => -c * y = c * (lagrange_coeff(0) * s[0] + lagrange_coeff(1) * s[1]) * T
-	// => -y = (lagrange_coeff(0) * s[0] + lagrange_coeff(1) * s[1]) * T
-	// => y[i] != s[i] * T
-	// => some other way is required
-	Ok(true)
+/// Check Schnorr signature share: sig[i] * G = r[i] -/+ c * shadow_coeff(i) * y[i], where r[i] is the public of node one-time
+/// secret coefficient and y[i] is the public of node secret share (sign depends on threshold, see compute_schnorr_signature_share).
+pub fn check_schnorr_signature_share<'a, I>(threshold: usize, combined_hash: &Secret, signature_share: &Secret, public_share: &Public,
+	one_time_public_share: &Public, node_number: &Secret, other_nodes_numbers: I) -> Result<bool, Error> where I: Iterator<Item=&'a Secret> {
+	let mut coeff = compute_shadow_mul(combined_hash, node_number, other_nodes_numbers)?;
+	if threshold % 2 == 0 {
+		coeff.neg()?;
+	}
+
+	let mut expected_public = public_share.clone();
+	ec_math_utils::public_mul_secret(&mut expected_public, &coeff)?;
+	ec_math_utils::public_add(&mut expected_public, one_time_public_share)?;
+
+	Ok(expected_public == compute_public_share(signature_share)?)
 }
 
 /// Compute Schnorr signature.
@@ -476,6 +742,146 @@ pub fn verify_schnorr_signature(public: &Public, signature: &(Secret, Secret), m
 	Ok(combined_hash == signature.0)
 }
 
+/// Compute BIP-340 tagged hash: SHA256(SHA256(tag) || SHA256(tag) || data).
+fn bip340_tagged_hash(tag: &str, data: &[&[u8]]) -> H256 {
+	let tag_hash = Sha256::digest(tag.as_bytes());
+	let mut hasher = Sha256::new();
+	hasher.input(&tag_hash);
+	hasher.input(&tag_hash);
+	for chunk in data {
+		hasher.input(chunk);
+	}
+	H256::from_slice(&hasher.result())
+}
+
+/// Get BIP-340 (x-only) representation of the public key.
+pub fn bip340_x_only_public(public: &Public) -> H256 {
+	public_x(public)
+}
+
+/// Restore the point with even Y coordinate from BIP-340 (x-only) public key.
+pub fn bip340_lift_x(public_x: &H256) -> Result<Public, Error> {
+	let mut compressed = [0u8; 33];
+	compressed[0] = 0x02;
+	compressed[1..].copy_from_slice(public_x.as_bytes());
+	let public = secp256k1::PublicKey::parse_compressed(&compressed)?;
+	Ok(Public::from_slice(&public.serialize()[1..]))
+}
+
+/// Compute BIP-340 challenge: hash_BIP0340/challenge(x(R) || x(P) || message_hash), mapped to EC finite field value.
+pub fn compute_bip340_challenge(nonce_public: &Public, public: &Public, message_hash: &H256) -> Result<Secret, Error> {
+	to_scalar(bip340_tagged_hash("BIP0340/challenge",
+		&[public_x(nonce_public).as_bytes(), public_x(public).as_bytes(), message_hash.as_bytes()]))
+}
+
+/// Compute BIP-340 signature share.
+/// BIP-340 only works with points with even Y coordinate => if joint public key (nonce public) has odd Y
+/// coordinate, every node negates its share of secret key (nonce), so that the combined signature
+/// corresponds to the negated point.
+pub fn compute_bip340_signature_share<'a, I>(threshold: usize, challenge: &Secret, nonce_public: &Public, one_time_secret_coeff: &Secret,
+	public: &Public, node_secret_share: &Secret, node_number: &Secret, other_nodes_numbers: I)
+	-> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let mut sum = one_time_secret_coeff.clone();
+	if !public_has_even_y(nonce_public) {
+		sum.neg()?;
+	}
+
+	// compute_shadow_mul result is multiplied by (-1)^threshold
+	let mut addendum = compute_shadow_mul(challenge, node_number, other_nodes_numbers)?;
+	addendum.mul(node_secret_share)?;
+	if (threshold % 2 == 1) == public_has_even_y(public) {
+		addendum.neg()?;
+	}
+
+	sum.add(&addendum)?;
+	Ok(sum)
+}
+
+/// Check BIP-340 signature share: sig[i] * G = -/+ r[i] -/+ e * shadow_coeff(i) * y[i], where r[i] is the public of node one-time
+/// secret coefficient and y[i] is the public of node secret share (signs are selected as in compute_bip340_signature_share).
+pub fn check_bip340_signature_share<'a, I>(threshold: usize, challenge: &Secret, signature_share: &Secret, nonce_public: &Public,
+	public: &Public, public_share: &Public, one_time_public_share: &Public, node_number: &Secret, other_nodes_numbers: I)
+	-> Result<bool, Error> where I: Iterator<Item=&'a Secret> {
+	let mut coeff = compute_shadow_mul(challenge, node_number, other_nodes_numbers)?;
+	if (threshold % 2 == 1) == public_has_even_y(public) {
+		coeff.neg()?;
+	}
+
+	let mut one_time_public_share = one_time_public_share.clone();
+	if !public_has_even_y(nonce_public) {
+		ec_math_utils::public_negate(&mut one_time_public_share)?;
+	}
+
+	let mut expected_public = public_share.clone();
+	ec_math_utils::public_mul_secret(&mut expected_public, &coeff)?;
+	ec_math_utils::public_add(&mut expected_public, &one_time_public_share)?;
+
+	Ok(expected_public == compute_public_share(signature_share)?)
+}
+
+/// Compute BIP-340 signature. First component of signature is the X coordinate of nonce public.
+pub fn compute_bip340_signature<'a, I>(nonce_public: &Public, signature_shares: I) -> Result<(Secret, Secret), Error> where I: Iterator<Item=&'a Secret> {
+	Ok((Secret::from(public_x(nonce_public).0), compute_secret_sum(signature_shares)?))
+}
+
+/// Locally compute BIP-340 signature, using nonce derivation from https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#default-signing.
+#[cfg(test)]
+pub fn local_compute_bip340_signature(secret: &Secret, message_hash: &H256, aux_rand: &H256) -> Result<(Secret, Secret), Error> {
+	let mut public = ec_math_utils::generation_point();
+	ec_math_utils::public_mul_secret(&mut public, secret)?;
+	let mut secret = secret.clone();
+	if !public_has_even_y(&public) {
+		secret.neg()?;
+	}
+
+	let aux_hash = bip340_tagged_hash("BIP0340/aux", &[aux_rand.as_bytes()]);
+	let masked_secret: Vec<u8> = secret.as_bytes().iter().zip(aux_hash.as_bytes()).map(|(s, a)| s ^ a).collect();
+	let mut nonce = to_scalar(bip340_tagged_hash("BIP0340/nonce", &[&masked_secret, public_x(&public).as_bytes(), message_hash.as_bytes()]))?;
+	let mut nonce_public = ec_math_utils::generation_point();
+	ec_math_utils::public_mul_secret(&mut nonce_public, &nonce)?;
+	if !public_has_even_y(&nonce_public) {
+		nonce.neg()?;
+	}
+
+	let mut signature_s = compute_bip340_challenge(&nonce_public, &public, message_hash)?;
+	signature_s.mul(&secret)?;
+	signature_s.add(&nonce)?;
+	Ok((Secret::from(public_x(&nonce_public).0), signature_s))
+}
+
+/// Verify BIP-340 signature as described in https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#verification.
+pub fn verify_bip340_signature(public_x: &H256, signature: &(Secret, Secret), message_hash: &H256) -> Result<bool, Error> {
+	let public = match bip340_lift_x(public_x) {
+		Ok(public) => public,
+		Err(_) => return Ok(false),
+	};
+	let signature_s: U256 = (*signature.1).into_uint();
+	if signature_s.is_zero() || signature_s >= *ec_math_utils::CURVE_ORDER {
+		return Ok(false);
+	}
+
+	// R = s * G - e * P
+	let challenge = to_scalar(bip340_tagged_hash("BIP0340/challenge",
+		&[signature.0.as_bytes(), public_x.as_bytes(), message_hash.as_bytes()]))?;
+	let mut nonce_public = ec_math_utils::generation_point();
+	ec_math_utils::public_mul_secret(&mut nonce_public, &signature.1)?;
+	let mut subtrahend = public;
+	ec_math_utils::public_mul_secret(&mut subtrahend, &challenge)?;
+	if ec_math_utils::public_sub(&mut nonce_public, &subtrahend).is_err() {
+		return Ok(false);
+	}
+
+	Ok(public_has_even_y(&nonce_public) && self::public_x(&nonce_public) == *signature.0)
+}
+
+/// Serialize BIP-340 signature to standard 64-bytes form: x(R) || s.
+pub fn serialize_bip340_signature(signature: &(Secret, Secret)) -> [u8; 64] {
+	let mut serialized = [0u8; 64];
+	serialized[..32].copy_from_slice(signature.0.as_bytes());
+	serialized[32..].copy_from_slice(signature.1.as_bytes());
+	serialized
+}
+
 /// Compute R part of ECDSA signature.
 pub fn compute_ecdsa_r(nonce_public: &Public) -> Result<Secret, Error> {
 	to_scalar(public_x(nonce_public))
@@ -563,6 +969,32 @@ pub fn compute_ecdsa_inversed_secret_coeff_from_shares(t: usize, id_numbers: &[S
 	Ok(u_inv)
 }
 
+/// Compute additive share of joint secret (lagrange coefficient * secret share), used by MtA-based ECDSA signing.
+pub fn compute_ecdsa_mta_secret_share<'a, I>(node_secret_share: &Secret, node_number: &Secret, other_nodes_numbers: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	// compute_shadow_mul result is multiplied by (-1)^(number of other nodes)
+	let other_nodes_numbers: Vec<_> = other_nodes_numbers.collect();
+	let mut secret_share = compute_shadow_mul(node_secret_share, node_number, other_nodes_numbers.iter().cloned())?;
+	if other_nodes_numbers.len() % 2 == 1 {
+		secret_share.neg()?;
+	}
+
+	Ok(secret_share)
+}
+
+/// Compute ECDSA inversed-nonce coefficient (inv(inv_nonce * gamma)) from additive shares of inv_nonce * gamma (MtA-based signing).
+pub fn compute_ecdsa_mta_inversed_nonce_coeff<'a, I>(delta_shares: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let mut inversed_nonce_coeff = compute_secret_sum(delta_shares)?;
+	invert_secret(&mut inversed_nonce_coeff)?;
+	Ok(inversed_nonce_coeff)
+}
+
+/// Compute ECDSA nonce public from sum of gamma publics && inversed-nonce coefficient (MtA-based signing).
+pub fn compute_ecdsa_mta_nonce_public(gamma_public: &Public, inversed_nonce_coeff: &Secret) -> Result<Public, Error> {
+	let mut nonce_public = gamma_public.clone();
+	ec_math_utils::public_mul_secret(&mut nonce_public, inversed_nonce_coeff)?;
+	Ok(nonce_public)
+}
+
 /// Computes footprint of all publics received from all nodes.
 pub fn compute_publics_footprint(nodes: BTreeMap<Public, Vec<Public>>) -> Result<H256, Error> {
 	let mut publics_keccak = Keccak::new_keccak256();
@@ -582,6 +1014,9 @@ pub fn compute_publics_footprint(nodes: BTreeMap<Public, Vec<Public>>) -> Result
 pub mod tests {
 	use std::iter::once;
 	use crypto::publickey::{KeyPair, Secret, recover, verify_public};
+	use crypto::publickey::ecdh::agree;
+	use crypto::publickey::ecies::encrypt;
+	use key_server_cluster::math_paillier;
 	use super::*;
 
 	#[derive(Clone)]
@@ -904,11 +1339,22 @@ pub mod tests {
 					.filter(|j| i != *j)
 					.map(|j| {
 						let signature_share = partial_signatures[j].clone();
-						assert!(_check_schnorr_signature_share(&combined_hash,
+						let other_id_numbers = || artifacts.id_numbers.iter().take(n).enumerate().filter(|&(k, _)| k != j).map(|(_, n)| n);
+						let public_share = compute_public_share(&artifacts.secret_shares[j]).unwrap();
+						assert!(check_schnorr_signature_share(t,
+							&combined_hash,
 							&signature_share,
-							&artifacts.public_shares[j],
+							&public_share,
+							&one_time_artifacts.public_shares[j],
+							&artifacts.id_numbers[j],
+							other_id_numbers()).unwrap());
+						assert!(!check_schnorr_signature_share(t,
+							&combined_hash,
+							&generate_random_scalar().unwrap(),
+							&public_share,
 							&one_time_artifacts.public_shares[j],
-							artifacts.id_numbers.iter().take(t)).unwrap_or(false));
+							&artifacts.id_numbers[j],
+							other_id_numbers()).unwrap());
 						signature_share
 					})
 					.collect())
@@ -930,6 +1376,121 @@ pub mod tests {
 		}
 	}
 
+	#[test]
+	fn bip340_signature_test_vectors() {
+		// signing vectors from https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+		let signing_vectors = [
+			("0000000000000000000000000000000000000000000000000000000000000003",
+				"f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+				"0000000000000000000000000000000000000000000000000000000000000000",
+				"0000000000000000000000000000000000000000000000000000000000000000",
+				"e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0"),
+			("b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+				"dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+				"0000000000000000000000000000000000000000000000000000000000000001",
+				"243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+				"6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a"),
+			("c90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b14e5c9",
+				"dd308afec5777e13121fa72b9cc1b7cc0139715309b086c960e18fd969774eb8",
+				"c87aa53824b4d7ae2eb035a2b5bbbccc080e76cdc6d1692c4b0b62d798e6d906",
+				"7e2d58d8b3bcdf1abadec7829054f90dda9805aab56c77333024b9d0a508b75c",
+				"5831aaeed7b44bb74e5eab94ba9d4294c49bcf2a60728d8b4c200f50dd313c1bab745879a5ad954a72c45a91c3a51d3c7adea98d82f8481e0e1e03674a6f3fb7"),
+			("0b432b2677937381aef05bb02a66ecd012773062cf3fa2549e44f58ed2401710",
+				"25d1dff95105f5253c4022f628a996ad3a0d95fbf21d468a1b33f8c160d8f517",
+				"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+				"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+				"7eb0509757e246f19449885651611cb965ecc1a187dd51b64fda1edc9637d5ec97582b9cb13db3933705b32ba982af5af25fd78881ebb32771fc5922efc66ea3"),
+		];
+		for &(secret, public, aux_rand, message, signature) in &signing_vectors {
+			let secret: Secret = secret.parse().unwrap();
+			let public: H256 = public.parse().unwrap();
+			let aux_rand: H256 = aux_rand.parse().unwrap();
+			let message: H256 = message.parse().unwrap();
+			let signature: (Secret, Secret) = (signature[..64].parse().unwrap(), signature[64..].parse().unwrap());
+
+			let mut full_public = ec_math_utils::generation_point();
+			ec_math_utils::public_mul_secret(&mut full_public, &secret).unwrap();
+			assert_eq!(bip340_x_only_public(&full_public), public);
+			assert_eq!(local_compute_bip340_signature(&secret, &message, &aux_rand).unwrap(), signature);
+			assert_eq!(verify_bip340_signature(&public, &signature, &message), Ok(true));
+		}
+
+		// verification vectors from the same file
+		let verification_vectors = [
+			("d69c3509bb99e412e68b0fe8544e72837dfa30746d8be2aa65975f29d22dc7b9",
+				"4df3c3f68fcc83b27e9d42c90431a72499f17875c81a599b566c9889b9696703",
+				"00000000000000000000003b78ce563f89a0ed9414f5aa28ad0d96d6795f9c6376afb1548af603b3eb45c9f8207dee1060cb71c04e80f593060b07d28308d7f4",
+				true),
+			// public key not on the curve
+			("eefdea4cdb677750a420fee807eacf21eb9898ae79b9768766e4faa04a2d4a34",
+				"243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+				"6cff5c3ba86c69ea4b7376f31a9bcb4f74c1976089b2d9963da2e5543e17776969e89b4c5564d00349106b8497785dd7d1d713a8ae82b32fa79d5f7fc407d39b",
+				false),
+			// has_even_y(R) is false
+			("dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+				"243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+				"fff97bd5755eeea420453a14355235d382f6472f8568a18b2f057a14602975563cc27944640ac607cd107ae10923d9ef7a73c643e166be5ebeafa34b1ac553e2",
+				false),
+		];
+		for &(public, message, signature, is_valid) in &verification_vectors {
+			let public: H256 = public.parse().unwrap();
+			let message: H256 = message.parse().unwrap();
+			let signature: (Secret, Secret) = (signature[..64].parse().unwrap(), signature[64..].parse().unwrap());
+			assert_eq!(verify_bip340_signature(&public, &signature, &message), Ok(is_valid));
+		}
+	}
+
+	#[test]
+	fn full_bip340_signature_math_session() {
+		let test_cases = [(0, 1), (0, 2), (1, 2), (1, 3), (2, 3), (1, 4), (2, 4), (3, 4), (1, 5), (2, 5), (3, 5), (4, 5)];
+		for &(t, n) in &test_cases {
+			let message_hash: H256 = "0000000000000000000000000000000000000000000000000000000000000042".parse().unwrap();
+
+			// all nodes share master secret key && every node knows master public key
+			let artifacts = run_key_generation(t, n, None, None);
+
+			// select t+1 nodes for signing session and run DKG to generate one-time secret key (nonce)
+			let n = t + 1;
+			let id_numbers = artifacts.id_numbers.iter().cloned().take(n).collect();
+			let one_time_artifacts = run_key_generation(t, n, Some(id_numbers), None);
+
+			// compute signature shares && combine them
+			let challenge = compute_bip340_challenge(&one_time_artifacts.joint_public, &artifacts.joint_public, &message_hash).unwrap();
+			let partial_signatures: Vec<_> = (0..n)
+				.map(|i| compute_bip340_signature_share(
+					t,
+					&challenge,
+					&one_time_artifacts.joint_public,
+					&one_time_artifacts.polynoms1[i][0],
+					&artifacts.joint_public,
+					&artifacts.secret_shares[i],
+					&artifacts.id_numbers[i],
+					artifacts.id_numbers.iter()
+						.enumerate()
+						.filter(|&(j, _)| i != j)
+						.map(|(_, n)| n)
+						.take(t)
+				).unwrap())
+				.collect();
+			for i in 0..n {
+				let other_id_numbers = || artifacts.id_numbers.iter().take(n).enumerate().filter(|&(j, _)| j != i).map(|(_, n)| n);
+				let public_share = compute_public_share(&artifacts.secret_shares[i]).unwrap();
+				assert!(check_bip340_signature_share(t, &challenge, &partial_signatures[i], &one_time_artifacts.joint_public,
+					&artifacts.joint_public, &public_share, &one_time_artifacts.public_shares[i], &artifacts.id_numbers[i],
+					other_id_numbers()).unwrap());
+				assert!(!check_bip340_signature_share(t, &challenge, &generate_random_scalar().unwrap(), &one_time_artifacts.joint_public,
+					&artifacts.joint_public, &public_share, &one_time_artifacts.public_shares[i], &artifacts.id_numbers[i],
+					other_id_numbers()).unwrap());
+			}
+			let signature = compute_bip340_signature(&one_time_artifacts.joint_public, partial_signatures.iter()).unwrap();
+
+			// === verify signature ===
+			let public_x = bip340_x_only_public(&artifacts.joint_public);
+			assert_eq!(verify_bip340_signature(&public_x, &signature, &message_hash), Ok(true));
+			assert_eq!(verify_bip340_signature(&public_x, &signature, &Default::default()), Ok(false));
+		}
+	}
+
 	#[test]
 	fn full_ecdsa_signature_math_session() {
 		let test_cases = [(2, 5), (2, 6), (3, 11), (4, 11)];
@@ -985,6 +1546,73 @@ pub mod tests {
 		}
 	}
 
+	#[test]
+	fn full_ecdsa_mta_signature_math_session() {
+		let test_cases = [(0, 1), (1, 2), (2, 3), (2, 5), (4, 5)];
+		for &(t, n) in &test_cases {
+			let message_hash: H256 = H256::random();
+			let message_hash_scalar = to_scalar(message_hash.clone()).unwrap();
+
+			// generate secret key shares && select t + 1 signers
+			let artifacts = run_key_generation(t, n, None, None);
+			let signers: Vec<_> = (0..t + 1).collect();
+
+			// every signer computes additive share of secret && generates random inv_nonce and gamma shares
+			let secret_shares: Vec<_> = signers.iter().map(|&i| compute_ecdsa_mta_secret_share(&artifacts.secret_shares[i],
+				&artifacts.id_numbers[i], signers.iter().filter(|&&j| j != i).map(|&j| &artifacts.id_numbers[j])).unwrap()).collect();
+			let inv_nonce_shares: Vec<_> = signers.iter().map(|_| generate_random_scalar().unwrap()).collect();
+			let gamma_shares: Vec<_> = signers.iter().map(|_| generate_random_scalar().unwrap()).collect();
+			let paillier_key_pairs: Vec<_> = signers.iter().map(|_| math_paillier::PaillierKeyPair::generate().unwrap()).collect();
+
+			// every pair of signers converts multiplicative shares into additive shares
+			let mut delta_shares: Vec<_> = signers.iter().map(|&i| compute_secret_mul(&inv_nonce_shares[i], &gamma_shares[i]).unwrap()).collect();
+			let mut sigma_shares: Vec<_> = signers.iter().map(|&i| compute_secret_mul(&inv_nonce_shares[i], &secret_shares[i]).unwrap()).collect();
+			for &i in &signers {
+				let paillier_public = paillier_key_pairs[i].public();
+				let encrypted_inv_nonce_share = math_paillier::encrypt_secret(paillier_public, &inv_nonce_shares[i]).unwrap();
+				let ciphertext = encrypted_inv_nonce_share.ciphertext();
+				for j in signers.iter().cloned().filter(|&j| j != i) {
+					let proof = math_paillier::prove_mta_request(&paillier_key_pairs[i], &encrypted_inv_nonce_share,
+						paillier_key_pairs[j].public(), &[i as u8]).unwrap();
+					math_paillier::verify_mta_request(paillier_public, paillier_key_pairs[j].public(), &ciphertext, &proof, &[i as u8]).unwrap();
+
+					let (response, proof, beta) = math_paillier::compute_mta_response(paillier_public, &ciphertext, &gamma_shares[j], &[j as u8]).unwrap();
+					let alpha = math_paillier::compute_mta_share(&paillier_key_pairs[i], &encrypted_inv_nonce_share, &response, &proof, &[j as u8]).unwrap();
+					delta_shares[i].add(&alpha).unwrap();
+					delta_shares[j].add(&beta).unwrap();
+
+					let (response, proof, nu) = math_paillier::compute_mta_response(paillier_public, &ciphertext, &secret_shares[j], &[j as u8]).unwrap();
+					let mu = math_paillier::compute_mta_share(&paillier_key_pairs[i], &encrypted_inv_nonce_share, &response, &proof, &[j as u8]).unwrap();
+					sigma_shares[i].add(&mu).unwrap();
+					sigma_shares[j].add(&nu).unwrap();
+				}
+			}
+
+			// compute nonce public
+			let gamma_publics: Vec<_> = gamma_shares.iter().map(|s| compute_public_share(s).unwrap()).collect();
+			let gamma_public = compute_public_sum(gamma_publics.iter()).unwrap();
+			let inversed_nonce_coeff = compute_ecdsa_mta_inversed_nonce_coeff(delta_shares.iter()).unwrap();
+			let nonce_public = compute_ecdsa_mta_nonce_public(&gamma_public, &inversed_nonce_coeff).unwrap();
+			let signature_r = compute_ecdsa_r(&nonce_public).unwrap();
+
+			// compute signature
+			let signature_s_shares: Vec<_> = signers.iter().map(|&i| compute_ecdsa_s_share(
+				&inv_nonce_shares[i],
+				&sigma_shares[i],
+				&signature_r,
+				&message_hash_scalar,
+			).unwrap()).collect();
+			let signature_s = compute_secret_sum(signature_s_shares.iter()).unwrap();
+
+			// check signature
+			let signature_actual = serialize_ecdsa_signature(&nonce_public, signature_r, signature_s);
+			let joint_secret = compute_joint_secret(artifacts.polynoms1.iter().map(|p| &p[0])).unwrap();
+			let joint_secret_pair = KeyPair::from_secret(joint_secret).unwrap();
+			assert_eq!(recover(&signature_actual, &message_hash).unwrap(), *joint_secret_pair.public());
+			assert!(verify_public(joint_secret_pair.public(), &signature_actual, &message_hash).unwrap());
+		}
+	}
+
 	#[test]
 	fn full_generation_math_session_with_refreshing_shares() {
 		let test_cases = vec![(1, 4), (6, 10)];
@@ -1140,4 +1768,252 @@ pub mod tests {
 			Secret::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap()
 		);
 	}
+
+	#[test]
+	fn node_public_shares_are_computed_from_share_proofs() {
+		let test_cases = [(0, 2), (1, 3), (2, 5), (3, 7)];
+		for &(t, n) in &test_cases {
+			let artifacts = run_key_generation(t, n, None, None);
+			let share_proofs: Vec<_> = artifacts.polynoms1.iter().map(|p| prepare_share_proof(p).unwrap()).collect();
+			for i in 0..n {
+				assert_eq!(compute_node_public_share(&artifacts.id_numbers[i], share_proofs.iter().map(|p| &p[..])).unwrap(),
+					compute_public_share(&artifacts.secret_shares[i]).unwrap());
+			}
+		}
+	}
+
+	#[test]
+	fn node_shadow_point_is_verified_with_dleq_proof() {
+		let test_cases = [(0, 2), (1, 3), (2, 5), (3, 7)];
+		for &(t, n) in &test_cases {
+			let artifacts = run_key_generation(t, n, None, None);
+			let access_key = generate_random_scalar().unwrap();
+			let common_point = generate_random_point().unwrap();
+			for i in 0..t + 1 {
+				let other_id_numbers = || artifacts.id_numbers.iter().enumerate().filter(|&(j, _)| j != i).take(t).map(|(_, id_number)| id_number);
+				let node_shadow = compute_node_shadow(&artifacts.secret_shares[i], &artifacts.id_numbers[i], other_id_numbers()).unwrap();
+				let (node_shadow_point, _) = compute_node_shadow_point(&access_key, &common_point, &node_shadow, None).unwrap();
+				let node_shadow_public = compute_node_shadow_public(&access_key, &compute_public_share(&artifacts.secret_shares[i]).unwrap(),
+					&artifacts.id_numbers[i], other_id_numbers()).unwrap();
+
+				// valid proof is accepted
+				let proof = compute_dleq_proof(&compute_secret_mul(&node_shadow, &access_key).unwrap(), &common_point).unwrap();
+				assert!(verify_dleq_proof(&node_shadow_public, &common_point, &node_shadow_point, &proof).unwrap());
+
+				// proof of other shadow point is rejected
+				let (other_node_shadow_point, _) = compute_node_shadow_point(&access_key, &common_point,
+					&generate_random_scalar().unwrap(), None).unwrap();
+				assert!(!verify_dleq_proof(&node_shadow_public, &common_point, &other_node_shadow_point, &proof).unwrap());
+
+				// shadow point, computed for shadow decryption, is verified with shadow decryption proof
+				let decrypt_shadow = generate_random_scalar().unwrap();
+				let (node_shadow_point, _) = compute_node_shadow_point(&access_key, &common_point, &node_shadow,
+					Some(decrypt_shadow.clone())).unwrap();
+				let proof = compute_shadow_decryption_proof(&access_key, &common_point, &node_shadow, &decrypt_shadow).unwrap();
+				assert!(verify_shadow_decryption_proof(&node_shadow_public, &common_point, &node_shadow_point, &proof).unwrap());
+				assert!(!verify_shadow_decryption_proof(&node_shadow_public, &common_point, &other_node_shadow_point, &proof).unwrap());
+
+				// proof, computed with other node shadow, is rejected
+				let other_proof = compute_shadow_decryption_proof(&access_key, &common_point, &generate_random_scalar().unwrap(),
+					&decrypt_shadow).unwrap();
+				assert!(!verify_shadow_decryption_proof(&node_shadow_public, &common_point, &node_shadow_point, &other_proof).unwrap());
+			}
+		}
+	}
+
+	#[test]
+	fn child_key_is_derived_from_tweaked_shares() {
+		let test_cases = [(0, 2), (1, 3), (2, 5), (3, 7)];
+		for &(t, n) in &test_cases {
+			let artifacts = run_key_generation(t, n, None, None);
+			let joint_secret = compute_joint_secret(artifacts.polynoms1.iter().map(|p| &p[0])).unwrap();
+
+			// every node tweaks its own share && public shares of other nodes
+			let (tweak, child_public) = compute_child_key_tweak(&artifacts.joint_public, &[0, 42, 7]).unwrap();
+			let tweak_public = compute_public_share(&tweak).unwrap();
+			let child_secret_shares: Vec<_> = artifacts.secret_shares.iter()
+				.map(|s| compute_secret_sum(vec![s.clone(), tweak.clone()].iter()).unwrap())
+				.collect();
+			let child_public_shares: Vec<_> = artifacts.secret_shares.iter()
+				.map(|s| compute_public_sum(vec![compute_public_share(s).unwrap(), tweak_public.clone()].iter()).unwrap())
+				.collect();
+
+			// t + 1 tweaked shares are restoring tweaked joint secret, which corresponds to child public
+			let child_secret = compute_joint_secret_from_shares(t,
+				&child_secret_shares.iter().take(t + 1).collect::<Vec<_>>(),
+				&artifacts.id_numbers.iter().take(t + 1).collect::<Vec<_>>()).unwrap();
+			assert_eq!(child_secret, compute_secret_sum(vec![joint_secret, tweak].iter()).unwrap());
+			assert_eq!(compute_public_share(&child_secret).unwrap(), child_public);
+			for i in 0..n {
+				assert_eq!(compute_public_share(&child_secret_shares[i]).unwrap(), child_public_shares[i]);
+			}
+
+			// data, encrypted with child public, is decrypted using tweaked shares
+			let document_secret_plain = generate_random_point().unwrap();
+			let (document_secret_decrypted, _) = do_encryption_and_decryption(t, &child_public, &artifacts.id_numbers,
+				&child_secret_shares, None, document_secret_plain.clone());
+			assert_eq!(document_secret_plain, document_secret_decrypted);
+		}
+	}
+
+	#[test]
+	fn child_key_derivation_is_composable() {
+		let public = generate_random_point().unwrap();
+		let (tweak1, child_public1) = compute_child_key_tweak(&public, &[1]).unwrap();
+		let (tweak2, child_public2) = compute_child_key_tweak(&child_public1, &[2]).unwrap();
+		let (tweak12, child_public12) = compute_child_key_tweak(&public, &[1, 2]).unwrap();
+		assert_eq!(child_public12, child_public2);
+		assert_eq!(tweak12, compute_secret_sum(vec![tweak1, tweak2].iter()).unwrap());
+
+		// different paths are leading to different keys
+		assert!(compute_child_key_tweak(&public, &[2, 1]).unwrap().1 != child_public12);
+		// empty path is leading to the same key
+		assert_eq!(compute_child_key_tweak(&public, &[]).unwrap(), (zero_scalar(), public));
+	}
+
+	#[test]
+	fn hardened_child_key_derivation_is_rejected() {
+		let public = generate_random_point().unwrap();
+		assert_eq!(compute_child_key_tweak(&public, &[0, HARDENED_KEY_INDEX]), Err(Error::InvalidDerivationPath));
+		assert!(compute_child_key_tweak(&public, &[0, HARDENED_KEY_INDEX - 1]).is_ok());
+	}
+
+	#[test]
+	fn ecies_shared_secret_is_computed_using_key_shares() {
+		let test_cases = [(0, 2), (1, 3), (2, 5), (3, 7)];
+		for &(t, n) in &test_cases {
+			let artifacts = run_key_generation(t, n, None, None);
+			let joint_secret = compute_joint_secret(artifacts.polynoms1.iter().map(|p| &p[0])).unwrap();
+
+			// ciphertext is created by regular ECIES encryption: 0x04 || R || iv || cipher || mac
+			let ciphertext = encrypt(&artifacts.joint_public, &[], b"arbitrary payload").unwrap();
+			let ephemeral_public = Public::from_slice(&ciphertext[1..65]);
+
+			// R is decrypted by t + 1 nodes, using R as common point
+			let access_key = generate_random_scalar().unwrap();
+			let nodes_shadow_points: Vec<_> = (0..t + 1)
+				.map(|i| compute_node_shadow(&artifacts.secret_shares[i], &artifacts.id_numbers[i], artifacts.id_numbers.iter()
+					.enumerate()
+					.filter(|&(j, _)| j != i)
+					.take(t)
+					.map(|(_, id_number)| id_number)).unwrap())
+				.map(|s| compute_node_shadow_point(&access_key, &ephemeral_public, &s, None).unwrap().0)
+				.collect();
+			let joint_shadow_point = compute_joint_shadow_point(nodes_shadow_points.iter()).unwrap();
+			let decrypted_point = decrypt_with_joint_shadow(t, &access_key, &ephemeral_public, &joint_shadow_point).unwrap();
+
+			// X coordinate of restored point is the same secret that would be computed using joint secret
+			assert_eq!(&compute_ecdh_shared_point(&ephemeral_public, &decrypted_point).unwrap()[..32],
+				agree(&joint_secret, &ephemeral_public).unwrap().as_bytes());
+		}
+	}
+
+	#[test]
+	fn ecdh_shared_point_is_computed_using_key_shares() {
+		let test_cases = [(0, 2), (1, 3), (2, 5), (3, 7)];
+		for &(t, n) in &test_cases {
+			let artifacts = run_key_generation(t, n, None, None);
+			let joint_secret = compute_joint_secret(artifacts.polynoms1.iter().map(|p| &p[0])).unwrap();
+			let counterparty_public = generate_random_point().unwrap();
+
+			// P is decrypted by t + 1 nodes, using P as common point
+			let access_key = generate_random_scalar().unwrap();
+			let nodes_shadow_points: Vec<_> = (0..t + 1)
+				.map(|i| compute_node_shadow(&artifacts.secret_shares[i], &artifacts.id_numbers[i], artifacts.id_numbers.iter()
+					.enumerate()
+					.filter(|&(j, _)| j != i)
+					.take(t)
+					.map(|(_, id_number)| id_number)).unwrap())
+				.map(|s| compute_node_shadow_point(&access_key, &counterparty_public, &s, None).unwrap().0)
+				.collect();
+			let joint_shadow_point = compute_joint_shadow_point(nodes_shadow_points.iter()).unwrap();
+			let decrypted_point = decrypt_with_joint_shadow(t, &access_key, &counterparty_public, &joint_shadow_point).unwrap();
+
+			// shared point is the same that would be computed using joint secret
+			let mut expected_shared_point = counterparty_public.clone();
+			ec_math_utils::public_mul_secret(&mut expected_shared_point, &joint_secret).unwrap();
+			assert_eq!(compute_ecdh_shared_point(&counterparty_public, &decrypted_point).unwrap(), expected_shared_point);
+		}
+	}
+
+	#[test]
+	fn document_key_is_reencrypted_with_rotated_key() {
+		let test_cases = [(0, 2), (1, 3), (2, 5), (3, 7)];
+		for &(t, n) in &test_cases {
+			let artifacts = run_key_generation(t, n, None, None);
+			let document_secret_plain = generate_random_point().unwrap();
+			let encrypted_secret = encrypt_secret(&document_secret_plain, &artifacts.joint_public).unwrap();
+
+			// every node generates random polynom && sends its values to other nodes
+			let polynoms: Vec<_> = (0..n).map(|_| generate_random_polynom(t).unwrap()).collect();
+			let delta_shares: Vec<_> = artifacts.id_numbers.iter()
+				.map(|id_number| compute_secret_sum(polynoms.iter()
+					.map(|p| compute_polynom(p, id_number).unwrap())
+					.collect::<Vec<_>>().iter()).unwrap())
+				.collect();
+			let new_secret_shares: Vec<_> = artifacts.secret_shares.iter().zip(delta_shares.iter())
+				.map(|(s, d)| compute_secret_sum(vec![s.clone(), d.clone()].iter()).unwrap())
+				.collect();
+			let new_public = compute_public_sum(::std::iter::once(artifacts.joint_public.clone())
+				.chain(polynoms.iter().map(|p| compute_public_share(&p[0]).unwrap()))
+				.collect::<Vec<_>>().iter()).unwrap();
+
+			// every node computes its share of re-encryption point && proves it against public of its delta share
+			let reencryption_points: Vec<_> = (0..n).map(|i| {
+				let other_id_numbers = || artifacts.id_numbers.iter().enumerate().filter(move |&(j, _)| j != i).map(|(_, id_number)| id_number);
+				let (reencryption_point, reencryption_proof) = compute_node_reencryption_point(&delta_shares[i],
+					&artifacts.id_numbers[i], other_id_numbers(), &encrypted_secret.common_point).unwrap();
+				let delta_public = compute_public_sum(polynoms.iter()
+					.map(|p| compute_polynom_public(Some(&compute_public_share(&p[0]).unwrap()), &prepare_share_proof(&p[1..]).unwrap(),
+						&artifacts.id_numbers[i]).unwrap().unwrap())
+					.collect::<Vec<_>>().iter()).unwrap();
+				assert_eq!(delta_public, compute_public_share(&delta_shares[i]).unwrap());
+				assert!(verify_node_reencryption_point(&delta_public, &artifacts.id_numbers[i], other_id_numbers(),
+					&encrypted_secret.common_point, &reencryption_point, &reencryption_proof).unwrap());
+				assert!(!verify_node_reencryption_point(&delta_public, &artifacts.id_numbers[i], other_id_numbers(),
+					&encrypted_secret.common_point, &generate_random_point().unwrap(), &reencryption_proof).unwrap());
+				reencryption_point
+			}).collect();
+			let new_encrypted_point = compute_reencrypted_point(&encrypted_secret.encrypted_point, reencryption_points.iter()).unwrap();
+
+			// rotated shares correspond to rotated public && are able to decrypt re-encrypted document key
+			let new_secret = compute_joint_secret_from_shares(t,
+				&new_secret_shares.iter().take(t + 1).collect::<Vec<_>>(),
+				&artifacts.id_numbers.iter().take(t + 1).collect::<Vec<_>>()).unwrap();
+			assert_eq!(compute_public_share(&new_secret).unwrap(), new_public);
+			assert!(new_public != artifacts.joint_public);
+			assert_eq!(decrypt_with_joint_secret(&new_encrypted_point, &encrypted_secret.common_point, &new_secret).unwrap(),
+				document_secret_plain);
+		}
+	}
+
+	#[test]
+	fn polynom_values_are_verified_against_coefficients_publics() {
+		for t in 0..4 {
+			let mut polynom = generate_random_polynom(t).unwrap();
+			polynom[0] = zero_scalar();
+			let coeffs_publics = prepare_share_proof(&polynom[1..]).unwrap();
+			let id_number = generate_random_scalar().unwrap();
+			let value = compute_polynom(&polynom, &id_number).unwrap();
+			assert!(polynom_value_verification(None, &coeffs_publics, &id_number, &value).unwrap());
+			assert!(!polynom_value_verification(None, &coeffs_publics, &id_number, &generate_random_scalar().unwrap()).unwrap());
+		}
+	}
+
+	#[test]
+	fn public_shares_are_verified_against_joint_public() {
+		let test_cases = [(0, 2), (1, 3), (2, 5), (3, 7)];
+		for &(t, n) in &test_cases {
+			let artifacts = run_key_generation(t, n, None, None);
+			let id_numbers: Vec<_> = artifacts.id_numbers.iter().collect();
+			let mut public_shares: Vec<_> = artifacts.secret_shares.iter().map(|s| compute_public_share(s).unwrap()).collect();
+			assert!(public_shares_verification(t, &artifacts.joint_public, &id_numbers,
+				&public_shares.iter().collect::<Vec<_>>()).unwrap());
+
+			// single wrong public share is detected
+			public_shares[n - 1] = generate_random_point().unwrap();
+			assert!(!public_shares_verification(t, &artifacts.joint_public, &id_numbers,
+				&public_shares.iter().collect::<Vec<_>>()).unwrap());
+		}
+	}
 }
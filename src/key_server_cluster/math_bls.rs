@@ -0,0 +1,384 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BLS12-381 math, used by threshold BLS sessions.
+//! Scalars are stored in `Secret` using little-endian canonical encoding.
+//! Publics are G1 points and signatures are G2 points, both in compressed encoding (48 and 96 bytes).
+//! When BLS public is stored in `DocumentKeyShare::public`, it occupies first 48 bytes, the rest is zero.
+//! Messages are hashed to G2 using the ciphersuite of the basic BLS scheme. Proof-of-possession ciphersuite isn't used,
+//! because the joint public is never aggregated with publics of other signers.
+
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2_09::Sha256;
+use bytes::Bytes;
+use crypto::publickey::{Public, Secret};
+use ethereum_types::H256;
+use key_server_cluster::Error;
+
+/// Domain separation tag of hash-to-curve.
+const HASH_TO_CURVE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+/// Size of compressed G1 point.
+const PUBLIC_SIZE: usize = 48;
+/// Size of compressed G2 point.
+const SIGNATURE_SIZE: usize = 96;
+
+/// Convert secret to BLS12-381 scalar.
+fn to_scalar(secret: &Secret) -> Result<Scalar, Error> {
+	let mut bytes = [0u8; 32];
+	bytes.copy_from_slice(secret.as_bytes());
+	Option::from(Scalar::from_bytes(&bytes))
+		.ok_or_else(|| Error::EthKey("invalid BLS12-381 scalar".into()))
+}
+
+/// Convert BLS12-381 scalar to secret.
+fn to_secret(scalar: &Scalar) -> Secret {
+	Secret::from(scalar.to_bytes())
+}
+
+/// Decompress G1 point. Identity is rejected.
+fn to_point(public: &[u8]) -> Result<G1Affine, Error> {
+	if public.len() != PUBLIC_SIZE {
+		return Err(Error::EthKey("invalid BLS12-381 public size".into()));
+	}
+
+	let mut bytes = [0u8; PUBLIC_SIZE];
+	bytes.copy_from_slice(public);
+	Option::<G1Affine>::from(G1Affine::from_compressed(&bytes))
+		.filter(|point| !bool::from(point.is_identity()))
+		.ok_or_else(|| Error::EthKey("invalid BLS12-381 public".into()))
+}
+
+/// Compress G1 point.
+fn to_public(point: &G1Projective) -> Bytes {
+	G1Affine::from(point).to_compressed().to_vec()
+}
+
+/// Decompress G2 point.
+fn to_signature_point(signature: &[u8]) -> Result<G2Affine, Error> {
+	if signature.len() != SIGNATURE_SIZE {
+		return Err(Error::EthKey("invalid BLS12-381 signature size".into()));
+	}
+
+	let mut bytes = [0u8; SIGNATURE_SIZE];
+	bytes.copy_from_slice(signature);
+	Option::from(G2Affine::from_compressed(&bytes))
+		.ok_or_else(|| Error::EthKey("invalid BLS12-381 signature".into()))
+}
+
+/// Compress G2 point.
+fn to_signature(point: &G2Projective) -> Bytes {
+	G2Affine::from(point).to_compressed().to_vec()
+}
+
+/// Hash message to G2 point.
+fn hash_message(message_hash: &H256) -> G2Projective {
+	<G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(message_hash.as_bytes(), HASH_TO_CURVE_DST)
+}
+
+/// Convert BLS public to the form, used to store it in the key share.
+pub fn into_key_share_public(public: &[u8]) -> Result<Public, Error> {
+	to_point(public)?;
+
+	let mut key_share_public = Public::zero();
+	key_share_public.as_bytes_mut()[..PUBLIC_SIZE].copy_from_slice(public);
+	Ok(key_share_public)
+}
+
+/// Read BLS public from the key share.
+pub fn from_key_share_public(public: &Public) -> Result<Bytes, Error> {
+	if public.as_bytes()[PUBLIC_SIZE..].iter().any(|b| *b != 0) {
+		return Err(Error::EthKey("invalid BLS12-381 key share public".into()));
+	}
+
+	let public = public.as_bytes()[..PUBLIC_SIZE].to_vec();
+	to_point(&public)?;
+	Ok(public)
+}
+
+/// Check if BLS public is valid.
+pub fn public_is_valid(public: &[u8]) -> bool {
+	to_point(public).is_ok()
+}
+
+/// Generate random scalar.
+pub fn generate_random_scalar() -> Result<Secret, Error> {
+	loop {
+		let mut bytes = [0u8; 64];
+		OsRng.fill_bytes(&mut bytes);
+		let scalar = Scalar::from_bytes_wide(&bytes);
+		if scalar != Scalar::zero() {
+			return Ok(to_secret(&scalar));
+		}
+	}
+}
+
+/// Generate random polynom of threshold degree.
+pub fn generate_random_polynom(threshold: usize) -> Result<Vec<Secret>, Error> {
+	(0..threshold + 1)
+		.map(|_| generate_random_scalar())
+		.collect()
+}
+
+/// Compute value of polynom, using `node_number` as argument.
+pub fn compute_polynom(polynom: &[Secret], node_number: &Secret) -> Result<Secret, Error> {
+	debug_assert!(!polynom.is_empty());
+
+	let node_number = to_scalar(node_number)?;
+	let mut result = Scalar::zero();
+	for coeff in polynom.iter().rev() {
+		result = result * node_number + to_scalar(coeff)?;
+	}
+	Ok(to_secret(&result))
+}
+
+/// Compute public share of the secret value.
+pub fn compute_public_share(secret: &Secret) -> Result<Bytes, Error> {
+	Ok(to_public(&(G1Projective::generator() * to_scalar(secret)?)))
+}
+
+/// Compute commitments to polynom coefficients (Feldman VSS).
+pub fn compute_polynom_commitments(polynom: &[Secret]) -> Result<Vec<Bytes>, Error> {
+	polynom.iter().map(compute_public_share).collect()
+}
+
+/// Compute public of the polynom value, using commitments to polynom coefficients.
+fn compute_committed_value(node_number: &Scalar, commitments: &[Bytes]) -> Result<G1Projective, Error> {
+	let mut value = G1Projective::identity();
+	let mut power = Scalar::one();
+	for commitment in commitments {
+		value = value + to_point(commitment)? * power;
+		power = power * node_number;
+	}
+	Ok(value)
+}
+
+/// Check that secret subshare, received from other node, matches its polynom commitments.
+pub fn verify_secret_subshare(node_number: &Secret, secret_subshare: &Secret, commitments: &[Bytes]) -> Result<bool, Error> {
+	let expected = compute_committed_value(&to_scalar(node_number)?, commitments)?;
+	Ok(G1Projective::generator() * to_scalar(secret_subshare)? == expected)
+}
+
+/// Compute public share (secret_share * G1) of the node with given id number, using commitments
+/// to polynoms of all nodes, received during key generation.
+pub fn compute_node_public_share<'a, I>(node_number: &Secret, commitments: I) -> Result<Bytes, Error> where I: Iterator<Item=&'a [Bytes]> {
+	let node_number = to_scalar(node_number)?;
+	let mut public_share = G1Projective::identity();
+	for commitments in commitments {
+		public_share = public_share + compute_committed_value(&node_number, commitments)?;
+	}
+	if bool::from(public_share.is_identity()) {
+		return Err(Error::EthKey("BLS12-381 public share is identity".into()));
+	}
+	Ok(to_public(&public_share))
+}
+
+/// Compute secrets sum.
+pub fn compute_secret_sum<'a, I>(secrets: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let mut sum = Scalar::zero();
+	for secret in secrets {
+		sum = sum + to_scalar(secret)?;
+	}
+	Ok(to_secret(&sum))
+}
+
+/// Compute publics sum.
+pub fn compute_public_sum<'a, I>(publics: I) -> Result<Bytes, Error> where I: Iterator<Item=&'a Bytes> {
+	let mut sum = G1Projective::identity();
+	for public in publics {
+		sum = sum + to_point(public)?;
+	}
+	if bool::from(sum.is_identity()) {
+		return Err(Error::EthKey("BLS12-381 publics sum is identity".into()));
+	}
+	Ok(to_public(&sum))
+}
+
+/// Compute Lagrange coefficient of the node: multiplication(s[j] / (s[j] - s[i])) for every i != j.
+pub fn compute_lagrange_coeff<'a, I>(node_number: &Secret, other_nodes_numbers: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let node_number = to_scalar(node_number)?;
+	let mut coeff = Scalar::one();
+	for other_node_number in other_nodes_numbers {
+		let other_node_number = to_scalar(other_node_number)?;
+		let denominator: Option<Scalar> = (other_node_number - node_number).invert().into();
+		let denominator = denominator.ok_or_else(|| Error::EthKey("duplicate BLS12-381 node number".into()))?;
+		coeff = coeff * other_node_number * denominator;
+	}
+	Ok(to_secret(&coeff))
+}
+
+/// Compute signature share: secret_share * H(M). Lagrange coefficient is applied when shares are combined.
+pub fn compute_signature_share(node_secret_share: &Secret, message_hash: &H256) -> Result<Bytes, Error> {
+	Ok(to_signature(&(hash_message(message_hash) * to_scalar(node_secret_share)?)))
+}
+
+/// Combine signature shares of t + 1 nodes into the signature, using Lagrange interpolation.
+pub fn compute_signature(signature_shares: &[(&Secret, &Bytes)]) -> Result<Bytes, Error> {
+	let mut signature = G2Projective::identity();
+	for (i, &(node_number, signature_share)) in signature_shares.iter().enumerate() {
+		let other_nodes_numbers = signature_shares.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, &(n, _))| n);
+		let lagrange_coeff = to_scalar(&compute_lagrange_coeff(node_number, other_nodes_numbers)?)?;
+		signature = signature + to_signature_point(signature_share)? * lagrange_coeff;
+	}
+	Ok(to_signature(&signature))
+}
+
+/// Locally compute BLS signature (for test purposes only).
+#[cfg(test)]
+pub fn local_compute_signature(secret: &Secret, message_hash: &H256) -> Result<Bytes, Error> {
+	compute_signature_share(secret, message_hash)
+}
+
+/// Recover joint secret from t + 1 secret shares (for test purposes only).
+#[cfg(test)]
+pub fn compute_joint_secret_from_shares(secret_shares: &[&Secret], id_numbers: &[&Secret]) -> Result<Secret, Error> {
+	debug_assert_eq!(secret_shares.len(), id_numbers.len());
+
+	let mut joint_secret = Scalar::zero();
+	for i in 0..secret_shares.len() {
+		let other_nodes_numbers = id_numbers.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, n)| *n);
+		let lagrange_coeff = to_scalar(&compute_lagrange_coeff(id_numbers[i], other_nodes_numbers)?)?;
+		joint_secret = joint_secret + lagrange_coeff * to_scalar(secret_shares[i])?;
+	}
+	Ok(to_secret(&joint_secret))
+}
+
+/// Verify signature share, computed by the node: e(secret_share * G1, H(M)) == e(G1, S).
+pub fn verify_signature_share(public_share: &[u8], signature_share: &[u8], message_hash: &H256) -> Result<bool, Error> {
+	verify_signature(public_share, signature_share, message_hash)
+}
+
+/// Verify BLS signature: e(P, H(M)) == e(G1, S).
+pub fn verify_signature(public: &[u8], signature: &[u8], message_hash: &H256) -> Result<bool, Error> {
+	let public = to_point(public)?;
+	let signature = to_signature_point(signature)?;
+	let message_point = G2Affine::from(hash_message(message_hash));
+	Ok(pairing(&public, &message_point) == pairing(&G1Affine::generator(), &signature))
+}
+
+#[cfg(test)]
+pub mod tests {
+	use bytes::Bytes;
+	use ethereum_types::H256;
+	use crypto::publickey::Secret;
+	use super::*;
+
+	/// Generate shares of the joint secret, as if it has been generated by DKG.
+	/// Returns (id_numbers, secret_shares, joint_secret, joint_public).
+	pub fn generate_shares(t: usize, n: usize) -> (Vec<Secret>, Vec<Secret>, Secret, Bytes) {
+		let id_numbers: Vec<_> = (0..n).map(|_| generate_random_scalar().unwrap()).collect();
+		let polynoms: Vec<_> = (0..n).map(|_| generate_random_polynom(t).unwrap()).collect();
+		let secret_shares: Vec<_> = id_numbers.iter()
+			.map(|id_number| {
+				let subshares: Vec<_> = polynoms.iter().map(|p| compute_polynom(p, id_number).unwrap()).collect();
+				compute_secret_sum(subshares.iter()).unwrap()
+			})
+			.collect();
+		let joint_secret = compute_secret_sum(polynoms.iter().map(|p| &p[0])).unwrap();
+		let joint_public = compute_public_share(&joint_secret).unwrap();
+		(id_numbers, secret_shares, joint_secret, joint_public)
+	}
+
+	#[test]
+	fn subshares_are_verified_with_commitments() {
+		let polynom = generate_random_polynom(3).unwrap();
+		let commitments = compute_polynom_commitments(&polynom).unwrap();
+		let id_number = generate_random_scalar().unwrap();
+		let subshare = compute_polynom(&polynom, &id_number).unwrap();
+		assert_eq!(verify_secret_subshare(&id_number, &subshare, &commitments), Ok(true));
+
+		let wrong_subshare = compute_polynom(&polynom, &generate_random_scalar().unwrap()).unwrap();
+		assert_eq!(verify_secret_subshare(&id_number, &wrong_subshare, &commitments), Ok(false));
+	}
+
+	#[test]
+	fn joint_public_is_sum_of_polynom_commitments() {
+		let polynoms: Vec<_> = (0..4).map(|_| generate_random_polynom(2).unwrap()).collect();
+		let commitments: Vec<_> = polynoms.iter().map(|p| compute_polynom_commitments(p).unwrap()).collect();
+		let joint_secret = compute_secret_sum(polynoms.iter().map(|p| &p[0])).unwrap();
+		assert_eq!(compute_public_sum(commitments.iter().map(|c| &c[0])).unwrap(), compute_public_share(&joint_secret).unwrap());
+	}
+
+	#[test]
+	fn local_signature_is_verified() {
+		let secret = generate_random_scalar().unwrap();
+		let message_hash = H256::random();
+		let signature = local_compute_signature(&secret, &message_hash).unwrap();
+		let public = compute_public_share(&secret).unwrap();
+		assert_eq!(verify_signature(&public, &signature, &message_hash), Ok(true));
+		assert_eq!(verify_signature(&public, &signature, &H256::random()), Ok(false));
+	}
+
+	#[test]
+	fn threshold_signature_is_verified() {
+		let test_cases = [(0, 1), (1, 3), (2, 5), (3, 5), (4, 5)];
+		for &(t, n) in &test_cases {
+			let (id_numbers, secret_shares, joint_secret, joint_public) = generate_shares(t, n);
+			let message_hash = H256::random();
+
+			// any t + 1 nodes are able to compute the signature
+			for offset in 0..n - t {
+				let signature_shares: Vec<_> = (offset..offset + t + 1)
+					.map(|i| compute_signature_share(&secret_shares[i], &message_hash).unwrap())
+					.collect();
+				let signature = compute_signature(&(offset..offset + t + 1)
+					.map(|i| (&id_numbers[i], &signature_shares[i - offset]))
+					.collect::<Vec<_>>()).unwrap();
+				assert_eq!(verify_signature(&joint_public, &signature, &message_hash), Ok(true));
+				assert_eq!(signature, local_compute_signature(&joint_secret, &message_hash).unwrap());
+			}
+		}
+	}
+
+	#[test]
+	fn signature_shares_are_verified_with_public_shares() {
+		let polynoms: Vec<_> = (0..3).map(|_| generate_random_polynom(1).unwrap()).collect();
+		let commitments: Vec<_> = polynoms.iter().map(|p| compute_polynom_commitments(p).unwrap()).collect();
+		let id_number = generate_random_scalar().unwrap();
+		let secret_share = compute_secret_sum(polynoms.iter()
+			.map(|p| compute_polynom(p, &id_number).unwrap())
+			.collect::<Vec<_>>()
+			.iter()).unwrap();
+		let public_share = compute_node_public_share(&id_number, commitments.iter().map(|c| c.as_slice())).unwrap();
+		assert_eq!(public_share, compute_public_share(&secret_share).unwrap());
+
+		let message_hash = H256::random();
+		let signature_share = compute_signature_share(&secret_share, &message_hash).unwrap();
+		assert_eq!(verify_signature_share(&public_share, &signature_share, &message_hash), Ok(true));
+		let wrong_signature_share = compute_signature_share(&generate_random_scalar().unwrap(), &message_hash).unwrap();
+		assert_eq!(verify_signature_share(&public_share, &wrong_signature_share, &message_hash), Ok(false));
+	}
+
+	#[test]
+	fn lagrange_coeffs_restore_joint_secret() {
+		let (id_numbers, secret_shares, joint_secret, _) = generate_shares(2, 4);
+		let restored = compute_joint_secret_from_shares(
+			&secret_shares[1..].iter().collect::<Vec<_>>(),
+			&id_numbers[1..].iter().collect::<Vec<_>>(),
+		).unwrap();
+		assert_eq!(restored, joint_secret);
+	}
+
+	#[test]
+	fn key_share_public_is_converted() {
+		let public = compute_public_share(&generate_random_scalar().unwrap()).unwrap();
+		assert_eq!(from_key_share_public(&into_key_share_public(&public).unwrap()), Ok(public));
+		assert!(from_key_share_public(&Public::from_low_u64_be(1)).is_err());
+		assert!(into_key_share_public(&[0u8; 48]).is_err());
+	}
+}
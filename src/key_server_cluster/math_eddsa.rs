@@ -0,0 +1,333 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ed25519 math, used by threshold EdDSA sessions.
+//! Scalars (mod group order l) are stored in `Secret` using little-endian canonical encoding.
+//! Points are stored in `H256` using compressed Edwards Y encoding. When Ed25519 public is
+//! stored in `DocumentKeyShare::public`, it occupies first 32 bytes, the rest is zero.
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand::rngs::OsRng;
+use sha2::Sha512;
+use crypto::publickey::{Public, Secret};
+use ethereum_types::H256;
+use key_server_cluster::Error;
+
+/// Convert secret to Ed25519 scalar.
+fn to_scalar(secret: &Secret) -> Result<Scalar, Error> {
+	let mut bytes = [0u8; 32];
+	bytes.copy_from_slice(secret.as_bytes());
+	Scalar::from_canonical_bytes(bytes)
+		.ok_or_else(|| Error::EthKey("invalid Ed25519 scalar".into()))
+}
+
+/// Convert Ed25519 scalar to secret.
+fn to_secret(scalar: &Scalar) -> Secret {
+	Secret::from(scalar.to_bytes())
+}
+
+/// Decompress Ed25519 point. Points of small order are rejected.
+fn to_point(public: &H256) -> Result<EdwardsPoint, Error> {
+	CompressedEdwardsY::from_slice(public.as_bytes())
+		.decompress()
+		.filter(|point| !point.is_small_order())
+		.ok_or_else(|| Error::EthKey("invalid Ed25519 point".into()))
+}
+
+/// Compress Ed25519 point.
+fn to_public(point: &EdwardsPoint) -> H256 {
+	H256::from(point.compress().to_bytes())
+}
+
+/// Convert Ed25519 public to the form, used to store it in the key share.
+pub fn into_key_share_public(public: &H256) -> Public {
+	let mut key_share_public = Public::zero();
+	key_share_public.as_bytes_mut()[..32].copy_from_slice(public.as_bytes());
+	key_share_public
+}
+
+/// Read Ed25519 public from the key share.
+pub fn from_key_share_public(public: &Public) -> Result<H256, Error> {
+	if public.as_bytes()[32..].iter().any(|b| *b != 0) {
+		return Err(Error::EthKey("invalid Ed25519 key share public".into()));
+	}
+
+	let public = H256::from_slice(&public.as_bytes()[..32]);
+	to_point(&public)?;
+	Ok(public)
+}
+
+/// Check if Ed25519 point is valid.
+pub fn public_is_valid(public: &H256) -> bool {
+	to_point(public).is_ok()
+}
+
+/// Generate random scalar.
+pub fn generate_random_scalar() -> Result<Secret, Error> {
+	loop {
+		let scalar = Scalar::random(&mut OsRng);
+		if scalar != Scalar::zero() {
+			return Ok(to_secret(&scalar));
+		}
+	}
+}
+
+/// Generate random polynom of threshold degree.
+pub fn generate_random_polynom(threshold: usize) -> Result<Vec<Secret>, Error> {
+	(0..threshold + 1)
+		.map(|_| generate_random_scalar())
+		.collect()
+}
+
+/// Compute value of polynom, using `node_number` as argument.
+pub fn compute_polynom(polynom: &[Secret], node_number: &Secret) -> Result<Secret, Error> {
+	debug_assert!(!polynom.is_empty());
+
+	let node_number = to_scalar(node_number)?;
+	let mut result = Scalar::zero();
+	for coeff in polynom.iter().rev() {
+		result = result * node_number + to_scalar(coeff)?;
+	}
+	Ok(to_secret(&result))
+}
+
+/// Compute public share of the secret value.
+pub fn compute_public_share(secret: &Secret) -> Result<H256, Error> {
+	Ok(to_public(&(&to_scalar(secret)? * &ED25519_BASEPOINT_TABLE)))
+}
+
+/// Compute commitments to polynom coefficients (Feldman VSS).
+pub fn compute_polynom_commitments(polynom: &[Secret]) -> Result<Vec<H256>, Error> {
+	polynom.iter().map(compute_public_share).collect()
+}
+
+/// Check that secret subshare, received from other node, matches its polynom commitments.
+pub fn verify_secret_subshare(node_number: &Secret, secret_subshare: &Secret, commitments: &[H256]) -> Result<bool, Error> {
+	let node_number = to_scalar(node_number)?;
+	let mut expected = EdwardsPoint::identity();
+	let mut power = Scalar::one();
+	for commitment in commitments {
+		expected = expected + to_point(commitment)? * power;
+		power = power * node_number;
+	}
+
+	Ok(&to_scalar(secret_subshare)? * &ED25519_BASEPOINT_TABLE == expected)
+}
+
+/// Compute secrets sum.
+pub fn compute_secret_sum<'a, I>(secrets: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let mut sum = Scalar::zero();
+	for secret in secrets {
+		sum = sum + to_scalar(secret)?;
+	}
+	Ok(to_secret(&sum))
+}
+
+/// Compute publics sum.
+pub fn compute_public_sum<'a, I>(publics: I) -> Result<H256, Error> where I: Iterator<Item=&'a H256> {
+	let mut sum = EdwardsPoint::identity();
+	for public in publics {
+		sum = sum + to_point(public)?;
+	}
+	if sum.is_small_order() {
+		return Err(Error::EthKey("Ed25519 publics sum has small order".into()));
+	}
+	Ok(to_public(&sum))
+}
+
+/// Compute Lagrange coefficient of the node: multiplication(s[j] / (s[j] - s[i])) for every i != j.
+pub fn compute_lagrange_coeff<'a, I>(node_number: &Secret, other_nodes_numbers: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let node_number = to_scalar(node_number)?;
+	let mut coeff = Scalar::one();
+	for other_node_number in other_nodes_numbers {
+		let other_node_number = to_scalar(other_node_number)?;
+		let denominator = other_node_number - node_number;
+		if denominator == Scalar::zero() {
+			return Err(Error::EthKey("duplicate Ed25519 node number".into()));
+		}
+		coeff = coeff * other_node_number * denominator.invert();
+	}
+	Ok(to_secret(&coeff))
+}
+
+/// Compute EdDSA challenge: SHA512(R || A || M) mod l.
+pub fn combine_message_hash_with_public(nonce_public: &H256, public: &H256, message_hash: &H256) -> Result<Secret, Error> {
+	let mut data = Vec::with_capacity(96);
+	data.extend_from_slice(nonce_public.as_bytes());
+	data.extend_from_slice(public.as_bytes());
+	data.extend_from_slice(message_hash.as_bytes());
+	Ok(to_secret(&Scalar::hash_from_bytes::<Sha512>(&data)))
+}
+
+/// Compute signature share: lagrange_coeff * (nonce_share + challenge * secret_share).
+pub fn compute_signature_share<'a, I>(challenge: &Secret, nonce_share: &Secret, node_secret_share: &Secret, node_number: &Secret, other_nodes_numbers: I)
+	-> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	let lagrange_coeff = to_scalar(&compute_lagrange_coeff(node_number, other_nodes_numbers)?)?;
+	let share = to_scalar(nonce_share)? + to_scalar(challenge)? * to_scalar(node_secret_share)?;
+	Ok(to_secret(&(lagrange_coeff * share)))
+}
+
+/// Compute signature S-portion from signature shares.
+pub fn compute_signature<'a, I>(signature_shares: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
+	compute_secret_sum(signature_shares)
+}
+
+/// Locally compute EdDSA signature (for test purposes only).
+#[cfg(test)]
+pub fn local_compute_signature(nonce: &Secret, secret: &Secret, message_hash: &H256) -> Result<(H256, Secret), Error> {
+	let nonce_public = compute_public_share(nonce)?;
+	let public = compute_public_share(secret)?;
+	let challenge = combine_message_hash_with_public(&nonce_public, &public, message_hash)?;
+	let signature_s = to_scalar(nonce)? + to_scalar(&challenge)? * to_scalar(secret)?;
+	Ok((nonce_public, to_secret(&signature_s)))
+}
+
+/// Recover joint secret from t + 1 secret shares (for test purposes only).
+#[cfg(test)]
+pub fn compute_joint_secret_from_shares(secret_shares: &[&Secret], id_numbers: &[&Secret]) -> Result<Secret, Error> {
+	debug_assert_eq!(secret_shares.len(), id_numbers.len());
+
+	let mut joint_secret = Scalar::zero();
+	for i in 0..secret_shares.len() {
+		let other_nodes_numbers = id_numbers.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, n)| *n);
+		let lagrange_coeff = to_scalar(&compute_lagrange_coeff(id_numbers[i], other_nodes_numbers)?)?;
+		joint_secret = joint_secret + lagrange_coeff * to_scalar(secret_shares[i])?;
+	}
+	Ok(to_secret(&joint_secret))
+}
+
+/// Verify EdDSA signature: S * B == R + challenge * A.
+pub fn verify_signature(public: &H256, signature: &(H256, Secret), message_hash: &H256) -> Result<bool, Error> {
+	let challenge = to_scalar(&combine_message_hash_with_public(&signature.0, public, message_hash)?)?;
+	let expected = to_point(&signature.0)? + to_point(public)? * challenge;
+	Ok(&to_scalar(&signature.1)? * &ED25519_BASEPOINT_TABLE == expected)
+}
+
+/// Serialize EdDSA signature into standard 64-bytes form: R || S.
+pub fn serialize_signature(signature: &(H256, Secret)) -> [u8; 64] {
+	let mut serialized = [0u8; 64];
+	serialized[..32].copy_from_slice(signature.0.as_bytes());
+	serialized[32..].copy_from_slice(signature.1.as_bytes());
+	serialized
+}
+
+#[cfg(test)]
+pub mod tests {
+	use ethereum_types::H256;
+	use crypto::publickey::Secret;
+	use super::*;
+
+	/// Generate shares of the joint secret, as if it has been generated by DKG.
+	/// Returns (id_numbers, secret_shares, joint_secret, joint_public).
+	pub fn generate_shares(t: usize, n: usize) -> (Vec<Secret>, Vec<Secret>, Secret, H256) {
+		let id_numbers: Vec<_> = (0..n).map(|_| generate_random_scalar().unwrap()).collect();
+		let polynoms: Vec<_> = (0..n).map(|_| generate_random_polynom(t).unwrap()).collect();
+		let secret_shares: Vec<_> = id_numbers.iter()
+			.map(|id_number| {
+				let subshares: Vec<_> = polynoms.iter().map(|p| compute_polynom(p, id_number).unwrap()).collect();
+				compute_secret_sum(subshares.iter()).unwrap()
+			})
+			.collect();
+		let joint_secret = compute_secret_sum(polynoms.iter().map(|p| &p[0])).unwrap();
+		let joint_public = compute_public_share(&joint_secret).unwrap();
+		(id_numbers, secret_shares, joint_secret, joint_public)
+	}
+
+	#[test]
+	fn subshares_are_verified_with_commitments() {
+		let polynom = generate_random_polynom(3).unwrap();
+		let commitments = compute_polynom_commitments(&polynom).unwrap();
+		let id_number = generate_random_scalar().unwrap();
+		let subshare = compute_polynom(&polynom, &id_number).unwrap();
+		assert_eq!(verify_secret_subshare(&id_number, &subshare, &commitments), Ok(true));
+
+		let wrong_subshare = compute_polynom(&polynom, &generate_random_scalar().unwrap()).unwrap();
+		assert_eq!(verify_secret_subshare(&id_number, &wrong_subshare, &commitments), Ok(false));
+	}
+
+	#[test]
+	fn joint_public_is_sum_of_polynom_commitments() {
+		let polynoms: Vec<_> = (0..4).map(|_| generate_random_polynom(2).unwrap()).collect();
+		let commitments: Vec<_> = polynoms.iter().map(|p| compute_polynom_commitments(p).unwrap()).collect();
+		let joint_secret = compute_secret_sum(polynoms.iter().map(|p| &p[0])).unwrap();
+		assert_eq!(compute_public_sum(commitments.iter().map(|c| &c[0])).unwrap(), compute_public_share(&joint_secret).unwrap());
+	}
+
+	#[test]
+	fn local_signature_is_verified() {
+		let secret = generate_random_scalar().unwrap();
+		let nonce = generate_random_scalar().unwrap();
+		let message_hash = H256::random();
+		let signature = local_compute_signature(&nonce, &secret, &message_hash).unwrap();
+		let public = compute_public_share(&secret).unwrap();
+		assert_eq!(verify_signature(&public, &signature, &message_hash), Ok(true));
+		assert_eq!(verify_signature(&public, &signature, &H256::random()), Ok(false));
+	}
+
+	#[test]
+	fn threshold_signature_is_verified() {
+		let test_cases = [(0, 1), (1, 3), (2, 5), (3, 5), (4, 5)];
+		for &(t, n) in &test_cases {
+			let (id_numbers, secret_shares, _, joint_public) = generate_shares(t, n);
+			let (nonce_id_numbers, nonce_shares, _, nonce_public) = {
+				let polynoms: Vec<_> = (0..t + 1).map(|_| generate_random_polynom(t).unwrap()).collect();
+				let nonce_shares: Vec<_> = id_numbers[..t + 1].iter()
+					.map(|id_number| compute_secret_sum(polynoms.iter()
+						.map(|p| compute_polynom(p, id_number).unwrap()).collect::<Vec<_>>().iter()).unwrap())
+					.collect();
+				let nonce = compute_secret_sum(polynoms.iter().map(|p| &p[0])).unwrap();
+				(id_numbers[..t + 1].to_vec(), nonce_shares, nonce.clone(), compute_public_share(&nonce).unwrap())
+			};
+
+			let message_hash = H256::random();
+			let challenge = combine_message_hash_with_public(&nonce_public, &joint_public, &message_hash).unwrap();
+			let signature_shares: Vec<_> = (0..t + 1)
+				.map(|i| compute_signature_share(
+					&challenge,
+					&nonce_shares[i],
+					&secret_shares[i],
+					&nonce_id_numbers[i],
+					nonce_id_numbers.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, n)| n),
+				).unwrap())
+				.collect();
+			let signature = (nonce_public, compute_signature(signature_shares.iter()).unwrap());
+			assert_eq!(verify_signature(&joint_public, &signature, &message_hash), Ok(true));
+		}
+	}
+
+	#[test]
+	fn lagrange_coeffs_restore_joint_secret() {
+		let (id_numbers, secret_shares, joint_secret, _) = generate_shares(2, 4);
+		let restored: Vec<_> = (0..3)
+			.map(|i| {
+				let coeff = compute_lagrange_coeff(&id_numbers[i], id_numbers[..3].iter().enumerate()
+					.filter(|&(j, _)| j != i).map(|(_, n)| n)).unwrap();
+				to_secret(&(to_scalar(&coeff).unwrap() * to_scalar(&secret_shares[i]).unwrap()))
+			})
+			.collect();
+		assert_eq!(compute_secret_sum(restored.iter()).unwrap(), joint_secret);
+	}
+
+	#[test]
+	fn key_share_public_is_converted() {
+		let public = compute_public_share(&generate_random_scalar().unwrap()).unwrap();
+		assert_eq!(from_key_share_public(&into_key_share_public(&public)), Ok(public));
+		assert!(from_key_share_public(&Public::from_low_u64_be(1)).is_err());
+	}
+}
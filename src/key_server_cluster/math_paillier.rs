@@ -0,0 +1,969 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Paillier cryptosystem and multiplicative-to-additive (MtA) share conversion, used by threshold ECDSA
+//! sessions. MtA converts multiplicative shares `a` (known to Alice) and `b` (known to Bob) into additive
+//! shares `alpha` (known to Alice) and `beta` (known to Bob), such that `a * b = alpha + beta (mod q)`:
+//! 1) Alice sends `Enc(a)`, encrypted with her Paillier key;
+//! 2) Bob picks random `beta'` and responds with `Enc(a) * b + Enc(beta')` = `Enc(a * b + beta')`. His share
+//! is `beta = -beta' (mod q)`;
+//! 3) Alice decrypts the response. Her share is `alpha = a * b + beta' (mod q)`.
+//!
+//! Every message is accompanied with zero-knowledge proof, so that malicious party can't learn other party' share:
+//! 1) Paillier public key comes with proof that modulus is Paillier-Blum modulus and that ring-Pedersen parameters
+//! (computed over the same modulus) are correct (`Π^mod` and `Π^prm` from "UC Non-Interactive, Proactive, Threshold
+//! ECDSA with Identifiable Aborts" by Canetti et al.);
+//! 2) Alice' request comes with proof that her modulus has no small factors (`Π^fac` from the same paper) and with
+//! proof that `a < q^3` (range proof from "Fast Multiparty Threshold ECDSA with Fast Trustless Setup" by Gennaro
+//! and Goldfeder);
+//! 3) Bob' response comes with proof that `b < q^3` and `beta' < q^7` (respondent proof from the same paper).
+//!
+//! Both range proofs are using ring-Pedersen parameters of the verifier.
+
+use num_bigint::{BigInt, BigUint, RandBigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use bytes::Bytes;
+use crypto::publickey::{Secret, ec_math_utils};
+use key_server_cluster::Error;
+
+/// Size of Paillier modulus prime factors.
+#[cfg(not(test))]
+const PRIME_BITS: u64 = 1024;
+/// Size of Paillier modulus prime factors. Smaller keys are used in tests to speed up key generation. They're
+/// still large enough for MtA between honest nodes (n > q^5 + q^4).
+#[cfg(test)]
+const PRIME_BITS: u64 = 672;
+/// Number of Miller-Rabin rounds, used to test primes.
+const MILLER_RABIN_ROUNDS: usize = 40;
+/// Small primes, used to filter prime candidates before running Miller-Rabin test.
+const SMALL_PRIMES: [u32; 24] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97];
+/// Number of iterations of Paillier-Blum modulus proof and ring-Pedersen parameters proof. Every iteration
+/// halves the probability of accepting invalid proof.
+#[cfg(not(test))]
+const PROOF_ITERATIONS: usize = 80;
+/// Number of iterations of Paillier-Blum modulus proof and ring-Pedersen parameters proof. Less iterations
+/// are used in tests to speed up proofs generation and verification.
+#[cfg(test)]
+const PROOF_ITERATIONS: usize = 8;
+/// Bit size of the curve order (`l` parameter of no-small-factors proof).
+const ORDER_BITS: usize = 256;
+/// Slackness parameter of no-small-factors proof (`epsilon` parameter).
+const SLACKNESS_BITS: usize = 512;
+
+/// Paillier public key along with ring-Pedersen parameters, which are computed over the same modulus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaillierPublic {
+	/// Modulus.
+	n: BigUint,
+	/// Square of modulus.
+	n_square: BigUint,
+	/// Ring-Pedersen parameter `s = t^lambda (mod n)`.
+	s: BigUint,
+	/// Ring-Pedersen parameter `t = r^2 (mod n)`.
+	t: BigUint,
+}
+
+/// Paillier key pair.
+#[derive(Debug, Clone)]
+pub struct PaillierKeyPair {
+	/// Public key.
+	public: PaillierPublic,
+	/// First prime factor of modulus.
+	p: BigUint,
+	/// Second prime factor of modulus.
+	q: BigUint,
+	/// Euler's totient of modulus.
+	phi: BigUint,
+	/// Inversion of phi (mod n).
+	phi_inv: BigUint,
+	/// Discrete logarithm of ring-Pedersen parameter `s` to the base `t`.
+	lambda: BigUint,
+}
+
+/// Secret, encrypted with Paillier key (first step of MtA). Encryption randomness is preserved to prove
+/// that the secret is in range.
+#[derive(Debug, Clone)]
+pub struct EncryptedSecret {
+	/// Encrypted secret.
+	secret: BigUint,
+	/// Encryption randomness.
+	randomness: BigUint,
+	/// Ciphertext.
+	ciphertext: BigUint,
+}
+
+/// Fiat-Shamir transcript: challenges are derived from hash of all values, appended so far.
+#[derive(Clone)]
+struct Transcript(Sha256);
+
+/// Writer of proof values.
+#[derive(Default)]
+struct ProofWriter(Bytes);
+
+/// Reader of proof values.
+struct ProofReader<'a>(&'a [u8]);
+
+impl PaillierPublic {
+	/// Read public key from its serialized form and check that it is valid, using the proof, generated by
+	/// `PaillierKeyPair::prove_public`.
+	pub fn deserialize(serialized: &[u8], proof: &[u8], context: &[u8]) -> Result<Self, Error> {
+		let mut reader = ProofReader(serialized);
+		let n = reader.read()?;
+		if n.bits() < PRIME_BITS * 2 || n.is_even() || is_probable_prime(&n) {
+			return Err(Error::InvalidMessage);
+		}
+		let s = reader.read_unit(&n)?;
+		let t = reader.read_unit(&n)?;
+		reader.finish()?;
+
+		let public = PaillierPublic {
+			n_square: &n * &n,
+			n: n,
+			s: s,
+			t: t,
+		};
+		public.verify_public_proof(proof, context)?;
+		Ok(public)
+	}
+
+	/// Serialize public key.
+	pub fn serialize(&self) -> Bytes {
+		let mut writer = ProofWriter::default();
+		writer.write(&self.n);
+		writer.write(&self.s);
+		writer.write(&self.t);
+		writer.0
+	}
+
+	/// Encrypt message with given randomness. Message is reduced modulo n.
+	fn encrypt(&self, message: &BigUint, randomness: &BigUint) -> BigUint {
+		// (1 + n)^m = 1 + m * n (mod n^2)
+		let gm = (BigUint::one() + (message % &self.n) * &self.n) % &self.n_square;
+		(gm * randomness.modpow(&self.n, &self.n_square)) % &self.n_square
+	}
+
+	/// Read ciphertext, encrypted with this key.
+	fn read_ciphertext(&self, ciphertext: &[u8]) -> Result<BigUint, Error> {
+		let ciphertext = BigUint::from_bytes_be(ciphertext);
+		if ciphertext.is_zero() || ciphertext >= self.n_square {
+			return Err(Error::InvalidMessage);
+		}
+
+		Ok(ciphertext)
+	}
+
+	/// Compute ring-Pedersen commitment `s^value * t^randomness (mod n)`.
+	fn commit(&self, value: &BigInt, randomness: &BigInt) -> Result<BigUint, Error> {
+		Ok((pow_signed(&self.s, value, &self.n)? * pow_signed(&self.t, randomness, &self.n)?) % &self.n)
+	}
+
+	/// Create transcript of the proof of public key.
+	fn public_transcript(&self, context: &[u8], w: &BigUint, prm_commitments: &[BigUint]) -> Transcript {
+		let mut transcript = Transcript::new("PAILLIER_PUBLIC", context);
+		transcript.append(&self.n);
+		transcript.append(&self.s);
+		transcript.append(&self.t);
+		transcript.append(w);
+		for commitment in prm_commitments {
+			transcript.append(commitment);
+		}
+		transcript
+	}
+
+	/// Verify proof of public key.
+	fn verify_public_proof(&self, proof: &[u8], context: &[u8]) -> Result<(), Error> {
+		let n = &self.n;
+		let mut reader = ProofReader(proof);
+		let w = reader.read_unit(n)?;
+		let mut mod_proof = Vec::with_capacity(PROOF_ITERATIONS);
+		for _ in 0..PROOF_ITERATIONS {
+			mod_proof.push((reader.read_flag()?, reader.read_flag()?, reader.read_below(n)?, reader.read_below(n)?));
+		}
+		let mut prm_commitments = Vec::with_capacity(PROOF_ITERATIONS);
+		let mut prm_responses = Vec::with_capacity(PROOF_ITERATIONS);
+		for _ in 0..PROOF_ITERATIONS {
+			prm_commitments.push(reader.read_unit(n)?);
+			prm_responses.push(reader.read_below(n)?);
+		}
+		reader.finish()?;
+
+		let transcript = self.public_transcript(context, &w, &prm_commitments);
+
+		// Paillier-Blum modulus proof: z^n = y (mod n) && x^4 = (-1)^a * w^b * y (mod n)
+		let four = BigUint::from(4u32);
+		for (i, &(a, b, ref x, ref z)) in mod_proof.iter().enumerate() {
+			let y = transcript.challenge_below(i, n);
+			if z.modpow(n, n) != y || x.modpow(&four, n) != blum_adjusted(n, &w, &y, a, b) {
+				return Err(Error::InvalidMessage);
+			}
+		}
+
+		// ring-Pedersen parameters proof: t^z = A * s^e (mod n)
+		for (i, (commitment, response)) in prm_commitments.iter().zip(prm_responses.iter()).enumerate() {
+			let expected = match transcript.challenge_bit(PROOF_ITERATIONS + i) {
+				true => (commitment * &self.s) % n,
+				false => commitment.clone(),
+			};
+			if self.t.modpow(response, n) != expected {
+				return Err(Error::InvalidMessage);
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl PaillierKeyPair {
+	/// Generate new random key pair.
+	pub fn generate() -> Result<Self, Error> {
+		let p = generate_prime(PRIME_BITS);
+		let q = loop {
+			let q = generate_prime(PRIME_BITS);
+			if q != p {
+				break q;
+			}
+		};
+
+		let n = &p * &q;
+		let phi = (&p - 1u32) * (&q - 1u32);
+		let phi_inv = invert(&phi, &n)?;
+		let r = random_unit(&n);
+		let t = (&r * &r) % &n;
+		let lambda = OsRng.gen_biguint_below(&phi);
+		let s = t.modpow(&lambda, &n);
+		Ok(PaillierKeyPair {
+			public: PaillierPublic {
+				n_square: &n * &n,
+				n: n,
+				s: s,
+				t: t,
+			},
+			p: p,
+			q: q,
+			phi: phi,
+			phi_inv: phi_inv,
+			lambda: lambda,
+		})
+	}
+
+	/// Get public key.
+	pub fn public(&self) -> &PaillierPublic {
+		&self.public
+	}
+
+	/// Prove that modulus is Paillier-Blum modulus and that ring-Pedersen parameters are correct.
+	pub fn prove_public(&self, context: &[u8]) -> Result<Bytes, Error> {
+		let n = &self.public.n;
+
+		// w is a quadratic non-residue with Jacobi symbol -1
+		let w = loop {
+			let w = random_unit(n);
+			if is_quadratic_residue(&w, &self.p) != is_quadratic_residue(&w, &self.q) {
+				break w;
+			}
+		};
+		let prm_randomness: Vec<_> = (0..PROOF_ITERATIONS).map(|_| OsRng.gen_biguint_below(&self.phi)).collect();
+		let prm_commitments: Vec<_> = prm_randomness.iter().map(|a| self.public.t.modpow(a, n)).collect();
+		let transcript = self.public.public_transcript(context, &w, &prm_commitments);
+
+		let mut writer = ProofWriter::default();
+		writer.write(&w);
+
+		// Paillier-Blum modulus proof: for every challenge y, exactly one of y, -y, w*y, -w*y is a quadratic
+		// residue modulo both p and q => we reveal its 4th root && the n-th root of y
+		let n_inv = invert(n, &self.phi)?;
+		for i in 0..PROOF_ITERATIONS {
+			let y = transcript.challenge_below(i, n);
+			let (a, b, adjusted_y) = [(false, false), (true, false), (false, true), (true, true)].iter()
+				.map(|&(a, b)| (a, b, blum_adjusted(n, &w, &y, a, b)))
+				.find(|(_, _, adjusted_y)| is_quadratic_residue(adjusted_y, &self.p) && is_quadratic_residue(adjusted_y, &self.q))
+				.ok_or_else(|| Error::Internal("challenge is not coprime to Paillier modulus".into()))?;
+			writer.write_flag(a);
+			writer.write_flag(b);
+			writer.write(&self.fourth_root(&adjusted_y)?);
+			writer.write(&y.modpow(&n_inv, n));
+		}
+
+		// ring-Pedersen parameters proof: z = a + e * lambda (mod phi)
+		for (i, (randomness, commitment)) in prm_randomness.into_iter().zip(prm_commitments).enumerate() {
+			let response = match transcript.challenge_bit(PROOF_ITERATIONS + i) {
+				true => (randomness + &self.lambda) % &self.phi,
+				false => randomness,
+			};
+			writer.write(&commitment);
+			writer.write(&response);
+		}
+
+		Ok(writer.0)
+	}
+
+	/// Decrypt ciphertext.
+	fn decrypt(&self, ciphertext: &BigUint) -> BigUint {
+		// L(c^phi mod n^2) * inv(phi) mod n, where L(x) = (x - 1) / n
+		let l = (ciphertext.modpow(&self.phi, &self.public.n_square) - 1u32) / &self.public.n;
+		(l * &self.phi_inv) % &self.public.n
+	}
+
+	/// Compute 4th root of value, which is a quadratic residue modulo both p and q.
+	fn fourth_root(&self, value: &BigUint) -> Result<BigUint, Error> {
+		let root_p = fourth_root_mod_prime(value, &self.p);
+		let root_q = fourth_root_mod_prime(value, &self.q);
+
+		// CRT: root = root_p + p * ((root_q - root_p) * inv(p) mod q)
+		let p_inv = invert(&self.p, &self.q)?;
+		let diff = (&root_q + &self.q - (&root_p % &self.q)) % &self.q;
+		Ok(root_p + &self.p * ((diff * p_inv) % &self.q))
+	}
+}
+
+impl EncryptedSecret {
+	/// Get serialized ciphertext.
+	pub fn ciphertext(&self) -> Bytes {
+		self.ciphertext.to_bytes_be()
+	}
+}
+
+impl Transcript {
+	/// Create new transcript for given proof type and context (session data the proof is bound to).
+	fn new(tag: &str, context: &[u8]) -> Self {
+		let mut transcript = Transcript(Sha256::new());
+		transcript.append_bytes(tag.as_bytes());
+		transcript.append_bytes(context);
+		transcript
+	}
+
+	/// Append bytes to the transcript.
+	fn append_bytes(&mut self, bytes: &[u8]) {
+		self.0.input((bytes.len() as u32).to_be_bytes());
+		self.0.input(bytes);
+	}
+
+	/// Append big integer to the transcript.
+	fn append(&mut self, value: &BigUint) {
+		self.append_bytes(&value.to_bytes_be());
+	}
+
+	/// Compute challenge bytes with given index.
+	fn challenge_bytes(&self, index: usize, len: usize) -> Bytes {
+		let mut bytes = Vec::with_capacity(len + 32);
+		let mut counter = 0u32;
+		while bytes.len() < len {
+			let mut hasher = self.0.clone();
+			hasher.input((index as u32).to_be_bytes());
+			hasher.input(counter.to_be_bytes());
+			bytes.extend_from_slice(&hasher.result());
+			counter += 1;
+		}
+		bytes.truncate(len);
+		bytes
+	}
+
+	/// Compute challenge bit with given index.
+	fn challenge_bit(&self, index: usize) -> bool {
+		self.challenge_bytes(index, 1)[0] & 1 == 1
+	}
+
+	/// Compute challenge with given index, which is less than given modulus.
+	fn challenge_below(&self, index: usize, modulus: &BigUint) -> BigUint {
+		// 128 extra bits make the modulo bias negligible
+		let len = (modulus.bits() as usize).div_ceil(8) + 16;
+		BigUint::from_bytes_be(&self.challenge_bytes(index, len)) % modulus
+	}
+}
+
+impl ProofWriter {
+	/// Write big integer.
+	fn write(&mut self, value: &BigUint) {
+		let bytes = value.to_bytes_be();
+		self.0.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+		self.0.extend_from_slice(&bytes);
+	}
+
+	/// Write signed big integer.
+	fn write_signed(&mut self, value: &BigInt) {
+		self.write_flag(value.sign() == Sign::Minus);
+		self.write(value.magnitude());
+	}
+
+	/// Write flag.
+	fn write_flag(&mut self, flag: bool) {
+		self.0.push(flag as u8);
+	}
+}
+
+impl<'a> ProofReader<'a> {
+	/// Read big integer.
+	fn read(&mut self) -> Result<BigUint, Error> {
+		if self.0.len() < 4 {
+			return Err(Error::InvalidMessage);
+		}
+
+		let len = ((self.0[0] as usize) << 24) | ((self.0[1] as usize) << 16) | ((self.0[2] as usize) << 8) | (self.0[3] as usize);
+		if self.0.len() < 4 + len {
+			return Err(Error::InvalidMessage);
+		}
+
+		let value = BigUint::from_bytes_be(&self.0[4..4 + len]);
+		self.0 = &self.0[4 + len..];
+		Ok(value)
+	}
+
+	/// Read big integer, which must be less than given bound.
+	fn read_below(&mut self, bound: &BigUint) -> Result<BigUint, Error> {
+		let value = self.read()?;
+		if &value >= bound {
+			return Err(Error::InvalidMessage);
+		}
+
+		Ok(value)
+	}
+
+	/// Read invertible element of the multiplicative group of integers modulo given modulus.
+	fn read_unit(&mut self, modulus: &BigUint) -> Result<BigUint, Error> {
+		let value = self.read_below(modulus)?;
+		if value.is_zero() || !value.gcd(modulus).is_one() {
+			return Err(Error::InvalidMessage);
+		}
+
+		Ok(value)
+	}
+
+	/// Read signed big integer, which magnitude must not exceed given bound.
+	fn read_signed(&mut self, bound: &BigUint) -> Result<BigInt, Error> {
+		let is_negative = self.read_flag()?;
+		let magnitude = self.read()?;
+		if &magnitude > bound {
+			return Err(Error::InvalidMessage);
+		}
+
+		Ok(BigInt::from_biguint(if is_negative { Sign::Minus } else { Sign::Plus }, magnitude))
+	}
+
+	/// Read flag.
+	fn read_flag(&mut self) -> Result<bool, Error> {
+		let flag = match self.0.first() {
+			Some(&0) => false,
+			Some(&1) => true,
+			_ => return Err(Error::InvalidMessage),
+		};
+
+		self.0 = &self.0[1..];
+		Ok(flag)
+	}
+
+	/// Check that everything has been read.
+	fn finish(self) -> Result<(), Error> {
+		match self.0.is_empty() {
+			true => Ok(()),
+			false => Err(Error::InvalidMessage),
+		}
+	}
+}
+
+/// Encrypt secret with Paillier key (first step of MtA).
+pub fn encrypt_secret(public: &PaillierPublic, secret: &Secret) -> Result<EncryptedSecret, Error> {
+	let secret = to_biguint(secret);
+	let randomness = random_unit(&public.n);
+	let ciphertext = public.encrypt(&secret, &randomness);
+	Ok(EncryptedSecret {
+		secret: secret,
+		randomness: randomness,
+		ciphertext: ciphertext,
+	})
+}
+
+/// Prove that Paillier modulus of the MtA request sender has no small factors and that the encrypted secret is
+/// in range. Proof is generated for the given verifier, because it is using verifier' ring-Pedersen parameters.
+pub fn prove_mta_request(key_pair: &PaillierKeyPair, encrypted_a: &EncryptedSecret, verifier: &PaillierPublic, context: &[u8]) -> Result<Bytes, Error> {
+	let mut writer = ProofWriter::default();
+	prove_no_small_factors(key_pair, verifier, context, &mut writer)?;
+	prove_encryption_range(&key_pair.public, encrypted_a, verifier, context, &mut writer)?;
+	Ok(writer.0)
+}
+
+/// Verify proof of the MtA request (see `prove_mta_request`).
+pub fn verify_mta_request(public: &PaillierPublic, verifier: &PaillierPublic, encrypted_a: &[u8], proof: &[u8], context: &[u8]) -> Result<(), Error> {
+	let encrypted_a = public.read_ciphertext(encrypted_a)?;
+	let mut reader = ProofReader(proof);
+	verify_no_small_factors(public, verifier, context, &mut reader)?;
+	verify_encryption_range(public, &encrypted_a, verifier, context, &mut reader)?;
+	reader.finish()
+}
+
+/// Compute response to the MtA request (second step of MtA). Returns Enc(a * b + beta'), proof of the response
+/// (generated using ring-Pedersen parameters of the request sender) and beta.
+pub fn compute_mta_response(public: &PaillierPublic, encrypted_a: &[u8], b: &Secret, context: &[u8]) -> Result<(Bytes, Bytes, Secret), Error> {
+	let encrypted_a = public.read_ciphertext(encrypted_a)?;
+
+	// a * b < q^2 and beta' < q^5 => a * b + beta' fits into plaintext space
+	let q = curve_order();
+	let beta_prime = OsRng.gen_biguint_below(&q.pow(5));
+	let randomness = random_unit(&public.n);
+
+	let b = to_biguint(b);
+	let encrypted_ab = encrypted_a.modpow(&b, &public.n_square);
+	let encrypted_response = (encrypted_ab * public.encrypt(&beta_prime, &randomness)) % &public.n_square;
+	let proof = prove_mta_response(public, &encrypted_a, &encrypted_response, &b, &beta_prime, &randomness, context)?;
+	let beta = (&q - beta_prime % &q) % &q;
+	Ok((encrypted_response.to_bytes_be(), proof, to_secret(&beta)))
+}
+
+/// Verify MtA response && compute alpha from it (third step of MtA).
+pub fn compute_mta_share(key_pair: &PaillierKeyPair, encrypted_a: &EncryptedSecret, encrypted_response: &[u8], proof: &[u8], context: &[u8]) -> Result<Secret, Error> {
+	let encrypted_response = key_pair.public.read_ciphertext(encrypted_response)?;
+	verify_mta_response(&key_pair.public, &encrypted_a.ciphertext, &encrypted_response, proof, context)?;
+
+	let alpha = key_pair.decrypt(&encrypted_response) % curve_order();
+	Ok(to_secret(&alpha))
+}
+
+/// Prove that Paillier modulus `n = p * q` has no small factors (`Π^fac`): both p and q are less than
+/// `sqrt(n) * 2^(l + epsilon)`, which means that they're both greater than `sqrt(n) / 2^(l + epsilon)`.
+fn prove_no_small_factors(key_pair: &PaillierKeyPair, verifier: &PaillierPublic, context: &[u8], writer: &mut ProofWriter) -> Result<(), Error> {
+	let n = BigInt::from(key_pair.public.n.clone());
+	let p = BigInt::from(key_pair.p.clone());
+	let q = BigInt::from(key_pair.q.clone());
+	let n_hat = &verifier.n;
+
+	let factor_bound = key_pair.public.n.sqrt() << (ORDER_BITS + SLACKNESS_BITS);
+	let alpha = random_signed(&factor_bound);
+	let beta = random_signed(&factor_bound);
+	let mu = random_signed(&(n_hat << ORDER_BITS));
+	let nu = random_signed(&(n_hat << ORDER_BITS));
+	let sigma = random_signed(&((n_hat * &key_pair.public.n) << ORDER_BITS));
+	let r = random_signed(&((n_hat * &key_pair.public.n) << (ORDER_BITS + SLACKNESS_BITS)));
+	let x = random_signed(&(n_hat << (ORDER_BITS + SLACKNESS_BITS)));
+	let y = random_signed(&(n_hat << (ORDER_BITS + SLACKNESS_BITS)));
+
+	let p_commitment = verifier.commit(&p, &mu)?;
+	let q_commitment = verifier.commit(&q, &nu)?;
+	let a_commitment = verifier.commit(&alpha, &x)?;
+	let b_commitment = verifier.commit(&beta, &y)?;
+	let r_commitment = verifier.commit(&n, &sigma)?;
+	let t_commitment = (pow_signed(&q_commitment, &alpha, n_hat)? * pow_signed(&verifier.t, &r, n_hat)?) % n_hat;
+	let commitments = [p_commitment, q_commitment, a_commitment, b_commitment, r_commitment, t_commitment];
+
+	let e = BigInt::from(no_small_factors_challenge(&key_pair.public, verifier, context, &commitments));
+	let sigma_hat = &sigma - &nu * &p;
+	for commitment in &commitments {
+		writer.write(commitment);
+	}
+	writer.write_signed(&(&alpha + &e * &p));
+	writer.write_signed(&(&beta + &e * &q));
+	writer.write_signed(&(&x + &e * &mu));
+	writer.write_signed(&(&y + &e * &nu));
+	writer.write_signed(&(&r + &e * &sigma_hat));
+	Ok(())
+}
+
+/// Verify proof that Paillier modulus has no small factors.
+fn verify_no_small_factors(public: &PaillierPublic, verifier: &PaillierPublic, context: &[u8], reader: &mut ProofReader) -> Result<(), Error> {
+	let n_hat = &verifier.n;
+	let factor_bound = public.n.sqrt() << (ORDER_BITS + SLACKNESS_BITS);
+	let mut commitments = Vec::with_capacity(6);
+	for _ in 0..6 {
+		commitments.push(reader.read_unit(n_hat)?);
+	}
+	let z1 = reader.read_signed(&factor_bound)?;
+	let z2 = reader.read_signed(&factor_bound)?;
+	let w1 = reader.read_signed(&(n_hat << (ORDER_BITS + SLACKNESS_BITS + 1)))?;
+	let w2 = reader.read_signed(&(n_hat << (ORDER_BITS + SLACKNESS_BITS + 1)))?;
+	let v = reader.read_signed(&((n_hat * &public.n) << (ORDER_BITS + SLACKNESS_BITS + 1)))?;
+
+	let e = no_small_factors_challenge(public, verifier, context, &commitments);
+	let (p_commitment, q_commitment, a_commitment, b_commitment, r_commitment, t_commitment) =
+		(&commitments[0], &commitments[1], &commitments[2], &commitments[3], &commitments[4], &commitments[5]);
+
+	// s^z1 * t^w1 = A * P^e, s^z2 * t^w2 = B * Q^e, Q^z1 * t^v = T * R^e (mod n_hat)
+	if verifier.commit(&z1, &w1)? != (a_commitment * p_commitment.modpow(&e, n_hat)) % n_hat
+		|| verifier.commit(&z2, &w2)? != (b_commitment * q_commitment.modpow(&e, n_hat)) % n_hat
+		|| (pow_signed(q_commitment, &z1, n_hat)? * pow_signed(&verifier.t, &v, n_hat)?) % n_hat
+			!= (t_commitment * r_commitment.modpow(&e, n_hat)) % n_hat {
+		return Err(Error::InvalidMessage);
+	}
+
+	Ok(())
+}
+
+/// Compute challenge of no-small-factors proof.
+fn no_small_factors_challenge(public: &PaillierPublic, verifier: &PaillierPublic, context: &[u8], commitments: &[BigUint]) -> BigUint {
+	let mut transcript = Transcript::new("PAILLIER_NO_SMALL_FACTORS", context);
+	transcript.append(&public.n);
+	transcript.append(&verifier.n);
+	transcript.append(&verifier.s);
+	transcript.append(&verifier.t);
+	for commitment in commitments {
+		transcript.append(commitment);
+	}
+	transcript.challenge_below(0, &curve_order())
+}
+
+/// Prove that secret, encrypted with given Paillier key, is less than q^3.
+fn prove_encryption_range(public: &PaillierPublic, encrypted: &EncryptedSecret, verifier: &PaillierPublic, context: &[u8], writer: &mut ProofWriter) -> Result<(), Error> {
+	let q = curve_order();
+	let q_cube = q.pow(3);
+	let n_hat = &verifier.n;
+
+	let alpha = OsRng.gen_biguint_below(&q_cube);
+	let beta = random_unit(&public.n);
+	let gamma = OsRng.gen_biguint_below(&(&q_cube * n_hat));
+	let rho = OsRng.gen_biguint_below(&(&q * n_hat));
+
+	let z = verifier.commit(&BigInt::from(encrypted.secret.clone()), &BigInt::from(rho.clone()))?;
+	let u = public.encrypt(&alpha, &beta);
+	let w = verifier.commit(&BigInt::from(alpha.clone()), &BigInt::from(gamma.clone()))?;
+
+	let e = encryption_range_challenge(public, &encrypted.ciphertext, verifier, context, &z, &u, &w);
+	writer.write(&z);
+	writer.write(&u);
+	writer.write(&w);
+	writer.write(&((encrypted.randomness.modpow(&e, &public.n) * beta) % &public.n));
+	writer.write(&(&e * &encrypted.secret + alpha));
+	writer.write(&(&e * rho + gamma));
+	Ok(())
+}
+
+/// Verify proof that encrypted secret is less than q^3.
+fn verify_encryption_range(public: &PaillierPublic, ciphertext: &BigUint, verifier: &PaillierPublic, context: &[u8], reader: &mut ProofReader) -> Result<(), Error> {
+	let q = curve_order();
+	let n_hat = &verifier.n;
+
+	let z = reader.read_unit(n_hat)?;
+	let u = reader.read_unit(&public.n_square)?;
+	let w = reader.read_unit(n_hat)?;
+	let s = reader.read_unit(&public.n)?;
+	let s1 = reader.read_below(&(q.pow(3) + 1u32))?;
+	let s2 = reader.read()?;
+
+	let e = encryption_range_challenge(public, ciphertext, verifier, context, &z, &u, &w);
+
+	// (1 + n)^s1 * s^n = u * c^e (mod n^2), h1^s1 * h2^s2 = w * z^e (mod n_hat)
+	if public.encrypt(&s1, &s) != (u * ciphertext.modpow(&e, &public.n_square)) % &public.n_square
+		|| verifier.commit(&BigInt::from(s1), &BigInt::from(s2))? != (w * z.modpow(&e, n_hat)) % n_hat {
+		return Err(Error::InvalidMessage);
+	}
+
+	Ok(())
+}
+
+/// Compute challenge of encryption range proof.
+fn encryption_range_challenge(public: &PaillierPublic, ciphertext: &BigUint, verifier: &PaillierPublic, context: &[u8],
+	z: &BigUint, u: &BigUint, w: &BigUint) -> BigUint {
+	let mut transcript = Transcript::new("MTA_REQUEST", context);
+	transcript.append(&public.n);
+	transcript.append(&verifier.n);
+	transcript.append(&verifier.s);
+	transcript.append(&verifier.t);
+	transcript.append(ciphertext);
+	transcript.append(z);
+	transcript.append(u);
+	transcript.append(w);
+	transcript.challenge_below(0, &curve_order())
+}
+
+/// Prove that MtA response `c2 = c1^x * (1 + n)^y * r^n (mod n^2)` is computed for `x < q^3` and `y < q^7`. Proof
+/// is using ring-Pedersen parameters of the request sender, which are computed over the same modulus n.
+fn prove_mta_response(public: &PaillierPublic, c1: &BigUint, c2: &BigUint, x: &BigUint, y: &BigUint, r: &BigUint, context: &[u8]) -> Result<Bytes, Error> {
+	let q = curve_order();
+	let q_cube = q.pow(3);
+	let n = &public.n;
+
+	let alpha = OsRng.gen_biguint_below(&q_cube);
+	let rho = OsRng.gen_biguint_below(&(&q * n));
+	let rho_prime = OsRng.gen_biguint_below(&(&q_cube * n));
+	let sigma = OsRng.gen_biguint_below(&(&q * n));
+	let beta = random_unit(n);
+	let gamma = OsRng.gen_biguint_below(&q.pow(7));
+	let tau = OsRng.gen_biguint_below(&(&q_cube * n));
+
+	let z = public.commit(&BigInt::from(x.clone()), &BigInt::from(rho.clone()))?;
+	let z_prime = public.commit(&BigInt::from(alpha.clone()), &BigInt::from(rho_prime.clone()))?;
+	let t = public.commit(&BigInt::from(y.clone()), &BigInt::from(sigma.clone()))?;
+	let v = (c1.modpow(&alpha, &public.n_square) * public.encrypt(&gamma, &beta)) % &public.n_square;
+	let w = public.commit(&BigInt::from(gamma.clone()), &BigInt::from(tau.clone()))?;
+	let commitments = [z, z_prime, t, v, w];
+
+	let e = mta_response_challenge(public, c1, c2, context, &commitments);
+	let mut writer = ProofWriter::default();
+	for commitment in &commitments {
+		writer.write(commitment);
+	}
+	writer.write(&((r.modpow(&e, n) * beta) % n));
+	writer.write(&(&e * x + alpha));
+	writer.write(&(&e * rho + rho_prime));
+	writer.write(&(&e * y + gamma));
+	writer.write(&(&e * sigma + tau));
+	Ok(writer.0)
+}
+
+/// Verify proof of the MtA response.
+fn verify_mta_response(public: &PaillierPublic, c1: &BigUint, c2: &BigUint, proof: &[u8], context: &[u8]) -> Result<(), Error> {
+	let q = curve_order();
+	let n = &public.n;
+
+	let mut reader = ProofReader(proof);
+	let z = reader.read_unit(n)?;
+	let z_prime = reader.read_unit(n)?;
+	let t = reader.read_unit(n)?;
+	let v = reader.read_unit(&public.n_square)?;
+	let w = reader.read_unit(n)?;
+	let s = reader.read_unit(n)?;
+	let s1 = reader.read_below(&(q.pow(3) + 1u32))?;
+	let s2 = reader.read()?;
+	let t1 = reader.read_below(&(q.pow(7) + 1u32))?;
+	let t2 = reader.read()?;
+	reader.finish()?;
+
+	let commitments = [z, z_prime, t, v, w];
+	let e = mta_response_challenge(public, c1, c2, context, &commitments);
+	let (z, z_prime, t, v, w) = (&commitments[0], &commitments[1], &commitments[2], &commitments[3], &commitments[4]);
+
+	// h1^s1 * h2^s2 = z^e * z' (mod n), h1^t1 * h2^t2 = t^e * w (mod n), c1^s1 * s^n * (1 + n)^t1 = c2^e * v (mod n^2)
+	if public.commit(&BigInt::from(s1.clone()), &BigInt::from(s2))? != (z.modpow(&e, n) * z_prime) % n
+		|| public.commit(&BigInt::from(t1.clone()), &BigInt::from(t2))? != (t.modpow(&e, n) * w) % n
+		|| (c1.modpow(&s1, &public.n_square) * public.encrypt(&t1, &s)) % &public.n_square
+			!= (c2.modpow(&e, &public.n_square) * v) % &public.n_square {
+		return Err(Error::InvalidMessage);
+	}
+
+	Ok(())
+}
+
+/// Compute challenge of MtA response proof.
+fn mta_response_challenge(public: &PaillierPublic, c1: &BigUint, c2: &BigUint, context: &[u8], commitments: &[BigUint]) -> BigUint {
+	let mut transcript = Transcript::new("MTA_RESPONSE", context);
+	transcript.append(&public.n);
+	transcript.append(&public.s);
+	transcript.append(&public.t);
+	transcript.append(c1);
+	transcript.append(c2);
+	for commitment in commitments {
+		transcript.append(commitment);
+	}
+	transcript.challenge_below(0, &curve_order())
+}
+
+/// Get order of secp256k1 curve.
+fn curve_order() -> BigUint {
+	let mut order = [0u8; 32];
+	ec_math_utils::CURVE_ORDER.to_big_endian(&mut order);
+	BigUint::from_bytes_be(&order)
+}
+
+/// Convert secret to big integer.
+fn to_biguint(secret: &Secret) -> BigUint {
+	BigUint::from_bytes_be(secret.as_bytes())
+}
+
+/// Convert big integer (that is less than curve order) to secret.
+fn to_secret(value: &BigUint) -> Secret {
+	let bytes = value.to_bytes_be();
+	let mut secret = [0u8; 32];
+	secret[32 - bytes.len()..].copy_from_slice(&bytes);
+	Secret::from(secret)
+}
+
+/// Compute inversion of value modulo given modulus.
+fn invert(value: &BigUint, modulus: &BigUint) -> Result<BigUint, Error> {
+	let value = BigInt::from(value.clone());
+	let modulus = BigInt::from(modulus.clone());
+	let egcd = value.extended_gcd(&modulus);
+	if !egcd.gcd.is_one() {
+		return Err(Error::Internal("value is not invertible".into()));
+	}
+
+	egcd.x.mod_floor(&modulus).to_biguint()
+		.ok_or_else(|| Error::Internal("value is not invertible".into()))
+}
+
+/// Raise base to the (possibly negative) power modulo given modulus.
+fn pow_signed(base: &BigUint, exponent: &BigInt, modulus: &BigUint) -> Result<BigUint, Error> {
+	match exponent.sign() {
+		Sign::Minus => Ok(invert(base, modulus)?.modpow(exponent.magnitude(), modulus)),
+		_ => Ok(base.modpow(exponent.magnitude(), modulus)),
+	}
+}
+
+/// Generate random invertible element of the multiplicative group of integers modulo given modulus.
+fn random_unit(modulus: &BigUint) -> BigUint {
+	loop {
+		let value = OsRng.gen_biguint_below(modulus);
+		if !value.is_zero() && value.gcd(modulus).is_one() {
+			return value;
+		}
+	}
+}
+
+/// Generate random signed integer in range [-bound; bound].
+fn random_signed(bound: &BigUint) -> BigInt {
+	let bound = BigInt::from(bound.clone());
+	OsRng.gen_bigint_range(&-&bound, &(bound + 1u32))
+}
+
+/// Compute (-1)^a * w^b * y (mod n).
+fn blum_adjusted(n: &BigUint, w: &BigUint, y: &BigUint, a: bool, b: bool) -> BigUint {
+	let value = match b {
+		true => (w * y) % n,
+		false => y.clone(),
+	};
+	match a {
+		true => (n - value) % n,
+		false => value,
+	}
+}
+
+/// Check if value is a quadratic residue modulo given odd prime, using Euler's criterion.
+fn is_quadratic_residue(value: &BigUint, prime: &BigUint) -> bool {
+	(value % prime).modpow(&((prime - 1u32) >> 1), prime).is_one()
+}
+
+/// Compute 4th root of value, which is a quadratic residue modulo given prime p = 3 (mod 4). The square root
+/// of such value is `value^((p + 1) / 4)`, which is a quadratic residue itself.
+fn fourth_root_mod_prime(value: &BigUint, prime: &BigUint) -> BigUint {
+	let exponent = (prime + 1u32) >> 2;
+	(value % prime).modpow(&exponent, prime).modpow(&exponent, prime)
+}
+
+/// Generate random Blum prime (p = 3 mod 4) of given size. Two highest bits are always set, so that the product
+/// of two such primes is exactly `2 * bits` long.
+fn generate_prime(bits: u64) -> BigUint {
+	let high_bits = BigUint::from(3u32) << (bits - 2) as usize;
+	let low_bits = BigUint::from(3u32);
+	loop {
+		let candidate = OsRng.gen_biguint(bits) | &high_bits | &low_bits;
+		if is_probable_prime(&candidate) {
+			return candidate;
+		}
+	}
+}
+
+/// Check if (odd) number is probable prime, using Miller-Rabin test.
+fn is_probable_prime(candidate: &BigUint) -> bool {
+	if SMALL_PRIMES.iter().any(|p| (candidate % *p).is_zero()) {
+		return false;
+	}
+
+	let one = BigUint::one();
+	let candidate_minus_one = candidate - 1u32;
+	let mut d = candidate_minus_one.clone();
+	let mut s = 0;
+	while d.is_even() {
+		d >>= 1;
+		s += 1;
+	}
+
+	let two = BigUint::from(2u32);
+	'rounds: for _ in 0..MILLER_RABIN_ROUNDS {
+		let a = OsRng.gen_biguint_range(&two, &candidate_minus_one);
+		let mut x = a.modpow(&d, candidate);
+		if x == one || x == candidate_minus_one {
+			continue;
+		}
+
+		for _ in 1..s {
+			x = x.modpow(&two, candidate);
+			if x == candidate_minus_one {
+				continue 'rounds;
+			}
+		}
+
+		return false;
+	}
+
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use num_bigint::BigUint;
+	use key_server_cluster::math;
+	use super::{PaillierKeyPair, PaillierPublic, encrypt_secret, prove_mta_request, verify_mta_request,
+		compute_mta_response, compute_mta_share, is_probable_prime, to_biguint};
+
+	#[test]
+	fn miller_rabin_works() {
+		assert!(is_probable_prime(&BigUint::from(104_729u32)));
+		assert!(!is_probable_prime(&BigUint::from(104_731u32)));
+		// Carmichael number
+		assert!(!is_probable_prime(&BigUint::from(8_911u32)));
+	}
+
+	#[test]
+	fn encryption_works() {
+		let key_pair = PaillierKeyPair::generate().unwrap();
+		let secret = math::generate_random_scalar().unwrap();
+		let encrypted = encrypt_secret(key_pair.public(), &secret).unwrap();
+		assert_eq!(key_pair.decrypt(&encrypted.ciphertext), to_biguint(&secret));
+	}
+
+	#[test]
+	fn public_serialization_works() {
+		let key_pair = PaillierKeyPair::generate().unwrap();
+		let serialized = key_pair.public().serialize();
+		let proof = key_pair.prove_public(b"context").unwrap();
+		assert_eq!(&PaillierPublic::deserialize(&serialized, &proof, b"context").unwrap(), key_pair.public());
+		assert!(PaillierPublic::deserialize(&serialized[1..], &proof, b"context").is_err());
+		assert!(PaillierPublic::deserialize(&serialized, &proof, b"other context").is_err());
+	}
+
+	#[test]
+	fn public_with_proof_of_other_key_is_rejected() {
+		let key_pair = PaillierKeyPair::generate().unwrap();
+		let other_key_pair = PaillierKeyPair::generate().unwrap();
+		let proof = other_key_pair.prove_public(b"context").unwrap();
+		assert!(PaillierPublic::deserialize(&key_pair.public().serialize(), &proof, b"context").is_err());
+	}
+
+	#[test]
+	fn mta_request_with_secret_out_of_range_is_rejected() {
+		let key_pair = PaillierKeyPair::generate().unwrap();
+		let verifier_key_pair = PaillierKeyPair::generate().unwrap();
+		let secret = math::generate_random_scalar().unwrap();
+		let mut encrypted = encrypt_secret(key_pair.public(), &secret).unwrap();
+
+		let proof = prove_mta_request(&key_pair, &encrypted, verifier_key_pair.public(), b"context").unwrap();
+		verify_mta_request(key_pair.public(), verifier_key_pair.public(), &encrypted.ciphertext(), &proof, b"context").unwrap();
+
+		encrypted.secret = &encrypted.secret + (BigUint::from(1u32) << 1024);
+		encrypted.ciphertext = key_pair.public().encrypt(&encrypted.secret, &encrypted.randomness);
+		let proof = prove_mta_request(&key_pair, &encrypted, verifier_key_pair.public(), b"context").unwrap();
+		assert!(verify_mta_request(key_pair.public(), verifier_key_pair.public(), &encrypted.ciphertext(), &proof, b"context").is_err());
+	}
+
+	#[test]
+	fn mta_works() {
+		let key_pair = PaillierKeyPair::generate().unwrap();
+		let other_key_pair = PaillierKeyPair::generate().unwrap();
+		for _ in 0..10 {
+			let a = math::generate_random_scalar().unwrap();
+			let b = math::generate_random_scalar().unwrap();
+
+			let encrypted_a = encrypt_secret(key_pair.public(), &a).unwrap();
+			let request_proof = prove_mta_request(&key_pair, &encrypted_a, other_key_pair.public(), b"alice").unwrap();
+			verify_mta_request(key_pair.public(), other_key_pair.public(), &encrypted_a.ciphertext(), &request_proof, b"alice").unwrap();
+			let (encrypted_response, response_proof, beta) = compute_mta_response(key_pair.public(),
+				&encrypted_a.ciphertext(), &b, b"bob").unwrap();
+			let alpha = compute_mta_share(&key_pair, &encrypted_a, &encrypted_response, &response_proof, b"bob").unwrap();
+
+			assert_eq!(math::compute_secret_sum(vec![alpha, beta].iter()).unwrap(),
+				math::compute_secret_mul(&a, &b).unwrap());
+			assert!(compute_mta_share(&key_pair, &encrypted_a, &encrypted_response, &response_proof, b"alice").is_err());
+		}
+	}
+}
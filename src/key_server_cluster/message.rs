@@ -17,9 +17,11 @@
 use std::fmt;
 use std::collections::{BTreeSet, BTreeMap};
 use crypto::publickey::Secret;
-use key_server_cluster::SessionId;
+use key_server_cluster::{SessionId, EcdsaSigningScheme, KeyExpiration};
+use key_server_cluster::math;
+use key_server_cluster::jobs::signing_job_schnorr::SchnorrSigningScheme;
 use super::{Error, SerializableH256, SerializablePublic, SerializableSecret,
-	SerializableSignature, SerializableMessageHash, SerializableRequester, SerializableAddress};
+	SerializableSignature, SerializableMessageHash, SerializableRequester, SerializableAddress, SerializableBytes};
 
 pub type MessageSessionId = SerializableH256;
 pub type MessageNodeId = SerializablePublic;
@@ -33,16 +35,38 @@ pub enum Message {
 	Generation(GenerationMessage),
 	/// Encryption message.
 	Encryption(EncryptionMessage),
+	/// Key deletion message.
+	KeyDeletion(KeyDeletionMessage),
+	/// Key import message.
+	KeyImport(KeyImportMessage),
 	/// Decryption message.
 	Decryption(DecryptionMessage),
 	/// Schnorr signing message.
 	SchnorrSigning(SchnorrSigningMessage),
 	/// ECDSA signing message.
 	EcdsaSigning(EcdsaSigningMessage),
+	/// Ed25519 key generation message.
+	EddsaGeneration(EddsaGenerationMessage),
+	/// EdDSA signing message.
+	EddsaSigning(EddsaSigningMessage),
+	/// BLS12-381 key generation message.
+	BlsGeneration(BlsGenerationMessage),
+	/// BLS signing message.
+	BlsSigning(BlsSigningMessage),
 	/// Key version negotiation message.
 	KeyVersionNegotiation(KeyVersionNegotiationMessage),
 	/// Share add message.
 	ShareAdd(ShareAddMessage),
+	/// Share refresh message.
+	ShareRefresh(ShareRefreshMessage),
+	/// Key rotation message.
+	KeyRotation(KeyRotationMessage),
+	/// Threshold change message.
+	ThresholdChange(ThresholdChangeMessage),
+	/// Key export message.
+	KeyExport(KeyExportMessage),
+	/// Randomness beacon message.
+	RandomnessBeacon(RandomnessBeaconMessage),
 	/// Servers set change message.
 	ServersSetChange(ServersSetChangeMessage),
 }
@@ -58,6 +82,12 @@ pub enum ClusterMessage {
 	KeepAlive(KeepAlive),
 	/// Keep alive message response.
 	KeepAliveResponse(KeepAliveResponse),
+	/// Reliably broadcasted message, sent by its origin.
+	ReliableBroadcastSend(ReliableBroadcastSend),
+	/// Reliably broadcasted message, echoed by other node.
+	ReliableBroadcastEcho(ReliableBroadcastEcho),
+	/// Node is ready to deliver reliably broadcasted message.
+	ReliableBroadcastReady(ReliableBroadcastReady),
 }
 
 /// All possible messages that can be sent during key generation session.
@@ -73,6 +103,8 @@ pub enum GenerationMessage {
 	KeysDissemination(KeysDissemination),
 	/// Broadcast self public key portion.
 	PublicKeyShare(PublicKeyShare),
+	/// Reveal keys that have been sent to the complaining node.
+	KeysComplaintResponse(KeysComplaintResponse),
 	/// Confirm that the joint public key has been computed.
 	JointPublicKey(JointPublicKey),
 	/// When session error has occured.
@@ -101,6 +133,36 @@ pub enum EncryptionMessage {
 	EncryptionSessionError(EncryptionSessionError),
 }
 
+/// All possible messages that can be sent during key deletion session.
+#[derive(Clone, Debug)]
+pub enum KeyDeletionMessage {
+	/// Initialize key deletion session.
+	InitializeKeyDeletionSession(InitializeKeyDeletionSession),
+	/// Confirm key deletion session initialization.
+	ConfirmKeyDeletionInitialization(ConfirmKeyDeletionInitialization),
+	/// Every node is asked to delete its key share.
+	CommitKeyDeletion(CommitKeyDeletion),
+	/// Node has deleted its key share.
+	ConfirmKeyDeletion(ConfirmKeyDeletion),
+	/// When key deletion session error has occured.
+	KeyDeletionSessionError(KeyDeletionSessionError),
+}
+
+/// All possible messages that can be sent during key import session.
+#[derive(Clone, Debug)]
+pub enum KeyImportMessage {
+	/// Initialize key import session.
+	InitializeKeyImportSession(InitializeKeyImportSession),
+	/// Confirm that the key share has been verified.
+	ConfirmKeyImportInitialization(ConfirmKeyImportInitialization),
+	/// Every node is asked to store its key share.
+	CommitKeyImport(CommitKeyImport),
+	/// Node has stored its key share.
+	ConfirmKeyImport(ConfirmKeyImport),
+	/// When key import session error has occured.
+	KeyImportSessionError(KeyImportSessionError),
+}
+
 /// All possible messages that can be sent during consensus establishing.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ConsensusMessage {
@@ -193,6 +255,84 @@ pub enum EcdsaSigningMessage {
 	EcdsaSigningSessionDelegation(EcdsaSigningSessionDelegation),
 	/// When delegated signing session is completed.
 	EcdsaSigningSessionDelegationCompleted(EcdsaSigningSessionDelegationCompleted),
+	/// Start MtA-based nonce generation.
+	EcdsaMtaNonceGenerationStart(EcdsaMtaNonceGenerationStart),
+	/// Paillier public key of the node, participating in MtA-based nonce generation.
+	EcdsaMtaPaillierPublic(EcdsaMtaPaillierPublic),
+	/// Encrypted share of inversed nonce (MtA request).
+	EcdsaMtaEncryptedNonceShare(EcdsaMtaEncryptedNonceShare),
+	/// MtA response.
+	EcdsaMtaResponse(EcdsaMtaResponse),
+}
+
+/// All possible messages that can be sent during Ed25519 key generation session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum EddsaGenerationMessage {
+	/// Initialize new Ed25519 key generation session.
+	InitializeEddsaGenerationSession(InitializeEddsaGenerationSession),
+	/// Confirm Ed25519 key generation session initialization.
+	ConfirmEddsaGenerationInitialization(ConfirmEddsaGenerationInitialization),
+	/// Secret subshare and polynom commitments are sent to every node.
+	EddsaKeysDissemination(EddsaKeysDissemination),
+	/// When session is completed on the node.
+	EddsaGenerationSessionCompleted(EddsaGenerationSessionCompleted),
+	/// When session error has occured.
+	EddsaGenerationSessionError(EddsaGenerationSessionError),
+}
+
+/// All possible messages that can be sent during EdDSA signing session.
+#[derive(Clone, Debug)]
+pub enum EddsaSigningMessage {
+	/// Consensus establishing message.
+	EddsaSigningConsensusMessage(EddsaSigningConsensusMessage),
+	/// Signature nonce generation message.
+	EddsaSigningGenerationMessage(EddsaSigningGenerationMessage),
+	/// Request partial signature from node.
+	EddsaRequestPartialSignature(EddsaRequestPartialSignature),
+	/// Partial signature is generated.
+	EddsaPartialSignature(EddsaPartialSignature),
+	/// Signing error occured.
+	EddsaSigningSessionError(EddsaSigningSessionError),
+	/// Signing session completed.
+	EddsaSigningSessionCompleted(EddsaSigningSessionCompleted),
+	/// When signing session is delegated to another node.
+	EddsaSigningSessionDelegation(EddsaSigningSessionDelegation),
+	/// When delegated signing session is completed.
+	EddsaSigningSessionDelegationCompleted(EddsaSigningSessionDelegationCompleted),
+}
+
+/// All possible messages that can be sent during BLS12-381 key generation session.
+#[derive(Clone, Debug)]
+pub enum BlsGenerationMessage {
+	/// Initialize new BLS12-381 key generation session.
+	InitializeBlsGenerationSession(InitializeBlsGenerationSession),
+	/// Confirm BLS12-381 key generation session initialization.
+	ConfirmBlsGenerationInitialization(ConfirmBlsGenerationInitialization),
+	/// Secret subshare and polynom commitments are sent to every node.
+	BlsKeysDissemination(BlsKeysDissemination),
+	/// When session is completed on the node.
+	BlsGenerationSessionCompleted(BlsGenerationSessionCompleted),
+	/// When session error has occured.
+	BlsGenerationSessionError(BlsGenerationSessionError),
+}
+
+/// All possible messages that can be sent during BLS signing session.
+#[derive(Clone, Debug)]
+pub enum BlsSigningMessage {
+	/// Consensus establishing message.
+	BlsSigningConsensusMessage(BlsSigningConsensusMessage),
+	/// Request partial signature from node.
+	BlsRequestPartialSignature(BlsRequestPartialSignature),
+	/// Partial signature is generated.
+	BlsPartialSignature(BlsPartialSignature),
+	/// Signing error occured.
+	BlsSigningSessionError(BlsSigningSessionError),
+	/// Signing session completed.
+	BlsSigningSessionCompleted(BlsSigningSessionCompleted),
+	/// When signing session is delegated to another node.
+	BlsSigningSessionDelegation(BlsSigningSessionDelegation),
+	/// When delegated signing session is completed.
+	BlsSigningSessionDelegationCompleted(BlsSigningSessionDelegationCompleted),
 }
 
 /// All possible messages that can be sent during servers set change session.
@@ -235,6 +375,83 @@ pub enum ShareAddMessage {
 	ShareAddError(ShareAddError),
 }
 
+/// All possible messages that can be sent during share refresh session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ShareRefreshMessage {
+	/// Initialize share refresh session.
+	InitializeShareRefreshSession(InitializeShareRefreshSession),
+	/// Confirm share refresh session initialization.
+	ConfirmShareRefreshInitialization(ConfirmShareRefreshInitialization),
+	/// Refreshing subshares are sent to every version holder.
+	ShareRefreshKeysDissemination(ShareRefreshKeysDissemination),
+	/// Confirm that refreshed key share has been saved.
+	ConfirmShareRefresh(ConfirmShareRefresh),
+	/// Remove obsolete key share version on all version holders.
+	CommitShareRefresh(CommitShareRefresh),
+	/// When session error has occured.
+	ShareRefreshError(ShareRefreshError),
+}
+
+/// All possible messages that can be sent during key rotation session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum KeyRotationMessage {
+	/// Initialize key rotation session.
+	InitializeKeyRotationSession(InitializeKeyRotationSession),
+	/// Confirm key rotation session initialization.
+	ConfirmKeyRotationInitialization(ConfirmKeyRotationInitialization),
+	/// Rotating subshares are sent to every version holder.
+	KeyRotationKeysDissemination(KeyRotationKeysDissemination),
+	/// Share of document key re-encryption is sent to master node.
+	ConfirmKeyRotation(ConfirmKeyRotation),
+	/// Save rotated key share && retire obsolete key shares on all version holders.
+	CommitKeyRotation(CommitKeyRotation),
+	/// When session error has occured.
+	KeyRotationError(KeyRotationError),
+}
+
+/// All possible messages that can be sent during threshold change session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ThresholdChangeMessage {
+	/// Initialize threshold change session.
+	InitializeThresholdChangeSession(InitializeThresholdChangeSession),
+	/// Confirm threshold change session initialization.
+	ConfirmThresholdChangeInitialization(ConfirmThresholdChangeInitialization),
+	/// Re-dealing subshares are sent to every version holder.
+	ThresholdChangeKeysDissemination(ThresholdChangeKeysDissemination),
+	/// Confirm that new key share has been computed.
+	ConfirmThresholdChange(ConfirmThresholdChange),
+	/// Replace obsolete key share with the new one on all version holders.
+	CommitThresholdChange(CommitThresholdChange),
+	/// When session error has occured.
+	ThresholdChangeError(ThresholdChangeError),
+}
+
+/// All possible messages that can be sent during key export session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum KeyExportMessage {
+	/// Initialize key export session.
+	InitializeKeyExportSession(InitializeKeyExportSession),
+	/// Encrypted secret shadow is sent to master node.
+	KeyExportShare(KeyExportShare),
+	/// When session error has occured.
+	KeyExportSessionError(KeyExportSessionError),
+}
+
+/// All possible messages that can be sent during randomness beacon session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RandomnessBeaconMessage {
+	/// Initialize randomness beacon session.
+	InitializeRandomnessBeaconSession(InitializeRandomnessBeaconSession),
+	/// Confirm randomness beacon session initialization.
+	ConfirmRandomnessBeaconInitialization(ConfirmRandomnessBeaconInitialization),
+	/// Wrapped random point generation message.
+	RandomnessBeaconPointGeneration(RandomnessBeaconPointGeneration),
+	/// Participant signature of the beacon transcript is sent to master node.
+	RandomnessBeaconTranscriptSignature(RandomnessBeaconTranscriptSignature),
+	/// When session error has occured.
+	RandomnessBeaconError(RandomnessBeaconError),
+}
+
 /// All possible messages that can be sent during key version negotiation message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum KeyVersionNegotiationMessage {
@@ -276,6 +493,43 @@ pub struct KeepAliveResponse {
 	pub session_id: Option<MessageSessionId>,
 }
 
+/// Reliably broadcasted message, sent by its origin to every other node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReliableBroadcastSend {
+	/// Broadcast id, unique among all broadcasts of the origin node.
+	pub broadcast_id: SerializableH256,
+	/// All nodes, participating in broadcast (including the origin).
+	pub nodes: BTreeSet<MessageNodeId>,
+	/// Serialized broadcasted message.
+	pub payload: SerializableBytes,
+}
+
+/// Reliably broadcasted message, echoed by the node that has received it from the origin.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReliableBroadcastEcho {
+	/// Broadcast origin.
+	pub origin: MessageNodeId,
+	/// Broadcast id, unique among all broadcasts of the origin node.
+	pub broadcast_id: SerializableH256,
+	/// All nodes, participating in broadcast (including the origin).
+	pub nodes: BTreeSet<MessageNodeId>,
+	/// Serialized broadcasted message.
+	pub payload: SerializableBytes,
+}
+
+/// Node is ready to deliver reliably broadcasted message with given hash.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReliableBroadcastReady {
+	/// Broadcast origin.
+	pub origin: MessageNodeId,
+	/// Broadcast id, unique among all broadcasts of the origin node.
+	pub broadcast_id: SerializableH256,
+	/// All nodes, participating in broadcast (including the origin).
+	pub nodes: BTreeSet<MessageNodeId>,
+	/// Hash of serialized broadcasted message.
+	pub payload_hash: SerializableH256,
+}
+
 /// Initialize new DKG session.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InitializeSession {
@@ -294,6 +548,12 @@ pub struct InitializeSession {
 	/// Decryption threshold. During decryption threshold-of-route.len() nodes must came to
 	/// consensus to successfully decrypt message.
 	pub threshold: usize,
+	/// Point after which generated key expires (None if key never expires).
+	#[serde(default)]
+	pub expiration: Option<KeyExpiration>,
+	/// ECDSA signing scheme of generated key.
+	#[serde(default)]
+	pub ecdsa_scheme: EcdsaSigningScheme,
 }
 
 /// Confirm DKG session initialization.
@@ -343,6 +603,24 @@ pub struct PublicKeyShare {
 	pub publics_footprint: SerializableH256,
 	/// Public key share proof.
 	pub public_share_proof: Vec<SerializablePublic>,
+	/// Nodes, which have sent invalid keys to this node.
+	#[serde(default)]
+	pub complaints: BTreeSet<MessageNodeId>,
+}
+
+/// Node is revealing keys that it has sent to the complaining node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeysComplaintResponse {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Node, which has complained about received keys.
+	pub complainer: MessageNodeId,
+	/// Secret 1, sent to the complaining node.
+	pub secret1: SerializableSecret,
+	/// Secret 2, sent to the complaining node.
+	pub secret2: SerializableSecret,
 }
 
 /// Node is sharing joint public key that it has computed.
@@ -389,6 +667,9 @@ pub struct InitializeEncryptionSession {
 	pub common_point: SerializablePublic,
 	/// Encrypted data.
 	pub encrypted_point: SerializablePublic,
+	/// Document key slot to store the key in (missing in messages from nodes without document key slots support).
+	#[serde(default)]
+	pub document_key_slot: Option<SerializableH256>,
 }
 
 /// Node is responding to encryption initialization request.
@@ -411,6 +692,115 @@ pub struct EncryptionSessionError {
 	pub error: Error,
 }
 
+/// Node is requested to check that the requester is allowed to delete the key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeKeyDeletionSession {
+	/// Key deletion session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Requester.
+	pub requester: SerializableRequester,
+}
+
+/// Node is responding to key deletion initialization request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmKeyDeletionInitialization {
+	/// Key deletion session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// True if node holds share of the key.
+	pub has_key_share: bool,
+}
+
+/// Node is requested to delete its key share.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitKeyDeletion {
+	/// Key deletion session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Node has deleted its key share.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmKeyDeletion {
+	/// Key deletion session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When key deletion session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyDeletionSessionError {
+	/// Key deletion session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Node is requested to verify its share of the imported key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeKeyImportSession {
+	/// Key import session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Author of the key.
+	pub author: SerializableAddress,
+	/// Commitments to coefficients of the dealer polynomial.
+	pub share_commitments: Vec<SerializablePublic>,
+	/// Id numbers of all share owners.
+	pub id_numbers: BTreeMap<MessageNodeId, SerializableSecret>,
+	/// Key share of the recipient, encrypted by the dealer with recipient public key.
+	pub encrypted_share: SerializableBytes,
+	/// ECDSA signing scheme of imported key.
+	#[serde(default)]
+	pub ecdsa_scheme: EcdsaSigningScheme,
+}
+
+/// Node has verified its share of the imported key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmKeyImportInitialization {
+	/// Key import session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Node is requested to store its share of the imported key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitKeyImport {
+	/// Key import session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Node has stored its share of the imported key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmKeyImport {
+	/// Key import session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When key import session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyImportSessionError {
+	/// Key import session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
 /// Node is asked to be part of consensus group.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InitializeConsensusSession {
@@ -418,6 +808,15 @@ pub struct InitializeConsensusSession {
 	pub requester: SerializableRequester,
 	/// Key version.
 	pub version: SerializableH256,
+	/// Path of the child key, which is used in the session. Empty if server key itself is used.
+	#[serde(default)]
+	pub derivation_path: Vec<u32>,
+	/// Ephemeral public of ECIES ciphertext, if ECDH secret is computed instead of decrypting document key.
+	#[serde(default)]
+	pub ecies_ephemeral_public: Option<SerializablePublic>,
+	/// Slot of the document key to decrypt. None if default document key is decrypted.
+	#[serde(default)]
+	pub document_key_slot: Option<SerializableH256>,
 }
 
 /// Node is responding to consensus initialization request.
@@ -483,8 +882,14 @@ pub struct SchnorrSigningGenerationMessage {
 	pub sub_session: SerializableSecret,
 	/// Session-level nonce.
 	pub session_nonce: u64,
+	/// Index of session key (nonce) in the batch.
+	#[serde(default)]
+	pub nonce_index: usize,
 	/// Generation message.
 	pub message: GenerationMessage,
+	/// Generation messages of other session keys in the batch (with their indices), sent within the same round.
+	#[serde(default)]
+	pub additional_messages: Vec<(usize, GenerationMessage)>,
 }
 
 /// Request partial Schnorr signature.
@@ -498,8 +903,14 @@ pub struct SchnorrRequestPartialSignature {
 	pub session_nonce: u64,
 	/// Request id.
 	pub request_id: SerializableSecret,
+	/// Signature scheme.
+	#[serde(default)]
+	pub scheme: SchnorrSigningScheme,
 	/// Message hash.
 	pub message_hash: SerializableMessageHash,
+	/// Hashes of other messages, signed in the same batch.
+	#[serde(default)]
+	pub additional_message_hashes: Vec<SerializableMessageHash>,
 	/// Selected nodes.
 	pub nodes: BTreeSet<MessageNodeId>,
 }
@@ -517,6 +928,9 @@ pub struct SchnorrPartialSignature {
 	pub request_id: SerializableSecret,
 	/// S part of signature.
 	pub partial_signature: SerializableSecret,
+	/// S parts of signatures of other messages, signed in the same batch.
+	#[serde(default)]
+	pub additional_partial_signatures: Vec<SerializableSecret>,
 }
 
 /// When Schnorr signing session error has occured.
@@ -556,8 +970,17 @@ pub struct SchnorrSigningSessionDelegation {
 	pub requester: SerializableRequester,
 	/// Key version.
 	pub version: SerializableH256,
+	/// Path of the child key, which is used in the session. Empty if server key itself is used.
+	#[serde(default)]
+	pub derivation_path: Vec<u32>,
+	/// Signature scheme.
+	#[serde(default)]
+	pub scheme: SchnorrSigningScheme,
 	/// Message hash.
 	pub message_hash: SerializableH256,
+	/// Hashes of other messages, signed in the same batch.
+	#[serde(default)]
+	pub additional_message_hashes: Vec<SerializableH256>,
 }
 
 /// When delegated Schnorr signing session is completed.
@@ -573,6 +996,9 @@ pub struct SchnorrSigningSessionDelegationCompleted {
 	pub signature_s: SerializableSecret,
 	/// C-portion of signature.
 	pub signature_c: SerializableSecret,
+	/// (C, S) signatures of other messages, signed in the same batch.
+	#[serde(default)]
+	pub additional_signatures: Vec<(SerializableSecret, SerializableSecret)>,
 }
 
 /// Consensus-related ECDSA signing message.
@@ -625,6 +1051,81 @@ pub struct EcdsaSigningInversedNonceCoeffShare {
 	pub session_nonce: u64,
 	/// Inversed nonce coefficient share.
 	pub inversed_nonce_coeff_share: SerializableSecret,
+	/// Attempt of MtA-based nonce generation, this share is computed in (always 0 for other schemes).
+	#[serde(default)]
+	pub mta_attempt: u32,
+}
+
+/// Start MtA-based ECDSA nonce generation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EcdsaMtaNonceGenerationStart {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Attempt of MtA-based nonce generation. It is restarted with other nodes if some node fails.
+	pub mta_attempt: u32,
+	/// Nodes, participating in signing.
+	pub nodes: BTreeSet<MessageNodeId>,
+}
+
+/// Paillier public key of the node, participating in MtA-based ECDSA nonce generation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EcdsaMtaPaillierPublic {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Attempt of MtA-based nonce generation.
+	pub mta_attempt: u32,
+	/// Sender' Paillier public key (along with ring-Pedersen parameters).
+	pub paillier_public: SerializableBytes,
+	/// Proof that sender' Paillier modulus is Paillier-Blum modulus && that ring-Pedersen parameters are correct.
+	pub paillier_public_proof: SerializableBytes,
+}
+
+/// Share of ECDSA inversed nonce, encrypted with sender' Paillier key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EcdsaMtaEncryptedNonceShare {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Attempt of MtA-based nonce generation.
+	pub mta_attempt: u32,
+	/// Encrypted share of inversed nonce.
+	pub encrypted_inv_nonce_share: SerializableBytes,
+	/// Proof that sender' Paillier modulus has no small factors && that encrypted share is in range.
+	pub encrypted_inv_nonce_share_proof: SerializableBytes,
+	/// Public of sender' gamma share.
+	pub gamma_public: SerializablePublic,
+}
+
+/// Response to the ECDSA MtA request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EcdsaMtaResponse {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Attempt of MtA-based nonce generation.
+	pub mta_attempt: u32,
+	/// Encrypted (inversed nonce share * gamma share + mask).
+	pub encrypted_inv_nonce_mul_gamma: SerializableBytes,
+	/// Proof that gamma share and mask of encrypted_inv_nonce_mul_gamma are in range.
+	pub encrypted_inv_nonce_mul_gamma_proof: SerializableBytes,
+	/// Encrypted (inversed nonce share * secret share + mask).
+	pub encrypted_inv_nonce_mul_secret: SerializableBytes,
+	/// Proof that secret share and mask of encrypted_inv_nonce_mul_secret are in range.
+	pub encrypted_inv_nonce_mul_secret_proof: SerializableBytes,
 }
 
 /// ECDSA inversion zero generation message.
@@ -709,6 +1210,9 @@ pub struct EcdsaSigningSessionDelegation {
 	pub requester: SerializableRequester,
 	/// Key version.
 	pub version: SerializableH256,
+	/// Path of the child key, which is used in the session. Empty if server key itself is used.
+	#[serde(default)]
+	pub derivation_path: Vec<u32>,
 	/// Message hash.
 	pub message_hash: SerializableH256,
 }
@@ -726,104 +1230,452 @@ pub struct EcdsaSigningSessionDelegationCompleted {
 	pub signature: SerializableSignature,
 }
 
-/// Consensus-related decryption message.
+/// Initialize new Ed25519 key generation session.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DecryptionConsensusMessage {
-	/// Generation session Id.
+pub struct InitializeEddsaGenerationSession {
+	/// Session Id.
 	pub session: MessageSessionId,
-	/// Signing session Id.
-	pub sub_session: SerializableSecret,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Session origin (in consensus initialization message).
-	pub origin: Option<SerializableAddress>,
-	/// Consensus message.
-	pub message: ConsensusMessage,
+	/// Session author.
+	pub author: SerializableAddress,
+	/// All session participants along with their identification numbers.
+	pub nodes: BTreeMap<MessageNodeId, SerializableSecret>,
+	/// Key threshold.
+	pub threshold: usize,
 }
 
-/// Node is requested to do a partial decryption.
+/// Confirm Ed25519 key generation session initialization.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct RequestPartialDecryption {
-	/// Encryption session Id.
+pub struct ConfirmEddsaGenerationInitialization {
+	/// Session Id.
 	pub session: MessageSessionId,
-	/// Decryption session Id.
-	pub sub_session: SerializableSecret,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Request id.
-	pub request_id: SerializableSecret,
-	/// Is shadow decryption requested? When true, decryption result
-	/// will be visible to the owner of requestor public key only.
-	pub is_shadow_decryption: bool,
-	/// Decryption result must be reconstructed on all participating nodes. This is useful
-	/// for service contract API so that all nodes from consensus group can confirm decryption.
-	pub is_broadcast_session: bool,
-	/// Nodes that are agreed to do a decryption.
-	pub nodes: BTreeSet<MessageNodeId>,
 }
 
-/// Node has partially decrypted the secret.
+/// Secret subshare and polynom commitments, sent to every node.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct PartialDecryption {
-	/// Encryption session Id.
+pub struct EddsaKeysDissemination {
+	/// Session Id.
 	pub session: MessageSessionId,
-	/// Decryption session Id.
-	pub sub_session: SerializableSecret,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Request id.
-	pub request_id: SerializableSecret,
-	/// Partially decrypted secret.
-	pub shadow_point: SerializablePublic,
-	/// Decrypt shadow coefficient (if requested), encrypted with requestor public.
-	pub decrypt_shadow: Option<Vec<u8>>,
+	/// Value of the sender' polynom at the receiver' id number.
+	pub secret_subshare: SerializableSecret,
+	/// Commitments to the sender' polynom coefficients (compressed Ed25519 points).
+	pub commitments: Vec<SerializableH256>,
 }
 
-/// When decryption session error has occured.
+/// Ed25519 key generation session is completed on the node.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DecryptionSessionError {
-	/// Encryption session Id.
+pub struct EddsaGenerationSessionCompleted {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Joint public key, computed by the node (compressed Ed25519 point).
+	pub joint_public: SerializableH256,
+}
+
+/// When Ed25519 key generation session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaGenerationSessionError {
+	/// Session Id.
 	pub session: MessageSessionId,
-	/// Decryption session Id.
-	pub sub_session: SerializableSecret,
 	/// Session-level nonce.
 	pub session_nonce: u64,
 	/// Error message.
 	pub error: Error,
 }
 
-/// When decryption session is completed.
+/// Consensus-related EdDSA signing message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DecryptionSessionCompleted {
-	/// Encryption session Id.
+pub struct EddsaSigningConsensusMessage {
+	/// Generation session Id.
 	pub session: MessageSessionId,
-	/// Decryption session Id.
+	/// Signing session Id.
 	pub sub_session: SerializableSecret,
 	/// Session-level nonce.
 	pub session_nonce: u64,
+	/// Consensus message.
+	pub message: ConsensusMessage,
 }
 
-/// When decryption session is delegated to another node.
+/// EdDSA signature nonce generation message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DecryptionSessionDelegation {
-	/// Encryption session Id.
+pub struct EddsaSigningGenerationMessage {
+	/// Generation session Id.
 	pub session: MessageSessionId,
-	/// Decryption session Id.
+	/// Signing session Id.
 	pub sub_session: SerializableSecret,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Session origin.
-	pub origin: Option<SerializableAddress>,
-	/// Requester.
-	pub requester: SerializableRequester,
-	/// Key version.
-	pub version: SerializableH256,
-	/// Is shadow decryption requested? When true, decryption result
-	/// will be visible to the owner of requestor public key only.
-	pub is_shadow_decryption: bool,
-	/// Decryption result must be reconstructed on all participating nodes. This is useful
-	/// for service contract API so that all nodes from consensus group can confirm decryption.
-	pub is_broadcast_session: bool,
+	/// Generation message.
+	pub message: EddsaGenerationMessage,
+}
+
+/// Request partial EdDSA signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaRequestPartialSignature {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// Message hash.
+	pub message_hash: SerializableMessageHash,
+	/// Selected nodes.
+	pub nodes: BTreeSet<MessageNodeId>,
+}
+
+/// Partial EdDSA signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaPartialSignature {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// S part of signature.
+	pub partial_signature: SerializableSecret,
+}
+
+/// When EdDSA signing session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaSigningSessionError {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// EdDSA signing session completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaSigningSessionCompleted {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When EdDSA signing session is delegated to another node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaSigningSessionDelegation {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Requester.
+	pub requester: SerializableRequester,
+	/// Key version.
+	pub version: SerializableH256,
+	/// Message hash.
+	pub message_hash: SerializableH256,
+}
+
+/// When delegated EdDSA signing session is completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaSigningSessionDelegationCompleted {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// R-portion of signature (compressed Ed25519 point).
+	pub signature_r: SerializableH256,
+	/// S-portion of signature.
+	pub signature_s: SerializableSecret,
+}
+
+/// Initialize new BLS12-381 key generation session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeBlsGenerationSession {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Session author.
+	pub author: SerializableAddress,
+	/// All session participants along with their identification numbers.
+	pub nodes: BTreeMap<MessageNodeId, SerializableSecret>,
+	/// Key threshold.
+	pub threshold: usize,
+}
+
+/// Confirm BLS12-381 key generation session initialization.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmBlsGenerationInitialization {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Secret subshare and polynom commitments, sent to every node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsKeysDissemination {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Value of the sender' polynom at the receiver' id number.
+	pub secret_subshare: SerializableSecret,
+	/// Commitments to the sender' polynom coefficients (compressed G1 points).
+	pub commitments: Vec<SerializableBytes>,
+}
+
+/// BLS12-381 key generation session is completed on the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsGenerationSessionCompleted {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Joint public key, computed by the node (compressed G1 point).
+	pub joint_public: SerializableBytes,
+}
+
+/// When BLS12-381 key generation session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsGenerationSessionError {
+	/// Session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Consensus-related BLS signing message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsSigningConsensusMessage {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Consensus message.
+	pub message: ConsensusMessage,
+}
+
+/// Request partial BLS signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsRequestPartialSignature {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// Message hash.
+	pub message_hash: SerializableMessageHash,
+	/// Selected nodes.
+	pub nodes: BTreeSet<MessageNodeId>,
+}
+
+/// Partial BLS signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsPartialSignature {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// Message point, multiplied by the secret share (compressed G2 point).
+	pub partial_signature: SerializableBytes,
+}
+
+/// When BLS signing session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsSigningSessionError {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// BLS signing session completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsSigningSessionCompleted {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When BLS signing session is delegated to another node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsSigningSessionDelegation {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Requester.
+	pub requester: SerializableRequester,
+	/// Key version.
+	pub version: SerializableH256,
+	/// Message hash.
+	pub message_hash: SerializableH256,
+}
+
+/// When delegated BLS signing session is completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlsSigningSessionDelegationCompleted {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Signature (compressed G2 point).
+	pub signature: SerializableBytes,
+}
+
+/// Consensus-related decryption message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecryptionConsensusMessage {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Signing session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Session origin (in consensus initialization message).
+	pub origin: Option<SerializableAddress>,
+	/// Consensus message.
+	pub message: ConsensusMessage,
+}
+
+/// Node is requested to do a partial decryption.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestPartialDecryption {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// Is shadow decryption requested? When true, decryption result
+	/// will be visible to the owner of requestor public key only.
+	pub is_shadow_decryption: bool,
+	/// Decryption result must be reconstructed on all participating nodes. This is useful
+	/// for service contract API so that all nodes from consensus group can confirm decryption.
+	pub is_broadcast_session: bool,
+	/// Nodes that are agreed to do a decryption.
+	pub nodes: BTreeSet<MessageNodeId>,
+}
+
+/// Node has partially decrypted the secret.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartialDecryption {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Request id.
+	pub request_id: SerializableSecret,
+	/// Partially decrypted secret.
+	pub shadow_point: SerializablePublic,
+	/// Decrypt shadow coefficient (if requested), encrypted with requestor public.
+	pub decrypt_shadow: Option<Vec<u8>>,
+	/// Proof (challenge, response) that shadow point has been computed using node public share.
+	/// Missing when shadow decryption is requested.
+	#[serde(default)]
+	pub decryption_proof: Option<(SerializableSecret, SerializableSecret)>,
+	/// Proof that shadow point has been computed using node public share && decrypt shadow.
+	/// Only present when shadow decryption is requested.
+	#[serde(default)]
+	pub shadow_decryption_proof: Option<ShadowDecryptionProof>,
+}
+
+/// When decryption session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecryptionSessionError {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// When decryption session is completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecryptionSessionCompleted {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When decryption session is delegated to another node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecryptionSessionDelegation {
+	/// Encryption session Id.
+	pub session: MessageSessionId,
+	/// Decryption session Id.
+	pub sub_session: SerializableSecret,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Session origin.
+	pub origin: Option<SerializableAddress>,
+	/// Requester.
+	pub requester: SerializableRequester,
+	/// Key version.
+	pub version: SerializableH256,
+	/// Path of the child key, which is used in the session. Empty if server key itself is used.
+	#[serde(default)]
+	pub derivation_path: Vec<u32>,
+	/// Ephemeral public of ECIES ciphertext, if ECDH secret is computed instead of decrypting document key.
+	#[serde(default)]
+	pub ecies_ephemeral_public: Option<SerializablePublic>,
+	/// Slot of the document key to decrypt. None if default document key is decrypted.
+	#[serde(default)]
+	pub document_key_slot: Option<SerializableH256>,
+	/// Is shadow decryption requested? When true, decryption result
+	/// will be visible to the owner of requestor public key only.
+	pub is_shadow_decryption: bool,
+	/// Decryption result must be reconstructed on all participating nodes. This is useful
+	/// for service contract API so that all nodes from consensus group can confirm decryption.
+	pub is_broadcast_session: bool,
 }
 
 /// When delegated decryption session is completed.
@@ -935,25 +1787,374 @@ pub struct ServersSetChangeDelegateResponse {
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Key id.
-	pub key_id: MessageSessionId,
+	/// Key id.
+	pub key_id: MessageSessionId,
+}
+
+/// Servers set change share add message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServersSetChangeShareAddMessage {
+	/// Servers set change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Unknown session id.
+	pub message: ShareAddMessage,
+}
+
+/// When servers set change session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServersSetChangeError {
+	/// Servers set change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// When servers set change session is completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServersSetChangeCompleted {
+	/// Servers set change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Consensus-related share add session message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareAddConsensusMessage {
+	/// Share add session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Consensus message.
+	pub message: ConsensusMessageOfShareAdd,
+}
+
+/// Key share common data is passed to new node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyShareCommon {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Common key data.
+	pub key_common: CommonKeyData,
+	/// Common (shared) encryption point.
+	pub common_point: Option<SerializablePublic>,
+	/// Encrypted point.
+	pub encrypted_point: Option<SerializablePublic>,
+	/// ECDSA signing scheme.
+	#[serde(default)]
+	pub ecdsa_scheme: EcdsaSigningScheme,
+	/// Key export flag.
+	#[serde(default)]
+	pub exported: bool,
+	/// Document keys, stored in slots: slot id => (common point, encrypted point).
+	#[serde(default)]
+	pub document_key_slots: BTreeMap<SerializableH256, (SerializablePublic, SerializablePublic)>,
+	/// Key expiration.
+	#[serde(default)]
+	pub expiration: Option<KeyExpiration>,
+	/// Selected version id numbers.
+	pub id_numbers: BTreeMap<MessageNodeId, SerializableSecret>,
+}
+
+/// Generated keys are sent to every node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NewKeysDissemination {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Sub share of rcevier' secret share.
+	pub secret_subshare: SerializableSecret,
+	/// Public share of the sender (public of its polynom absolute term).
+	pub public_share: SerializablePublic,
+	/// Publics of sender' polynom coefficients (except the absolute term).
+	pub commitments: Vec<SerializablePublic>,
+}
+
+/// When share add session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareAddError {
+	/// Generation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Initialize share refresh session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeShareRefreshSession {
+	/// Share refresh session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Key version to refresh.
+	pub version: SerializableH256,
+	/// Hash of the refreshed key version.
+	pub new_version: SerializableH256,
+	/// Id, signed by administrator (either key id, or bulk refresh id).
+	pub signed_id: SerializableH256,
+	/// Administrator signature of `signed_id`.
+	pub admin_signature: SerializableSignature,
+}
+
+/// Confirm share refresh session initialization.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmShareRefreshInitialization {
+	/// Share refresh session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Refreshing subshares are sent to every version holder.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareRefreshKeysDissemination {
+	/// Share refresh session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Value of sender' zero-constant polynom at receiver' id number.
+	pub secret_subshare: SerializableSecret,
+	/// Public share of the sender in the refreshed key version.
+	pub public_share: SerializablePublic,
+	/// Publics of sender' polynom coefficients (except the zero absolute term).
+	pub commitments: Vec<SerializablePublic>,
+}
+
+/// Confirm that refreshed key share has been saved.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmShareRefresh {
+	/// Share refresh session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Footprint of refreshed public shares of all version holders, computed by the sender.
+	pub public_shares_footprint: SerializableH256,
+}
+
+/// Remove obsolete key share version on all version holders.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitShareRefresh {
+	/// Share refresh session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When share refresh session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareRefreshError {
+	/// Share refresh session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Initialize key rotation session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeKeyRotationSession {
+	/// Key rotation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Key version to rotate.
+	pub version: SerializableH256,
+	/// Hash of the rotated key version.
+	pub new_version: SerializableH256,
+	/// Administrator signature of key id.
+	pub admin_signature: SerializableSignature,
+}
+
+/// Confirm key rotation session initialization.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmKeyRotationInitialization {
+	/// Key rotation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Rotating subshares are sent to every version holder.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyRotationKeysDissemination {
+	/// Key rotation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Value of sender' random polynom at receiver' id number.
+	pub secret_subshare: SerializableSecret,
+	/// Public of sender' random polynom absolute term.
+	pub public_delta: SerializablePublic,
+	/// Public share of the sender in the rotated key version.
+	pub public_share: SerializablePublic,
+	/// Publics of other coefficients of sender' random polynom.
+	pub commitments: Vec<SerializablePublic>,
+}
+
+/// Share of document key re-encryption is sent to master node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmKeyRotation {
+	/// Key rotation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Rotated joint public, computed by the sender.
+	pub new_public: SerializablePublic,
+	/// Footprint of rotated public shares of all version holders, computed by the sender.
+	pub public_shares_footprint: SerializableH256,
+	/// Sender' share of the document key re-encryption point (if document key is stored).
+	pub reencryption_point: Option<SerializablePublic>,
+	/// Proof that the sender' share of the re-encryption point is computed using its share of the key delta.
+	pub reencryption_proof: Option<(SerializableSecret, SerializableSecret)>,
+	/// Sender' shares of re-encryption points of document keys, stored in slots.
+	#[serde(default)]
+	pub slots_reencryption_points: BTreeMap<SerializableH256, SerializablePublic>,
+	/// Proofs of sender' shares of re-encryption points of document keys, stored in slots.
+	#[serde(default)]
+	pub slots_reencryption_proofs: BTreeMap<SerializableH256, (SerializableSecret, SerializableSecret)>,
+}
+
+/// Save rotated key share && retire obsolete key shares on all version holders.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitKeyRotation {
+	/// Key rotation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Document key, re-encrypted with rotated joint public (if document key is stored).
+	pub encrypted_point: Option<SerializablePublic>,
+	/// Document keys, stored in slots, re-encrypted with rotated joint public.
+	#[serde(default)]
+	pub slots_encrypted_points: BTreeMap<SerializableH256, SerializablePublic>,
+}
+
+/// When key rotation session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyRotationError {
+	/// Key rotation session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Initialize threshold change session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeThresholdChangeSession {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Key version to re-share.
+	pub version: SerializableH256,
+	/// Hash of the new key version.
+	pub new_version: SerializableH256,
+	/// New key threshold.
+	pub new_threshold: usize,
+	/// Administrator signature of keccak(key id || new threshold).
+	pub admin_signature: SerializableSignature,
+}
+
+/// Confirm threshold change session initialization.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmThresholdChangeInitialization {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// Re-dealing subshares are sent to every version holder.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdChangeKeysDissemination {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Value of sender' new-threshold polynom (with sender' secret share as absolute term) at receiver' id number.
+	pub secret_subshare: SerializableSecret,
+	/// Public share of the sender in the re-shared key version (public of the polynom absolute term).
+	pub public_share: SerializablePublic,
+	/// Publics of sender' polynom coefficients (except the absolute term).
+	pub commitments: Vec<SerializablePublic>,
+}
+
+/// Confirm that new key share has been computed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmThresholdChange {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Footprint of public shares of the new key version, computed by the sender.
+	pub public_shares_footprint: SerializableH256,
+}
+
+/// Replace obsolete key share with the new one on all version holders.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitThresholdChange {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When threshold change session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdChangeError {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// Initialize key export session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeKeyExportSession {
+	/// Key export session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Key version to export.
+	pub version: SerializableH256,
+	/// Public of the exported key recipient.
+	pub recipient: SerializablePublic,
+	/// Approvals expiry (unix timestamp, in seconds).
+	pub expiry: u64,
+	/// Approvers signatures of keccak(key id || recipient || expiry).
+	pub approvals: Vec<SerializableSignature>,
+	/// Version holders, whose secret shadows are used to reconstruct the key.
+	pub shadow_holders: BTreeSet<MessageNodeId>,
 }
 
-/// Servers set change share add message.
+/// Encrypted secret shadow is sent to master node.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ServersSetChangeShareAddMessage {
-	/// Servers set change session Id.
+pub struct KeyExportShare {
+	/// Key export session Id.
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Unknown session id.
-	pub message: ShareAddMessage,
+	/// Secret shadow of the sender, encrypted with recipient public.
+	pub encrypted_secret_shadow: SerializableBytes,
 }
 
-/// When servers set change session error has occured.
+/// When key export session error has occured.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ServersSetChangeError {
-	/// Servers set change session Id.
+pub struct KeyExportSessionError {
+	/// Key export session Id.
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
@@ -961,58 +2162,52 @@ pub struct ServersSetChangeError {
 	pub error: Error,
 }
 
-/// When servers set change session is completed.
+/// Initialize randomness beacon session.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ServersSetChangeCompleted {
-	/// Servers set change session Id.
+pub struct InitializeRandomnessBeaconSession {
+	/// Randomness beacon session Id.
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
+	/// All participants of the beacon session.
+	pub participants: BTreeSet<MessageNodeId>,
 }
 
-/// Consensus-related share add session message.
+/// Confirm randomness beacon session initialization.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ShareAddConsensusMessage {
-	/// Share add session Id.
+pub struct ConfirmRandomnessBeaconInitialization {
+	/// Randomness beacon session Id.
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Consensus message.
-	pub message: ConsensusMessageOfShareAdd,
 }
 
-/// Key share common data is passed to new node.
+/// Wrapped random point generation message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct KeyShareCommon {
-	/// Generation session Id.
+pub struct RandomnessBeaconPointGeneration {
+	/// Randomness beacon session Id.
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Common key data.
-	pub key_common: CommonKeyData,
-	/// Common (shared) encryption point.
-	pub common_point: Option<SerializablePublic>,
-	/// Encrypted point.
-	pub encrypted_point: Option<SerializablePublic>,
-	/// Selected version id numbers.
-	pub id_numbers: BTreeMap<MessageNodeId, SerializableSecret>,
+	/// Wrapped random point generation message.
+	pub message: RandomPointGenerationMessage,
 }
 
-/// Generated keys are sent to every node.
+/// Participant signature of the beacon transcript.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct NewKeysDissemination {
-	/// Generation session Id.
+pub struct RandomnessBeaconTranscriptSignature {
+	/// Randomness beacon session Id.
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
-	/// Sub share of rcevier' secret share.
-	pub secret_subshare: SerializableSecret,
+	/// Signature of the transcript hash, made with the node key.
+	pub signature: SerializableSignature,
 }
 
-/// When share add session error has occured.
+/// When randomness beacon session error has occured.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ShareAddError {
-	/// Generation session Id.
+pub struct RandomnessBeaconError {
+	/// Randomness beacon session Id.
 	pub session: MessageSessionId,
 	/// Session-level nonce.
 	pub session_nonce: u64,
@@ -1057,6 +2252,19 @@ pub struct CommonKeyData {
 	pub public: SerializablePublic,
 }
 
+/// Proof of the shadow point, computed for shadow decryption.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShadowDecryptionProof {
+	/// Public of the decrypt shadow.
+	pub decrypt_shadow_public: SerializablePublic,
+	/// Public of the shadow key.
+	pub shadow_key_public: SerializablePublic,
+	/// Proof (challenge, response) that shadow point has been computed using shadow key.
+	pub shadow_point_proof: (SerializableSecret, SerializableSecret),
+	/// Proof (challenge, response) that shadow key has been computed using node shadow && decrypt shadow.
+	pub decrypt_shadow_proof: (SerializableSecret, SerializableSecret),
+}
+
 /// When key versions error has occured.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct KeyVersionsError {
@@ -1099,6 +2307,8 @@ impl Message {
 		match *self {
 			Message::Generation(GenerationMessage::InitializeSession(_)) => true,
 			Message::Encryption(EncryptionMessage::InitializeEncryptionSession(_)) => true,
+			Message::KeyDeletion(KeyDeletionMessage::InitializeKeyDeletionSession(_)) => true,
+			Message::KeyImport(KeyImportMessage::InitializeKeyImportSession(_)) => true,
 			Message::Decryption(DecryptionMessage::DecryptionConsensusMessage(ref msg)) => match msg.message {
 				ConsensusMessage::InitializeConsensusSession(_) => true,
 				_ => false
@@ -1111,12 +2321,27 @@ impl Message {
 				ConsensusMessage::InitializeConsensusSession(_) => true,
 				_ => false
 			},
+			Message::EddsaGeneration(EddsaGenerationMessage::InitializeEddsaGenerationSession(_)) => true,
+			Message::EddsaSigning(EddsaSigningMessage::EddsaSigningConsensusMessage(ref msg)) => match msg.message {
+				ConsensusMessage::InitializeConsensusSession(_) => true,
+				_ => false
+			},
+			Message::BlsGeneration(BlsGenerationMessage::InitializeBlsGenerationSession(_)) => true,
+			Message::BlsSigning(BlsSigningMessage::BlsSigningConsensusMessage(ref msg)) => match msg.message {
+				ConsensusMessage::InitializeConsensusSession(_) => true,
+				_ => false
+			},
 			Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::RequestKeyVersions(_)) => true,
 			Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::KeyVersionsError(ref msg)) if msg.continue_with.is_some() => true,
 			Message::ShareAdd(ShareAddMessage::ShareAddConsensusMessage(ref msg)) => match msg.message {
 				ConsensusMessageOfShareAdd::InitializeConsensusSession(_) => true,
 				_ => false
 			},
+			Message::ShareRefresh(ShareRefreshMessage::InitializeShareRefreshSession(_)) => true,
+			Message::KeyRotation(KeyRotationMessage::InitializeKeyRotationSession(_)) => true,
+			Message::ThresholdChange(ThresholdChangeMessage::InitializeThresholdChangeSession(_)) => true,
+			Message::KeyExport(KeyExportMessage::InitializeKeyExportSession(_)) => true,
+			Message::RandomnessBeacon(RandomnessBeaconMessage::InitializeRandomnessBeaconSession(_)) => true,
 			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeConsensusMessage(ref msg)) => match msg.message {
 				ConsensusMessageWithServersSet::InitializeConsensusSession(_) => true,
 				_ => false
@@ -1130,6 +2355,8 @@ impl Message {
 			Message::Decryption(DecryptionMessage::DecryptionSessionDelegation(_)) => true,
 			Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionDelegation(_)) => true,
 			Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionDelegation(_)) => true,
+			Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionDelegation(_)) => true,
+			Message::BlsSigning(BlsSigningMessage::BlsSigningSessionDelegation(_)) => true,
 			_ => false,
 		}
 	}
@@ -1138,11 +2365,22 @@ impl Message {
 		match *self {
 			Message::Generation(GenerationMessage::SessionError(_)) => true,
 			Message::Encryption(EncryptionMessage::EncryptionSessionError(_)) => true,
+			Message::KeyDeletion(KeyDeletionMessage::KeyDeletionSessionError(_)) => true,
+			Message::KeyImport(KeyImportMessage::KeyImportSessionError(_)) => true,
 			Message::Decryption(DecryptionMessage::DecryptionSessionError(_)) => true,
 			Message::SchnorrSigning(SchnorrSigningMessage::SchnorrSigningSessionError(_)) => true,
 			Message::EcdsaSigning(EcdsaSigningMessage::EcdsaSigningSessionError(_)) => true,
+			Message::EddsaGeneration(EddsaGenerationMessage::EddsaGenerationSessionError(_)) => true,
+			Message::EddsaSigning(EddsaSigningMessage::EddsaSigningSessionError(_)) => true,
+			Message::BlsGeneration(BlsGenerationMessage::BlsGenerationSessionError(_)) => true,
+			Message::BlsSigning(BlsSigningMessage::BlsSigningSessionError(_)) => true,
 			Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::KeyVersionsError(_)) => true,
 			Message::ShareAdd(ShareAddMessage::ShareAddError(_)) => true,
+			Message::ShareRefresh(ShareRefreshMessage::ShareRefreshError(_)) => true,
+			Message::KeyRotation(KeyRotationMessage::KeyRotationError(_)) => true,
+			Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeError(_)) => true,
+			Message::KeyExport(KeyExportMessage::KeyExportSessionError(_)) => true,
+			Message::RandomnessBeacon(RandomnessBeaconMessage::RandomnessBeaconError(_)) => true,
 			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeError(_)) => true,
 			_ => false,
 		}
@@ -1160,10 +2398,21 @@ impl Message {
 			Message::Cluster(_) => None,
 			Message::Generation(ref message) => Some(message.session_nonce()),
 			Message::Encryption(ref message) => Some(message.session_nonce()),
+			Message::KeyDeletion(ref message) => Some(message.session_nonce()),
+			Message::KeyImport(ref message) => Some(message.session_nonce()),
 			Message::Decryption(ref message) => Some(message.session_nonce()),
 			Message::SchnorrSigning(ref message) => Some(message.session_nonce()),
 			Message::EcdsaSigning(ref message) => Some(message.session_nonce()),
+			Message::EddsaGeneration(ref message) => Some(message.session_nonce()),
+			Message::EddsaSigning(ref message) => Some(message.session_nonce()),
+			Message::BlsGeneration(ref message) => Some(message.session_nonce()),
+			Message::BlsSigning(ref message) => Some(message.session_nonce()),
 			Message::ShareAdd(ref message) => Some(message.session_nonce()),
+			Message::ShareRefresh(ref message) => Some(message.session_nonce()),
+			Message::KeyRotation(ref message) => Some(message.session_nonce()),
+			Message::ThresholdChange(ref message) => Some(message.session_nonce()),
+			Message::KeyExport(ref message) => Some(message.session_nonce()),
+			Message::RandomnessBeacon(ref message) => Some(message.session_nonce()),
 			Message::ServersSetChange(ref message) => Some(message.session_nonce()),
 			Message::KeyVersionNegotiation(ref message) => Some(message.session_nonce()),
 		}
@@ -1178,6 +2427,7 @@ impl GenerationMessage {
 			GenerationMessage::DerivedPointGeneration(ref msg) => &msg.session,
 			GenerationMessage::KeysDissemination(ref msg) => &msg.session,
 			GenerationMessage::PublicKeyShare(ref msg) => &msg.session,
+			GenerationMessage::KeysComplaintResponse(ref msg) => &msg.session,
 			GenerationMessage::JointPublicKey(ref msg) => &msg.session,
 			GenerationMessage::SessionError(ref msg) => &msg.session,
 			GenerationMessage::SessionCompleted(ref msg) => &msg.session,
@@ -1191,6 +2441,7 @@ impl GenerationMessage {
 			GenerationMessage::DerivedPointGeneration(ref msg) => msg.session_nonce,
 			GenerationMessage::KeysDissemination(ref msg) => msg.session_nonce,
 			GenerationMessage::PublicKeyShare(ref msg) => msg.session_nonce,
+			GenerationMessage::KeysComplaintResponse(ref msg) => msg.session_nonce,
 			GenerationMessage::JointPublicKey(ref msg) => msg.session_nonce,
 			GenerationMessage::SessionError(ref msg) => msg.session_nonce,
 			GenerationMessage::SessionCompleted(ref msg) => msg.session_nonce,
@@ -1216,6 +2467,50 @@ impl EncryptionMessage {
 	}
 }
 
+impl KeyDeletionMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			KeyDeletionMessage::InitializeKeyDeletionSession(ref msg) => &msg.session,
+			KeyDeletionMessage::ConfirmKeyDeletionInitialization(ref msg) => &msg.session,
+			KeyDeletionMessage::CommitKeyDeletion(ref msg) => &msg.session,
+			KeyDeletionMessage::ConfirmKeyDeletion(ref msg) => &msg.session,
+			KeyDeletionMessage::KeyDeletionSessionError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			KeyDeletionMessage::InitializeKeyDeletionSession(ref msg) => msg.session_nonce,
+			KeyDeletionMessage::ConfirmKeyDeletionInitialization(ref msg) => msg.session_nonce,
+			KeyDeletionMessage::CommitKeyDeletion(ref msg) => msg.session_nonce,
+			KeyDeletionMessage::ConfirmKeyDeletion(ref msg) => msg.session_nonce,
+			KeyDeletionMessage::KeyDeletionSessionError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl KeyImportMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			KeyImportMessage::InitializeKeyImportSession(ref msg) => &msg.session,
+			KeyImportMessage::ConfirmKeyImportInitialization(ref msg) => &msg.session,
+			KeyImportMessage::CommitKeyImport(ref msg) => &msg.session,
+			KeyImportMessage::ConfirmKeyImport(ref msg) => &msg.session,
+			KeyImportMessage::KeyImportSessionError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			KeyImportMessage::InitializeKeyImportSession(ref msg) => msg.session_nonce,
+			KeyImportMessage::ConfirmKeyImportInitialization(ref msg) => msg.session_nonce,
+			KeyImportMessage::CommitKeyImport(ref msg) => msg.session_nonce,
+			KeyImportMessage::ConfirmKeyImport(ref msg) => msg.session_nonce,
+			KeyImportMessage::KeyImportSessionError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
 impl DecryptionMessage {
 	pub fn session_id(&self) -> &SessionId {
 		match *self {
@@ -1309,6 +2604,10 @@ impl EcdsaSigningMessage {
 			EcdsaSigningMessage::EcdsaSigningSessionCompleted(ref msg) => &msg.session,
 			EcdsaSigningMessage::EcdsaSigningSessionDelegation(ref msg) => &msg.session,
 			EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(ref msg) => &msg.session,
+			EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(ref msg) => &msg.session,
+			EcdsaSigningMessage::EcdsaMtaPaillierPublic(ref msg) => &msg.session,
+			EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(ref msg) => &msg.session,
+			EcdsaSigningMessage::EcdsaMtaResponse(ref msg) => &msg.session,
 		}
 	}
 
@@ -1325,6 +2624,10 @@ impl EcdsaSigningMessage {
 			EcdsaSigningMessage::EcdsaSigningSessionCompleted(ref msg) => &msg.sub_session,
 			EcdsaSigningMessage::EcdsaSigningSessionDelegation(ref msg) => &msg.sub_session,
 			EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(ref msg) => &msg.sub_session,
+			EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(ref msg) => &msg.sub_session,
+			EcdsaSigningMessage::EcdsaMtaPaillierPublic(ref msg) => &msg.sub_session,
+			EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(ref msg) => &msg.sub_session,
+			EcdsaSigningMessage::EcdsaMtaResponse(ref msg) => &msg.sub_session,
 		}
 	}
 
@@ -1341,6 +2644,133 @@ impl EcdsaSigningMessage {
 			EcdsaSigningMessage::EcdsaSigningSessionCompleted(ref msg) => msg.session_nonce,
 			EcdsaSigningMessage::EcdsaSigningSessionDelegation(ref msg) => msg.session_nonce,
 			EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(ref msg) => msg.session_nonce,
+			EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(ref msg) => msg.session_nonce,
+			EcdsaSigningMessage::EcdsaMtaPaillierPublic(ref msg) => msg.session_nonce,
+			EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(ref msg) => msg.session_nonce,
+			EcdsaSigningMessage::EcdsaMtaResponse(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl EddsaGenerationMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			EddsaGenerationMessage::InitializeEddsaGenerationSession(ref msg) => &msg.session,
+			EddsaGenerationMessage::ConfirmEddsaGenerationInitialization(ref msg) => &msg.session,
+			EddsaGenerationMessage::EddsaKeysDissemination(ref msg) => &msg.session,
+			EddsaGenerationMessage::EddsaGenerationSessionCompleted(ref msg) => &msg.session,
+			EddsaGenerationMessage::EddsaGenerationSessionError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			EddsaGenerationMessage::InitializeEddsaGenerationSession(ref msg) => msg.session_nonce,
+			EddsaGenerationMessage::ConfirmEddsaGenerationInitialization(ref msg) => msg.session_nonce,
+			EddsaGenerationMessage::EddsaKeysDissemination(ref msg) => msg.session_nonce,
+			EddsaGenerationMessage::EddsaGenerationSessionCompleted(ref msg) => msg.session_nonce,
+			EddsaGenerationMessage::EddsaGenerationSessionError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl EddsaSigningMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			EddsaSigningMessage::EddsaSigningConsensusMessage(ref msg) => &msg.session,
+			EddsaSigningMessage::EddsaSigningGenerationMessage(ref msg) => &msg.session,
+			EddsaSigningMessage::EddsaRequestPartialSignature(ref msg) => &msg.session,
+			EddsaSigningMessage::EddsaPartialSignature(ref msg) => &msg.session,
+			EddsaSigningMessage::EddsaSigningSessionError(ref msg) => &msg.session,
+			EddsaSigningMessage::EddsaSigningSessionCompleted(ref msg) => &msg.session,
+			EddsaSigningMessage::EddsaSigningSessionDelegation(ref msg) => &msg.session,
+			EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn sub_session_id(&self) -> &Secret {
+		match *self {
+			EddsaSigningMessage::EddsaSigningConsensusMessage(ref msg) => &msg.sub_session,
+			EddsaSigningMessage::EddsaSigningGenerationMessage(ref msg) => &msg.sub_session,
+			EddsaSigningMessage::EddsaRequestPartialSignature(ref msg) => &msg.sub_session,
+			EddsaSigningMessage::EddsaPartialSignature(ref msg) => &msg.sub_session,
+			EddsaSigningMessage::EddsaSigningSessionError(ref msg) => &msg.sub_session,
+			EddsaSigningMessage::EddsaSigningSessionCompleted(ref msg) => &msg.sub_session,
+			EddsaSigningMessage::EddsaSigningSessionDelegation(ref msg) => &msg.sub_session,
+			EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(ref msg) => &msg.sub_session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			EddsaSigningMessage::EddsaSigningConsensusMessage(ref msg) => msg.session_nonce,
+			EddsaSigningMessage::EddsaSigningGenerationMessage(ref msg) => msg.session_nonce,
+			EddsaSigningMessage::EddsaRequestPartialSignature(ref msg) => msg.session_nonce,
+			EddsaSigningMessage::EddsaPartialSignature(ref msg) => msg.session_nonce,
+			EddsaSigningMessage::EddsaSigningSessionError(ref msg) => msg.session_nonce,
+			EddsaSigningMessage::EddsaSigningSessionCompleted(ref msg) => msg.session_nonce,
+			EddsaSigningMessage::EddsaSigningSessionDelegation(ref msg) => msg.session_nonce,
+			EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl BlsGenerationMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			BlsGenerationMessage::InitializeBlsGenerationSession(ref msg) => &msg.session,
+			BlsGenerationMessage::ConfirmBlsGenerationInitialization(ref msg) => &msg.session,
+			BlsGenerationMessage::BlsKeysDissemination(ref msg) => &msg.session,
+			BlsGenerationMessage::BlsGenerationSessionCompleted(ref msg) => &msg.session,
+			BlsGenerationMessage::BlsGenerationSessionError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			BlsGenerationMessage::InitializeBlsGenerationSession(ref msg) => msg.session_nonce,
+			BlsGenerationMessage::ConfirmBlsGenerationInitialization(ref msg) => msg.session_nonce,
+			BlsGenerationMessage::BlsKeysDissemination(ref msg) => msg.session_nonce,
+			BlsGenerationMessage::BlsGenerationSessionCompleted(ref msg) => msg.session_nonce,
+			BlsGenerationMessage::BlsGenerationSessionError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl BlsSigningMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			BlsSigningMessage::BlsSigningConsensusMessage(ref msg) => &msg.session,
+			BlsSigningMessage::BlsRequestPartialSignature(ref msg) => &msg.session,
+			BlsSigningMessage::BlsPartialSignature(ref msg) => &msg.session,
+			BlsSigningMessage::BlsSigningSessionError(ref msg) => &msg.session,
+			BlsSigningMessage::BlsSigningSessionCompleted(ref msg) => &msg.session,
+			BlsSigningMessage::BlsSigningSessionDelegation(ref msg) => &msg.session,
+			BlsSigningMessage::BlsSigningSessionDelegationCompleted(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn sub_session_id(&self) -> &Secret {
+		match *self {
+			BlsSigningMessage::BlsSigningConsensusMessage(ref msg) => &msg.sub_session,
+			BlsSigningMessage::BlsRequestPartialSignature(ref msg) => &msg.sub_session,
+			BlsSigningMessage::BlsPartialSignature(ref msg) => &msg.sub_session,
+			BlsSigningMessage::BlsSigningSessionError(ref msg) => &msg.sub_session,
+			BlsSigningMessage::BlsSigningSessionCompleted(ref msg) => &msg.sub_session,
+			BlsSigningMessage::BlsSigningSessionDelegation(ref msg) => &msg.sub_session,
+			BlsSigningMessage::BlsSigningSessionDelegationCompleted(ref msg) => &msg.sub_session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			BlsSigningMessage::BlsSigningConsensusMessage(ref msg) => msg.session_nonce,
+			BlsSigningMessage::BlsRequestPartialSignature(ref msg) => msg.session_nonce,
+			BlsSigningMessage::BlsPartialSignature(ref msg) => msg.session_nonce,
+			BlsSigningMessage::BlsSigningSessionError(ref msg) => msg.session_nonce,
+			BlsSigningMessage::BlsSigningSessionCompleted(ref msg) => msg.session_nonce,
+			BlsSigningMessage::BlsSigningSessionDelegation(ref msg) => msg.session_nonce,
+			BlsSigningMessage::BlsSigningSessionDelegationCompleted(ref msg) => msg.session_nonce,
 		}
 	}
 }
@@ -1399,6 +2829,118 @@ impl ShareAddMessage {
 	}
 }
 
+impl ShareRefreshMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			ShareRefreshMessage::InitializeShareRefreshSession(ref msg) => &msg.session,
+			ShareRefreshMessage::ConfirmShareRefreshInitialization(ref msg) => &msg.session,
+			ShareRefreshMessage::ShareRefreshKeysDissemination(ref msg) => &msg.session,
+			ShareRefreshMessage::ConfirmShareRefresh(ref msg) => &msg.session,
+			ShareRefreshMessage::CommitShareRefresh(ref msg) => &msg.session,
+			ShareRefreshMessage::ShareRefreshError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			ShareRefreshMessage::InitializeShareRefreshSession(ref msg) => msg.session_nonce,
+			ShareRefreshMessage::ConfirmShareRefreshInitialization(ref msg) => msg.session_nonce,
+			ShareRefreshMessage::ShareRefreshKeysDissemination(ref msg) => msg.session_nonce,
+			ShareRefreshMessage::ConfirmShareRefresh(ref msg) => msg.session_nonce,
+			ShareRefreshMessage::CommitShareRefresh(ref msg) => msg.session_nonce,
+			ShareRefreshMessage::ShareRefreshError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl KeyRotationMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			KeyRotationMessage::InitializeKeyRotationSession(ref msg) => &msg.session,
+			KeyRotationMessage::ConfirmKeyRotationInitialization(ref msg) => &msg.session,
+			KeyRotationMessage::KeyRotationKeysDissemination(ref msg) => &msg.session,
+			KeyRotationMessage::ConfirmKeyRotation(ref msg) => &msg.session,
+			KeyRotationMessage::CommitKeyRotation(ref msg) => &msg.session,
+			KeyRotationMessage::KeyRotationError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			KeyRotationMessage::InitializeKeyRotationSession(ref msg) => msg.session_nonce,
+			KeyRotationMessage::ConfirmKeyRotationInitialization(ref msg) => msg.session_nonce,
+			KeyRotationMessage::KeyRotationKeysDissemination(ref msg) => msg.session_nonce,
+			KeyRotationMessage::ConfirmKeyRotation(ref msg) => msg.session_nonce,
+			KeyRotationMessage::CommitKeyRotation(ref msg) => msg.session_nonce,
+			KeyRotationMessage::KeyRotationError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl ThresholdChangeMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			ThresholdChangeMessage::InitializeThresholdChangeSession(ref msg) => &msg.session,
+			ThresholdChangeMessage::ConfirmThresholdChangeInitialization(ref msg) => &msg.session,
+			ThresholdChangeMessage::ThresholdChangeKeysDissemination(ref msg) => &msg.session,
+			ThresholdChangeMessage::ConfirmThresholdChange(ref msg) => &msg.session,
+			ThresholdChangeMessage::CommitThresholdChange(ref msg) => &msg.session,
+			ThresholdChangeMessage::ThresholdChangeError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			ThresholdChangeMessage::InitializeThresholdChangeSession(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ConfirmThresholdChangeInitialization(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ThresholdChangeKeysDissemination(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ConfirmThresholdChange(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::CommitThresholdChange(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ThresholdChangeError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl KeyExportMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			KeyExportMessage::InitializeKeyExportSession(ref msg) => &msg.session,
+			KeyExportMessage::KeyExportShare(ref msg) => &msg.session,
+			KeyExportMessage::KeyExportSessionError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			KeyExportMessage::InitializeKeyExportSession(ref msg) => msg.session_nonce,
+			KeyExportMessage::KeyExportShare(ref msg) => msg.session_nonce,
+			KeyExportMessage::KeyExportSessionError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
+impl RandomnessBeaconMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			RandomnessBeaconMessage::InitializeRandomnessBeaconSession(ref msg) => &msg.session,
+			RandomnessBeaconMessage::ConfirmRandomnessBeaconInitialization(ref msg) => &msg.session,
+			RandomnessBeaconMessage::RandomnessBeaconPointGeneration(ref msg) => &msg.session,
+			RandomnessBeaconMessage::RandomnessBeaconTranscriptSignature(ref msg) => &msg.session,
+			RandomnessBeaconMessage::RandomnessBeaconError(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			RandomnessBeaconMessage::InitializeRandomnessBeaconSession(ref msg) => msg.session_nonce,
+			RandomnessBeaconMessage::ConfirmRandomnessBeaconInitialization(ref msg) => msg.session_nonce,
+			RandomnessBeaconMessage::RandomnessBeaconPointGeneration(ref msg) => msg.session_nonce,
+			RandomnessBeaconMessage::RandomnessBeaconTranscriptSignature(ref msg) => msg.session_nonce,
+			RandomnessBeaconMessage::RandomnessBeaconError(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
 impl KeyVersionNegotiationMessage {
 	pub fn session_id(&self) -> &SessionId {
 		match *self {
@@ -1431,11 +2973,22 @@ impl fmt::Display for Message {
 			Message::Cluster(ref message) => write!(f, "Cluster.{}", message),
 			Message::Generation(ref message) => write!(f, "Generation.{}", message),
 			Message::Encryption(ref message) => write!(f, "Encryption.{}", message),
+			Message::KeyDeletion(ref message) => write!(f, "KeyDeletion.{}", message),
+			Message::KeyImport(ref message) => write!(f, "KeyImport.{}", message),
 			Message::Decryption(ref message) => write!(f, "Decryption.{}", message),
 			Message::SchnorrSigning(ref message) => write!(f, "SchnorrSigning.{}", message),
 			Message::EcdsaSigning(ref message) => write!(f, "EcdsaSigning.{}", message),
+			Message::EddsaGeneration(ref message) => write!(f, "EddsaGeneration.{}", message),
+			Message::EddsaSigning(ref message) => write!(f, "EddsaSigning.{}", message),
+			Message::BlsGeneration(ref message) => write!(f, "BlsGeneration.{}", message),
+			Message::BlsSigning(ref message) => write!(f, "BlsSigning.{}", message),
 			Message::ServersSetChange(ref message) => write!(f, "ServersSetChange.{}", message),
 			Message::ShareAdd(ref message) => write!(f, "ShareAdd.{}", message),
+			Message::ShareRefresh(ref message) => write!(f, "ShareRefresh.{}", message),
+			Message::KeyRotation(ref message) => write!(f, "KeyRotation.{}", message),
+			Message::ThresholdChange(ref message) => write!(f, "ThresholdChange.{}", message),
+			Message::KeyExport(ref message) => write!(f, "KeyExport.{}", message),
+			Message::RandomnessBeacon(ref message) => write!(f, "RandomnessBeacon.{}", message),
 			Message::KeyVersionNegotiation(ref message) => write!(f, "KeyVersionNegotiation.{}", message),
 		}
 	}
@@ -1448,6 +3001,9 @@ impl fmt::Display for ClusterMessage {
 			ClusterMessage::NodePrivateKeySignature(_) => write!(f, "NodePrivateKeySignature"),
 			ClusterMessage::KeepAlive(_) => write!(f, "KeepAlive"),
 			ClusterMessage::KeepAliveResponse(_) => write!(f, "KeepAliveResponse"),
+			ClusterMessage::ReliableBroadcastSend(_) => write!(f, "ReliableBroadcastSend"),
+			ClusterMessage::ReliableBroadcastEcho(_) => write!(f, "ReliableBroadcastEcho"),
+			ClusterMessage::ReliableBroadcastReady(_) => write!(f, "ReliableBroadcastReady"),
 		}
 	}
 }
@@ -1460,6 +3016,7 @@ impl fmt::Display for GenerationMessage {
 			GenerationMessage::DerivedPointGeneration(ref msg) => write!(f, "DerivedPointGeneration({})", msg.message),
 			GenerationMessage::KeysDissemination(_) => write!(f, "KeysDissemination"),
 			GenerationMessage::PublicKeyShare(_) => write!(f, "PublicKeyShare"),
+			GenerationMessage::KeysComplaintResponse(_) => write!(f, "KeysComplaintResponse"),
 			GenerationMessage::JointPublicKey(_) => write!(f, "JointPublicKey"),
 			GenerationMessage::SessionError(ref msg) => write!(f, "SessionError({})", msg.error),
 			GenerationMessage::SessionCompleted(_) => write!(f, "SessionCompleted"),
@@ -1477,6 +3034,30 @@ impl fmt::Display for EncryptionMessage {
 	}
 }
 
+impl fmt::Display for KeyDeletionMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			KeyDeletionMessage::InitializeKeyDeletionSession(_) => write!(f, "InitializeKeyDeletionSession"),
+			KeyDeletionMessage::ConfirmKeyDeletionInitialization(ref msg) => write!(f, "ConfirmKeyDeletionInitialization({})", msg.has_key_share),
+			KeyDeletionMessage::CommitKeyDeletion(_) => write!(f, "CommitKeyDeletion"),
+			KeyDeletionMessage::ConfirmKeyDeletion(_) => write!(f, "ConfirmKeyDeletion"),
+			KeyDeletionMessage::KeyDeletionSessionError(ref msg) => write!(f, "KeyDeletionSessionError({})", msg.error),
+		}
+	}
+}
+
+impl fmt::Display for KeyImportMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			KeyImportMessage::InitializeKeyImportSession(_) => write!(f, "InitializeKeyImportSession"),
+			KeyImportMessage::ConfirmKeyImportInitialization(_) => write!(f, "ConfirmKeyImportInitialization"),
+			KeyImportMessage::CommitKeyImport(_) => write!(f, "CommitKeyImport"),
+			KeyImportMessage::ConfirmKeyImport(_) => write!(f, "ConfirmKeyImport"),
+			KeyImportMessage::KeyImportSessionError(ref msg) => write!(f, "KeyImportSessionError({})", msg.error),
+		}
+	}
+}
+
 impl fmt::Display for ConsensusMessage {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
@@ -1547,6 +3128,63 @@ impl fmt::Display for EcdsaSigningMessage {
 			EcdsaSigningMessage::EcdsaSigningSessionCompleted(_) => write!(f, "EcdsaSigningSessionCompleted"),
 			EcdsaSigningMessage::EcdsaSigningSessionDelegation(_) => write!(f, "EcdsaSigningSessionDelegation"),
 			EcdsaSigningMessage::EcdsaSigningSessionDelegationCompleted(_) => write!(f, "EcdsaSigningSessionDelegationCompleted"),
+			EcdsaSigningMessage::EcdsaMtaNonceGenerationStart(_) => write!(f, "EcdsaMtaNonceGenerationStart"),
+			EcdsaSigningMessage::EcdsaMtaPaillierPublic(_) => write!(f, "EcdsaMtaPaillierPublic"),
+			EcdsaSigningMessage::EcdsaMtaEncryptedNonceShare(_) => write!(f, "EcdsaMtaEncryptedNonceShare"),
+			EcdsaSigningMessage::EcdsaMtaResponse(_) => write!(f, "EcdsaMtaResponse"),
+		}
+	}
+}
+
+impl fmt::Display for EddsaGenerationMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			EddsaGenerationMessage::InitializeEddsaGenerationSession(_) => write!(f, "InitializeEddsaGenerationSession"),
+			EddsaGenerationMessage::ConfirmEddsaGenerationInitialization(_) => write!(f, "ConfirmEddsaGenerationInitialization"),
+			EddsaGenerationMessage::EddsaKeysDissemination(_) => write!(f, "EddsaKeysDissemination"),
+			EddsaGenerationMessage::EddsaGenerationSessionCompleted(_) => write!(f, "EddsaGenerationSessionCompleted"),
+			EddsaGenerationMessage::EddsaGenerationSessionError(ref msg) => write!(f, "EddsaGenerationSessionError({})", msg.error),
+		}
+	}
+}
+
+impl fmt::Display for EddsaSigningMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			EddsaSigningMessage::EddsaSigningConsensusMessage(ref m) => write!(f, "EddsaSigningConsensusMessage.{}", m.message),
+			EddsaSigningMessage::EddsaSigningGenerationMessage(ref m) => write!(f, "EddsaSigningGenerationMessage.{}", m.message),
+			EddsaSigningMessage::EddsaRequestPartialSignature(_) => write!(f, "EddsaRequestPartialSignature"),
+			EddsaSigningMessage::EddsaPartialSignature(_) => write!(f, "EddsaPartialSignature"),
+			EddsaSigningMessage::EddsaSigningSessionError(_) => write!(f, "EddsaSigningSessionError"),
+			EddsaSigningMessage::EddsaSigningSessionCompleted(_) => write!(f, "EddsaSigningSessionCompleted"),
+			EddsaSigningMessage::EddsaSigningSessionDelegation(_) => write!(f, "EddsaSigningSessionDelegation"),
+			EddsaSigningMessage::EddsaSigningSessionDelegationCompleted(_) => write!(f, "EddsaSigningSessionDelegationCompleted"),
+		}
+	}
+}
+
+impl fmt::Display for BlsGenerationMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BlsGenerationMessage::InitializeBlsGenerationSession(_) => write!(f, "InitializeBlsGenerationSession"),
+			BlsGenerationMessage::ConfirmBlsGenerationInitialization(_) => write!(f, "ConfirmBlsGenerationInitialization"),
+			BlsGenerationMessage::BlsKeysDissemination(_) => write!(f, "BlsKeysDissemination"),
+			BlsGenerationMessage::BlsGenerationSessionCompleted(_) => write!(f, "BlsGenerationSessionCompleted"),
+			BlsGenerationMessage::BlsGenerationSessionError(ref msg) => write!(f, "BlsGenerationSessionError({})", msg.error),
+		}
+	}
+}
+
+impl fmt::Display for BlsSigningMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BlsSigningMessage::BlsSigningConsensusMessage(ref m) => write!(f, "BlsSigningConsensusMessage.{}", m.message),
+			BlsSigningMessage::BlsRequestPartialSignature(_) => write!(f, "BlsRequestPartialSignature"),
+			BlsSigningMessage::BlsPartialSignature(_) => write!(f, "BlsPartialSignature"),
+			BlsSigningMessage::BlsSigningSessionError(_) => write!(f, "BlsSigningSessionError"),
+			BlsSigningMessage::BlsSigningSessionCompleted(_) => write!(f, "BlsSigningSessionCompleted"),
+			BlsSigningMessage::BlsSigningSessionDelegation(_) => write!(f, "BlsSigningSessionDelegation"),
+			BlsSigningMessage::BlsSigningSessionDelegationCompleted(_) => write!(f, "BlsSigningSessionDelegationCompleted"),
 		}
 	}
 }
@@ -1581,6 +3219,67 @@ impl fmt::Display for ShareAddMessage {
 	}
 }
 
+impl fmt::Display for ShareRefreshMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ShareRefreshMessage::InitializeShareRefreshSession(_) => write!(f, "InitializeShareRefreshSession"),
+			ShareRefreshMessage::ConfirmShareRefreshInitialization(_) => write!(f, "ConfirmShareRefreshInitialization"),
+			ShareRefreshMessage::ShareRefreshKeysDissemination(_) => write!(f, "ShareRefreshKeysDissemination"),
+			ShareRefreshMessage::ConfirmShareRefresh(_) => write!(f, "ConfirmShareRefresh"),
+			ShareRefreshMessage::CommitShareRefresh(_) => write!(f, "CommitShareRefresh"),
+			ShareRefreshMessage::ShareRefreshError(ref msg) => write!(f, "ShareRefreshError({})", msg.error),
+		}
+	}
+}
+
+impl fmt::Display for KeyRotationMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			KeyRotationMessage::InitializeKeyRotationSession(_) => write!(f, "InitializeKeyRotationSession"),
+			KeyRotationMessage::ConfirmKeyRotationInitialization(_) => write!(f, "ConfirmKeyRotationInitialization"),
+			KeyRotationMessage::KeyRotationKeysDissemination(_) => write!(f, "KeyRotationKeysDissemination"),
+			KeyRotationMessage::ConfirmKeyRotation(_) => write!(f, "ConfirmKeyRotation"),
+			KeyRotationMessage::CommitKeyRotation(_) => write!(f, "CommitKeyRotation"),
+			KeyRotationMessage::KeyRotationError(ref msg) => write!(f, "KeyRotationError({})", msg.error),
+		}
+	}
+}
+
+impl fmt::Display for ThresholdChangeMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ThresholdChangeMessage::InitializeThresholdChangeSession(_) => write!(f, "InitializeThresholdChangeSession"),
+			ThresholdChangeMessage::ConfirmThresholdChangeInitialization(_) => write!(f, "ConfirmThresholdChangeInitialization"),
+			ThresholdChangeMessage::ThresholdChangeKeysDissemination(_) => write!(f, "ThresholdChangeKeysDissemination"),
+			ThresholdChangeMessage::ConfirmThresholdChange(_) => write!(f, "ConfirmThresholdChange"),
+			ThresholdChangeMessage::CommitThresholdChange(_) => write!(f, "CommitThresholdChange"),
+			ThresholdChangeMessage::ThresholdChangeError(ref msg) => write!(f, "ThresholdChangeError({})", msg.error),
+		}
+	}
+}
+
+impl fmt::Display for KeyExportMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			KeyExportMessage::InitializeKeyExportSession(_) => write!(f, "InitializeKeyExportSession"),
+			KeyExportMessage::KeyExportShare(_) => write!(f, "KeyExportShare"),
+			KeyExportMessage::KeyExportSessionError(ref msg) => write!(f, "KeyExportSessionError({})", msg.error),
+		}
+	}
+}
+
+impl fmt::Display for RandomnessBeaconMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			RandomnessBeaconMessage::InitializeRandomnessBeaconSession(_) => write!(f, "InitializeRandomnessBeaconSession"),
+			RandomnessBeaconMessage::ConfirmRandomnessBeaconInitialization(_) => write!(f, "ConfirmRandomnessBeaconInitialization"),
+			RandomnessBeaconMessage::RandomnessBeaconPointGeneration(ref msg) => write!(f, "RandomnessBeaconPointGeneration.{}", msg.message),
+			RandomnessBeaconMessage::RandomnessBeaconTranscriptSignature(_) => write!(f, "RandomnessBeaconTranscriptSignature"),
+			RandomnessBeaconMessage::RandomnessBeaconError(ref msg) => write!(f, "RandomnessBeaconError({})", msg.error),
+		}
+	}
+}
+
 impl fmt::Display for KeyVersionNegotiationMessage {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
@@ -1599,3 +3298,25 @@ impl fmt::Display for RandomPointGenerationMessage {
 		}
 	}
 }
+
+impl From<math::ShadowDecryptionProof> for ShadowDecryptionProof {
+	fn from(proof: math::ShadowDecryptionProof) -> Self {
+		ShadowDecryptionProof {
+			decrypt_shadow_public: proof.decrypt_shadow_public.into(),
+			shadow_key_public: proof.shadow_key_public.into(),
+			shadow_point_proof: (proof.shadow_point_proof.0.into(), proof.shadow_point_proof.1.into()),
+			decrypt_shadow_proof: (proof.decrypt_shadow_proof.0.into(), proof.decrypt_shadow_proof.1.into()),
+		}
+	}
+}
+
+impl From<ShadowDecryptionProof> for math::ShadowDecryptionProof {
+	fn from(proof: ShadowDecryptionProof) -> Self {
+		math::ShadowDecryptionProof {
+			decrypt_shadow_public: proof.decrypt_shadow_public.into(),
+			shadow_key_public: proof.shadow_key_public.into(),
+			shadow_point_proof: (proof.shadow_point_proof.0.into(), proof.shadow_point_proof.1.into()),
+			decrypt_shadow_proof: (proof.decrypt_shadow_proof.0.into(), proof.decrypt_shadow_proof.1.into()),
+		}
+	}
+}
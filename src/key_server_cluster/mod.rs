@@ -16,16 +16,20 @@
 
 use super::types::ServerKeyId;
 
-pub use super::blockchain::SigningKeyPair;
-pub use super::types::{Error, NodeId, Requester, EncryptedDocumentKeyShadow};
+pub use super::blockchain::{SigningKeyPair, SecretKeyPair};
+pub use super::types::{Error, NodeId, Requester, EncryptedDocumentKeyShadow, KeyDerivationPath, RandomnessBeaconOutput,
+	KeyImportData, ImportedKeyShare, KeyExportApprovers, EncryptedServerKey, DocumentKeySlotId, KeyExpiration};
 pub use super::acl_storage::AclStorage;
-pub use super::key_storage::{KeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
+pub use super::key_expiration::{ExpirationClock, SystemClock};
+pub use super::key_storage::{KeyStorage, DocumentKeyShare, DocumentKeyShareVersion, DocumentKeySlot, KeyCurve,
+	EcdsaSigningScheme};
 pub use super::key_server_set::{is_migration_required, KeyServerSet, KeyServerSetSnapshot, KeyServerSetMigration};
 pub use super::serialization::{SerializableSignature, SerializableH256, SerializableSecret, SerializablePublic,
-	SerializableRequester, SerializableMessageHash, SerializableAddress};
+	SerializableRequester, SerializableMessageHash, SerializableAddress, SerializableBytes};
 pub use self::cluster::{new_network_cluster, ClusterCore, ClusterConfiguration, ClusterClient};
 pub use self::cluster_connections_net::NetConnectionsManagerConfig;
 pub use self::cluster_sessions::{ClusterSession, ClusterSessionsListener, WaitableSession};
+pub use self::jobs::signing_job_schnorr::SchnorrSigningScheme;
 #[cfg(test)]
 pub use self::cluster::tests::DummyClusterClient;
 
@@ -59,22 +63,34 @@ pub struct SessionMeta {
 mod admin_sessions;
 mod client_sessions;
 
+pub use self::admin_sessions::key_export_session;
+pub use self::admin_sessions::key_rotation_session;
 pub use self::admin_sessions::key_version_negotiation_session;
 pub use self::admin_sessions::servers_set_change_session;
 pub use self::admin_sessions::share_add_session;
 pub use self::admin_sessions::share_change_session;
+pub use self::admin_sessions::share_refresh_session;
+pub use self::admin_sessions::threshold_change_session;
 
 pub use self::client_sessions::decryption_session;
 pub use self::client_sessions::encryption_session;
 pub use self::client_sessions::generation_session;
+pub use self::client_sessions::generation_session_bls;
+pub use self::client_sessions::generation_session_eddsa;
+pub use self::client_sessions::key_deletion_session;
+pub use self::client_sessions::key_import_session;
 pub use self::client_sessions::random_point_generation_session;
+pub use self::client_sessions::randomness_beacon_session;
+pub use self::client_sessions::signing_session_bls;
 pub use self::client_sessions::signing_session_ecdsa;
+pub use self::client_sessions::signing_session_eddsa;
 pub use self::client_sessions::signing_session_schnorr;
 
 mod cluster;
 mod cluster_connections;
 mod cluster_connections_net;
 mod cluster_message_processor;
+mod cluster_reliable_broadcast;
 mod cluster_sessions;
 mod cluster_sessions_creator;
 mod connection_trigger;
@@ -82,5 +98,8 @@ mod connection_trigger_with_migration;
 mod io;
 mod jobs;
 pub mod math;
+pub mod math_bls;
+pub mod math_eddsa;
+pub mod math_paillier;
 mod message;
 mod net;
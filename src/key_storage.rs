@@ -14,16 +14,23 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use parking_lot::RwLock;
 use serde_json;
 use tiny_keccak::Keccak;
 use ethereum_types::{H256, Address};
-use crypto::publickey::{Secret, Public};
+use crypto::publickey::{Secret, Public, KeyPair, ecies};
 use kvdb::KeyValueDB;
-use types::{Error, ServerKeyId, NodeId};
+use blockchain::SecretKeyPair;
+use types::{Error, ServerKeyId, NodeId, DocumentKeySlotId, KeyExpiration};
 use serialization::{SerializablePublic, SerializableSecret, SerializableH256, SerializableAddress};
 
+/// Prefix of db keys, which are used to store tombstones of deleted keys.
+pub const TOMBSTONE_PREFIX: &'static [u8] = b"tombstone:";
+/// Purpose of the secret that is derived from the node secret to encrypt key storage.
+const ENCRYPTION_KEY_DERIVATION_PURPOSE: &'static [u8] = b"secretstore:key_storage_encryption_key";
+
 /// Encrypted key share, stored by key storage on the single key server.
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct DocumentKeyShare {
@@ -39,6 +46,45 @@ pub struct DocumentKeyShare {
 	pub encrypted_point: Option<Public>,
 	/// Key share versions.
 	pub versions: Vec<DocumentKeyShareVersion>,
+	/// Curve, the key has been generated for.
+	pub curve: KeyCurve,
+	/// Threshold ECDSA signing scheme, that is used with the key.
+	pub ecdsa_scheme: EcdsaSigningScheme,
+	/// True if the full key has been exported (reconstructed) by the key export session.
+	pub exported: bool,
+	/// Additional document keys, stored alongside the default one (common_point + encrypted_point).
+	pub document_key_slots: BTreeMap<DocumentKeySlotId, DocumentKeySlot>,
+	/// Point after which the key couldn't be used anymore (None if key never expires).
+	pub expiration: Option<KeyExpiration>,
+}
+
+/// Additional document key, encrypted with the server key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentKeySlot {
+	/// Common (shared) encryption point.
+	pub common_point: Public,
+	/// Encrypted point.
+	pub encrypted_point: Public,
+}
+
+/// Elliptic curve of the server key.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum KeyCurve {
+	/// secp256k1 key (used by decryption, Schnorr and ECDSA signing sessions).
+	Secp256k1,
+	/// Ed25519 key (used by EdDSA signing sessions).
+	Ed25519,
+	/// BLS12-381 key (used by BLS signing sessions).
+	Bls12381,
+}
+
+/// Threshold ECDSA signing scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EcdsaSigningScheme {
+	/// Nonce-inversion scheme. Requires 2 * t + 1 nodes to compute signature => only works if 2 * t < N.
+	NonceInversion,
+	/// Multiplicative-to-additive shares conversion based scheme. Requires t + 1 nodes to compute signature.
+	MultiplicativeToAdditive,
 }
 
 /// Versioned portion of document key share.
@@ -50,6 +96,10 @@ pub struct DocumentKeyShareVersion {
 	pub id_numbers: BTreeMap<NodeId, Secret>,
 	/// Node secret share.
 	pub secret_share: Secret,
+	/// Public shares (secret_share * G) of all nodes. Empty if version has been migrated from older storage
+	/// versions. Partial decryptions && Schnorr partial signatures, computed using such versions, aren't verified
+	/// until they're refreshed by the share refresh session, which computes public shares of all version holders.
+	pub public_shares: BTreeMap<NodeId, Public>,
 }
 
 /// Document encryption keys storage
@@ -64,25 +114,57 @@ pub trait KeyStorage: Send + Sync {
 	fn remove(&self, document: &ServerKeyId) -> Result<(), Error>;
 	/// Clears the database
 	fn clear(&self) -> Result<(), Error>;
+	/// Remove document encryption key, leaving a tombstone which prevents it from being inserted again
+	fn tombstone(&self, document: &ServerKeyId) -> Result<(), Error>;
+	/// Check if document encryption key has been removed with tombstone
+	fn is_tombstoned(&self, document: &ServerKeyId) -> bool;
 	/// Check if storage contains document encryption key
 	fn contains(&self, document: &ServerKeyId) -> bool;
 	/// Iterate through storage
 	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a>;
 }
 
+/// Key that is used to encrypt key shares before writing them to the disk.
+/// Every value is encrypted using ECIES (AES-128-CTR + HMAC-SHA256), with
+/// document id used as the MAC data, so that values couldn't be swapped between keys.
+#[derive(Clone)]
+pub struct KeyStorageEncryptionKey {
+	key_pair: KeyPair,
+}
+
 /// Persistent document encryption keys storage
 pub struct PersistentKeyStorage {
 	db: Arc<dyn KeyValueDB>,
+	encryption_key: KeyStorageEncryptionKey,
 }
 
 /// Persistent document encryption keys storage iterator
 pub struct PersistentKeyStorageIterator<'a> {
 	iter: Box<dyn Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>,
+	encryption_key: &'a KeyStorageEncryptionKey,
+}
+
+/// Document encryption keys storage, backed by embedded sled database
+#[cfg(feature = "sled")]
+pub struct SledKeyStorage {
+	/// Key shares encryption key.
+	encryption_key: KeyStorageEncryptionKey,
+	/// Key shares tree.
+	keys: ::sled::Tree,
+	/// Tombstones of deleted keys tree.
+	tombstones: ::sled::Tree,
+}
+
+/// In-memory document encryption keys storage
+#[derive(Default)]
+pub struct InMemoryKeyStorage {
+	keys: RwLock<HashMap<ServerKeyId, DocumentKeyShare>>,
+	tombstones: RwLock<HashSet<ServerKeyId>>,
 }
 
 /// V3 of encrypted key share, as it is stored by key storage on the single key server.
 #[derive(Serialize, Deserialize)]
-struct SerializableDocumentKeyShareV3 {
+pub struct SerializableDocumentKeyShareV3 {
 	/// Author of the entry.
 	pub author: SerializableAddress,
 	/// Decryption threshold (at least threshold + 1 nodes are required to decrypt data).
@@ -94,31 +176,95 @@ struct SerializableDocumentKeyShareV3 {
 	/// Encrypted point.
 	pub encrypted_point: Option<SerializablePublic>,
 	/// Versions.
-	pub versions: Vec<SerializableDocumentKeyShareVersionV3>
+	pub versions: Vec<SerializableDocumentKeyShareVersionV3>,
+	/// Key curve (missing in records, created before Ed25519 keys were supported).
+	#[serde(default)]
+	pub curve: KeyCurve,
+	/// ECDSA signing scheme (missing in records, created before MtA-based scheme was supported).
+	#[serde(default)]
+	pub ecdsa_scheme: EcdsaSigningScheme,
+	/// Key export flag (missing in records, created before key export was supported).
+	#[serde(default)]
+	pub exported: bool,
+	/// Document key slots (missing in records, created before document key slots were supported).
+	#[serde(default)]
+	pub document_key_slots: BTreeMap<SerializableH256, SerializableDocumentKeySlotV3>,
+	/// Key expiration (missing in records, created before keys expiration was supported).
+	#[serde(default)]
+	pub expiration: Option<KeyExpiration>,
+}
+
+/// V3 of document key slot, as it is stored by key storage on the single key server.
+#[derive(Serialize, Deserialize)]
+pub struct SerializableDocumentKeySlotV3 {
+	/// Common (shared) encryption point.
+	pub common_point: SerializablePublic,
+	/// Encrypted point.
+	pub encrypted_point: SerializablePublic,
 }
 
 /// V3 of encrypted key share version, as it is stored by key storage on the single key server.
 #[derive(Serialize, Deserialize)]
-struct SerializableDocumentKeyShareVersionV3 {
+pub struct SerializableDocumentKeyShareVersionV3 {
 	/// Version hash.
 	pub hash: SerializableH256,
 	/// Nodes ids numbers.
 	pub id_numbers: BTreeMap<SerializablePublic, SerializableSecret>,
 	/// Node secret share.
 	pub secret_share: SerializableSecret,
+	/// Public shares of all nodes (missing in records, created before public shares were stored).
+	#[serde(default)]
+	pub public_shares: BTreeMap<SerializablePublic, SerializablePublic>,
+}
+
+impl KeyStorageEncryptionKey {
+	/// Create encryption key from given secret.
+	pub fn new(secret: Secret) -> Result<Self, Error> {
+		Ok(KeyStorageEncryptionKey {
+			key_pair: KeyPair::from_secret(secret)?,
+		})
+	}
+
+	/// Derive encryption key from the node secret.
+	pub fn derive(self_key_pair: &dyn SecretKeyPair) -> Result<Self, Error> {
+		Ok(KeyStorageEncryptionKey {
+			key_pair: derive_encryption_key_pair(self_key_pair, ENCRYPTION_KEY_DERIVATION_PURPOSE)?,
+		})
+	}
+
+	/// Encrypt storage value of given document.
+	pub fn encrypt(&self, document: &ServerKeyId, value: &[u8]) -> Result<Vec<u8>, Error> {
+		ecies::encrypt(self.key_pair.public(), document.as_bytes(), value)
+			.map_err(|e| Error::Database(format!("failed to encrypt key share: {}", e)))
+	}
+
+	/// Decrypt storage value of given document.
+	pub fn decrypt(&self, document: &ServerKeyId, value: &[u8]) -> Result<Vec<u8>, Error> {
+		ecies::decrypt(self.key_pair.secret(), document.as_bytes(), value)
+			.map_err(|e| Error::Database(format!("failed to decrypt key share: {}", e)))
+	}
+}
+
+/// Derive encryption key pair for given purpose from the node secret. Signatures of the node key pair
+/// are never used here, because node signs arbitrary data, chosen by peers, during cluster handshake.
+pub fn derive_encryption_key_pair(self_key_pair: &dyn SecretKeyPair, purpose: &[u8]) -> Result<KeyPair, Error> {
+	KeyPair::from_secret(self_key_pair.derive_secret(purpose)?).map_err(Into::into)
 }
 
 impl PersistentKeyStorage {
 	/// Create new persistent document encryption keys storage
-	pub fn new(db: Arc<dyn KeyValueDB>) -> Result<Self, Error> {
-		Ok(Self { db })
+	pub fn new(db: Arc<dyn KeyValueDB>, encryption_key: KeyStorageEncryptionKey) -> Result<Self, Error> {
+		Ok(Self { db, encryption_key })
 	}
 }
 
 impl KeyStorage for PersistentKeyStorage {
 	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
-		let key: SerializableDocumentKeyShareV3 = key.into();
-		let key = serde_json::to_vec(&key).map_err(|e| Error::Database(e.to_string()))?;
+		if self.is_tombstoned(&document) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
+
+		let key = serialize_key_share(&self.encryption_key, &document, key)?;
 		let mut batch = self.db.transaction();
 		batch.put(0, document.as_bytes(), &key);
 		self.db.write(batch).map_err(Into::into)
@@ -133,10 +279,7 @@ impl KeyStorage for PersistentKeyStorage {
 			.map_err(|e| Error::Database(e.to_string()))
 			.and_then(|key| match key {
 				None => Ok(None),
-				Some(key) => serde_json::from_slice::<SerializableDocumentKeyShareV3>(&key)
-					.map_err(|e| Error::Database(e.to_string()))
-					.map(Into::into)
-					.map(Some),
+				Some(key) => deserialize_key_share(&self.encryption_key, document, &key).map(Some),
 			})
 	}
 
@@ -155,6 +298,19 @@ impl KeyStorage for PersistentKeyStorage {
 			.map_err(|e| Error::Database(e.to_string()))
 	}
 
+	fn tombstone(&self, document: &ServerKeyId) -> Result<(), Error> {
+		let mut batch = self.db.transaction();
+		batch.delete(0, document.as_bytes());
+		batch.put(0, &tombstone_key(document), &[]);
+		self.db.write(batch).map_err(Into::into)
+	}
+
+	fn is_tombstoned(&self, document: &ServerKeyId) -> bool {
+		self.db.get(0, &tombstone_key(document))
+			.map(|k| k.is_some())
+			.unwrap_or(false)
+	}
+
 	fn contains(&self, document: &ServerKeyId) -> bool {
 		self.db.get(0, document.as_bytes())
 			.map(|k| k.is_some())
@@ -164,6 +320,7 @@ impl KeyStorage for PersistentKeyStorage {
 	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
 		Box::new(PersistentKeyStorageIterator {
 			iter: self.db.iter(0),
+			encryption_key: &self.encryption_key,
 		})
 	}
 }
@@ -172,16 +329,182 @@ impl<'a> Iterator for PersistentKeyStorageIterator<'a> {
 	type Item = (ServerKeyId, DocumentKeyShare);
 
 	fn next(&mut self) -> Option<(ServerKeyId, DocumentKeyShare)> {
-		self.iter.as_mut().next()
-			.and_then(|(db_key, db_val)| serde_json::from_slice::<SerializableDocumentKeyShareV3>(&db_val)
-					  .ok()
-					  .map(|key| (ServerKeyId::from_slice(&*db_key), key.into())))
+		loop {
+			let (db_key, db_val) = self.iter.as_mut().next()?;
+			// tombstones are not holding any key data => skip them
+			if db_key.starts_with(TOMBSTONE_PREFIX) {
+				continue;
+			}
+
+			let document = ServerKeyId::from_slice(&*db_key);
+			return deserialize_key_share(self.encryption_key, &document, &db_val)
+				.ok()
+				.map(|key| (document, key));
+		}
+	}
+}
+
+#[cfg(feature = "sled")]
+impl SledKeyStorage {
+	/// Create new sled-backed document encryption keys storage
+	pub fn new(db: ::sled::Db, encryption_key: KeyStorageEncryptionKey) -> Result<Self, Error> {
+		Ok(SledKeyStorage {
+			encryption_key: encryption_key,
+			tombstones: db.open_tree(b"tombstones").map_err(sled_error)?,
+			keys: (*db).clone(),
+		})
+	}
+}
+
+#[cfg(feature = "sled")]
+impl KeyStorage for SledKeyStorage {
+	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		if self.is_tombstoned(&document) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
+
+		let key = serialize_key_share(&self.encryption_key, &document, key)?;
+		self.keys.insert(document.as_bytes(), key).map_err(sled_error)?;
+		self.keys.flush().map(|_| ()).map_err(sled_error)
+	}
+
+	fn update(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		self.insert(document, key)
+	}
+
+	fn get(&self, document: &ServerKeyId) -> Result<Option<DocumentKeyShare>, Error> {
+		match self.keys.get(document.as_bytes()).map_err(sled_error)? {
+			None => Ok(None),
+			Some(key) => deserialize_key_share(&self.encryption_key, document, &key).map(Some),
+		}
+	}
+
+	fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
+		self.keys.remove(document.as_bytes()).map_err(sled_error)?;
+		self.keys.flush().map(|_| ()).map_err(sled_error)
+	}
+
+	fn clear(&self) -> Result<(), Error> {
+		self.keys.clear().map_err(sled_error)?;
+		self.keys.flush().map(|_| ()).map_err(sled_error)
+	}
+
+	fn tombstone(&self, document: &ServerKeyId) -> Result<(), Error> {
+		// tombstone is written first, so that the key could not be inserted again even if removal fails
+		self.tombstones.insert(document.as_bytes(), &[][..]).map_err(sled_error)?;
+		self.tombstones.flush().map_err(sled_error)?;
+		self.remove(document)
+	}
+
+	fn is_tombstoned(&self, document: &ServerKeyId) -> bool {
+		self.tombstones.contains_key(document.as_bytes()).unwrap_or(false)
+	}
+
+	fn contains(&self, document: &ServerKeyId) -> bool {
+		self.keys.contains_key(document.as_bytes()).unwrap_or(false)
+	}
+
+	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
+		Box::new(self.keys.iter()
+			.filter_map(|item| item.ok())
+			.filter_map(move |(db_key, db_val)| {
+				let document = ServerKeyId::from_slice(&*db_key);
+				deserialize_key_share(&self.encryption_key, &document, &db_val)
+					.ok()
+					.map(|key| (document, key))
+			}))
+	}
+}
+
+impl KeyStorage for InMemoryKeyStorage {
+	fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		if self.is_tombstoned(&document) {
+			return Err(Error::ServerKeyIsDeleted);
+		}
+
+		self.keys.write().insert(document, key);
+		Ok(())
+	}
+
+	fn update(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
+		self.insert(document, key)
+	}
+
+	fn get(&self, document: &ServerKeyId) -> Result<Option<DocumentKeyShare>, Error> {
+		Ok(self.keys.read().get(document).cloned())
+	}
+
+	fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
+		self.keys.write().remove(document);
+		Ok(())
+	}
+
+	fn clear(&self) -> Result<(), Error> {
+		self.keys.write().clear();
+		Ok(())
+	}
+
+	fn tombstone(&self, document: &ServerKeyId) -> Result<(), Error> {
+		self.keys.write().remove(document);
+		self.tombstones.write().insert(document.clone());
+		Ok(())
+	}
+
+	fn is_tombstoned(&self, document: &ServerKeyId) -> bool {
+		self.tombstones.read().contains(document)
+	}
+
+	fn contains(&self, document: &ServerKeyId) -> bool {
+		self.keys.read().contains_key(document)
+	}
+
+	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
+		Box::new(self.keys.read().clone().into_iter())
+	}
+}
+
+/// Serialize and encrypt key share to the storage format.
+fn serialize_key_share(encryption_key: &KeyStorageEncryptionKey, document: &ServerKeyId, key: DocumentKeyShare) -> Result<Vec<u8>, Error> {
+	let key: SerializableDocumentKeyShareV3 = key.into();
+	let key = serde_json::to_vec(&key).map_err(|e| Error::Database(e.to_string()))?;
+	encryption_key.encrypt(document, &key)
+}
+
+/// Decrypt and deserialize key share from the storage format.
+fn deserialize_key_share(encryption_key: &KeyStorageEncryptionKey, document: &ServerKeyId, key: &[u8]) -> Result<DocumentKeyShare, Error> {
+	let key = encryption_key.decrypt(document, key)?;
+	serde_json::from_slice::<SerializableDocumentKeyShareV3>(&key)
+		.map_err(|e| Error::Database(e.to_string()))
+		.map(Into::into)
+}
+
+/// Convert sled error to key storage error.
+#[cfg(feature = "sled")]
+fn sled_error(error: ::sled::Error) -> Error {
+	Error::Database(error.to_string())
+}
+
+/// Returns db key of the tombstone of given document.
+pub fn tombstone_key(document: &ServerKeyId) -> Vec<u8> {
+	let mut key = TOMBSTONE_PREFIX.to_vec();
+	key.extend_from_slice(document.as_bytes());
+	key
+}
+
+impl Default for KeyCurve {
+	fn default() -> Self {
+		KeyCurve::Secp256k1
+	}
+}
+
+impl Default for EcdsaSigningScheme {
+	fn default() -> Self {
+		EcdsaSigningScheme::NonceInversion
 	}
 }
 
 impl DocumentKeyShare {
 	/// Get last version reference.
-	#[cfg(test)]
 	pub fn last_version(&self) -> Result<&DocumentKeyShareVersion, Error> {
 		self.versions.iter().rev()
 			.nth(0)
@@ -194,6 +517,35 @@ impl DocumentKeyShare {
 			.find(|v| &v.hash == version)
 			.ok_or_else(|| Error::Database("key version is not found".into()))
 	}
+
+	/// Get (common_point, encrypted_point) of the document key, stored in given slot.
+	/// None slot stands for the default document key.
+	pub fn document_key(&self, slot: Option<&DocumentKeySlotId>) -> Option<(Public, Public)> {
+		match slot {
+			None => match (self.common_point.as_ref(), self.encrypted_point.as_ref()) {
+				(Some(common_point), Some(encrypted_point)) => Some((common_point.clone(), encrypted_point.clone())),
+				_ => None,
+			},
+			Some(slot) => self.document_key_slots.get(slot)
+				.map(|slot| (slot.common_point.clone(), slot.encrypted_point.clone())),
+		}
+	}
+
+	/// Store document key in given slot. None slot stands for the default document key.
+	pub fn set_document_key(&mut self, slot: Option<DocumentKeySlotId>, common_point: Public, encrypted_point: Public) {
+		match slot {
+			None => {
+				self.common_point = Some(common_point);
+				self.encrypted_point = Some(encrypted_point);
+			},
+			Some(slot) => {
+				self.document_key_slots.insert(slot, DocumentKeySlot {
+					common_point: common_point,
+					encrypted_point: encrypted_point,
+				});
+			},
+		}
+	}
 }
 
 impl DocumentKeyShareVersion {
@@ -203,6 +555,7 @@ impl DocumentKeyShareVersion {
 			hash: Self::data_hash(id_numbers.iter().map(|(k, v)| (k.as_bytes(), v.as_bytes()))),
 			id_numbers: id_numbers,
 			secret_share: secret_share,
+			public_shares: BTreeMap::new(),
 		}
 	}
 
@@ -231,6 +584,16 @@ impl From<DocumentKeyShare> for SerializableDocumentKeyShareV3 {
 			common_point: key.common_point.map(Into::into),
 			encrypted_point: key.encrypted_point.map(Into::into),
 			versions: key.versions.into_iter().map(Into::into).collect(),
+			curve: key.curve,
+			ecdsa_scheme: key.ecdsa_scheme,
+			exported: key.exported,
+			document_key_slots: key.document_key_slots.into_iter()
+				.map(|(id, slot)| (id.into(), SerializableDocumentKeySlotV3 {
+					common_point: slot.common_point.into(),
+					encrypted_point: slot.encrypted_point.into(),
+				}))
+				.collect(),
+			expiration: key.expiration,
 		}
 	}
 }
@@ -241,6 +604,7 @@ impl From<DocumentKeyShareVersion> for SerializableDocumentKeyShareVersionV3 {
 			hash: version.hash.into(),
 			id_numbers: version.id_numbers.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
 			secret_share: version.secret_share.into(),
+			public_shares: version.public_shares.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
 		}
 	}
 }
@@ -253,11 +617,22 @@ impl From<SerializableDocumentKeyShareV3> for DocumentKeyShare {
 			public: key.public.into(),
 			common_point: key.common_point.map(Into::into),
 			encrypted_point: key.encrypted_point.map(Into::into),
+			curve: key.curve,
+			ecdsa_scheme: key.ecdsa_scheme,
+			exported: key.exported,
+			document_key_slots: key.document_key_slots.into_iter()
+				.map(|(id, slot)| (id.into(), DocumentKeySlot {
+					common_point: slot.common_point.into(),
+					encrypted_point: slot.encrypted_point.into(),
+				}))
+				.collect(),
+			expiration: key.expiration,
 			versions: key.versions.into_iter()
 				.map(|v| DocumentKeyShareVersion {
 					hash: v.hash.into(),
 					id_numbers: v.id_numbers.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
 					secret_share: v.secret_share.into(),
+					public_shares: v.public_shares.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
 				})
 				.collect(),
 		}
@@ -266,53 +641,21 @@ impl From<SerializableDocumentKeyShareV3> for DocumentKeyShare {
 
 #[cfg(test)]
 pub mod tests {
-	use std::collections::HashMap;
 	use std::sync::Arc;
-	use parking_lot::RwLock;
 	use tempdir::TempDir;
+	use ethereum_types::H256;
 	use crypto::publickey::{Random, Generator, Public};
+	use kvdb::KeyValueDB;
 	use kvdb_rocksdb::{Database, DatabaseConfig};
-	use types::{Error, ServerKeyId};
-	use super::{KeyStorage, PersistentKeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
+	use types::{Error, ServerKeyId, KeyExpiration};
+	use super::{KeyStorage, PersistentKeyStorage, InMemoryKeyStorage, KeyStorageEncryptionKey,
+		DocumentKeyShare, DocumentKeyShareVersion, DocumentKeySlot, KeyCurve, EcdsaSigningScheme};
 
 	/// In-memory document encryption keys storage
-	#[derive(Default)]
-	pub struct DummyKeyStorage {
-		keys: RwLock<HashMap<ServerKeyId, DocumentKeyShare>>,
-	}
+	pub type DummyKeyStorage = InMemoryKeyStorage;
 
-	impl KeyStorage for DummyKeyStorage {
-		fn insert(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
-			self.keys.write().insert(document, key);
-			Ok(())
-		}
-
-		fn update(&self, document: ServerKeyId, key: DocumentKeyShare) -> Result<(), Error> {
-			self.keys.write().insert(document, key);
-			Ok(())
-		}
-
-		fn get(&self, document: &ServerKeyId) -> Result<Option<DocumentKeyShare>, Error> {
-			Ok(self.keys.read().get(document).cloned())
-		}
-
-		fn remove(&self, document: &ServerKeyId) -> Result<(), Error> {
-			self.keys.write().remove(document);
-			Ok(())
-		}
-
-		fn clear(&self) -> Result<(), Error> {
-			self.keys.write().clear();
-			Ok(())
-		}
-
-		fn contains(&self, document: &ServerKeyId) -> bool {
-			self.keys.read().contains_key(document)
-		}
-
-		fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(ServerKeyId, DocumentKeyShare)> + 'a> {
-			Box::new(self.keys.read().clone().into_iter())
-		}
+	pub fn random_encryption_key() -> KeyStorageEncryptionKey {
+		KeyStorageEncryptionKey::new(Random.generate().secret().clone()).unwrap()
 	}
 
 	#[test]
@@ -325,12 +668,18 @@ pub mod tests {
 			public: Public::default(),
 			common_point: Some(Random.generate().public().clone()),
 			encrypted_point: Some(Random.generate().public().clone()),
+			curve: KeyCurve::Secp256k1,
+			ecdsa_scheme: EcdsaSigningScheme::MultiplicativeToAdditive,
+			exported: false,
+			document_key_slots: Default::default(),
+			expiration: None,
 			versions: vec![DocumentKeyShareVersion {
 				hash: Default::default(),
 				id_numbers: vec![
 					(Random.generate().public().clone(), Random.generate().secret().clone())
 				].into_iter().collect(),
 				secret_share: Random.generate().secret().clone(),
+				public_shares: Default::default(),
 			}],
 		};
 		let key2 = ServerKeyId::from_low_u64_be(2);
@@ -340,20 +689,32 @@ pub mod tests {
 			public: Public::default(),
 			common_point: Some(Random.generate().public().clone()),
 			encrypted_point: Some(Random.generate().public().clone()),
+			curve: KeyCurve::Ed25519,
+			ecdsa_scheme: EcdsaSigningScheme::NonceInversion,
+			exported: false,
+			document_key_slots: vec![(H256::from_low_u64_be(1), DocumentKeySlot {
+				common_point: Random.generate().public().clone(),
+				encrypted_point: Random.generate().public().clone(),
+			})].into_iter().collect(),
+			expiration: Some(KeyExpiration::Block(100)),
 			versions: vec![DocumentKeyShareVersion {
 				hash: Default::default(),
 				id_numbers: vec![
 					(Random.generate().public().clone(), Random.generate().secret().clone())
 				].into_iter().collect(),
 				secret_share: Random.generate().secret().clone(),
+				public_shares: vec![
+					(Random.generate().public().clone(), Random.generate().public().clone())
+				].into_iter().collect(),
 			}],
 		};
 		let key3 = ServerKeyId::from_low_u64_be(3);
+		let encryption_key = random_encryption_key();
 
 		let db_config = DatabaseConfig::with_columns(1);
 		let db = Database::open(&db_config, &tempdir.path().display().to_string()).unwrap();
 
-		let key_storage = PersistentKeyStorage::new(Arc::new(db)).unwrap();
+		let key_storage = PersistentKeyStorage::new(Arc::new(db), encryption_key.clone()).unwrap();
 		key_storage.insert(key1.clone(), value1.clone()).unwrap();
 		key_storage.insert(key2.clone(), value2.clone()).unwrap();
 		assert_eq!(key_storage.get(&key1), Ok(Some(value1.clone())));
@@ -363,9 +724,148 @@ pub mod tests {
 
 		let db = Database::open(&db_config, &tempdir.path().display().to_string()).unwrap();
 
-		let key_storage = PersistentKeyStorage::new(Arc::new(db)).unwrap();
+		let key_storage = PersistentKeyStorage::new(Arc::new(db), encryption_key).unwrap();
 		assert_eq!(key_storage.get(&key1), Ok(Some(value1)));
 		assert_eq!(key_storage.get(&key2), Ok(Some(value2)));
 		assert_eq!(key_storage.get(&key3), Ok(None));
 	}
+
+	#[test]
+	fn persistent_key_storage_tombstone() {
+		let tempdir = TempDir::new("").unwrap();
+		let key1 = ServerKeyId::from_low_u64_be(1);
+		let value1 = DocumentKeyShare {
+			author: Default::default(),
+			threshold: 100,
+			public: Public::default(),
+			common_point: None,
+			encrypted_point: None,
+			curve: KeyCurve::Secp256k1,
+			ecdsa_scheme: EcdsaSigningScheme::NonceInversion,
+			exported: false,
+			document_key_slots: Default::default(),
+			expiration: None,
+			versions: vec![DocumentKeyShareVersion {
+				hash: Default::default(),
+				id_numbers: vec![
+					(Random.generate().public().clone(), Random.generate().secret().clone())
+				].into_iter().collect(),
+				secret_share: Random.generate().secret().clone(),
+				public_shares: Default::default(),
+			}],
+		};
+		let key2 = ServerKeyId::from_low_u64_be(2);
+
+		let db_config = DatabaseConfig::with_columns(1);
+		let db = Database::open(&db_config, &tempdir.path().display().to_string()).unwrap();
+
+		let key_storage = PersistentKeyStorage::new(Arc::new(db), random_encryption_key()).unwrap();
+		key_storage.insert(key1.clone(), value1.clone()).unwrap();
+		key_storage.insert(key2.clone(), value1.clone()).unwrap();
+		key_storage.tombstone(&key1).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(None));
+		assert!(!key_storage.contains(&key1));
+		assert!(key_storage.is_tombstoned(&key1));
+		assert!(!key_storage.is_tombstoned(&key2));
+		assert_eq!(key_storage.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec![key2]);
+		assert_eq!(key_storage.insert(key1, value1), Err(Error::ServerKeyIsDeleted));
+	}
+
+	#[test]
+	fn persistent_key_storage_encrypts_key_shares() {
+		let tempdir = TempDir::new("").unwrap();
+		let key1 = ServerKeyId::from_low_u64_be(1);
+		let key2 = ServerKeyId::from_low_u64_be(2);
+		let secret_share = Random.generate().secret().clone();
+		let value1 = DocumentKeyShare {
+			versions: vec![DocumentKeyShareVersion {
+				hash: Default::default(),
+				id_numbers: Default::default(),
+				secret_share: secret_share.clone(),
+				public_shares: Default::default(),
+			}],
+			..Default::default()
+		};
+
+		let db_config = DatabaseConfig::with_columns(1);
+		let db = Arc::new(Database::open(&db_config, &tempdir.path().display().to_string()).unwrap());
+		let key_storage = PersistentKeyStorage::new(db.clone(), random_encryption_key()).unwrap();
+		key_storage.insert(key1.clone(), value1.clone()).unwrap();
+
+		// secret share is not stored as plain text
+		let raw_value = db.get(0, key1.as_bytes()).unwrap().unwrap();
+		let secret_share_hex = format!("{:x}", *secret_share);
+		assert!(!String::from_utf8_lossy(&raw_value).contains(&secret_share_hex));
+
+		// value couldn't be moved to other key
+		let mut batch = db.transaction();
+		batch.put(0, key2.as_bytes(), &raw_value);
+		db.write(batch).unwrap();
+		assert!(key_storage.get(&key2).is_err());
+
+		// value couldn't be read with other encryption key
+		let key_storage = PersistentKeyStorage::new(db, random_encryption_key()).unwrap();
+		assert!(key_storage.get(&key1).is_err());
+	}
+
+	#[test]
+	fn in_memory_key_storage_tombstone() {
+		let key1 = ServerKeyId::from_low_u64_be(1);
+		let key2 = ServerKeyId::from_low_u64_be(2);
+
+		let key_storage = InMemoryKeyStorage::default();
+		key_storage.insert(key1.clone(), Default::default()).unwrap();
+		key_storage.insert(key2.clone(), Default::default()).unwrap();
+		key_storage.tombstone(&key1).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(None));
+		assert!(key_storage.is_tombstoned(&key1));
+		assert_eq!(key_storage.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec![key2]);
+		assert_eq!(key_storage.insert(key1, Default::default()), Err(Error::ServerKeyIsDeleted));
+	}
+
+	#[cfg(feature = "sled")]
+	#[test]
+	fn sled_key_storage() {
+		use super::SledKeyStorage;
+
+		let tempdir = TempDir::new("").unwrap();
+		let key1 = ServerKeyId::from_low_u64_be(1);
+		let value1 = DocumentKeyShare {
+			author: Default::default(),
+			threshold: 100,
+			public: Public::default(),
+			common_point: Some(Random.generate().public().clone()),
+			encrypted_point: Some(Random.generate().public().clone()),
+			curve: KeyCurve::Secp256k1,
+			ecdsa_scheme: EcdsaSigningScheme::NonceInversion,
+			exported: false,
+			document_key_slots: Default::default(),
+			expiration: None,
+			versions: vec![DocumentKeyShareVersion {
+				hash: Default::default(),
+				id_numbers: vec![
+					(Random.generate().public().clone(), Random.generate().secret().clone())
+				].into_iter().collect(),
+				secret_share: Random.generate().secret().clone(),
+				public_shares: Default::default(),
+			}],
+		};
+		let key2 = ServerKeyId::from_low_u64_be(2);
+		let key3 = ServerKeyId::from_low_u64_be(3);
+		let encryption_key = random_encryption_key();
+
+		let key_storage = SledKeyStorage::new(::sled::open(tempdir.path()).unwrap(), encryption_key.clone()).unwrap();
+		key_storage.insert(key1.clone(), value1.clone()).unwrap();
+		key_storage.insert(key2.clone(), value1.clone()).unwrap();
+		key_storage.tombstone(&key2).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(Some(value1.clone())));
+		assert_eq!(key_storage.get(&key3), Ok(None));
+		drop(key_storage);
+
+		let key_storage = SledKeyStorage::new(::sled::open(tempdir.path()).unwrap(), encryption_key).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(Some(value1.clone())));
+		assert_eq!(key_storage.get(&key2), Ok(None));
+		assert_eq!(key_storage.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec![key1]);
+		assert_eq!(key_storage.insert(key2, value1), Err(Error::ServerKeyIsDeleted));
+	}
 }
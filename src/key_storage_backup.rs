@@ -0,0 +1,314 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Secret Store.
+
+// Parity Secret Store is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Secret Store is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Backup and restore of key shares, stored by the single key server.
+//!
+//! Key shares are exported into archive, which is encrypted with the key derived from the node
+//! secret and signed by the node. Since key shares are bound to the node id, archive could
+//! only be imported back by the key server with the same key pair.
+
+use std::collections::BTreeSet;
+use serde_json;
+use ethereum_types::H256;
+use hash::keccak;
+use crypto::publickey::{ecies, recover};
+use blockchain::{SigningKeyPair, SecretKeyPair};
+use key_storage::{KeyStorage, DocumentKeyShare, KeyCurve, SerializableDocumentKeyShareV3, derive_encryption_key_pair};
+use key_server_cluster::{math, math_bls, math_eddsa};
+use serialization::{SerializableBytes, SerializableH256, SerializablePublic, SerializableSignature};
+use types::{Error, ServerKeyId, KeySharesFilter, KeySharesImportResult};
+
+/// Current version of key shares archive.
+const ARCHIVE_VERSION: u8 = 1;
+/// Purpose of the secret that is derived from the node secret to encrypt archive.
+const ENCRYPTION_KEY_DERIVATION_PURPOSE: &'static [u8] = b"secretstore:key_shares_archive_encryption_key";
+
+lazy_static! {
+	/// Id, which is signed by administrator to export key shares.
+	pub static ref KEY_SHARES_EXPORT_ID: H256 = "a6e2b5ad73c3a5a1d8f8e1c3d3b8f5a0f6a9a2e4a9c0d3b6c53e8f1e7ab21d04"
+		.parse()
+		.expect("hardcoded id should parse without errors; qed");
+}
+
+/// Signed and encrypted key shares archive.
+#[derive(Serialize, Deserialize)]
+struct SerializableKeySharesArchive {
+	/// Archive format version.
+	pub version: u8,
+	/// Id of the node that has created the archive.
+	pub node: SerializablePublic,
+	/// Encrypted key shares.
+	pub key_shares: SerializableBytes,
+	/// Node signature of the archive hash.
+	pub signature: SerializableSignature,
+}
+
+/// Export all key shares, matching given filter, into encrypted archive.
+pub fn export_key_shares(
+	key_storage: &dyn KeyStorage,
+	self_key_pair: &dyn SigningKeyPair,
+	self_secret_key_pair: &dyn SecretKeyPair,
+	filter: &KeySharesFilter,
+) -> Result<Vec<u8>, Error> {
+	let key_shares: Vec<(SerializableH256, SerializableDocumentKeyShareV3)> = key_storage.iter()
+		.filter(|&(ref key_id, ref key_share)| filter.matches(key_id, key_share))
+		.map(|(key_id, key_share)| (key_id.into(), key_share.into()))
+		.collect();
+	let key_shares = serde_json::to_vec(&key_shares).map_err(|e| Error::Serde(e.to_string()))?;
+
+	let encryption_key = derive_encryption_key_pair(self_secret_key_pair, ENCRYPTION_KEY_DERIVATION_PURPOSE)?;
+	let key_shares = ecies::encrypt(encryption_key.public(), &[ARCHIVE_VERSION], &key_shares)?;
+	let signature = self_key_pair.sign(&archive_hash(ARCHIVE_VERSION, &key_shares))?;
+
+	serde_json::to_vec(&SerializableKeySharesArchive {
+		version: ARCHIVE_VERSION,
+		node: self_key_pair.public().clone().into(),
+		key_shares: key_shares.into(),
+		signature: signature.into(),
+	}).map_err(|e| Error::Serde(e.to_string()))
+}
+
+/// Import key shares from the archive, previously created by this node. All shares are validated
+/// before insertion. Shares of keys that are already stored or have been deleted are skipped.
+pub fn import_key_shares(
+	key_storage: &dyn KeyStorage,
+	self_key_pair: &dyn SigningKeyPair,
+	self_secret_key_pair: &dyn SecretKeyPair,
+	archive: &[u8],
+) -> Result<KeySharesImportResult, Error> {
+	let archive: SerializableKeySharesArchive = serde_json::from_slice(archive)
+		.map_err(|e| Error::Serde(e.to_string()))?;
+	if archive.version != ARCHIVE_VERSION {
+		return Err(Error::Serde(format!("unsupported key shares archive version {}", archive.version)));
+	}
+	let is_signed_by_self = *archive.node == *self_key_pair.public()
+		&& recover(&archive.signature, &archive_hash(archive.version, &archive.key_shares))
+			.map(|signer| signer == *archive.node)
+			.unwrap_or(false);
+	if !is_signed_by_self {
+		return Err(Error::AccessDenied);
+	}
+
+	let encryption_key = derive_encryption_key_pair(self_secret_key_pair, ENCRYPTION_KEY_DERIVATION_PURPOSE)?;
+	let key_shares = ecies::decrypt(encryption_key.secret(), &[archive.version], &archive.key_shares)?;
+	let key_shares: Vec<(SerializableH256, SerializableDocumentKeyShareV3)> = serde_json::from_slice(&key_shares)
+		.map_err(|e| Error::Serde(e.to_string()))?;
+	let key_shares: Vec<(ServerKeyId, DocumentKeyShare)> = key_shares.into_iter()
+		.map(|(key_id, key_share)| (key_id.into(), key_share.into()))
+		.collect();
+
+	// validate all shares first => nothing is imported from invalid archive
+	let mut result = KeySharesImportResult::default();
+	for &(ref key_id, ref key_share) in &key_shares {
+		check_key_share(self_key_pair, key_id, key_share)?;
+
+		if key_storage.is_tombstoned(key_id) {
+			result.skipped.insert(key_id.clone());
+			continue;
+		}
+
+		match key_storage.get(key_id)? {
+			Some(ref stored_key_share) if stored_key_share.public != key_share.public =>
+				return Err(Error::Database(format!("key {:?} is already stored with different public", key_id))),
+			Some(_) => {
+				result.skipped.insert(key_id.clone());
+			},
+			None => {
+				result.imported.insert(key_id.clone());
+			},
+		}
+	}
+
+	for (key_id, key_share) in key_shares {
+		if result.imported.contains(&key_id) {
+			key_storage.insert(key_id, key_share)?;
+		}
+	}
+
+	Ok(result)
+}
+
+impl KeySharesFilter {
+	/// Filter that matches shares of given keys.
+	pub fn with_key_ids(key_ids: BTreeSet<ServerKeyId>) -> Self {
+		KeySharesFilter {
+			key_ids: Some(key_ids),
+			author: None,
+		}
+	}
+
+	/// Check if key share matches the filter.
+	pub fn matches(&self, key_id: &ServerKeyId, key_share: &DocumentKeyShare) -> bool {
+		self.key_ids.as_ref().map(|key_ids| key_ids.contains(key_id)).unwrap_or(true)
+			&& self.author.as_ref().map(|author| *author == key_share.author).unwrap_or(true)
+	}
+}
+
+/// Check that key share is consistent with the key public and could be used by this node.
+fn check_key_share(self_key_pair: &dyn SigningKeyPair, key_id: &ServerKeyId, key_share: &DocumentKeyShare) -> Result<(), Error> {
+	let invalid = |reason: &str| Err(Error::Database(format!("key share {:?} is invalid: {}", key_id, reason)));
+
+	let is_public_valid = match key_share.curve {
+		KeyCurve::Secp256k1 => math::public_is_valid(&key_share.public),
+		KeyCurve::Ed25519 => math_eddsa::from_key_share_public(&key_share.public)
+			.map(|public| math_eddsa::public_is_valid(&public))
+			.unwrap_or(false),
+		KeyCurve::Bls12381 => math_bls::from_key_share_public(&key_share.public)
+			.map(|public| math_bls::public_is_valid(&public))
+			.unwrap_or(false),
+	};
+	if !is_public_valid {
+		return invalid("key public is not a valid point");
+	}
+	if key_share.versions.is_empty() {
+		return invalid("there are no key versions");
+	}
+	for version in &key_share.versions {
+		if version.id_numbers.len() <= key_share.threshold {
+			return invalid("threshold is too large for key version");
+		}
+		if !version.id_numbers.contains_key(self_key_pair.public()) {
+			return invalid("this node is not a holder of the key version");
+		}
+		if *version.secret_share == H256::zero() {
+			return invalid("secret share is zero");
+		}
+	}
+
+	Ok(())
+}
+
+/// Compute hash of the archive, which is signed by the node.
+fn archive_hash(version: u8, key_shares: &[u8]) -> H256 {
+	let mut data = vec![version];
+	data.extend_from_slice(key_shares);
+	keccak(data)
+}
+
+#[cfg(test)]
+mod tests {
+	use crypto::publickey::{Random, Generator, KeyPair};
+	use ethereum_types::Address;
+	use node_key_pair::PlainNodeKeyPair;
+	use key_storage::{KeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
+	use key_storage::tests::DummyKeyStorage;
+	use types::{Error, ServerKeyId, KeySharesFilter};
+	use super::{export_key_shares, import_key_shares};
+
+	fn key_share(node: &KeyPair, author: Address) -> DocumentKeyShare {
+		let id_numbers = vec![
+			(node.public().clone(), Random.generate().secret().clone()),
+			(Random.generate().public().clone(), Random.generate().secret().clone()),
+		].into_iter().collect();
+		DocumentKeyShare {
+			author: author,
+			threshold: 1,
+			public: Random.generate().public().clone(),
+			versions: vec![DocumentKeyShareVersion::new(id_numbers, Random.generate().secret().clone())],
+			..Default::default()
+		}
+	}
+
+	fn prepare_storage(node: &KeyPair) -> (DummyKeyStorage, Vec<(ServerKeyId, DocumentKeyShare)>) {
+		let key_storage = DummyKeyStorage::default();
+		let key_shares: Vec<_> = (1..4)
+			.map(|i| (ServerKeyId::from_low_u64_be(i), key_share(node, Address::from_low_u64_be(i % 2))))
+			.collect();
+		for &(ref key_id, ref key_share) in &key_shares {
+			key_storage.insert(key_id.clone(), key_share.clone()).unwrap();
+		}
+		(key_storage, key_shares)
+	}
+
+	#[test]
+	fn key_shares_are_restored_from_archive() {
+		let key_pair = Random.generate();
+		let node = PlainNodeKeyPair::new(key_pair.clone());
+		let (key_storage, key_shares) = prepare_storage(&key_pair);
+
+		let archive = export_key_shares(&key_storage, &node, &node, &Default::default()).unwrap();
+
+		let restored_storage = DummyKeyStorage::default();
+		restored_storage.insert(key_shares[0].0.clone(), key_shares[0].1.clone()).unwrap();
+		restored_storage.tombstone(&key_shares[1].0).unwrap();
+		let result = import_key_shares(&restored_storage, &node, &node, &archive).unwrap();
+		assert_eq!(result.imported, vec![key_shares[2].0.clone()].into_iter().collect());
+		assert_eq!(result.skipped, vec![key_shares[0].0.clone(), key_shares[1].0.clone()].into_iter().collect());
+		assert_eq!(restored_storage.get(&key_shares[2].0), Ok(Some(key_shares[2].1.clone())));
+	}
+
+	#[test]
+	fn key_shares_are_filtered_on_export() {
+		let key_pair = Random.generate();
+		let node = PlainNodeKeyPair::new(key_pair.clone());
+		let (key_storage, key_shares) = prepare_storage(&key_pair);
+
+		let filter = KeySharesFilter { key_ids: None, author: Some(Address::from_low_u64_be(1)) };
+		let archive = export_key_shares(&key_storage, &node, &node, &filter).unwrap();
+		let result = import_key_shares(&DummyKeyStorage::default(), &node, &node, &archive).unwrap();
+		assert_eq!(result.imported, vec![key_shares[0].0.clone(), key_shares[2].0.clone()].into_iter().collect());
+
+		let filter = KeySharesFilter::with_key_ids(vec![key_shares[1].0.clone()].into_iter().collect());
+		let archive = export_key_shares(&key_storage, &node, &node, &filter).unwrap();
+		let result = import_key_shares(&DummyKeyStorage::default(), &node, &node, &archive).unwrap();
+		assert_eq!(result.imported, vec![key_shares[1].0.clone()].into_iter().collect());
+	}
+
+	#[test]
+	fn archive_is_not_imported_by_other_node() {
+		let key_pair = Random.generate();
+		let node = PlainNodeKeyPair::new(key_pair.clone());
+		let (key_storage, _) = prepare_storage(&key_pair);
+
+		let archive = export_key_shares(&key_storage, &node, &node, &Default::default()).unwrap();
+		let other_node = PlainNodeKeyPair::new(Random.generate());
+		assert_eq!(import_key_shares(&DummyKeyStorage::default(), &other_node, &other_node, &archive), Err(Error::AccessDenied));
+	}
+
+	#[test]
+	fn tampered_archive_is_rejected() {
+		let key_pair = Random.generate();
+		let node = PlainNodeKeyPair::new(key_pair.clone());
+		let (key_storage, _) = prepare_storage(&key_pair);
+
+		let archive = export_key_shares(&key_storage, &node, &node, &Default::default()).unwrap();
+		let mut archive: ::serde_json::Value = ::serde_json::from_slice(&archive).unwrap();
+		let key_shares = archive["key_shares"].as_str().unwrap().to_owned();
+		let tampered_byte = if key_shares.ends_with('0') { "1" } else { "0" };
+		archive["key_shares"] = format!("{}{}", &key_shares[..key_shares.len() - 1], tampered_byte).into();
+		let archive = ::serde_json::to_vec(&archive).unwrap();
+
+		assert_eq!(import_key_shares(&DummyKeyStorage::default(), &node, &node, &archive), Err(Error::AccessDenied));
+	}
+
+	#[test]
+	fn invalid_key_shares_are_not_imported() {
+		let key_pair = Random.generate();
+		let node = PlainNodeKeyPair::new(key_pair.clone());
+		let (key_storage, key_shares) = prepare_storage(&key_pair);
+		let mut invalid_key_share = key_share(&key_pair, Default::default());
+		invalid_key_share.threshold = 2;
+		key_storage.insert(ServerKeyId::from_low_u64_be(100), invalid_key_share).unwrap();
+
+		let archive = export_key_shares(&key_storage, &node, &node, &Default::default()).unwrap();
+		let restored_storage = DummyKeyStorage::default();
+		match import_key_shares(&restored_storage, &node, &node, &archive) {
+			Err(Error::Database(_)) => (),
+			result => panic!("unexpected import result: {:?}", result),
+		}
+		assert_eq!(restored_storage.get(&key_shares[0].0), Ok(None));
+	}
+}
@@ -14,22 +14,33 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
+extern crate bls12_381;
 extern crate byteorder;
+extern crate curve25519_dalek;
 extern crate ethabi;
 extern crate ethereum_types;
 extern crate hyper;
 extern crate secp256k1;
 extern crate keccak_hash as hash;
 extern crate kvdb;
+#[cfg(any(test, feature = "rocksdb"))]
 extern crate kvdb_rocksdb;
+extern crate num_bigint;
+extern crate num_integer;
+extern crate num_traits;
 extern crate parity_bytes as bytes;
 extern crate parity_crypto as crypto;
 extern crate parity_runtime;
 extern crate parking_lot;
 extern crate percent_encoding;
+extern crate rand;
 extern crate rustc_hex;
 extern crate serde;
 extern crate serde_json;
+extern crate sha2;
+extern crate sha2_09;
+#[cfg(feature = "sled")]
+extern crate sled;
 extern crate tiny_keccak;
 extern crate tokio;
 extern crate tokio_io;
@@ -61,41 +72,124 @@ mod traits;
 mod acl_storage;
 mod key_server;
 mod key_storage;
+mod key_expiration;
+mod key_storage_backup;
 mod serialization;
 mod key_server_set;
 mod node_key_pair;
 mod listener;
 mod blockchain;
+#[cfg(feature = "rocksdb")]
 mod migration;
 
 use std::sync::Arc;
+#[cfg(feature = "rocksdb")]
 use kvdb::KeyValueDB;
+#[cfg(feature = "rocksdb")]
 use kvdb_rocksdb::{Database, DatabaseConfig};
 use parity_runtime::Executor;
 
-pub use types::{ServerKeyId, EncryptedDocumentKey, RequestSignature, Public,
-	Error, NodeAddress, ServiceConfiguration, ClusterConfiguration};
+pub use types::{ServerKeyId, EncryptedDocumentKey, EncryptedEciesSecret, EncryptedEcdhSharedPoint, RequestSignature, Public,
+	KeyDerivationPath, Error, NodeAddress, ServiceConfiguration, ClusterConfiguration, KeyStorageConfiguration,
+	KeySharesFilter, KeySharesImportResult, RandomnessBeaconOutput, KeyImportData, ImportedKeyShare, KeyExportApprovers,
+	EncryptedServerKey, DocumentKeySlotId, KeyExpiration};
 pub use traits::KeyServer;
-pub use blockchain::{SecretStoreChain, SigningKeyPair, ContractAddress, BlockId, BlockNumber, NewBlocksNotify, Filter};
+pub use key_storage::{KeyStorage, KeyStorageEncryptionKey, PersistentKeyStorage, InMemoryKeyStorage,
+	DocumentKeyShare, DocumentKeyShareVersion, DocumentKeySlot};
+#[cfg(feature = "sled")]
+pub use key_storage::SledKeyStorage;
+pub use key_expiration::{ExpirationClock, SystemClock, KeyExpirationSweeper};
+pub use key_storage_backup::{export_key_shares, import_key_shares, KEY_SHARES_EXPORT_ID};
+pub use key_server_cluster::key_import_session::prepare_key_import;
+pub use key_server_cluster::key_export_session::{key_export_hash, decrypt_server_key};
+pub use key_server_cluster::threshold_change_session::threshold_change_hash;
+#[cfg(feature = "rocksdb")]
+pub use migration::{MigrationOptions, MigrationReport};
+pub use blockchain::{SecretStoreChain, SigningKeyPair, SecretKeyPair, ContractAddress, BlockId, BlockNumber, NewBlocksNotify, Filter};
 pub use self::node_key_pair::PlainNodeKeyPair;
 
 /// Open a secret store DB using the given secret store data path. The DB path is one level beneath the data path.
-pub fn open_secretstore_db(data_path: &str) -> Result<Arc<dyn KeyValueDB>, String> {
+/// Key shares that are stored by older versions are encrypted with given encryption key during upgrade.
+#[cfg(feature = "rocksdb")]
+pub fn open_secretstore_db(data_path: &str, encryption_key: &KeyStorageEncryptionKey) -> Result<Arc<dyn KeyValueDB>, String> {
 	use std::path::PathBuf;
 
-	migration::upgrade_db(data_path).map_err(|e| e.to_string())?;
+	let mut db_path = PathBuf::from(data_path);
+	db_path.push("db");
+	let db_path = db_path.to_str().ok_or_else(|| "Invalid secretstore path".to_string())?;
+
+	let config = DatabaseConfig::with_columns(1);
+	let db = Database::open(&config, &db_path).map_err(|e| format!("Error opening database: {:?}", e))?;
+
+	let options = MigrationOptions { dry_run: false, backup: true };
+	migration::upgrade_db(data_path, &db, encryption_key, options).map_err(|e| e.to_string())?;
+
+	Ok(Arc::new(db))
+}
+
+/// Upgrade secret store DB at the given secret store data path without starting key server.
+/// With `dry_run` option, only checks that all records could be upgraded.
+#[cfg(feature = "rocksdb")]
+pub fn migrate_secretstore_db(data_path: &str, self_key_pair: &dyn SecretKeyPair, options: MigrationOptions) -> Result<MigrationReport, String> {
+	use std::path::PathBuf;
+
+	let encryption_key = KeyStorageEncryptionKey::derive(self_key_pair).map_err(|e| e.to_string())?;
 
 	let mut db_path = PathBuf::from(data_path);
 	db_path.push("db");
 	let db_path = db_path.to_str().ok_or_else(|| "Invalid secretstore path".to_string())?;
 
 	let config = DatabaseConfig::with_columns(1);
-	Ok(Arc::new(Database::open(&config, &db_path).map_err(|e| format!("Error opening database: {:?}", e))?))
+	let db = Database::open(&config, &db_path).map_err(|e| format!("Error opening database: {:?}", e))?;
+
+	migration::upgrade_db(data_path, &db, &encryption_key, options).map_err(|e| e.to_string())
+}
+
+/// Open key storage, selected by the given configuration. Persistent key storages are
+/// encrypted with the key that is derived from the node secret.
+pub fn open_key_storage(config: &KeyStorageConfiguration, self_key_pair: &dyn SecretKeyPair) -> Result<Arc<dyn KeyStorage>, String> {
+	match *config {
+		KeyStorageConfiguration::RocksDb(ref data_path) => open_rocksdb_key_storage(data_path, self_key_pair),
+		KeyStorageConfiguration::Sled(ref data_path) => open_sled_key_storage(data_path, self_key_pair),
+		KeyStorageConfiguration::InMemory => Ok(Arc::new(InMemoryKeyStorage::default())),
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+fn open_rocksdb_key_storage(data_path: &str, self_key_pair: &dyn SecretKeyPair) -> Result<Arc<dyn KeyStorage>, String> {
+	let encryption_key = KeyStorageEncryptionKey::derive(self_key_pair).map_err(|e| e.to_string())?;
+	let db = open_secretstore_db(data_path, &encryption_key)?;
+	Ok(Arc::new(PersistentKeyStorage::new(db, encryption_key).map_err(|e| e.to_string())?))
+}
+
+#[cfg(not(feature = "rocksdb"))]
+fn open_rocksdb_key_storage(_data_path: &str, _self_key_pair: &dyn SecretKeyPair) -> Result<Arc<dyn KeyStorage>, String> {
+	Err("Secret store is compiled without RocksDB key storage support".into())
+}
+
+/// Open sled key storage using the given secret store data path. The DB path is one level beneath the data path.
+#[cfg(feature = "sled")]
+fn open_sled_key_storage(data_path: &str, self_key_pair: &dyn SecretKeyPair) -> Result<Arc<dyn KeyStorage>, String> {
+	use std::path::PathBuf;
+
+	let encryption_key = KeyStorageEncryptionKey::derive(self_key_pair).map_err(|e| e.to_string())?;
+
+	let mut db_path = PathBuf::from(data_path);
+	db_path.push("sled");
+
+	let db = sled::open(&db_path).map_err(|e| format!("Error opening database: {:?}", e))?;
+	Ok(Arc::new(SledKeyStorage::new(db, encryption_key).map_err(|e| e.to_string())?))
+}
+
+#[cfg(not(feature = "sled"))]
+fn open_sled_key_storage(_data_path: &str, _self_key_pair: &dyn SecretKeyPair) -> Result<Arc<dyn KeyStorage>, String> {
+	Err("Secret store is compiled without sled key storage support".into())
 }
 
 /// Start new key server instance
-pub fn start(trusted_client: Arc<dyn SecretStoreChain>, self_key_pair: Arc<dyn SigningKeyPair>, mut config: ServiceConfiguration,
-	db: Arc<dyn KeyValueDB>, executor: Executor) -> Result<Box<dyn KeyServer>, Error>
+/// `key_storage` could be opened using `open_key_storage(&config.key_storage, &node_key_pair)`, or be any other `KeyStorage` implementation.
+pub fn start(trusted_client: Arc<dyn SecretStoreChain>, self_key_pair: Arc<dyn SigningKeyPair>, self_secret_key_pair: Arc<dyn SecretKeyPair>,
+	mut config: ServiceConfiguration, key_storage: Arc<dyn KeyStorage>, executor: Executor) -> Result<Box<dyn KeyServer>, Error>
 {
 	let acl_storage: Arc<dyn acl_storage::AclStorage> = match config.acl_check_contract_address.take() {
 		Some(acl_check_contract_address) => acl_storage::OnChainAclStorage::new(trusted_client.clone(), acl_check_contract_address)?,
@@ -104,9 +198,9 @@ pub fn start(trusted_client: Arc<dyn SecretStoreChain>, self_key_pair: Arc<dyn S
 
 	let key_server_set = key_server_set::OnChainKeyServerSet::new(trusted_client.clone(), config.cluster_config.key_server_set_contract_address.take(),
 		self_key_pair.clone(), config.cluster_config.auto_migrate_enabled, config.cluster_config.nodes.clone())?;
-	let key_storage = Arc::new(key_storage::PersistentKeyStorage::new(db)?);
+	let expiration_sweeper = key_expiration::KeyExpirationSweeper::new(trusted_client.clone(), key_storage.clone(), &executor);
 	let key_server = Arc::new(key_server::KeyServerImpl::new(&config.cluster_config, key_server_set.clone(), self_key_pair.clone(),
-		acl_storage.clone(), key_storage.clone(), executor.clone())?);
+		self_secret_key_pair, acl_storage.clone(), key_storage.clone(), expiration_sweeper, executor.clone())?);
 	let cluster = key_server.cluster();
 	let key_server: Arc<dyn KeyServer> = key_server;
 
@@ -32,24 +32,58 @@ use futures::{future, Future, Stream};
 use percent_encoding::percent_decode;
 
 use traits::KeyServer;
-use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableBytes, SerializablePublic};
-use types::{Error, Public, MessageHash, NodeAddress, RequestSignature, ServerKeyId,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId};
+use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableBytes, SerializablePublic,
+	SerializableEncryptedServerKey, SerializableH256, SerializableKeySharesFilter, SerializableKeySharesImportResult,
+	SerializableRandomnessBeaconOutput, SerializableKeyImportData, SerializableSignature};
+use types::{Error, Public, EddsaPublic, BlsPublic, MessageHash, NodeAddress, RequestSignature, ServerKeyId,
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, EncryptedEciesSecret, EncryptedEcdhSharedPoint, NodeId, KeySharesFilter,
+	KeySharesImportResult, KeyDerivationPath, RandomnessBeaconOutput, KeyImportData, EncryptedServerKey, DocumentKeySlotId, KeyExpiration};
+use ethereum_types::H256;
 use jsonrpc_server_utils::cors::{self, AllowCors, AccessControlAllowOrigin};
 
 /// Key server http-requests listener. Available requests:
 /// To generate server key:							POST		/shadow/{server_key_id}/{signature}/{threshold}
+/// To generate expiring server key:				POST		/expiring/{server_key_id}/{signature}/{threshold}/{block|timestamp}/{not_after}
+/// To import existing server key:					POST		/import/{server_key_id}/{signature} + BODY: json object with hex-encoded share commitments and per-node shares
 /// To store pregenerated encrypted document key: 	POST		/shadow/{server_key_id}/{signature}/{common_point}/{encrypted_key}
 /// To generate server && document key:				POST		/{server_key_id}/{signature}/{threshold}
 /// To get public portion of server key:			GET			/server/{server_key_id}/{signature}
+/// To delete server key:							DELETE		/shadow/{server_key_id}/{signature}
 /// To get document key:							GET			/{server_key_id}/{signature}
 /// To get document key shadow:						GET			/shadow/{server_key_id}/{signature}
+/// To store encrypted document key in slot:		POST		/shadow/{server_key_id}/{slot}/{signature}/{common_point}/{encrypted_key}
+/// To get document key from slot:					GET			/{server_key_id}/{slot}/{signature}
+/// To get document key shadow from slot:			GET			/shadow/{server_key_id}/{slot}/{signature}
+/// To get ECDH secret of ECIES ciphertext:			GET			/ecies/{server_key_id}/{signature}/{ephemeral_public}
+/// To compute ECDH shared point with server key:	GET			/ecdh/{server_key_id}/{signature}/{counterparty_public}
 /// To generate Schnorr signature with server key:	GET			/schnorr/{server_key_id}/{signature}/{message_hash}
+/// To generate Schnorr signatures of many messages:	POST		/schnorr/{server_key_id}/{signature} + BODY: json array of hex-encoded message hashes
+/// To generate BIP-340 signature with server key:	GET			/bip340/{server_key_id}/{signature}/{message_hash}
 /// To generate ECDSA signature with server key:	GET			/ecdsa/{server_key_id}/{signature}/{message_hash}
+/// To generate Ed25519 server key:					POST		/eddsa/{server_key_id}/{signature}/{threshold}
+/// To generate EdDSA signature with server key:	GET			/eddsa/{server_key_id}/{signature}/{message_hash}
+/// To generate BLS12-381 server key:				POST		/bls/{server_key_id}/{signature}/{threshold}
+/// To generate BLS signature with server key:		GET			/bls/{server_key_id}/{signature}/{message_hash}
+/// To get public portion of child key:				GET			/child/server/{server_key_id}/{signature}/{derivation_path}
+/// To get document key using child key:			GET			/child/{server_key_id}/{signature}/{derivation_path}
+/// To generate Schnorr signature with child key:	GET			/child/schnorr/{server_key_id}/{signature}/{derivation_path}/{message_hash}
+/// To generate ECDSA signature with child key:		GET			/child/ecdsa/{server_key_id}/{signature}/{derivation_path}/{message_hash}
+/// Derivation path is a comma-separated list of non-hardened child key indices (i.e. 0,42).
 /// To change servers set:							POST		/admin/servers_set_change/{old_signature}/{new_signature} + BODY: json array of hex-encoded nodes ids
+/// To rotate server key:							POST		/admin/rotate_key/{server_key_id}/{signature}
+/// To change server key threshold:					POST		/admin/change_threshold/{server_key_id}/{new_threshold}/{signature}
+/// To export server key:							POST		/admin/export_key/{server_key_id}/{recipient_public}/{expiry} + BODY: json array of hex-encoded approvers signatures
+/// To export key shares:							POST		/admin/key_shares/export/{signature} + BODY: optional json object with hex-encoded key_ids and author
+/// To import key shares:							POST		/admin/key_shares/import/{signature} + BODY: hex-encoded archive
+/// To generate verifiable randomness:				POST		/randomness/{beacon_id}/{signature}
 
 type CorsDomains = Option<Vec<AccessControlAllowOrigin>>;
 
+/// Max number of messages that could be signed in a single Schnorr batch signing request.
+const MAX_SCHNORR_BATCH_SIZE: usize = 64;
+/// Max size of Schnorr batch signing request body (json array of MAX_SCHNORR_BATCH_SIZE hex-encoded hashes + whitespaces).
+const MAX_SCHNORR_BATCH_BODY_SIZE: usize = MAX_SCHNORR_BATCH_SIZE * 128;
+
 pub struct KeyServerHttpListener {
 	_executor: Executor,
 	_handler: Arc<KeyServerSharedHttpHandler>,
@@ -62,22 +96,70 @@ enum Request {
 	Invalid,
 	/// Generate server key.
 	GenerateServerKey(ServerKeyId, RequestSignature, usize),
+	/// Generate expiring server key.
+	GenerateExpiringServerKey(ServerKeyId, RequestSignature, usize, KeyExpiration),
+	/// Import existing server key.
+	ImportServerKey(ServerKeyId, RequestSignature, KeyImportData),
 	/// Store document key.
 	StoreDocumentKey(ServerKeyId, RequestSignature, Public, Public),
 	/// Generate encryption key.
 	GenerateDocumentKey(ServerKeyId, RequestSignature, usize),
 	/// Request public portion of server key.
 	GetServerKey(ServerKeyId, RequestSignature),
+	/// Delete server key.
+	DeleteServerKey(ServerKeyId, RequestSignature),
 	/// Request encryption key of given document for given requestor.
 	GetDocumentKey(ServerKeyId, RequestSignature),
 	/// Request shadow of encryption key of given document for given requestor.
 	GetDocumentKeyShadow(ServerKeyId, RequestSignature),
+	/// Request ECDH secret of ECIES ciphertext, encrypted with server key.
+	GetEciesSecret(ServerKeyId, RequestSignature, Public),
+	/// Compute ECDH shared point of server key and counterparty public.
+	ComputeEcdhSharedPoint(ServerKeyId, RequestSignature, Public),
 	/// Generate Schnorr signature for the message.
 	SchnorrSignMessage(ServerKeyId, RequestSignature, MessageHash),
+	/// Generate Schnorr signatures for the batch of messages.
+	SchnorrSignMessages(ServerKeyId, RequestSignature, Vec<MessageHash>),
+	/// Generate BIP-340 Schnorr signature for the message.
+	Bip340SignMessage(ServerKeyId, RequestSignature, MessageHash),
 	/// Generate ECDSA signature for the message.
 	EcdsaSignMessage(ServerKeyId, RequestSignature, MessageHash),
+	/// Generate Ed25519 server key.
+	GenerateEddsaServerKey(ServerKeyId, RequestSignature, usize),
+	/// Generate EdDSA signature for the message.
+	EddsaSignMessage(ServerKeyId, RequestSignature, MessageHash),
+	/// Generate BLS12-381 server key.
+	GenerateBlsServerKey(ServerKeyId, RequestSignature, usize),
+	/// Generate BLS signature for the message.
+	BlsSignMessage(ServerKeyId, RequestSignature, MessageHash),
+	/// Request public portion of child key.
+	GetChildKey(ServerKeyId, RequestSignature, KeyDerivationPath),
+	/// Request encryption key of given document for given requestor, using child key.
+	GetDocumentKeyWithChildKey(ServerKeyId, RequestSignature, KeyDerivationPath),
+	/// Generate Schnorr signature for the message, using child key.
+	SchnorrSignMessageWithChildKey(ServerKeyId, RequestSignature, KeyDerivationPath, MessageHash),
+	/// Generate ECDSA signature for the message, using child key.
+	EcdsaSignMessageWithChildKey(ServerKeyId, RequestSignature, KeyDerivationPath, MessageHash),
+	/// Store document key in slot.
+	StoreDocumentKeyInSlot(ServerKeyId, DocumentKeySlotId, RequestSignature, Public, Public),
+	/// Request encryption key, stored in slot, for given requestor.
+	GetDocumentKeyFromSlot(ServerKeyId, DocumentKeySlotId, RequestSignature),
+	/// Request shadow of encryption key, stored in slot, for given requestor.
+	GetDocumentKeyShadowFromSlot(ServerKeyId, DocumentKeySlotId, RequestSignature),
 	/// Change servers set.
 	ChangeServersSet(RequestSignature, RequestSignature, BTreeSet<NodeId>),
+	/// Rotate server key.
+	RotateServerKey(ServerKeyId, RequestSignature),
+	/// Change server key threshold.
+	ChangeServerKeyThreshold(ServerKeyId, usize, RequestSignature),
+	/// Export server key.
+	ExportServerKey(ServerKeyId, Public, u64, Vec<RequestSignature>),
+	/// Export key shares.
+	ExportKeyShares(RequestSignature, KeySharesFilter),
+	/// Import key shares.
+	ImportKeyShares(RequestSignature, Vec<u8>),
+	/// Generate verifiable randomness.
+	GenerateRandomness(H256, RequestSignature),
 }
 
 /// Cloneable http handler
@@ -148,6 +230,14 @@ impl KeyServerHttpHandler {
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.generate_key(document, signature.into(), threshold))
 					.then(move |result| ok(return_server_public_key("GenerateServerKey", &req_uri, cors, result)))),
+			Request::GenerateExpiringServerKey(document, signature, threshold, expiration) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.generate_expiring_key(document, signature.into(), threshold, expiration))
+					.then(move |result| ok(return_server_public_key("GenerateExpiringServerKey", &req_uri, cors, result)))),
+			Request::ImportServerKey(document, signature, key) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.import_key(document, signature.into(), key))
+					.then(move |result| ok(return_server_public_key("ImportServerKey", &req_uri, cors, result)))),
 			Request::StoreDocumentKey(document, signature, common_point, encrypted_document_key) =>
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.store_document_key(
@@ -172,10 +262,22 @@ impl KeyServerHttpHandler {
 						signature.into(),
 					))
 					.then(move |result| ok(return_server_public_key("GetServerKey", &req_uri, cors, result)))),
+			Request::DeleteServerKey(document, signature) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.delete_key(document, signature.into()))
+					.then(move |result| ok(return_empty("DeleteServerKey", &req_uri, cors, result)))),
 			Request::GetDocumentKey(document, signature) =>
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.restore_document_key(document, signature.into()))
 					.then(move |result| ok(return_document_key("GetDocumentKey", &req_uri, cors, result)))),
+			Request::GetEciesSecret(document, signature, ephemeral_public) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.restore_ecies_secret(document, signature.into(), ephemeral_public))
+					.then(move |result| ok(return_ecies_secret("GetEciesSecret", &req_uri, cors, result)))),
+			Request::ComputeEcdhSharedPoint(document, signature, counterparty_public) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.agree_shared_point(document, signature.into(), counterparty_public))
+					.then(move |result| ok(return_ecdh_shared_point("ComputeEcdhSharedPoint", &req_uri, cors, result)))),
 			Request::GetDocumentKeyShadow(document, signature) =>
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.restore_document_key_shadow(document, signature.into()))
@@ -188,6 +290,22 @@ impl KeyServerHttpHandler {
 						message_hash,
 					))
 					.then(move |result| ok(return_message_signature("SchnorrSignMessage", &req_uri, cors, result)))),
+			Request::SchnorrSignMessages(document, signature, message_hashes) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.sign_messages_schnorr(
+						document,
+						signature.into(),
+						message_hashes,
+					))
+					.then(move |result| ok(return_message_signatures("SchnorrSignMessages", &req_uri, cors, result)))),
+			Request::Bip340SignMessage(document, signature, message_hash) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.sign_message_schnorr_bip340(
+						document,
+						signature.into(),
+						message_hash,
+					))
+					.then(move |result| ok(return_message_signature("Bip340SignMessage", &req_uri, cors, result)))),
 			Request::EcdsaSignMessage(document, signature, message_hash) =>
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.sign_message_ecdsa(
@@ -196,6 +314,82 @@ impl KeyServerHttpHandler {
 						message_hash,
 					))
 					.then(move |result| ok(return_message_signature("EcdsaSignMessage", &req_uri, cors, result)))),
+			Request::GenerateEddsaServerKey(document, signature, threshold) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.generate_eddsa_key(document, signature.into(), threshold))
+					.then(move |result| ok(return_eddsa_public_key("GenerateEddsaServerKey", &req_uri, cors, result)))),
+			Request::EddsaSignMessage(document, signature, message_hash) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.sign_message_eddsa(
+						document,
+						signature.into(),
+						message_hash,
+					))
+					.then(move |result| ok(return_message_signature("EddsaSignMessage", &req_uri, cors, result)))),
+			Request::GenerateBlsServerKey(document, signature, threshold) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.generate_bls_key(document, signature.into(), threshold))
+					.then(move |result| ok(return_bls_public_key("GenerateBlsServerKey", &req_uri, cors, result)))),
+			Request::BlsSignMessage(document, signature, message_hash) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.sign_message_bls(
+						document,
+						signature.into(),
+						message_hash,
+					))
+					.then(move |result| ok(return_message_signature("BlsSignMessage", &req_uri, cors, result)))),
+			Request::GetChildKey(document, signature, derivation_path) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.restore_child_key_public(
+						document,
+						signature.into(),
+						derivation_path,
+					))
+					.then(move |result| ok(return_server_public_key("GetChildKey", &req_uri, cors, result)))),
+			Request::GetDocumentKeyWithChildKey(document, signature, derivation_path) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.restore_document_key_with_child_key(
+						document,
+						derivation_path,
+						signature.into(),
+					))
+					.then(move |result| ok(return_document_key("GetDocumentKeyWithChildKey", &req_uri, cors, result)))),
+			Request::StoreDocumentKeyInSlot(document, slot, signature, common_point, encrypted_document_key) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.store_document_key_in_slot(
+						document,
+						slot,
+						signature.into(),
+						common_point,
+						encrypted_document_key,
+					))
+					.then(move |result| ok(return_empty("StoreDocumentKeyInSlot", &req_uri, cors, result)))),
+			Request::GetDocumentKeyFromSlot(document, slot, signature) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.restore_document_key_from_slot(document, slot, signature.into()))
+					.then(move |result| ok(return_document_key("GetDocumentKeyFromSlot", &req_uri, cors, result)))),
+			Request::GetDocumentKeyShadowFromSlot(document, slot, signature) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.restore_document_key_shadow_from_slot(document, slot, signature.into()))
+					.then(move |result| ok(return_document_key_shadow("GetDocumentKeyShadowFromSlot", &req_uri, cors, result)))),
+			Request::SchnorrSignMessageWithChildKey(document, signature, derivation_path, message_hash) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.sign_message_schnorr_with_child_key(
+						document,
+						derivation_path,
+						signature.into(),
+						message_hash,
+					))
+					.then(move |result| ok(return_message_signature("SchnorrSignMessageWithChildKey", &req_uri, cors, result)))),
+			Request::EcdsaSignMessageWithChildKey(document, signature, derivation_path, message_hash) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.sign_message_ecdsa_with_child_key(
+						document,
+						derivation_path,
+						signature.into(),
+						message_hash,
+					))
+					.then(move |result| ok(return_message_signature("EcdsaSignMessageWithChildKey", &req_uri, cors, result)))),
 			Request::ChangeServersSet(old_set_signature, new_set_signature, new_servers_set) =>
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.change_servers_set(
@@ -204,6 +398,30 @@ impl KeyServerHttpHandler {
 						new_servers_set,
 					))
 					.then(move |result| ok(return_empty("ChangeServersSet", &req_uri, cors, result)))),
+			Request::RotateServerKey(document, signature) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.rotate_server_key(document, signature))
+					.then(move |result| ok(return_empty("RotateServerKey", &req_uri, cors, result)))),
+			Request::ChangeServerKeyThreshold(document, new_threshold, signature) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.change_server_key_threshold(document, new_threshold, signature))
+					.then(move |result| ok(return_empty("ChangeServerKeyThreshold", &req_uri, cors, result)))),
+			Request::ExportServerKey(document, recipient, expiry, approvals) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.export_server_key(document, recipient, expiry, approvals))
+					.then(move |result| ok(return_server_key("ExportServerKey", &req_uri, cors, result)))),
+			Request::ExportKeyShares(signature, filter) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.export_key_shares(signature, filter))
+					.then(move |result| ok(return_key_shares_archive("ExportKeyShares", &req_uri, cors, result)))),
+			Request::ImportKeyShares(signature, archive) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.import_key_shares(signature, archive))
+					.then(move |result| ok(return_key_shares_import_result("ImportKeyShares", &req_uri, cors, result)))),
+			Request::GenerateRandomness(beacon_id, signature) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.generate_randomness(beacon_id, signature.into()))
+					.then(move |result| ok(return_randomness_beacon_output("GenerateRandomness", &req_uri, cors, result)))),
 			Request::Invalid => {
 				warn!(target: "secretstore", "Ignoring invalid {}-request {}", req_method, req_uri);
 				Box::new(ok(HttpResponse::builder()
@@ -239,10 +457,18 @@ impl Service for KeyServerHttpHandler {
 				let req_method = req.method().clone();
 				let req_uri = req.uri().clone();
 				let path = req_uri.path().to_string();
+				let max_body_size = max_request_body_size(&req_method, &path);
 				// We cannot consume Self because of the Service trait requirement.
 				let this = self.clone();
 
-				Box::new(req.into_body().concat2()
+				// bytes above the limit are not buffered => oversized request is rejected when body is parsed
+				Box::new(req.into_body()
+					.fold(Vec::new(), move |mut body, chunk| {
+						if max_body_size.map(|max_body_size| body.len() <= max_body_size).unwrap_or(true) {
+							body.extend_from_slice(&chunk);
+						}
+						future::ok::<_, hyper::Error>(body)
+					})
 					.and_then(move |body| this.process(req_method, req_uri, &path, &body, cors)))
 			}
 		}
@@ -262,6 +488,24 @@ fn return_server_public_key(
 	return_bytes(req_type, req_uri, cors, server_public.map(|k| Some(SerializablePublic(k))))
 }
 
+fn return_eddsa_public_key(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	server_public: Result<EddsaPublic, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, server_public.map(|k| Some(SerializableH256(k))))
+}
+
+fn return_bls_public_key(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	server_public: Result<BlsPublic, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, server_public.map(|k| Some(SerializableBytes(k))))
+}
+
 fn return_message_signature(
 	req_type: &str,
 	req_uri: &Uri,
@@ -271,6 +515,15 @@ fn return_message_signature(
 	return_bytes(req_type, req_uri, cors, signature.map(|s| Some(SerializableBytes(s))))
 }
 
+fn return_message_signatures(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	signatures: Result<Vec<EncryptedDocumentKey>, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, signatures.map(|s| Some(s.into_iter().map(SerializableBytes).collect::<Vec<_>>())))
+}
+
 fn return_document_key(
 	req_type: &str,
 	req_uri: &Uri,
@@ -280,6 +533,36 @@ fn return_document_key(
 	return_bytes(req_type, req_uri, cors, document_key.map(|k| Some(SerializableBytes(k))))
 }
 
+fn return_ecies_secret(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	ecies_secret: Result<EncryptedEciesSecret, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, ecies_secret.map(|s| Some(SerializableBytes(s))))
+}
+
+fn return_ecdh_shared_point(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	shared_point: Result<EncryptedEcdhSharedPoint, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, shared_point.map(|p| Some(SerializableBytes(p))))
+}
+
+fn return_server_key(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	server_key: Result<EncryptedServerKey, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, server_key.map(|k| Some(SerializableEncryptedServerKey {
+		public: k.public.into(),
+		secret_shadows: k.secret_shadows.into_iter().map(Into::into).collect(),
+	})))
+}
+
 fn return_document_key_shadow(
 	req_type: &str,
 	req_uri: &Uri,
@@ -293,6 +576,33 @@ fn return_document_key_shadow(
 	})))
 }
 
+fn return_key_shares_archive(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	archive: Result<Vec<u8>, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, archive.map(|a| Some(SerializableBytes(a))))
+}
+
+fn return_key_shares_import_result(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	import_result: Result<KeySharesImportResult, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, import_result.map(|r| Some(SerializableKeySharesImportResult::from(r))))
+}
+
+fn return_randomness_beacon_output(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	output: Result<RandomnessBeaconOutput, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, output.map(|o| Some(SerializableRandomnessBeaconOutput::from(o))))
+}
+
 fn return_bytes<T: Serialize>(
 	req_type: &str,
 	req_uri: &Uri,
@@ -342,11 +652,16 @@ fn return_error(err: Error) -> HttpResponse<Body> {
 		| Error::ServerKeyIsNotFound
 		| Error::DocumentKeyIsNotFound =>
 			HttpStatusCode::NOT_FOUND,
+		| Error::ServerKeyIsDeleted
+		| Error::ServerKeyIsExpired =>
+			HttpStatusCode::GONE,
 		| Error::InsufficientRequesterData(_)
 		| Error::Hyper(_)
 		| Error::Serde(_)
 		| Error::DocumentKeyAlreadyStored
-		| Error::ServerKeyAlreadyGenerated =>
+		| Error::ServerKeyAlreadyGenerated
+		| Error::InvalidKeyCurve
+		| Error::InvalidDerivationPath =>
 			HttpStatusCode::BAD_REQUEST,
 		_ => HttpStatusCode::INTERNAL_SERVER_ERROR,
 	};
@@ -366,6 +681,13 @@ fn return_error(err: Error) -> HttpResponse<Body> {
 	}
 }
 
+fn max_request_body_size(method: &HttpMethod, uri_path: &str) -> Option<usize> {
+	match *method {
+		HttpMethod::POST if uri_path.starts_with("/schnorr/") => Some(MAX_SCHNORR_BATCH_BODY_SIZE),
+		_ => None,
+	}
+}
+
 fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 	let uri_path = match percent_decode(uri_path.as_bytes()).decode_utf8() {
 		Ok(path) => path,
@@ -381,7 +703,40 @@ fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 		return parse_admin_request(method, path, body);
 	}
 
-	let is_known_prefix = &path[0] == "shadow" || &path[0] == "schnorr" || &path[0] == "ecdsa" || &path[0] == "server";
+	if path[0] == "child" {
+		return parse_child_key_request(method, path);
+	}
+
+	if path[0] == "import" {
+		return parse_import_request(method, path, body);
+	}
+
+	if path[0] == "expiring" {
+		return parse_expiring_key_request(method, path);
+	}
+
+	if path[0] == "randomness" {
+		if *method != HttpMethod::POST || path.len() != 3 {
+			return Request::Invalid;
+		}
+
+		return match (path[1].parse(), path[2].parse()) {
+			(Ok(beacon_id), Ok(signature)) => Request::GenerateRandomness(beacon_id, signature),
+			_ => Request::Invalid,
+		};
+	}
+
+	let is_known_prefix = &path[0] == "shadow" || &path[0] == "schnorr" || &path[0] == "bip340"
+		|| &path[0] == "ecdsa" || &path[0] == "eddsa" || &path[0] == "bls" || &path[0] == "server"
+		|| &path[0] == "ecies" || &path[0] == "ecdh";
+	let is_document_key_slot_request = match (method, is_known_prefix, &*path[0], path.len()) {
+		(&HttpMethod::GET, false, _, 3) | (&HttpMethod::GET, true, "shadow", 4) | (&HttpMethod::POST, true, "shadow", 6) => true,
+		_ => false,
+	};
+	if is_document_key_slot_request {
+		return parse_document_key_slot_request(method, path, is_known_prefix);
+	}
+
 	let (prefix, args_offset) = if is_known_prefix { (&*path[0], 1) } else { ("", 0) };
 	let args_count = path.len() - args_offset;
 	if args_count < 2 || path[args_offset].is_empty() || path[args_offset + 1].is_empty() {
@@ -410,21 +765,182 @@ fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 			Request::GenerateDocumentKey(document, signature, threshold),
 		("server", 2, &HttpMethod::GET, _, _, _, _) =>
 			Request::GetServerKey(document, signature),
+		("shadow", 2, &HttpMethod::DELETE, _, _, _, _) =>
+			Request::DeleteServerKey(document, signature),
 		("", 2, &HttpMethod::GET, _, _, _, _) =>
 			Request::GetDocumentKey(document, signature),
 		("shadow", 2, &HttpMethod::GET, _, _, _, _) =>
 			Request::GetDocumentKeyShadow(document, signature),
+		("ecies", 3, &HttpMethod::GET, _, _, Some(Ok(ephemeral_public)), _) =>
+			Request::GetEciesSecret(document, signature, ephemeral_public),
+		("ecdh", 3, &HttpMethod::GET, _, _, Some(Ok(counterparty_public)), _) =>
+			Request::ComputeEcdhSharedPoint(document, signature, counterparty_public),
 		("schnorr", 3, &HttpMethod::GET, _, Some(Ok(message_hash)), _, _) =>
 			Request::SchnorrSignMessage(document, signature, message_hash),
+		("schnorr", 2, &HttpMethod::POST, _, _, _, _) if body.len() <= MAX_SCHNORR_BATCH_BODY_SIZE => match serde_json::from_slice::<Vec<SerializableH256>>(body) {
+			Ok(ref message_hashes) if !message_hashes.is_empty() && message_hashes.len() <= MAX_SCHNORR_BATCH_SIZE =>
+				Request::SchnorrSignMessages(document, signature, message_hashes.iter().cloned().map(Into::into).collect()),
+			_ => Request::Invalid,
+		},
+		("bip340", 3, &HttpMethod::GET, _, Some(Ok(message_hash)), _, _) =>
+			Request::Bip340SignMessage(document, signature, message_hash),
 		("ecdsa", 3, &HttpMethod::GET, _, Some(Ok(message_hash)), _, _) =>
 			Request::EcdsaSignMessage(document, signature, message_hash),
+		("eddsa", 3, &HttpMethod::POST, Some(Ok(threshold)), _, _, _) =>
+			Request::GenerateEddsaServerKey(document, signature, threshold),
+		("eddsa", 3, &HttpMethod::GET, _, Some(Ok(message_hash)), _, _) =>
+			Request::EddsaSignMessage(document, signature, message_hash),
+		("bls", 3, &HttpMethod::POST, Some(Ok(threshold)), _, _, _) =>
+			Request::GenerateBlsServerKey(document, signature, threshold),
+		("bls", 3, &HttpMethod::GET, _, Some(Ok(message_hash)), _, _) =>
+			Request::BlsSignMessage(document, signature, message_hash),
+		_ => Request::Invalid,
+	}
+}
+
+fn parse_expiring_key_request(method: &HttpMethod, path: Vec<String>) -> Request {
+	if *method != HttpMethod::POST || path.len() != 6 {
+		return Request::Invalid;
+	}
+
+	let document = match path[1].parse() {
+		Ok(document) => document,
+		_ => return Request::Invalid,
+	};
+	let signature = match path[2].parse() {
+		Ok(signature) => signature,
+		_ => return Request::Invalid,
+	};
+	let threshold = match path[3].parse() {
+		Ok(threshold) => threshold,
+		_ => return Request::Invalid,
+	};
+	let expiration = match (&*path[4], path[5].parse()) {
+		("block", Ok(not_after)) => KeyExpiration::Block(not_after),
+		("timestamp", Ok(not_after)) => KeyExpiration::Timestamp(not_after),
+		_ => return Request::Invalid,
+	};
+
+	Request::GenerateExpiringServerKey(document, signature, threshold, expiration)
+}
+
+fn parse_child_key_request(method: &HttpMethod, path: Vec<String>) -> Request {
+	if *method != HttpMethod::GET {
+		return Request::Invalid;
+	}
+
+	let is_known_prefix = path.len() > 1 && (&path[1] == "server" || &path[1] == "schnorr" || &path[1] == "ecdsa");
+	let (prefix, args_offset) = if is_known_prefix { (&*path[1], 2) } else { ("", 1) };
+	let args_count = path.len() - args_offset;
+	if args_count < 3 {
+		return Request::Invalid;
+	}
+
+	let document = match path[args_offset].parse() {
+		Ok(document) => document,
+		_ => return Request::Invalid,
+	};
+	let signature = match path[args_offset + 1].parse() {
+		Ok(signature) => signature,
+		_ => return Request::Invalid,
+	};
+	let derivation_path = match path[args_offset + 2].split(',').map(|index| index.parse()).collect() {
+		Ok(derivation_path) => derivation_path,
+		_ => return Request::Invalid,
+	};
+
+	let message_hash = path.get(args_offset + 3).map(|v| v.parse());
+	match (prefix, args_count, message_hash) {
+		("server", 3, _) =>
+			Request::GetChildKey(document, signature, derivation_path),
+		("", 3, _) =>
+			Request::GetDocumentKeyWithChildKey(document, signature, derivation_path),
+		("schnorr", 4, Some(Ok(message_hash))) =>
+			Request::SchnorrSignMessageWithChildKey(document, signature, derivation_path, message_hash),
+		("ecdsa", 4, Some(Ok(message_hash))) =>
+			Request::EcdsaSignMessageWithChildKey(document, signature, derivation_path, message_hash),
+		_ => Request::Invalid,
+	}
+}
+
+fn parse_document_key_slot_request(method: &HttpMethod, path: Vec<String>, is_shadow: bool) -> Request {
+	let args_offset = if is_shadow { 1 } else { 0 };
+	let (document, slot, signature) = match (path[args_offset].parse(), path[args_offset + 1].parse(), path[args_offset + 2].parse()) {
+		(Ok(document), Ok(slot), Ok(signature)) => (document, slot, signature),
+		_ => return Request::Invalid,
+	};
+
+	let common_point = path.get(args_offset + 3).map(|v| v.parse());
+	let encrypted_key = path.get(args_offset + 4).map(|v| v.parse());
+	match (is_shadow, method, common_point, encrypted_key) {
+		(false, &HttpMethod::GET, None, None) =>
+			Request::GetDocumentKeyFromSlot(document, slot, signature),
+		(true, &HttpMethod::GET, None, None) =>
+			Request::GetDocumentKeyShadowFromSlot(document, slot, signature),
+		(true, &HttpMethod::POST, Some(Ok(common_point)), Some(Ok(encrypted_key))) =>
+			Request::StoreDocumentKeyInSlot(document, slot, signature, common_point, encrypted_key),
+		_ => Request::Invalid,
+	}
+}
+
+fn parse_import_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> Request {
+	if *method != HttpMethod::POST || path.len() != 3 {
+		return Request::Invalid;
+	}
+
+	let (document, signature) = match (path[1].parse(), path[2].parse()) {
+		(Ok(document), Ok(signature)) => (document, signature),
+		_ => return Request::Invalid,
+	};
+
+	match serde_json::from_slice::<SerializableKeyImportData>(body) {
+		Ok(key) => Request::ImportServerKey(document, signature, key.into()),
 		_ => Request::Invalid,
 	}
 }
 
 fn parse_admin_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> Request {
 	let args_count = path.len();
-	if *method != HttpMethod::POST || args_count != 4 || path[1] != "servers_set_change" {
+	if *method != HttpMethod::POST {
+		return Request::Invalid;
+	}
+
+	if args_count == 5 && path[1] == "change_threshold" {
+		return match (path[2].parse(), path[3].parse(), path[4].parse()) {
+			(Ok(document), Ok(new_threshold), Ok(signature)) => Request::ChangeServerKeyThreshold(document, new_threshold, signature),
+			_ => Request::Invalid,
+		};
+	}
+
+	if args_count == 5 && path[1] == "export_key" {
+		let approvals: Vec<SerializableSignature> = match serde_json::from_slice(body) {
+			Ok(approvals) => approvals,
+			_ => return Request::Invalid,
+		};
+
+		return match (path[2].parse(), path[3].parse(), path[4].parse()) {
+			(Ok(document), Ok(recipient), Ok(expiry)) => Request::ExportServerKey(document, recipient, expiry,
+				approvals.into_iter().map(Into::into).collect()),
+			_ => Request::Invalid,
+		};
+	}
+
+	if args_count != 4 {
+		return Request::Invalid;
+	}
+
+	if path[1] == "key_shares" {
+		return parse_key_shares_request(path, body);
+	}
+
+	if path[1] == "rotate_key" {
+		return match (path[2].parse(), path[3].parse()) {
+			(Ok(document), Ok(signature)) => Request::RotateServerKey(document, signature),
+			_ => Request::Invalid,
+		};
+	}
+
+	if path[1] != "servers_set_change" {
 		return Request::Invalid;
 	}
 
@@ -447,6 +963,36 @@ fn parse_admin_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> R
 		new_servers_set.into_iter().map(Into::into).collect())
 }
 
+fn parse_key_shares_request(path: Vec<String>, body: &[u8]) -> Request {
+	let signature = match path[3].parse() {
+		Ok(signature) => signature,
+		_ => return Request::Invalid,
+	};
+
+	match &*path[2] {
+		"export" => {
+			let filter: SerializableKeySharesFilter = match body.is_empty() {
+				true => Default::default(),
+				false => match serde_json::from_slice(body) {
+					Ok(filter) => filter,
+					_ => return Request::Invalid,
+				},
+			};
+
+			Request::ExportKeyShares(signature, filter.into())
+		},
+		"import" => {
+			let archive: SerializableBytes = match serde_json::from_slice(body) {
+				Ok(archive) => archive,
+				_ => return Request::Invalid,
+			};
+
+			Request::ImportKeyShares(signature, archive.into())
+		},
+		_ => Request::Invalid,
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use std::sync::Arc;
@@ -458,7 +1004,10 @@ mod tests {
 	use types::NodeAddress;
 	use parity_runtime::Runtime;
 	use ethereum_types::H256;
-	use super::{parse_request, Request, KeyServerHttpListener};
+	use types::{KeySharesFilter, KeyImportData, ImportedKeyShare, KeyExpiration};
+	use serde_json;
+	use serialization::SerializableH256;
+	use super::{parse_request, Request, KeyServerHttpListener, MAX_SCHNORR_BATCH_SIZE, MAX_SCHNORR_BATCH_BODY_SIZE};
 
 	#[test]
 	fn http_listener_successfully_drops() {
@@ -477,6 +1026,15 @@ mod tests {
 			Request::GenerateServerKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
 				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
 				2));
+		// POST		/expiring/{server_key_id}/{signature}/{threshold}/{block|timestamp}/{not_after}	=> generate expiring server key
+		assert_eq!(parse_request(&HttpMethod::POST, "/expiring/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2/block/100", Default::default()),
+			Request::GenerateExpiringServerKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				2, KeyExpiration::Block(100)));
+		assert_eq!(parse_request(&HttpMethod::POST, "/expiring/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2/timestamp/1600000000", Default::default()),
+			Request::GenerateExpiringServerKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				2, KeyExpiration::Timestamp(1600000000)));
 		// POST		/shadow/{server_key_id}/{signature}/{common_point}/{encrypted_key}	=> store encrypted document key
 		assert_eq!(parse_request(&HttpMethod::POST, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8/1395568277679f7f583ab7c0992da35f26cde57149ee70e524e49bdae62db3e18eb96122501e7cbb798b784395d7bb5a499edead0706638ad056d886e56cf8fb", Default::default()),
 			Request::StoreDocumentKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
@@ -492,6 +1050,10 @@ mod tests {
 		assert_eq!(parse_request(&HttpMethod::GET, "/server/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
 			Request::GetServerKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
 				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// DELETE	/shadow/{server_key_id}/{signature}									=> delete server key
+		assert_eq!(parse_request(&HttpMethod::DELETE, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
+			Request::DeleteServerKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
 		// GET		/{server_key_id}/{signature}										=> get document key
 		assert_eq!(parse_request(&HttpMethod::GET, "/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
 			Request::GetDocumentKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
@@ -503,16 +1065,99 @@ mod tests {
 		assert_eq!(parse_request(&HttpMethod::GET, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
 			Request::GetDocumentKeyShadow(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
 				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// POST		/shadow/{server_key_id}/{slot}/{signature}/{common_point}/{encrypted_key}	=> store encrypted document key in slot
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/0000000000000000000000000000000000000000000000000000000000000002/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8/1395568277679f7f583ab7c0992da35f26cde57149ee70e524e49bdae62db3e18eb96122501e7cbb798b784395d7bb5a499edead0706638ad056d886e56cf8fb", Default::default()),
+			Request::StoreDocumentKeyInSlot(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				H256::from_low_u64_be(2),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				"b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8".parse().unwrap(),
+				"1395568277679f7f583ab7c0992da35f26cde57149ee70e524e49bdae62db3e18eb96122501e7cbb798b784395d7bb5a499edead0706638ad056d886e56cf8fb".parse().unwrap()));
+		// GET		/{server_key_id}/{slot}/{signature}									=> get document key from slot
+		assert_eq!(parse_request(&HttpMethod::GET, "/0000000000000000000000000000000000000000000000000000000000000001/0000000000000000000000000000000000000000000000000000000000000002/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
+			Request::GetDocumentKeyFromSlot(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				H256::from_low_u64_be(2),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// GET		/shadow/{server_key_id}/{slot}/{signature}							=> get document key shadow from slot
+		assert_eq!(parse_request(&HttpMethod::GET, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/0000000000000000000000000000000000000000000000000000000000000002/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
+			Request::GetDocumentKeyShadowFromSlot(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				H256::from_low_u64_be(2),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// GET		/ecies/{server_key_id}/{signature}/{ephemeral_public}				=> get ECDH secret of ECIES ciphertext
+		assert_eq!(parse_request(&HttpMethod::GET, "/ecies/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8", Default::default()),
+			Request::GetEciesSecret(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				"b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8".parse().unwrap()));
+		// GET		/ecdh/{server_key_id}/{signature}/{counterparty_public}				=> compute ECDH shared point with server key
+		assert_eq!(parse_request(&HttpMethod::GET, "/ecdh/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8", Default::default()),
+			Request::ComputeEcdhSharedPoint(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				"b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8".parse().unwrap()));
 		// GET		/schnorr/{server_key_id}/{signature}/{message_hash}					=> schnorr-sign message with server key
 		assert_eq!(parse_request(&HttpMethod::GET, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
 			Request::SchnorrSignMessage(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
 				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
 				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
+		// POST		/schnorr/{server_key_id}/{signature} + body							=> schnorr-sign batch of messages with server key
+		assert_eq!(parse_request(&HttpMethod::POST, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#"["0x281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c","0x0000000000000000000000000000000000000000000000000000000000000002"]"#.as_bytes()),
+			Request::SchnorrSignMessages(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				vec![
+					"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap(),
+					H256::from_low_u64_be(2),
+				]));
+		// GET		/bip340/{server_key_id}/{signature}/{message_hash}					=> bip340-sign message with server key
+		assert_eq!(parse_request(&HttpMethod::GET, "/bip340/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
+			Request::Bip340SignMessage(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
 		// GET		/ecdsa/{server_key_id}/{signature}/{message_hash}					=> ecdsa-sign message with server key
 		assert_eq!(parse_request(&HttpMethod::GET, "/ecdsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
 			Request::EcdsaSignMessage(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
 				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
 				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
+		// POST		/eddsa/{server_key_id}/{signature}/{threshold}						=> generate Ed25519 server key
+		assert_eq!(parse_request(&HttpMethod::POST, "/eddsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2", Default::default()),
+			Request::GenerateEddsaServerKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				2));
+		// GET		/eddsa/{server_key_id}/{signature}/{message_hash}					=> eddsa-sign message with server key
+		assert_eq!(parse_request(&HttpMethod::GET, "/eddsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
+			Request::EddsaSignMessage(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
+		// POST		/bls/{server_key_id}/{signature}/{threshold}						=> generate BLS12-381 server key
+		assert_eq!(parse_request(&HttpMethod::POST, "/bls/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2", Default::default()),
+			Request::GenerateBlsServerKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				2));
+		// GET		/bls/{server_key_id}/{signature}/{message_hash}						=> bls-sign message with server key
+		assert_eq!(parse_request(&HttpMethod::GET, "/bls/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
+			Request::BlsSignMessage(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
+		// GET		/child/server/{server_key_id}/{signature}/{derivation_path}			=> get public portion of child key
+		assert_eq!(parse_request(&HttpMethod::GET, "/child/server/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0,42", Default::default()),
+			Request::GetChildKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				vec![0, 42]));
+		// GET		/child/{server_key_id}/{signature}/{derivation_path}					=> get document key using child key
+		assert_eq!(parse_request(&HttpMethod::GET, "/child/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/7", Default::default()),
+			Request::GetDocumentKeyWithChildKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				vec![7]));
+		// GET		/child/schnorr/{server_key_id}/{signature}/{derivation_path}/{message_hash}	=> schnorr-sign message with child key
+		assert_eq!(parse_request(&HttpMethod::GET, "/child/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0,42/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
+			Request::SchnorrSignMessageWithChildKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				vec![0, 42],
+				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
+		// GET		/child/ecdsa/{server_key_id}/{signature}/{derivation_path}/{message_hash}	=> ecdsa-sign message with child key
+		assert_eq!(parse_request(&HttpMethod::GET, "/child/ecdsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0,42/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
+			Request::EcdsaSignMessageWithChildKey(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				vec![0, 42],
+				"281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c".parse().unwrap()));
 		// POST		/admin/servers_set_change/{old_set_signature}/{new_set_signature} + body
 		let node1: Public = "843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91".parse().unwrap();
 		let node2: Public = "07230e34ebfe41337d3ed53b186b3861751f2401ee74b988bba55694e2a6f60c757677e194be2e53c3523cc8548694e636e6acb35c4e8fdc5e29d28679b9b2f3".parse().unwrap();
@@ -525,19 +1170,111 @@ mod tests {
 				"b199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
 				nodes,
 			));
+		// POST		/admin/rotate_key/{server_key_id}/{signature}
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/rotate_key/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			Default::default()),
+			Request::RotateServerKey(H256::from_low_u64_be(1),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// POST		/admin/change_threshold/{server_key_id}/{new_threshold}/{signature}
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/change_threshold/0000000000000000000000000000000000000000000000000000000000000001/3/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			Default::default()),
+			Request::ChangeServerKeyThreshold(H256::from_low_u64_be(1), 3,
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// POST		/admin/export_key/{server_key_id}/{recipient_public}/{expiry} + body
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/export_key/0000000000000000000000000000000000000000000000000000000000000001/843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91/1600000000",
+			&r#"["0xa199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01"]"#.as_bytes()),
+			Request::ExportServerKey(H256::from_low_u64_be(1),
+				"843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91".parse().unwrap(),
+				1600000000,
+				vec!["a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()]));
+		// POST		/admin/key_shares/export/{signature} + body
+		let key_id = H256::from_low_u64_be(1);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/key_shares/export/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#"{"key_ids":["0x0000000000000000000000000000000000000000000000000000000000000001"]}"#.as_bytes()),
+			Request::ExportKeyShares(
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				KeySharesFilter::with_key_ids(vec![key_id].into_iter().collect()),
+			));
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/key_shares/export/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			Default::default()),
+			Request::ExportKeyShares(
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				Default::default(),
+			));
+		// POST		/admin/key_shares/import/{signature} + body
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/key_shares/import/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#""0x01020304""#.as_bytes()),
+			Request::ImportKeyShares(
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				vec![1, 2, 3, 4],
+			));
+		// POST		/import/{server_key_id}/{signature} + body
+		let node: Public = "843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91".parse().unwrap();
+		assert_eq!(parse_request(&HttpMethod::POST, "/import/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#"{"share_commitments":["0x843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91"],
+				"shares":{"0x843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91":{"id_number":"0x0000000000000000000000000000000000000000000000000000000000000001","encrypted_share":"0x0102"}}}"#.as_bytes()),
+			Request::ImportServerKey(
+				H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				KeyImportData {
+					share_commitments: vec![node.clone()],
+					shares: vec![(node, ImportedKeyShare {
+						id_number: "0000000000000000000000000000000000000000000000000000000000000001".parse().unwrap(),
+						encrypted_share: vec![1, 2],
+					})].into_iter().collect(),
+				},
+			));
+		// POST		/randomness/{beacon_id}/{signature}
+		assert_eq!(parse_request(&HttpMethod::POST, "/randomness/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
+			Request::GenerateRandomness(H256::from_low_u64_be(1),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
 	}
 
 	#[test]
 	fn parse_request_failed() {
 		assert_eq!(parse_request(&HttpMethod::GET, "", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/expiring/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2/epoch/100", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/expiring/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2/block", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "/shadow", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "///2", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "/shadow///2", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "/0000000000000000000000000000000000000000000000000000000000000001", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "/0000000000000000000000000000000000000000000000000000000000000001/", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "/a/b", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0000000000000000000000000000000000000000000000000000000000000002", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/0000000000000000000000000000000000000000000000000000000000000001/0000000000000000000000000000000000000000000000000000000000000002/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/0000000000000000000000000000000000000000000000000000000000000002/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8/xyz", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/ecies/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/ecdh/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/b486d3840218837b035c66196ecb15e6b067ca20101e11bd5e626288ab6806ecc70b8307012626bd512bad1559112d11d21025cef48cc7a1d2f3976da08f36c8", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/ecdh/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/xyz", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/key_shares/import/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/admin/rotate_key/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/rotate_key/0000000000000000000000000000000000000000000000000000000000000001/xyz", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/change_threshold/0000000000000000000000000000000000000000000000000000000000000001/-1/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/export_key/0000000000000000000000000000000000000000000000000000000000000001/843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91/1600000000", "not json".as_bytes()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/export_key/0000000000000000000000000000000000000000000000000000000000000001/843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91", "[]".as_bytes()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/import/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/import/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", "[]".as_bytes()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/import/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/randomness/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/randomness/0000000000000000000000000000000000000000000000000000000000000001", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/randomness/xyz/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/randomness/0000000000000000000000000000000000000000000000000000000000000001/2", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/key_shares/backup/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::DELETE, "/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::DELETE, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0000000000000000000000000000000000000000000000000000000000000002/0000000000000000000000000000000000000000000000000000000000000002", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::GET, "/ecdsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0000000000000000000000000000000000000000000000000000000000000002/0000000000000000000000000000000000000000000000000000000000000002", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", "[]".as_bytes()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()), Request::Invalid);
+		let oversized_batch = serde_json::to_vec(&vec![SerializableH256(H256::from_low_u64_be(2)); MAX_SCHNORR_BATCH_SIZE + 1]).unwrap();
+		assert_eq!(parse_request(&HttpMethod::POST, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", &oversized_batch), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", &vec![b' '; MAX_SCHNORR_BATCH_BODY_SIZE + 1]), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::DELETE, "/eddsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::DELETE, "/bls/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/2", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/child/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0,x", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/child/ecdsa/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/child/server/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/0", Default::default()), Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::POST, "/admin/servers_set_change/xxx/yyy",
 			&r#"["0x843645726384530ffb0c52f175278143b5a93959af7864460f5a4fec9afd1450cfb8aef63dec90657f43f55b13e0a73c7524d4e9a13c051b4e5f1e53f39ecd91",
 				"0x07230e34ebfe41337d3ed53b186b3861751f2401ee74b988bba55694e2a6f60c757677e194be2e53c3523cc8548694e636e6acb35c4e8fdc5e29d28679b9b2f3"]"#.as_bytes()),
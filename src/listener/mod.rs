@@ -23,9 +23,13 @@ mod tasks_queue;
 use std::collections::BTreeSet;
 use std::sync::Arc;
 use futures::Future;
-use traits::{ServerKeyGenerator, DocumentKeyServer, MessageSigner, AdminSessionsServer, KeyServer};
-use types::{Error, Public, MessageHash, EncryptedMessageSignature, RequestSignature, ServerKeyId,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, Requester};
+use ethereum_types::H256;
+use traits::{ServerKeyGenerator, DocumentKeyServer, MessageSigner, ChildKeyServer, DocumentKeySlotsServer, KeyAgreementServer,
+	RandomnessBeacon, AdminSessionsServer, KeyServer};
+use types::{Error, Public, EddsaPublic, BlsPublic, MessageHash, EncryptedMessageSignature, RequestSignature, ServerKeyId,
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, EncryptedEciesSecret, EncryptedEcdhSharedPoint, NodeId, Requester, KeySharesFilter,
+	KeySharesImportResult, KeyDerivationPath, RandomnessBeaconOutput, KeyImportData, EncryptedServerKey, DocumentKeySlotId,
+	KeyExpiration};
 
 /// Available API mask.
 #[derive(Debug, Default)]
@@ -82,6 +86,43 @@ impl ServerKeyGenerator for Listener {
 		self.key_server.generate_key(key_id, author, threshold)
 	}
 
+	fn generate_expiring_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+		expiration: KeyExpiration,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+		self.key_server.generate_expiring_key(key_id, author, threshold, expiration)
+	}
+
+	fn generate_eddsa_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+	) -> Box<dyn Future<Item=EddsaPublic, Error=Error> + Send> {
+		self.key_server.generate_eddsa_key(key_id, author, threshold)
+	}
+
+	fn generate_bls_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+	) -> Box<dyn Future<Item=BlsPublic, Error=Error> + Send> {
+		self.key_server.generate_bls_key(key_id, author, threshold)
+	}
+
+	fn import_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		key: KeyImportData,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+		self.key_server.import_key(key_id, author, key)
+	}
+
 	fn restore_key_public(
 		&self,
 		key_id: ServerKeyId,
@@ -89,6 +130,14 @@ impl ServerKeyGenerator for Listener {
 	) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
 		self.key_server.restore_key_public(key_id, author)
 	}
+
+	fn delete_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		self.key_server.delete_key(key_id, author)
+	}
 }
 
 impl DocumentKeyServer for Listener {
@@ -126,6 +175,15 @@ impl DocumentKeyServer for Listener {
 	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
 		self.key_server.restore_document_key_shadow(key_id, requester)
 	}
+
+	fn restore_ecies_secret(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		ephemeral_public: Public,
+	) -> Box<dyn Future<Item=EncryptedEciesSecret, Error=Error> + Send> {
+		self.key_server.restore_ecies_secret(key_id, requester, ephemeral_public)
+	}
 }
 
 impl MessageSigner for Listener {
@@ -138,6 +196,24 @@ impl MessageSigner for Listener {
 		self.key_server.sign_message_schnorr(key_id, requester, message)
 	}
 
+	fn sign_messages_schnorr(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		messages: Vec<MessageHash>,
+	) -> Box<dyn Future<Item=Vec<EncryptedMessageSignature>, Error=Error> + Send> {
+		self.key_server.sign_messages_schnorr(key_id, requester, messages)
+	}
+
+	fn sign_message_schnorr_bip340(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		self.key_server.sign_message_schnorr_bip340(key_id, requester, message)
+	}
+
 	fn sign_message_ecdsa(
 		&self,
 		key_id: ServerKeyId,
@@ -146,6 +222,116 @@ impl MessageSigner for Listener {
 	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
 		self.key_server.sign_message_ecdsa(key_id, requester, message)
 	}
+
+	fn sign_message_eddsa(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		self.key_server.sign_message_eddsa(key_id, requester, message)
+	}
+
+	fn sign_message_bls(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		self.key_server.sign_message_bls(key_id, requester, message)
+	}
+}
+
+impl ChildKeyServer for Listener {
+	fn restore_child_key_public(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		derivation_path: KeyDerivationPath,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send> {
+		self.key_server.restore_child_key_public(key_id, author, derivation_path)
+	}
+
+	fn sign_message_schnorr_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		self.key_server.sign_message_schnorr_with_child_key(key_id, derivation_path, requester, message)
+	}
+
+	fn sign_message_ecdsa_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send> {
+		self.key_server.sign_message_ecdsa_with_child_key(key_id, derivation_path, requester, message)
+	}
+
+	fn restore_document_key_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+		self.key_server.restore_document_key_with_child_key(key_id, derivation_path, requester)
+	}
+}
+
+impl DocumentKeySlotsServer for Listener {
+	fn store_document_key_in_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		author: Requester,
+		common_point: Public,
+		encrypted_document_key: Public,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		self.key_server.store_document_key_in_slot(key_id, slot, author, common_point, encrypted_document_key)
+	}
+
+	fn restore_document_key_from_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send> {
+		self.key_server.restore_document_key_from_slot(key_id, slot, requester)
+	}
+
+	fn restore_document_key_shadow_from_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
+		self.key_server.restore_document_key_shadow_from_slot(key_id, slot, requester)
+	}
+}
+
+impl KeyAgreementServer for Listener {
+	fn agree_shared_point(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		counterparty_public: Public,
+	) -> Box<dyn Future<Item=EncryptedEcdhSharedPoint, Error=Error> + Send> {
+		self.key_server.agree_shared_point(key_id, requester, counterparty_public)
+	}
+}
+
+impl RandomnessBeacon for Listener {
+	fn generate_randomness(
+		&self,
+		beacon_id: H256,
+		requester: Requester,
+	) -> Box<dyn Future<Item=RandomnessBeaconOutput, Error=Error> + Send> {
+		self.key_server.generate_randomness(beacon_id, requester)
+	}
 }
 
 impl AdminSessionsServer for Listener {
@@ -157,4 +343,62 @@ impl AdminSessionsServer for Listener {
 	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
 		self.key_server.change_servers_set(old_set_signature, new_set_signature, new_servers_set)
 	}
+
+	fn refresh_key_shares(
+		&self,
+		key_id: ServerKeyId,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		self.key_server.refresh_key_shares(key_id, signature)
+	}
+
+	fn refresh_all_key_shares(
+		&self,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		self.key_server.refresh_all_key_shares(signature)
+	}
+
+	fn rotate_server_key(
+		&self,
+		key_id: ServerKeyId,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		self.key_server.rotate_server_key(key_id, signature)
+	}
+
+	fn change_server_key_threshold(
+		&self,
+		key_id: ServerKeyId,
+		new_threshold: usize,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send> {
+		self.key_server.change_server_key_threshold(key_id, new_threshold, signature)
+	}
+
+	fn export_server_key(
+		&self,
+		key_id: ServerKeyId,
+		recipient: Public,
+		expiry: u64,
+		approvals: Vec<RequestSignature>,
+	) -> Box<dyn Future<Item=EncryptedServerKey, Error=Error> + Send> {
+		self.key_server.export_server_key(key_id, recipient, expiry, approvals)
+	}
+
+	fn export_key_shares(
+		&self,
+		signature: RequestSignature,
+		filter: KeySharesFilter,
+	) -> Box<dyn Future<Item=Vec<u8>, Error=Error> + Send> {
+		self.key_server.export_key_shares(signature, filter)
+	}
+
+	fn import_key_shares(
+		&self,
+		signature: RequestSignature,
+		archive: Vec<u8>,
+	) -> Box<dyn Future<Item=KeySharesImportResult, Error=Error> + Send> {
+		self.key_server.import_key_shares(signature, archive)
+	}
 }
@@ -311,7 +311,7 @@ impl ServiceContractListener {
 	/// Generate server key (start generation session).
 	fn generate_server_key(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId, author: Address, threshold: usize) -> Result<(), String> {
 		Self::process_server_key_generation_result(data, origin, server_key_id, data.cluster.new_generation_session(
-			server_key_id.clone(), Some(origin), author, threshold).map(|_| None).map_err(Into::into))
+			server_key_id.clone(), Some(origin), author, threshold, None).map(|_| None).map_err(Into::into))
 	}
 
 	/// Process server key generation result.
@@ -356,9 +356,9 @@ impl ServiceContractListener {
 	fn store_document_key(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId, author: &Address, common_point: &Public, encrypted_point: &Public) -> Result<(), String> {
 		let store_result = data.key_storage.get(server_key_id)
 			.and_then(|key_share| key_share.ok_or(Error::ServerKeyIsNotFound))
-			.and_then(|key_share| check_encrypted_data(Some(&key_share)).map(|_| key_share).map_err(Into::into))
+			.and_then(|key_share| check_encrypted_data(Some(&key_share), None).map(|_| key_share).map_err(Into::into))
 			.and_then(|key_share| update_encrypted_data(&data.key_storage, server_key_id.clone(), key_share,
-				author.clone(), common_point.clone(), encrypted_point.clone()).map_err(Into::into));
+				author.clone(), None, common_point.clone(), encrypted_point.clone()).map_err(Into::into));
 		match store_result {
 			Ok(()) => {
 				data.contract.publish_stored_document_key(&origin, server_key_id)
@@ -401,7 +401,7 @@ impl ServiceContractListener {
 	/// Retrieve personal part of document key (start decryption session).
 	fn retrieve_document_key_personal(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId, requester: Public) -> Result<(), String> {
 		Self::process_document_key_retrieval_result(data, origin, server_key_id, &public_to_address(&requester), data.cluster.new_decryption_session(
-			server_key_id.clone(), Some(origin), requester.clone().into(), None, true, true).map(|_| None).map_err(Into::into))
+			server_key_id.clone(), Some(origin), requester.clone().into(), Vec::new(), None, None, None, true, true).map(|_| None).map_err(Into::into))
 	}
 
 	/// Process document key retrieval result.
@@ -15,19 +15,43 @@
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Secret Store DB migration module.
+//!
+//! Every migration step upgrades all key records from the previous database version
+//! to the next one. Steps are applied in memory, so database is left untouched when
+//! any step fails. Before upgraded records are written, original records are saved
+//! to the backup file in the data directory and they're restored if the upgrade fails.
 
-
+use std::collections::BTreeMap;
 use std::fmt::{Display, Error as FmtError, Formatter};
 use std::fs;
 use std::io::{Error as IoError, ErrorKind as IoErrorKind, Read as _};
 use std::path::PathBuf;
+use serde_json;
+use ethereum_types::Address;
+use crypto::publickey::{Public, public_to_address};
+use kvdb::KeyValueDB;
+use key_storage::{KeyStorageEncryptionKey, DocumentKeyShareVersion, KeyCurve, EcdsaSigningScheme,
+	SerializableDocumentKeyShareV3, SerializableDocumentKeyShareVersionV3};
+use serialization::{SerializableBytes, SerializablePublic, SerializableSecret, SerializableH256};
+use types::ServerKeyId;
 
 /// Current db version.
-const CURRENT_VERSION: u8 = 4;
+const CURRENT_VERSION: u8 = 5;
 /// Database is assumed to be at the default version, when no version file is found.
 const DEFAULT_VERSION: u8 = 3;
 /// Version file name.
 const VERSION_FILE_NAME: &str = "db_version";
+/// Key of the database version record, used by legacy (pre-V4) databases.
+const LEGACY_VERSION_KEY: &'static [u8] = b"version";
+
+/// Registry of all migration steps. Step at index N upgrades records from version N to version N + 1.
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [
+	Migration { from: 0, description: "make common and encrypted points optional", upgrade: upgrade_v0_record },
+	Migration { from: 1, description: "introduce key share versions", upgrade: upgrade_v1_record },
+	Migration { from: 2, description: "replace author public with author address", upgrade: upgrade_v2_record },
+	Migration { from: 3, description: "move database version to the version file", upgrade: upgrade_v3_record },
+	Migration { from: 4, description: "encrypt key shares", upgrade: upgrade_v4_record },
+];
 
 /// Migration related errors.
 #[derive(Debug)]
@@ -36,13 +60,120 @@ pub enum Error {
 	UnknownDatabaseVersion,
 	/// Existing DB is newer than the known one.
 	FutureDBVersion,
-	/// Migration using parity-ethereum 2.6.7 is required.
-	MigrationWithLegacyVersionRequired,
+	/// Key record cannot be upgraded.
+	InvalidRecord(u8, ServerKeyId, String),
+	/// Error reading or writing database records.
+	Database(String),
+	/// Migration has failed and original records have been restored.
+	RolledBack(String),
 	/// Migration was completed successfully,
 	/// but there was a problem with io.
 	Io(IoError),
 }
 
+/// Migration options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MigrationOptions {
+	/// Only check that all records could be upgraded, without writing anything.
+	pub dry_run: bool,
+	/// Save original records to the backup file before writing upgraded records.
+	pub backup: bool,
+}
+
+/// Migration result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationReport {
+	/// Database version before migration.
+	pub from_version: u8,
+	/// Database version after migration.
+	pub to_version: u8,
+	/// Descriptions of applied (or, in dry-run mode, pending) migration steps.
+	pub steps: Vec<&'static str>,
+	/// Number of upgraded key records.
+	pub records: usize,
+	/// Path to the backup of original records.
+	pub backup_path: Option<PathBuf>,
+}
+
+/// Single migration step.
+struct Migration {
+	/// Version the records are upgraded from.
+	from: u8,
+	/// Human-readable description of the step.
+	description: &'static str,
+	/// Upgrade single key record.
+	upgrade: fn(&KeyStorageEncryptionKey, &ServerKeyId, Vec<u8>) -> Result<Vec<u8>, String>,
+}
+
+/// Backup of database records, made before migration.
+#[derive(Serialize, Deserialize)]
+struct DatabaseBackup {
+	/// Database version of backed up records.
+	version: u8,
+	/// Backed up records.
+	records: Vec<(SerializableBytes, SerializableBytes)>,
+}
+
+/// V0 of encrypted key share, as it is stored by key storage on the single key server.
+#[derive(Serialize, Deserialize)]
+struct SerializableDocumentKeyShareV0 {
+	/// Decryption threshold (at least threshold + 1 nodes are required to decrypt data).
+	pub threshold: usize,
+	/// Nodes ids numbers.
+	pub id_numbers: BTreeMap<SerializablePublic, SerializableSecret>,
+	/// Node secret share.
+	pub secret_share: SerializableSecret,
+	/// Common (shared) encryption point.
+	pub common_point: SerializablePublic,
+	/// Encrypted point.
+	pub encrypted_point: SerializablePublic,
+}
+
+/// V1 of encrypted key share, as it is stored by key storage on the single key server.
+#[derive(Serialize, Deserialize)]
+struct SerializableDocumentKeyShareV1 {
+	/// Decryption threshold (at least threshold + 1 nodes are required to decrypt data).
+	pub threshold: usize,
+	/// Nodes ids numbers.
+	pub id_numbers: BTreeMap<SerializablePublic, SerializableSecret>,
+	/// Node secret share.
+	pub secret_share: SerializableSecret,
+	/// Key share polynom.
+	pub polynom1: Vec<SerializableSecret>,
+	/// Common (shared) encryption point.
+	pub common_point: Option<SerializablePublic>,
+	/// Encrypted point.
+	pub encrypted_point: Option<SerializablePublic>,
+}
+
+/// V2 of encrypted key share, as it is stored by key storage on the single key server.
+#[derive(Serialize, Deserialize)]
+struct SerializableDocumentKeyShareV2 {
+	/// Author of the entry.
+	pub author: SerializablePublic,
+	/// Decryption threshold (at least threshold + 1 nodes are required to decrypt data).
+	pub threshold: usize,
+	/// Server public.
+	pub public: SerializablePublic,
+	/// Common (shared) encryption point.
+	pub common_point: Option<SerializablePublic>,
+	/// Encrypted point.
+	pub encrypted_point: Option<SerializablePublic>,
+	/// Versions.
+	pub versions: Vec<SerializableDocumentKeyShareVersionV2>,
+}
+
+/// V2 of encrypted key share version, as it is stored by key storage on the single key server.
+#[derive(Serialize, Deserialize)]
+struct SerializableDocumentKeyShareVersionV2 {
+	/// Version hash.
+	pub hash: SerializableH256,
+	/// Nodes ids numbers.
+	pub id_numbers: BTreeMap<SerializablePublic, SerializableSecret>,
+	/// Node secret share.
+	pub secret_share: SerializableSecret,
+}
+
 impl Display for Error {
 	fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
 		let out = match *self {
@@ -51,9 +182,12 @@ impl Display for Error {
 			Error::FutureDBVersion =>
 				"Secret Store database was created with newer client version.\
 				Upgrade your client or delete DB and resync.".into(),
-			Error::MigrationWithLegacyVersionRequired =>
-				"Secret Store database was created with an older client version.\
-				To migrate, use parity-ethereum v2.6.7, then retry using the latest.".into(),
+			Error::InvalidRecord(version, ref key, ref err) =>
+				format!("Secret Store database record {:?} cannot be upgraded from version {}: {}.", key, version, err),
+			Error::Database(ref err) =>
+				format!("Unexpected database error on Secret Store database migration: {}.", err),
+			Error::RolledBack(ref err) =>
+				format!("Secret Store database migration has failed and has been rolled back: {}.", err),
 			Error::Io(ref err) =>
 				format!("Unexpected io error on Secret Store database migration: {}.", err),
 		};
@@ -68,14 +202,180 @@ impl From<IoError> for Error {
 }
 
 /// Apply all migrations if possible.
-pub fn upgrade_db(db_path: &str) -> Result<(), Error> {
-	match current_version(db_path)? {
-		old_version if old_version < CURRENT_VERSION => {
-			Err(Error::MigrationWithLegacyVersionRequired)
+pub fn upgrade_db(db_path: &str, db: &dyn KeyValueDB, encryption_key: &KeyStorageEncryptionKey, options: MigrationOptions) -> Result<MigrationReport, Error> {
+	let from_version = current_version(db_path, db)?;
+	if from_version > CURRENT_VERSION {
+		return Err(Error::FutureDBVersion);
+	}
+
+	let mut report = MigrationReport {
+		from_version: from_version,
+		to_version: CURRENT_VERSION,
+		steps: MIGRATIONS[from_version as usize..].iter().map(|m| m.description).collect(),
+		records: 0,
+		backup_path: None,
+	};
+	if from_version == CURRENT_VERSION {
+		if !options.dry_run && !version_file_path(db_path).exists() {
+			update_version(db_path, CURRENT_VERSION)?;
+		}
+		return Ok(report);
+	}
+
+	// upgrade all records in memory first => db is untouched if any record can't be upgraded
+	let original_records = read_records(db);
+	let mut upgraded_records = original_records.clone();
+	for migration in &MIGRATIONS[from_version as usize..] {
+		upgraded_records = upgraded_records.into_iter()
+			.map(|(key, value)| (migration.upgrade)(encryption_key, &key, value)
+				.map(|value| (key.clone(), value))
+				.map_err(|err| Error::InvalidRecord(migration.from, key, err)))
+			.collect::<Result<_, _>>()?;
+	}
+	report.records = upgraded_records.len();
+
+	if options.dry_run {
+		return Ok(report);
+	}
+
+	if options.backup {
+		report.backup_path = Some(write_backup(db_path, from_version, &original_records)?);
+	}
+
+	write_upgraded_records(db_path, db, &upgraded_records)
+		.or_else(|err| {
+			write_records(db, &original_records)
+				.map_err(|restore_err| Error::Database(format!("{}; failed to restore original records: {}", err, restore_err)))?;
+			Err(Error::RolledBack(err.to_string()))
+		})?;
+
+	Ok(report)
+}
+
+/// Write upgraded records and update database version.
+fn write_upgraded_records(db_path: &str, db: &dyn KeyValueDB, records: &[(ServerKeyId, Vec<u8>)]) -> Result<(), Error> {
+	write_records(db, records)?;
+
+	let mut batch = db.transaction();
+	batch.delete(0, LEGACY_VERSION_KEY);
+	db.write(batch).map_err(|e| Error::Database(e.to_string()))?;
+
+	update_version(db_path, CURRENT_VERSION)
+}
+
+/// Read all key records from the database. Tombstones and other service records are skipped.
+fn read_records(db: &dyn KeyValueDB) -> Vec<(ServerKeyId, Vec<u8>)> {
+	db.iter(0)
+		.filter(|&(ref db_key, _)| db_key.len() == ServerKeyId::len_bytes())
+		.map(|(db_key, db_val)| (ServerKeyId::from_slice(&*db_key), db_val.into_vec()))
+		.collect()
+}
+
+/// Write given key records to the database.
+fn write_records(db: &dyn KeyValueDB, records: &[(ServerKeyId, Vec<u8>)]) -> Result<(), Error> {
+	let mut batch = db.transaction();
+	for &(ref key, ref value) in records {
+		batch.put(0, key.as_bytes(), value);
+	}
+	db.write(batch).map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Write backup of given key records to the file in data directory.
+fn write_backup(db_path: &str, version: u8, records: &[(ServerKeyId, Vec<u8>)]) -> Result<PathBuf, Error> {
+	let backup = DatabaseBackup {
+		version: version,
+		records: records.iter()
+			.map(|&(ref key, ref value)| (key.as_bytes().to_vec().into(), value.clone().into()))
+			.collect(),
+	};
+	let backup = serde_json::to_vec(&backup).map_err(|e| Error::Database(e.to_string()))?;
+
+	let mut backup_path = PathBuf::from(db_path);
+	backup_path.push(format!("db_backup_v{}", version));
+	fs::write(&backup_path, backup)?;
+	Ok(backup_path)
+}
+
+/// Upgrade V0 record to V1.
+fn upgrade_v0_record(_: &KeyStorageEncryptionKey, _: &ServerKeyId, value: Vec<u8>) -> Result<Vec<u8>, String> {
+	let v0_key: SerializableDocumentKeyShareV0 = serde_json::from_slice(&value).map_err(|e| e.to_string())?;
+	let v1_key = SerializableDocumentKeyShareV1 {
+		threshold: v0_key.threshold,
+		id_numbers: v0_key.id_numbers,
+		secret_share: v0_key.secret_share,
+		// polynom1 has been used in obsolete generation + encryption sessions only
+		polynom1: Vec::new(),
+		common_point: Some(v0_key.common_point),
+		encrypted_point: Some(v0_key.encrypted_point),
+	};
+	serde_json::to_vec(&v1_key).map_err(|e| e.to_string())
+}
+
+/// Upgrade V1 record to V2.
+fn upgrade_v1_record(_: &KeyStorageEncryptionKey, _: &ServerKeyId, value: Vec<u8>) -> Result<Vec<u8>, String> {
+	let v1_key: SerializableDocumentKeyShareV1 = serde_json::from_slice(&value).map_err(|e| e.to_string())?;
+	let v2_key = SerializableDocumentKeyShareV2 {
+		// author and public are unknown for V1 keys, which were generated with
+		// simultaneous generation + encryption sessions only
+		author: Public::default().into(),
+		threshold: v1_key.threshold,
+		public: Public::default().into(),
+		common_point: v1_key.common_point,
+		encrypted_point: v1_key.encrypted_point,
+		versions: vec![SerializableDocumentKeyShareVersionV2 {
+			hash: DocumentKeyShareVersion::data_hash(v1_key.id_numbers.iter()
+				.map(|(k, v)| (k.as_bytes(), v.as_bytes()))).into(),
+			id_numbers: v1_key.id_numbers,
+			secret_share: v1_key.secret_share,
+		}],
+	};
+	serde_json::to_vec(&v2_key).map_err(|e| e.to_string())
+}
+
+/// Upgrade V2 record to V3.
+fn upgrade_v2_record(_: &KeyStorageEncryptionKey, _: &ServerKeyId, value: Vec<u8>) -> Result<Vec<u8>, String> {
+	let v2_key: SerializableDocumentKeyShareV2 = serde_json::from_slice(&value).map_err(|e| e.to_string())?;
+	let v3_key = SerializableDocumentKeyShareV3 {
+		author: match *v2_key.author == Public::default() {
+			true => Address::default().into(),
+			false => public_to_address(&v2_key.author).into(),
 		},
-		CURRENT_VERSION => Ok(()),
-		_ => Err(Error::FutureDBVersion),
+		threshold: v2_key.threshold,
+		public: v2_key.public,
+		common_point: v2_key.common_point,
+		encrypted_point: v2_key.encrypted_point,
+		versions: v2_key.versions.into_iter()
+			.map(|v| SerializableDocumentKeyShareVersionV3 {
+				hash: v.hash,
+				id_numbers: v.id_numbers,
+				secret_share: v.secret_share,
+				public_shares: Default::default(),
+			})
+			.collect(),
+		curve: KeyCurve::Secp256k1,
+		ecdsa_scheme: EcdsaSigningScheme::NonceInversion,
+		exported: false,
+		document_key_slots: Default::default(),
+		expiration: None,
+	};
+	serde_json::to_vec(&v3_key).map_err(|e| e.to_string())
+}
+
+/// Upgrade V3 record to V4. Records format hasn't been changed.
+fn upgrade_v3_record(_: &KeyStorageEncryptionKey, _: &ServerKeyId, value: Vec<u8>) -> Result<Vec<u8>, String> {
+	serde_json::from_slice::<SerializableDocumentKeyShareV3>(&value).map_err(|e| e.to_string())?;
+	Ok(value)
+}
+
+/// Upgrade V4 record to V5. Records that are already encrypted
+/// (i.e. when previous migration has been interrupted) are left untouched.
+fn upgrade_v4_record(encryption_key: &KeyStorageEncryptionKey, key: &ServerKeyId, value: Vec<u8>) -> Result<Vec<u8>, String> {
+	if encryption_key.decrypt(key, &value).is_ok() {
+		return Ok(value);
 	}
+
+	serde_json::from_slice::<SerializableDocumentKeyShareV3>(&value).map_err(|e| e.to_string())?;
+	encryption_key.encrypt(key, &value).map_err(|e| e.to_string())
 }
 
 /// Returns the version file path.
@@ -85,17 +385,225 @@ fn version_file_path(path: &str) -> PathBuf {
 	file_path
 }
 
+/// Writes new database version to the file at given path.
+fn update_version(path: &str, version: u8) -> Result<(), Error> {
+	fs::write(version_file_path(path), version.to_string()).map_err(Into::into)
+}
+
 /// Reads current database version from the file at given path.
-/// If the file does not exist returns `DEFAULT_VERSION`.
-fn current_version(path: &str) -> Result<u8, Error> {
+/// If the file does not exist, reads version from the legacy version record. If there's no such
+/// record, empty database is assumed to be at `CURRENT_VERSION`, V0 records are detected by
+/// their format and all other databases are assumed to be at `DEFAULT_VERSION`.
+fn current_version(path: &str, db: &dyn KeyValueDB) -> Result<u8, Error> {
 	match fs::File::open(version_file_path(path)) {
-		Err(ref err) if err.kind() == IoErrorKind::NotFound => Ok(DEFAULT_VERSION),
-		Err(err) => Err(err.into()),
+		Err(ref err) if err.kind() == IoErrorKind::NotFound => (),
+		Err(err) => return Err(err.into()),
 		Ok(mut file) => {
 			let mut s = String::new();
 			file.read_to_string(&mut s)?;
-			u8::from_str_radix(&s, 10).map_err(|_| Error::UnknownDatabaseVersion)
+			return u8::from_str_radix(&s, 10).map_err(|_| Error::UnknownDatabaseVersion);
 		},
 	}
+
+	match db.get(0, LEGACY_VERSION_KEY).map_err(|e| Error::Database(e.to_string()))? {
+		Some(ref version) if version.len() == 1 => return Ok(version[0]),
+		Some(_) => return Err(Error::UnknownDatabaseVersion),
+		None => (),
+	}
+
+	match read_records(db).into_iter().next() {
+		None => Ok(CURRENT_VERSION),
+		Some((_, ref value)) if serde_json::from_slice::<SerializableDocumentKeyShareV0>(value).is_ok() => Ok(0),
+		Some(_) => Ok(DEFAULT_VERSION),
+	}
 }
 
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::sync::Arc;
+	use tempdir::TempDir;
+	use kvdb::KeyValueDB;
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+	use crypto::publickey::{Random, Generator, Secret};
+	use key_storage::{KeyStorage, PersistentKeyStorage, DocumentKeyShare, DocumentKeyShareVersion, tombstone_key};
+	use key_storage::tests::random_encryption_key;
+	use types::ServerKeyId;
+	use super::{upgrade_db, current_version, version_file_path, MigrationOptions, Error,
+		MIGRATIONS, CURRENT_VERSION, LEGACY_VERSION_KEY};
+
+	const UPGRADE: MigrationOptions = MigrationOptions { dry_run: false, backup: true };
+	const DRY_RUN: MigrationOptions = MigrationOptions { dry_run: true, backup: true };
+
+	fn open_db(tempdir: &TempDir) -> (String, Arc<Database>) {
+		let data_path = tempdir.path().display().to_string();
+		let db_path = tempdir.path().join("db").display().to_string();
+		let db = Database::open(&DatabaseConfig::with_columns(1), &db_path).unwrap();
+		(data_path, Arc::new(db))
+	}
+
+	fn put(db: &Database, key: &[u8], value: &[u8]) {
+		let mut batch = db.transaction();
+		batch.put(0, key, value);
+		db.write(batch).unwrap();
+	}
+
+	fn v3_record(secret_share: &Secret) -> String {
+		format!(r#"{{"author":"0x0000000000000000000000000000000000000000","threshold":1,"public":"0x{:0128x}","common_point":null,"encrypted_point":null,"versions":[{{"hash":"0x{:064x}","id_numbers":{{}},"secret_share":"0x{:x}"}}]}}"#,
+			0, 0, **secret_share)
+	}
+
+	fn v3_key_share(secret_share: Secret) -> DocumentKeyShare {
+		DocumentKeyShare {
+			threshold: 1,
+			versions: vec![DocumentKeyShareVersion {
+				hash: Default::default(),
+				id_numbers: Default::default(),
+				secret_share: secret_share,
+				public_shares: Default::default(),
+			}],
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn migrations_are_ordered() {
+		assert_eq!(MIGRATIONS.len(), CURRENT_VERSION as usize);
+		for (index, migration) in MIGRATIONS.iter().enumerate() {
+			assert_eq!(migration.from as usize, index);
+		}
+	}
+
+	#[test]
+	fn empty_db_is_created_at_current_version() {
+		let tempdir = TempDir::new("").unwrap();
+		let (data_path, db) = open_db(&tempdir);
+
+		let report = upgrade_db(&data_path, &*db, &random_encryption_key(), UPGRADE).unwrap();
+		assert_eq!(report.from_version, CURRENT_VERSION);
+		assert!(report.steps.is_empty());
+		assert_eq!(fs::read_to_string(version_file_path(&data_path)).unwrap(), CURRENT_VERSION.to_string());
+	}
+
+	#[test]
+	fn v4_key_shares_are_encrypted_on_upgrade() {
+		let tempdir = TempDir::new("").unwrap();
+		let (data_path, db) = open_db(&tempdir);
+		fs::write(version_file_path(&data_path), "4").unwrap();
+
+		let key1 = ServerKeyId::from_low_u64_be(1);
+		let key2 = ServerKeyId::from_low_u64_be(2);
+		let secret_share = Random.generate().secret().clone();
+		let secret_share_hex = format!("{:x}", *secret_share);
+		put(&db, key1.as_bytes(), v3_record(&secret_share).as_bytes());
+		put(&db, &tombstone_key(&key2), &[]);
+
+		let encryption_key = random_encryption_key();
+		upgrade_db(&data_path, &*db, &encryption_key, UPGRADE).unwrap();
+		assert_eq!(current_version(&data_path, &*db).unwrap(), CURRENT_VERSION);
+
+		// key share is not stored as plain text anymore
+		let raw_value = db.get(0, key1.as_bytes()).unwrap().unwrap();
+		assert!(!String::from_utf8_lossy(&raw_value).contains(&secret_share_hex));
+
+		// but it is readable by key storage
+		let key_storage = PersistentKeyStorage::new(db.clone(), encryption_key.clone()).unwrap();
+		assert_eq!(key_storage.get(&key1), Ok(Some(v3_key_share(secret_share))));
+		assert!(key_storage.is_tombstoned(&key2));
+
+		// second upgrade is noop
+		upgrade_db(&data_path, &*db, &encryption_key, UPGRADE).unwrap();
+		assert_eq!(db.get(0, key1.as_bytes()).unwrap().unwrap(), raw_value);
+	}
+
+	#[test]
+	fn v0_key_shares_are_upgraded() {
+		let tempdir = TempDir::new("").unwrap();
+		let (data_path, db) = open_db(&tempdir);
+
+		let key = ServerKeyId::from_low_u64_be(1);
+		let node = Random.generate().public().clone();
+		let id_number = Random.generate().secret().clone();
+		let secret_share = Random.generate().secret().clone();
+		let common_point = Random.generate().public().clone();
+		let encrypted_point = Random.generate().public().clone();
+		let v0_record = format!(r#"{{"threshold":1,"id_numbers":{{"0x{:x}":"0x{:x}"}},"secret_share":"0x{:x}","common_point":"0x{:x}","encrypted_point":"0x{:x}"}}"#,
+			node, *id_number, *secret_share, common_point, encrypted_point);
+		put(&db, key.as_bytes(), v0_record.as_bytes());
+
+		let encryption_key = random_encryption_key();
+		let report = upgrade_db(&data_path, &*db, &encryption_key, UPGRADE).unwrap();
+		assert_eq!(report.from_version, 0);
+		assert_eq!(report.to_version, CURRENT_VERSION);
+		assert_eq!(report.steps.len(), MIGRATIONS.len());
+		assert_eq!(report.records, 1);
+		assert!(report.backup_path.unwrap().exists());
+
+		let id_numbers = vec![(node, id_number)].into_iter().collect();
+		let key_storage = PersistentKeyStorage::new(db.clone(), encryption_key).unwrap();
+		assert_eq!(key_storage.get(&key), Ok(Some(DocumentKeyShare {
+			threshold: 1,
+			common_point: Some(common_point),
+			encrypted_point: Some(encrypted_point),
+			versions: vec![DocumentKeyShareVersion::new(id_numbers, secret_share)],
+			..Default::default()
+		})));
+	}
+
+	#[test]
+	fn legacy_version_record_is_used_and_removed() {
+		let tempdir = TempDir::new("").unwrap();
+		let (data_path, db) = open_db(&tempdir);
+
+		let key = ServerKeyId::from_low_u64_be(1);
+		let secret_share = Random.generate().secret().clone();
+		put(&db, LEGACY_VERSION_KEY, &[3]);
+		put(&db, key.as_bytes(), v3_record(&secret_share).as_bytes());
+
+		let encryption_key = random_encryption_key();
+		let report = upgrade_db(&data_path, &*db, &encryption_key, UPGRADE).unwrap();
+		assert_eq!(report.from_version, 3);
+		assert_eq!(db.get(0, LEGACY_VERSION_KEY).unwrap(), None);
+
+		let key_storage = PersistentKeyStorage::new(db.clone(), encryption_key).unwrap();
+		assert_eq!(key_storage.get(&key), Ok(Some(v3_key_share(secret_share))));
+	}
+
+	#[test]
+	fn dry_run_leaves_db_untouched() {
+		let tempdir = TempDir::new("").unwrap();
+		let (data_path, db) = open_db(&tempdir);
+		fs::write(version_file_path(&data_path), "4").unwrap();
+
+		let key = ServerKeyId::from_low_u64_be(1);
+		let record = v3_record(Random.generate().secret());
+		put(&db, key.as_bytes(), record.as_bytes());
+
+		let report = upgrade_db(&data_path, &*db, &random_encryption_key(), DRY_RUN).unwrap();
+		assert_eq!(report.from_version, 4);
+		assert_eq!(report.records, 1);
+		assert_eq!(report.backup_path, None);
+		assert_eq!(current_version(&data_path, &*db).unwrap(), 4);
+		assert_eq!(db.get(0, key.as_bytes()).unwrap().unwrap(), record.into_bytes());
+	}
+
+	#[test]
+	fn invalid_record_leaves_db_untouched() {
+		let tempdir = TempDir::new("").unwrap();
+		let (data_path, db) = open_db(&tempdir);
+		fs::write(version_file_path(&data_path), "3").unwrap();
+
+		let key1 = ServerKeyId::from_low_u64_be(1);
+		let key2 = ServerKeyId::from_low_u64_be(2);
+		let record = v3_record(Random.generate().secret());
+		put(&db, key1.as_bytes(), record.as_bytes());
+		put(&db, key2.as_bytes(), b"garbage");
+
+		match upgrade_db(&data_path, &*db, &random_encryption_key(), UPGRADE) {
+			Err(Error::InvalidRecord(3, ref key, _)) if *key == key2 => (),
+			result => panic!("unexpected migration result: {:?}", result),
+		}
+		assert_eq!(current_version(&data_path, &*db).unwrap(), 3);
+		assert_eq!(db.get(0, key1.as_bytes()).unwrap().unwrap(), record.into_bytes());
+	}
+}
@@ -14,9 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
-use crypto::publickey::{KeyPair, Public, Signature, Error as EthKeyError, sign, public_to_address};
+use tiny_keccak::Keccak;
+use crypto::publickey::{KeyPair, Public, Secret, Signature, Error as EthKeyError, sign, public_to_address, ecies};
 use ethereum_types::{H256, Address};
-use blockchain::SigningKeyPair;
+use blockchain::{SigningKeyPair, SecretKeyPair};
+
+/// Domain separator of secrets, derived from the node secret.
+const SECRET_DERIVATION_DOMAIN: &'static [u8] = b"secretstore:derived_secret";
 
 pub struct PlainNodeKeyPair {
 	key_pair: KeyPair,
@@ -48,3 +52,27 @@ impl SigningKeyPair for PlainNodeKeyPair {
 		sign(self.key_pair.secret(), data)
 	}
 }
+
+impl SecretKeyPair for PlainNodeKeyPair {
+	fn public(&self) -> &Public {
+		self.key_pair.public()
+	}
+
+	fn derive_secret(&self, purpose: &[u8]) -> Result<Secret, EthKeyError> {
+		// keyed keccak (KMAC-like) of the purpose, keyed with the node secret
+		let mut derived = [0u8; 32];
+		let mut keccak = Keccak::new_keccak256();
+		keccak.update(SECRET_DERIVATION_DOMAIN);
+		keccak.update(self.key_pair.secret().as_bytes());
+		keccak.update(purpose);
+		keccak.finalize(&mut derived);
+
+		let derived = Secret::copy_from_slice(&derived).ok_or(EthKeyError::InvalidSecretKey)?;
+		derived.check_validity()?;
+		Ok(derived)
+	}
+
+	fn decrypt(&self, shared_mac: &[u8], data: &[u8]) -> Result<Vec<u8>, EthKeyError> {
+		ecies::decrypt(self.key_pair.secret(), shared_mac, data)
+	}
+}
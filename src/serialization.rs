@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeSet, BTreeMap};
 use std::fmt;
 use std::ops::Deref;
 use rustc_hex::{self, FromHex};
@@ -22,7 +23,7 @@ use serde::de::{Visitor, Error as SerdeError};
 use crypto::publickey::{Public, Secret, Signature};
 use ethereum_types::{H160, H256};
 use bytes::Bytes;
-use types::Requester;
+use types::{Requester, KeySharesFilter, KeySharesImportResult, RandomnessBeaconOutput, KeyImportData, ImportedKeyShare};
 
 trait ToHex {
 	fn to_hex(&self) -> String;
@@ -153,6 +154,15 @@ pub struct SerializableEncryptedDocumentKeyShadow {
 	pub decrypt_shadows: Vec<SerializableBytes>,
 }
 
+/// Serializable exported server key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableEncryptedServerKey {
+	/// Server key public.
+	pub public: SerializablePublic,
+	/// Secret shadows, encrypted with recipient public.
+	pub secret_shadows: Vec<SerializableBytes>,
+}
+
 /// Serializable requester identification data.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SerializableRequester {
@@ -164,6 +174,99 @@ pub enum SerializableRequester {
 	Address(SerializableAddress),
 }
 
+/// Serializable filter of exported key shares.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SerializableKeySharesFilter {
+	/// Export only shares of given keys.
+	pub key_ids: Option<BTreeSet<SerializableH256>>,
+	/// Export only shares of keys, generated by given author.
+	pub author: Option<SerializableAddress>,
+}
+
+/// Serializable result of key shares import.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableKeySharesImportResult {
+	/// Keys, which shares have been imported.
+	pub imported: BTreeSet<SerializableH256>,
+	/// Keys, which shares have been skipped.
+	pub skipped: BTreeSet<SerializableH256>,
+}
+
+/// Serializable output of the distributed randomness beacon.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableRandomnessBeaconOutput {
+	/// Beacon identifier.
+	pub beacon_id: SerializableH256,
+	/// Jointly generated random point.
+	pub point: SerializablePublic,
+	/// Random value: keccak256 of the random point.
+	pub value: SerializableH256,
+	/// Hash of the beacon transcript.
+	pub transcript_hash: SerializableH256,
+	/// Signatures of the transcript hash, made by every participant.
+	pub signatures: BTreeMap<SerializablePublic, SerializableSignature>,
+}
+
+/// Serializable key, prepared by the dealer for import.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableKeyImportData {
+	/// Commitments to coefficients of the dealer polynomial.
+	pub share_commitments: Vec<SerializablePublic>,
+	/// Shares of all key servers.
+	pub shares: BTreeMap<SerializablePublic, SerializableImportedKeyShare>,
+}
+
+/// Serializable share of the imported key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableImportedKeyShare {
+	/// Id number of the key server.
+	pub id_number: SerializableSecret,
+	/// Key share, encrypted with key server public key.
+	pub encrypted_share: SerializableBytes,
+}
+
+impl From<SerializableKeySharesFilter> for KeySharesFilter {
+	fn from(filter: SerializableKeySharesFilter) -> KeySharesFilter {
+		KeySharesFilter {
+			key_ids: filter.key_ids.map(|key_ids| key_ids.into_iter().map(Into::into).collect()),
+			author: filter.author.map(Into::into),
+		}
+	}
+}
+
+impl From<KeySharesImportResult> for SerializableKeySharesImportResult {
+	fn from(result: KeySharesImportResult) -> SerializableKeySharesImportResult {
+		SerializableKeySharesImportResult {
+			imported: result.imported.into_iter().map(Into::into).collect(),
+			skipped: result.skipped.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+impl From<SerializableKeyImportData> for KeyImportData {
+	fn from(key: SerializableKeyImportData) -> KeyImportData {
+		KeyImportData {
+			share_commitments: key.share_commitments.into_iter().map(Into::into).collect(),
+			shares: key.shares.into_iter().map(|(node, share)| (node.into(), ImportedKeyShare {
+				id_number: share.id_number.into(),
+				encrypted_share: share.encrypted_share.into(),
+			})).collect(),
+		}
+	}
+}
+
+impl From<RandomnessBeaconOutput> for SerializableRandomnessBeaconOutput {
+	fn from(output: RandomnessBeaconOutput) -> SerializableRandomnessBeaconOutput {
+		SerializableRandomnessBeaconOutput {
+			beacon_id: output.beacon_id.into(),
+			point: output.point.into(),
+			value: output.value.into(),
+			transcript_hash: output.transcript_hash.into(),
+			signatures: output.signatures.into_iter().map(|(node, signature)| (node.into(), signature.into())).collect(),
+		}
+	}
+}
+
 impl From<SerializableRequester> for Requester {
 	fn from(requester: SerializableRequester) -> Requester {
 		match requester {
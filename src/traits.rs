@@ -15,9 +15,11 @@
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeSet;
+use ethereum_types::H256;
 use futures::Future;
-use types::{Error, Public, ServerKeyId, MessageHash, EncryptedMessageSignature, RequestSignature, Requester,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId};
+use types::{Error, Public, EddsaPublic, BlsPublic, ServerKeyId, MessageHash, EncryptedMessageSignature, RequestSignature, Requester,
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, EncryptedEciesSecret, EncryptedEcdhSharedPoint, NodeId, KeySharesFilter,
+	KeySharesImportResult, KeyDerivationPath, RandomnessBeaconOutput, KeyImportData, EncryptedServerKey, DocumentKeySlotId, KeyExpiration};
 
 /// Server key (SK) generator.
 pub trait ServerKeyGenerator {
@@ -32,6 +34,53 @@ pub trait ServerKeyGenerator {
 		author: Requester,
 		threshold: usize,
 	) -> Box<dyn Future<Item=Public, Error=Error> + Send>;
+	/// Generate new SK, which expires after given point. Expired SK couldn't be used for decryption and signing
+	/// and its shares are eventually deleted by every key server.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `author` is the author of key entry.
+	/// `threshold + 1` is the minimal number of nodes, required to restore private key.
+	/// `expiration` is the not-after timestamp or block number of SK.
+	/// Result is a public portion of SK.
+	fn generate_expiring_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+		expiration: KeyExpiration,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send>;
+	/// Generate new SK over Ed25519 curve. Such SK could only be used to compute EdDSA signatures.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `author` is the author of key entry.
+	/// `threshold + 1` is the minimal number of nodes, required to restore private key.
+	/// Result is a compressed public portion of SK.
+	fn generate_eddsa_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+	) -> Box<dyn Future<Item=EddsaPublic, Error=Error> + Send>;
+	/// Generate new SK over BLS12-381 curve. Such SK could only be used to compute BLS signatures.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `author` is the author of key entry.
+	/// `threshold + 1` is the minimal number of nodes, required to restore private key.
+	/// Result is a compressed (G1) public portion of SK.
+	fn generate_bls_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		threshold: usize,
+	) -> Box<dyn Future<Item=BlsPublic, Error=Error> + Send>;
+	/// Import existing SK, which has been split into key servers shares by its owner (dealer).
+	/// `key_id` is the caller-provided identifier of imported SK.
+	/// `author` is the author of key entry.
+	/// `key` is the dealer polynomial commitments and shares, encrypted with public keys of key servers.
+	/// Result is a public portion of SK.
+	fn import_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		key: KeyImportData,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send>;
 	/// Retrieve public portion of previously generated SK.
 	/// `key_id` is identifier of previously generated SK.
 	/// `author` is the same author, that has created the server key.
@@ -40,6 +89,15 @@ pub trait ServerKeyGenerator {
 		key_id: ServerKeyId,
 		author: Requester,
 	) -> Box<dyn Future<Item=Public, Error=Error> + Send>;
+	/// Delete previously generated SK on all nodes, holding its shares. Fails if any of these nodes is offline.
+	/// `key_id` is identifier of previously generated SK.
+	/// `author` is the same author, that has created the server key.
+	/// Deleted SK can not be generated again.
+	fn delete_key(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send>;
 }
 
 /// Document key (DK) server.
@@ -91,6 +149,19 @@ pub trait DocumentKeyServer: ServerKeyGenerator {
 		key_id: ServerKeyId,
 		requester: Requester,
 	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send>;
+	/// Compute ECDH secret of the arbitrary ECIES ciphertext, encrypted with public portion of SK (`crypto::publickey::ecies`).
+	/// Secret is computed on the key server (which might be considered unsafe), and then encrypted with caller public key.
+	/// `key_id` is identifier of previously generated SK.
+	/// `requester` is the one who requests access to ciphertext. Caller must be on ACL for this function to succeed.
+	/// `ephemeral_public` is the ephemeral public key of ECIES ciphertext (bytes 1..65 of the ciphertext).
+	/// Result is an ECDH secret (the one that `crypto::publickey::ecdh::agree` computes), encrypted with caller public key.
+	/// Caller is able to derive ECIES encryption and MAC keys from this secret and decrypt the ciphertext.
+	fn restore_ecies_secret(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		ephemeral_public: Public,
+	) -> Box<dyn Future<Item=EncryptedEciesSecret, Error=Error> + Send>;
 }
 
 /// Message signer.
@@ -106,8 +177,33 @@ pub trait MessageSigner: ServerKeyGenerator {
 		requester: Requester,
 		message: MessageHash,
 	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send>;
+	/// Generate Schnorr signatures for several messages with previously generated SK.
+	/// All messages are signed within single signing session, so access is checked once.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `requester` is the one who requests access to server key private.
+	/// `messages` are the messages to be signed.
+	/// Result is a vector of signed messages (in the same order), encrypted with caller public key.
+	fn sign_messages_schnorr(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		messages: Vec<MessageHash>,
+	) -> Box<dyn Future<Item=Vec<EncryptedMessageSignature>, Error=Error> + Send>;
+	/// Generate BIP-340 Schnorr signature for message with previously generated SK.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `requester` is the one who requests access to server key private.
+	/// `message` is the message to be signed.
+	/// Result is a signed message in standard 64-bytes form (`x(R) || s`), encrypted with caller public key.
+	/// Signature is verifiable using X coordinate of the SK public.
+	fn sign_message_schnorr_bip340(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send>;
 	/// Generate ECDSA signature for message with previously generated SK.
-	/// WARNING: only possible when SK was generated using t <= 2 * N.
+	/// Keys, generated by current version, could be used with any t < N. Keys, generated before MtA-based
+	/// signing was supported, are using legacy scheme, which is only possible when 2 * t < N.
 	/// `key_id` is the caller-provided identifier of generated SK.
 	/// `signature` is `key_id`, signed with caller public key.
 	/// `message` is the message to be signed.
@@ -118,6 +214,152 @@ pub trait MessageSigner: ServerKeyGenerator {
 		signature: Requester,
 		message: MessageHash,
 	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send>;
+	/// Generate EdDSA (Ed25519) signature for message with previously generated Ed25519 SK.
+	/// `key_id` is the caller-provided identifier of SK, generated with `generate_eddsa_key`.
+	/// `requester` is the one who requests access to server key private.
+	/// `message` is the message to be signed.
+	/// Result is a signed message (`R || s`), encrypted with caller public key.
+	fn sign_message_eddsa(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send>;
+	/// Generate BLS signature for message with previously generated BLS12-381 SK.
+	/// `key_id` is the caller-provided identifier of SK, generated with `generate_bls_key`.
+	/// `requester` is the one who requests access to server key private.
+	/// `message` is the message to be signed.
+	/// Result is a signed message (compressed G2 point), encrypted with caller public key.
+	fn sign_message_bls(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send>;
+}
+
+/// Child keys server. Child keys are non-hardened keys, derived from SK (BIP32-like). Every key server
+/// derives share of the child key from its own SK share, so child keys require neither key generation,
+/// nor additional storage.
+pub trait ChildKeyServer: ServerKeyGenerator {
+	/// Retrieve public portion of the child key of previously generated SK.
+	/// `key_id` is identifier of previously generated SK.
+	/// `author` is the same author, that has created the server key.
+	/// `derivation_path` is the path of the child key. Hardened indices (>= 2^31) are not supported.
+	fn restore_child_key_public(
+		&self,
+		key_id: ServerKeyId,
+		author: Requester,
+		derivation_path: KeyDerivationPath,
+	) -> Box<dyn Future<Item=Public, Error=Error> + Send>;
+	/// Generate Schnorr signature for message with the child key of previously generated SK.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `derivation_path` is the path of the child key.
+	/// `requester` is the one who requests access to server key private.
+	/// `message` is the message to be signed.
+	/// Result is a signed message, encrypted with caller public key.
+	fn sign_message_schnorr_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send>;
+	/// Generate ECDSA signature for message with the child key of previously generated SK.
+	/// `key_id` is the caller-provided identifier of generated SK.
+	/// `derivation_path` is the path of the child key.
+	/// `requester` is the one who requests access to server key private.
+	/// `message` is the message to be signed.
+	/// Result is a signed message, encrypted with caller public key.
+	fn sign_message_ecdsa_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+		message: MessageHash,
+	) -> Box<dyn Future<Item=EncryptedMessageSignature, Error=Error> + Send>;
+	/// Restore previously stored DK, using shares of the child key of SK.
+	/// `key_id` is identifier of previously generated SK.
+	/// `derivation_path` is the path of the child key.
+	/// `requester` is the one who requests access to document key. Caller must be on ACL for this function to succeed.
+	/// Result is a DK, encrypted with caller public key.
+	fn restore_document_key_with_child_key(
+		&self,
+		key_id: ServerKeyId,
+		derivation_path: KeyDerivationPath,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send>;
+}
+
+/// Document key slots server. Every SK could protect several additional DKs, each stored in its own slot
+/// (addressed by the caller-provided slot id) alongside the default DK.
+pub trait DocumentKeySlotsServer: ServerKeyGenerator {
+	/// Store externally generated DK in the given slot.
+	/// `key_id` is identifier of previously generated SK.
+	/// `slot` is the caller-provided identifier of DK slot. Slot must be empty.
+	/// `author` is the same author, that has created the server key.
+	/// `common_point` is a result of `k * T` expression, where `T` is generation point and `k` is random scalar in EC field.
+	/// `encrypted_document_key` is a result of `M + k * y` expression, where `M` is unencrypted document key (point on EC),
+	///   `k` is the same scalar used in `common_point` calculation and `y` is previously generated public part of SK.
+	fn store_document_key_in_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		author: Requester,
+		common_point: Public,
+		encrypted_document_key: Public,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send>;
+	/// Restore DK, previously stored in the given slot.
+	/// `key_id` is identifier of previously generated SK.
+	/// `slot` is the identifier of DK slot.
+	/// `requester` is the one who requests access to document key. Caller must be on ACL for this function to succeed.
+	/// Result is a DK, encrypted with caller public key.
+	fn restore_document_key_from_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKey, Error=Error> + Send>;
+	/// Restore DK shadow, previously stored in the given slot. See `DocumentKeyServer::restore_document_key_shadow`.
+	/// `key_id` is identifier of previously generated SK.
+	/// `slot` is the identifier of DK slot.
+	/// `requester` is the one who requests access to document key. Caller must be on ACL for this function to succeed.
+	fn restore_document_key_shadow_from_slot(
+		&self,
+		key_id: ServerKeyId,
+		slot: DocumentKeySlotId,
+		requester: Requester,
+	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send>;
+}
+
+/// ECDH key agreement with server key.
+pub trait KeyAgreementServer: ServerKeyGenerator {
+	/// Compute ECDH shared point `x * P`, where `x` is the SK secret and `P` is the counterparty public key.
+	/// Every consensus node computes its partial point and the master node combines them, so that SK secret
+	/// is never reconstructed. Shared point is then encrypted with caller public key.
+	/// `key_id` is identifier of previously generated SK.
+	/// `requester` is the one who requests key agreement. Caller must be on ACL for this function to succeed.
+	/// `counterparty_public` is the public key of the counterparty (`P`).
+	/// Result is the shared point (64 bytes of X and Y coordinates), encrypted with caller public key.
+	fn agree_shared_point(
+		&self,
+		key_id: ServerKeyId,
+		requester: Requester,
+		counterparty_public: Public,
+	) -> Box<dyn Future<Item=EncryptedEcdhSharedPoint, Error=Error> + Send>;
+}
+
+/// Distributed randomness beacon.
+pub trait RandomnessBeacon {
+	/// Jointly generate random value with all connected key servers, so that neither of them is able to bias it.
+	/// `beacon_id` is the caller-provided identifier of the beacon round, which is bound to the signed transcript.
+	/// `requester` is the `beacon_id`, signed by the requester, who must have access to the `beacon_id` (checked by ACL storage).
+	/// Result is the random value, along with the transcript, signed by every participating key server.
+	fn generate_randomness(
+		&self,
+		beacon_id: H256,
+		requester: Requester,
+	) -> Box<dyn Future<Item=RandomnessBeaconOutput, Error=Error> + Send>;
 }
 
 /// Administrative sessions server.
@@ -132,8 +374,66 @@ pub trait AdminSessionsServer {
 		new_set_signature: RequestSignature,
 		new_servers_set: BTreeSet<NodeId>,
 	) -> Box<dyn Future<Item=(), Error=Error> + Send>;
+	/// Re-randomize shares of given SK on all its holders, without changing SK itself.
+	/// Shares of the previous key version could not be combined with refreshed shares.
+	/// `signature` is `key_id`, signed with administrator secret key.
+	fn refresh_key_shares(
+		&self,
+		key_id: ServerKeyId,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send>;
+	/// Re-randomize shares of all SKs, which have shares on this key server.
+	/// `signature` is 3ee49bd1e8ad12ab5f7ef5a2bba9c8d91d4e59e5c1ba0a7ab1b29d3fa6cd0a7e, signed with administrator secret key.
+	fn refresh_all_key_shares(
+		&self,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send>;
+	/// Replace SK with the new one on all its holders, re-encrypting associated document key (if any),
+	/// so that it could only be decrypted with the new SK. Shares of the previous SK are retired.
+	/// `signature` is `key_id`, signed with administrator secret key.
+	fn rotate_server_key(
+		&self,
+		key_id: ServerKeyId,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send>;
+	/// Re-share SK under the new threshold on all its holders, without changing SK itself.
+	/// Shares of the previous key version are replaced with shares of the new version.
+	/// `signature` is keccak(key_id || new_threshold as big-endian u64), signed with administrator secret key.
+	fn change_server_key_threshold(
+		&self,
+		key_id: ServerKeyId,
+		new_threshold: usize,
+		signature: RequestSignature,
+	) -> Box<dyn Future<Item=(), Error=Error> + Send>;
+	/// Export SK to `recipient`: every selected SK holder encrypts its secret shadow with `recipient` public, so that
+	/// the SK secret is only reconstructed by the recipient. SK is marked as exported on all its holders.
+	/// `approvals` are keccak(key_id || recipient || expiry), signed by (at least threshold of) configured key export
+	/// approvers. Approvals are rejected after `expiry` (unix timestamp, in seconds).
+	fn export_server_key(
+		&self,
+		key_id: ServerKeyId,
+		recipient: Public,
+		expiry: u64,
+		approvals: Vec<RequestSignature>,
+	) -> Box<dyn Future<Item=EncryptedServerKey, Error=Error> + Send>;
+	/// Export key shares, stored on this key server, into encrypted archive, which could only be
+	/// imported back by this key server.
+	/// `signature` is a6e2b5ad73c3a5a1d8f8e1c3d3b8f5a0f6a9a2e4a9c0d3b6c53e8f1e7ab21d04, signed with administrator secret key.
+	fn export_key_shares(
+		&self,
+		signature: RequestSignature,
+		filter: KeySharesFilter,
+	) -> Box<dyn Future<Item=Vec<u8>, Error=Error> + Send>;
+	/// Import key shares from the archive, previously exported by this key server.
+	/// `signature` is keccak(archive), signed with administrator secret key.
+	fn import_key_shares(
+		&self,
+		signature: RequestSignature,
+		archive: Vec<u8>,
+	) -> Box<dyn Future<Item=KeySharesImportResult, Error=Error> + Send>;
 }
 
 /// Key server.
-pub trait KeyServer: AdminSessionsServer + DocumentKeyServer + MessageSigner + Send + Sync {
+pub trait KeyServer: AdminSessionsServer + DocumentKeyServer + MessageSigner + ChildKeyServer + DocumentKeySlotsServer
+	+ KeyAgreementServer + RandomnessBeacon + Send + Sync {
 }
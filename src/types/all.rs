@@ -14,9 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Secret Store.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-use blockchain::ContractAddress;
+use blockchain::{ContractAddress, BlockNumber};
 use {bytes, ethereum_types};
 
 /// Node id.
@@ -25,6 +25,10 @@ pub type NodeId = crypto::publickey::Public;
 pub type ServerKeyId = ethereum_types::H256;
 /// Encrypted document key type.
 pub type EncryptedDocumentKey = bytes::Bytes;
+/// Encrypted ECDH secret of ECIES ciphertext.
+pub type EncryptedEciesSecret = bytes::Bytes;
+/// Encrypted ECDH shared point of server key and counterparty public.
+pub type EncryptedEcdhSharedPoint = bytes::Bytes;
 /// Message hash.
 pub type MessageHash = ethereum_types::H256;
 /// Message signature.
@@ -33,6 +37,14 @@ pub type EncryptedMessageSignature = bytes::Bytes;
 pub type RequestSignature = crypto::publickey::Signature;
 /// Public key type.
 pub use crypto::publickey::Public;
+/// Compressed Ed25519 public key type.
+pub type EddsaPublic = ethereum_types::H256;
+/// Compressed BLS12-381 (G1) public key type.
+pub type BlsPublic = bytes::Bytes;
+/// Path of the non-hardened child key, derived from the server key. Empty path means server key itself.
+pub type KeyDerivationPath = Vec<u32>;
+/// Id of the additional document key slot of the server key.
+pub type DocumentKeySlotId = ethereum_types::H256;
 
 /// Secret store configuration
 #[derive(Debug, Clone)]
@@ -64,6 +76,19 @@ pub struct ServiceConfiguration {
 	pub cluster_config: ClusterConfiguration,
 	// Allowed CORS domains
 	pub cors: Option<Vec<String>>,
+	/// Key storage configuration.
+	pub key_storage: KeyStorageConfiguration,
+}
+
+/// Key storage configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyStorageConfiguration {
+	/// Key shares are stored in RocksDB database, located beneath given secret store data path.
+	RocksDb(String),
+	/// Key shares are stored in embedded sled database, located beneath given secret store data path.
+	Sled(String),
+	/// Key shares are stored in memory and are lost when key server is stopped.
+	InMemory,
 }
 
 /// Key server cluster configuration
@@ -80,9 +105,38 @@ pub struct ClusterConfiguration {
 	pub allow_connecting_to_higher_nodes: bool,
 	/// Administrator public key.
 	pub admin_public: Option<Public>,
+	/// Administrators, who are able to approve export of the full server key. If None, keys couldn't be exported.
+	pub key_export_approvers: Option<KeyExportApprovers>,
 	/// Should key servers set change session should be started when servers set changes.
 	/// This will only work when servers set is configured using KeyServerSet contract.
 	pub auto_migrate_enabled: bool,
+	/// Use echo-based reliable broadcast for key generation and servers set change session messages.
+	/// This requires all configured key servers to be connected to each other.
+	pub reliable_broadcast: bool,
+	/// Generate server keys that are signed using MtA-based ECDSA scheme (any threshold is supported).
+	/// Signing is slower, because every signer generates and proves new Paillier key for every session.
+	/// When disabled, NonceInversion scheme is used.
+	pub ecdsa_mta_enabled: bool,
+}
+
+/// Administrators, who are able to approve export of the full server key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyExportApprovers {
+	/// Public keys of all approvers.
+	pub approvers: BTreeSet<Public>,
+	/// Number of distinct approvers, which must sign the key export request.
+	pub threshold: usize,
+}
+
+/// Exported server key. The key secret is never reconstructed by key servers. Instead, every selected key server
+/// sends its secret shadow, encrypted with public of the key export recipient. To reconstruct the secret, recipient
+/// must decrypt all shadows && compute their sum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncryptedServerKey {
+	/// Server key public.
+	pub public: crypto::publickey::Public,
+	/// Secret shadows (key shares, multiplied by Lagrange coefficients), encrypted with recipient public.
+	pub secret_shadows: Vec<Vec<u8>>,
 }
 
 /// Shadow decryption result.
@@ -96,6 +150,79 @@ pub struct EncryptedDocumentKeyShadow {
 	pub decrypt_shadows: Option<Vec<Vec<u8>>>,
 }
 
+/// Point in time after which the server key expires. Expired keys couldn't be used for decryption
+/// and signing and their shares are eventually deleted by every key server.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum KeyExpiration {
+	/// Key expires after given unix timestamp (in seconds).
+	Timestamp(u64),
+	/// Key expires after given block is enacted.
+	Block(BlockNumber),
+}
+
+impl KeyExpiration {
+	/// Check if key has expired at given unix timestamp && best block number (if known).
+	pub fn is_expired(&self, timestamp: u64, block_number: Option<BlockNumber>) -> bool {
+		match *self {
+			KeyExpiration::Timestamp(not_after) => timestamp > not_after,
+			KeyExpiration::Block(not_after) => block_number.map(|block_number| block_number > not_after).unwrap_or(false),
+		}
+	}
+}
+
+/// Filter of key shares to export.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeySharesFilter {
+	/// Export only shares of given keys.
+	pub key_ids: Option<BTreeSet<ServerKeyId>>,
+	/// Export only shares of keys, generated by given author.
+	pub author: Option<crypto::publickey::Address>,
+}
+
+/// Result of key shares import.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeySharesImportResult {
+	/// Keys, which shares have been imported.
+	pub imported: BTreeSet<ServerKeyId>,
+	/// Keys, which shares have been skipped, because they're already stored or have been deleted.
+	pub skipped: BTreeSet<ServerKeyId>,
+}
+
+/// Output of the distributed randomness beacon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomnessBeaconOutput {
+	/// Caller-provided beacon identifier (i.e. lottery round), which is bound to the transcript.
+	pub beacon_id: ethereum_types::H256,
+	/// Jointly generated random point, which neither participant is able to bias.
+	pub point: Public,
+	/// Random value: keccak256 of the random point.
+	pub value: ethereum_types::H256,
+	/// Hash of the beacon transcript: keccak256("secretstore:beacon" || beacon_id || point || sorted participants ids).
+	pub transcript_hash: ethereum_types::H256,
+	/// Signatures of the transcript hash, made by every participant with its node key.
+	pub signatures: BTreeMap<NodeId, crypto::publickey::Signature>,
+}
+
+/// Existing private key, split by the dealer (key owner) into shares of key servers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyImportData {
+	/// Commitments to coefficients of the dealer polynomial (coeff * G). The first commitment is the
+	/// public portion of the imported key. Number of commitments is `threshold + 1`.
+	pub share_commitments: Vec<Public>,
+	/// Shares of all key servers.
+	pub shares: BTreeMap<NodeId, ImportedKeyShare>,
+}
+
+/// Share of the imported key, prepared by the dealer for the single key server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedKeyShare {
+	/// Id number of the key server: non-zero point, at which the dealer polynomial has been evaluated.
+	pub id_number: crypto::publickey::Secret,
+	/// Value of the dealer polynomial at `id_number`, encrypted (ECIES) with key server public key.
+	/// Key id is used as the MAC data, so that shares couldn't be imported under another key id.
+	pub encrypted_share: bytes::Bytes,
+}
+
 /// Requester identification data.
 #[derive(Debug, Clone)]
 pub enum Requester {
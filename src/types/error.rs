@@ -19,6 +19,7 @@ use std::net;
 use std::io::Error as IoError;
 
 use crypto;
+use types::NodeId;
 
 /// Secret store error.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -47,6 +48,9 @@ pub enum Error {
 	InvalidMessage,
 	/// Message version is not supported.
 	InvalidMessageVersion,
+	/// Partial result, computed by given node, has failed verification.
+	/// This means that node is misbehaving/cheating and must be excluded from the session.
+	InvalidPartialResponse(NodeId),
 	/// Message is invalid because of replay-attack protection.
 	ReplayProtection,
 	/// Connection to node, required for this session is not established.
@@ -55,6 +59,17 @@ pub enum Error {
 	ServerKeyAlreadyGenerated,
 	/// Server key with this ID is not yet generated.
 	ServerKeyIsNotFound,
+	/// Server key with this ID has been deleted.
+	ServerKeyIsDeleted,
+	/// Server key with this ID has been generated for other elliptic curve.
+	InvalidKeyCurve,
+	/// Server key with this ID has expired and couldn't be used anymore.
+	ServerKeyIsExpired,
+	/// Public shares of server key version are unknown, so partial results of version holders can't be verified.
+	/// Version must be refreshed (using share refresh session) before it could be used.
+	ServerKeyPublicSharesAreUnknown,
+	/// Key derivation path is invalid (i.e. it contains hardened index, which can't be derived from server key).
+	InvalidDerivationPath,
 	/// Document key with this ID is already stored.
 	DocumentKeyAlreadyStored,
 	/// Document key with this ID is not yet stored.
@@ -103,7 +118,7 @@ impl Error {
 			// unexpected message errors => restarting session/excluding node is a solution
 			Error::TooEarlyForRequest | Error::InvalidStateForRequest | Error::InvalidNodeForRequest |
 			// invalid message errors => restarting/updating/excluding node is a solution
-			Error::InvalidMessage | Error::InvalidMessageVersion | Error::ReplayProtection |
+			Error::InvalidMessage | Error::InvalidMessageVersion | Error::InvalidPartialResponse(_) | Error::ReplayProtection |
 			// connectivity problems => waiting for reconnect && restarting session is a solution
 			Error::NodeDisconnected |
 			// temporary (?) consensus problems, related to other non-fatal errors => restarting is probably (!) a solution
@@ -117,7 +132,7 @@ impl Error {
 			Error::InvalidNodeAddress | Error::InvalidNodeId |
 			// wrong session input params errors
 			Error::NotEnoughNodesForThreshold | Error::ServerKeyAlreadyGenerated | Error::ServerKeyIsNotFound |
-				Error::DocumentKeyAlreadyStored | Error::DocumentKeyIsNotFound | Error::InsufficientRequesterData(_) |
+				Error::ServerKeyIsDeleted | Error::ServerKeyIsExpired | Error::ServerKeyPublicSharesAreUnknown | Error::InvalidKeyCurve | Error::InvalidDerivationPath | Error::DocumentKeyAlreadyStored | Error::DocumentKeyIsNotFound | Error::InsufficientRequesterData(_) |
 			// access denied/consensus error
 			Error::AccessDenied | Error::ConsensusUnreachable |
 			// indeterminate internal errors, which could be either fatal (db failure, invalid request), or not (network error),
@@ -140,10 +155,16 @@ impl fmt::Display for Error {
 			Error::InvalidNodeForRequest => write!(f, "invalid node for this request"),
 			Error::InvalidMessage => write!(f, "invalid message is received"),
 			Error::InvalidMessageVersion => write!(f, "unsupported message is received"),
+			Error::InvalidPartialResponse(ref node) => write!(f, "invalid partial response is received from node {}", node),
 			Error::ReplayProtection => write!(f, "replay message is received"),
 			Error::NodeDisconnected => write!(f, "node required for this operation is currently disconnected"),
 			Error::ServerKeyAlreadyGenerated => write!(f, "Server key with this ID is already generated"),
 			Error::ServerKeyIsNotFound => write!(f, "Server key with this ID is not found"),
+			Error::ServerKeyIsDeleted => write!(f, "Server key with this ID has been deleted"),
+			Error::ServerKeyIsExpired => write!(f, "Server key with this ID has expired"),
+			Error::ServerKeyPublicSharesAreUnknown => write!(f, "Public shares of server key with this ID are unknown"),
+			Error::InvalidKeyCurve => write!(f, "Server key with this ID has been generated for other elliptic curve"),
+			Error::InvalidDerivationPath => write!(f, "Key derivation path is invalid"),
 			Error::DocumentKeyAlreadyStored => write!(f, "Document key with this ID is already stored"),
 			Error::DocumentKeyIsNotFound => write!(f, "Document key with this ID is not found"),
 			Error::ConsensusUnreachable => write!(f, "Consensus unreachable"),